@@ -505,8 +505,9 @@ impl RendererToWinit for SurfaceRenderer<Instant> {
     }
 
     fn redraw(&mut self, session: &Session, _window: &Window) {
+        // The cursor is provided by the cameras from `Session::create_cameras()`.
         let _info = self
-            .render_frame(session.cursor_result(), |render_info| {
+            .render_frame(None, |render_info| {
                 format!("{}", session.info_text(render_info))
             })
             .unwrap();
@@ -530,7 +531,8 @@ impl RendererToWinit for RtToSoftbuffer {
     }
 
     fn redraw(&mut self, session: &Session, window: &Window) {
-        self.renderer.update(session.cursor_result()).unwrap(/* TODO: fix */);
+        // The cursor is provided by the cameras from `Session::create_cameras()`.
+        self.renderer.update(None).unwrap(/* TODO: fix */);
 
         let sb_image_size = window.inner_size();
         if self.resize.get_and_clear() {
@@ -1,5 +1,6 @@
 //! Command line option parsing.
 
+use std::ffi::OsStr;
 use std::fmt::Write as _;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
@@ -101,7 +102,10 @@ pub(crate) struct AicDesktopArgs {
     /// * “.png” or “.apng” — export rendered scene.
     /// * “.gltf” — export scene as meshes in glTF format
     ///   (has accompanying “.glbin” data files).
+    /// * “.stl” — export world to STL format.
     /// * “.vox” — export world to MagicaVoxel .vox format.
+    /// * “.aiclight” — export light data of the world.
+    /// * “.ply” — export world as a PLY point cloud.
     #[arg(
         long = "output",
         short = 'o',
@@ -119,6 +123,15 @@ pub(crate) struct AicDesktopArgs {
     #[arg(long, requires = "output_file")]
     pub(crate) save_all: bool,
 
+    /// In 'record' mode, make the sky transparent instead of drawing it.
+    #[arg(long = "transparent", requires = "output_file")]
+    pub(crate) transparent_background: bool,
+
+    /// In 'record' mode with glTF output, bake the light of each frame into the meshes,
+    /// so that the recording shows changing light. This may make the output much larger.
+    #[arg(long = "bake-light", requires = "output_file")]
+    pub(crate) bake_light: bool,
+
     // TODO: Generalize this to "exit after this much time has passed".
     /// Length of time to simulate.
     ///
@@ -190,6 +203,9 @@ impl AicDesktopArgs {
                 }
                 None => None,
             },
+            background: None,
+            transparent_background: self.transparent_background,
+            bake_light: self.bake_light,
         };
 
         Ok(Some(options))
@@ -315,25 +331,22 @@ impl FromStr for SpaceSizeArg {
 }
 
 pub fn determine_record_format(output_path: &Path) -> Result<RecordFormat, &'static str> {
-    if let Some(extension) = output_path.extension() {
-        match extension.to_str() {
-            // When updating this match, also update the docs for output_file!
-            // TODO: RecordFormat and ExportFormat should be merged?
-            Some("alliscubesjson" | "ALLISCUBESJSON") => {
-                return Ok(RecordFormat::Export(ExportFormat::AicJson))
-            }
-            Some("png" | "PNG") => return Ok(RecordFormat::PngOrApng),
-            Some("apng" | "APNG") => return Ok(RecordFormat::PngOrApng),
-            Some("gltf" | "GLTF") => return Ok(RecordFormat::Gltf),
-            Some("stl" | "STL") => return Ok(RecordFormat::Export(ExportFormat::Stl)),
-            Some("vox" | "VOX") => return Ok(RecordFormat::Export(ExportFormat::DotVox)),
-            _ => {}
+    // When updating this, also update the docs for output_file!
+    // TODO: RecordFormat and ExportFormat should be merged?
+    if let Some(extension) = output_path.extension().and_then(OsStr::to_str) {
+        if extension.eq_ignore_ascii_case("png") || extension.eq_ignore_ascii_case("apng") {
+            return Ok(RecordFormat::PngOrApng);
+        }
+        match ExportFormat::from_extension(extension) {
+            Some(ExportFormat::Gltf) => return Ok(RecordFormat::Gltf),
+            Some(format) => return Ok(RecordFormat::Export(format)),
+            None => {}
         }
     }
     // TODO: Have a separate option for choosing file type as a fallback
     Err(
         "file name must have an extension specifying the type; one of \
-        'alliscubesjson', 'png', 'apng', 'gltf', 'stl', or 'vox'",
+        'alliscubesjson', 'png', 'apng', 'gltf', 'stl', 'vox', 'aiclight', or 'ply'",
     )
 }
 
@@ -380,6 +393,25 @@ mod tests {
             .find_map(|(k, v)| if k == wanted_kind { Some(v) } else { None })
     }
 
+    #[test]
+    fn record_format_from_extension() {
+        assert_eq!(
+            determine_record_format(Path::new("foo.APNG")),
+            Ok(RecordFormat::PngOrApng)
+        );
+        assert_eq!(
+            determine_record_format(Path::new("foo.gltf")),
+            Ok(RecordFormat::Gltf)
+        );
+        assert_eq!(
+            determine_record_format(Path::new("foo.Stl")),
+            Ok(RecordFormat::Export(ExportFormat::Stl))
+        );
+        // Binary glTF is not supported.
+        assert!(determine_record_format(Path::new("foo.glb")).is_err());
+        assert!(determine_record_format(Path::new("foo")).is_err());
+    }
+
     #[test]
     fn record_options_image() {
         assert_eq!(
@@ -394,6 +426,9 @@ mod tests {
                 save_all: false,
                 image_size: Vector2::new(640, 480),
                 animation: None,
+                background: None,
+                transparent_background: false,
+                bake_light: false,
             },
         );
     }
@@ -414,6 +449,30 @@ mod tests {
                     frame_count: 180,
                     frame_period: Duration::from_nanos((1e9 / 60.0) as u64),
                 }),
+                background: None,
+                transparent_background: false,
+                bake_light: false,
+            },
+        );
+    }
+
+    #[test]
+    fn record_options_transparent_background() {
+        assert_eq!(
+            parse(&["-g", "record", "-o", "output.png", "--transparent"])
+                .unwrap()
+                .record_options()
+                .unwrap()
+                .unwrap(),
+            RecordOptions {
+                output_path: PathBuf::from("output.png"),
+                output_format: RecordFormat::PngOrApng,
+                save_all: false,
+                image_size: Vector2::new(640, 480),
+                animation: None,
+                background: None,
+                transparent_background: true,
+                bake_light: false,
             },
         );
     }
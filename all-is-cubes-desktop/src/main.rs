@@ -96,7 +96,9 @@ fn main() -> Result<(), anyhow::Error> {
         precompute_light,
         input_file,
         output_file,
-        save_all: _, // used in RecordOptions
+        save_all: _,               // used in RecordOptions
+        transparent_background: _, // used in RecordOptions
+        bake_light,                // also used in RecordOptions
         duration,
         verbose,
         simplify_log_format,
@@ -179,7 +181,8 @@ fn main() -> Result<(), anyhow::Error> {
         graphics_type,
         input_source,
         output_file,
-        precompute_light,
+        // Baked light should not start out incomplete.
+        precompute_light: precompute_light || bake_light,
         headless: options.is_headless(),
     };
 
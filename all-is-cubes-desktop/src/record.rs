@@ -8,7 +8,7 @@ use anyhow::Context;
 
 use all_is_cubes::camera::{Flaws, StandardCameras};
 use all_is_cubes::listen::{self, ListenableSource};
-use all_is_cubes::raytracer::RtRenderer;
+use all_is_cubes::raytracer::{Background, RtRenderer};
 use all_is_cubes::universe::Universe;
 use all_is_cubes_port::gltf::{GltfDataDestination, GltfWriter};
 use all_is_cubes_port::{ExportFormat, ExportSet};
@@ -102,6 +102,8 @@ impl Recorder {
                     })
                     .context("failed to create recording renderer thread")?;
 
+                let background = options.effective_background();
+
                 // Image encoding and writing thread.
                 std::thread::Builder::new()
                     .name("image encoder".to_string())
@@ -122,6 +124,7 @@ impl Recorder {
 
                 RecorderInner::Raytrace(RtRecorder {
                     cameras,
+                    background,
                     scene_sender,
                 })
             }
@@ -129,10 +132,11 @@ impl Recorder {
                 let (scene_sender, scene_receiver) =
                     mpsc::sync_channel::<write_gltf::MeshRecordMsg>(1);
 
-                let writer = GltfWriter::new(GltfDataDestination::new(
+                let mut writer = GltfWriter::new(GltfDataDestination::new(
                     Some(options.output_path.clone()),
                     2000,
                 ));
+                writer.set_bake_light(options.bake_light);
                 let tex = writer.texture_allocator();
 
                 // TODO: implement options.save_all
@@ -185,6 +189,7 @@ impl Recorder {
                     Box::new(|v| v),
                     ListenableSource::constant(()),
                 );
+                renderer.set_background(rec.background.clone());
                 renderer.update(None).unwrap();
 
                 // TODO: instead of panic on send failure, log the problem
@@ -250,5 +255,6 @@ impl listen::Listen for Recorder {
 #[derive(Debug)]
 pub(crate) struct RtRecorder {
     cameras: StandardCameras,
+    background: Option<Background>,
     scene_sender: mpsc::SyncSender<(FrameNumber, RtRenderer)>,
 }
@@ -7,6 +7,7 @@ use std::time::Duration;
 
 use all_is_cubes::camera::Viewport;
 use all_is_cubes::cgmath::Vector2;
+use all_is_cubes::raytracer::Background;
 
 /// Options for recording and output in [`record_main`].
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -17,6 +18,13 @@ pub struct RecordOptions {
     pub save_all: bool,
     pub image_size: Vector2<u32>,
     pub animation: Option<RecordAnimationOptions>,
+    /// If set, overrides the sky color of the recorded space when raytracing.
+    pub background: Option<Background>,
+    /// If true, pixels which show only the sky are fully transparent instead.
+    /// Overrides `background`.
+    pub transparent_background: bool,
+    /// If true, and the format is glTF, the light of each frame is baked into the meshes.
+    pub bake_light: bool,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -36,6 +44,16 @@ pub enum RecordFormat {
 }
 
 impl RecordOptions {
+    /// The background to give to the raytracer, taking into account
+    /// `transparent_background`.
+    pub(crate) fn effective_background(&self) -> Option<Background> {
+        if self.transparent_background {
+            Some(Background::Transparent)
+        } else {
+            self.background.clone()
+        }
+    }
+
     pub(crate) fn viewport(&self) -> Viewport {
         Viewport::with_scale(1.0, self.image_size)
     }
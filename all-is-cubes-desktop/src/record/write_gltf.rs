@@ -85,6 +85,7 @@ impl MeshRecorder {
                         )
                     })
                     .collect(),
+                self.cameras.world_space().snapshot(),
             ))
             .expect("channel closed; recorder render thread died?")
     }
@@ -103,6 +104,8 @@ pub(crate) enum MeshRecordMsg {
         super::FrameNumber,
         camera::Camera,
         Vec<(MeshIndexCell, GridVector)>,
+        /// Source of the light to bake into the meshes, if enabled.
+        Option<universe::URef<Space>>,
     ),
 }
 
@@ -139,24 +142,28 @@ pub(super) fn start_gltf_writing(
                             .set(mesh_index)
                             .expect("mesh index cell used more than once");
                     }
-                    MeshRecordMsg::FinishFrame(frame_number, camera, meshes) => {
-                        let flaws = writer.add_frame(
-                            Some(&camera),
-                            &meshes
-                                .into_iter()
-                                .filter_map(|(index_cell, translation)| {
-                                    let opt_mesh =
-                                        *index_cell.get().expect("mesh index cell not set");
-                                    // If there is no mesh index then the original mesh was empty.
-                                    // Just filter it out.
-                                    let mesh = opt_mesh?;
-                                    Some(MeshInstance {
-                                        mesh,
-                                        translation: translation.into(),
-                                    })
+                    MeshRecordMsg::FinishFrame(frame_number, camera, meshes, space) => {
+                        let instances = meshes
+                            .into_iter()
+                            .filter_map(|(index_cell, translation)| {
+                                let opt_mesh = *index_cell.get().expect("mesh index cell not set");
+                                // If there is no mesh index then the original mesh was empty.
+                                // Just filter it out.
+                                let mesh = opt_mesh?;
+                                Some(MeshInstance {
+                                    mesh,
+                                    translation: translation.into(),
                                 })
-                                .collect::<Vec<_>>(),
-                        );
+                            })
+                            .collect::<Vec<_>>();
+                        // TODO: The light is read when the frame is written rather than when
+                        // it was captured, so it may be slightly late.
+                        let flaws = match space.as_ref().map(universe::URef::read) {
+                            Some(Ok(space)) => {
+                                writer.add_frame_with_light(Some(&camera), &instances, &space)
+                            }
+                            _ => writer.add_frame(Some(&camera), &instances),
+                        };
                         status_notifier.notify(super::Status {
                             frame_number,
                             flaws,
@@ -10,6 +10,8 @@ mod debug_lines;
 pub(crate) use debug_lines::*;
 mod draw_to_texture;
 pub(crate) use draw_to_texture::*;
+mod image_texture;
+pub use image_texture::*;
 mod info;
 pub use info::*;
 
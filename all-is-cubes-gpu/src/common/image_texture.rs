@@ -0,0 +1,402 @@
+//! [`ImageTextureAllocator`], a [`texture::Allocator`] which produces an in-memory
+//! image instead of a GPU texture.
+
+use std::sync::{Arc, Mutex, Weak};
+
+use all_is_cubes::cgmath::{Point2, Point3};
+use all_is_cubes::math::{GridAab, GridCoordinate};
+use all_is_cubes_mesh::texture;
+
+use crate::octree_alloc::Alloctree;
+
+/// [`texture::Allocator`] implementation which gathers texels and packs them into a
+/// 2D [`image::RgbaImage`] texture atlas, for export or other non-GPU uses.
+///
+/// Since the atlas is two-dimensional, each [`texture::Plane`] sliced from a tile gets
+/// its own region of the atlas. The layout of the atlas is not decided until
+/// [`ImageTextureAllocator::build_atlas()`] is called, so the points stored in vertices
+/// are not yet texture coordinates; convert them using [`ImageAtlas::texcoord()`].
+///
+/// This allocator is intended for producing a single atlas and then being discarded;
+/// it does not reuse the identifiers of dropped planes.
+#[derive(Clone, Debug, Default)]
+pub struct ImageTextureAllocator {
+    backing: Arc<Mutex<AllocatorBacking>>,
+}
+
+/// Tile type used by [`ImageTextureAllocator`].
+///
+/// This is public out of necessity but should not generally need to be used.
+#[derive(Clone, Debug)]
+pub struct ImageTile {
+    bounds: GridAab,
+    texels: TexelsCell,
+    allocator: Arc<Mutex<AllocatorBacking>>,
+}
+
+/// Plane type used by [`ImageTextureAllocator`].
+///
+/// Its region of the atlas may be found using [`ImageAtlas::uv_rect()`].
+#[derive(Clone, Debug)]
+pub struct ImagePlane {
+    backing: Arc<PlaneBacking>,
+}
+
+/// Type of points produced by [`ImageTextureAllocator`], identifying an [`ImagePlane`]
+/// and a position within it.
+///
+/// These are not directly usable as texture coordinates; they must first be converted
+/// using [`ImageAtlas::texcoord()`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ImagePoint {
+    plane_id: usize,
+    /// Point within the plane, in texel units.
+    point_within: Point2<f32>,
+}
+
+/// Texture atlas image produced by [`ImageTextureAllocator::build_atlas()`],
+/// and the locations of each [`ImagePlane`] within it.
+#[derive(Clone, Debug)]
+pub struct ImageAtlas {
+    image: image::RgbaImage,
+    /// Lower corner of each plane's region in the image, in pixels,
+    /// indexed by plane ID. [`None`] if the plane was dropped before the atlas was built.
+    placements: Vec<Option<Point2<u32>>>,
+    /// Size of each plane, in pixels, with the same indexing as `placements`.
+    sizes: Vec<Point2<u32>>,
+}
+
+/// Rectangle within an [`ImageAtlas`], in texture coordinates which range from 0 to 1
+/// across the whole image.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[allow(clippy::exhaustive_structs)]
+pub struct UvRect {
+    /// Corner with the lowest coordinates.
+    pub lower: Point2<f32>,
+    /// Corner with the highest coordinates.
+    pub upper: Point2<f32>,
+}
+
+/// Texels are written here through tiles and read when building the atlas.
+type TexelsCell = Arc<Mutex<Option<Box<[texture::Texel]>>>>;
+
+#[derive(Debug, Default)]
+struct AllocatorBacking {
+    /// Weak references to every plane ever created, indexed by plane ID.
+    /// Dropped planes are not included in the atlas.
+    planes: Vec<Weak<PlaneBacking>>,
+}
+
+#[derive(Debug)]
+struct PlaneBacking {
+    plane_id: usize,
+    /// Texels of the tile this plane was sliced from.
+    source_texels: TexelsCell,
+    /// Bounds of the tile this plane was sliced from.
+    source_bounds: GridAab,
+    /// Bounds of the slice; has size 1 on axis `axis`.
+    sliced_bounds: GridAab,
+    axis: usize,
+}
+
+impl ImageTextureAllocator {
+    /// Creates an allocator with no tiles.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Packs the texels of every [`ImagePlane`] which has not yet been dropped into
+    /// a single image.
+    ///
+    /// Planes whose tiles have not been written are left transparent black.
+    /// This may be called more than once, in which case the layout may differ.
+    pub fn build_atlas(&self) -> ImageAtlas {
+        let planes: Vec<Option<Arc<PlaneBacking>>> = self
+            .backing
+            .lock()
+            .unwrap()
+            .planes
+            .iter()
+            .map(Weak::upgrade)
+            .collect();
+
+        // Allocate each plane as a one-texel-thick box in the octree. The layers of the
+        // octree along the Z axis are then stacked vertically to form the image.
+        let mut alloctree = Alloctree::new(0);
+        let mut placements_3d: Vec<Option<GridAab>> = Vec::with_capacity(planes.len());
+        let mut sizes: Vec<Point2<u32>> = Vec::with_capacity(planes.len());
+        for plane in &planes {
+            let Some(plane) = plane else {
+                placements_3d.push(None);
+                sizes.push(Point2::new(0, 0));
+                continue;
+            };
+            let size = plane.size();
+            let request = GridAab::from_lower_size(
+                [0, 0, 0],
+                [
+                    GridCoordinate::try_from(size.x).unwrap(),
+                    GridCoordinate::try_from(size.y).unwrap(),
+                    1,
+                ],
+            );
+            let handle = alloctree
+                .allocate_with_growth(request)
+                .expect("texture atlas too large");
+            placements_3d.push(Some(handle.allocation));
+            sizes.push(size);
+        }
+
+        let layer_size = u32::try_from(alloctree.bounds().size().x).unwrap();
+        let layer_count = placements_3d
+            .iter()
+            .flatten()
+            .map(|allocation| allocation.upper_bounds().z)
+            .max()
+            .unwrap_or(0);
+        let placements: Vec<Option<Point2<u32>>> = placements_3d
+            .iter()
+            .map(|allocation| {
+                allocation.map(|allocation| {
+                    let lower = allocation.lower_bounds().cast::<u32>().unwrap();
+                    Point2::new(lower.x, lower.z * layer_size + lower.y)
+                })
+            })
+            .collect();
+
+        let mut image =
+            image::RgbaImage::new(layer_size, layer_size * u32::try_from(layer_count).unwrap());
+        for (plane, &placement) in planes.iter().zip(&placements) {
+            if let (Some(plane), Some(placement)) = (plane, placement) {
+                plane.copy_into(&mut image, placement);
+            }
+        }
+
+        ImageAtlas {
+            image,
+            placements,
+            sizes,
+        }
+    }
+}
+
+impl texture::Allocator for ImageTextureAllocator {
+    type Tile = ImageTile;
+    type Point = ImagePoint;
+
+    fn allocate(&self, bounds: GridAab) -> Option<ImageTile> {
+        Some(ImageTile {
+            bounds,
+            texels: TexelsCell::default(),
+            allocator: self.backing.clone(),
+        })
+    }
+}
+
+impl texture::Tile for ImageTile {
+    type Point = ImagePoint;
+    type Plane = ImagePlane;
+    const REUSABLE: bool = true;
+
+    fn bounds(&self) -> GridAab {
+        self.bounds
+    }
+
+    fn slice(&self, sliced_bounds: GridAab) -> Self::Plane {
+        let axis = texture::validate_slice(self.bounds, sliced_bounds);
+
+        let planes = &mut self.allocator.lock().unwrap().planes;
+        let backing = Arc::new(PlaneBacking {
+            plane_id: planes.len(),
+            source_texels: self.texels.clone(),
+            source_bounds: self.bounds,
+            sliced_bounds,
+            axis,
+        });
+        planes.push(Arc::downgrade(&backing));
+        ImagePlane { backing }
+    }
+
+    fn write(&mut self, data: &[texture::Texel]) {
+        assert_eq!(data.len(), self.bounds.volume());
+        *self.texels.lock().unwrap() = Some(data.into());
+    }
+}
+
+/// Compared by reference.
+impl PartialEq for ImageTile {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.texels, &other.texels)
+    }
+}
+impl Eq for ImageTile {}
+
+impl texture::Plane for ImagePlane {
+    type Point = ImagePoint;
+
+    fn grid_to_texcoord(&self, in_tile_grid: Point3<f32>) -> Self::Point {
+        let relative = in_tile_grid - self.backing.sliced_bounds.lower_bounds().cast().unwrap();
+        let [u, v] = self.backing.axis_to_uv(relative.into());
+        ImagePoint {
+            plane_id: self.backing.plane_id,
+            point_within: Point2::new(u, v),
+        }
+    }
+}
+
+/// Compared by reference.
+impl PartialEq for ImagePlane {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.backing, &other.backing)
+    }
+}
+impl Eq for ImagePlane {}
+
+impl PlaneBacking {
+    /// Choose which two axes of the slice become the U and V axes of the image.
+    fn axis_to_uv<T: Copy>(&self, [x, y, z]: [T; 3]) -> [T; 2] {
+        match self.axis {
+            0 => [y, z],
+            1 => [x, z],
+            2 => [x, y],
+            _ => unreachable!(),
+        }
+    }
+
+    fn size(&self) -> Point2<u32> {
+        let [w, h] = self.axis_to_uv(self.sliced_bounds.size().into());
+        // cannot fail because sizes are nonnegative
+        Point2::new(u32::try_from(w).unwrap(), u32::try_from(h).unwrap())
+    }
+
+    /// Copy this plane's texels into the image with its lower corner at `placement`.
+    fn copy_into(&self, image: &mut image::RgbaImage, placement: Point2<u32>) {
+        let texels_guard = self.source_texels.lock().unwrap();
+        let Some(texels) = texels_guard.as_deref() else {
+            return;
+        };
+        let source_size = self.source_bounds.size();
+        for cube in self.sliced_bounds.interior_iter() {
+            let position_in_texels = cube.lower_bounds() - self.source_bounds.lower_bounds();
+            // X-major ordering as specified by `texture::Tile::write()`.
+            let index = position_in_texels.x
+                + source_size.x * (position_in_texels.y + source_size.y * position_in_texels.z);
+            let [u, v] =
+                self.axis_to_uv((cube.lower_bounds() - self.sliced_bounds.lower_bounds()).into());
+            image.put_pixel(
+                placement.x + u32::try_from(u).unwrap(),
+                placement.y + u32::try_from(v).unwrap(),
+                image::Rgba(texels[usize::try_from(index).unwrap()]),
+            );
+        }
+    }
+}
+
+impl ImageAtlas {
+    /// Returns the atlas image.
+    pub fn image(&self) -> &image::RgbaImage {
+        &self.image
+    }
+
+    /// Returns the atlas image, discarding the layout information.
+    pub fn into_image(self) -> image::RgbaImage {
+        self.image
+    }
+
+    /// Returns the region of the image occupied by the given plane, or [`None`] if the
+    /// plane was not created by the allocator this atlas was built from, or was
+    /// created after the atlas was built.
+    pub fn uv_rect(&self, plane: &ImagePlane) -> Option<UvRect> {
+        let id = plane.backing.plane_id;
+        let lower = (*self.placements.get(id)?)?;
+        let size = self.sizes[id];
+        Some(UvRect {
+            lower: self.normalize(lower.cast().unwrap()),
+            upper: self.normalize(Point2::new(
+                (lower.x + size.x) as f32,
+                (lower.y + size.y) as f32,
+            )),
+        })
+    }
+
+    /// Converts a point stored in a vertex to texture coordinates within this atlas.
+    ///
+    /// Returns [`None`] under the same conditions as [`ImageAtlas::uv_rect()`].
+    pub fn texcoord(&self, point: ImagePoint) -> Option<Point2<f32>> {
+        let lower = (*self.placements.get(point.plane_id)?)?;
+        Some(self.normalize(Point2::new(
+            lower.x as f32 + point.point_within.x,
+            lower.y as f32 + point.point_within.y,
+        )))
+    }
+
+    fn normalize(&self, pixel_point: Point2<f32>) -> Point2<f32> {
+        Point2::new(
+            pixel_point.x / self.image.width() as f32,
+            pixel_point.y / self.image.height() as f32,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use all_is_cubes_mesh::texture::{Allocator as _, Tile as _};
+
+    #[test]
+    fn two_tiles_in_atlas() {
+        let allocator = ImageTextureAllocator::new();
+        let bounds = GridAab::from_lower_size([0, 0, 0], [2, 3, 1]);
+        let mut tile_1 = allocator.allocate(bounds).unwrap();
+        let mut tile_2 = allocator.allocate(bounds).unwrap();
+        tile_1.write(&[[255, 0, 0, 255]; 6]);
+        tile_2.write(&[[0, 0, 255, 255]; 6]);
+        let plane_1 = tile_1.slice(bounds);
+        let plane_2 = tile_2.slice(bounds);
+
+        let atlas = allocator.build_atlas();
+        let image = atlas.image();
+
+        let rect_1 = atlas.uv_rect(&plane_1).unwrap();
+        let rect_2 = atlas.uv_rect(&plane_2).unwrap();
+        assert_ne!(rect_1, rect_2);
+        for (rect, color) in [(rect_1, [255, 0, 0, 255]), (rect_2, [0, 0, 255, 255])] {
+            let to_pixel = |uv: Point2<f32>| {
+                Point2::new(
+                    (uv.x * image.width() as f32).round() as u32,
+                    (uv.y * image.height() as f32).round() as u32,
+                )
+            };
+            let lower = to_pixel(rect.lower);
+            let upper = to_pixel(rect.upper);
+            assert_eq!((upper.x - lower.x, upper.y - lower.y), (2, 3));
+            for y in lower.y..upper.y {
+                for x in lower.x..upper.x {
+                    assert_eq!(*image.get_pixel(x, y), image::Rgba(color), "at {x}, {y}");
+                }
+            }
+        }
+    }
+
+    /// Slicing a 3D tile on a non-Z axis copies the correct texels.
+    #[test]
+    fn slice_of_volume() {
+        let allocator = ImageTextureAllocator::new();
+        let bounds = GridAab::from_lower_size([10, 0, 0], [2, 2, 2]);
+        let mut tile = allocator.allocate(bounds).unwrap();
+        // Each texel's red channel encodes its X-major index.
+        tile.write(&core::array::from_fn::<_, 8, _>(|i| [i as u8, 0, 0, 255]));
+        let plane = tile.slice(GridAab::from_lower_size([11, 0, 0], [1, 2, 2]));
+        let point = texture::Plane::grid_to_texcoord(&plane, Point3::new(11.0, 1.5, 0.5));
+
+        let atlas = allocator.build_atlas();
+        let image = atlas.image();
+        let uv = atlas.texcoord(point).unwrap();
+        let pixel = image.get_pixel(
+            (uv.x * image.width() as f32) as u32,
+            (uv.y * image.height() as f32) as u32,
+        );
+        // Cube [11, 1, 0] has index 1 + 2 * 1 = 3.
+        assert_eq!(*pixel, image::Rgba([3, 0, 0, 255]));
+    }
+}
@@ -8,7 +8,9 @@ use std::sync::Arc;
 use all_is_cubes::notnan;
 use all_is_cubes::time;
 
-use all_is_cubes::camera::{info_text_drawable, Layers, StandardCameras};
+use all_is_cubes::camera::{
+    info_text_drawable, Flaws, Layers, StandardCameras, TextureFilterOption,
+};
 use all_is_cubes::cgmath::Vector2;
 use all_is_cubes::character::Cursor;
 use all_is_cubes::content::palette;
@@ -277,7 +279,13 @@ impl<I: time::Instant> EverythingRenderer<I> {
 
             space_renderers: Default::default(),
             #[allow(clippy::arc_with_non_send_sync)]
-            block_texture: AtlasAllocator::new("EverythingRenderer"),
+            block_texture: AtlasAllocator::new(
+                "EverythingRenderer",
+                match cameras.graphics_options().block_texture_filter {
+                    TextureFilterOption::Linear => wgpu::FilterMode::Linear,
+                    /* TextureFilterOption::Nearest | */ _ => wgpu::FilterMode::Nearest,
+                },
+            ),
 
             lines_buffer: ResizingBuffer::default(),
             lines_vertex_count: 0,
@@ -332,6 +340,9 @@ impl<I: time::Instant> EverythingRenderer<I> {
 
     /// Read current scene content, compute meshes, and send updated resources
     /// to the GPU to prepare for actually drawing it.
+    ///
+    /// If `cursor_result` is [`None`], the cursor from [`StandardCameras::cursor()`] is
+    /// used instead.
     pub fn update(
         &mut self,
         queue: &wgpu::Queue,
@@ -343,6 +354,8 @@ impl<I: time::Instant> EverythingRenderer<I> {
         // This updates camera matrices and graphics options which we are going to consult
         // or copy to the GPU.
         self.cameras.update();
+        let cameras_cursor = self.cameras.cursor().cloned();
+        let cursor_result = cursor_result.or(cameras_cursor.as_ref());
 
         // Update viewport-sized resources from viewport.
         {
@@ -411,6 +424,17 @@ impl<I: time::Instant> EverythingRenderer<I> {
             &self.block_texture,
         )?;
 
+        // If block textures have been added and removed enough that the atlas is wasting
+        // space, repack it. This moves the textures, so the meshes must be rebuilt.
+        let compacted =
+            self.block_texture.is_fragmented() && self.block_texture.compact(&self.device, queue);
+        if compacted {
+            let Layers { world, ui } = &mut self.space_renderers;
+            for sr in [world, ui].into_iter().flatten() {
+                sr.textures_moved();
+            }
+        }
+
         let mut encoder = self
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor {
@@ -425,9 +449,19 @@ impl<I: time::Instant> EverythingRenderer<I> {
 
         let update_prep_to_space_update_time = I::now();
 
-        let world_deadline =
-            time::Deadline::At(update_prep_to_space_update_time + frame_budget.update_meshes.world);
-        let ui_deadline = world_deadline + frame_budget.update_meshes.ui;
+        let (world_deadline, ui_deadline) = if compacted {
+            // Finish rebuilding the meshes using moved textures, rather than leaving
+            // parts of the spaces missing.
+            (time::Deadline::Whenever, time::Deadline::Whenever)
+        } else {
+            let world_deadline = time::Deadline::At(
+                update_prep_to_space_update_time + frame_budget.update_meshes.world,
+            );
+            (
+                world_deadline,
+                world_deadline + frame_budget.update_meshes.ui,
+            )
+        };
 
         let space_infos: Layers<SpaceUpdateInfo> = Layers {
             world: self
@@ -518,7 +552,15 @@ impl<I: time::Instant> EverythingRenderer<I> {
 
         let finish_update_time = I::now();
         Ok(UpdateInfo {
-            flaws: self.fb.flaws() | space_infos.world.flaws() | space_infos.ui.flaws(),
+            flaws: self.fb.flaws()
+                | space_infos.world.flaws()
+                | space_infos.ui.flaws()
+                | if self.cameras.space_layers().is_empty() {
+                    Flaws::empty()
+                } else {
+                    // TODO: implement drawing space layers
+                    Flaws::NO_SPACE_LAYERS
+                },
             total_time: finish_update_time.saturating_duration_since(start_frame_time),
             prep_time: update_prep_to_space_update_time.saturating_duration_since(start_frame_time),
             lines_time: lines_to_submit_time.saturating_duration_since(space_update_to_lines_time),
@@ -3,10 +3,11 @@
 
 #![allow(clippy::arc_with_non_send_sync)] // wgpu on wasm
 
-use std::sync::{Arc, Mutex, Weak};
+use std::cmp::Reverse;
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
 
 use all_is_cubes::cgmath::{Point3, Vector3};
-use all_is_cubes::math::GridAab;
+use all_is_cubes::math::{GridAab, GridCoordinate, Rgba};
 use all_is_cubes::time;
 use all_is_cubes_mesh::texture;
 
@@ -15,11 +16,21 @@ use crate::in_wgpu::vertex::TexPoint;
 use crate::octree_alloc::{Alloctree, AlloctreeHandle};
 use crate::BlockTextureInfo;
 
+/// Number of mip levels of the atlas texture when mipmaps are enabled, including the
+/// full-resolution level 0.
+///
+/// This must not exceed the number of levels that the minimum atlas size (32) supports.
+const MIP_LEVELS: u32 = 4;
+
+/// [`Alloctree`] size exponent of a new or freshly compacted atlas.
+/// 2⁵ = 32 holds up to 8 × 16³ block textures.
+const INITIAL_SIZE_EXPONENT: u8 = 5;
+
 /// Implementation of [`texture::Allocator`] for [`wgpu`].
 ///
 /// After any allocations, you must call [`AtlasAllocator::flush()`] to write the
 /// new texels to the actual GPU texture for drawing. Existing allocations will remain
-/// valid regardless.
+/// valid regardless, unless [`AtlasAllocator::compact()`] is called.
 #[derive(Clone, Debug)]
 pub struct AtlasAllocator {
     /// Note on lock ordering: Do not attempt to acquire this lock while a tile's lock is held.
@@ -33,8 +44,6 @@ pub struct AtlasAllocator {
 pub struct AtlasTile {
     /// Original bounds as requested (not texture coordinates).
     requested_bounds: GridAab,
-    /// Translation of the requested bounds to the actual region within the texture.
-    offset: Vector3<i32>,
     /// Actual storage and metadata about the tile; may be updated as needed by the
     /// allocator to grow the texture.
     ///
@@ -50,11 +59,16 @@ pub struct AtlasTile {
 pub struct AtlasPlane {
     tile: AtlasTile,
     requested_bounds: GridAab,
+    /// Translation from the tile's requested bounds to the atlas texture, as of when this
+    /// plane was created, so that it can be used without locking the tile.
+    offset: Vector3<GridCoordinate>,
 }
 
 #[derive(Debug)]
 struct TileBacking {
     /// Allocator information, and the region of the atlas texture which this tile owns.
+    /// This includes the translation of the requested bounds to that region, which
+    /// changes if the tile is moved by [`AtlasAllocator::compact()`].
     ///
     /// Property: `self.handle.unwrap().allocation.volume() == self.data.len()`.
     handle: Option<AlloctreeHandle>,
@@ -74,6 +88,10 @@ struct AllocatorBacking {
     /// Tracks which regions of the texture are free or allocated.
     alloctree: Alloctree,
 
+    /// Whether the atlas had to grow while less than half of it was occupied, which
+    /// means that its free space is too fragmented to use and it should be compacted.
+    fragmented: bool,
+
     /// Whether flush needs to do anything.
     dirty: bool,
 
@@ -85,6 +103,12 @@ struct AllocatorBacking {
     /// Debug label for the GPU texture resource.
     texture_label: String,
 
+    /// How the shader should filter texels; used for `sampler`.
+    filter: wgpu::FilterMode,
+
+    /// GPU sampler for the texture. [`None`] if it has not yet been created.
+    sampler: Option<Arc<wgpu::Sampler>>,
+
     /// GPU texture. [`None`] if no texture has yet been created.
     ///
     /// The texture view is wrapped in [`Arc`] so that it can be used by drawing code
@@ -93,37 +117,58 @@ struct AllocatorBacking {
 }
 
 impl AtlasAllocator {
-    pub fn new(label_prefix: &str) -> Self {
-        // Default size of 2⁵ = 32 holding up to 8 × 16³ block textures.
-        let alloctree = Alloctree::new(5);
-
+    /// `filter` is used for both magnification and minification of the textures.
+    /// Tiles do not bleed into each other with either filter mode, because texture
+    /// coordinates are clamped to the centers of each face's texels.
+    pub fn new(label_prefix: &str, filter: wgpu::FilterMode) -> Self {
         Self {
             backing: Arc::new(Mutex::new(AllocatorBacking {
-                alloctree,
+                alloctree: Alloctree::new(INITIAL_SIZE_EXPONENT),
+                fragmented: false,
                 dirty: false,
                 in_use: Vec::new(),
                 texture_label: format!("{label_prefix} block texture"),
+                filter,
+                sampler: None,
                 texture: None,
             })),
         }
     }
 
+    /// Returns a `wgpu::Sampler` for the texture, creating it if it does not yet exist.
+    pub fn sampler(&self, device: &wgpu::Device) -> Arc<wgpu::Sampler> {
+        let backing = &mut *self.backing.lock().unwrap();
+        backing
+            .sampler
+            .get_or_insert_with(|| {
+                Arc::new(device.create_sampler(&sampler_descriptor(backing.filter)))
+            })
+            .clone()
+    }
+
     /// Copy the texels of all modified and still-referenced tiles to the GPU's texture.
+    ///
+    /// If `mipmaps` is true, the texture has [`MIP_LEVELS`] mip levels, and each tile is
+    /// downsampled into them (independently of other tiles, so that they do not bleed
+    /// into each other).
     pub fn flush<I: time::Instant>(
         &self,
         device: &wgpu::Device,
         queue: &wgpu::Queue,
+        mipmaps: bool,
     ) -> (Arc<wgpu::TextureView>, BlockTextureInfo) {
         let start_time = I::now();
         let backing = &mut *self.backing.lock().unwrap();
 
         let needed_texture_size = size_vector_to_extent(backing.alloctree.bounds().size());
+        let needed_mip_level_count = if mipmaps { MIP_LEVELS } else { 1 };
 
-        // If we have a texture, check if it is the right size.
+        // If we have a texture, check if it is the right size and has the right mip levels.
         let old_texture: Option<wgpu::Texture> = if matches!(
             backing.texture,
             Some((ref texture, _))
             if texture.size() != needed_texture_size
+                || texture.mip_level_count() != needed_mip_level_count
         ) {
             backing.texture.take().map(|(texture, _)| texture)
         } else {
@@ -132,25 +177,21 @@ impl AtlasAllocator {
 
         // TODO: On WebGL, copying from the old texture silently does nothing. We should
         // report a `wgpu` bug, but for now, avoid it by re-writing everything
-        // instead of copying. When the bug is fixed, delete this variable entirely.
-        let copy_everything_anyway = cfg!(target_family = "wasm") && old_texture.is_some();
+        // instead of copying. When the bug is fixed, delete the wasm condition.
+        //
+        // When there are mip levels, we also re-write everything instead of copying,
+        // since the lower levels may not exist in the old texture.
+        let copy_everything_anyway =
+            old_texture.is_some() && (cfg!(target_family = "wasm") || needed_mip_level_count > 1);
 
         // Allocate a texture if needed.
         let (texture, texture_view) = backing.texture.get_or_insert_with(|| {
-            // TODO: Add an error scope so we can detect and recover from errors,
-            // including out-of-memory.
-            let texture = device.create_texture(&wgpu::TextureDescriptor {
-                size: needed_texture_size,
-                mip_level_count: 1,
-                sample_count: 1,
-                dimension: wgpu::TextureDimension::D3,
-                format: wgpu::TextureFormat::Rgba8UnormSrgb,
-                view_formats: &[],
-                usage: wgpu::TextureUsages::TEXTURE_BINDING
-                    | wgpu::TextureUsages::COPY_SRC
-                    | wgpu::TextureUsages::COPY_DST,
-                label: Some(&backing.texture_label),
-            });
+            let texture = create_atlas_texture(
+                device,
+                &backing.texture_label,
+                needed_texture_size,
+                needed_mip_level_count,
+            );
 
             // Copy the old texture into the low corner of the new texture, so existing
             // data is preserved. (Note that this assumes that the new texture is larger,
@@ -181,9 +222,7 @@ impl AtlasAllocator {
                 queue.submit([encoder.finish()]);
             }
 
-            let texture_view =
-                Arc::new(texture.create_view(&wgpu::TextureViewDescriptor::default()));
-
+            let texture_view = create_view(&texture);
             (texture, texture_view)
         });
 
@@ -201,7 +240,10 @@ impl AtlasAllocator {
                                 .expect("can't happen: dead TileBacking")
                                 .allocation;
 
-                            write_texture_by_aab(queue, texture, region, data);
+                            write_texture_by_aab(queue, texture, 0, region, data);
+                            if mipmaps {
+                                write_tile_mips(queue, texture, region, data);
+                            }
                             backing.dirty = false;
                             count_written += 1;
                         }
@@ -224,6 +266,34 @@ impl AtlasAllocator {
         )
     }
 
+    /// Returns whether the atlas's free space has become fragmented, such that calling
+    /// [`AtlasAllocator::compact()`] would be worthwhile.
+    pub fn is_fragmented(&self) -> bool {
+        self.backing.lock().unwrap().fragmented
+    }
+
+    /// Repack all still-referenced tiles into a fresh, tightly packed arrangement, so that
+    /// space freed by dropped tiles can be reused for large allocations and the texture
+    /// may shrink.
+    ///
+    /// The texels of tiles which were already flushed are copied to their new locations on
+    /// the GPU; others will be written by the next [`AtlasAllocator::flush()`] as usual.
+    /// However, the texture coordinates of the tiles change, so any meshes using tiles
+    /// from this allocator must be regenerated afterward, before they are drawn.
+    /// Regenerating them should reuse the same tiles; rewriting a tile with its existing
+    /// texels does not write them to the GPU again.
+    ///
+    /// Returns whether the tiles were moved.
+    pub fn compact(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> bool {
+        let mut backing = self.backing.lock().unwrap();
+        let live_tiles = backing.live_tiles();
+        let moved = backing.repack(&live_tiles, Some((device, queue)));
+        drop(backing);
+        // Dropping a tile takes the allocator lock, so this must happen after unlocking.
+        drop(live_tiles);
+        moved
+    }
+
     /// Returns a `wgpu::TextureView` that is current as of the last `flush()`, or
     /// `None` if `flush()` has not been called.
     pub fn current_texture_view(&self) -> Option<Arc<wgpu::TextureView>> {
@@ -236,21 +306,275 @@ impl AtlasAllocator {
     }
 }
 
+impl AllocatorBacking {
+    /// Returns strong references to all tiles which have not been dropped, and discards
+    /// the weak references to the rest.
+    ///
+    /// The caller must not drop the returned references while holding the lock on `self`.
+    fn live_tiles(&mut self) -> Vec<Arc<Mutex<TileBacking>>> {
+        let live_tiles: Vec<Arc<Mutex<TileBacking>>> =
+            self.in_use.iter().filter_map(Weak::upgrade).collect();
+        self.in_use = live_tiles.iter().map(Arc::downgrade).collect();
+        live_tiles
+    }
+
+    /// Implementation of [`AtlasAllocator::compact()`], which may be used without a GPU.
+    ///
+    /// If `gpu` is [`None`] or the texels cannot be copied, the GPU texture is discarded
+    /// and all tiles are marked dirty so that the next flush writes them again.
+    fn repack(
+        &mut self,
+        live_tiles: &[Arc<Mutex<TileBacking>>],
+        gpu: Option<(&wgpu::Device, &wgpu::Queue)>,
+    ) -> bool {
+        // Allocating larger tiles first packs them more tightly.
+        let mut tiles: Vec<MutexGuard<'_, TileBacking>> =
+            live_tiles.iter().map(|tile| tile.lock().unwrap()).collect();
+        tiles.sort_by_key(|tile| Reverse(tile.allocation().volume()));
+
+        let mut alloctree = Alloctree::new(INITIAL_SIZE_EXPONENT);
+        let Some(new_handles) = tiles
+            .iter()
+            .map(|tile| {
+                let handle = tile
+                    .handle
+                    .as_ref()
+                    .expect("can't happen: dead TileBacking");
+                alloctree.allocate_with_growth(handle.allocation.translate(-handle.offset))
+            })
+            .collect::<Option<Vec<AlloctreeHandle>>>()
+        else {
+            // Can't happen, since the tiles fit before, but if it does, leave them be.
+            return false;
+        };
+
+        // Regions to copy from the old texture to the new one.
+        let mut moves: Vec<(GridAab, GridAab)> = Vec::new();
+        for (tile, new_handle) in tiles.iter_mut().zip(new_handles) {
+            let old_handle = tile.handle.replace(new_handle);
+            if tile.data.is_some() && !tile.dirty {
+                moves.push((old_handle.unwrap().allocation, tile.allocation()));
+            }
+        }
+        self.alloctree = alloctree;
+        self.fragmented = false;
+
+        let old_texture = self.texture.take().map(|(texture, _)| texture);
+        match (old_texture, gpu) {
+            // See flush() regarding copying on WebGL.
+            (Some(old_texture), Some((device, queue))) if !cfg!(target_family = "wasm") => {
+                let texture = create_atlas_texture(
+                    device,
+                    &self.texture_label,
+                    size_vector_to_extent(self.alloctree.bounds().size()),
+                    old_texture.mip_level_count(),
+                );
+                let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some(&format!("{} compaction copy", self.texture_label)),
+                });
+                for (from, to) in moves {
+                    for (mip_level, from_level) in tile_mip_regions(from, &texture) {
+                        encoder.copy_texture_to_texture(
+                            wgpu::ImageCopyTexture {
+                                texture: &old_texture,
+                                mip_level,
+                                origin: point_to_origin(from_level.lower_bounds()),
+                                aspect: wgpu::TextureAspect::default(),
+                            },
+                            wgpu::ImageCopyTexture {
+                                texture: &texture,
+                                mip_level,
+                                origin: point_to_origin(to.lower_bounds().map(|c| c >> mip_level)),
+                                aspect: wgpu::TextureAspect::default(),
+                            },
+                            size_vector_to_extent(from_level.size()),
+                        );
+                    }
+                }
+                queue.submit([encoder.finish()]);
+                let texture_view = create_view(&texture);
+                self.texture = Some((texture, texture_view));
+            }
+            (None, _) => {
+                // Nothing was written to the GPU yet.
+            }
+            (Some(_), _) => {
+                for tile in tiles.iter_mut().filter(|tile| tile.data.is_some()) {
+                    tile.dirty = true;
+                }
+                self.dirty = true;
+            }
+        }
+        true
+    }
+}
+
+impl TileBacking {
+    /// Returns the region of the atlas texture which this tile owns.
+    fn allocation(&self) -> GridAab {
+        self.handle
+            .as_ref()
+            .expect("can't happen: dead TileBacking")
+            .allocation
+    }
+}
+
+fn create_atlas_texture(
+    device: &wgpu::Device,
+    label: &str,
+    size: wgpu::Extent3d,
+    mip_level_count: u32,
+) -> wgpu::Texture {
+    // TODO: Add an error scope so we can detect and recover from errors,
+    // including out-of-memory.
+    device.create_texture(&wgpu::TextureDescriptor {
+        size,
+        mip_level_count,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D3,
+        format: wgpu::TextureFormat::Rgba8UnormSrgb,
+        view_formats: &[],
+        usage: wgpu::TextureUsages::TEXTURE_BINDING
+            | wgpu::TextureUsages::COPY_SRC
+            | wgpu::TextureUsages::COPY_DST,
+        label: Some(label),
+    })
+}
+
+fn create_view(texture: &wgpu::Texture) -> Arc<wgpu::TextureView> {
+    Arc::new(texture.create_view(&wgpu::TextureViewDescriptor::default()))
+}
+
+fn point_to_origin(point: Point3<GridCoordinate>) -> wgpu::Origin3d {
+    let [x, y, z] = point
+        .cast::<u32>()
+        .expect("negative texture coordinates")
+        .into();
+    wgpu::Origin3d { x, y, z }
+}
+
+/// Returns the descriptor of the sampler [`AtlasAllocator`] creates for the given filter.
+///
+/// Mip levels are chosen explicitly by the shader, so `mipmap_filter` is irrelevant.
+fn sampler_descriptor(filter: wgpu::FilterMode) -> wgpu::SamplerDescriptor<'static> {
+    wgpu::SamplerDescriptor {
+        label: Some("block texture sampler"),
+        address_mode_u: wgpu::AddressMode::ClampToEdge,
+        address_mode_v: wgpu::AddressMode::ClampToEdge,
+        address_mode_w: wgpu::AddressMode::ClampToEdge,
+        mag_filter: filter,
+        min_filter: filter,
+        mipmap_filter: wgpu::FilterMode::Nearest,
+        ..Default::default()
+    }
+}
+
+/// Write the mip levels after level 0 of a single tile occupying `region` of level 0.
+///
+/// Levels are only written as far as the tile's [`Alloctree`] cell is exclusively its
+/// own; beyond that, the shader is responsible for not reading them.
+fn write_tile_mips(
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+    region: GridAab,
+    data: &[texture::Texel],
+) {
+    let mut size = region.size();
+    let mut level_data: Vec<texture::Texel> = data.to_vec();
+    for (mip_level, level_region) in tile_mip_regions(region, texture).skip(1) {
+        (size, level_data) = downsample_texels(size, &level_data);
+        debug_assert_eq!(size, level_region.size());
+        write_texture_by_aab(queue, texture, mip_level, level_region, &level_data);
+    }
+}
+
+/// Returns the regions of each mip level of `texture`, starting with level 0, which
+/// belong to the tile occupying `region` of level 0.
+fn tile_mip_regions(
+    region: GridAab,
+    texture: &wgpu::Texture,
+) -> impl Iterator<Item = (u32, GridAab)> {
+    // The alloctree places each allocation at the low corner of an aligned cube cell
+    // whose edge length is the next power of two ≥ the allocation's largest edge.
+    let max_edge = region.size().x.max(region.size().y).max(region.size().z);
+    let cell_exponent = GridCoordinate::BITS - (max_edge - 1).max(0).leading_zeros();
+    let levels = (texture.mip_level_count() - 1).min(cell_exponent);
+
+    (0..=levels).scan(region.size(), move |size, mip_level| {
+        let level_region =
+            GridAab::from_lower_size(region.lower_bounds().map(|c| c >> mip_level), *size);
+        *size = size.map(|c| (c + 1) / 2);
+        Some((mip_level, level_region))
+    })
+}
+
+/// Downsample a box of texels to half resolution (rounding up), for the next mip level.
+///
+/// `data` is in the same x-fastest order as the texture, and the returned texels are too.
+///
+/// Colors are averaged in linear space and weighted by alpha, so that invisible texels
+/// don't darken their neighbors. The alpha of the result is the maximum of the inputs,
+/// because the textures are volumetric: a surface texel is very often next to an
+/// invisible texel just outside the block, and averaging alpha would make distant
+/// surfaces fade into transparency.
+fn downsample_texels(
+    size: Vector3<GridCoordinate>,
+    data: &[texture::Texel],
+) -> (Vector3<GridCoordinate>, Vec<texture::Texel>) {
+    debug_assert_eq!(data.len(), (size.x * size.y * size.z) as usize);
+    let new_size = size.map(|c| (c + 1) / 2);
+    let index = |x: GridCoordinate, y: GridCoordinate, z: GridCoordinate| {
+        ((z * size.y + y) * size.x + x) as usize
+    };
+
+    let mut output = Vec::with_capacity((new_size.x * new_size.y * new_size.z) as usize);
+    for z in 0..new_size.z {
+        for y in 0..new_size.y {
+            for x in 0..new_size.x {
+                let mut weighted_sum = Vector3::new(0.0f32, 0.0, 0.0);
+                let mut total_weight = 0.0;
+                let mut max_alpha = 0.0f32;
+                for sz in (z * 2)..(z * 2 + 2).min(size.z) {
+                    for sy in (y * 2)..(y * 2 + 2).min(size.y) {
+                        for sx in (x * 2)..(x * 2 + 2).min(size.x) {
+                            let color = Rgba::from_srgb8(data[index(sx, sy, sz)]);
+                            let alpha = color.alpha().into_inner();
+                            weighted_sum += Vector3::from(color.to_rgb()) * alpha;
+                            total_weight += alpha;
+                            max_alpha = max_alpha.max(alpha);
+                        }
+                    }
+                }
+                output.push(if total_weight > 0.0 {
+                    let [r, g, b] = (weighted_sum / total_weight).into();
+                    Rgba::new(r, g, b, max_alpha).to_srgb8()
+                } else {
+                    [0, 0, 0, 0]
+                });
+            }
+        }
+    }
+    (new_size, output)
+}
+
 impl texture::Allocator for AtlasAllocator {
     type Tile = AtlasTile;
     type Point = TexPoint;
 
     fn allocate(&self, requested_bounds: GridAab) -> Option<AtlasTile> {
         let mut allocator_backing = self.backing.lock().unwrap();
+        let alloctree = &mut allocator_backing.alloctree;
 
         // If alloctree grows, the next flush() will take care of reallocating the texture.
-        let handle = allocator_backing
-            .alloctree
-            .allocate_with_growth(requested_bounds)?;
+        let old_size_exponent = alloctree.size_exponent();
+        let mostly_free = alloctree.occupied_volume() < alloctree.bounds().volume() / 2;
+        let handle = alloctree.allocate_with_growth(requested_bounds)?;
+        if alloctree.size_exponent() > old_size_exponent && mostly_free {
+            allocator_backing.fragmented = true;
+        }
 
         let result = AtlasTile {
             requested_bounds,
-            offset: handle.offset,
             backing: Arc::new(Mutex::new(TileBacking {
                 handle: Some(handle),
                 data: None,
@@ -276,9 +600,18 @@ impl texture::Tile for AtlasTile {
 
     fn slice(&self, requested_bounds: GridAab) -> Self::Plane {
         texture::validate_slice(self.requested_bounds, requested_bounds);
+        let offset = self
+            .backing
+            .lock()
+            .unwrap()
+            .handle
+            .as_ref()
+            .expect("can't happen: dead TileBacking")
+            .offset;
         AtlasPlane {
             tile: self.clone(),
             requested_bounds,
+            offset,
         }
     }
 
@@ -297,6 +630,10 @@ impl texture::Tile for AtlasTile {
         // write the data.
         let allocator_backing_ref = {
             let mut backing = self.backing.lock().unwrap();
+            if backing.data.as_deref() == Some(data) {
+                // Nothing to do, such as when the tile is reused after being moved.
+                return;
+            }
             backing.data = Some(data.into());
             backing.dirty = true;
 
@@ -322,7 +659,7 @@ impl texture::Plane for AtlasPlane {
 
     fn grid_to_texcoord(&self, in_tile_grid: Point3<f32>) -> Self::Point {
         // TODO: assert in bounds, just in case
-        in_tile_grid + self.tile.offset.map(|c| c as f32)
+        in_tile_grid + self.offset.map(|c| c as f32)
     }
 }
 
@@ -348,3 +685,105 @@ impl Drop for TileBacking {
         backing.alloctree.free(handle);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use all_is_cubes::block::Resolution::*;
+    use all_is_cubes_mesh::texture::{Allocator as _, Plane as _, Tile as _};
+
+    /// Freeing every other tile leaves half the atlas free, but in pieces too small for a
+    /// larger tile, until it is compacted.
+    #[test]
+    fn compaction_makes_room() {
+        let allocator = AtlasAllocator::new("compaction_makes_room", wgpu::FilterMode::Nearest);
+        let mut tiles: Vec<AtlasTile> = (0..64)
+            .map(|_| allocator.allocate(GridAab::for_block(R8)).unwrap())
+            .collect();
+        for (i, tile) in tiles.iter_mut().enumerate() {
+            tile.write(&[[i as u8, 0, 0, 255]; 8 * 8 * 8]);
+        }
+        let mut i = 0;
+        tiles.retain(|_| {
+            i += 1;
+            i % 2 == 0
+        });
+
+        let mut backing = allocator.backing.lock().unwrap();
+        assert_eq!(backing.alloctree.size_exponent(), INITIAL_SIZE_EXPONENT);
+        let large = GridAab::for_block(R16);
+        assert_eq!(backing.alloctree.clone().allocate(large), None);
+
+        let live_tiles = backing.live_tiles();
+        assert_eq!(live_tiles.len(), 32);
+        assert!(backing.repack(&live_tiles, None));
+        assert_eq!(backing.alloctree.size_exponent(), INITIAL_SIZE_EXPONENT);
+        assert!(backing.alloctree.clone().allocate(large).is_some());
+        drop(backing);
+        drop(live_tiles);
+
+        // Tiles' texture coordinates follow their new locations, which do not overlap.
+        let regions: Vec<GridAab> = tiles
+            .iter()
+            .map(|tile| {
+                let allocation = tile.backing.lock().unwrap().allocation();
+                let texcoord = tile
+                    .slice(GridAab::from_lower_size([0, 0, 0], [8, 8, 1]))
+                    .grid_to_texcoord(Point3::new(0., 0., 0.));
+                assert_eq!(texcoord, allocation.lower_bounds().map(|c| c as f32));
+                allocation
+            })
+            .collect();
+        for (i, a) in regions.iter().enumerate() {
+            for b in &regions[..i] {
+                assert!(a.intersection(*b).map_or(true, |i| i.volume() == 0));
+            }
+        }
+    }
+
+    #[test]
+    fn rewriting_same_texels_is_not_dirty() {
+        let allocator = AtlasAllocator::new("rewriting_same_texels", wgpu::FilterMode::Nearest);
+        let mut tile = allocator.allocate(GridAab::for_block(R2)).unwrap();
+        tile.write(&[[1, 2, 3, 255]; 8]);
+        tile.backing.lock().unwrap().dirty = false;
+
+        tile.write(&[[1, 2, 3, 255]; 8]);
+        assert!(!tile.backing.lock().unwrap().dirty);
+        tile.write(&[[4, 5, 6, 255]; 8]);
+        assert!(tile.backing.lock().unwrap().dirty);
+    }
+
+    #[test]
+    fn sampler_descriptor_uses_filter() {
+        for filter in [wgpu::FilterMode::Nearest, wgpu::FilterMode::Linear] {
+            let descriptor = sampler_descriptor(filter);
+            assert_eq!(descriptor.mag_filter, filter);
+            assert_eq!(descriptor.min_filter, filter);
+        }
+    }
+
+    #[test]
+    fn downsample_2x2_tile() {
+        let data = [
+            [255, 0, 0, 255],
+            [0, 255, 0, 255],
+            [0, 0, 255, 255],
+            [0, 0, 0, 0], // transparent; should not darken the result
+        ];
+        let (size, output) = downsample_texels(Vector3::new(2, 2, 1), &data);
+        assert_eq!(size, Vector3::new(1, 1, 1));
+        assert_eq!(
+            output,
+            vec![Rgba::new(1. / 3., 1. / 3., 1. / 3., 1.0).to_srgb8()]
+        );
+    }
+
+    #[test]
+    fn downsample_odd_size() {
+        let data = [[10, 20, 30, 255]; 3];
+        let (size, output) = downsample_texels(Vector3::new(3, 1, 1), &data);
+        assert_eq!(size, Vector3::new(2, 1, 1));
+        assert_eq!(output, vec![[10, 20, 30, 255]; 2]);
+    }
+}
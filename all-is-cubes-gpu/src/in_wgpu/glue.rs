@@ -84,7 +84,8 @@ pub(crate) fn create_wgsl_module_from_reloadable(
     })
 }
 
-/// Write to a texture, with the region written specified by a [`GridAab`].
+/// Write to a texture, with the region written specified by a [`GridAab`] in the
+/// coordinates of mip level `mip_level`.
 ///
 /// `T` must be a single texel of the appropriate format.
 ///
@@ -92,6 +93,7 @@ pub(crate) fn create_wgsl_module_from_reloadable(
 pub fn write_texture_by_aab<T: Pod>(
     queue: &wgpu::Queue,
     texture: &wgpu::Texture,
+    mip_level: u32,
     region: GridAab,
     data: &[T],
 ) {
@@ -105,7 +107,7 @@ pub fn write_texture_by_aab<T: Pod>(
     queue.write_texture(
         wgpu::ImageCopyTexture {
             texture,
-            mip_level: 0,
+            mip_level,
             origin: point_to_origin(region.lower_bounds()),
             aspect: wgpu::TextureAspect::All,
         },
@@ -89,10 +89,17 @@ impl Pipelines {
                         ty: wgpu::BindingType::Texture {
                             multisampled: false,
                             view_dimension: wgpu::TextureViewDimension::D3,
-                            sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
                         },
                         count: None,
                     },
+                    // Block color texture sampler
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
                     // Space light texture
                     wgpu::BindGroupLayoutEntry {
                         binding: 2,
@@ -187,9 +194,9 @@ impl Pipelines {
                     module: &shader,
                     entry_point: match current_graphics_options.transparency {
                         TransparencyOption::Volumetric => "block_fragment_transparent_volumetric",
-                        TransparencyOption::Surface | TransparencyOption::Threshold(_) => {
-                            "block_fragment_transparent_surface"
-                        }
+                        TransparencyOption::Surface
+                        | TransparencyOption::Threshold(_)
+                        | TransparencyOption::OpaqueOnly => "block_fragment_transparent_surface",
                         ref t => panic!("unimplemented transparency option {t:?}"),
                     },
                     targets: &[Some(wgpu::ColorTargetState {
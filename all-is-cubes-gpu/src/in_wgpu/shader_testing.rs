@@ -122,8 +122,9 @@ where
     });
 
     // Placeholder space data for the bind group
-    let texture_allocator = in_wgpu::block_texture::AtlasAllocator::new("shader test space");
-    texture_allocator.flush::<time::NoTime>(&device, &queue);
+    let texture_allocator =
+        in_wgpu::block_texture::AtlasAllocator::new("shader test space", wgpu::FilterMode::Nearest);
+    texture_allocator.flush::<time::NoTime>(&device, &queue, false);
     let space_bind_group = in_wgpu::space::create_space_bind_group(
         "shader test space",
         &device,
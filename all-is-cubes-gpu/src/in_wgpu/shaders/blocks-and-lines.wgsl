@@ -39,6 +39,7 @@ struct WgpuLinesVertex {
 
 // This group is named space_texture_bind_group_layout in the code.
 @group(1) @binding(0) var block_texture: texture_3d<f32>;
+@group(1) @binding(1) var block_sampler: sampler;
 @group(1) @binding(2) var light_texture: texture_3d<u32>;
 
 // --- Fog computation --------------------------------------------------------
@@ -388,9 +389,41 @@ fn lighting(in: BlockFragmentInput) -> vec3<f32> {
 
 // Get the vertex color or texel value to display
 fn get_diffuse_color(in: BlockFragmentInput) -> vec4<f32> {
+    // Choose a mip level from how many texels this fragment covers.
+    // This must be computed outside the conditional because derivatives
+    // require uniform control flow. Levels beyond those the texture has (for example,
+    // when mipmaps are disabled) are clamped by the sampler, so we do not need to
+    // check that here (which would require `textureNumLevels()`, unsupported on GLES).
+    let texel_footprint = max(
+        length(dpdx(in.color_or_texture.xyz)),
+        length(dpdy(in.color_or_texture.xyz))
+    );
+    // Don't use mip levels coarser than the texture's region, because those
+    // texels may be shared with other tiles. (This is conservative since the clamp
+    // region is one face of the tile, which may be smaller than the whole tile.)
+    let region_size = in.clamp_max - in.clamp_min + vec3<f32>(1.0);
+    let max_region_level = log2(max(region_size.x, max(region_size.y, region_size.z)));
+    let mip_level = i32(max(
+        floor(min(log2(max(texel_footprint, 1.0)), max_region_level)),
+        0.0
+    ));
+
     if in.color_or_texture[3] < -0.5 {
         // Texture coordinates.
-        let texcoord: vec3<f32> = clamp(in.color_or_texture.xyz, in.clamp_min, in.clamp_max);
+        //
+        // clamp_min and clamp_max are the centers of the face's edge texels at level 0.
+        // A texel of the chosen mip level spans `level_scale` level-0 texels, so inset
+        // the clamp by the remaining distance to that texel's center. (Tiles are aligned
+        // to their size, so level texels within the region never straddle other tiles.)
+        // If the region is narrower than one level texel on some axis, use its center.
+        let level_scale = exp2(f32(mip_level));
+        let level_inset = vec3<f32>((level_scale - 1.0) * 0.5);
+        let clamp_center = (in.clamp_min + in.clamp_max) * 0.5;
+        let texcoord: vec3<f32> = clamp(
+            in.color_or_texture.xyz,
+            min(in.clamp_min + level_inset, clamp_center),
+            max(in.clamp_max - level_inset, clamp_center)
+        );
         
         // If activated, this code will produce an “x-ray” view of all textured surfaces
         // by cutting out all but the clamped border. Other similar changes could be used to
@@ -400,11 +433,25 @@ fn get_diffuse_color(in: BlockFragmentInput) -> vec4<f32> {
         //     discard;
         // }
         
-        // Read texture; using textureLoad because our coordinates are in units of texels
-        // and we don't want any filtering or wrapping, so using a sampler gives no benefit.
-        // Note that coordinate rounding towards zero is effectively floor() since the
-        // input is nonnegative.
-        return textureLoad(block_texture, vec3<i32>(texcoord), 0);
+        // The clamp keeps in-plane coordinates at or inside the centers of the face's
+        // edge texels of the mip level, so linear filtering cannot blend in other tiles.
+        // Along the axis perpendicular to the face, the coordinate is exactly on a texel
+        // boundary (clamp_min == clamp_max); move it to the center of the texel that
+        // rounding down would select, so that we read one layer rather than blending two.
+        let sample_point = select(
+            texcoord,
+            floor(texcoord) + vec3<f32>(0.5),
+            in.clamp_min == in.clamp_max
+        );
+
+        // Our coordinates are in units of level-0 texels, so normalize them.
+        // The sampler's filter mode determines whether adjacent texels are blended.
+        return textureSampleLevel(
+            block_texture,
+            block_sampler,
+            sample_point / vec3<f32>(textureDimensions(block_texture, 0)),
+            f32(mip_level)
+        );
     } else {
         // Solid color.
         return in.color_or_texture;
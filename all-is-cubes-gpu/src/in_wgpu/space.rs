@@ -154,6 +154,12 @@ impl<I: time::Instant> SpaceRenderer<I> {
         *light_texture = SpaceLightTexture::new(space_label, device, space_borrowed.bounds());
     }
 
+    /// Regenerate the meshes using block texture tiles, because the tiles have moved.
+    /// See [`ChunkedSpaceMesh::textures_moved()`].
+    pub(crate) fn textures_moved(&mut self) {
+        self.csm.textures_moved();
+    }
+
     /// Update renderer internal state from the given [`Camera`] and referenced [`Space`],
     /// so that the next rendered meshes will be up to date (or as far up to date as the
     /// given [`deadline`] permits).
@@ -242,7 +248,9 @@ impl<I: time::Instant> SpaceRenderer<I> {
         // Flush all texture updates to GPU.
         // This must happen after `csm.update_blocks_and_some_chunks` so that the newly
         // generated meshes have the texels they expect.
-        let (block_texture_view, texture_info) = self.block_texture.flush::<I>(device, queue);
+        let (block_texture_view, texture_info) =
+            self.block_texture
+                .flush::<I>(device, queue, camera.options().mipmap_block_textures);
 
         // Update space bind group if needed.
         self.space_bind_group.get_or_insert(
@@ -520,6 +528,10 @@ pub(in crate::in_wgpu) fn create_space_bind_group(
                     &block_texture.current_texture_view().unwrap(),
                 ),
             },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(&block_texture.sampler(device)),
+            },
             wgpu::BindGroupEntry {
                 binding: 2,
                 resource: wgpu::BindingResource::TextureView(&light_texture.texture_view),
@@ -686,6 +698,7 @@ impl SpaceLightTexture {
         write_texture_by_aab(
             queue,
             &self.texture,
+            0,
             region.translate(self.light_lookup_offset()),
             &data,
         );
@@ -183,7 +183,46 @@ fn dynamic_benches(c: &mut Criterion) {
         );
     });
 
-    // TODO: Add a test for updates past the initial one
+    g.bench_function("remesh-static", |b| {
+        // Re-mesh a chunk after a change to one cube, with the rest of the chunk unchanged,
+        // as happens repeatedly when editing a mostly static world.
+        let space_ref = URef::new_pending(Name::Pending, half_space(Block::from(Rgba::WHITE)));
+        let mut csm: dynamic::ChunkedSpaceMesh<
+            (),
+            BlockVertex<TestPoint>,
+            TestAllocator,
+            std::time::Instant,
+            16,
+        > = dynamic::ChunkedSpaceMesh::new(space_ref.clone(), true);
+        let tex = TestAllocator::new();
+        csm.update_blocks_and_some_chunks(&camera, &tex, time::DeadlineStd::Whenever, |_| {});
+        let mut toggle = false;
+        b.iter(|| {
+            toggle = !toggle;
+            space_ref
+                .try_modify(|space| {
+                    space.set(
+                        [8, 15, 8],
+                        if toggle {
+                            Block::from(Rgba::WHITE)
+                        } else {
+                            AIR
+                        },
+                    )
+                })
+                .unwrap()
+                .unwrap();
+            let info = csm.update_blocks_and_some_chunks(
+                &camera,
+                &tex,
+                time::DeadlineStd::Whenever,
+                |_| {},
+            );
+            assert_eq!(info.flaws, Flaws::empty());
+        });
+    });
+
+    // TODO: Add a test for updates involving changed blocks
 }
 
 // --- End of benches, beginning helpers ---
@@ -15,7 +15,8 @@ use all_is_cubes::space::Space;
 
 use crate::texture;
 use crate::{
-    push_quad, BlockVertex, GreedyMesher, IndexVec, MeshOptions, QuadColoring, QuadTransform,
+    push_quad, BlockVertex, GreedyMesher, IndexVec, MeshOptions, Outline, QuadColoring,
+    QuadTransform,
 };
 
 /// Part of the triangle mesh calculated for a [`Block`], stored in a [`BlockMesh`] keyed
@@ -287,6 +288,13 @@ where
         } else {
             &block.voxels
         };
+        let tmp_outlined_voxels;
+        let voxels = if let Some(outline) = &options.outline {
+            tmp_outlined_voxels = apply_outline(voxels, outline);
+            &tmp_outlined_voxels
+        } else {
+            voxels
+        };
         match *voxels {
             Evoxels::One(Evoxel {
                 color: block_color, ..
@@ -329,7 +337,18 @@ where
                     return;
                 }
 
-                let block_resolution = GridCoordinate::from(resolution);
+                let block_resolution = resolution.to_grid();
+
+                // Skip scanning the empty margins of the block, if we know where they are.
+                // (Outlining may add voxels outside of the block's occupied bounds.)
+                let scan_bounds = if options.outline.is_none() {
+                    block
+                        .occupied_bounds()
+                        .and_then(|occupied| occupied.intersection(voxels_array.bounds()))
+                        .unwrap_or(voxels_array.bounds())
+                } else {
+                    voxels_array.bounds()
+                };
 
                 // Construct empty output to mutate.
                 for (_, face_mesh) in self.face_vertices.iter_mut() {
@@ -351,17 +370,15 @@ where
                     let quad_transform = QuadTransform::new(face, resolution);
                     let face_mesh = &mut self.face_vertices[face];
 
-                    // Rotate the voxel array's extent into our local coordinate system, so we can find
+                    // Rotate the scanned extent into our local coordinate system, so we can find
                     // out what range to iterate over.
-                    let rotated_voxel_range = voxels_array
-                        .bounds()
-                        .transform(voxel_transform.inverse())
-                        .unwrap();
+                    let rotated_voxel_range =
+                        scan_bounds.transform(voxel_transform.inverse()).unwrap();
 
                     // Check the case where the block's voxels don't meet its front face, or don't fill that face.
                     if !rotated_voxel_range.z_range().contains(&0)
-                        || rotated_voxel_range.x_range() != (0..block_resolution)
-                        || rotated_voxel_range.y_range() != (0..block_resolution)
+                        || rotated_voxel_range.x_range() != resolution.range()
+                        || rotated_voxel_range.y_range() != resolution.range()
                     {
                         face_mesh.fully_opaque = false;
                     }
@@ -552,7 +569,9 @@ where
 
                 // TODO: avoid allocation
                 self.textures_used = texture_if_needed.into_iter().collect();
-                self.voxel_opacity_mask = if used_any_vertex_colors {
+                // If we drew an outline, then the texture is not a copy of the block's
+                // voxels, so try_update_texture_only() would be incorrect.
+                self.voxel_opacity_mask = if used_any_vertex_colors || options.outline.is_some() {
                     None
                 } else {
                     block.voxel_opacity_mask.clone()
@@ -580,6 +599,41 @@ impl<V, T> Default for BlockMesh<V, T> {
     }
 }
 
+/// Returns a copy of `voxels` recolored to draw `outline`,
+/// at a resolution of at least [`Outline::resolution`].
+fn apply_outline(voxels: &Evoxels, outline: &Outline) -> Evoxels {
+    let original_resolution = voxels.resolution();
+    let resolution = original_resolution.max(outline.resolution);
+    // Number of output voxels per input voxel, along each axis.
+    let scale = GridCoordinate::from(resolution) / GridCoordinate::from(original_resolution);
+    let width = GridCoordinate::from(outline.width) * GridCoordinate::from(resolution)
+        / GridCoordinate::from(outline.resolution);
+    let max = GridCoordinate::from(resolution) - 1;
+    let outline_rgb = outline.color.to_rgb();
+    let outline_alpha = outline.color.clamp().alpha().into_inner();
+
+    Evoxels::Many(
+        resolution,
+        GridArray::from_fn(voxels.bounds().multiply(scale), |cube| {
+            let mut voxel = voxels
+                .get(cube.map(|c| c.div_euclid(scale)))
+                .unwrap_or(Evoxel::AIR);
+
+            // Distance from this voxel to the nearest face of the block, along each axis.
+            let mut distances = [cube.x, cube.y, cube.z].map(|c| c.min(max - c));
+            distances.sort_unstable();
+            // A voxel is part of the outline if it is on one face of the block and
+            // close enough to another face to be near their shared edge.
+            if distances[0] == 0 && distances[1] < width && !voxel.color.fully_transparent() {
+                let rgb =
+                    voxel.color.to_rgb() * (1.0 - outline_alpha) + outline_rgb * outline_alpha;
+                voxel.color = rgb.with_alpha(voxel.color.alpha());
+            }
+            voxel
+        }),
+    )
+}
+
 /// Computes [`BlockMeshes`] for blocks currently present in a [`Space`].
 /// Pass the result to [`SpaceMesh::new()`](super::SpaceMesh::new) to use it.
 ///
@@ -613,7 +667,7 @@ mod tests {
 
     use super::*;
     use crate::tests::test_block_mesh;
-    use crate::texture::{NoTexture, NoTextures};
+    use crate::texture::{NoTexture, NoTextures, TestAllocator, TestPoint, TestTile};
     use crate::Coloring;
     use all_is_cubes::block::{Block, AIR};
     use all_is_cubes::camera::GraphicsOptions;
@@ -676,4 +730,33 @@ mod tests {
         // Check what we actually care about: given the vertex colors we must not have a mask.
         assert!(mesh.voxel_opacity_mask.is_none());
     }
+
+    #[test]
+    fn outline_borders_uniform_face() {
+        let color = Rgba::new(0.0, 1.0, 0.5, 1.0);
+        let ev = Block::from(color).evaluate().unwrap();
+        let outline = Outline {
+            color: Rgba::BLACK,
+            resolution: Resolution::R4,
+            width: 1,
+        };
+
+        // Check the voxels: border darkened, interior of the face unchanged.
+        let outlined = apply_outline(&ev.voxels, &outline);
+        assert_eq!(outlined.resolution(), Resolution::R4);
+        assert_eq!(outlined[Cube::new(0, 0, 3)].color, Rgba::BLACK);
+        assert_eq!(outlined[Cube::new(0, 2, 3)].color, Rgba::BLACK);
+        assert_eq!(outlined[Cube::new(1, 2, 3)].color, color);
+        assert_eq!(outlined[Cube::new(2, 1, 3)].color, color);
+
+        // Without the outline, the atom needs no texture; with it, it does.
+        let tex = TestAllocator::new();
+        let mut options = MeshOptions::new(&GraphicsOptions::default());
+        let _: BlockMesh<BlockVertex<TestPoint>, TestTile> = BlockMesh::new(&ev, &tex, &options);
+        assert_eq!(tex.count_allocated(), 0);
+        options.set_outline(Some(outline));
+        let mesh: BlockMesh<BlockVertex<TestPoint>, TestTile> = BlockMesh::new(&ev, &tex, &options);
+        assert_eq!(tex.count_allocated(), 1);
+        assert!(mesh.voxel_opacity_mask.is_none());
+    }
 }
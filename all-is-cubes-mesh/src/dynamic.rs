@@ -1,7 +1,7 @@
 //! Updating meshes as their source data changes.
 
 mod blocks;
-use blocks::{BlockMeshVersion, VersionedBlockMeshes};
+use blocks::{BlockMeshVersion, LowDetail, VersionedBlockMeshes};
 
 mod chunk;
 pub use chunk::ChunkMesh;
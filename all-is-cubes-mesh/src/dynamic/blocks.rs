@@ -2,19 +2,23 @@ use std::num::NonZeroU32;
 
 use fnv::FnvHashSet;
 
-use all_is_cubes::block::{EvaluatedBlock, Resolution};
+use all_is_cubes::block::{AnimationChange, EvaluatedBlock, Resolution};
 use all_is_cubes::space::{BlockIndex, Space};
 use all_is_cubes::time;
 use all_is_cubes::util::{CustomFormat as _, StatusText, TimeStats};
 
 use crate::texture;
-use crate::{BlockMesh, GetBlockMesh, GfxVertex, MeshOptions, SpaceMesh};
+use crate::{BlockMesh, GetBlockMesh, GfxVertex, MeshOptions, OpacityCache, SpaceMesh};
 
 #[derive(Debug)]
 pub(crate) struct VersionedBlockMeshes<D, Vert, Tile> {
     /// Indices of this vector are block IDs in the Space.
     pub(crate) meshes: Vec<VersionedBlockMesh<D, Vert, Tile>>,
 
+    /// Which faces of each of `meshes` are fully opaque, for use in chunk meshing.
+    /// Always updated together with `meshes`.
+    pub(crate) opacity: OpacityCache,
+
     last_version_counter: NonZeroU32,
 }
 
@@ -22,6 +26,7 @@ impl<D, Vert, Tile> VersionedBlockMeshes<D, Vert, Tile> {
     pub fn new() -> Self {
         Self {
             meshes: Vec::new(),
+            opacity: OpacityCache::default(),
             last_version_counter: NonZeroU32::new(u32::MAX).unwrap(),
         }
     }
@@ -31,6 +36,7 @@ impl<D, Vert, Tile> VersionedBlockMeshes<D, Vert, Tile> {
     /// extra data.
     pub fn clear(&mut self) {
         self.meshes.clear();
+        self.opacity.clear();
     }
 }
 
@@ -46,13 +52,19 @@ where
     /// always equal `space.block_data().len()`. It may not be fully updated yet, but
     /// it will be the correct length.
     ///
-    /// Relies on the caller to check if `mesh_options` has changed and fill `todo`.
+    /// If `low_detail` is true, low-detail meshes are also computed, for chunks drawn
+    /// at reduced detail; otherwise they are skipped.
+    ///
+    /// Relies on the caller to check if `mesh_options` or `low_detail` has changed and
+    /// fill `todo`.
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn update<A, F, I>(
         &mut self,
         todo: &mut FnvHashSet<BlockIndex>,
         space: &Space,
         block_texture_allocator: &A,
         mesh_options: &MeshOptions,
+        low_detail: bool,
         deadline: time::Deadline<I>,
         mut render_data_updater: F,
     ) -> TimeStats
@@ -66,12 +78,7 @@ where
             return TimeStats::default();
         }
 
-        // Bump version number.
-        self.last_version_counter = match self.last_version_counter.get().checked_add(1) {
-            None => NonZeroU32::MIN,
-            Some(n) => NonZeroU32::new(n).unwrap(),
-        };
-        let current_version_number = BlockMeshVersion::Numbered(self.last_version_counter);
+        let current_version_number = self.next_version();
 
         let block_data = space.block_data();
 
@@ -81,14 +88,12 @@ where
             let new_len = block_data.len();
             if old_len > new_len {
                 self.meshes.truncate(new_len);
+                self.opacity.truncate(new_len);
             } else {
                 // Increase length, and initialize the new elements.
                 // This must be done quickly, so that we do not have a hiccup when initializing
                 // from a space with many blocks.
 
-                let mut fast_options = mesh_options.clone();
-                fast_options.ignore_voxels = true;
-
                 self.meshes.reserve(new_len);
                 for bd in &block_data[self.meshes.len()..new_len] {
                     let evaluated = bd.evaluated();
@@ -96,13 +101,14 @@ where
                         .push(if evaluated.resolution() > Resolution::R1 {
                             // If the block has voxels, generate a placeholder mesh,
                             // marked as not-ready so it will be replaced eventually.
+                            // The placeholder is also the final low-detail mesh.
+                            let mesh =
+                                low_detail_mesh(evaluated, block_texture_allocator, mesh_options);
                             VersionedBlockMesh {
-                                mesh: BlockMesh::new(
-                                    evaluated,
-                                    block_texture_allocator,
-                                    &fast_options,
-                                ),
+                                low_detail_mesh: low_detail.then(|| mesh.clone()),
+                                mesh,
                                 version: BlockMeshVersion::NotReady,
+                                low_detail_version: current_version_number,
                                 instance_data: Default::default(),
                             }
                         } else {
@@ -114,10 +120,15 @@ where
                                     block_texture_allocator,
                                     mesh_options,
                                 ),
+                                low_detail_mesh: None,
                                 version: current_version_number,
+                                low_detail_version: current_version_number,
                                 instance_data: Default::default(),
                             }
                         });
+                    let index = self.meshes.len() - 1;
+                    self.opacity
+                        .set(index.try_into().unwrap(), &self.meshes[index].mesh);
                 }
             }
         }
@@ -141,11 +152,33 @@ where
             //     None => 1,
             // };
 
-            if current_mesh_entry
-                .mesh
-                .try_update_texture_only(new_evaluated_block)
+            // If the block declares that its shape is expected to change, then don't bother
+            // trying to reuse the existing vertices; otherwise, try it first, since it is
+            // much cheaper than a full rebuild when only colors changed.
+            let expect_shape_change = new_evaluated_block.attributes.animation_hint.redefinition
+                == AnimationChange::Shape;
+
+            // If the block has no voxels, the low-detail mesh is identical to the full
+            // mesh, so don't compute it twice.
+            let new_low_detail_mesh =
+                (low_detail && new_evaluated_block.resolution() > Resolution::R1).then(|| {
+                    low_detail_mesh(new_evaluated_block, block_texture_allocator, mesh_options)
+                });
+
+            if !expect_shape_change
+                && current_mesh_entry
+                    .mesh
+                    .try_update_texture_only(new_evaluated_block)
             {
-                // Updated the texture in-place. No need for mesh updates.
+                // Updated the texture in-place. No need for mesh updates, unless the
+                // block's overall color, and hence its low-detail mesh, changed;
+                // that only affects chunks drawn at low detail.
+                if new_low_detail_mesh.is_some()
+                    && new_low_detail_mesh != current_mesh_entry.low_detail_mesh
+                {
+                    current_mesh_entry.low_detail_mesh = new_low_detail_mesh;
+                    current_mesh_entry.low_detail_version = current_version_number;
+                }
             } else {
                 // Compute a new mesh.
                 // TODO: Try using BlockMesh::compute() to reuse allocations.
@@ -162,14 +195,26 @@ where
                 // (If they were, we'd need to consider what we want to do about stale chunks with
                 // updated texture tiles, which might have geometry gaps or otherwise be obviously
                 // inconsistent.)
-                if new_block_mesh != current_mesh_entry.mesh
-                    || current_mesh_entry.version == BlockMeshVersion::NotReady
-                {
+                let mesh_changed = new_block_mesh != current_mesh_entry.mesh
+                    || current_mesh_entry.version == BlockMeshVersion::NotReady;
+                let low_detail_changed = new_low_detail_mesh != current_mesh_entry.low_detail_mesh;
+                if mesh_changed || low_detail_changed {
                     *current_mesh_entry = VersionedBlockMesh {
                         mesh: new_block_mesh,
-                        version: current_version_number,
+                        low_detail_mesh: new_low_detail_mesh,
+                        version: if mesh_changed {
+                            current_version_number
+                        } else {
+                            current_mesh_entry.version
+                        },
+                        low_detail_version: if low_detail_changed {
+                            current_version_number
+                        } else {
+                            current_mesh_entry.low_detail_version
+                        },
                         instance_data: Default::default(), // TODO: reuse old render data
                     };
+                    self.opacity.set(index, &current_mesh_entry.mesh);
 
                     // TODO(instancing): Enable this for all blocks that we might want to draw
                     // instances of.
@@ -205,6 +250,55 @@ where
 
         stats
     }
+
+    /// Recompute all block meshes which use textures, because their texture tiles have
+    /// moved and so their texture coordinates are wrong.
+    ///
+    /// The existing tiles are reused rather than allocating new ones, so their texels
+    /// need not be written again.
+    /// Meshes which change are given a new version, so chunks using them become stale.
+    pub(crate) fn remesh_reusing_textures<A>(
+        &mut self,
+        space: &Space,
+        block_texture_allocator: &A,
+        mesh_options: &MeshOptions,
+    ) where
+        A: texture::Allocator<Tile = Tile>,
+    {
+        let current_version_number = self.next_version();
+        let entries = self.meshes.iter_mut().zip(space.block_data()).enumerate();
+        for (index, (entry, bd)) in entries {
+            // Placeholder and low-detail meshes are a single color and have no textures.
+            if entry.version == BlockMeshVersion::NotReady || entry.mesh.textures().is_empty() {
+                continue;
+            }
+            let new_block_mesh = BlockMesh::new(
+                bd.evaluated(),
+                &texture::ReuseAllocator {
+                    tile: entry.mesh.textures().first().cloned(),
+                    allocator: block_texture_allocator,
+                },
+                mesh_options,
+            );
+            if new_block_mesh != entry.mesh {
+                entry.mesh = new_block_mesh;
+                entry.version = current_version_number;
+                self.opacity
+                    .set(BlockIndex::try_from(index).unwrap(), &entry.mesh);
+            }
+        }
+    }
+}
+
+impl<D, Vert, Tile> VersionedBlockMeshes<D, Vert, Tile> {
+    /// Bump the version number, and return the new version for meshes computed now.
+    fn next_version(&mut self) -> BlockMeshVersion {
+        self.last_version_counter = match self.last_version_counter.get().checked_add(1) {
+            None => NonZeroU32::MIN,
+            Some(n) => NonZeroU32::new(n).unwrap(),
+        };
+        BlockMeshVersion::Numbered(self.last_version_counter)
+    }
 }
 
 impl<D, Vert, Tile> Default for VersionedBlockMeshes<D, Vert, Tile> {
@@ -224,15 +318,56 @@ impl<'a, D, Vert: 'static, Tile: 'static> GetBlockMesh<'a, Vert, Tile>
     }
 }
 
+/// [`GetBlockMesh`] implementation which provides the
+/// [`VersionedBlockMesh::low_detail_mesh`]es of a [`VersionedBlockMeshes`].
+pub(crate) struct LowDetail<'a, D, Vert, Tile>(pub &'a VersionedBlockMeshes<D, Vert, Tile>);
+
+impl<'a, D, Vert: 'static, Tile: 'static> GetBlockMesh<'a, Vert, Tile>
+    for LowDetail<'a, D, Vert, Tile>
+{
+    fn get_block_mesh(&mut self, index: BlockIndex) -> &'a BlockMesh<Vert, Tile> {
+        self.0
+            .meshes
+            .get(usize::from(index))
+            .map(|vbm| vbm.mesh_for(true))
+            .unwrap_or(BlockMesh::<Vert, Tile>::EMPTY_REF)
+    }
+}
+
+/// Computes a mesh of `block` as a single cube of its overall color, ignoring its voxels.
+/// This is used both as a quick placeholder and for drawing distant chunks.
+fn low_detail_mesh<Vert, A>(
+    block: &EvaluatedBlock,
+    block_texture_allocator: &A,
+    mesh_options: &MeshOptions,
+) -> BlockMesh<Vert, A::Tile>
+where
+    Vert: GfxVertex<TexPoint = <A::Tile as texture::Tile>::Point>,
+    A: texture::Allocator,
+{
+    let mut options = mesh_options.clone();
+    options.ignore_voxels = true;
+    BlockMesh::new(block, block_texture_allocator, &options)
+}
+
 /// Entry in [`VersionedBlockMeshes`].
 #[derive(Debug)]
 pub(crate) struct VersionedBlockMesh<D, Vert, Tile> {
     pub(crate) mesh: BlockMesh<Vert, Tile>,
 
+    /// Mesh of the block as a single cube of its overall color, used for chunks drawn at
+    /// reduced detail. [`None`] if it would be identical to `mesh` because the block has
+    /// no voxels, or if low-detail meshes are not being computed.
+    pub(crate) low_detail_mesh: Option<BlockMesh<Vert, Tile>>,
+
     /// Version ID used to track whether chunks have stale block meshes (ones that don't
     /// match the current definition of that block-index in the space).
     pub(crate) version: BlockMeshVersion,
 
+    /// Version ID of `low_detail_mesh`, tracked separately so that changes which affect
+    /// only the low-detail mesh do not make full-detail chunks stale, and vice versa.
+    pub(crate) low_detail_version: BlockMeshVersion,
+
     /// Arbitrary data used for rendering the block in standalone/instanced form
     /// (not part of a larger mesh).
     ///
@@ -240,6 +375,24 @@ pub(crate) struct VersionedBlockMesh<D, Vert, Tile> {
     pub(crate) instance_data: (crate::MeshMeta<Tile>, D),
 }
 
+impl<D, Vert, Tile> VersionedBlockMesh<D, Vert, Tile> {
+    /// Returns the mesh to use for chunks drawn at full or low detail.
+    pub(crate) fn mesh_for(&self, low_detail: bool) -> &BlockMesh<Vert, Tile> {
+        match &self.low_detail_mesh {
+            Some(low_detail_mesh) if low_detail => low_detail_mesh,
+            _ => &self.mesh,
+        }
+    }
+
+    /// Returns the version of [`Self::mesh_for()`]`(low_detail)`.
+    pub(crate) fn version_for(&self, low_detail: bool) -> BlockMeshVersion {
+        match &self.low_detail_mesh {
+            Some(_) if low_detail => self.low_detail_version,
+            _ => self.version,
+        }
+    }
+}
+
 /// Together with a [`BlockIndex`], uniquely identifies a block mesh.
 /// Used to determine when chunk meshes need updating.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -252,3 +405,137 @@ pub(crate) enum BlockMeshVersion {
     /// in the course of a single batch of updates unless we're perpetually behind.
     Numbered(NonZeroU32),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::texture::TestAllocator;
+    use crate::BlockVertex;
+    use all_is_cubes::block::{AnimationHint, Block, Resolution::R2};
+    use all_is_cubes::camera::GraphicsOptions;
+    use all_is_cubes::math::Rgba;
+    use all_is_cubes::universe::Universe;
+
+    /// Result of [`recolor()`].
+    struct Recolored {
+        /// Number of textures the second update allocated.
+        textures_allocated: usize,
+        /// Whether the second update changed the full-detail mesh version.
+        version_changed: bool,
+        /// Whether the second update changed the low-detail mesh version.
+        low_detail_version_changed: bool,
+        /// Whether a low-detail mesh was computed.
+        has_low_detail_mesh: bool,
+    }
+
+    /// Meshes a space containing a voxel block of the first color, then a space containing
+    /// the same block but with the second color, and reports what the second update did.
+    fn recolor(change: AnimationChange, low_detail: bool) -> Recolored {
+        let mut universe = Universe::new();
+        let mut hint = AnimationHint::UNCHANGING;
+        hint.redefinition = change;
+        let mut make_space = |color: Rgba| {
+            let block = Block::builder()
+                .voxels_fn(&mut universe, R2, |_| Block::from(color))
+                .unwrap()
+                .animation_hint(hint)
+                .build();
+            let mut space = Space::empty_positive(1, 1, 1);
+            space.set([0, 0, 0], block).unwrap();
+            space
+        };
+        let space_1 = make_space(Rgba::new(1.0, 0.0, 0.0, 1.0));
+        let space_2 = make_space(Rgba::new(0.0, 0.0, 1.0, 1.0));
+
+        let allocator = TestAllocator::new();
+        let options = MeshOptions::new(&GraphicsOptions::default());
+        let mut meshes = VersionedBlockMeshes::<(), BlockVertex<_>, _>::new();
+        let mut update = |space: &Space| {
+            meshes.update(
+                &mut (0..space.block_data().len() as BlockIndex).collect(),
+                space,
+                &allocator,
+                &options,
+                low_detail,
+                time::DeadlineStd::Whenever,
+                |_| {},
+            );
+            let entry = &meshes.meshes[0];
+            (
+                entry.version,
+                entry.low_detail_version,
+                entry.low_detail_mesh.is_some(),
+            )
+        };
+
+        let (version_1, low_detail_version_1, _) = update(&space_1);
+        let allocated_before = allocator.count_allocated();
+        let (version_2, low_detail_version_2, has_low_detail_mesh) = update(&space_2);
+        Recolored {
+            textures_allocated: allocator.count_allocated() - allocated_before,
+            version_changed: version_1 != version_2,
+            low_detail_version_changed: low_detail_version_1 != low_detail_version_2,
+            has_low_detail_mesh,
+        }
+    }
+
+    #[test]
+    fn color_animation_hint_updates_texture_only() {
+        let result = recolor(AnimationChange::ColorSameCategory, false);
+        assert_eq!(result.textures_allocated, 0);
+        assert!(!result.version_changed);
+        assert!(!result.has_low_detail_mesh);
+    }
+
+    /// With LOD on, a color change updates the low-detail mesh without making
+    /// full-detail chunks stale.
+    #[test]
+    fn color_animation_hint_updates_low_detail_only() {
+        let result = recolor(AnimationChange::ColorSameCategory, true);
+        assert_eq!(result.textures_allocated, 0);
+        assert!(!result.version_changed);
+        assert!(result.low_detail_version_changed);
+        assert!(result.has_low_detail_mesh);
+    }
+
+    #[test]
+    fn shape_animation_hint_rebuilds_mesh() {
+        assert_ne!(recolor(AnimationChange::Shape, false).textures_allocated, 0);
+    }
+
+    #[test]
+    fn remesh_reusing_textures_does_not_allocate() {
+        let mut universe = Universe::new();
+        let block = Block::builder()
+            .voxels_fn(&mut universe, R2, |cube| {
+                Block::from(Rgba::new(cube.x as f32, 0.0, 0.0, 1.0))
+            })
+            .unwrap()
+            .build();
+        let mut space = Space::empty_positive(1, 1, 1);
+        space.set([0, 0, 0], block).unwrap();
+
+        let allocator = TestAllocator::new();
+        let options = MeshOptions::new(&GraphicsOptions::default());
+        let mut meshes = VersionedBlockMeshes::<(), BlockVertex<_>, _>::new();
+        meshes.update(
+            &mut (0..space.block_data().len() as BlockIndex).collect(),
+            &space,
+            &allocator,
+            &options,
+            false,
+            time::DeadlineStd::Whenever,
+            |_| {},
+        );
+        let index = usize::from(space.get_block_index([0, 0, 0]).unwrap());
+        assert_eq!(meshes.meshes[index].mesh.textures().len(), 1);
+        let version = meshes.meshes[index].version;
+        let allocated_before = allocator.count_allocated();
+
+        meshes.remesh_reusing_textures(&space, &allocator, &options);
+
+        assert_eq!(allocator.count_allocated(), allocated_before);
+        // The texture coordinates of the test allocator's tiles never change.
+        assert_eq!(meshes.meshes[index].version, version);
+    }
+}
@@ -19,6 +19,10 @@ where
     mesh: SpaceMesh<Vert, Tex::Tile>,
     block_dependencies: Vec<(BlockIndex, dynamic::BlockMeshVersion)>,
 
+    /// Whether the mesh was computed from low-detail block meshes,
+    /// as per [`GraphicsOptions::lod_distance`](all_is_cubes::camera::GraphicsOptions::lod_distance).
+    low_detail: bool,
+
     /// Per-chunk data the owner of the [`ChunkedSpaceMesh`]
     /// may use for whatever purpose suits it, such as handles to GPU buffers.
     pub render_data: D,
@@ -41,6 +45,7 @@ where
             mesh: SpaceMesh::default(),
             render_data: D::default(),
             block_dependencies: Vec::new(),
+            low_detail: false,
             update_debug: false,
         }
     }
@@ -57,6 +62,14 @@ where
         self.position
     }
 
+    /// Returns whether this chunk's mesh depicts each block as a single cube of its
+    /// overall color, because the chunk is farther away than
+    /// [`GraphicsOptions::lod_distance`](all_is_cubes::camera::GraphicsOptions::lod_distance).
+    #[inline]
+    pub fn is_low_detail(&self) -> bool {
+        self.low_detail
+    }
+
     pub(crate) fn borrow_for_update(
         &mut self,
         indices_only: bool,
@@ -75,10 +88,30 @@ where
         space: &Space,
         options: &MeshOptions,
         block_meshes: &dynamic::VersionedBlockMeshes<D, Vert, Tex::Tile>,
+        low_detail: bool,
+        block_index_buffer: &mut Vec<BlockIndex>,
     ) {
         // let compute_start: Option<I> = dynamic::LOG_CHUNK_UPDATES.then(Instant::now);
         let bounds = self.position.bounds();
-        self.mesh.compute(space, bounds, options, block_meshes);
+        if low_detail {
+            // The opacity cache describes the full-detail meshes, so don't use it.
+            self.mesh.compute_with_opacity(
+                space,
+                bounds,
+                options,
+                dynamic::LowDetail(block_meshes),
+                None,
+            );
+        } else {
+            self.mesh.compute_with_opacity(
+                space,
+                bounds,
+                options,
+                block_meshes,
+                Some((&block_meshes.opacity, block_index_buffer)),
+            );
+        }
+        self.low_detail = low_detail;
 
         // Logging
         // TODO: This logging code has been disabled to avoid`std::time::Instant
@@ -108,11 +141,11 @@ where
 
         // Record the block meshes we incorporated into the chunk mesh.
         self.block_dependencies.clear();
-        self.block_dependencies.extend(
-            self.mesh
-                .blocks_used_iter()
-                .map(|index| (index, block_meshes.meshes[usize::from(index)].version)),
-        );
+        self.block_dependencies
+            .extend(self.mesh.blocks_used_iter().map(|index| {
+                let version = block_meshes.meshes[usize::from(index)].version_for(low_detail);
+                (index, version)
+            }));
 
         chunk_todo.recompute_mesh = false;
     }
@@ -145,9 +178,9 @@ where
         &self,
         block_meshes: &dynamic::VersionedBlockMeshes<D, Vert, Tex::Tile>,
     ) -> bool {
-        self.block_dependencies
-            .iter()
-            .any(|&(index, version)| block_meshes.meshes[usize::from(index)].version != version)
+        self.block_dependencies.iter().any(|&(index, version)| {
+            block_meshes.meshes[usize::from(index)].version_for(self.low_detail) != version
+        })
         // Note: We could also check here to avoid recomputing the mesh while we're still
         // working on blocks that the mesh needs,
         // && self.block_dependencies.iter().all(|&(index, _version)| {
@@ -1,5 +1,6 @@
 use std::collections::{hash_map::Entry::*, HashMap, HashSet};
 use std::fmt;
+use std::mem;
 use std::sync::{Arc, Mutex, Weak};
 
 use fnv::{FnvHashMap, FnvHashSet};
@@ -46,6 +47,10 @@ where
     /// in `todo.read().unwrap().chunks`.
     chunks: FnvHashMap<ChunkPos<CHUNK_SIZE>, ChunkMesh<D, Vert, Tex, CHUNK_SIZE>>,
 
+    /// Scratch buffer for block indices, reused by each chunk's mesh computation
+    /// rather than allocating a new one each time.
+    block_index_buffer: Vec<BlockIndex>,
+
     /// Resized as needed upon each [`Self::update_blocks_and_some_chunks()`].
     chunk_chart: ChunkChart<CHUNK_SIZE>,
 
@@ -66,6 +71,15 @@ where
     /// The [`MeshOptions`] specified by the last [`Camera`] provided.
     last_mesh_options: Option<MeshOptions>,
 
+    /// Whether the last [`Camera`] provided had a
+    /// [`GraphicsOptions::lod_distance`](all_is_cubes::camera::GraphicsOptions::lod_distance),
+    /// and hence whether low-detail block meshes are being computed.
+    last_low_detail_enabled: bool,
+
+    /// Whether the texture tiles used by the block meshes have moved since the last update,
+    /// as reported by [`Self::textures_moved()`].
+    textures_moved: bool,
+
     /// Most recent time at which we reset to no data.
     zero_time: I,
     /// Earliest time prior to `zero_time` at which we finished everything in the queues.
@@ -99,11 +113,14 @@ where
             todo: todo_rc,
             block_meshes: dynamic::VersionedBlockMeshes::new(),
             chunks: FnvHashMap::default(),
+            block_index_buffer: Vec::new(),
             chunk_chart: ChunkChart::new(0.0),
             view_chunk: ChunkPos(Cube::new(0, 0, 0)),
             did_not_finish_chunks: true,
             startup_chunks_only: interactive,
             last_mesh_options: None,
+            last_low_detail_enabled: false,
+            textures_moved: false,
             zero_time: I::now(),
             complete_time: None,
         }
@@ -114,6 +131,17 @@ where
         &self.space
     }
 
+    /// Notify this that previously allocated texture tiles have moved, so the meshes,
+    /// which contain their texture coordinates, are wrong.
+    ///
+    /// The next [`Self::update_blocks_and_some_chunks()`] recomputes the block meshes,
+    /// reusing the same tiles, and discards the chunk meshes using them, so that chunks
+    /// it does not finish rebuilding before its deadline are missing rather than drawn
+    /// with the wrong textures.
+    pub fn textures_moved(&mut self) {
+        self.textures_moved = true;
+    }
+
     /// Returns a [`ChunkChart`] for the view distance used by the most recent
     /// [`Self::update_blocks_and_some_chunks`].
     pub fn chunk_chart(&self) -> &ChunkChart<CHUNK_SIZE> {
@@ -182,7 +210,11 @@ where
     /// Recompute meshes of all blocks that need it, and the nearest chunks that need it.
     ///
     /// * `camera`'s view position is used to choose what to update and for depth
-    ///    ordering; its graphics options are used for triangulation and view distance.
+    ///   ordering; its graphics options are used for triangulation and view distance.
+    ///   If [frustum culling](all_is_cubes::camera::GraphicsOptions::use_frustum_culling)
+    ///   is enabled, chunks in view are updated before chunks which are not; but all
+    ///   chunks within the view distance are still kept, so that they are ready if the
+    ///   camera turns. Use [`Self::iter_in_view()`] to skip drawing the latter.
     /// * `deadline` is the approximate time at which this should stop.
     /// * `render_data_updater` is called for every re-meshed or depth-sorted chunk.
     ///
@@ -260,11 +292,17 @@ where
         };
 
         // Check for mesh options changes that would invalidate the meshes.
+        // Turning LOD on or off does too, because low-detail block meshes are only kept
+        // while it is on.
+        let low_detail_enabled = graphics_options.lod_distance.is_some();
         let mesh_options = {
             let current_mesh_options = MeshOptions::new(graphics_options);
-            if Some(&current_mesh_options) != self.last_mesh_options.as_ref() {
+            if Some(&current_mesh_options) != self.last_mesh_options.as_ref()
+                || low_detail_enabled != self.last_low_detail_enabled
+            {
                 todo.all_blocks_and_chunks = true;
                 self.last_mesh_options = Some(current_mesh_options);
+                self.last_low_detail_enabled = low_detail_enabled;
             }
             self.last_mesh_options.as_ref().unwrap()
         };
@@ -291,6 +329,7 @@ where
             space,
             block_texture_allocator,
             mesh_options,
+            low_detail_enabled,
             if self.startup_chunks_only {
                 time::Deadline::Asap
             } else {
@@ -301,6 +340,15 @@ where
         );
         let all_done_with_blocks = todo.blocks.is_empty();
 
+        if mem::take(&mut self.textures_moved) {
+            self.block_meshes
+                .remesh_reusing_textures(space, block_texture_allocator, mesh_options);
+            let block_meshes = &self.block_meshes;
+            self.chunks
+                .retain(|_, chunk| !chunk.stale_blocks(block_meshes));
+            todo.chunks.retain(|pos, _| self.chunks.contains_key(pos));
+        }
+
         // We are now done with todo preparation, and block mesh updates,
         // and can start updating chunk meshes.
 
@@ -327,11 +375,27 @@ where
         let mut chunk_mesh_generation_times = TimeStats::default();
         let mut chunk_mesh_callback_times = TimeStats::default();
         let mut did_not_finish = false;
-        for p in self.chunk_chart.chunks(view_chunk, OctantMask::ALL) {
-            if !chunk_bounds.contains_cube(p.0) {
-                // Chunk not in the Space
-                continue;
-            }
+        let lod_distance_squared = graphics_options
+            .lod_distance
+            .map(|distance| distance.ceil().powi(2) as GridCoordinate);
+        let use_frustum_culling = graphics_options.use_frustum_culling;
+        let in_view = move |p: &ChunkPos<CHUNK_SIZE>| {
+            !use_frustum_culling || camera.aab_in_view(p.bounds().into())
+        };
+        let chunks_in_space = || {
+            self.chunk_chart
+                .chunks(view_chunk, OctantMask::ALL)
+                .filter(|p| chunk_bounds.contains_cube(p.0))
+        };
+        // Update chunks which are in view first, then the rest, so that chunks out of view
+        // don't delay visible ones but are still ready if the camera turns.
+        // Each group is in nearest-first order.
+        let update_order = chunks_in_space()
+            .filter(in_view)
+            .chain(chunks_in_space().filter(|p| !in_view(p)));
+        for p in update_order {
+            let low_detail = lod_distance_squared
+                .is_some_and(|lod| p.min_distance_squared_from(view_chunk) > lod);
 
             let this_chunk_start_time = I::now();
             if deadline < this_chunk_start_time {
@@ -351,6 +415,9 @@ where
                 || matches!(
                     chunk_entry,
                     Occupied(ref oe) if oe.get().stale_blocks(&self.block_meshes))
+                || matches!(
+                    chunk_entry,
+                    Occupied(ref oe) if oe.get().is_low_detail() != low_detail)
             {
                 //let compute_start = I::now();
                 let chunk = chunk_entry.or_insert_with(|| {
@@ -364,6 +431,8 @@ where
                     space,
                     mesh_options,
                     &self.block_meshes,
+                    low_detail,
+                    &mut self.block_index_buffer,
                 );
                 let compute_end_update_start = I::now();
                 render_data_updater(chunk.borrow_for_update(false));
@@ -1,6 +1,6 @@
 use std::sync::{Arc, Mutex};
 
-use all_is_cubes::block::Block;
+use all_is_cubes::block::{Block, Resolution::R4, AIR};
 use all_is_cubes::camera::{Camera, Flaws, GraphicsOptions, TransparencyOption, Viewport};
 use all_is_cubes::cgmath::{EuclideanSpace as _, Point3};
 use all_is_cubes::chunking::ChunkPos;
@@ -11,7 +11,7 @@ use all_is_cubes::space::{Space, SpaceChange, SpaceTransaction};
 use all_is_cubes::universe::{URef, Universe};
 use all_is_cubes::{notnan, rgba_const, time, transaction};
 
-use crate::dynamic;
+use crate::dynamic::{self, MeshLabelImpl};
 use crate::texture::{NoTexture, NoTextures};
 use crate::BlockVertex;
 
@@ -289,6 +289,55 @@ fn drop_chunks_when_moving() {
     assert!(tester.csm.iter_chunks().count() < initial_chunk_count * 3);
 }
 
+/// Chunks in the view frustum are updated before the ones out of view,
+/// and chunks behind the camera are not returned from `iter_in_view()`.
+#[test]
+fn chunks_in_view_first() {
+    let mut tester = CsmTester::new(
+        Space::builder(GridAab::from_lower_upper(
+            [-CHUNK_SIZE * 3, -CHUNK_SIZE, -CHUNK_SIZE * 3],
+            [CHUNK_SIZE * 3, CHUNK_SIZE * 2, CHUNK_SIZE * 3],
+        ))
+        .build(),
+        LARGE_VIEW_DISTANCE,
+    );
+    // The camera looks toward -Z, so chunks with z >= 1 are entirely behind it.
+    tester.move_camera_to([0.5, 0.5, 0.5]);
+    let is_behind = |p: &ChunkPos<CHUNK_SIZE>| p.0.z >= 1;
+    let camera = tester.camera.clone();
+    let in_view = |p: &ChunkPos<CHUNK_SIZE>| camera.aab_in_view(p.bounds().into());
+
+    let mut update_order = Vec::new();
+    tester.update(|u| {
+        if let MeshLabelImpl::Chunk(p) = u.mesh_label.0 {
+            update_order.push(ChunkPos::<CHUNK_SIZE>(Cube::from(GridPoint::from(p))));
+        }
+    });
+
+    // All chunks were updated, but the ones out of view came last.
+    assert_eq!(update_order.len(), 6 * 3 * 6);
+    let first_out_of_view = update_order.iter().position(|p| !in_view(p)).unwrap();
+    assert!(
+        !update_order[first_out_of_view..].iter().any(in_view),
+        "{update_order:?}"
+    );
+    assert!(
+        update_order[..first_out_of_view]
+            .iter()
+            .all(|p| !is_behind(p)),
+        "{update_order:?}"
+    );
+    assert_eq!(tester.csm.iter_chunks().count(), update_order.len());
+
+    let visible: Vec<ChunkPos<CHUNK_SIZE>> = tester
+        .csm
+        .iter_in_view(&tester.camera)
+        .map(|chunk| chunk.position)
+        .collect();
+    assert!(visible.contains(&ChunkPos::new(0, 0, -1)));
+    assert!(!visible.iter().any(is_behind), "{visible:?}");
+}
+
 /// Test the logic which decides whether `ChunkedSpaceMesh` managed to completely
 /// update itself.
 #[test]
@@ -327,3 +376,47 @@ fn did_not_finish_detection() {
         (Flaws::empty(), false, true)
     );
 }
+
+#[test]
+fn distant_chunks_use_low_detail() {
+    // A block with many faces, far from the camera.
+    let mut universe = Universe::new();
+    let block = Block::builder()
+        .voxels_fn(&mut universe, R4, |cube| {
+            if (cube.x + cube.y + cube.z).rem_euclid(2) == 0 {
+                Block::from(rgba_const!(1., 1., 1., 1.))
+            } else {
+                AIR
+            }
+        })
+        .unwrap()
+        .build();
+    let mut space = Space::empty(GridAab::from_lower_size([0, 0, 0], [CHUNK_SIZE * 3, 1, 1]));
+    let block_cube = Cube::new(CHUNK_SIZE * 2, 0, 0);
+    space.set(block_cube, block).unwrap();
+    let block_chunk = ChunkPos::new(2, 0, 0);
+
+    let mut tester = CsmTester::new(space, LARGE_VIEW_DISTANCE);
+    let mut options = tester.camera.options().clone();
+    options.lod_distance = Some(NotNan::from(CHUNK_SIZE / 2));
+    tester.camera.set_options(options);
+
+    let chunk_vertices = |tester: &mut CsmTester| {
+        tester.update(|_| {});
+        tester.update(|_| {});
+        let chunk = tester.csm.chunk(block_chunk).unwrap();
+        (chunk.is_low_detail(), chunk.mesh().vertices().len())
+    };
+
+    tester.move_camera_to([0.5, 0.5, 0.5]);
+    let (far_low_detail, far_vertices) = chunk_vertices(&mut tester);
+    tester.move_camera_to([2.5, 0.5, 0.5]);
+    let (near_low_detail, near_vertices) = chunk_vertices(&mut tester);
+
+    assert_eq!((far_low_detail, near_low_detail), (true, false));
+    assert_eq!(far_vertices, 24);
+    assert!(
+        near_vertices > far_vertices,
+        "{near_vertices} should be > {far_vertices}"
+    );
+}
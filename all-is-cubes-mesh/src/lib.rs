@@ -52,7 +52,9 @@
 #![forbid(unsafe_code)]
 #![warn(missing_docs)]
 
+use all_is_cubes::block::Resolution;
 use all_is_cubes::camera::{GraphicsOptions, TransparencyOption};
+use all_is_cubes::math::Rgba;
 
 mod block_vertex;
 pub use block_vertex::*;
@@ -86,6 +88,12 @@ pub struct MeshOptions {
     ///
     /// [`voxels`]: all_is_cubes::block::EvaluatedBlock::voxels
     ignore_voxels: bool,
+
+    /// Outline to draw along the edges of each block, if any.
+    outline: Option<Outline>,
+
+    /// Draw faces even if they are hidden by an adjacent opaque block.
+    disable_face_culling: bool,
 }
 
 impl MeshOptions {
@@ -94,6 +102,8 @@ impl MeshOptions {
         Self {
             transparency: graphics_options.transparency.clone(),
             ignore_voxels: false,
+            outline: None,
+            disable_face_culling: graphics_options.debug_disable_face_culling,
         }
     }
 
@@ -104,6 +114,54 @@ impl MeshOptions {
         Self {
             transparency: TransparencyOption::Volumetric,
             ignore_voxels: false,
+            outline: None,
+            disable_face_culling: false,
         }
     }
+
+    /// Sets the outline to draw along the edges of each block, or [`None`] for no outline
+    /// (the default).
+    ///
+    /// Outlines are drawn by recoloring voxels, so they make textures necessary for blocks
+    /// that would otherwise be drawn with solid colors.
+    pub fn set_outline(&mut self, outline: Option<Outline>) {
+        self.outline = outline;
+    }
+
+    /// Sets how transparent and partially transparent voxels are meshed, overriding the
+    /// [`GraphicsOptions::transparency`] these options were created from.
+    ///
+    /// [`TransparencyOption::Threshold`] and [`TransparencyOption::OpaqueOnly`] produce
+    /// meshes with no partial transparency, which is useful for exports such as 3D printing.
+    pub fn set_transparency(&mut self, transparency: TransparencyOption) {
+        self.transparency = transparency;
+    }
+}
+
+/// Dark (or otherwise colored) edges drawn along the boundaries of blocks, to give a
+/// “blocky” appearance and make adjacent blocks of the same color distinguishable.
+/// Used with [`MeshOptions::set_outline()`].
+///
+/// The outline covers the voxels on the block’s surface that are within
+/// [`width`](Self::width) voxels of an edge of the block’s cube.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[allow(clippy::exhaustive_structs)]
+pub struct Outline {
+    /// Color of the outline.
+    ///
+    /// The outline’s alpha determines how much of the original voxel color is replaced;
+    /// the voxel’s own alpha is unchanged, so the outline does not make transparent
+    /// voxels visible.
+    pub color: Rgba,
+
+    /// The resolution at which [`width`](Self::width) is measured.
+    ///
+    /// Blocks of lower resolution are subdivided to this resolution so that the outline
+    /// may be drawn, and blocks of higher resolution get a proportionally larger number
+    /// of outline voxels, so that the outline looks the same on all blocks.
+    pub resolution: Resolution,
+
+    /// Width of the outline, in voxels at [`resolution`](Self::resolution).
+    pub width: u8,
 }
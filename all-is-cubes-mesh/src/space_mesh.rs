@@ -6,7 +6,7 @@ use ordered_float::OrderedFloat;
 
 use all_is_cubes::camera::Flaws;
 use all_is_cubes::cgmath::{EuclideanSpace as _, MetricSpace as _, Point3, Vector3, Zero as _};
-use all_is_cubes::math::{Cube, Face6, GridAab, GridCoordinate, GridRotation};
+use all_is_cubes::math::{Cube, Face6, FaceMap, GridAab, GridCoordinate, GridRotation};
 use all_is_cubes::space::{BlockIndex, Space};
 
 use crate::texture;
@@ -172,8 +172,30 @@ impl<V: GfxVertex, T: texture::Tile> SpaceMesh<V, T> {
         &mut self,
         space: &Space,
         bounds: GridAab,
-        _options: &MeshOptions,
+        options: &MeshOptions,
+        block_meshes: P,
+    ) where
+        P: GetBlockMesh<'p, V, T>,
+        V: 'p,
+        T: 'p,
+    {
+        self.compute_with_opacity(space, bounds, options, block_meshes, None)
+    }
+
+    /// As [`SpaceMesh::compute`], but if `opacity` is given, it is consulted to determine
+    /// whether neighboring blocks obscure faces, instead of looking up the neighbors'
+    /// meshes, and the block indices of the neighborhood are read from `space` only once,
+    /// into the given buffer (whose previous contents are discarded). The buffer may be
+    /// reused across calls to avoid allocating.
+    ///
+    /// `opacity` must be up-to-date with `block_meshes` or the result will be inaccurate.
+    pub(crate) fn compute_with_opacity<'p, P>(
+        &mut self,
+        space: &Space,
+        bounds: GridAab,
+        options: &MeshOptions,
         mut block_meshes: P,
+        opacity: Option<(&OpacityCache, &mut Vec<BlockIndex>)>,
     ) where
         P: GetBlockMesh<'p, V, T>,
         V: 'p,
@@ -189,10 +211,36 @@ impl<V: GfxVertex, T: texture::Tile> SpaceMesh<V, T> {
         // TODO: Consider reuse
         let mut transparent_indices = IndexVec::new();
 
+        // If we have an opacity cache, then also copy the block indices of the region
+        // (including the neighbors of its edges) so that each cube is looked up only once,
+        // rather than once for itself and once for each of its neighbors.
+        // The indices are stored in `region.interior_iter()` order, as in a `GridArray`.
+        let neighborhood: Option<(&OpacityCache, Option<GridAab>, &[BlockIndex])> =
+            opacity.map(|(opacity, buffer)| {
+                let region = bounds
+                    .expand(FaceMap::repeat(1))
+                    .intersection(space.bounds());
+                buffer.clear();
+                if let Some(region) = region {
+                    buffer.extend(region.interior_iter().map(|cube| {
+                        space
+                            .get_block_index(cube)
+                            .expect("region should be within the space")
+                    }));
+                }
+                (opacity, region, &**buffer)
+            });
+        let get_block_index = |cube: Cube| -> Option<BlockIndex> {
+            match neighborhood {
+                Some((_, region, indices)) => Some(indices[region?.index(cube)?]),
+                None => space.get_block_index(cube),
+            }
+        };
+
         bounds.interior_iter().for_each(|cube| {
             // TODO: On out-of-range, draw an obviously invalid block instead of an invisible one?
             // Do we want to make it the caller's responsibility to specify in-bounds?
-            let index: BlockIndex = match space.get_block_index(cube) {
+            let index: BlockIndex = match get_block_index(cube) {
                 Some(index) => index,
                 None => return, // continue in for_each() loop
             };
@@ -216,12 +264,22 @@ impl<V: GfxVertex, T: texture::Tile> SpaceMesh<V, T> {
                 &mut self.indices,
                 &mut transparent_indices,
                 |face| {
+                    if options.disable_face_culling {
+                        return false;
+                    }
                     let adjacent_cube = cube + face.normal_vector();
-                    if let Some(adj_block_index) = space.get_block_index(adjacent_cube) {
-                        if block_meshes.get_block_mesh(adj_block_index).face_vertices
-                            [face.opposite()]
-                        .fully_opaque
-                        {
+                    if let Some(adj_block_index) = get_block_index(adjacent_cube) {
+                        let adj_fully_opaque = match neighborhood {
+                            Some((opacity, _, _)) => {
+                                opacity.is_fully_opaque(adj_block_index, face.opposite())
+                            }
+                            None => {
+                                block_meshes.get_block_mesh(adj_block_index).face_vertices
+                                    [face.opposite()]
+                                .fully_opaque
+                            }
+                        };
+                        if adj_fully_opaque {
                             // Don't draw obscured faces, but do record that we depended on them.
                             bitset_set_and_get(
                                 &mut self.block_indices_used,
@@ -601,6 +659,62 @@ pub trait GetBlockMesh<'a, V, T> {
     fn get_block_mesh(&mut self, index: BlockIndex) -> &'a BlockMesh<V, T>;
 }
 
+/// Records which faces of each block's [`BlockMesh`] are fully opaque, indexed by
+/// [`BlockIndex`], so that [`SpaceMesh::compute_with_opacity()`] can cull obscured faces
+/// without looking up the neighboring blocks' meshes.
+///
+/// Must be kept up-to-date with the meshes it was derived from.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct OpacityCache {
+    /// For each block index, a bitmask with bit `face as u8` set if that face is fully
+    /// opaque.
+    opaque_faces: Vec<u8>,
+}
+
+impl OpacityCache {
+    /// Derives a cache from the given meshes.
+    #[cfg(test)]
+    pub(crate) fn from_meshes<V, T>(meshes: &[BlockMesh<V, T>]) -> Self {
+        let mut cache = Self::default();
+        for (index, mesh) in meshes.iter().enumerate() {
+            cache.set(index.try_into().unwrap(), mesh);
+        }
+        cache
+    }
+
+    /// Discards all entries.
+    pub(crate) fn clear(&mut self) {
+        self.opaque_faces.clear();
+    }
+
+    /// Discards entries for block indices greater than or equal to `len`.
+    pub(crate) fn truncate(&mut self, len: usize) {
+        self.opaque_faces.truncate(len);
+    }
+
+    /// Records the opacity of `mesh` as that of the block with index `index`.
+    pub(crate) fn set<V, T>(&mut self, index: BlockIndex, mesh: &BlockMesh<V, T>) {
+        let index = usize::from(index);
+        if index >= self.opaque_faces.len() {
+            self.opaque_faces.resize(index + 1, 0);
+        }
+        self.opaque_faces[index] = Face6::ALL
+            .into_iter()
+            .filter(|&face| mesh.face_vertices[face].fully_opaque)
+            .fold(0, |mask, face| mask | (1 << face as u8));
+    }
+
+    /// Returns whether the given face of the block with index `index` is fully opaque.
+    /// Unknown indices are treated as not opaque.
+    #[inline]
+    fn is_fully_opaque(&self, index: BlockIndex, face: Face6) -> bool {
+        match self.opaque_faces.get(usize::from(index)) {
+            Some(&mask) => mask & (1 << face as u8) != 0,
+            None => false,
+        }
+    }
+}
+
 /// Basic implementation of [`GetBlockMesh`] for any slice of meshes.
 impl<'a, V: 'static, T: 'static> GetBlockMesh<'a, V, T> for &'a [BlockMesh<V, T>] {
     fn get_block_mesh(&mut self, index: BlockIndex) -> &'a BlockMesh<V, T> {
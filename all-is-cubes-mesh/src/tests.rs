@@ -7,7 +7,9 @@ use all_is_cubes::block::{
     Atom, Block, BlockAttributes, BlockCollision, Primitive, Resolution::*, AIR,
 };
 use all_is_cubes::camera::{Flaws, GraphicsOptions, TransparencyOption};
-use all_is_cubes::cgmath::{MetricSpace as _, Point3, Transform as _, Vector3};
+use all_is_cubes::cgmath::{
+    EuclideanSpace as _, MetricSpace as _, Point3, Transform as _, Vector3,
+};
 use all_is_cubes::content::{make_some_blocks, make_some_voxel_blocks};
 use all_is_cubes::math::{
     Face6::{self, *},
@@ -20,7 +22,7 @@ use all_is_cubes::{notnan, rgba_const};
 use crate::texture::{TestAllocator, TestPoint, TestTile};
 use crate::{
     block_meshes_for_space, BlockMesh, BlockMeshes, BlockVertex, Coloring, DepthOrdering,
-    IndexSlice, MeshOptions, SpaceMesh,
+    IndexSlice, MeshOptions, OpacityCache, SpaceMesh,
 };
 
 /// Shorthand for writing out an entire [`BlockVertex`] with solid color.
@@ -137,6 +139,88 @@ fn excludes_hidden_faces_of_blocks() {
     );
 }
 
+/// [`GraphicsOptions::debug_disable_face_culling`] brings back the faces that
+/// [`excludes_hidden_faces_of_blocks`] checks are excluded.
+#[test]
+fn disable_face_culling() {
+    let mut space = Space::empty_positive(2, 2, 2);
+    space
+        .fill(space.bounds(), |p| Some(non_uniform_fill(p)))
+        .unwrap();
+    let mut graphics_options = GraphicsOptions::default();
+    graphics_options.debug_disable_face_culling = true;
+    let options = &MeshOptions::new(&graphics_options);
+    let block_meshes = block_meshes_for_space(&space, &TestAllocator::new(), options);
+    let space_mesh: SpaceMesh<BlockVertex<TestPoint>, TestTile> =
+        SpaceMesh::new(&space, space.bounds(), options, &*block_meshes);
+
+    // The block meshes still know they are opaque, even though that isn't used.
+    for block_mesh in block_meshes.iter().filter(|m| !m.is_empty()) {
+        assert!(block_mesh.face_vertices.values().all(|m| m.fully_opaque));
+    }
+
+    assert_eq!(space_mesh.flaws(), Flaws::empty());
+    assert!(
+        space_mesh
+            .vertices()
+            .iter()
+            .any(|vertex| vertex.position.distance2(Point3::new(1.0, 1.0, 1.0)) < 0.99),
+        "found no interior point"
+    );
+    assert_eq!(
+        space_mesh.vertices().len(),
+        4 /* vertices per face */
+        * 6 /* faces per block */
+        * 8, /* blocks */
+        "wrong number of faces"
+    );
+}
+
+/// [`SpaceMesh::compute_with_opacity`] should produce exactly the same mesh as
+/// [`SpaceMesh::compute`], whether the region is interior to the space or touches its edges.
+#[test]
+fn opacity_cache_output_equivalent() {
+    let mut universe = Universe::new();
+    let [voxel_block] = make_some_voxel_blocks(&mut universe);
+    let glass = Block::from(rgba_const!(0.5, 0.5, 0.5, 0.5));
+    let mut space = Space::empty_positive(4, 4, 4);
+    space
+        .fill(space.bounds(), |cube| {
+            match (cube.x + 2 * cube.y + 3 * cube.z).rem_euclid(4) {
+                0 => Some(non_uniform_fill(cube)),
+                1 => Some(&voxel_block),
+                2 => Some(&glass),
+                _ => Some(&AIR),
+            }
+        })
+        .unwrap();
+
+    let options = &MeshOptions::new(&GraphicsOptions::default());
+    let block_meshes: BlockMeshes<BlockVertex<TestPoint>, TestTile> =
+        block_meshes_for_space(&space, &TestAllocator::new(), options);
+    let opacity = OpacityCache::from_meshes(&block_meshes);
+    // Reused across iterations, as it is in chunked meshing.
+    let mut buffer = Vec::new();
+
+    for bounds in [
+        space.bounds(),
+        GridAab::from_lower_size([1, 1, 1], [2, 2, 2]),
+        GridAab::from_lower_size([-1, 2, 0], [3, 3, 3]),
+    ] {
+        let without_cache: SpaceMesh<BlockVertex<TestPoint>, TestTile> =
+            SpaceMesh::new(&space, bounds, options, &*block_meshes);
+        let mut with_cache = SpaceMesh::default();
+        with_cache.compute_with_opacity(
+            &space,
+            bounds,
+            options,
+            &*block_meshes,
+            Some((&opacity, &mut buffer)),
+        );
+        assert_eq!(with_cache, without_cache, "{bounds:?}");
+    }
+}
+
 /// Run [`SpaceMesh::new`] with stale block data and confirm it does not panic.
 #[test]
 fn no_panic_on_missing_blocks() {
@@ -408,7 +492,49 @@ fn atom_transparency_thresholded() {
         test_block_mesh_threshold(Block::from(Rgba::new(1.0, 1.0, 1.0, 1.0))),
     );
 
-    // TODO: also test voxels -- including self-occlusion (thresholded voxel in front of truly opaque voxel)
+    // TODO: also test self-occlusion (thresholded voxel in front of truly opaque voxel)
+}
+
+/// Test helper to create [`BlockMesh`] of a block with two voxels, of the given alphas,
+/// using the given transparency option.
+fn two_voxel_block_mesh(
+    alphas: [f32; 2],
+    transparency: TransparencyOption,
+) -> BlockMesh<BlockVertex<TestPoint>, TestTile> {
+    let mut universe = Universe::new();
+    let voxels = alphas.map(|alpha| Block::from(Rgba::new(1.0, 1.0, 1.0, alpha)));
+    let block = Block::builder()
+        .voxels_fn(&mut universe, R2, |cube| {
+            if cube.y == 0 && cube.z == 0 {
+                &voxels[cube.x as usize]
+            } else {
+                &AIR
+            }
+        })
+        .unwrap()
+        .build();
+    let mut options = MeshOptions::dont_care_for_test();
+    options.set_transparency(transparency);
+    BlockMesh::new(&block.evaluate().unwrap(), &TestAllocator::new(), &options)
+}
+
+#[test]
+fn voxel_transparency_thresholded() {
+    let threshold = TransparencyOption::Threshold(notnan!(0.5));
+    let mesh = two_voxel_block_mesh([0.4, 0.6], threshold.clone());
+    assert_eq!(mesh, two_voxel_block_mesh([0.0, 1.0], threshold));
+    assert!(!mesh.is_empty());
+}
+
+#[test]
+fn voxel_transparency_opaque_only() {
+    let mesh = two_voxel_block_mesh([0.6, 1.0], TransparencyOption::OpaqueOnly);
+    assert_eq!(
+        mesh,
+        two_voxel_block_mesh([0.0, 1.0], TransparencyOption::OpaqueOnly)
+    );
+    assert!(!mesh.is_empty());
+    assert!(two_voxel_block_mesh([0.6, 0.99], TransparencyOption::OpaqueOnly).is_empty());
 }
 
 /// Test [`BlockMesh::fully_opaque`] results from basic voxels.
@@ -498,6 +624,65 @@ fn transparency_split() {
     }
 }
 
+/// [`SpaceMesh::depth_sort_for_view()`] should reorder only the transparent quads.
+#[test]
+fn depth_sort_only_transparent() {
+    let mut space = Space::empty_positive(4, 1, 1);
+    space
+        .set([0, 0, 0], Block::from(Rgba::new(1.0, 0.0, 0.0, 1.0)))
+        .unwrap();
+    space
+        .set([2, 0, 0], Block::from(Rgba::new(0.0, 0.0, 1.0, 0.5)))
+        .unwrap();
+    space
+        .set([3, 0, 0], Block::from(Rgba::new(0.0, 1.0, 0.0, 0.5)))
+        .unwrap();
+    let (_, _, mut mesh) = mesh_blocks_and_space(&space);
+
+    let opaque_range = mesh.opaque_range();
+    let sorted_range = mesh.transparent_range(DepthOrdering::Within);
+    assert_eq!(opaque_range.len(), 6 * 6);
+    assert_eq!(sorted_range.len(), 6 * 6 * 2);
+    let before: Vec<u32> = mesh.indices().iter_u32().collect();
+
+    let view_position = Point3::new(10.0, 0.5, 0.5);
+    assert!(mesh.depth_sort_for_view(view_position));
+    let after: Vec<u32> = mesh.indices().iter_u32().collect();
+
+    // Everything outside the sorted range is untouched.
+    assert_eq!(before[opaque_range.clone()], after[opaque_range]);
+    assert_eq!(before[..sorted_range.start], after[..sorted_range.start]);
+    assert_eq!(before[sorted_range.end..], after[sorted_range.end..]);
+
+    // The sorted range contains the same quads, now in far-to-near order.
+    let quads = |indices: &[u32]| -> Vec<[u32; 6]> {
+        indices
+            .chunks_exact(6)
+            .map(|quad| <[u32; 6]>::try_from(quad).unwrap())
+            .collect()
+    };
+    let mut before_quads = quads(&before[sorted_range.clone()]);
+    let mut after_quads = quads(&after[sorted_range]);
+    assert_ne!(before_quads, after_quads);
+    let distances: Vec<f64> = after_quads
+        .iter()
+        .map(|quad| {
+            let midpoint = quad
+                .iter()
+                .map(|&i| mesh.vertices()[i as usize].position)
+                .fold(Vector3::new(0.0, 0.0, 0.0), |sum, p| sum + p.to_vec() / 6.0);
+            view_position.distance2(Point3::from_vec(midpoint))
+        })
+        .collect();
+    assert!(
+        distances.windows(2).all(|pair| pair[0] >= pair[1]),
+        "{distances:?}"
+    );
+    before_quads.sort();
+    after_quads.sort();
+    assert_eq!(before_quads, after_quads);
+}
+
 #[test]
 fn handling_allocation_failure() {
     let resolution = R8;
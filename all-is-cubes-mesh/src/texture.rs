@@ -184,6 +184,29 @@ pub(super) fn copy_voxels_into_existing_texture<T: Tile>(voxels: &Evoxels, textu
     texture.write(&texels);
 }
 
+/// [`Allocator`] which returns an existing tile, instead of allocating a new one, if it
+/// has the requested bounds and is [reusable](Tile::REUSABLE).
+///
+/// This is used to recompute a mesh whose texture coordinates have changed, without
+/// rewriting its texture into a new tile.
+#[derive(Debug)]
+pub(crate) struct ReuseAllocator<'a, A: Allocator> {
+    pub(crate) tile: Option<A::Tile>,
+    pub(crate) allocator: &'a A,
+}
+
+impl<A: Allocator> Allocator for ReuseAllocator<'_, A> {
+    type Tile = A::Tile;
+    type Point = A::Point;
+
+    fn allocate(&self, bounds: GridAab) -> Option<Self::Tile> {
+        match &self.tile {
+            Some(tile) if A::Tile::REUSABLE && tile.bounds() == bounds => Some(tile.clone()),
+            _ => self.allocator.allocate(bounds),
+        }
+    }
+}
+
 /// Null [`Allocator`]; rejects all allocations.
 ///
 /// Used for generating textureless meshes. TODO: Modify triangulator to actually
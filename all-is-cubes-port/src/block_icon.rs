@@ -0,0 +1,121 @@
+//! Rendering of individual blocks as small images; see [`render_block_icon()`].
+
+use all_is_cubes::block::{Block, EvaluatedBlock};
+use all_is_cubes::camera::{Camera, GraphicsOptions, ProjectionOption, Viewport};
+use all_is_cubes::cgmath::{Decomposed, Transform as _, Vector2, Vector3};
+use all_is_cubes::math::{FreeCoordinate, GridAab, NotNan, Rgba};
+use all_is_cubes::raytracer::{Background, ColorBuf, SpaceRaytracer};
+use all_is_cubes::space::{LightPhysics, Space};
+
+/// Draws `block` as a square image `size` pixels on a side, viewed from above and to
+/// the +X, +Z side with an isometric projection, against a transparent background.
+///
+/// This is suitable for inventory slots, documentation, and so on.
+/// Colors are drawn without any lighting or shading.
+///
+/// The block's voxels are raytraced as a space of their own; if the block has more than
+/// 65536 distinct voxels (which is only possible when it is composed in some way other
+/// than from a [`Space`]), some voxels may be missing from the image.
+pub fn render_block_icon(block: &EvaluatedBlock, size: u32) -> image::RgbaImage {
+    let resolution = block.resolution();
+    let resolution_f = FreeCoordinate::from(resolution);
+    let framing_bounds = GridAab::for_block(resolution);
+
+    // Copy the voxels into a space, where each voxel is one cube.
+    let mut space = Space::builder(block.voxels.bounds())
+        .light_physics(LightPhysics::None)
+        .build();
+    for cube in block.voxels.bounds().interior_iter() {
+        let Some(voxel) = block.voxels.get(cube) else {
+            continue;
+        };
+        if voxel.color.fully_transparent() {
+            continue;
+        }
+        // Ignore palette overflow; see above.
+        let _ = space.set(
+            cube,
+            Block::builder()
+                .color(voxel.color)
+                .light_emission(voxel.emission)
+                .build(),
+        );
+    }
+
+    let mut options = GraphicsOptions::UNALTERED_COLORS;
+    // A cube, seen along its diagonal, is sqrt(8/3) ≈ 1.63 times its edge length tall;
+    // include a small margin.
+    options.projection = ProjectionOption::Orthographic {
+        height: NotNan::new(resolution_f * 1.7).unwrap(),
+    };
+    options.view_distance = NotNan::new(resolution_f * 4.0).unwrap();
+    let viewport = Viewport::with_scale(1.0, Vector2::new(size, size));
+    let mut camera = Camera::new(options.clone(), viewport);
+    let center = framing_bounds.center();
+    camera.set_view_transform(
+        Decomposed::look_at_rh(
+            center + Vector3::new(1.0, 1.0, 1.0) * resolution_f * 2.0,
+            center,
+            Vector3::new(0., 1., 0.),
+        )
+        .inverse_transform()
+        .unwrap(),
+    );
+
+    let rt = SpaceRaytracer::<()>::new(&space, options, ());
+    image::RgbaImage::from_fn(size, size, |x, y| {
+        let ray = camera.project_ndc_into_world(
+            [
+                viewport.normalize_fb_x(x as usize),
+                viewport.normalize_fb_y(y as usize),
+            ]
+            .into(),
+        );
+        let (buf, _info): (ColorBuf, _) =
+            rt.trace_ray_with_background(ray, &Background::Transparent);
+        image::Rgba(camera.post_process_color(Rgba::from(buf)).to_srgb8())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn solid_red_block() {
+        let block = Block::from(Rgba::new(1.0, 0.0, 0.0, 1.0));
+        let image = render_block_icon(&block.evaluate().unwrap(), 32);
+
+        assert_eq!(image.dimensions(), (32, 32));
+        for (x, y) in [(15, 15), (16, 16), (16, 8), (16, 24)] {
+            let image::Rgba([r, g, b, a]) = *image.get_pixel(x, y);
+            assert!(
+                r > 200 && g < 50 && b < 50 && a == 255,
+                "pixel at {x}, {y} not red: {:?}",
+                [r, g, b, a]
+            );
+        }
+        for (x, y) in [(0, 0), (31, 0), (0, 31), (31, 31)] {
+            assert_eq!(
+                image.get_pixel(x, y)[3],
+                0,
+                "pixel at {x}, {y} not transparent"
+            );
+        }
+    }
+
+    #[test]
+    fn voxel_block() {
+        let mut universe = all_is_cubes::universe::Universe::new();
+        let [block] = all_is_cubes::content::make_some_voxel_blocks(&mut universe);
+        let image = render_block_icon(&block.evaluate().unwrap(), 16);
+
+        assert_eq!(image.get_pixel(8, 8)[3], 255);
+        assert_eq!(image.get_pixel(0, 0)[3], 0);
+        // The voxels' distinct colors are visible.
+        let opaque_colors: HashSet<image::Rgba<u8>> =
+            image.pixels().filter(|p| p[3] == 255).copied().collect();
+        assert!(opaque_colors.len() > 1, "{opaque_colors:?}");
+    }
+}
@@ -4,6 +4,10 @@
 use std::fmt;
 use std::io;
 use std::path::PathBuf;
+use std::sync::Arc;
+
+mod zip;
+pub use self::zip::{ZipArchive, ZipFileish};
 
 /// A “file” that we can load things from and which has a name,
 /// without being tied to the current OS file system.
@@ -19,6 +23,67 @@ pub trait Fileish: fmt::Debug + Send + Sync {
     ///
     /// TODO: This should probably be async.
     fn read(&self) -> Result<Vec<u8>, io::Error>;
+
+    /// Returns the size of the file contents in bytes, if it can be determined without
+    /// reading them.
+    ///
+    /// This is used to reject files that are too large before reading them.
+    /// The default implementation returns [`None`].
+    fn size_hint(&self) -> Option<u64> {
+        None
+    }
+
+    /// Obtains another file relative to the directory containing this one, such as a data
+    /// file this one refers to. This is used when importing formats which may consist of
+    /// more than one file.
+    ///
+    /// `relative` is a relative path with `/` as the separator. It may not be absolute,
+    /// nor use `..` to leave the directory containing this file; if it does, an error of
+    /// kind [`io::ErrorKind::InvalidInput`] is returned. The file is not opened until
+    /// it is read, so the result may refer to a file that does not exist.
+    ///
+    /// The default implementation, for files that have no siblings, always returns an
+    /// error of kind [`io::ErrorKind::Unsupported`].
+    fn sibling(&self, relative: &str) -> Result<Arc<dyn Fileish>, io::Error> {
+        let _ = relative;
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("cannot find files relative to {}", self.display_full_path()),
+        ))
+    }
+}
+
+/// Splits a relative path, as accepted by [`Fileish::sibling()`], into the names of the
+/// directories and file it refers to, resolving `.` and `..` components.
+///
+/// Returns an error if the path is absolute or escapes the directory it is relative to.
+pub(crate) fn relative_path_components(relative: &str) -> Result<Vec<&str>, io::Error> {
+    let invalid = |reason: &str| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("invalid relative path {relative:?}: {reason}"),
+        )
+    };
+
+    if relative.starts_with('/') || relative.contains('\\') || relative.contains(':') {
+        return Err(invalid("must be relative and use '/' separators"));
+    }
+    let mut components = Vec::new();
+    for component in relative.split('/') {
+        match component {
+            "" | "." => {}
+            ".." => {
+                if components.pop().is_none() {
+                    return Err(invalid("leaves the containing directory"));
+                }
+            }
+            name => components.push(name),
+        }
+    }
+    if components.is_empty() {
+        return Err(invalid("does not name a file"));
+    }
+    Ok(components)
 }
 
 // TODO: when Rust has generic associated types we will no longer
@@ -40,6 +105,16 @@ impl Fileish for PathBuf {
     fn read(&self) -> Result<Vec<u8>, io::Error> {
         std::fs::read(self)
     }
+
+    fn size_hint(&self) -> Option<u64> {
+        std::fs::metadata(self).ok().map(|metadata| metadata.len())
+    }
+
+    fn sibling(&self, relative: &str) -> Result<Arc<dyn Fileish>, io::Error> {
+        let mut path = self.parent().map(PathBuf::from).unwrap_or_default();
+        path.extend(relative_path_components(relative)?);
+        Ok(Arc::new(path))
+    }
 }
 
 /// General-purpose implementation of [`Fileish`].
@@ -80,3 +155,139 @@ where
         (self.opener)()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{export_to_path, load_universe_from_file, ExportFormat, ExportSet};
+    use all_is_cubes::space::Space;
+    use all_is_cubes::universe::{URef, Universe};
+    use all_is_cubes::util::yield_progress_for_testing;
+    use std::collections::BTreeMap;
+    use std::path::Path;
+
+    /// A file in an in-memory directory tree, which can find its siblings.
+    #[derive(Debug)]
+    struct VirtualFile {
+        files: Arc<BTreeMap<String, Vec<u8>>>,
+        path: String,
+    }
+
+    impl Fileish for VirtualFile {
+        fn document_name(&self) -> String {
+            self.path.clone()
+        }
+
+        fn display_full_path(&self) -> String {
+            self.path.clone()
+        }
+
+        fn read(&self) -> Result<Vec<u8>, io::Error> {
+            self.files
+                .get(&self.path)
+                .cloned()
+                .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+        }
+
+        fn sibling(&self, relative: &str) -> Result<Arc<dyn Fileish>, io::Error> {
+            let mut components: Vec<&str> = self.path.split('/').collect();
+            components.pop();
+            components.extend(relative_path_components(relative)?);
+            Ok(Arc::new(VirtualFile {
+                files: self.files.clone(),
+                path: components.join("/"),
+            }))
+        }
+    }
+
+    #[test]
+    fn relative_path_resolution() {
+        assert_eq!(
+            relative_path_components("a/./b/../c.bin").unwrap(),
+            vec!["a", "c.bin"]
+        );
+        for bad in ["", ".", "../x", "a/../../x", "/etc/passwd", "a\\b", "C:x"] {
+            assert_eq!(
+                relative_path_components(bad).unwrap_err().kind(),
+                io::ErrorKind::InvalidInput,
+                "{bad:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn path_sibling() {
+        let file = PathBuf::from("/data/scene/main.gltf");
+        assert_eq!(
+            file.sibling("buffers/mesh.bin")
+                .unwrap()
+                .display_full_path(),
+            Path::new("/data/scene/buffers/mesh.bin")
+                .display()
+                .to_string()
+        );
+        assert_eq!(
+            file.sibling("../secret").unwrap_err().kind(),
+            io::ErrorKind::InvalidInput
+        );
+    }
+
+    #[test]
+    fn non_disk_file_has_no_siblings() {
+        let file = NonDiskFile::from_name_and_data_source("foo".into(), || Ok(Vec::new()));
+        assert_eq!(
+            file.sibling("bar").unwrap_err().kind(),
+            io::ErrorKind::Unsupported
+        );
+    }
+
+    /// Import a universe found by following a reference from one virtual file to another.
+    #[tokio::test]
+    async fn import_via_virtual_sibling() {
+        let mut universe = Universe::new();
+        let space: URef<Space> = universe
+            .insert("space".into(), Space::empty_positive(1, 1, 1))
+            .unwrap();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let temp_path = temp_dir.path().join("world.alliscubesjson");
+        export_to_path(
+            yield_progress_for_testing(),
+            ExportFormat::AicJson,
+            ExportSet::from_spaces(vec![space]),
+            temp_path.clone(),
+        )
+        .await
+        .unwrap();
+
+        let files = Arc::new(BTreeMap::from([
+            (
+                "scene/index.txt".to_owned(),
+                b"parts/world.alliscubesjson".to_vec(),
+            ),
+            (
+                "scene/parts/world.alliscubesjson".to_owned(),
+                std::fs::read(temp_path).unwrap(),
+            ),
+        ]));
+        let index = VirtualFile {
+            files,
+            path: "scene/index.txt".into(),
+        };
+
+        let reference = String::from_utf8(index.read().unwrap()).unwrap();
+        let world_file = index.sibling(&reference).unwrap();
+        assert_eq!(
+            world_file.display_full_path(),
+            "scene/parts/world.alliscubesjson"
+        );
+        let imported = load_universe_from_file(yield_progress_for_testing(), world_file)
+            .await
+            .unwrap();
+        assert!(imported.get::<Space>(&"space".into()).is_some());
+
+        assert_eq!(
+            index.sibling("../outside").unwrap_err().kind(),
+            io::ErrorKind::InvalidInput
+        );
+    }
+}
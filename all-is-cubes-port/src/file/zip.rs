@@ -0,0 +1,470 @@
+//! [`ZipArchive`] and [`ZipFileish`], for reading and writing files within a zip archive.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{self, Read as _, Write as _};
+use std::sync::Arc;
+
+use crate::file::{relative_path_components, Fileish};
+use crate::{saturating_u64, ImportLimits, LimitExceeded, LimitedResource};
+
+/// Largest amount of memory to allocate for an entry's contents based on the size the
+/// archive claims it has, which might not be true.
+const MAX_PREALLOCATION: u64 = 1 << 20;
+
+/// The contents of a zip archive, held in memory.
+///
+/// Entries are identified by their paths within the archive, which use `/` as the
+/// separator and are relative to the root of the archive. Use [`ZipFileish`] to access
+/// an entry as a [`Fileish`], so that files it refers to are found within the archive.
+#[derive(Clone, Default, Eq, PartialEq)]
+pub struct ZipArchive {
+    /// Uncompressed data of each file, keyed by normalized path.
+    entries: BTreeMap<String, Vec<u8>>,
+}
+
+impl fmt::Debug for ZipArchive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // File contents are too big to print; list names and sizes only.
+        f.debug_map()
+            .entries(self.entries.iter().map(|(path, data)| (path, data.len())))
+            .finish()
+    }
+}
+
+impl ZipArchive {
+    /// Constructs an archive with no entries.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads the archive stored in `data`, such as the contents of a `.zip` file,
+    /// with the default [`ImportLimits`].
+    ///
+    /// See [`ZipArchive::from_bytes_with_limits()`] for details.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, io::Error> {
+        Self::from_bytes_with_limits(data, &ImportLimits::default())
+    }
+
+    /// Reads the archive stored in `data`, such as the contents of a `.zip` file.
+    ///
+    /// All entries are decompressed immediately. If their total size exceeds
+    /// [`ImportLimits::max_bytes`], decompression stops and an error of kind
+    /// [`io::ErrorKind::Other`] is returned.
+    /// Returns an error of kind [`io::ErrorKind::InvalidData`] if the data is not a
+    /// valid zip archive, or [`io::ErrorKind::Unsupported`] if it uses features
+    /// which are not supported, such as encryption.
+    pub fn from_bytes_with_limits(data: &[u8], limits: &ImportLimits) -> Result<Self, io::Error> {
+        let mut reader = ::zip::ZipArchive::new(io::Cursor::new(data)).map_err(zip_error)?;
+
+        let mut archive = Self::new();
+        let mut total_size: u64 = 0;
+        for index in 0..reader.len() {
+            let mut entry = reader.by_index(index).map_err(zip_error)?;
+            if entry.is_dir() {
+                // Directory entries carry no data; directories are implied by file paths.
+                continue;
+            }
+            let name = entry.name().to_owned();
+
+            // Don't trust the declared size, which might be wrong or malicious: allocate
+            // only a bounded amount in advance, and stop reading at the limit.
+            let remaining = limits.max_bytes.saturating_sub(total_size);
+            let mut contents = Vec::with_capacity(
+                usize::try_from(entry.size().min(remaining).min(MAX_PREALLOCATION)).unwrap(),
+            );
+            (&mut entry)
+                .take(remaining.saturating_add(1))
+                .read_to_end(&mut contents)
+                .map_err(|e| invalid(&format!("entry {name:?} is corrupt: {e}")))?;
+            total_size = total_size.saturating_add(saturating_u64(contents.len()));
+            limits
+                .check(LimitedResource::Bytes, total_size)
+                .map_err(|error: LimitExceeded| io::Error::new(io::ErrorKind::Other, error))?;
+
+            archive.insert(&name, contents)?;
+        }
+        Ok(archive)
+    }
+
+    /// Returns the paths of all files in the archive, in sorted order.
+    pub fn paths(&self) -> impl Iterator<Item = &str> + '_ {
+        self.entries.keys().map(String::as_str)
+    }
+
+    /// Returns the contents of the file at `path`, if there is one.
+    ///
+    /// `path` is resolved as by [`ZipArchive::insert()`].
+    pub fn get(&self, path: &str) -> Option<&[u8]> {
+        let path = normalize(path).ok()?;
+        self.entries.get(&path).map(Vec::as_slice)
+    }
+
+    /// Adds a file to the archive, replacing any existing file with the same path.
+    ///
+    /// `path` is relative to the root of the archive, with `/` as the separator; `.` and
+    /// `..` components are resolved. Returns an error of kind
+    /// [`io::ErrorKind::InvalidInput`] if it is absolute or leaves the root.
+    pub fn insert(&mut self, path: &str, contents: Vec<u8>) -> Result<(), io::Error> {
+        self.entries.insert(normalize(path)?, contents);
+        Ok(())
+    }
+
+    /// Writes the archive in zip format, with each file compressed.
+    pub fn write_to(&self, mut destination: impl io::Write) -> Result<(), io::Error> {
+        destination.write_all(&self.to_bytes()?)
+    }
+
+    /// Returns the archive in zip format, as written by [`ZipArchive::write_to()`].
+    pub fn to_bytes(&self) -> Result<Vec<u8>, io::Error> {
+        let mut writer = ::zip::ZipWriter::new(io::Cursor::new(Vec::new()));
+        let options = ::zip::write::FileOptions::default()
+            .compression_method(::zip::CompressionMethod::Deflated);
+        for (name, contents) in &self.entries {
+            writer.start_file(name, options).map_err(zip_error)?;
+            writer.write_all(contents)?;
+        }
+        Ok(writer.finish().map_err(zip_error)?.into_inner())
+    }
+}
+
+/// A file within a [`ZipArchive`].
+///
+/// [`Fileish::sibling()`] finds other entries in the same archive, so that formats
+/// consisting of more than one file can be imported from an archive.
+#[derive(Clone)]
+pub struct ZipFileish {
+    archive_name: String,
+    archive: Arc<ZipArchive>,
+    path: String,
+}
+
+impl fmt::Debug for ZipFileish {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            archive_name,
+            archive: _,
+            path,
+        } = self;
+        f.debug_struct("ZipFileish")
+            .field("archive_name", archive_name)
+            .field("path", path)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ZipFileish {
+    /// Refers to the file at `path` within `archive`.
+    ///
+    /// `archive_name` is used for display purposes, such as the file name the archive
+    /// was read from. `path` is resolved as by [`ZipArchive::insert()`]; the file need
+    /// not exist, in which case reading it will fail.
+    pub fn new(
+        archive_name: String,
+        archive: Arc<ZipArchive>,
+        path: &str,
+    ) -> Result<Self, io::Error> {
+        Ok(Self {
+            archive_name,
+            archive,
+            path: normalize(path)?,
+        })
+    }
+
+    /// Returns the archive this file is in.
+    pub fn archive(&self) -> &Arc<ZipArchive> {
+        &self.archive
+    }
+
+    /// Returns the path of this file within the archive.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+impl Fileish for ZipFileish {
+    fn document_name(&self) -> String {
+        let file_name = self.path.rsplit('/').next().unwrap_or(&self.path);
+        match file_name.rsplit_once('.') {
+            Some((stem, _)) if !stem.is_empty() => stem,
+            _ => file_name,
+        }
+        .to_owned()
+    }
+
+    fn display_full_path(&self) -> String {
+        format!("{}/{}", self.archive_name, self.path)
+    }
+
+    fn read(&self) -> Result<Vec<u8>, io::Error> {
+        self.archive
+            .entries
+            .get(&self.path)
+            .cloned()
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("{} does not exist", self.display_full_path()),
+                )
+            })
+    }
+
+    fn size_hint(&self) -> Option<u64> {
+        self.archive
+            .entries
+            .get(&self.path)
+            .map(|data| crate::saturating_u64(data.len()))
+    }
+
+    fn sibling(&self, relative: &str) -> Result<Arc<dyn Fileish>, io::Error> {
+        let mut components: Vec<&str> = self.path.split('/').collect();
+        components.pop();
+        components.extend(relative_path_components(relative)?);
+        Ok(Arc::new(ZipFileish {
+            archive_name: self.archive_name.clone(),
+            archive: self.archive.clone(),
+            path: components.join("/"),
+        }))
+    }
+}
+
+fn normalize(path: &str) -> Result<String, io::Error> {
+    Ok(relative_path_components(path)?.join("/"))
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("invalid zip archive: {message}"),
+    )
+}
+
+fn zip_error(error: ::zip::result::ZipError) -> io::Error {
+    use ::zip::result::ZipError;
+    match error {
+        ZipError::Io(error) => error,
+        ZipError::InvalidArchive(message) => invalid(message),
+        ZipError::UnsupportedArchive(message) => io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("unsupported zip archive: {message}"),
+        ),
+        ZipError::FileNotFound => invalid("file not found"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{export_to_path, load_universe_from_file, ExportFormat, ExportSet};
+    use all_is_cubes::block::{Block, BlockDef, Resolution, AIR};
+    use all_is_cubes::content::make_some_blocks;
+    use all_is_cubes::space::Space;
+    use all_is_cubes::universe::{URef, Universe};
+    use all_is_cubes::util::yield_progress_for_testing;
+    use std::path::Path;
+
+    /// Adds every file in `dir` (not recursively) to a new archive, under `prefix`.
+    fn archive_directory(dir: &Path, prefix: &str) -> ZipArchive {
+        let mut archive = ZipArchive::new();
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let entry = entry.unwrap();
+            let name = entry.file_name().into_string().unwrap();
+            archive
+                .insert(
+                    &format!("{prefix}/{name}"),
+                    std::fs::read(entry.path()).unwrap(),
+                )
+                .unwrap();
+        }
+        archive
+    }
+
+    #[test]
+    fn round_trip_bytes() {
+        let mut archive = ZipArchive::new();
+        archive.insert("empty", Vec::new()).unwrap();
+        archive.insert("a/./short.txt", b"hello".to_vec()).unwrap();
+        archive.insert("a/b/../long.bin", vec![7; 10000]).unwrap();
+
+        let bytes = archive.to_bytes().unwrap();
+        // The repetitive file should have been compressed.
+        assert!(bytes.len() < 10000, "{}", bytes.len());
+        let read = ZipArchive::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            read.paths().collect::<Vec<_>>(),
+            vec!["a/long.bin", "a/short.txt", "empty"]
+        );
+        assert_eq!(read, archive);
+    }
+
+    #[test]
+    fn corrupt_archive() {
+        let mut archive = ZipArchive::new();
+        archive.insert("x", b"hello".to_vec()).unwrap();
+        let mut bytes = archive.to_bytes().unwrap();
+        // Damage the stored contents, which comes right after the 31-byte local header.
+        bytes[31] ^= 1;
+        assert_eq!(
+            ZipArchive::from_bytes(&bytes).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        assert_eq!(
+            ZipArchive::from_bytes(b"not a zip").unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn decompressed_size_limit() {
+        let mut archive = ZipArchive::new();
+        archive.insert("a", vec![0; 6000]).unwrap();
+        archive.insert("b", vec![0; 6000]).unwrap();
+        let bytes = archive.to_bytes().unwrap();
+
+        let limits = |max_bytes| ImportLimits {
+            max_bytes,
+            ..ImportLimits::default()
+        };
+        assert_eq!(
+            ZipArchive::from_bytes_with_limits(&bytes, &limits(12000)).unwrap(),
+            archive
+        );
+        // The limit applies to the total of all entries, not each one.
+        let error = ZipArchive::from_bytes_with_limits(&bytes, &limits(11999)).unwrap_err();
+        assert_eq!(
+            error.into_inner().unwrap().downcast_ref::<LimitExceeded>(),
+            Some(&LimitExceeded {
+                resource: LimitedResource::Bytes,
+                limit: 11999
+            })
+        );
+    }
+
+    /// The uncompressed size recorded in the archive is not used to decide how much
+    /// memory to allocate.
+    #[test]
+    fn declared_size_is_not_trusted() {
+        let mut archive = ZipArchive::new();
+        archive.insert("x", b"hello".to_vec()).unwrap();
+        let mut bytes = archive.to_bytes().unwrap();
+
+        // Overwrite the uncompressed size in the central directory entry.
+        let central_entry = bytes
+            .windows(4)
+            .position(|window| window == b"PK\x01\x02")
+            .unwrap();
+        bytes[central_entry + 24..central_entry + 28]
+            .copy_from_slice(&(u32::MAX - 1).to_le_bytes());
+
+        let read = ZipArchive::from_bytes(&bytes).unwrap();
+        assert_eq!(read.get("x"), Some(&b"hello"[..]));
+    }
+
+    #[test]
+    fn fileish_sibling() {
+        let mut archive = ZipArchive::new();
+        archive.insert("scene/main.gltf", b"main".to_vec()).unwrap();
+        archive
+            .insert("scene/data/mesh.bin", b"mesh".to_vec())
+            .unwrap();
+        let file =
+            ZipFileish::new("scene.zip".into(), Arc::new(archive), "scene/main.gltf").unwrap();
+
+        assert_eq!(file.document_name(), "main");
+        assert_eq!(file.display_full_path(), "scene.zip/scene/main.gltf");
+        let sibling = file.sibling("data/./mesh.bin").unwrap();
+        assert_eq!(sibling.display_full_path(), "scene.zip/scene/data/mesh.bin");
+        assert_eq!(sibling.read().unwrap(), b"mesh");
+        assert_eq!(
+            file.sibling("missing").unwrap().read().unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+        assert_eq!(
+            file.sibling("../../outside").unwrap_err().kind(),
+            io::ErrorKind::InvalidInput
+        );
+    }
+
+    /// Export glTF whose buffers are in separate files, archive them, and follow the
+    /// references from the glTF JSON to the buffers within the archive.
+    #[tokio::test]
+    async fn gltf_with_buffer_files() {
+        // A checkerboard has many faces, so its mesh buffers will be too big to inline.
+        let mut universe = Universe::new();
+        let [color] = make_some_blocks();
+        let block = Block::builder()
+            .voxels_fn(&mut universe, Resolution::R16, |p| {
+                if (p.x + p.y + p.z).rem_euclid(2) == 0 {
+                    &color
+                } else {
+                    &AIR
+                }
+            })
+            .unwrap()
+            .build();
+        let block_def = universe
+            .insert("checkerboard".into(), BlockDef::new(block))
+            .unwrap();
+        let temp_dir = tempfile::tempdir().unwrap();
+        export_to_path(
+            yield_progress_for_testing(),
+            ExportFormat::Gltf,
+            ExportSet::from_block_defs(vec![block_def]),
+            temp_dir.path().join("blocks.gltf"),
+        )
+        .await
+        .unwrap();
+
+        let bytes = archive_directory(temp_dir.path(), "export")
+            .to_bytes()
+            .unwrap();
+        let archive = Arc::new(ZipArchive::from_bytes(&bytes).unwrap());
+        let gltf_file =
+            ZipFileish::new("export.zip".into(), archive, "export/blocks.gltf").unwrap();
+
+        let root: gltf_json::Root = serde_json::from_slice(&gltf_file.read().unwrap()).unwrap();
+        let mut external_buffers = 0;
+        for buffer in &root.buffers {
+            let uri = buffer.uri.as_deref().unwrap();
+            if uri.starts_with("data:") {
+                continue;
+            }
+            let contents = gltf_file.sibling(uri).unwrap().read().unwrap();
+            assert_eq!(contents.len(), buffer.byte_length as usize, "{uri}");
+            assert_eq!(contents, std::fs::read(temp_dir.path().join(uri)).unwrap());
+            external_buffers += 1;
+        }
+        assert!(external_buffers > 0, "export did not use any buffer files");
+    }
+
+    #[tokio::test]
+    async fn import_universe() {
+        let mut universe = Universe::new();
+        let space: URef<Space> = universe
+            .insert("space".into(), Space::empty_positive(1, 1, 1))
+            .unwrap();
+        let temp_dir = tempfile::tempdir().unwrap();
+        export_to_path(
+            yield_progress_for_testing(),
+            ExportFormat::AicJson,
+            ExportSet::from_spaces(vec![space]),
+            temp_dir.path().join("world.alliscubesjson"),
+        )
+        .await
+        .unwrap();
+
+        let bytes = archive_directory(temp_dir.path(), "saves")
+            .to_bytes()
+            .unwrap();
+        let file = ZipFileish::new(
+            "saves.zip".into(),
+            Arc::new(ZipArchive::from_bytes(&bytes).unwrap()),
+            "saves/world.alliscubesjson",
+        )
+        .unwrap();
+        let imported = load_universe_from_file(yield_progress_for_testing(), Arc::new(file))
+            .await
+            .unwrap();
+        assert!(imported.get::<Space>(&"space".into()).is_some());
+    }
+}
@@ -6,8 +6,9 @@
 //!
 //! TODO: This is not a clean, well-abstracted library API yet.
 
-use std::collections::{BTreeMap, BTreeSet};
-use std::path::PathBuf;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 use std::{fmt, fs, io};
 
@@ -15,27 +16,36 @@ pub use gltf_json as json;
 use gltf_json::validation::Checked::Valid;
 use gltf_json::Index;
 
-use all_is_cubes::camera::{Camera, Flaws, GraphicsOptions, ViewTransform};
+use all_is_cubes::block::{Block, BlockDef, Modifier};
+use all_is_cubes::camera::{
+    Camera, Flaws, GraphicsOptions, ProjectionOption, ViewTransform, Viewport,
+};
 use all_is_cubes::cgmath::One as _;
-use all_is_cubes::universe::PartialUniverse;
+use all_is_cubes::character::Character;
+use all_is_cubes::inv::Slot;
+use all_is_cubes::math::Rgb;
+use all_is_cubes::space::Space;
+use all_is_cubes::universe::{Name, PartialUniverse};
 use all_is_cubes::util::YieldProgress;
-use all_is_cubes_mesh::{BlockMesh, MeshOptions, SpaceMesh};
+use all_is_cubes_mesh::{block_meshes_for_space, BlockMesh, BlockMeshes, MeshOptions, SpaceMesh};
 
 mod buffer;
-use buffer::create_buffer_and_accessor;
 pub use buffer::GltfDataDestination;
+use buffer::{create_buffer_and_accessor, DeferredFile};
 mod animation;
 use animation::FrameState;
 mod mesh;
-use mesh::Materials;
+use mesh::{Dequantization, Materials};
 mod glue;
-use glue::{convert_quaternion, empty_node, push_and_return_index};
+use glue::{convert_block_rotation, convert_quaternion, empty_node, push_and_return_index};
+mod light;
+mod normal;
 mod texture;
-pub use texture::{GltfTextureAllocator, GltfTile};
+pub use texture::{GltfTextureAllocator, GltfTextureStyle, GltfTile};
 mod vertex;
 pub use vertex::GltfVertex;
 
-use crate::{ExportError, ExportSet};
+use crate::{unblock, ExportError, ExportFormat, ExportSet};
 #[cfg(test)]
 mod tests;
 
@@ -53,6 +63,56 @@ pub struct MeshInstance {
     pub translation: [i32; 3],
 }
 
+/// How [`GltfWriter::add_space()`] arranges the contents of a [`Space`] into meshes.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum GltfSceneLayout {
+    /// The whole space is a single mesh.
+    /// This is more efficient for rendering.
+    #[default]
+    Combined,
+
+    /// Each distinct block has its own mesh, and each cube containing a visible block
+    /// is a separate node using that shared mesh.
+    /// This is more convenient for editing the exported scene.
+    PerBlockInstances,
+}
+
+/// How [`GltfWriter`] stores the vertex positions of meshes.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum GltfPositionEncoding {
+    /// Positions are 32-bit floats.
+    /// This is compatible with all glTF readers.
+    #[default]
+    F32,
+
+    /// Positions are normalized signed 16-bit integers spanning each mesh's bounding box,
+    /// with a node transform to restore the original scale and position.
+    /// This makes meshes smaller, but requires the reader to support the
+    /// `KHR_mesh_quantization` extension, and positions are only accurate to within
+    /// 1/65534 of the size of the mesh.
+    NormalizedI16,
+}
+
+/// Which vertex normals [`GltfWriter`] writes for meshes.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum GltfNormals {
+    /// No normals are written, so readers use the flat normal of each triangle, as the
+    /// glTF 2.0 specification § 3.7.2.1 requires. Each face is shaded separately,
+    /// giving blocks hard edges.
+    #[default]
+    Flat,
+
+    /// Each vertex has a normal which is the average of the normals of the faces that
+    /// meet at its position, so that rounded shapes made of voxels appear smoothly shaded.
+    Smooth,
+}
+
+/// A mesh with light baked into it, and the light it was made with (one color per vertex).
+type LitMesh = (Vec<Rgb>, Index<gltf_json::Mesh>);
+
 /// Handles the construction of [`gltf_json::Root`] and the writing of supporting files
 /// for a single glTF asset.
 ///
@@ -77,6 +137,25 @@ pub struct GltfWriter {
     /// Testure allocator configured to write to this destination.
     texture_allocator: GltfTextureAllocator,
 
+    /// How to arrange the textures from `texture_allocator` into images.
+    texture_style: GltfTextureStyle,
+
+    /// How [`GltfWriter::add_space()`] arranges meshes.
+    scene_layout: GltfSceneLayout,
+
+    /// How [`GltfWriter::add_mesh()`] stores vertex positions.
+    position_encoding: GltfPositionEncoding,
+
+    /// Which normals [`GltfWriter::add_mesh()`] writes.
+    normals: GltfNormals,
+
+    /// Meshes added by [`GltfWriter::add_mesh()`], keyed by [`mesh::content_hash()`], so
+    /// that identical meshes are written only once.
+    meshes_by_content: HashMap<[u8; 32], Index<gltf_json::Mesh>>,
+
+    /// Transforms to be applied to meshes whose positions were quantized.
+    dequantizations: BTreeMap<Index<gltf_json::Mesh>, Dequantization>,
+
     /// Materials the meshes need.
     materials: Materials,
 
@@ -91,6 +170,17 @@ pub struct GltfWriter {
     /// Using BTreeSet for stable ordering.
     any_time_visible_mesh_instances: BTreeSet<MeshInstance>,
 
+    /// Whether to bake light into the meshes of each frame;
+    /// see [`GltfWriter::set_bake_light()`].
+    bake_light: bool,
+
+    /// If `bake_light` is true, copies of the meshes added, from which lit meshes are made.
+    unlit_meshes: BTreeMap<Index<gltf_json::Mesh>, SpaceMesh<GltfVertex, GltfTile>>,
+
+    /// Lit meshes made so far for each unlit mesh instance, so that they can be reused
+    /// when the light is unchanged.
+    lit_meshes: BTreeMap<MeshInstance, Vec<LitMesh>>,
+
     /// All flaws encountered so far.
     flaws: Flaws,
 }
@@ -114,12 +204,21 @@ impl GltfWriter {
 
             // TODO: Once texturing actually works, enable allocation here.
             texture_allocator: GltfTextureAllocator::new(buffer_dest.clone(), false),
+            texture_style: GltfTextureStyle::default(),
+            scene_layout: GltfSceneLayout::default(),
+            position_encoding: GltfPositionEncoding::default(),
+            normals: GltfNormals::default(),
+            meshes_by_content: HashMap::new(),
+            dequantizations: BTreeMap::new(),
 
             root,
             buffer_dest,
             camera: None,
             frame_states: Vec::new(),
             any_time_visible_mesh_instances: BTreeSet::new(),
+            bake_light: false,
+            unlit_meshes: BTreeMap::new(),
+            lit_meshes: BTreeMap::new(),
             flaws: Flaws::empty(),
         }
     }
@@ -130,6 +229,45 @@ impl GltfWriter {
         self.texture_allocator.clone()
     }
 
+    /// Sets how block textures are arranged into images; see [`GltfTextureStyle`].
+    ///
+    /// This may be changed at any time before [`GltfWriter::into_root()`].
+    pub fn set_texture_style(&mut self, style: GltfTextureStyle) {
+        self.texture_style = style;
+    }
+
+    /// Sets how [`GltfWriter::add_space()`] arranges the contents of spaces into meshes;
+    /// see [`GltfSceneLayout`].
+    pub fn set_scene_layout(&mut self, layout: GltfSceneLayout) {
+        self.scene_layout = layout;
+    }
+
+    /// Sets how vertex positions are stored; see [`GltfPositionEncoding`].
+    ///
+    /// This affects only meshes added after it is set.
+    pub fn set_position_encoding(&mut self, encoding: GltfPositionEncoding) {
+        self.position_encoding = encoding;
+    }
+
+    /// Sets which vertex normals are written; see [`GltfNormals`].
+    ///
+    /// This affects only meshes added after it is set.
+    pub fn set_normals(&mut self, normals: GltfNormals) {
+        self.normals = normals;
+    }
+
+    /// Sets whether [`GltfWriter::add_frame_with_light()`] bakes the light of each frame
+    /// into the vertex colors of the meshes, so that the animation shows changing light.
+    ///
+    /// This is disabled by default, because it may write a separate copy of each mesh for
+    /// every distinct lighting it is seen in. When it is disabled, frames will report
+    /// [`Flaws::NO_LIGHTING`].
+    ///
+    /// This must be set before calling [`GltfWriter::add_mesh()`] for the meshes to be lit.
+    pub fn set_bake_light(&mut self, bake_light: bool) {
+        self.bake_light = bake_light;
+    }
+
     /// Add one frame of an animated scene.
     ///
     /// `our_camera` should be the current camera state (its `view_transform`s in
@@ -139,7 +277,8 @@ impl GltfWriter {
     /// current frame; the meshes should have been produced by previous calls to
     /// [`GltfWriter::add_mesh()`].
     ///
-    /// Returns flaws which come from \[TODO: explain\].
+    /// Returns flaws which come from \[TODO: explain\]. Since no light is given, these
+    /// always include [`Flaws::NO_LIGHTING`]; see [`GltfWriter::add_frame_with_light()`].
     ///
     /// TODO: This is not a clean API yet; it was designed around the needs of
     /// `all-is-cubes-desktop`'s recording mode.
@@ -147,6 +286,36 @@ impl GltfWriter {
         &mut self,
         our_camera: Option<&Camera>,
         visible_meshes: &[MeshInstance],
+    ) -> Flaws {
+        self.flaws |= Flaws::NO_LIGHTING;
+        self.add_frame_state(our_camera, visible_meshes.to_vec())
+    }
+
+    /// Add one frame of an animated scene, as [`GltfWriter::add_frame()`] does, with the
+    /// meshes lit by the light in `light_source` as of this frame.
+    ///
+    /// The light is applied only if enabled by [`GltfWriter::set_bake_light()`];
+    /// otherwise, this is the same as [`GltfWriter::add_frame()`].
+    pub fn add_frame_with_light(
+        &mut self,
+        our_camera: Option<&Camera>,
+        visible_meshes: &[MeshInstance],
+        light_source: &Space,
+    ) -> Flaws {
+        if !self.bake_light {
+            return self.add_frame(our_camera, visible_meshes);
+        }
+        let lit_instances = visible_meshes
+            .iter()
+            .map(|&instance| self.lit_instance(instance, light_source))
+            .collect();
+        self.add_frame_state(our_camera, lit_instances)
+    }
+
+    fn add_frame_state(
+        &mut self,
+        our_camera: Option<&Camera>,
+        visible_meshes: Vec<MeshInstance>,
     ) -> Flaws {
         // Create camera if and only if one was given and we didn't have one.
         if self.camera.is_none() {
@@ -158,57 +327,160 @@ impl GltfWriter {
             }
         }
 
+        self.any_time_visible_mesh_instances
+            .extend(visible_meshes.iter());
         self.frame_states.push(FrameState {
-            visible_mesh_instances: visible_meshes.to_vec(),
+            visible_mesh_instances: visible_meshes,
             camera_transform: our_camera
                 .map_or_else(ViewTransform::one, |camera| camera.get_view_transform()),
         });
-        self.any_time_visible_mesh_instances
-            .extend(visible_meshes.iter());
 
         // TODO: report only flaws from this frame
         self.flaws
     }
 
+    /// Returns an instance of a copy of `instance`'s mesh with `light_source`'s light
+    /// baked in, creating the copy unless one with the same light already exists.
+    fn lit_instance(&mut self, instance: MeshInstance, light_source: &Space) -> MeshInstance {
+        // Temporarily take the unlit mesh so that we can write to `self`.
+        let Some(unlit_mesh) = self.unlit_meshes.remove(&instance.mesh) else {
+            // The mesh was added before light baking was enabled.
+            self.flaws |= Flaws::NO_LIGHTING;
+            return instance;
+        };
+
+        let light = light::sample_light(&unlit_mesh, instance.translation, light_source);
+        let existing = self
+            .lit_meshes
+            .get(&instance)
+            .and_then(|lit| lit.iter().find(|(l, _)| *l == light))
+            .map(|&(_, mesh)| mesh);
+        let lit_mesh = existing.or_else(|| {
+            let vertices = light::apply_light(unlit_mesh.vertices(), &light);
+            let name = format!(
+                "{mesh} lit {n}",
+                mesh = instance.mesh,
+                n = self.lit_meshes.get(&instance).map_or(0, Vec::len)
+            );
+            let lit_mesh = mesh::add_mesh(self, &name, &unlit_mesh, &vertices)?;
+            self.lit_meshes
+                .entry(instance)
+                .or_default()
+                .push((light, lit_mesh));
+            Some(lit_mesh)
+        });
+
+        self.unlit_meshes.insert(instance.mesh, unlit_mesh);
+        MeshInstance {
+            mesh: lit_mesh.unwrap_or(instance.mesh),
+            ..instance
+        }
+    }
+
     /// Add one [`SpaceMesh`] to the output.
     ///
     /// The mesh's texture allocator must be [`self.texture_allocator()`].
+    ///
+    /// If an identical mesh was previously added, this returns the index of that mesh
+    /// instead of storing the same data again (and `name` is not used). This avoids
+    /// duplication when a world change is undone, or in a cyclic animation.
     pub fn add_mesh(
         &mut self,
         name: &dyn fmt::Display,
         mesh: &SpaceMesh<GltfVertex, GltfTile>,
     ) -> Option<Index<gltf_json::Mesh>> {
-        // TODO: Deduplicate meshes so that we don't have to store the same data twice if
-        // a world change is undone, or in a cyclic animation (or if two chunks have the
-        // same contents — once we make chunks in relative coordinates).
-        mesh::add_mesh(self, name, mesh)
+        let hash = mesh::content_hash(mesh, self.position_encoding, self.normals);
+        if let Some(&index) = self.meshes_by_content.get(&hash) {
+            self.flaws |= mesh.flaws();
+            return Some(index);
+        }
+        let index = mesh::add_mesh(self, name, mesh, mesh.vertices());
+        if let Some(index) = index {
+            self.meshes_by_content.insert(hash, index);
+        }
+        if self.bake_light {
+            if let Some(index) = index {
+                self.unlit_meshes.insert(index, mesh.clone());
+            }
+        }
+        index
+    }
+
+    /// Add meshes for the current contents of `space`, arranged according to the
+    /// [`GltfSceneLayout`] set by [`GltfWriter::set_scene_layout()`], and return the
+    /// [`MeshInstance`]s which display them, to be passed to [`GltfWriter::add_frame()`].
+    pub fn add_space(&mut self, name: &dyn fmt::Display, space: &Space) -> Vec<MeshInstance> {
+        let options = MeshOptions::new(&GraphicsOptions::default());
+        let block_meshes: BlockMeshes<GltfVertex, GltfTile> =
+            block_meshes_for_space(space, &self.texture_allocator, &options);
+
+        match self.scene_layout {
+            GltfSceneLayout::Combined => {
+                // The mesh is positioned relative to the lower corner of the bounds.
+                let mesh = SpaceMesh::new(space, space.bounds(), &options, &*block_meshes);
+                self.add_mesh(name, &mesh)
+                    .map(|mesh| MeshInstance {
+                        mesh,
+                        translation: space.bounds().lower_bounds().into(),
+                    })
+                    .into_iter()
+                    .collect()
+            }
+            GltfSceneLayout::PerBlockInstances => {
+                // glTF mesh for each block index, added when first used.
+                // `None` means not yet added; `Some(None)` means the block has no mesh.
+                let mut block_gltf_meshes: Vec<Option<Option<Index<gltf_json::Mesh>>>> =
+                    vec![None; block_meshes.len()];
+                let mut instances = Vec::new();
+                for cube in space.bounds().interior_iter() {
+                    let block_index = usize::from(space.get_block_index(cube).unwrap());
+                    let gltf_mesh = *block_gltf_meshes[block_index].get_or_insert_with(|| {
+                        self.add_mesh(
+                            &format_args!("{name} block {block_index}"),
+                            &SpaceMesh::from(&block_meshes[block_index]),
+                        )
+                    });
+                    if let Some(mesh) = gltf_mesh {
+                        instances.push(MeshInstance {
+                            mesh,
+                            translation: cube.lower_bounds().into(),
+                        });
+                    }
+                }
+                instances
+            }
+        }
     }
 
     /// Finish all scene preparation and return the [`gltf_json::Root`] which is to be
     /// written to a JSON file.
-    pub fn into_root(mut self, frame_pace: Duration) -> io::Result<gltf_json::Root> {
+    pub fn into_root(mut self, frame_pace: Duration) -> Result<gltf_json::Root, ExportError> {
         if !self.texture_allocator.is_empty() {
-            let _block_texture_index =
-                texture::insert_block_texture_atlas(&mut self.root, &self.texture_allocator)?;
-
-            // TODO: Rewrite meshes to have texture coordinates and materials to designate
-            // the texture. Otherwise it's useless.
+            let tile_textures = texture::insert_block_textures(
+                &mut self.root,
+                &self.texture_allocator,
+                self.texture_style,
+            )?;
+            for (&(tile, _), &material) in &self.materials.textured {
+                self.root.materials[material.value()]
+                    .pbr_metallic_roughness
+                    .base_color_texture = Some(tile_textures[tile].clone());
+            }
         }
 
         let mut scene_nodes: Vec<Index<gltf_json::Node>> = Vec::new();
 
         // If we have a camera entity, create a node for it.
         if let Some(camera_index) = self.camera {
-            let mut camera_node = gltf_json::Node {
-                camera: Some(camera_index),
-                ..empty_node(None)
+            let camera_node = match self.frame_states.first() {
+                Some(initial_state) => {
+                    camera_node(None, camera_index, initial_state.camera_transform)
+                }
+                None => gltf_json::Node {
+                    camera: Some(camera_index),
+                    ..empty_node(None)
+                },
             };
-            if let Some(initial_state) = self.frame_states.get(0) {
-                let t = initial_state.camera_transform;
-                camera_node.translation = Some(t.disp.map(|c| c as f32).into());
-                camera_node.rotation = Some(convert_quaternion(t.rot));
-                camera_node.scale = Some([t.scale as f32; 3]);
-            }
             let camera_node_index = push_and_return_index(&mut self.root.nodes, camera_node);
             scene_nodes.push(camera_node_index);
 
@@ -222,13 +494,13 @@ impl GltfWriter {
         let mut instance_nodes: BTreeMap<MeshInstance, Index<gltf_json::Node>> = BTreeMap::new();
         for &instance in self.any_time_visible_mesh_instances.iter() {
             let MeshInstance { mesh, translation } = instance;
+            // TODO: give this node a name if we can figure out what a good, cheap one is
+            let mesh_node = mesh_node(&mut self.root, mesh, self.dequantizations.get(&mesh), None);
             let node_index = push_and_return_index(
                 &mut self.root.nodes,
                 gltf_json::Node {
-                    mesh: Some(mesh),
                     translation: Some(translation.map(|c| c as f32)),
-                    // TODO: give this node a name if we can figure out what a good, cheap one is
-                    ..empty_node(None)
+                    ..mesh_node
                 },
             );
             instance_nodes.insert(instance, node_index);
@@ -355,95 +627,315 @@ pub(crate) async fn export_gltf(
     source: ExportSet,
     destination: PathBuf,
 ) -> Result<(), ExportError> {
+    preflight_gltf(&source)?;
     let ExportSet {
         contents:
             PartialUniverse {
                 blocks: block_defs,
-                spaces,
+                spaces: _, // rejected by preflight_gltf()
                 characters,
             },
+        crop_spaces: _,         // rejected by preflight_gltf()
+        space_region: _,        // applied by export_to_path()
+        character_inventory: _, // applied by export_to_path()
+        pretty_json,
+        current_light: _, // applied by export_to_path()
     } = source;
 
-    // If unsupported list is nonempty, fail.
-    if let Some(first) = spaces.get(0) {
-        return Err(ExportError::NotRepresentable {
-            name: Some(first.name()),
-            reason: "Exporting spaces to glTF is not yet supported".into(),
-        });
-    }
-    if let Some(first) = characters.get(0) {
-        return Err(ExportError::NotRepresentable {
-            name: Some(first.name()),
-            reason: "Exporting characters to glTF is not yet supported".into(),
-        });
-    }
-
-    let mut writer = GltfWriter::new(GltfDataDestination::new(Some(destination.clone()), 2000));
+    // Data files are kept in memory until write_data_files() writes them, off the
+    // executor thread, after each member.
+    let data_destination = GltfDataDestination::new_deferred(destination.clone(), 2000);
+    let mut writer = GltfWriter::new(data_destination.clone());
     let mesh_options = MeshOptions::new(&GraphicsOptions::default());
 
-    for (mut p, block_def_ref) in progress.split_evenly(block_defs.len()).zip(block_defs) {
+    let [member_progress, mut write_progress] = progress.split(0.9);
+    let [block_progress, character_progress] = member_progress
+        .split(block_defs.len() as f32 / (block_defs.len() + characters.len()).max(1) as f32);
+
+    for (mut p, block_def_ref) in block_progress
+        .split_evenly(block_defs.len())
+        .zip(block_defs)
+    {
         let block_def = block_def_ref.read()?;
         let name = block_def_ref.name();
-        p.set_label(&name);
+        p.set_label(format!("Exporting block {name}"));
         p.progress(0.01).await;
-        let mesh = SpaceMesh::from(&BlockMesh::new(
-            &block_def
-                .evaluate()
-                .map_err(|eve| ExportError::NotRepresentable {
-                    name: Some(name.clone()),
-                    reason: format!("block evaluation failed: {eve}"),
-                })?,
-            &writer.texture_allocator(),
-            &mesh_options,
-        ));
-
-        let mesh_index = writer.add_mesh(&name, &mesh);
-        // TODO: if the mesh is empty/None, should we include the node anyway or not?
-        let mesh_node = push_and_return_index(
-            &mut writer.root.nodes,
-            gltf_json::Node {
-                mesh: mesh_index,
-                ..empty_node(Some(name.to_string()))
-            },
-        );
-
-        writer.root.scenes.push(json::Scene {
-            name: Some(format!("{name} display scene")),
-            nodes: vec![mesh_node],
-            extensions: None,
-            extras: Default::default(),
-        });
+        add_block_def(&mut writer, &name, &block_def, &mesh_options)?;
+        write_data_files(&data_destination).await?;
+        p.finish().await;
+    }
 
+    for (mut p, character_ref) in character_progress
+        .split_evenly(characters.len())
+        .zip(characters)
+    {
+        let character = character_ref.read()?;
+        let name = character_ref.name();
+        p.set_label(format!("Exporting character {name}"));
+        p.progress(0.01).await;
+        add_character_viewpoint(&mut writer, &name, &character);
         p.finish().await;
     }
 
+    write_progress.set_label("Writing files");
+    write_progress.progress(0.0).await;
+    let root = writer.into_root(Duration::from_secs(1))?;
+    write_data_files(&data_destination).await?;
+    write_progress.progress(0.5).await;
+    unblock(move || write_and_sync(&destination, |file| write_root(&root, file, pretty_json)))
+        .await?;
+    write_progress.finish().await;
+
+    Ok(())
+}
+
+/// Add a mesh of `block_def` and a scene displaying it.
+///
+/// If the block's last modifier is a [`Modifier::Rotate`], then the mesh is of the
+/// unrotated block, and the rotation is exported as the node's transform instead.
+fn add_block_def(
+    writer: &mut GltfWriter,
+    name: &Name,
+    block_def: &BlockDef,
+    mesh_options: &MeshOptions,
+) -> Result<(), ExportError> {
+    let mut block = Block::clone(block_def);
+    let node_transform = match block.modifiers().last() {
+        Some(&Modifier::Rotate(rotation)) => {
+            let transform = convert_block_rotation(rotation);
+            if transform.is_some() {
+                block.modifiers_mut().pop();
+            }
+            transform
+        }
+        _ => None,
+    };
+
+    let mesh = SpaceMesh::from(&BlockMesh::new(
+        &block
+            .evaluate()
+            .map_err(|eve| ExportError::NotRepresentable {
+                name: Some(name.clone()),
+                reason: format!("block evaluation failed: {eve}"),
+            })?,
+        &writer.texture_allocator(),
+        mesh_options,
+    ));
+
+    let mesh_index = writer.add_mesh(name, &mesh);
+    // TODO: if the mesh is empty/None, should we include the node anyway or not?
+    let mut mesh_node = match mesh_index {
+        Some(mesh_index) => mesh_node(
+            &mut writer.root,
+            mesh_index,
+            writer.dequantizations.get(&mesh_index),
+            Some(name.to_string()),
+        ),
+        None => empty_node(Some(name.to_string())),
+    };
+    if let Some((rotation, translation)) = node_transform {
+        mesh_node.rotation = Some(rotation);
+        mesh_node.translation = Some(translation);
+    }
+    let mesh_node = push_and_return_index(&mut writer.root.nodes, mesh_node);
+
+    writer.root.scenes.push(json::Scene {
+        name: Some(format!("{name} display scene")),
+        nodes: vec![mesh_node],
+        extensions: None,
+        extras: Default::default(),
+    });
+    Ok(())
+}
+
+/// Write the data files which have been written to `data_destination` so far, so that
+/// they do not all need to be kept in memory until the end.
+async fn write_data_files(data_destination: &GltfDataDestination) -> Result<(), ExportError> {
+    for DeferredFile { path, contents } in data_destination.take_deferred_files() {
+        // TODO: refuse to overwrite existing files unless we are also overwriting a corresponding .gltf
+        unblock(move || write_and_sync(&path, |file| Ok(file.write_all(&contents)?))).await?;
+    }
+    Ok(())
+}
+
+/// Add a camera and a scene showing the viewpoint of `character`.
+///
+/// The character's body, inventory, and behaviors are not exported.
+fn add_character_viewpoint(writer: &mut GltfWriter, name: &Name, character: &Character) {
+    if character
+        .inventory()
+        .slots
+        .iter()
+        .any(|slot| *slot != Slot::Empty)
     {
-        let file = fs::File::create(destination)?;
-        writer
-            .into_root(Duration::from_secs(1))?
-            .to_writer_pretty(&file) // TODO: non-pretty option
-            .map_err(|_| -> ExportError { todo!("serialization error conversion") })?;
-        file.sync_all()?;
+        log::warn!("glTF export of character {name} does not include its inventory");
     }
 
+    let mut camera = Camera::new(GraphicsOptions::default(), Viewport::ARBITRARY);
+    camera.set_view_transform(character.view());
+    let camera_index = push_and_return_index(
+        &mut writer.root.cameras,
+        convert_camera(Some(name.to_string()), &camera),
+    );
+    let node = push_and_return_index(
+        &mut writer.root.nodes,
+        camera_node(Some(name.to_string()), camera_index, character.view()),
+    );
+    writer.root.scenes.push(json::Scene {
+        name: Some(format!("{name} viewpoint scene")),
+        nodes: vec![node],
+        extensions: None,
+        extras: Default::default(),
+    });
+}
+
+/// Serialize `root` as JSON to `destination`.
+fn write_root(
+    root: &gltf_json::Root,
+    destination: impl io::Write,
+    pretty_json: bool,
+) -> Result<(), ExportError> {
+    if pretty_json {
+        root.to_writer_pretty(destination)
+    } else {
+        root.to_writer(destination)
+    }
+    // serde_json preserves IO errors, and reports other errors as `InvalidData`.
+    .map_err(|error| ExportError::from(io::Error::from(error)))
+}
+
+/// Create the file at `path`, write its contents using `contents_fn`, and wait for it
+/// to be stored.
+///
+/// This performs blocking IO, so it should be called via [`unblock()`].
+fn write_and_sync(
+    path: &Path,
+    contents_fn: impl FnOnce(&mut io::BufWriter<fs::File>) -> Result<(), ExportError>,
+) -> Result<(), ExportError> {
+    let write_error = |error| ExportError::Write {
+        path: Some(path.to_owned()),
+        error,
+    };
+    let mut file = io::BufWriter::new(fs::File::create(path).map_err(write_error)?);
+    contents_fn(&mut file).map_err(|error| error.with_path(path))?;
+    file.into_inner()
+        .map_err(|error| write_error(error.into_error()))?
+        .sync_all()
+        .map_err(write_error)
+}
+
+/// Check for members of `source` which cannot be exported to glTF.
+pub(crate) fn preflight_gltf(source: &ExportSet) -> Result<(), ExportError> {
+    let PartialUniverse {
+        blocks: _,
+        spaces,
+        characters: _, // exported as cameras
+    } = &source.contents;
+
+    source.check_cropping(ExportFormat::Gltf)?;
+
+    // If unsupported list is nonempty, fail.
+    if let Some(first) = spaces.first() {
+        return Err(ExportError::NotRepresentable {
+            name: Some(first.name()),
+            reason: "Exporting spaces to glTF is not yet supported".into(),
+        });
+    }
     Ok(())
 }
 
+/// Returns a node which displays `mesh`, not yet added to `root`.
+///
+/// If the mesh's positions were quantized, then the mesh is placed in a child node with
+/// the `dequantization` transform, so that the returned node's own transform is free
+/// to be set or animated.
+fn mesh_node(
+    root: &mut gltf_json::Root,
+    mesh: Index<gltf_json::Mesh>,
+    dequantization: Option<&Dequantization>,
+    name: Option<String>,
+) -> gltf_json::Node {
+    match dequantization {
+        None => gltf_json::Node {
+            mesh: Some(mesh),
+            ..empty_node(name)
+        },
+        Some(&Dequantization { translation, scale }) => {
+            let child = push_and_return_index(
+                &mut root.nodes,
+                gltf_json::Node {
+                    mesh: Some(mesh),
+                    translation: Some(translation),
+                    scale: Some(scale),
+                    ..empty_node(None)
+                },
+            );
+            gltf_json::Node {
+                children: Some(vec![child]),
+                ..empty_node(name)
+            }
+        }
+    }
+}
+
+/// Construct a node which places the given camera according to `view_transform`.
+fn camera_node(
+    name: Option<String>,
+    camera: Index<gltf_json::Camera>,
+    view_transform: ViewTransform,
+) -> gltf_json::Node {
+    gltf_json::Node {
+        camera: Some(camera),
+        translation: Some(view_transform.disp.map(|c| c as f32).into()),
+        rotation: Some(convert_quaternion(view_transform.rot.into())),
+        scale: Some([view_transform.scale as f32; 3]),
+        ..empty_node(name)
+    }
+}
+
 /// Construct gltf camera entity.
 /// Note that this is not complete since it does not contain the viewpoint; a node is also needed.
 fn convert_camera(name: Option<String>, camera: &Camera) -> gltf_json::Camera {
+    let options = camera.options();
+    let aspect_ratio = camera.viewport().nominal_aspect_ratio();
+    let zfar = camera.view_distance() as f32;
+    let znear = camera.near_plane() as f32;
+    let (type_, orthographic, perspective) = match options.projection {
+        ProjectionOption::Orthographic { height } => {
+            // glTF magnifications are half the extent of the view.
+            let ymag = height.into_inner() / 2.;
+            (
+                gltf_json::camera::Type::Orthographic,
+                Some(gltf_json::camera::Orthographic {
+                    xmag: (ymag * aspect_ratio) as f32,
+                    ymag: ymag as f32,
+                    zfar,
+                    znear,
+                    extensions: Default::default(),
+                    extras: Default::default(),
+                }),
+                None,
+            )
+        }
+        // `ProjectionOption` is non-exhaustive; fall back to perspective.
+        _ => (
+            gltf_json::camera::Type::Perspective,
+            None,
+            Some(gltf_json::camera::Perspective {
+                aspect_ratio: Some(aspect_ratio as f32),
+                yfov: options.fov_y.into_inner() as f32 * (std::f32::consts::PI / 180.),
+                zfar: Some(zfar),
+                znear,
+                extensions: Default::default(),
+                extras: Default::default(),
+            }),
+        ),
+    };
     gltf_json::Camera {
         name,
-        type_: Valid(gltf_json::camera::Type::Perspective),
-        orthographic: None,
-        perspective: Some(gltf_json::camera::Perspective {
-            aspect_ratio: Some(camera.viewport().nominal_aspect_ratio() as f32),
-            yfov: camera.options().fov_y.into_inner() as f32 * (std::f32::consts::PI / 180.),
-            zfar: Some(camera.options().view_distance.into_inner() as f32),
-            znear: 1. / 32., // TODO: expose this from `Camera`
-            extensions: Default::default(),
-            extras: Default::default(),
-        }),
+        type_: Valid(type_),
+        orthographic,
+        perspective,
         extensions: Default::default(),
         extras: Default::default(),
     }
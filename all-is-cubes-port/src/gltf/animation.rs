@@ -1,4 +1,3 @@
-use std::io;
 use std::time::Duration;
 
 use gltf_json::validation::Checked::Valid;
@@ -7,6 +6,7 @@ use gltf_json::Index;
 use super::buffer::create_buffer_and_accessor;
 use super::glue::{convert_quaternion, push_and_return_index};
 use super::{GltfWriter, MeshInstance};
+use crate::ExportError;
 
 #[derive(Debug)]
 pub(crate) struct FrameState {
@@ -26,7 +26,7 @@ pub(crate) fn add_camera_animation(
     writer: &mut GltfWriter,
     camera_node_index: Index<gltf_json::Node>,
     frame_pace: Duration,
-) -> io::Result<()> {
+) -> Result<(), ExportError> {
     let mut animation_channels = Vec::new();
     let mut animation_samplers = Vec::new();
 
@@ -87,7 +87,7 @@ pub(crate) fn add_camera_animation(
                     writer
                         .frame_states
                         .iter()
-                        .map(|frame| convert_quaternion(frame.camera_transform.rot).0),
+                        .map(|frame| convert_quaternion(frame.camera_transform.rot.into()).0),
                 )?,
                 extensions: Default::default(),
                 extras: Default::default(),
@@ -11,6 +11,7 @@ use std::sync::{Arc, Mutex};
 use gltf_json::Index;
 
 use super::glue::{create_accessor, push_and_return_index, u32size, Lef32};
+use crate::ExportError;
 
 /// Designates the location where glTF buffer data (meshes, textures) should be written
 /// (either to disk files or inline in the glTF JSON).
@@ -40,6 +41,17 @@ struct Inner {
     /// Filename suffixes (the 'bar' in `foo-bar.glbin`) that have already been used,
     /// tracked to ensure uniqueness.
     suffix_uses: Mutex<HashSet<String>>,
+
+    /// If not `None`, data files are not written immediately, but collected here to be
+    /// written later by [`GltfDataDestination::take_deferred_files()`]'s caller.
+    deferred_files: Option<Mutex<Vec<DeferredFile>>>,
+}
+
+/// A data file which [`GltfDataDestination`] has not yet written to disk.
+#[derive(Debug)]
+pub(crate) struct DeferredFile {
+    pub path: PathBuf,
+    pub contents: Vec<u8>,
 }
 
 impl GltfDataDestination {
@@ -50,6 +62,7 @@ impl GltfDataDestination {
             maximum_inline_length: 0,
             file_base_path: None,
             suffix_uses: Mutex::new(HashSet::new()),
+            deferred_files: None,
         }))
     }
 
@@ -66,9 +79,32 @@ impl GltfDataDestination {
             maximum_inline_length,
             file_base_path,
             suffix_uses: Mutex::new(HashSet::new()),
+            deferred_files: None,
+        }))
+    }
+
+    /// Like [`GltfDataDestination::new()`], but data files are kept in memory instead of
+    /// being written, so that the caller can write them without blocking, using
+    /// [`GltfDataDestination::take_deferred_files()`].
+    pub(crate) fn new_deferred(file_base_path: PathBuf, maximum_inline_length: usize) -> Self {
+        Self(Arc::new(Inner {
+            discard: false,
+            maximum_inline_length,
+            file_base_path: Some(file_base_path),
+            suffix_uses: Mutex::new(HashSet::new()),
+            deferred_files: Some(Mutex::new(Vec::new())),
         }))
     }
 
+    /// Remove and return all data files which were written to this destination since
+    /// the last call, if it was created by [`GltfDataDestination::new_deferred()`].
+    pub(crate) fn take_deferred_files(&self) -> Vec<DeferredFile> {
+        match &self.0.deferred_files {
+            Some(files) => std::mem::take(&mut *files.lock().unwrap_or_else(|e| e.into_inner())),
+            None => Vec::new(),
+        }
+    }
+
     /// Write glTF buffer data, then return a [`gltf_json::Buffer`] pointing to it by
     /// one of the permitted means.
     ///
@@ -82,23 +118,21 @@ impl GltfDataDestination {
     ///
     /// # Errors
     ///
-    /// Returns `Err` if:
+    /// Returns [`ExportError::Write`] if:
     ///
-    /// * An IO error occurs while writing.
+    /// * An IO error occurs while writing. If a data file was to be written, the error
+    ///   includes its path.
     /// * The data file path constructed using `self`'s base file path is not UTF-8.
     ///
     /// The outcome is not specified if IO errors from the writer given to `contents_fn`
     /// are ignored rather than propagated.
-    //
-    // ---
-    // TODO: Add context (filename) to the IO error
     pub fn write<F>(
         &self,
         buffer_entity_name: String,
         proposed_file_name: &str,
         proposed_file_extension: &str,
         contents_fn: F,
-    ) -> io::Result<gltf_json::Buffer>
+    ) -> Result<gltf_json::Buffer, ExportError>
     where
         F: FnOnce(&mut dyn io::Write) -> io::Result<()>,
     {
@@ -152,6 +186,7 @@ impl GltfDataDestination {
                 limit: self.0.maximum_inline_length,
                 path: Some(buffer_file_path),
                 future_file_uri: Some(relative_url),
+                defer: self.0.deferred_files.is_some(),
             }
         } else {
             SwitchingWriter::Memory {
@@ -159,12 +194,29 @@ impl GltfDataDestination {
                 limit: self.0.maximum_inline_length,
                 path: None,
                 future_file_uri: None,
+                defer: false,
             }
         };
 
+        let file_path = match &implementation {
+            SwitchingWriter::Memory { path, .. } => path.clone(),
+            _ => None,
+        };
+        let write_error = |error| ExportError::Write {
+            path: file_path.clone(),
+            error,
+        };
+
         // Write data to file
-        contents_fn(&mut implementation)?;
-        let (uri, byte_length) = implementation.close()?;
+        contents_fn(&mut implementation).map_err(write_error)?;
+        let (uri, byte_length, deferred_file) = implementation.close().map_err(write_error)?;
+        if let (Some(deferred_file), Some(deferred_files)) = (deferred_file, &self.0.deferred_files)
+        {
+            deferred_files
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .push(deferred_file);
+        }
 
         Ok(gltf_json::Buffer {
             byte_length: u32size(byte_length),
@@ -198,6 +250,14 @@ enum SwitchingWriter {
         limit: usize,
         future_file_uri: Option<String>,
         path: Option<PathBuf>,
+        /// Whether to switch to [`SwitchingWriter::Deferred`] instead of
+        /// [`SwitchingWriter::File`] when the limit is exceeded.
+        defer: bool,
+    },
+    Deferred {
+        buffer: Vec<u8>,
+        path: PathBuf,
+        file_uri: Option<String>,
     },
     File {
         file: io::BufWriter<File>,
@@ -207,10 +267,11 @@ enum SwitchingWriter {
 }
 
 impl SwitchingWriter {
-    /// Close the file (if any) and return the uri and the bytes written.
-    fn close(self) -> io::Result<(Option<String>, usize)> {
+    /// Close the file (if any) and return the uri, the bytes written, and the file still
+    /// to be written (if deferred).
+    fn close(self) -> io::Result<(Option<String>, usize, Option<DeferredFile>)> {
         match self {
-            SwitchingWriter::Null { bytes_written } => Ok((None, bytes_written)),
+            SwitchingWriter::Null { bytes_written } => Ok((None, bytes_written, None)),
             SwitchingWriter::Memory { buffer, .. } => {
                 use base64::Engine as _;
 
@@ -222,8 +283,20 @@ impl SwitchingWriter {
                 // in question is for e.g. base64 components within ordinary URLs or
                 // file names.
                 base64::engine::general_purpose::STANDARD_NO_PAD.encode_string(&buffer, &mut url);
-                Ok((Some(url), buffer.len()))
+                Ok((Some(url), buffer.len(), None))
             }
+            SwitchingWriter::Deferred {
+                buffer,
+                path,
+                file_uri,
+            } => Ok((
+                file_uri,
+                buffer.len(),
+                Some(DeferredFile {
+                    path,
+                    contents: buffer,
+                }),
+            )),
             SwitchingWriter::File {
                 bytes_written,
                 file,
@@ -235,7 +308,7 @@ impl SwitchingWriter {
                 // clippy false positive when this code is compiled for wasm -- TODO: remove the file support when compiling for wasm
                 #[allow(clippy::drop_non_drop)]
                 drop(file);
-                Ok((file_uri, bytes_written))
+                Ok((file_uri, bytes_written, None))
             }
         }
     }
@@ -255,6 +328,7 @@ impl io::Write for SwitchingWriter {
                 limit,
                 ref path,
                 ref future_file_uri,
+                defer,
             } => {
                 let n = buffer.write(bytes)?;
                 if buffer.len() > limit {
@@ -264,6 +338,14 @@ impl io::Write for SwitchingWriter {
                             format!("no destination was provided for glTF buffers > {limit} bytes"),
                         )
                     })?;
+                    if defer {
+                        *self = SwitchingWriter::Deferred {
+                            buffer: std::mem::take(buffer),
+                            path: path.clone(),
+                            file_uri: future_file_uri.clone(),
+                        };
+                        return Ok(n);
+                    }
                     // TODO: refuse to overwrite existing files unless we are also overwriting a corresponding .gltf
                     let file = File::create(path)?;
                     let mut new_writer = SwitchingWriter::File {
@@ -276,6 +358,7 @@ impl io::Write for SwitchingWriter {
                 }
                 Ok(n)
             }
+            SwitchingWriter::Deferred { ref mut buffer, .. } => buffer.write(bytes),
             SwitchingWriter::File {
                 ref mut file,
                 ref mut bytes_written,
@@ -292,6 +375,7 @@ impl io::Write for SwitchingWriter {
         match self {
             SwitchingWriter::Null { .. } => Ok(()),
             SwitchingWriter::Memory { .. } => Ok(()),
+            SwitchingWriter::Deferred { .. } => Ok(()),
             SwitchingWriter::File { file, .. } => file.flush(),
         }
     }
@@ -310,7 +394,7 @@ pub(crate) fn create_buffer_and_accessor<I, const COMPONENTS: usize>(
     name: String,
     file_suffix: &str,
     data_source: I,
-) -> io::Result<Index<gltf_json::Accessor>>
+) -> Result<Index<gltf_json::Accessor>, ExportError>
 where
     I: IntoIterator<Item = [f32; COMPONENTS]> + Clone,
     I::IntoIter: ExactSizeIterator,
@@ -400,9 +484,39 @@ mod tests {
         let error = d
             .write("foo".into(), "bar", "glbin", |w| w.write_all(&[1, 2, 255]))
             .unwrap_err();
+        match error {
+            ExportError::Write { path: None, error } => assert_eq!(
+                error.to_string(),
+                "no destination was provided for glTF buffers > 1 bytes"
+            ),
+            other => panic!("unexpected error {other:?}"),
+        }
+    }
+
+    #[test]
+    fn file_failure_reports_path() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let file_base_path = temp_dir.path().join("nonexistent").join("basepath.gltf");
+
+        let d = GltfDataDestination::new(Some(file_base_path), 0);
+        let error = d.write("foo".into(), "bar", "glbin", write1).unwrap_err();
+        let expected_path = temp_dir
+            .path()
+            .join("nonexistent")
+            .join("basepath-bar.glbin");
+        match error {
+            ExportError::Write {
+                path: Some(ref path),
+                ..
+            } => assert_eq!(*path, expected_path),
+            ref other => panic!("unexpected error {other:?}"),
+        }
         assert_eq!(
             error.to_string(),
-            "no destination was provided for glTF buffers > 1 bytes"
+            format!(
+                "could not write export data to '{}'",
+                expected_path.display()
+            )
         );
     }
 
@@ -443,6 +557,39 @@ mod tests {
         assert_eq!(e2.uri.as_deref(), Some("basepath-bar-2.glbin"));
     }
 
+    #[test]
+    fn deferred_switch_to_file() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let file_base_path = temp_dir.path().join("basepath.gltf");
+
+        let d = GltfDataDestination::new_deferred(file_base_path, 3);
+        let inline_entity = d
+            .write("foo".into(), "inline", "glbin", |w| w.write_all(&[1, 2, 3]))
+            .unwrap();
+        let file_entity = d
+            .write("foo".into(), "bar", "glbin", |w| {
+                w.write_all(&[1, 2, 3])?;
+                w.write_all(&[4, 5, 6])?;
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(
+            inline_entity.uri.as_deref(),
+            Some("data:application/gltf-buffer;base64,AQID")
+        );
+        assert_eq!(file_entity.uri.as_deref(), Some("basepath-bar.glbin"));
+        assert_eq!(file_entity.byte_length, 6);
+
+        // Nothing was written yet; the file is handed to the caller instead.
+        let expected_path = temp_dir.path().join("basepath-bar.glbin");
+        assert!(!expected_path.exists());
+        let files = d.take_deferred_files();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, expected_path);
+        assert_eq!(files[0].contents, vec![1, 2, 3, 4, 5, 6]);
+        assert!(d.take_deferred_files().is_empty());
+    }
+
     /// Write one byte to make the buffer nonempty.
     fn write1(w: &mut dyn io::Write) -> io::Result<()> {
         w.write_all(&[0])
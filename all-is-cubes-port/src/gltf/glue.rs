@@ -2,7 +2,8 @@
 
 use std::fmt;
 
-use all_is_cubes::cgmath;
+use all_is_cubes::cgmath::{self, Rotation as _};
+use all_is_cubes::math::GridRotation;
 
 use gltf_json::validation::Checked::Valid;
 use gltf_json::Index;
@@ -95,12 +96,23 @@ impl From<Lef32> for f32 {
     }
 }
 
-pub(crate) fn convert_quaternion(rot: cgmath::Basis3<f64>) -> gltf_json::scene::UnitQuaternion {
-    let q: cgmath::Quaternion<f64> = rot.into();
+pub(crate) fn convert_quaternion(q: cgmath::Quaternion<f64>) -> gltf_json::scene::UnitQuaternion {
     let q: cgmath::Quaternion<f32> = q.cast().unwrap();
     gltf_json::scene::UnitQuaternion([q.v.x, q.v.y, q.v.z, q.s])
 }
 
+/// Returns the node rotation and translation which rotate a block about its center, as
+/// [`Modifier::Rotate`](all_is_cubes::block::Modifier::Rotate) does, or [`None`] if
+/// `rotation` is a reflection, which a node rotation cannot represent.
+pub(crate) fn convert_block_rotation(
+    rotation: GridRotation,
+) -> Option<(gltf_json::scene::UnitQuaternion, [f32; 3])> {
+    let q = rotation.to_quaternion()?;
+    let center = cgmath::Vector3::new(0.5, 0.5, 0.5);
+    let translation = (center - q.rotate_vector(center)).map(|c| c as f32);
+    Some((convert_quaternion(q), translation.into()))
+}
+
 /// Shorthand to construct a `Node` having no contents until modified.
 pub(crate) fn empty_node(name: Option<String>) -> gltf_json::Node {
     gltf_json::Node {
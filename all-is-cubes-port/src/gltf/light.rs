@@ -0,0 +1,66 @@
+//! Baking [`Space`] light data into glTF vertex colors.
+
+use all_is_cubes::cgmath::{InnerSpace as _, Vector3, Zero as _};
+use all_is_cubes::math::{Cube, FreeCoordinate, Rgb, Rgba};
+use all_is_cubes::space::Space;
+use all_is_cubes_mesh::{GfxVertex as _, SpaceMesh};
+
+use super::glue::Lef32;
+use super::{normal, GltfTile, GltfVertex};
+
+/// Distance to move each vertex off its surface before looking up the light, so that
+/// the cube sampled is the one the surface faces. Much smaller than any voxel.
+const SURFACE_OFFSET: FreeCoordinate = 1. / 1024.;
+
+/// Look up the light falling on each vertex of `mesh`, when it is placed at `translation`
+/// in `light_source`.
+pub(crate) fn sample_light(
+    mesh: &SpaceMesh<GltfVertex, GltfTile>,
+    translation: [i32; 3],
+    light_source: &Space,
+) -> Vec<Rgb> {
+    let vertices = mesh.vertices();
+
+    let normals = normal::face_normals(vertices, mesh.indices());
+
+    let translation = Vector3::from(translation).map(FreeCoordinate::from);
+    vertices
+        .iter()
+        .zip(normals)
+        .map(|(vertex, normal)| {
+            let normal =
+                normal.map_or_else(Vector3::zero, |n| n.normalize().map(FreeCoordinate::from));
+            let point =
+                vertex.position().map(FreeCoordinate::from) + translation + normal * SURFACE_OFFSET;
+            Cube::containing(point).map_or(Rgb::ONE, |cube| light_source.get_lighting(cube).value())
+        })
+        .collect()
+}
+
+/// Returns a copy of `vertices` with their colors multiplied by the corresponding `light`.
+///
+/// Textured vertices are left unchanged, since their colors are placeholders.
+pub(crate) fn apply_light(vertices: &[GltfVertex], light: &[Rgb]) -> Vec<GltfVertex> {
+    vertices
+        .iter()
+        .zip(light)
+        .map(|(&vertex, &light)| {
+            let [r, g, b, a] = vertex.base_color.map(f32::from);
+            if a < 0. {
+                // Texture placeholder; see `GltfVertex::from()`.
+                return vertex;
+            }
+            let lit = Rgb::new(r, g, b) * light;
+            let lit = Rgba::new(
+                lit.red().into_inner(),
+                lit.green().into_inner(),
+                lit.blue().into_inner(),
+                a,
+            );
+            GltfVertex {
+                base_color: Lef32::from_vec4(lit.clamp().into()),
+                ..vertex
+            }
+        })
+        .collect()
+}
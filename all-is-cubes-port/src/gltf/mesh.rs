@@ -1,35 +1,158 @@
 use std::collections::BTreeMap;
 use std::fmt;
 use std::mem::size_of;
+use std::ops::Range;
 
 use bytemuck::offset_of;
 use gltf_json::extras::Void;
 use gltf_json::validation::Checked::Valid;
 use gltf_json::Index;
+use sha2::{Digest as _, Sha256};
 
 use all_is_cubes_mesh::{IndexSlice, SpaceMesh};
 
-use super::glue::{create_accessor, push_and_return_index, u32size};
-use super::{GltfTile, GltfVertex, GltfWriter};
+use super::glue::{accessor_minmax, create_accessor, push_and_return_index, u32size, Lef32};
+use super::{normal, GltfNormals, GltfPositionEncoding, GltfTile, GltfVertex, GltfWriter};
 
 /// Create [`gltf_json::Mesh`] and all its parts (accessors, buffers) from a [`SpaceMesh`].
 ///
+/// `vertices` are written in place of `mesh`'s own vertices, which allows writing a
+/// modified copy of the mesh; they must be equal in number.
+///
 /// If the input is empty, does nothing and returns `None`.
 pub(crate) fn add_mesh(
     writer: &mut GltfWriter,
     name: &dyn fmt::Display,
     mesh: &SpaceMesh<GltfVertex, GltfTile>,
+    vertices: &[GltfVertex],
 ) -> Option<Index<gltf_json::Mesh>> {
+    assert_eq!(vertices.len(), mesh.vertices().len());
     if mesh.is_empty() {
         return None;
     }
 
-    let vertex_bytes = bytemuck::cast_slice::<GltfVertex, u8>(mesh.vertices());
+    // If the mesh is textured, replace the texture placeholders with texture coordinates
+    // within each tile's texture.
+    let resolved_vertices: Vec<GltfVertex>;
+    let mut vertex_tiles: Vec<Option<usize>> = Vec::new();
+    let vertices = if vertices.iter().any(|v| v.texture_placeholder().is_some()) {
+        let mut copy = vertices.to_vec();
+        vertex_tiles = writer
+            .texture_allocator
+            .resolve_texture_placeholders(&mut copy);
+        resolved_vertices = copy;
+        &resolved_vertices[..]
+    } else {
+        vertices
+    };
+
+    // Each primitive has a single material, so textured triangles must be split into
+    // a primitive per tile. To do that, the indices of each range are reordered so that
+    // the triangles using each material are contiguous.
+    let mut reordered_indices: Option<Vec<u32>> = None;
+    let original_indices: Vec<u32> = if vertex_tiles.is_empty() {
+        Vec::new()
+    } else {
+        mesh.indices().iter_u32().collect()
+    };
+    let mut primitive_ranges: Vec<(Range<usize>, Index<gltf_json::Material>, String)> = Vec::new();
+    for (index_range, transparent) in [
+        (mesh.opaque_range(), false),
+        (
+            mesh.transparent_range(all_is_cubes_mesh::DepthOrdering::Any),
+            true,
+        ),
+    ] {
+        let range_name = if transparent { "transparent" } else { "opaque" };
+        if vertex_tiles.is_empty() {
+            primitive_ranges.push((
+                index_range,
+                writer.materials.vertex_colored(transparent),
+                format!("{name} {range_name} index"),
+            ));
+            continue;
+        }
+
+        let reordered = reordered_indices.get_or_insert_with(Vec::new);
+        let mut triangles_by_tile: BTreeMap<Option<usize>, Vec<u32>> = BTreeMap::new();
+        for triangle in original_indices[index_range].chunks_exact(3) {
+            // All vertices of a triangle come from the same plane, so checking one suffices.
+            triangles_by_tile
+                .entry(vertex_tiles[triangle[0] as usize])
+                .or_default()
+                .extend_from_slice(triangle);
+        }
+        for (tile, triangles) in triangles_by_tile {
+            let start = reordered.len();
+            reordered.extend(triangles);
+            let (material, primitive_name) = match tile {
+                None => (
+                    writer.materials.vertex_colored(transparent),
+                    format!("{name} {range_name} index"),
+                ),
+                Some(tile) => (
+                    writer
+                        .materials
+                        .textured(&mut writer.root.materials, tile, transparent),
+                    format!("{name} {range_name} tile {tile} index"),
+                ),
+            };
+            primitive_ranges.push((start..reordered.len(), material, primitive_name));
+        }
+    }
+
     let index_type = match mesh.indices() {
         IndexSlice::U16(_) => gltf_json::accessor::ComponentType::U16,
         IndexSlice::U32(_) => gltf_json::accessor::ComponentType::U32,
     };
 
+    // If quantizing, positions are written in a separate buffer view from the other
+    // attributes, since they have a different stride.
+    let quantized = match writer.position_encoding {
+        GltfPositionEncoding::F32 => None,
+        GltfPositionEncoding::NormalizedI16 => {
+            for list in [
+                &mut writer.root.extensions_used,
+                &mut writer.root.extensions_required,
+            ] {
+                if !list.iter().any(|e| e == QUANTIZATION_EXTENSION) {
+                    list.push(QUANTIZATION_EXTENSION.into());
+                }
+            }
+            Some(quantize_positions(vertices))
+        }
+    };
+    let mut vertex_bytes: Vec<u8> = match &quantized {
+        None => bytemuck::cast_slice::<GltfVertex, u8>(vertices).to_vec(),
+        Some((positions, _)) => {
+            let mut bytes = Vec::with_capacity(
+                vertices.len() * (QUANTIZED_POSITION_STRIDE + ATTRIBUTES_STRIDE),
+            );
+            for position in positions {
+                for component in position {
+                    bytes.extend_from_slice(&component.to_le_bytes());
+                }
+                // Padding to keep vertex attributes 4-byte aligned, as glTF requires.
+                bytes.extend_from_slice(&[0, 0]);
+            }
+            for vertex in vertices {
+                bytes.extend_from_slice(bytemuck::bytes_of(&vertex.base_color));
+                bytes.extend_from_slice(bytemuck::bytes_of(&vertex.base_color_tc));
+            }
+            bytes
+        }
+    };
+
+    // Normals, if any, are written after the other vertex data, as their own buffer view.
+    let normals = match writer.normals {
+        GltfNormals::Flat => None,
+        GltfNormals::Smooth => Some(normal::smooth_normals(vertices, mesh.indices())),
+    };
+    let normals_offset = vertex_bytes.len();
+    for component in normals.iter().flatten().flatten() {
+        vertex_bytes.extend_from_slice(&component.to_le_bytes());
+    }
+
     // TODO: use the given name (sanitized) in the file name
     let buffer_entity = writer
         .buffer_dest
@@ -38,43 +161,120 @@ pub(crate) fn add_mesh(
             &format!("mesh-{i}", i = writer.root.buffers.len()),
             "glbin",
             |w| {
-                w.write_all(vertex_bytes)?;
+                w.write_all(&vertex_bytes)?;
                 // Convert index bytes to little-endian
-                match mesh.indices() {
-                    IndexSlice::U16(slice) => {
+                match (&reordered_indices, mesh.indices()) {
+                    (None, IndexSlice::U16(slice)) => {
                         for index in slice {
                             w.write_all(&index.to_le_bytes())?;
                         }
                     }
-                    IndexSlice::U32(slice) => {
+                    (None, IndexSlice::U32(slice)) => {
                         for index in slice {
                             w.write_all(&index.to_le_bytes())?;
                         }
                     }
+                    // Reordered indices are still within the range of the original type.
+                    (Some(reordered), IndexSlice::U16(_)) => {
+                        for &index in reordered {
+                            w.write_all(&(index as u16).to_le_bytes())?;
+                        }
+                    }
+                    (Some(reordered), IndexSlice::U32(_)) => {
+                        for index in reordered {
+                            w.write_all(&index.to_le_bytes())?;
+                        }
+                    }
                 }
                 Ok(())
             },
         )
         .expect("buffer write error");
     let buffer_index = push_and_return_index(&mut writer.root.buffers, buffer_entity);
-    let vertex_buffer_view = push_and_return_index(
-        &mut writer.root.buffer_views,
-        gltf_json::buffer::View {
-            buffer: buffer_index,
-            byte_length: u32size(vertex_bytes.len()),
-            byte_offset: None,
-            byte_stride: Some(u32size(size_of::<GltfVertex>())),
-            name: Some(format!("{name} vertex")),
-            target: Some(Valid(gltf_json::buffer::Target::ArrayBuffer)),
-            extensions: Default::default(),
-            extras: Default::default(),
-        },
-    );
+    let mut vertex_buffer_view = |name: String, byte_offset: usize, count: usize, stride: usize| {
+        push_and_return_index(
+            &mut writer.root.buffer_views,
+            gltf_json::buffer::View {
+                buffer: buffer_index,
+                byte_length: u32size(count * stride),
+                byte_offset: (byte_offset != 0).then(|| u32size(byte_offset)),
+                byte_stride: Some(u32size(stride)),
+                name: Some(name),
+                target: Some(Valid(gltf_json::buffer::Target::ArrayBuffer)),
+                extensions: Default::default(),
+                extras: Default::default(),
+            },
+        )
+    };
+    let (position_accessor, attributes_view, attributes_offset) = match &quantized {
+        None => {
+            let view = vertex_buffer_view(
+                format!("{name} vertex"),
+                0,
+                vertices.len(),
+                size_of::<GltfVertex>(),
+            );
+            let position_accessor = create_accessor(
+                format!("{name} position"),
+                view,
+                offset_of!(GltfVertex::DUMMY, GltfVertex, position),
+                vertices.iter().map(|v| v.position.map(f32::from)),
+            );
+            (position_accessor, view, 0)
+        }
+        Some((positions, _)) => {
+            let position_view = vertex_buffer_view(
+                format!("{name} vertex position"),
+                0,
+                vertices.len(),
+                QUANTIZED_POSITION_STRIDE,
+            );
+            let attributes_view = vertex_buffer_view(
+                format!("{name} vertex attributes"),
+                vertices.len() * QUANTIZED_POSITION_STRIDE,
+                vertices.len(),
+                ATTRIBUTES_STRIDE,
+            );
+            // The min and max are of the stored integers, not the dequantized values.
+            let [min, max] = accessor_minmax(positions.iter().map(|p| p.map(f32::from)));
+            let position_accessor = gltf_json::Accessor {
+                buffer_view: Some(position_view),
+                byte_offset: None,
+                count: u32size(positions.len()),
+                component_type: Valid(gltf_json::accessor::GenericComponentType(
+                    gltf_json::accessor::ComponentType::I16,
+                )),
+                type_: Valid(gltf_json::accessor::Type::Vec3),
+                min,
+                max,
+                name: Some(format!("{name} position")),
+                normalized: true,
+                sparse: None,
+                extensions: Default::default(),
+                extras: Default::default(),
+            };
+            // The other attributes are written without the position field.
+            let attributes_offset = size_of::<[Lef32; 3]>();
+            (position_accessor, attributes_view, attributes_offset)
+        }
+    };
+    let normal_accessor = normals.map(|normals| {
+        let view = vertex_buffer_view(
+            format!("{name} vertex normal"),
+            normals_offset,
+            normals.len(),
+            size_of::<[f32; 3]>(),
+        );
+        create_accessor(format!("{name} normal"), view, 0, normals)
+    });
     let index_buffer_view = push_and_return_index(
         &mut writer.root.buffer_views,
         gltf_json::buffer::View {
             buffer: buffer_index,
-            byte_length: u32size(mesh.indices().as_bytes().len()),
+            byte_length: u32size(match &reordered_indices {
+                None => mesh.indices().as_bytes().len(),
+                Some(reordered) => reordered.len() * index_type.size(),
+            }),
             // Indexes are packed into the same buffer, so they start at the end of the vertex bytes
             byte_offset: Some(u32size(vertex_bytes.len())),
             byte_stride: None,
@@ -86,18 +286,10 @@ pub(crate) fn add_mesh(
         },
     );
 
-    let vertex_colored_attributes = BTreeMap::from([
+    let mut attributes = BTreeMap::from([
         (
             Valid(gltf_json::mesh::Semantic::Positions),
-            push_and_return_index(
-                &mut writer.root.accessors,
-                create_accessor(
-                    format!("{name} position"),
-                    vertex_buffer_view,
-                    offset_of!(GltfVertex::DUMMY, GltfVertex, position),
-                    mesh.vertices().iter().map(|v| v.position.map(f32::from)),
-                ),
-            ),
+            push_and_return_index(&mut writer.root.accessors, position_accessor),
         ),
         (
             Valid(gltf_json::mesh::Semantic::Colors(0)),
@@ -105,9 +297,9 @@ pub(crate) fn add_mesh(
                 &mut writer.root.accessors,
                 create_accessor(
                     format!("{name} base color"),
-                    vertex_buffer_view,
-                    offset_of!(GltfVertex::DUMMY, GltfVertex, base_color),
-                    mesh.vertices().iter().map(|v| v.base_color.map(f32::from)),
+                    attributes_view,
+                    offset_of!(GltfVertex::DUMMY, GltfVertex, base_color) - attributes_offset,
+                    vertices.iter().map(|v| v.base_color.map(f32::from)),
                 ),
             ),
         ),
@@ -117,78 +309,164 @@ pub(crate) fn add_mesh(
                 &mut writer.root.accessors,
                 create_accessor(
                     format!("{name} base color texcoords"),
-                    vertex_buffer_view,
-                    offset_of!(GltfVertex::DUMMY, GltfVertex, base_color_tc),
-                    mesh.vertices()
-                        .iter()
-                        .map(|v| v.base_color_tc.map(f32::from)),
+                    attributes_view,
+                    offset_of!(GltfVertex::DUMMY, GltfVertex, base_color_tc) - attributes_offset,
+                    vertices.iter().map(|v| v.base_color_tc.map(f32::from)),
                 ),
             ),
         ),
     ]);
 
+    if let Some(normal_accessor) = normal_accessor {
+        attributes.insert(
+            Valid(gltf_json::mesh::Semantic::Normals),
+            push_and_return_index(&mut writer.root.accessors, normal_accessor),
+        );
+    }
+
     writer.flaws |= mesh.flaws();
 
     let mesh_index = push_and_return_index(
         &mut writer.root.meshes,
         gltf_json::Mesh {
             name: Some(format!("{name} mesh")),
-            primitives: [
-                (
-                    mesh.opaque_range(),
-                    writer.materials.opaque_vertex_colored,
-                    format!("{name} opaque index"),
-                ),
-                (
-                    mesh.transparent_range(all_is_cubes_mesh::DepthOrdering::Any),
-                    writer.materials.transparent_vertex_colored,
-                    format!("{name} transparent index"),
-                ),
-            ]
-            .into_iter()
-            .filter_map(|(index_range, material, name)| {
-                if !index_range.is_empty() {
-                    Some(gltf_json::mesh::Primitive {
-                        attributes: vertex_colored_attributes.clone(),
-                        indices: Some(push_and_return_index(
-                            &mut writer.root.accessors,
-                            gltf_json::Accessor {
-                                buffer_view: Some(index_buffer_view),
-                                byte_offset: Some(u32size(index_range.start * index_type.size())),
-                                count: u32size(index_range.len()),
-                                component_type: Valid(gltf_json::accessor::GenericComponentType(
-                                    index_type,
-                                )),
-                                extensions: Default::default(),
-                                extras: Default::default(),
-                                type_: Valid(gltf_json::accessor::Type::Scalar),
-                                min: None,
-                                max: None,
-                                name: Some(name),
-                                normalized: false,
-                                sparse: None,
-                            },
-                        )),
-                        mode: Valid(gltf_json::mesh::Mode::Triangles),
-                        material: Some(material),
-                        targets: None,
-                        extensions: Default::default(),
-                        extras: Default::default(),
-                    })
-                } else {
-                    None
-                }
-            })
-            .collect(),
+            primitives: primitive_ranges
+                .into_iter()
+                .filter_map(|(index_range, material, name)| {
+                    if !index_range.is_empty() {
+                        Some(gltf_json::mesh::Primitive {
+                            attributes: attributes.clone(),
+                            indices: Some(push_and_return_index(
+                                &mut writer.root.accessors,
+                                gltf_json::Accessor {
+                                    buffer_view: Some(index_buffer_view),
+                                    byte_offset: Some(u32size(
+                                        index_range.start * index_type.size(),
+                                    )),
+                                    count: u32size(index_range.len()),
+                                    component_type: Valid(
+                                        gltf_json::accessor::GenericComponentType(index_type),
+                                    ),
+                                    extensions: Default::default(),
+                                    extras: Default::default(),
+                                    type_: Valid(gltf_json::accessor::Type::Scalar),
+                                    min: None,
+                                    max: None,
+                                    name: Some(name),
+                                    normalized: false,
+                                    sparse: None,
+                                },
+                            )),
+                            mode: Valid(gltf_json::mesh::Mode::Triangles),
+                            material: Some(material),
+                            targets: None,
+                            extensions: Default::default(),
+                            extras: Default::default(),
+                        })
+                    } else {
+                        None
+                    }
+                })
+                .collect(),
             weights: None,
             extensions: Default::default(),
             extras: Default::default(),
         },
     );
 
+    if let Some((_, dequantization)) = quantized {
+        writer.dequantizations.insert(mesh_index, dequantization);
+    }
+
     Some(mesh_index)
 }
 
+/// Computes a hash of everything about `mesh` that [`add_mesh()`] writes, so that meshes
+/// with equal hashes can share one glTF mesh.
+pub(crate) fn content_hash(
+    mesh: &SpaceMesh<GltfVertex, GltfTile>,
+    encoding: GltfPositionEncoding,
+    normals: GltfNormals,
+) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([
+        match encoding {
+            GltfPositionEncoding::F32 => 0,
+            GltfPositionEncoding::NormalizedI16 => 1,
+        },
+        match normals {
+            GltfNormals::Flat => 0,
+            GltfNormals::Smooth => 1,
+        },
+    ]);
+    for range in [
+        mesh.opaque_range(),
+        mesh.transparent_range(all_is_cubes_mesh::DepthOrdering::Any),
+    ] {
+        hasher.update((range.start as u64).to_le_bytes());
+        hasher.update((range.end as u64).to_le_bytes());
+    }
+    hasher.update((mesh.vertices().len() as u64).to_le_bytes());
+    hasher.update(bytemuck::cast_slice::<GltfVertex, u8>(mesh.vertices()));
+    // The index type is implied by the byte length divided by the vertex count.
+    hasher.update(mesh.indices().as_bytes());
+    hasher.finalize().into()
+}
+
+/// Name of the glTF extension permitting quantized vertex attributes.
+const QUANTIZATION_EXTENSION: &str = "KHR_mesh_quantization";
+
+/// Size of a quantized position in the buffer, including padding.
+const QUANTIZED_POSITION_STRIDE: usize = 8;
+
+/// Size of the attributes of a [`GltfVertex`] other than its position.
+const ATTRIBUTES_STRIDE: usize = size_of::<GltfVertex>() - size_of::<[Lef32; 3]>();
+
+/// Transform which, applied to the normalized quantized positions of a mesh (which are
+/// in the range −1 to 1), recovers the original positions.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Dequantization {
+    pub translation: [f32; 3],
+    pub scale: [f32; 3],
+}
+
+/// Convert the positions of `vertices` to normalized signed 16-bit integers spanning the
+/// bounding box of the positions, as permitted by `KHR_mesh_quantization`.
+pub(crate) fn quantize_positions(vertices: &[GltfVertex]) -> (Vec<[i16; 3]>, Dequantization) {
+    let [min, max] = vertices.iter().map(|v| v.position.map(f32::from)).fold(
+        [[f32::INFINITY; 3], [f32::NEG_INFINITY; 3]],
+        |[min, max], p| {
+            [
+                std::array::from_fn(|i| min[i].min(p[i])),
+                std::array::from_fn(|i| max[i].max(p[i])),
+            ]
+        },
+    );
+    let translation: [f32; 3] = std::array::from_fn(|i| (min[i] + max[i]) / 2.0);
+    let scale: [f32; 3] = std::array::from_fn(|i| {
+        let half_extent = (max[i] - min[i]) / 2.0;
+        // A zero scale would be a degenerate transform; any scale works for a flat mesh.
+        if half_extent > 0.0 {
+            half_extent
+        } else {
+            1.0
+        }
+    });
+
+    let positions = vertices
+        .iter()
+        .map(|v| {
+            let p = v.position.map(f32::from);
+            std::array::from_fn(|i| {
+                ((p[i] - translation[i]) / scale[i] * f32::from(i16::MAX))
+                    .round()
+                    .clamp(-f32::from(i16::MAX), f32::from(i16::MAX)) as i16
+            })
+        })
+        .collect();
+    (positions, Dequantization { translation, scale })
+}
+
 /// Collection of materials used in the glTF.
 ///
 /// TODO: Each should be optional and created only if required.
@@ -196,62 +474,97 @@ pub(crate) fn add_mesh(
 pub(crate) struct Materials {
     pub opaque_vertex_colored: Index<gltf_json::Material>,
     pub transparent_vertex_colored: Index<gltf_json::Material>,
+    /// Materials for textured triangles, keyed by tile index and transparency, created
+    /// as needed. Their textures are filled in by [`GltfWriter::into_root()`], once all
+    /// textures have been gathered.
+    pub textured: BTreeMap<(usize, bool), Index<gltf_json::Material>>,
 }
 
 impl Materials {
     pub fn new(materials_json: &mut Vec<gltf_json::Material>) -> Self {
-        let pbr_metallic_roughness = gltf_json::material::PbrMetallicRoughness {
-            // Per glTF 2.0 § 3.9.2, the base_color_factor will be
-            // multiplied by the vertex color.
-            base_color_factor: gltf_json::material::PbrBaseColorFactor([1.0, 1.0, 1.0, 1.0]),
-            base_color_texture: None,
-            metallic_factor: gltf_json::material::StrengthFactor(0.0),
-            roughness_factor: gltf_json::material::StrengthFactor(1.0),
-            ..<_>::default()
-        };
         Self {
             opaque_vertex_colored: push_and_return_index(
                 materials_json,
-                gltf_json::Material {
-                    name: Some("aic-vertex-opaque".into()),
-                    alpha_mode: Valid(gltf_json::material::AlphaMode::Opaque),
-                    double_sided: false,
-                    pbr_metallic_roughness: pbr_metallic_roughness.clone(),
-                    ..gltf_json::Material::default()
-                },
+                material("aic-vertex-opaque".into(), false),
             ),
             transparent_vertex_colored: push_and_return_index(
                 materials_json,
-                gltf_json::Material {
-                    name: Some("aic-vertex-transparent".into()),
-                    alpha_mode: Valid(gltf_json::material::AlphaMode::Blend),
-                    double_sided: false,
-                    pbr_metallic_roughness,
-                    extensions: Some(gltf_json::extensions::material::Material {
-                        volume: None,
-                        // TODO: Reenable this when attenuation_distance serialization bug is fixed.
-                        // https://github.com/gltf-rs/gltf/issues/364
-                        // Some(gltf_json::extensions::material::Volume {
-                        //     thickness_factor: gltf_json::extensions::material::ThicknessFactor(1.0),
-                        //     thickness_texture: None,
-                        //     attenuation_distance:
-                        //         gltf_json::extensions::material::AttenuationDistance::default(),
-                        //     attenuation_color:
-                        //         gltf_json::extensions::material::AttenuationColor::default(),
-                        //     extras: Void::default(),
-                        // }),
-                        transmission: Some(gltf_json::extensions::material::Transmission {
-                            transmission_factor:
-                                gltf_json::extensions::material::TransmissionFactor::default(),
-                            transmission_texture: None,
-                            extras: Void::default(),
-                        }),
-                    }),
-                    ..gltf_json::Material::default()
-                },
+                material("aic-vertex-transparent".into(), true),
             ),
+            textured: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the material for triangles colored only by their vertex colors.
+    pub fn vertex_colored(&self, transparent: bool) -> Index<gltf_json::Material> {
+        if transparent {
+            self.transparent_vertex_colored
+        } else {
+            self.opaque_vertex_colored
         }
     }
+
+    /// Returns the material for triangles textured with the texture of tile `tile`,
+    /// creating it if it does not exist yet.
+    pub fn textured(
+        &mut self,
+        materials_json: &mut Vec<gltf_json::Material>,
+        tile: usize,
+        transparent: bool,
+    ) -> Index<gltf_json::Material> {
+        *self.textured.entry((tile, transparent)).or_insert_with(|| {
+            let name = if transparent {
+                format!("aic-block-{tile}-transparent")
+            } else {
+                format!("aic-block-{tile}")
+            };
+            push_and_return_index(materials_json, material(name, transparent))
+        })
+    }
+}
+
+/// Create a material whose base color is the vertex color, which is multiplied by
+/// the texture, if one is added later.
+fn material(name: String, transparent: bool) -> gltf_json::Material {
+    let pbr_metallic_roughness = gltf_json::material::PbrMetallicRoughness {
+        // Per glTF 2.0 § 3.9.2, the base_color_factor will be
+        // multiplied by the vertex color.
+        base_color_factor: gltf_json::material::PbrBaseColorFactor([1.0, 1.0, 1.0, 1.0]),
+        base_color_texture: None,
+        metallic_factor: gltf_json::material::StrengthFactor(0.0),
+        roughness_factor: gltf_json::material::StrengthFactor(1.0),
+        ..<_>::default()
+    };
+    gltf_json::Material {
+        name: Some(name),
+        alpha_mode: Valid(if transparent {
+            gltf_json::material::AlphaMode::Blend
+        } else {
+            gltf_json::material::AlphaMode::Opaque
+        }),
+        double_sided: false,
+        pbr_metallic_roughness,
+        extensions: transparent.then(|| gltf_json::extensions::material::Material {
+            volume: None,
+            // TODO: Reenable this when attenuation_distance serialization bug is fixed.
+            // https://github.com/gltf-rs/gltf/issues/364
+            // Some(gltf_json::extensions::material::Volume {
+            //     thickness_factor: gltf_json::extensions::material::ThicknessFactor(1.0),
+            //     thickness_texture: None,
+            //     attenuation_distance:
+            //         gltf_json::extensions::material::AttenuationDistance::default(),
+            //     attenuation_color:
+            //         gltf_json::extensions::material::AttenuationColor::default(),
+            //     extras: Void::default(),
+            // }),
+            transmission: Some(gltf_json::extensions::material::Transmission {
+                transmission_factor: gltf_json::extensions::material::TransmissionFactor::default(),
+                transmission_texture: None,
+                extras: Void::default(),
+            }),
+        }),
+        ..gltf_json::Material::default()
+    }
 }
 
 #[cfg(test)]
@@ -320,4 +633,33 @@ mod tests {
 
         assert!(mesh_index.is_none());
     }
+
+    #[test]
+    fn quantized_positions_round_trip() {
+        let mut universe = all_is_cubes::universe::Universe::new();
+        let [block] = all_is_cubes::content::make_some_voxel_blocks(&mut universe);
+        let mut space = Space::empty_positive(3, 1, 2);
+        space.set([2, 0, 1], &block).unwrap();
+        let mut writer = GltfWriter::new(GltfDataDestination::null());
+        let (mesh, _) = gltf_mesh(&space, &mut writer);
+
+        let (quantized, Dequantization { translation, scale }) =
+            quantize_positions(mesh.vertices());
+
+        // Decode as a glTF reader would: normalize, then apply the node transform.
+        assert_eq!(quantized.len(), mesh.vertices().len());
+        for (q, vertex) in quantized.iter().zip(mesh.vertices()) {
+            let expected = vertex.position.map(f32::from);
+            let decoded: [f32; 3] = std::array::from_fn(|i| {
+                let normalized = (f32::from(q[i]) / f32::from(i16::MAX)).max(-1.0);
+                normalized * scale[i] + translation[i]
+            });
+            for i in 0..3 {
+                assert!(
+                    (decoded[i] - expected[i]).abs() < 1e-4,
+                    "decoded {decoded:?} != {expected:?}"
+                );
+            }
+        }
+    }
 }
@@ -0,0 +1,115 @@
+//! Computing vertex normals of glTF meshes, since [`GltfVertex`] does not store them.
+
+use std::collections::HashMap;
+
+use all_is_cubes::cgmath::{InnerSpace as _, Vector3};
+use all_is_cubes_mesh::{GfxVertex as _, IndexSlice};
+
+use super::GltfVertex;
+
+/// Recover each vertex's face normal from a triangle using it.
+/// All triangles sharing a vertex are on the same flat face, so any will do.
+///
+/// The normals are not normalized. A vertex used by no nondegenerate triangle has
+/// [`None`].
+pub(crate) fn face_normals(
+    vertices: &[GltfVertex],
+    indices: IndexSlice<'_>,
+) -> Vec<Option<Vector3<f32>>> {
+    let mut normals: Vec<Option<Vector3<f32>>> = vec![None; vertices.len()];
+    let indices: Vec<u32> = indices.iter_u32().collect();
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [0, 1, 2].map(|i| vertices[triangle[i] as usize].position());
+        let normal = (b - a).cross(c - a);
+        if normal.magnitude2() > 0. {
+            for &index in triangle {
+                normals[index as usize].get_or_insert(normal);
+            }
+        }
+    }
+    normals
+}
+
+/// Compute smooth normals for [`GltfNormals::Smooth`](super::GltfNormals::Smooth).
+///
+/// Each vertex's normal is the average of the distinct face normals found at its
+/// position, ignoring faces which face away from the vertex's own face. Because the
+/// vertex's own face normal is always included, the result always points to the same
+/// side as the vertex's triangles' winding does.
+pub(crate) fn smooth_normals(vertices: &[GltfVertex], indices: IndexSlice<'_>) -> Vec<[f32; 3]> {
+    let face_normals: Vec<Option<Vector3<f32>>> = face_normals(vertices, indices)
+        .into_iter()
+        .map(|normal| normal.map(Vector3::normalize))
+        .collect();
+
+    // Positions are compared exactly, since vertices that meet were generated from the
+    // same integer coordinates.
+    let position_key = |vertex: &GltfVertex| {
+        // Adding zero turns negative zero into positive zero.
+        vertex.position().map(|c| (c + 0.0).to_bits())
+    };
+    let mut normals_at_position: HashMap<_, Vec<Vector3<f32>>> = HashMap::new();
+    for (vertex, &normal) in vertices.iter().zip(&face_normals) {
+        if let Some(normal) = normal {
+            let normals = normals_at_position.entry(position_key(vertex)).or_default();
+            if !normals.contains(&normal) {
+                normals.push(normal);
+            }
+        }
+    }
+
+    vertices
+        .iter()
+        .zip(&face_normals)
+        .map(|(vertex, &own_normal)| {
+            let Some(own_normal) = own_normal else {
+                // Not part of any triangle, so the normal will never be used.
+                return [0., 0., 1.];
+            };
+            normals_at_position[&position_key(vertex)]
+                .iter()
+                .filter(|&&normal| normal.dot(own_normal) >= 0.)
+                .sum::<Vector3<f32>>()
+                .normalize()
+                .into()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use all_is_cubes::block::Block;
+    use all_is_cubes::camera::GraphicsOptions;
+    use all_is_cubes::cgmath::EuclideanSpace as _;
+    use all_is_cubes::math::Rgba;
+    use all_is_cubes::space::Space;
+    use all_is_cubes_mesh::{block_meshes_for_space, MeshOptions, SpaceMesh};
+
+    use crate::gltf::{GltfDataDestination, GltfTile, GltfWriter};
+
+    #[test]
+    fn smooth_normals_of_cube() {
+        let mut space = Space::empty_positive(1, 1, 1);
+        space.set([0, 0, 0], Block::from(Rgba::WHITE)).unwrap();
+        let writer = GltfWriter::new(GltfDataDestination::null());
+        let options = &MeshOptions::new(&GraphicsOptions::default());
+        let blocks = block_meshes_for_space(&space, &writer.texture_allocator(), options);
+        let mesh: SpaceMesh<GltfVertex, GltfTile> =
+            SpaceMesh::new(&space, space.bounds(), options, &*blocks);
+
+        let normals = smooth_normals(mesh.vertices(), mesh.indices());
+
+        assert_eq!(normals.len(), mesh.vertices().len());
+        for (vertex, normal) in mesh.vertices().iter().zip(normals) {
+            // Every vertex of a cube is a corner, where three faces meet, so the normal
+            // points diagonally away from the center.
+            let expected = vertex.position().map(|c| c * 2. - 1.).to_vec().normalize();
+            let normal = Vector3::from(normal);
+            assert!(
+                (normal - expected).magnitude() < 1e-6,
+                "{normal:?} != {expected:?}"
+            );
+        }
+    }
+}
@@ -5,16 +5,22 @@ use gltf_json::validation::Validate;
 use gltf_json::Index;
 
 use all_is_cubes::block::{Block, BlockDef, Resolution, AIR};
-use all_is_cubes::camera::GraphicsOptions;
+use all_is_cubes::camera::{Camera, Flaws, GraphicsOptions, ProjectionOption, Viewport};
+use all_is_cubes::cgmath::Vector2;
+use all_is_cubes::character::Character;
 use all_is_cubes::content::{make_some_blocks, make_some_voxel_blocks};
+use all_is_cubes::math::{GridAab, GridRotation, NotNan, Rgb, Rgba};
 use all_is_cubes::space::Space;
 use all_is_cubes::universe::{Name, URef, Universe};
 use all_is_cubes::util::yield_progress_for_testing;
-use all_is_cubes_mesh::{block_meshes_for_space, MeshOptions, SpaceMesh};
+use all_is_cubes_mesh::{block_meshes_for_space, GfxVertex as _, MeshOptions, SpaceMesh};
 
 use crate::{ExportError, ExportFormat, ExportSet};
 
-use super::{GltfDataDestination, GltfTile, GltfVertex, GltfWriter, MeshInstance};
+use super::{
+    GltfDataDestination, GltfNormals, GltfPositionEncoding, GltfSceneLayout, GltfTextureAllocator,
+    GltfTextureStyle, GltfTile, GltfVertex, GltfWriter, MeshInstance,
+};
 
 /// Test helper to insert one mesh
 pub(crate) fn gltf_mesh(
@@ -74,6 +80,399 @@ fn gltf_smoke_test() {
     });
 }
 
+#[test]
+fn orthographic_camera() {
+    let mut options = GraphicsOptions::default();
+    options.projection = ProjectionOption::Orthographic {
+        height: NotNan::from(10),
+    };
+    let camera = Camera::new(options, Viewport::with_scale(1.0, Vector2::new(20, 10)));
+
+    let mut writer = GltfWriter::new(GltfDataDestination::null());
+    writer.add_frame(Some(&camera), &[]);
+    let root = writer.into_root(Duration::ZERO).unwrap();
+
+    let [gltf_camera] = &root.cameras[..] else {
+        panic!("expected one camera, got {:?}", root.cameras);
+    };
+    assert!(gltf_camera.perspective.is_none());
+    let orthographic = gltf_camera.orthographic.as_ref().unwrap();
+    assert_eq!((orthographic.xmag, orthographic.ymag), (10.0, 5.0));
+    root.validate(&root, gltf_json::Path::new, &mut |pf, error| {
+        panic!("{path} {error}", path = pf())
+    });
+}
+
+#[test]
+fn camera_clipping_planes() {
+    let mut options = GraphicsOptions::default();
+    options.near_plane = NotNan::new(0.25).unwrap();
+    options.view_distance = NotNan::from(50);
+    let camera = Camera::new(options, Viewport::with_scale(1.0, Vector2::new(20, 10)));
+
+    let mut writer = GltfWriter::new(GltfDataDestination::null());
+    writer.add_frame(Some(&camera), &[]);
+    let root = writer.into_root(Duration::ZERO).unwrap();
+
+    let [gltf_camera] = &root.cameras[..] else {
+        panic!("expected one camera, got {:?}", root.cameras);
+    };
+    let perspective = gltf_camera.perspective.as_ref().unwrap();
+    assert_eq!((perspective.znear, perspective.zfar), (0.25, Some(50.0)));
+}
+
+fn space_with_three_blocks() -> Space {
+    let [block1, block2] = make_some_blocks();
+    let mut space = Space::empty_positive(4, 1, 1);
+    space.set([0, 0, 0], &block1).unwrap();
+    space.set([1, 0, 0], &block2).unwrap();
+    space.set([3, 0, 0], &block1).unwrap();
+    space
+}
+
+#[test]
+fn add_space_combined() {
+    let mut writer = GltfWriter::new(GltfDataDestination::null());
+    let instances = writer.add_space(&"space", &space_with_three_blocks());
+    assert_eq!(
+        instances,
+        vec![MeshInstance {
+            mesh: Index::new(0),
+            translation: [0, 0, 0]
+        }]
+    );
+}
+
+#[test]
+fn add_space_per_block_instances() {
+    let mut writer = GltfWriter::new(GltfDataDestination::null());
+    writer.set_scene_layout(GltfSceneLayout::PerBlockInstances);
+    let instances = writer.add_space(&"space", &space_with_three_blocks());
+    writer.add_frame(None, &instances);
+    let root = writer.into_root(Duration::ZERO).unwrap();
+
+    // One node per block, sharing one mesh per distinct block.
+    let mut mesh_nodes: Vec<([f32; 3], Index<gltf_json::Mesh>)> = root
+        .nodes
+        .iter()
+        .filter_map(|node| Some((node.translation?, node.mesh?)))
+        .collect();
+    mesh_nodes.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+    assert_eq!(root.meshes.len(), 2);
+    assert_eq!(
+        mesh_nodes,
+        vec![
+            ([0., 0., 0.], Index::new(0)),
+            ([1., 0., 0.], Index::new(1)),
+            ([3., 0., 0.], Index::new(0)),
+        ]
+    );
+    root.validate(&root, gltf_json::Path::new, &mut |pf, error| {
+        panic!("{path} {error}", path = pf())
+    });
+}
+
+/// Both layouts place the contents of a space which is not at the origin at their
+/// actual positions.
+#[test]
+fn add_space_not_at_origin() {
+    let [block] = make_some_blocks();
+    let mut space = Space::empty(GridAab::from_lower_size([-2, 5, 1], [2, 1, 1]));
+    space.set([-1, 5, 1], &block).unwrap();
+
+    let mut writer = GltfWriter::new(GltfDataDestination::null());
+    let combined = writer.add_space(&"combined", &space);
+    assert_eq!(
+        combined,
+        vec![MeshInstance {
+            mesh: Index::new(0),
+            translation: [-2, 5, 1]
+        }]
+    );
+    // The combined mesh's own coordinates start at the lower corner of the space, so
+    // the block's geometry ends up at the block's position.
+    let (mesh, _) = gltf_mesh(&space, &mut writer);
+    let lowest = mesh
+        .vertices()
+        .iter()
+        .map(|vertex| vertex.position())
+        .fold([f32::INFINITY; 3], |low, p| {
+            [low[0].min(p.x), low[1].min(p.y), low[2].min(p.z)]
+        });
+    let translation = combined[0].translation.map(|c| c as f32);
+    assert_eq!([0, 1, 2].map(|i| lowest[i] + translation[i]), [-1., 5., 1.]);
+
+    writer.set_scene_layout(GltfSceneLayout::PerBlockInstances);
+    let per_block = writer.add_space(&"per block", &space);
+    assert_eq!(
+        per_block.iter().map(|i| i.translation).collect::<Vec<_>>(),
+        vec![[-1, 5, 1]]
+    );
+}
+
+#[test]
+fn identical_meshes_are_shared() {
+    let mut writer = GltfWriter::new(GltfDataDestination::null());
+    let space = space_with_three_blocks();
+    let (mesh, first) = gltf_mesh(&space, &mut writer);
+    let second = writer.add_mesh(&"again", &mesh);
+    assert_eq!(first, second);
+
+    // A different mesh is not shared.
+    let mut other_space = space_with_three_blocks();
+    other_space
+        .set([2, 0, 0], &make_some_blocks::<1>()[0])
+        .unwrap();
+    let (_, third) = gltf_mesh(&other_space, &mut writer);
+    let root = writer.into_root(Duration::ZERO).unwrap();
+    assert_eq!(root.meshes.len(), 2);
+    assert_eq!(root.buffers.len(), 2);
+    assert_ne!(first, third);
+}
+
+#[test]
+fn quantized_position_encoding() {
+    let mut writer = GltfWriter::new(GltfDataDestination::null());
+    writer.set_position_encoding(GltfPositionEncoding::NormalizedI16);
+    let instances = writer.add_space(&"space", &space_with_three_blocks());
+    writer.add_frame(None, &instances);
+    let root = writer.into_root(Duration::ZERO).unwrap();
+
+    let extension = String::from("KHR_mesh_quantization");
+    assert!(root.extensions_used.contains(&extension));
+    assert!(root.extensions_required.contains(&extension));
+
+    let position_accessor = root
+        .get(
+            root.meshes[0].primitives[0].attributes
+                [&gltf_json::validation::Checked::Valid(gltf_json::mesh::Semantic::Positions)],
+        )
+        .unwrap();
+    assert!(position_accessor.normalized);
+    assert_eq!(
+        position_accessor.component_type.unwrap().0,
+        gltf_json::accessor::ComponentType::I16
+    );
+
+    // The mesh spans 4 × 1 × 1 blocks, so the dequantization node scales by half that.
+    let mesh_node = root.nodes.iter().find(|node| node.mesh.is_some()).unwrap();
+    assert_eq!(mesh_node.translation, Some([2.0, 0.5, 0.5]));
+    assert_eq!(mesh_node.scale, Some([2.0, 0.5, 0.5]));
+
+    root.validate(&root, gltf_json::Path::new, &mut |pf, error| {
+        panic!("{path} {error}", path = pf())
+    });
+}
+
+#[test]
+fn smooth_normals() {
+    let mut writer = GltfWriter::new(GltfDataDestination::new(None, usize::MAX));
+    writer.set_normals(GltfNormals::Smooth);
+    let mut space = Space::empty_positive(1, 1, 1);
+    space.set([0, 0, 0], Block::from(Rgba::WHITE)).unwrap();
+    let (mesh, mesh_index) = gltf_mesh(&space, &mut writer);
+    writer.add_frame(
+        None,
+        &[MeshInstance {
+            mesh: mesh_index.unwrap(),
+            translation: [0, 0, 0],
+        }],
+    );
+    let root = writer.into_root(Duration::ZERO).unwrap();
+
+    let normal_accessor = root
+        .get(
+            root.meshes[0].primitives[0].attributes
+                [&gltf_json::validation::Checked::Valid(gltf_json::mesh::Semantic::Normals)],
+        )
+        .unwrap();
+    assert_eq!(
+        normal_accessor.count,
+        u32::try_from(mesh.vertices().len()).unwrap()
+    );
+    assert_eq!(
+        normal_accessor.type_.as_ref().unwrap(),
+        &gltf_json::accessor::Type::Vec3
+    );
+
+    // Read the normals back from the inline buffer.
+    let view = root.get(normal_accessor.buffer_view.unwrap()).unwrap();
+    let data = inline_buffer_data(&root, view.buffer);
+    let start = view.byte_offset.unwrap_or_default() as usize
+        + normal_accessor.byte_offset.unwrap_or_default() as usize;
+    let stride = view.byte_stride.unwrap() as usize;
+    for i in 0..normal_accessor.count as usize {
+        let normal: [f32; 3] = std::array::from_fn(|c| {
+            let offset = start + i * stride + c * 4;
+            f32::from_le_bytes(data[offset..][..4].try_into().unwrap())
+        });
+        let length = normal.iter().map(|c| c * c).sum::<f32>().sqrt();
+        assert!((length - 1.0).abs() < 1e-6, "{normal:?} is not unit length");
+    }
+
+    root.validate(&root, gltf_json::Path::new, &mut |pf, error| {
+        panic!("{path} {error}", path = pf())
+    });
+}
+
+#[test]
+fn flat_normals_by_default() {
+    let mut writer = GltfWriter::new(GltfDataDestination::null());
+    let instances = writer.add_space(&"space", &space_with_three_blocks());
+    writer.add_frame(None, &instances);
+    let root = writer.into_root(Duration::ZERO).unwrap();
+
+    assert!(!root.meshes[0].primitives[0].attributes.contains_key(
+        &gltf_json::validation::Checked::Valid(gltf_json::mesh::Semantic::Normals)
+    ));
+}
+
+/// Decode the contents of a buffer written by `GltfDataDestination::new(None, usize::MAX)`.
+fn inline_buffer_data(root: &gltf_json::Root, buffer: Index<gltf_json::Buffer>) -> Vec<u8> {
+    let uri = root.get(buffer).unwrap().uri.as_deref().unwrap();
+    base64::Engine::decode(
+        &base64::engine::general_purpose::STANDARD_NO_PAD,
+        uri.strip_prefix("data:application/gltf-buffer;base64,")
+            .unwrap(),
+    )
+    .unwrap()
+}
+
+/// Two different textured blocks side by side.
+/// The universe must be kept alive for the blocks to be usable.
+fn space_with_two_textured_blocks() -> (Universe, Space) {
+    let mut universe = Universe::new();
+    let [block1, block2] = make_some_voxel_blocks(&mut universe);
+    let mut space = Space::empty_positive(2, 1, 1);
+    space.set([0, 0, 0], &block1).unwrap();
+    space.set([1, 0, 0], &block2).unwrap();
+    (universe, space)
+}
+
+/// Export `space` with textures, arranging them according to `style`.
+fn export_textured(space: &Space, style: GltfTextureStyle) -> gltf_json::Root {
+    let mut writer = GltfWriter::new(GltfDataDestination::new(None, usize::MAX));
+    // Texture allocation is not yet enabled by default.
+    writer.texture_allocator = GltfTextureAllocator::new(writer.buffer_dest.clone(), true);
+    writer.set_texture_style(style);
+    writer.set_scene_layout(GltfSceneLayout::PerBlockInstances);
+    let instances = writer.add_space(&"space", space);
+    writer.add_frame(None, &instances);
+    let root = writer.into_root(Duration::ZERO).unwrap();
+    root.validate(&root, gltf_json::Path::new, &mut |pf, error| {
+        panic!("{path} {error}", path = pf())
+    });
+    root
+}
+
+/// The base color texture of the material of each primitive which has one.
+fn primitive_textures(root: &gltf_json::Root) -> Vec<gltf_json::texture::Info> {
+    root.meshes
+        .iter()
+        .flat_map(|mesh| &mesh.primitives)
+        .filter_map(|primitive| {
+            let material = root.get(primitive.material?).unwrap();
+            material.pbr_metallic_roughness.base_color_texture.clone()
+        })
+        .collect()
+}
+
+/// Decode the image used by `texture`.
+fn texture_image(root: &gltf_json::Root, texture: &gltf_json::texture::Info) -> image::RgbaImage {
+    let image = root.get(root.get(texture.index).unwrap().source).unwrap();
+    let view = root.get(image.buffer_view.unwrap()).unwrap();
+    let data = inline_buffer_data(root, view.buffer);
+    let start = view.byte_offset.unwrap_or_default() as usize;
+    image::load_from_memory(&data[start..][..view.byte_length as usize])
+        .unwrap()
+        .to_rgba8()
+}
+
+#[test]
+fn per_block_textures_are_used_by_primitives() {
+    let (_universe, space) = space_with_two_textured_blocks();
+    let root = export_textured(&space, GltfTextureStyle::PerBlock);
+
+    let textures = primitive_textures(&root);
+    let mut images: Vec<Index<gltf_json::Image>> = textures
+        .iter()
+        .map(|info| root.get(info.index).unwrap().source)
+        .collect();
+    images.dedup();
+    assert_eq!(images.len(), 2, "{textures:?}");
+    assert_eq!(root.images.len(), 2);
+    for info in &textures {
+        assert!(info.extensions.is_none());
+        texture_image(&root, info);
+    }
+
+    // Texture coordinates are within each block's own image.
+    for primitive in root.meshes.iter().flat_map(|mesh| &mesh.primitives) {
+        let accessor = root
+            .get(
+                primitive.attributes[&gltf_json::validation::Checked::Valid(
+                    gltf_json::mesh::Semantic::TexCoords(0),
+                )],
+            )
+            .unwrap();
+        for bound in [&accessor.min, &accessor.max] {
+            for c in bound.as_ref().unwrap().as_array().unwrap() {
+                let c = c.as_f64().unwrap();
+                assert!((0.0..=1.0).contains(&c), "{c} out of range");
+            }
+        }
+    }
+}
+
+#[test]
+fn atlas_texture_transforms_select_each_block_texture() {
+    let (_universe, space) = space_with_two_textured_blocks();
+    let atlas_root = export_textured(&space, GltfTextureStyle::Atlas);
+    let per_block_root = export_textured(&space, GltfTextureStyle::PerBlock);
+    assert_eq!(atlas_root.images.len(), 1);
+    assert!(atlas_root
+        .extensions_used
+        .contains(&String::from("KHR_texture_transform")));
+
+    let atlas_textures = primitive_textures(&atlas_root);
+    let per_block_textures = primitive_textures(&per_block_root);
+    assert_eq!(atlas_textures.len(), per_block_textures.len());
+    assert!(!atlas_textures.is_empty());
+    for (atlas_texture, block_texture) in atlas_textures.iter().zip(&per_block_textures) {
+        let atlas = texture_image(&atlas_root, atlas_texture);
+        let block_image = texture_image(&per_block_root, block_texture);
+        let transform = atlas_texture
+            .extensions
+            .as_ref()
+            .and_then(|e| e.texture_transform.as_ref())
+            .expect("no texture transform");
+
+        // The transform maps the block's texture coordinates onto exactly the region
+        // of the atlas that contains the block's image.
+        let size = atlas.width() as f32;
+        assert_eq!(
+            transform.scale.0,
+            [
+                block_image.width() as f32 / size,
+                block_image.height() as f32 / size
+            ]
+        );
+        let [x, y] = transform.offset.0.map(|c| c * size);
+        assert_eq!([x, y], [x.round(), y.round()]);
+        assert_eq!(
+            *image::imageops::crop_imm(
+                &atlas,
+                x as u32,
+                y as u32,
+                block_image.width(),
+                block_image.height()
+            )
+            .to_image(),
+            *block_image
+        );
+    }
+}
+
 #[tokio::test]
 async fn export_block_defs() {
     let mut universe = Universe::new();
@@ -94,7 +493,8 @@ async fn export_block_defs() {
     crate::export_to_path(
         yield_progress_for_testing(),
         ExportFormat::Gltf,
-        ExportSet::from_block_defs(block_defs),
+        // Pretty, so that the snapshot is readable.
+        ExportSet::from_block_defs(block_defs).with_pretty_json(),
         PathBuf::from(&destination),
     )
     .await
@@ -106,6 +506,48 @@ async fn export_block_defs() {
     );
 }
 
+/// A rotated block is exported as the unrotated block's mesh in a rotated node.
+#[test]
+fn block_def_rotation_is_node_transform() {
+    let mut universe = Universe::new();
+    let [block] = make_some_voxel_blocks(&mut universe);
+    let mut writer = GltfWriter::new(GltfDataDestination::null());
+    let mesh_options = MeshOptions::new(&GraphicsOptions::default());
+    for (name, block) in [
+        ("plain", block.clone()),
+        ("rotated", block.rotate(GridRotation::CLOCKWISE)),
+    ] {
+        super::add_block_def(
+            &mut writer,
+            &name.into(),
+            &BlockDef::new(block),
+            &mesh_options,
+        )
+        .unwrap();
+    }
+    let [plain, rotated] =
+        [0, 1].map(|i| &writer.root.nodes[writer.root.scenes[i].nodes[0].value()]);
+
+    assert!(plain.rotation.is_none() && plain.translation.is_none());
+    let (expected_rotation, expected_translation) =
+        super::glue::convert_block_rotation(GridRotation::CLOCKWISE).unwrap();
+    assert_eq!(rotated.rotation.map(|q| q.0), Some(expected_rotation.0));
+    assert_eq!(rotated.translation, Some(expected_translation));
+    for (actual, expected) in expected_translation.into_iter().zip([1., 0., 0.]) {
+        assert!((actual - expected).abs() < 1e-6, "{expected_translation:?}");
+    }
+
+    // The rotated block's mesh is not rotated, so it has the same extent.
+    let position_bounds = |node: &gltf_json::Node| {
+        let mesh = &writer.root.meshes[node.mesh.unwrap().value()];
+        let accessor = mesh.primitives[0].attributes
+            [&gltf_json::validation::Checked::Valid(gltf_json::mesh::Semantic::Positions)];
+        let accessor = &writer.root.accessors[accessor.value()];
+        (accessor.min.clone(), accessor.max.clone())
+    };
+    assert_eq!(position_bounds(plain), position_bounds(rotated));
+}
+
 #[tokio::test]
 async fn export_space_not_supported() {
     let mut universe = Universe::new();
@@ -131,3 +573,191 @@ async fn export_space_not_supported() {
         }
      if name == "x".into()));
 }
+
+#[tokio::test]
+async fn export_character_as_camera() {
+    let mut universe = Universe::new();
+    let space = universe
+        .insert("space".into(), Space::empty_positive(4, 4, 4))
+        .unwrap();
+    let character = universe
+        .insert("character".into(), Character::spawn_default(space))
+        .unwrap();
+    let view = character.read().unwrap().view();
+    let destination_dir = tempfile::tempdir().unwrap();
+    let destination: PathBuf = destination_dir.path().join("character.gltf");
+
+    crate::export_to_path(
+        yield_progress_for_testing(),
+        ExportFormat::Gltf,
+        ExportSet::from_characters(vec![character]),
+        destination.clone(),
+    )
+    .await
+    .unwrap();
+
+    let root: gltf_json::Root =
+        serde_json::from_reader(std::fs::File::open(destination).unwrap()).unwrap();
+    assert_eq!(root.cameras.len(), 1);
+    assert_eq!(root.cameras[0].name.as_deref(), Some("'character'"));
+    let camera_nodes: Vec<&gltf_json::Node> = root
+        .nodes
+        .iter()
+        .filter(|node| node.camera.is_some())
+        .collect();
+    assert_eq!(camera_nodes.len(), 1);
+    assert_eq!(
+        camera_nodes[0].translation,
+        Some(view.disp.map(|c| c as f32).into())
+    );
+    assert_eq!(root.scenes[0].nodes.len(), 1);
+}
+
+#[test]
+fn baked_light_follows_frames() {
+    let lit_space = |sky_color: Rgb| {
+        let mut space = Space::builder(GridAab::ORIGIN_CUBE)
+            .sky_color(sky_color)
+            .build();
+        space.set([0, 0, 0], Block::from(Rgba::WHITE)).unwrap();
+        space
+    };
+    let bright = lit_space(Rgb::ONE);
+    let dim = lit_space(Rgb::new(0.25, 0.5, 0.25));
+
+    let mut writer = GltfWriter::new(GltfDataDestination::null());
+    writer.set_bake_light(true);
+    let (mesh, mesh_index) = gltf_mesh(&bright, &mut writer);
+    let instance = MeshInstance {
+        mesh: mesh_index.unwrap(),
+        translation: [0, 0, 0],
+    };
+    for space in [&bright, &dim, &bright] {
+        let flaws = writer.add_frame_with_light(None, &[instance], space);
+        assert!(!flaws.contains(Flaws::NO_LIGHTING), "{flaws:?}");
+    }
+
+    // Each distinct light gets its own mesh, reused when the light recurs.
+    let frame_meshes: Vec<_> = writer
+        .frame_states
+        .iter()
+        .map(|state| state.visible_mesh_instances[0].mesh)
+        .collect();
+    assert_ne!(frame_meshes[0], frame_meshes[1]);
+    assert_eq!(frame_meshes[0], frame_meshes[2]);
+    assert!(!frame_meshes.contains(&instance.mesh));
+
+    // The baked colors differ between the two lights.
+    let [(bright_light, _), (dim_light, _)] = &writer.lit_meshes[&instance][..] else {
+        panic!("expected two lit meshes");
+    };
+    let bright_vertices = super::light::apply_light(mesh.vertices(), bright_light);
+    let dim_vertices = super::light::apply_light(mesh.vertices(), dim_light);
+    assert_eq!(bright_vertices, mesh.vertices());
+    assert_ne!(bright_vertices, dim_vertices);
+    assert_eq!(
+        dim_vertices[0].base_color.map(f32::from),
+        [0.25, 0.5, 0.25, 1.0]
+    );
+}
+
+#[test]
+fn unbaked_light_is_a_flaw() {
+    let mut writer = GltfWriter::new(GltfDataDestination::null());
+    let space = Space::empty_positive(1, 1, 1);
+    assert!(writer
+        .add_frame_with_light(None, &[], &space)
+        .contains(Flaws::NO_LIGHTING));
+}
+
+/// Exporting asynchronously, with file writing on another thread, must produce exactly
+/// the same files as writing synchronously.
+#[tokio::test]
+async fn async_export_matches_synchronous() {
+    let mut universe = Universe::new();
+    // Checkerboard blocks, so that their meshes are large enough to be written to files.
+    let [color] = make_some_blocks();
+    let block_defs: Vec<URef<BlockDef>> = (0..2)
+        .map(|i| {
+            let block = Block::builder()
+                .voxels_fn(&mut universe, Resolution::R8, |cube| {
+                    if (cube.x + cube.y + cube.z + i).rem_euclid(2) == 0 {
+                        &color
+                    } else {
+                        &AIR
+                    }
+                })
+                .unwrap()
+                .build();
+            universe
+                .insert(Name::from(format!("block{i}")), BlockDef::new(block))
+                .unwrap()
+        })
+        .collect();
+    let async_dir = tempfile::tempdir().unwrap();
+    let sync_dir = tempfile::tempdir().unwrap();
+
+    crate::export_to_path(
+        yield_progress_for_testing(),
+        ExportFormat::Gltf,
+        ExportSet::from_block_defs(block_defs.clone()),
+        async_dir.path().join("foo.gltf"),
+    )
+    .await
+    .unwrap();
+
+    let sync_path = sync_dir.path().join("foo.gltf");
+    let mut writer = GltfWriter::new(GltfDataDestination::new(Some(sync_path.clone()), 2000));
+    let mesh_options = MeshOptions::new(&GraphicsOptions::default());
+    for block_def in &block_defs {
+        super::add_block_def(
+            &mut writer,
+            &block_def.name(),
+            &*block_def.read().unwrap(),
+            &mesh_options,
+        )
+        .unwrap();
+    }
+    let root = writer.into_root(Duration::from_secs(1)).unwrap();
+    super::write_root(&root, std::fs::File::create(sync_path).unwrap(), false).unwrap();
+
+    let read_files = |dir: &Path| {
+        let mut files: Vec<(std::ffi::OsString, Vec<u8>)> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| {
+                let entry = entry.unwrap();
+                (entry.file_name(), std::fs::read(entry.path()).unwrap())
+            })
+            .collect();
+        files.sort();
+        files
+    };
+    let async_files = read_files(async_dir.path());
+    assert!(async_files.len() > 1, "expected data files");
+    assert_eq!(async_files, read_files(sync_dir.path()));
+}
+
+#[test]
+fn write_error_is_returned() {
+    struct FailingWriter;
+    impl std::io::Write for FailingWriter {
+        fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::new(std::io::ErrorKind::Other, "disk full"))
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let root = GltfWriter::new(GltfDataDestination::null())
+        .into_root(Duration::ZERO)
+        .unwrap();
+    for pretty_json in [false, true] {
+        match super::write_root(&root, FailingWriter, pretty_json) {
+            Err(ExportError::Write { path: None, error }) => {
+                assert_eq!(error.to_string(), "disk full")
+            }
+            other => panic!("unexpected result {other:?}"),
+        }
+    }
+}
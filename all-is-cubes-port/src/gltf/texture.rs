@@ -9,7 +9,22 @@ use all_is_cubes::math::{GridAab, GridRotation};
 use all_is_cubes_mesh::texture;
 
 use super::glue::push_and_return_index;
-use super::GltfDataDestination;
+use super::{GltfDataDestination, GltfVertex};
+use crate::ExportError;
+
+/// How the textures of blocks should be arranged into images in a glTF export.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum GltfTextureStyle {
+    /// All block textures are packed into a single image (a texture atlas).
+    /// This is more efficient for rendering.
+    #[default]
+    Atlas,
+
+    /// Each block's texture is written as a separate image.
+    /// This is more convenient for editing the exported textures.
+    PerBlock,
+}
 
 /// [`texture::Allocator`] implementation for glTF exports.
 ///
@@ -46,11 +61,57 @@ impl GltfTextureAllocator {
         self.gatherer.is_empty()
     }
 
-    pub(crate) fn write_png_atlas(&self) -> Result<gltf_json::Buffer, io::Error> {
-        let image: image::RgbaImage = self.gatherer.build_atlas();
-        let buffer = self
-            .destination
-            .write(String::from("texture"), "texture", "png", |w| {
+    /// Write all gathered textures as PNG images, arranged according to `style`.
+    ///
+    /// Returns a buffer entity for each image written, and the region of that image
+    /// which each block's texture occupies.
+    pub(crate) fn write_png_images(
+        &self,
+        style: GltfTextureStyle,
+    ) -> Result<Vec<(gltf_json::Buffer, Vec<internal::TileRegion>)>, ExportError> {
+        let tile_images: Vec<image::RgbaImage> = self.gatherer.build_tile_images();
+        match style {
+            GltfTextureStyle::Atlas => {
+                let (atlas, regions) = internal::pack_tile_images(&tile_images);
+                Ok(vec![(
+                    self.write_png(String::from("texture"), "texture", &atlas),
+                    regions,
+                )])
+            }
+            GltfTextureStyle::PerBlock => Ok(tile_images
+                .iter()
+                .enumerate()
+                .map(|(i, image)| {
+                    (
+                        self.write_png(format!("texture {i}"), &format!("texture-{i}"), image),
+                        vec![internal::TileRegion::WHOLE],
+                    )
+                })
+                .collect()),
+        }
+    }
+
+    /// Replace the texture placeholders in `vertices` (see
+    /// [`GltfVertex::texture_placeholder()`]) with
+    /// texture coordinates ranging from 0 to 1 over the texture of the tile they came from.
+    ///
+    /// Returns the index of that tile for each vertex, or [`None`] if the vertex is not
+    /// textured. The tile's texture is the `n`th one returned by [`insert_block_textures()`].
+    pub(crate) fn resolve_texture_placeholders(
+        &self,
+        vertices: &mut [GltfVertex],
+    ) -> Vec<Option<usize>> {
+        self.gatherer.resolve_texture_placeholders(vertices)
+    }
+
+    fn write_png(
+        &self,
+        buffer_entity_name: String,
+        proposed_file_name: &str,
+        image: &image::RgbaImage,
+    ) -> gltf_json::Buffer {
+        self.destination
+            .write(buffer_entity_name, proposed_file_name, "png", |w| {
                 // `image` wants `Write + Seek` but `w` is not currently `Seek`
                 let mut tmp = io::Cursor::new(Vec::new());
                 image
@@ -59,8 +120,7 @@ impl GltfTextureAllocator {
                 w.write_all(tmp.into_inner().as_slice())?;
                 Ok(())
             })
-            .expect("TODO: propagate IO errors to later instead of panicking");
-        Ok(buffer)
+            .expect("TODO: propagate IO errors to later instead of panicking")
     }
 }
 
@@ -130,6 +190,7 @@ impl texture::Tile for GltfTile {
             source_bounds: self.bounds,
             sliced_bounds,
             rotation,
+            tile: 0,
         });
 
         GltfTexturePlane {
@@ -192,27 +253,29 @@ pub struct GltfAtlasPoint {
     pub(crate) point_within: Point2<f32>,
 }
 
-/// Generate the atlas texture and necessary glTF entities.
-pub(super) fn insert_block_texture_atlas(
+/// Name of the glTF extension used to map each block's texture coordinates into its
+/// region of a texture atlas.
+const TEXTURE_TRANSFORM_EXTENSION: &str = "KHR_texture_transform";
+
+/// Generate the block texture image(s) and necessary glTF entities.
+///
+/// With [`GltfTextureStyle::Atlas`] this produces one texture; with
+/// [`GltfTextureStyle::PerBlock`] it produces one texture per textured block.
+///
+/// Returns a reference to the texture of each tile, in tile index order, for use as the
+/// base color texture of that tile's materials. Each tile's texture coordinates range from
+/// 0 to 1 over its own texture, and when the texture is part of an atlas, the reference's
+/// `KHR_texture_transform` maps them to the tile's region of the atlas.
+pub(super) fn insert_block_textures(
     root: &mut gltf_json::Root,
     allocator: &GltfTextureAllocator,
-) -> Result<gltf_json::Index<gltf_json::Texture>, io::Error> {
-    let block_texture_buffer = allocator.write_png_atlas()?;
-    let block_texture_len = block_texture_buffer.byte_length;
-    let block_texture_buffer = push_and_return_index(&mut root.buffers, block_texture_buffer);
-    let block_texture_buffer_view = push_and_return_index(
-        &mut root.buffer_views,
-        gltf_json::buffer::View {
-            buffer: block_texture_buffer,
-            byte_length: block_texture_len,
-            byte_offset: None,
-            byte_stride: None,
-            name: Some("block texture".into()),
-            target: None,
-            extensions: None,
-            extras: Default::default(),
-        },
-    );
+    style: GltfTextureStyle,
+) -> Result<Vec<gltf_json::texture::Info>, ExportError> {
+    let images = allocator.write_png_images(style)?;
+    if images.is_empty() {
+        return Ok(Vec::new());
+    }
+
     let block_texture_sampler = push_and_return_index(
         &mut root.samplers,
         gltf_json::texture::Sampler {
@@ -225,28 +288,81 @@ pub(super) fn insert_block_texture_atlas(
             extras: Default::default(),
         },
     );
-    let block_texture_image = push_and_return_index(
-        &mut root.images,
-        gltf_json::Image {
-            buffer_view: Some(block_texture_buffer_view),
-            mime_type: Some(gltf_json::image::MimeType("image/png".into())),
-            name: Some("block texture".into()),
-            uri: None,
-            extensions: None,
-            extras: Default::default(),
-        },
-    );
-    let block_texture = push_and_return_index(
-        &mut root.textures,
-        gltf_json::Texture {
-            name: None,
-            sampler: Some(block_texture_sampler),
-            source: block_texture_image,
-            extensions: None,
-            extras: Default::default(),
-        },
-    );
-    Ok(block_texture)
+
+    let mut tile_textures = Vec::new();
+    for (block_texture_buffer, regions) in images {
+        let image_name = block_texture_buffer
+            .name
+            .clone()
+            .unwrap_or_else(|| "texture".into());
+        let block_texture_len = block_texture_buffer.byte_length;
+        let block_texture_buffer = push_and_return_index(&mut root.buffers, block_texture_buffer);
+        let block_texture_buffer_view = push_and_return_index(
+            &mut root.buffer_views,
+            gltf_json::buffer::View {
+                buffer: block_texture_buffer,
+                byte_length: block_texture_len,
+                byte_offset: None,
+                byte_stride: None,
+                name: Some(format!("block {image_name}")),
+                target: None,
+                extensions: None,
+                extras: Default::default(),
+            },
+        );
+        let block_texture_image = push_and_return_index(
+            &mut root.images,
+            gltf_json::Image {
+                buffer_view: Some(block_texture_buffer_view),
+                mime_type: Some(gltf_json::image::MimeType("image/png".into())),
+                name: Some(format!("block {image_name}")),
+                uri: None,
+                extensions: None,
+                extras: Default::default(),
+            },
+        );
+        let block_texture = push_and_return_index(
+            &mut root.textures,
+            gltf_json::Texture {
+                name: None,
+                sampler: Some(block_texture_sampler),
+                source: block_texture_image,
+                extensions: None,
+                extras: Default::default(),
+            },
+        );
+
+        for region in regions {
+            let texture_transform = (region != internal::TileRegion::WHOLE).then(|| {
+                gltf_json::extensions::texture::TextureTransform {
+                    offset: gltf_json::extensions::texture::TextureTransformOffset(region.offset),
+                    scale: gltf_json::extensions::texture::TextureTransformScale(region.scale),
+                    ..Default::default()
+                }
+            });
+            if texture_transform.is_some()
+                && !root
+                    .extensions_used
+                    .iter()
+                    .any(|e| e == TEXTURE_TRANSFORM_EXTENSION)
+            {
+                root.extensions_used
+                    .push(TEXTURE_TRANSFORM_EXTENSION.into());
+            }
+
+            tile_textures.push(gltf_json::texture::Info {
+                index: block_texture,
+                tex_coord: 0,
+                extensions: texture_transform.map(|texture_transform| {
+                    gltf_json::extensions::texture::Info {
+                        texture_transform: Some(texture_transform),
+                    }
+                }),
+                extras: Default::default(),
+            });
+        }
+    }
+    Ok(tile_textures)
 }
 
 mod internal {
@@ -254,7 +370,6 @@ mod internal {
 
     use super::*;
     use std::collections::BTreeMap;
-    use std::mem;
     use std::sync::{Arc, Mutex, OnceLock};
 
     /// Texels are written here through tiles and read through planes.
@@ -262,43 +377,136 @@ mod internal {
 
     /// Interior-mutable accumulator of textures to put in the atlas.
     #[derive(Clone, Debug, Default)]
-    pub(super) struct Gatherer(Arc<Mutex<Vec<AtlasEntry>>>);
+    pub(super) struct Gatherer(Arc<Mutex<GathererState>>);
+
+    #[derive(Debug, Default)]
+    pub(super) struct GathererState {
+        /// All entries, indexed by plane ID.
+        entries: Vec<AtlasEntry>,
+        /// Entries grouped by the tile they came from (and therefore, in ordinary use,
+        /// the block), in order of first appearance. Each group gets its own image.
+        tiles: Vec<TileEntries>,
+    }
+
+    #[derive(Debug)]
+    struct TileEntries {
+        /// Plane IDs of the entries in this group.
+        plane_ids: Vec<usize>,
+        /// Where each entry is placed in the tile's image, once decided.
+        ///
+        /// Once this is set, texture coordinates may have been computed from it, so
+        /// further planes sliced from the same tile start a new group.
+        layout: Option<TileLayout>,
+    }
+
+    /// Placement of a tile's planes in its image.
+    #[derive(Debug)]
+    struct TileLayout {
+        /// Width and height of the (square) image.
+        size: u32,
+        /// Position in the image of each plane, in the same order as
+        /// [`TileEntries::plane_ids`].
+        locations: Vec<[u32; 2]>,
+    }
 
     impl Gatherer {
+        fn lock(&self) -> std::sync::MutexGuard<'_, GathererState> {
+            self.0.lock().expect("mutex in atlas gatherer")
+        }
+
         pub(crate) fn is_empty(&self) -> bool {
-            self.0.lock().expect("mutex in atlas gatherer").is_empty()
+            self.lock().entries.is_empty()
         }
 
         pub fn insert(&self, entry: AtlasEntry) -> u64 {
-            let mut data = self.0.lock().expect("mutex in atlas gatherer");
-            let plane_id = u64::try_from(data.len()).unwrap();
-            data.push(entry);
-            plane_id
+            let state = &mut *self.lock();
+            let plane_id = state.entries.len();
+            let tile = match state.tiles.iter().rposition(|tile| {
+                tile.layout.is_none()
+                    && Arc::ptr_eq(
+                        &state.entries[tile.plane_ids[0]].source_texels,
+                        &entry.source_texels,
+                    )
+            }) {
+                Some(tile) => tile,
+                None => {
+                    state.tiles.push(TileEntries {
+                        plane_ids: Vec::new(),
+                        layout: None,
+                    });
+                    state.tiles.len() - 1
+                }
+            };
+            state.tiles[tile].plane_ids.push(plane_id);
+            state.entries.push(AtlasEntry { tile, ..entry });
+            u64::try_from(plane_id).unwrap()
         }
 
-        pub(crate) fn build_atlas(&self) -> image::RgbaImage {
-            use rectangle_pack as rp;
+        /// Implementation of [`GltfTextureAllocator::resolve_texture_placeholders()`].
+        pub(crate) fn resolve_texture_placeholders(
+            &self,
+            vertices: &mut [GltfVertex],
+        ) -> Vec<Option<usize>> {
+            let state = &mut *self.lock();
+            vertices
+                .iter_mut()
+                .map(|vertex| {
+                    let GltfAtlasPoint {
+                        plane_id,
+                        point_within,
+                    } = vertex.texture_placeholder()?;
+                    let plane_id = usize::try_from(plane_id).unwrap();
+                    let tile_index = state.entries[plane_id].tile;
+                    let tile = &mut state.tiles[tile_index];
+                    let layout = tile
+                        .layout
+                        .get_or_insert_with(|| TileLayout::new(&state.entries, &tile.plane_ids));
+                    let location = layout.locations[tile
+                        .plane_ids
+                        .iter()
+                        .position(|&id| id == plane_id)
+                        .unwrap()];
+                    let size = layout.size as f32;
+                    vertex.set_texture_coordinates(Point2::new(
+                        (location[0] as f32 + point_within.x) / size,
+                        (location[1] as f32 + point_within.y) / size,
+                    ));
+                    Some(tile_index)
+                })
+                .collect()
+        }
+
+        /// Build the image of each tile, in tile index order.
+        pub(crate) fn build_tile_images(&self) -> Vec<image::RgbaImage> {
+            let state = &mut *self.lock();
+            state
+                .tiles
+                .iter_mut()
+                .map(|tile| {
+                    let layout = tile
+                        .layout
+                        .get_or_insert_with(|| TileLayout::new(&state.entries, &tile.plane_ids));
+                    build_atlas(&state.entries, &tile.plane_ids, layout)
+                })
+                .collect()
+        }
+    }
 
-            let entries: Vec<AtlasEntry> =
-                mem::take(&mut self.0.lock().expect("mutex in atlas gatherer"));
-            // TODO: exit early if vec is empty
+    impl TileLayout {
+        /// Pack the slices described by `plane_ids` into a single image.
+        fn new(entries: &[AtlasEntry], plane_ids: &[usize]) -> Self {
+            use rectangle_pack as rp;
 
-            // TODO: add anti-bleed borders to atlas
             let mut rects_to_place: rp::GroupedRectsToPlace<usize, ()> =
                 rp::GroupedRectsToPlace::new();
-            for (
-                i,
-                &AtlasEntry {
-                    source_texels: _,
-                    source_bounds: _,
+            for (i, &plane_id) in plane_ids.iter().enumerate() {
+                let AtlasEntry {
                     sliced_bounds,
                     rotation,
-                },
-            ) in entries.iter().enumerate()
-            {
-                let size = sliced_bounds
-                    .transform(rotation.into())
-                    .unwrap(/* cannot overflow? */)
+                    ..
+                } = entries[plane_id];
+                let size = entries[plane_id]
+                    .rotated_slice_bounds()
                     .size()
                     .cast::<u32>()
                     .unwrap(/* cannot overflow */);
@@ -309,85 +517,177 @@ mod internal {
                 rects_to_place.push_rect(i, None, rp::RectToInsert::new(size.x, size.y, size.z));
             }
 
-            let mut texture_size = 1;
-            let placements = loop {
-                // "Bins" correspond to multiple textures. We will use one bin,
-                // because our goal is to fit everything into one texture rather than
-                // requiring multiple meshes.
-                let mut bins =
-                    BTreeMap::from([((), rp::TargetBin::new(texture_size, texture_size, 1))]);
-
-                match rp::pack_rects(
-                    &rects_to_place,
-                    &mut bins,
-                    &rp::volume_heuristic,
-                    &rp::contains_smallest_box,
-                ) {
-                    Ok(placements) => {
-                        break placements;
-                    }
-                    Err(rp::RectanglePackError::NotEnoughBinSpace) => {
-                        texture_size = texture_size.checked_mul(2).unwrap();
-                    }
+            let (size, placements) = pack(&rects_to_place);
+            let locations = (0..plane_ids.len())
+                .map(|i| {
+                    let ((), location) = placements.packed_locations()[&i];
+                    [location.x(), location.y()]
+                })
+                .collect();
+            Self { size, locations }
+        }
+    }
+
+    /// Copy the slices described by `plane_ids` into an image according to `layout`.
+    fn build_atlas(
+        entries: &[AtlasEntry],
+        plane_ids: &[usize],
+        layout: &TileLayout,
+    ) -> image::RgbaImage {
+        // TODO: add anti-bleed borders to atlas
+        let mut atlas_image = image::RgbaImage::new(layout.size, layout.size);
+
+        for (&plane_id, &slice_location_in_atlas) in plane_ids.iter().zip(&layout.locations) {
+            let entry = &entries[plane_id];
+
+            let rotated_slice_bounds = entry.rotated_slice_bounds();
+            let rotated_size = rotated_slice_bounds.size().cast::<u32>().unwrap(); // cannot overflow because nonnegative
+            let unrotate = entry.rotation.inverse();
+            let texels_size = entry.source_bounds.size();
+            let texels = entry
+                .source_texels
+                .get()
+                .expect("image texels not set -- TODO propagate error");
+
+            // Copy slice from 3D `texels` into 2D atlas image.
+            // TODO: Something is wrong in this code causing skewed outputs; not yet diagnosed.
+            for y in 0..rotated_size.y {
+                for x in 0..rotated_size.x {
+                    // Zero-offset position in the rotated-to-flat slice.
+                    let pixel_position = Point3::new(x, y, 0).cast::<i32>().unwrap();
+                    // Position in the rotated-to-flat slice's coordinates.
+                    let position_in_rotated_slice =
+                        Cube::from(pixel_position + rotated_slice_bounds.lower_bounds().to_vec());
+                    // TODO: this single cube is a kludge to simplify off-by-1 problems with rotation
+                    // We should have transform helpers instead of computing twice as many coordinates.
+                    let cube_in_rotated_slice = position_in_rotated_slice.grid_aab();
+
+                    // Position in the original requested slice's coordinates.
+                    let unrotated = cube_in_rotated_slice.transform(unrotate.into()).unwrap();
+                    assert!(
+                        entry.sliced_bounds.contains_box(unrotated),
+                        "{pixel_position:?} in {rotated_size:?} -> {unrotated:?} in {sliced_bounds:?}",
+                        sliced_bounds = entry.sliced_bounds
+                    );
+
+                    // Position within tile bounds.
+                    let position_in_texels =
+                        unrotated.lower_bounds() - entry.source_bounds.lower_bounds();
+                    // Index into the `texels` array at that position.
+                    let index_in_texels = (position_in_texels.x
+                        + texels_size.x * position_in_texels.y)
+                        + texels_size.y * position_in_texels.z;
+
+                    let texel = texels[usize::try_from(index_in_texels).unwrap()];
+                    atlas_image.put_pixel(
+                        slice_location_in_atlas[0] + x,
+                        slice_location_in_atlas[1] + y,
+                        image::Rgba(texel),
+                    );
                 }
-            };
+            }
+        }
 
-            let mut atlas_image = image::RgbaImage::new(texture_size, texture_size);
-
-            for (&index, &((), slice_location_in_atlas)) in placements.packed_locations() {
-                let entry = &entries[index];
-
-                let rotated_slice_bounds = entry.rotated_slice_bounds();
-                let rotated_size = rotated_slice_bounds.size().cast::<u32>().unwrap(); // cannot overflow because nonnegative
-                let unrotate = entry.rotation.inverse();
-                let texels_size = entry.source_bounds.size();
-                let texels = entry
-                    .source_texels
-                    .get()
-                    .expect("image texels not set -- TODO propagate error");
-
-                // Copy slice from 3D `texels` into 2D atlas image.
-                // TODO: Something is wrong in this code causing skewed outputs; not yet diagnosed.
-                for y in 0..rotated_size.y {
-                    for x in 0..rotated_size.x {
-                        // Zero-offset position in the rotated-to-flat slice.
-                        let pixel_position = Point3::new(x, y, 0).cast::<i32>().unwrap();
-                        // Position in the rotated-to-flat slice's coordinates.
-                        let position_in_rotated_slice = Cube::from(
-                            pixel_position + rotated_slice_bounds.lower_bounds().to_vec(),
-                        );
-                        // TODO: this single cube is a kludge to simplify off-by-1 problems with rotation
-                        // We should have transform helpers instead of computing twice as many coordinates.
-                        let cube_in_rotated_slice = position_in_rotated_slice.grid_aab();
-
-                        // Position in the original requested slice's coordinates.
-                        let unrotated = cube_in_rotated_slice.transform(unrotate.into()).unwrap();
-                        assert!(
-                            entry.sliced_bounds.contains_box(unrotated),
-                            "{pixel_position:?} in {rotated_size:?} -> {unrotated:?} in {sliced_bounds:?}",
-                            sliced_bounds = entry.sliced_bounds
-                        );
-
-                        // Position within tile bounds.
-                        let position_in_texels =
-                            unrotated.lower_bounds() - entry.source_bounds.lower_bounds();
-                        // Index into the `texels` array at that position.
-                        let index_in_texels = (position_in_texels.x
-                            + texels_size.x * position_in_texels.y)
-                            + texels_size.y * position_in_texels.z;
-
-                        let texel = texels[usize::try_from(index_in_texels).unwrap()];
-                        atlas_image.put_pixel(
-                            slice_location_in_atlas.x() + x,
-                            slice_location_in_atlas.y() + y,
-                            image::Rgba(texel),
-                        );
-                    }
+        atlas_image
+    }
+
+    /// Pack rectangles into the smallest power-of-two-sized square that fits them all.
+    ///
+    /// Returns the size of the square and the placement of each rectangle.
+    fn pack<K: Clone + Ord + std::hash::Hash + std::fmt::Debug>(
+        rects_to_place: &rectangle_pack::GroupedRectsToPlace<K, ()>,
+    ) -> (u32, rectangle_pack::RectanglePackOk<K, ()>) {
+        use rectangle_pack as rp;
+
+        let mut texture_size = 1;
+        loop {
+            // "Bins" correspond to multiple textures. We will use one bin,
+            // because our goal is to fit everything into one texture rather than
+            // requiring multiple meshes.
+            let mut bins =
+                BTreeMap::from([((), rp::TargetBin::new(texture_size, texture_size, 1))]);
+
+            match rp::pack_rects(
+                rects_to_place,
+                &mut bins,
+                &rp::volume_heuristic,
+                &rp::contains_smallest_box,
+            ) {
+                Ok(placements) => {
+                    return (texture_size, placements);
+                }
+                Err(rp::RectanglePackError::NotEnoughBinSpace) => {
+                    texture_size = texture_size.checked_mul(2).unwrap();
                 }
             }
+        }
+    }
 
-            atlas_image
+    /// Pack the images of individual blocks' textures, as produced by [`build_atlas()`],
+    /// into a single image.
+    ///
+    /// Returns the image and the region of it that each input image occupies.
+    pub(crate) fn pack_tile_images(
+        tile_images: &[image::RgbaImage],
+    ) -> (image::RgbaImage, Vec<TileRegion>) {
+        use rectangle_pack as rp;
+
+        let mut rects_to_place: rp::GroupedRectsToPlace<usize, ()> = rp::GroupedRectsToPlace::new();
+        for (i, tile_image) in tile_images.iter().enumerate() {
+            rects_to_place.push_rect(
+                i,
+                None,
+                rp::RectToInsert::new(tile_image.width(), tile_image.height(), 1),
+            );
         }
+
+        let (texture_size, placements) = pack(&rects_to_place);
+
+        let mut atlas_image = image::RgbaImage::new(texture_size, texture_size);
+        let regions = tile_images
+            .iter()
+            .enumerate()
+            .map(|(i, tile_image)| {
+                let ((), location) = placements.packed_locations()[&i];
+                image::imageops::replace(
+                    &mut atlas_image,
+                    tile_image,
+                    location.x().into(),
+                    location.y().into(),
+                );
+                let size = texture_size as f32;
+                TileRegion {
+                    offset: [location.x() as f32 / size, location.y() as f32 / size],
+                    scale: [
+                        location.width() as f32 / size,
+                        location.height() as f32 / size,
+                    ],
+                }
+            })
+            .collect();
+
+        (atlas_image, regions)
+    }
+
+    /// The region of an image occupied by one block's texture, in texture coordinates
+    /// ranging from 0 to 1 over the whole image.
+    ///
+    /// These are the parameters of a `KHR_texture_transform` that maps texture
+    /// coordinates in the block's own texture to coordinates in the image.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub(crate) struct TileRegion {
+        /// Position of the region's top left corner.
+        pub offset: [f32; 2],
+        /// Size of the region.
+        pub scale: [f32; 2],
+    }
+
+    impl TileRegion {
+        /// The region covering the whole image, which needs no transform.
+        pub const WHOLE: Self = Self {
+            offset: [0.0, 0.0],
+            scale: [1.0, 1.0],
+        };
     }
 
     impl PartialEq for Gatherer {
@@ -407,6 +707,8 @@ mod internal {
         pub(super) sliced_bounds: GridAab,
         /// Rotation that will rotate `sliced_bounds` to be flat in the atlas.
         pub(super) rotation: GridRotation,
+        /// Index of the group of entries this belongs to; set by [`Gatherer::insert()`].
+        pub(super) tile: usize,
     }
 
     impl AtlasEntry {
@@ -422,7 +724,10 @@ mod internal {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::gltf::GltfVertex;
+    use all_is_cubes::camera::GraphicsOptions;
     use all_is_cubes_mesh::texture::{Allocator, Tile};
+    use all_is_cubes_mesh::{BlockMesh, MeshOptions};
     use std::fs;
 
     /// TODO: this is just a smoke-test; add more rigorous tests.
@@ -440,7 +745,7 @@ mod tests {
         tile.write(&[[0, 1, 2, 3]]);
         drop(tile);
 
-        allocator.write_png_atlas().unwrap();
+        allocator.write_png_images(GltfTextureStyle::Atlas).unwrap();
 
         assert_eq!(
             fs::read_dir(temp_dir.path())
@@ -450,4 +755,112 @@ mod tests {
             vec!["filetest-texture.png"],
         );
     }
+
+    #[test]
+    fn per_block_style_writes_one_image_per_block() {
+        let mut universe = all_is_cubes::universe::Universe::new();
+        let blocks: [all_is_cubes::block::Block; 2] =
+            all_is_cubes::content::make_some_voxel_blocks(&mut universe);
+
+        let texture_count = |style: GltfTextureStyle| -> usize {
+            let allocator = GltfTextureAllocator::new(GltfDataDestination::null(), true);
+            let mesh_options = MeshOptions::new(&GraphicsOptions::default());
+            for block in &blocks {
+                let _: BlockMesh<GltfVertex, GltfTile> =
+                    BlockMesh::new(&block.evaluate().unwrap(), &allocator, &mesh_options);
+            }
+            let mut root = gltf_json::Root::default();
+            let tile_textures = insert_block_textures(&mut root, &allocator, style).unwrap();
+            assert_eq!(tile_textures.len(), blocks.len());
+            assert_eq!(root.textures.len(), root.images.len());
+            root.images.len()
+        };
+
+        assert_eq!(texture_count(GltfTextureStyle::Atlas), 1);
+        assert_eq!(texture_count(GltfTextureStyle::PerBlock), 2);
+    }
+
+    /// Mesh `blocks` and return the [`gltf_json::Root`] containing their textures and the
+    /// texture transform of each block's texture reference.
+    fn block_texture_transforms(
+        style: GltfTextureStyle,
+    ) -> (gltf_json::Root, Vec<Option<[[f32; 2]; 2]>>) {
+        let mut universe = all_is_cubes::universe::Universe::new();
+        let blocks: [all_is_cubes::block::Block; 2] =
+            all_is_cubes::content::make_some_voxel_blocks(&mut universe);
+        let allocator = GltfTextureAllocator::new(GltfDataDestination::null(), true);
+        let mesh_options = MeshOptions::new(&GraphicsOptions::default());
+        for block in &blocks {
+            let _: BlockMesh<GltfVertex, GltfTile> =
+                BlockMesh::new(&block.evaluate().unwrap(), &allocator, &mesh_options);
+        }
+
+        let mut root = gltf_json::Root::default();
+        let tile_textures = insert_block_textures(&mut root, &allocator, style).unwrap();
+        let transforms = tile_textures
+            .iter()
+            .map(|info| {
+                info.extensions
+                    .as_ref()
+                    .and_then(|e| e.texture_transform.as_ref())
+                    .map(|t| [t.offset.0, t.scale.0])
+            })
+            .collect();
+        (root, transforms)
+    }
+
+    #[test]
+    fn atlas_textures_have_texture_transform() {
+        let (root, transforms) = block_texture_transforms(GltfTextureStyle::Atlas);
+
+        assert_eq!(root.extensions_used, vec![TEXTURE_TRANSFORM_EXTENSION]);
+        let regions: Vec<[[f32; 2]; 2]> = transforms
+            .into_iter()
+            .map(|t| t.expect("atlas texture has no texture transform"))
+            .collect();
+        assert_eq!(regions.len(), 2);
+        for [offset, scale] in &regions {
+            for axis in 0..2 {
+                assert!(scale[axis] > 0.0 && offset[axis] >= 0.0);
+                assert!(offset[axis] + scale[axis] <= 1.0, "{regions:?}");
+            }
+        }
+        // The two blocks' regions must not overlap.
+        let [[o0, s0], [o1, s1]] = [regions[0], regions[1]];
+        assert!(
+            (0..2).any(|axis| o0[axis] + s0[axis] <= o1[axis] || o1[axis] + s1[axis] <= o0[axis]),
+            "{regions:?}"
+        );
+    }
+
+    #[test]
+    fn per_block_textures_have_no_texture_transform() {
+        let (root, transforms) = block_texture_transforms(GltfTextureStyle::PerBlock);
+
+        assert_eq!(root.extensions_used, Vec::<String>::new());
+        assert_eq!(transforms, vec![None, None]);
+    }
+
+    /// Each [`internal::TileRegion`] locates its tile's image within the atlas.
+    #[test]
+    fn tile_regions_match_atlas() {
+        let tile_images = [
+            image::RgbaImage::from_pixel(4, 4, image::Rgba([255, 0, 0, 255])),
+            image::RgbaImage::from_pixel(2, 2, image::Rgba([0, 255, 0, 255])),
+            image::RgbaImage::from_pixel(2, 2, image::Rgba([0, 0, 255, 255])),
+        ];
+        let (atlas, regions) = internal::pack_tile_images(&tile_images);
+
+        assert_eq!(atlas.dimensions(), (8, 8));
+        for (tile_image, region) in tile_images.iter().zip(regions) {
+            let size = atlas.width() as f32;
+            let [x, y] = region.offset.map(|c| (c * size) as u32);
+            let [w, h] = region.scale.map(|c| (c * size) as u32);
+            assert_eq!((w, h), tile_image.dimensions());
+            assert_eq!(
+                &*image::imageops::crop_imm(&atlas, x, y, w, h).to_image(),
+                &**tile_image
+            );
+        }
+    }
 }
@@ -1,6 +1,6 @@
 //! [`GltfVertex`], vertex type for writing to glTF buffers.
 
-use all_is_cubes::cgmath::{EuclideanSpace as _, Point3, Vector3};
+use all_is_cubes::cgmath::{EuclideanSpace as _, Point2, Point3, Vector3};
 use all_is_cubes::math::Cube;
 use all_is_cubes_mesh::{BlockVertex, Coloring, GfxVertex};
 
@@ -13,7 +13,9 @@ use super::texture::GltfAtlasPoint;
 /// little-endian as per the specification.
 ///
 /// These vertices store no normals; they would be redundant since glTF 2.0 specification
-/// § 3.7.2.1 specifies that flat normals must be assumed.
+/// § 3.7.2.1 specifies that flat normals must be assumed. If smooth normals are
+/// requested with [`GltfWriter::set_normals()`](super::GltfWriter::set_normals), they are
+/// computed when the mesh is written and stored separately.
 #[derive(Clone, Copy, Debug, Default, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
 #[repr(C)]
 pub struct GltfVertex {
@@ -36,6 +38,24 @@ impl GltfVertex {
         base_color: [Lef32::ZERO; 4],
         base_color_tc: [Lef32::ZERO; 2],
     };
+
+    /// If this vertex is textured, returns the texture point which the conversion from
+    /// [`BlockVertex`] temporarily stored in it. Such vertices must have their texture coordinates set
+    /// by [`GltfVertex::set_texture_coordinates()`] before they are written.
+    pub(crate) fn texture_placeholder(&self) -> Option<GltfAtlasPoint> {
+        let [id_low, id_high, blue, alpha] = self.base_color.map(f32::from);
+        (blue < 0. && alpha < 0.).then(|| GltfAtlasPoint {
+            plane_id: u64::from(id_low.to_bits()) | (u64::from(id_high.to_bits()) << 32),
+            point_within: Point2::from(self.base_color_tc.map(f32::from)),
+        })
+    }
+
+    /// Make this vertex use the texture coordinates `tc`, replacing its texture
+    /// placeholder. The color is set to white so that the texture color is unchanged.
+    pub(crate) fn set_texture_coordinates(&mut self, tc: Point2<f32>) {
+        self.base_color = [Lef32::from(1.0); 4];
+        self.base_color_tc = Lef32::from_vec2(tc.to_vec());
+    }
 }
 
 impl From<BlockVertex<GltfAtlasPoint>> for GltfVertex {
@@ -4,13 +4,16 @@
 //!
 //! | Format              | Extension         | Import  | Export  | Caveats |
 //! |---------------------|-------------------|:-------:|:-------:|---------|
-//! | All is Cubes native | `.alliscubesjson` | **Yes** | **Yes** | Version compatibility not yet guaranteed. |
+//! | All is Cubes native | `.alliscubesjson` | **Yes** | **Yes** | Version compatibility not yet guaranteed. Patches between universes are supported separately; see [`export_patch()`]. |
 //! | MagicaVoxel `.vox`  | `.vox`            | **Yes** | **Yes** | Materials, scenes, and layers are ignored. |
-//! | [glTF 2.0]          | `.gltf`           | —       | **Yes** | Textures are not yet implemented. Output is suitable for rendering but not necessarily editing due to combined meshes. |
+//! | [glTF 2.0]          | `.gltf`           | —       | **Yes** | Textures are not yet implemented. Output is suitable for rendering but not necessarily editing due to combined meshes. Characters are exported as cameras only. |
 //! | [STL]               | `.stl`            | —       | **Yes** | Meshes are not necessarily “manifold”/“watertight”. |
+//! | Light volume        | `.aiclight`       | —       | **Yes** | Raw light data only; see [`ExportFormat::LightVolume`]. |
+//! | [PLY]               | `.ply`            | —       | **Yes** | Point clouds of spaces only; see [`ExportFormat::Ply`]. |
 //!
 //! [glTF 2.0]: https://registry.khronos.org/glTF/specs/2.0/glTF-2.0.html
 //! [STL]: <https://en.wikipedia.org/wiki/STL_(file_format)>
+//! [PLY]: <https://en.wikipedia.org/wiki/PLY_(file_format)>
 
 // Basic lint settings, which should be identical across all all-is-cubes project crates.
 // This list is sorted.
@@ -47,7 +50,8 @@
 #![forbid(unsafe_code)]
 #![warn(missing_docs)]
 
-use std::ffi::OsString;
+use std::ffi::{OsStr, OsString};
+use std::fmt;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
@@ -55,16 +59,33 @@ use std::sync::Arc;
 use futures_core::future::BoxFuture;
 
 use all_is_cubes::block::{self, BlockDef};
-use all_is_cubes::space::Space;
-use all_is_cubes::universe::{self, PartialUniverse, URef, Universe};
+use all_is_cubes::cgmath::EuclideanSpace as _;
+use all_is_cubes::character::{Character, CharacterTransaction, Spawn};
+use all_is_cubes::inv::InventoryTransaction;
+use all_is_cubes::math::{GridAab, Rgb};
+use all_is_cubes::space::{LightPhysics, Space};
+use all_is_cubes::time::{Duration, NoTime};
+use all_is_cubes::transaction::{self, Merge as _, Transaction as _};
+use all_is_cubes::universe::{self, Name, PartialUniverse, URef, Universe};
 use all_is_cubes::util::YieldProgress;
 
+mod block_icon;
+pub use block_icon::render_block_icon;
 pub mod file;
 pub mod gltf;
+mod light_volume;
 mod mv;
+mod ply;
 use mv::load_dot_vox;
 mod native;
+pub use native::{apply_patch, export_patch};
+mod slice_image;
+pub use slice_image::{draw_space_slice, space_slice_image};
 mod stl;
+mod unblock;
+use unblock::unblock;
+mod voxelize;
+pub use voxelize::{voxelize_triangles, VoxelizationMode};
 
 #[cfg(test)]
 mod tests;
@@ -76,26 +97,89 @@ pub async fn load_universe_from_file(
     progress: YieldProgress,
     file: Arc<dyn file::Fileish>,
 ) -> Result<Universe, ImportError> {
+    load_universe_from_file_with_options(progress, file, &ImportOptions::default()).await
+}
+
+/// Load a [`Universe`] described by the given file (of guessed format), using `options`
+/// to fill in anything the file does not specify.
+pub async fn load_universe_from_file_with_options(
+    progress: YieldProgress,
+    file: Arc<dyn file::Fileish>,
+    options: &ImportOptions,
+) -> Result<Universe, ImportError> {
+    let (universe, member_errors) = load_universe_impl(progress, file, options, false).await?;
+    debug_assert!(member_errors.is_empty());
+    Ok(universe)
+}
+
+/// Load a [`Universe`] described by the given file (of guessed format), skipping any
+/// members that cannot be loaded rather than failing entirely.
+/// This is intended for recovering data from damaged files.
+///
+/// Returns the universe along with the name of each member that could not be loaded
+/// and the reason why. References to such members are replaced with placeholders.
+///
+/// Currently, only the native format supports recovery; imports of other formats
+/// succeed or fail as [`load_universe_from_file()`] would.
+pub async fn load_universe_from_file_recovering(
+    progress: YieldProgress,
+    file: Arc<dyn file::Fileish>,
+) -> Result<(Universe, Vec<(Name, ImportErrorKind)>), ImportError> {
+    load_universe_impl(progress, file, &ImportOptions::default(), true).await
+}
+
+async fn load_universe_impl(
+    progress: YieldProgress,
+    file: Arc<dyn file::Fileish>,
+    options: &ImportOptions,
+    recover: bool,
+) -> Result<(Universe, Vec<(Name, ImportErrorKind)>), ImportError> {
+    let limit_error = |error: LimitExceeded| ImportError {
+        source_path: file.display_full_path(),
+        detail: error.into(),
+    };
+
+    // Check the size before reading, if we can, so as not to read a huge file at all,
+    // and then check the size of what was actually read.
+    if let Some(size) = file.size_hint() {
+        options
+            .limits
+            .check(LimitedResource::Bytes, size)
+            .map_err(limit_error)?;
+    }
     // TODO: use extension, if any, for format detection
     let bytes = file.read().map_err(|error| ImportError {
         source_path: file.display_full_path(),
         detail: ImportErrorKind::Read { path: None, error },
     })?;
+    options
+        .limits
+        .check(LimitedResource::Bytes, saturating_u64(bytes.len()))
+        .map_err(limit_error)?;
 
-    let (mut universe, save_format) = if bytes.starts_with(b"{") {
+    let (mut universe, member_errors, save_format) = if bytes.starts_with(b"{") {
         // Assume it's JSON. Furthermore, assume it's ours.
-        (
-            native::import_native_json(progress, &bytes, &*file)?,
-            Some(ExportFormat::AicJson),
-        )
+        let (universe, member_errors) = if recover {
+            native::import_native_json_recovering(progress, &bytes, &options.limits, &*file)?
+        } else {
+            (
+                native::import_native_json(progress, &bytes, &options.limits, &*file)?,
+                Vec::new(),
+            )
+        };
+        (universe, member_errors, Some(ExportFormat::AicJson))
     } else if bytes.starts_with(b"VOX ") {
         (
-            load_dot_vox(progress, &bytes)
+            load_dot_vox(progress, &bytes, options)
                 .await
                 .map_err(|error| ImportError {
                     source_path: file.display_full_path(),
-                    detail: ImportErrorKind::Parse(Box::new(error)),
+                    detail: match error {
+                        mv::DotVoxConversionError::ResourceLimit(error) => error.into(),
+                        error => ImportErrorKind::Parse(Box::new(error)),
+                    },
                 })?,
+            Vec::new(),
             Some(ExportFormat::DotVox),
         )
     } else {
@@ -107,7 +191,124 @@ pub async fn load_universe_from_file(
 
     universe.whence = Arc::new(PortWhence { file, save_format });
 
-    Ok(universe)
+    Ok((universe, member_errors))
+}
+
+/// Choices for data which imported files do not specify, and limits on what they may
+/// contain, for [`load_universe_from_file_with_options()`].
+///
+/// Each [`Option`] field left as [`None`] keeps the importer's usual choice.
+/// Currently, those options affect only MagicaVoxel `.vox` files, since the native format
+/// always specifies them.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[non_exhaustive]
+pub struct ImportOptions {
+    /// Sky color of imported [`Space`]s.
+    /// If [`None`], the sky is white.
+    pub sky_color: Option<Rgb>,
+
+    /// Light physics of imported [`Space`]s.
+    /// If [`None`], [`LightPhysics::Rays`] is used with a maximum distance equal to the
+    /// height of the space.
+    pub light_physics: Option<LightPhysics>,
+
+    /// Limits on the resources the import may consume.
+    pub limits: ImportLimits,
+}
+
+/// Limits on the size of data that will be imported, to protect against running out of
+/// memory when importing untrusted files. Part of [`ImportOptions`].
+///
+/// If any limit is exceeded, the import fails with [`ImportErrorKind::ResourceLimit`].
+/// Limits are checked before the corresponding data is fully constructed, where the format
+/// allows.
+///
+/// The [`Default`] limits are generous, intended to admit any reasonable file while
+/// still being finite.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct ImportLimits {
+    /// Maximum size of the file, in bytes.
+    pub max_bytes: u64,
+
+    /// Maximum number of members of the resulting [`Universe`].
+    pub max_members: u64,
+
+    /// Maximum total volume, in cubes, of all [`Space`]s in the resulting [`Universe`].
+    pub max_space_volume: u64,
+}
+
+impl Default for ImportLimits {
+    fn default() -> Self {
+        Self {
+            max_bytes: 1 << 30,
+            max_members: 1 << 20,
+            max_space_volume: 1 << 28,
+        }
+    }
+}
+
+impl ImportLimits {
+    /// Returns an error if `amount` exceeds the limit for `resource`.
+    pub(crate) fn check(
+        &self,
+        resource: LimitedResource,
+        amount: u64,
+    ) -> Result<(), LimitExceeded> {
+        let limit = match resource {
+            LimitedResource::Bytes => self.max_bytes,
+            LimitedResource::Members => self.max_members,
+            LimitedResource::SpaceVolume => self.max_space_volume,
+        };
+        if amount > limit {
+            Err(LimitExceeded { resource, limit })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// A resource which is limited by [`ImportLimits`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum LimitedResource {
+    /// Size of the file; limited by [`ImportLimits::max_bytes`].
+    Bytes,
+    /// Number of universe members; limited by [`ImportLimits::max_members`].
+    Members,
+    /// Total volume of spaces; limited by [`ImportLimits::max_space_volume`].
+    SpaceVolume,
+}
+
+impl fmt::Display for LimitedResource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LimitedResource::Bytes => "bytes",
+            LimitedResource::Members => "members",
+            LimitedResource::SpaceVolume => "cubes of space volume",
+        })
+    }
+}
+
+/// Error from [`ImportLimits::check()`], which is converted to
+/// [`ImportErrorKind::ResourceLimit`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, thiserror::Error)]
+#[error("the data exceeds the import limit of {limit} {resource}")]
+pub(crate) struct LimitExceeded {
+    pub(crate) resource: LimitedResource,
+    pub(crate) limit: u64,
+}
+
+impl From<LimitExceeded> for ImportErrorKind {
+    fn from(LimitExceeded { resource, limit }: LimitExceeded) -> Self {
+        ImportErrorKind::ResourceLimit { resource, limit }
+    }
+}
+
+/// Converts a size to [`u64`] for comparison with [`ImportLimits`], saturating if it
+/// does not fit.
+pub(crate) fn saturating_u64(size: usize) -> u64 {
+    u64::try_from(size).unwrap_or(u64::MAX)
 }
 
 /// Export data specified by an [`ExportSet`] to a file on disk.
@@ -115,6 +316,10 @@ pub async fn load_universe_from_file(
 /// If the format requires multiple files, then they will be named with hyphenated suffixes
 /// before the extension; i.e. "foo.gltf" becomes "foo-bar.gltf".
 ///
+/// If the format [includes light](ExportFormat::includes_light), then the light of each
+/// space is finished being computed first, unless [`ExportSet::with_current_light()`]
+/// was used; this modifies the spaces.
+///
 /// TODO: Generalize this or add a parallel function for non-filesystem destinations.
 pub async fn export_to_path(
     progress: YieldProgress,
@@ -122,17 +327,43 @@ pub async fn export_to_path(
     source: ExportSet,
     destination: PathBuf,
 ) -> Result<(), crate::ExportError> {
+    let mut source = source
+        .with_space_region_applied()?
+        .with_character_inventory_applied()?;
+    let progress = if format.includes_light() && !source.current_light {
+        let [light_progress, rest] = progress.split(0.5);
+        source.finish_light(light_progress).await?;
+        rest
+    } else {
+        progress
+    };
+    // Held until the export is complete.
+    let _pins = source.pin_dependencies(format)?;
     match format {
         ExportFormat::AicJson => native::export_native_json(progress, source, destination).await,
         ExportFormat::DotVox => {
             // TODO: async file IO?
-            mv::export_dot_vox(progress, source, fs::File::create(destination)?).await
+            let file = fs::File::create(&destination).map_err(|error| ExportError::Write {
+                path: Some(destination.clone()),
+                error,
+            })?;
+            mv::export_dot_vox(progress, source, file)
+                .await
+                .map_err(|error| error.with_path(&destination))
         }
         ExportFormat::Gltf => gltf::export_gltf(progress, source, destination).await,
         ExportFormat::Stl => stl::export_stl(progress, source, destination).await,
+        ExportFormat::LightVolume => {
+            light_volume::export_light_volume(progress, source, destination).await
+        }
+        ExportFormat::Ply => ply::export_ply(progress, source, destination).await,
     }
 }
 
+/// Amount of time to spend on light updates between progress reports, when finishing
+/// light before export.
+const LIGHT_UPDATE_BUDGET: Duration = Duration::from_millis(20);
+
 /// Selection of the data to be exported.
 #[derive(Clone, Debug)]
 pub struct ExportSet {
@@ -140,65 +371,427 @@ pub struct ExportSet {
     /// to the same serialization helpers as `Universe` and be guaranteed to serialize the
     /// exact same way.
     contents: PartialUniverse,
+
+    /// Whether to export spaces cropped to their occupied region; see
+    /// [`ExportSet::with_spaces_cropped()`].
+    crop_spaces: bool,
+
+    /// If not [`None`], the single space in `contents` is to be exported as only this
+    /// region of it; see [`ExportSet::from_space_region()`].
+    /// This is applied by [`export_to_path()`] before the format-specific code sees it.
+    space_region: Option<GridAab>,
+
+    /// If true, the single character in `contents` is to be exported as only its
+    /// inventory; see [`ExportSet::from_character_inventory()`].
+    /// This is applied by [`export_to_path()`] before the format-specific code sees it.
+    character_inventory: bool,
+
+    /// Whether JSON-based formats should be written indented for readability;
+    /// see [`ExportSet::with_pretty_json()`].
+    pretty_json: bool,
+
+    /// Whether to export light as it currently is instead of finishing computing it
+    /// first; see [`ExportSet::with_current_light()`].
+    current_light: bool,
 }
 
 impl ExportSet {
     /// Construct an [`ExportSet`] specifying exporting all members of the universe
-    /// (insofar as that is possible).
+    /// (insofar as that is possible). Members are exported in order of their [`Name`]s.
     ///
     /// Any members added between the call to this function and the export operation will
-    /// not be included; removals may cause errors.
+    /// not be included. Members removed before the export begins cause it to fail with
+    /// [`ExportError::Read`]; members removed while it is in progress are still exported.
     pub fn all_of_universe(universe: &Universe) -> Self {
         Self {
             contents: PartialUniverse::all_of(universe),
+            crop_spaces: false,
+            space_region: None,
+            character_inventory: false,
+            pretty_json: false,
+            current_light: false,
         }
     }
 
-    /// Construct an [`ExportSet`] specifying exporting only the given [`BlockDef`]s.
+    /// Construct an [`ExportSet`] specifying exporting only the given [`BlockDef`]s,
+    /// in the given order.
     pub fn from_block_defs(block_defs: Vec<URef<BlockDef>>) -> Self {
         Self {
             contents: PartialUniverse::from_set(block_defs),
+            crop_spaces: false,
+            space_region: None,
+            character_inventory: false,
+            pretty_json: false,
+            current_light: false,
         }
     }
 
-    /// Construct an [`ExportSet`] specifying exporting only the given [`Space`]s.
+    /// Construct an [`ExportSet`] specifying exporting only the given [`Space`]s,
+    /// in the given order.
     pub fn from_spaces(spaces: Vec<URef<Space>>) -> Self {
         Self {
             contents: PartialUniverse::from_set(spaces),
+            crop_spaces: false,
+            space_region: None,
+            character_inventory: false,
+            pretty_json: false,
+            current_light: false,
+        }
+    }
+
+    /// Construct an [`ExportSet`] specifying exporting only the given [`Character`]s,
+    /// in the given order.
+    ///
+    /// Formats which can represent characters do so in their own way; for example,
+    /// glTF exports them as cameras.
+    pub fn from_characters(characters: Vec<URef<Character>>) -> Self {
+        Self {
+            contents: PartialUniverse::from_set(characters),
+            crop_spaces: false,
+            space_region: None,
+            character_inventory: false,
+            pretty_json: false,
+            current_light: false,
+        }
+    }
+
+    /// Construct an [`ExportSet`] specifying exporting only the cubes of `space` which
+    /// are within `bounds`, as if they were a separate space whose lower corner is at
+    /// the origin.
+    ///
+    /// If `bounds` does not intersect the bounds of the space at the time of export,
+    /// then the export will fail with [`ExportError::NotRepresentable`].
+    pub fn from_space_region(space: URef<Space>, bounds: GridAab) -> Self {
+        Self {
+            contents: PartialUniverse::from_set([space]),
+            crop_spaces: false,
+            space_region: Some(bounds),
+            character_inventory: false,
+            pretty_json: false,
+            current_light: false,
         }
     }
 
+    /// Construct an [`ExportSet`] specifying exporting only the inventory of `character`
+    /// (its tools, and the blocks they refer to), so that it may be shared and imported
+    /// separately from the rest of the universe.
+    ///
+    /// The export contains a character with the same name, inventory, and selected
+    /// slots as `character`, in a placeholder space. Its other properties are not
+    /// exported. This is supported only by [`ExportFormat::AicJson`].
+    pub fn from_character_inventory(character: URef<Character>) -> Self {
+        Self {
+            contents: PartialUniverse::from_set([character]),
+            crop_spaces: false,
+            space_region: None,
+            character_inventory: true,
+            pretty_json: false,
+            current_light: false,
+        }
+    }
+
+    /// Returns the number of universe members this set specifies exporting.
+    pub fn len(&self) -> usize {
+        self.contents.count()
+    }
+
+    /// Returns whether this set specifies exporting nothing.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the members this set specifies exporting, in the order they will be
+    /// exported (which determines, for example, the order of scenes in a glTF file).
+    ///
+    /// Members of different types are listed with [`BlockDef`]s first, then
+    /// [`Character`](all_is_cubes::character::Character)s, then [`Space`]s.
+    pub fn members(&self) -> Vec<universe::AnyURef> {
+        self.contents.members()
+    }
+
+    /// Checks whether this set can be exported in the given format, without actually
+    /// performing the export; for example, to explain to the user why an export option
+    /// is unavailable.
+    ///
+    /// Returns the first [`ExportError::NotRepresentable`] error (or other error reading
+    /// the data) which the export would encounter. Success does not guarantee that the
+    /// export will succeed, since it may still encounter IO errors, or the exported
+    /// members may be modified in the meantime.
+    pub fn preflight(&self, format: ExportFormat) -> Result<(), ExportError> {
+        for space_ref in &self.contents.spaces {
+            self.exported_space_bounds(space_ref)?;
+        }
+        if self.character_inventory && format != ExportFormat::AicJson {
+            return Err(ExportError::NotRepresentable {
+                name: self.contents.characters.first().map(URef::name),
+                reason: format!("exporting only an inventory is not supported in {format:?}"),
+            });
+        }
+        match format {
+            ExportFormat::AicJson => native::preflight_native(self),
+            ExportFormat::Stl => Ok(()),
+            ExportFormat::DotVox => mv::preflight_dot_vox(self),
+            ExportFormat::Gltf => gltf::preflight_gltf(self),
+            ExportFormat::LightVolume => light_volume::preflight_light_volume(self),
+            ExportFormat::Ply => ply::preflight_ply(self),
+        }
+    }
+
+    /// Export each space as only the smallest region containing all of its
+    /// non-[`AIR`](block::AIR) blocks, translated so that the lower corner of that
+    /// region is at the origin, rather than exporting its entire bounds.
+    ///
+    /// This is currently supported only by [`ExportFormat::Stl`]; exporting spaces to
+    /// any other format with this option fails with [`ExportError::NotRepresentable`].
+    /// STL files cannot record the translation that was applied, so it is logged instead.
+    #[must_use]
+    pub fn with_spaces_cropped(mut self) -> Self {
+        self.crop_spaces = true;
+        self
+    }
+
+    /// Returns an error if spaces are to be cropped, for use by the preflight checks of
+    /// formats which do not support cropping, so that they refuse rather than exporting
+    /// spaces uncropped.
+    pub(crate) fn check_cropping(&self, format: ExportFormat) -> Result<(), ExportError> {
+        match self.contents.spaces.first() {
+            Some(space_ref) if self.crop_spaces => Err(ExportError::NotRepresentable {
+                name: Some(space_ref.name()),
+                reason: format!("cropping spaces is not supported in {format:?}"),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Write JSON-based formats ([`ExportFormat::AicJson`] and [`ExportFormat::Gltf`])
+    /// with indentation and line breaks, for readability, instead of compactly.
+    /// This does not change the data exported, only its formatting.
+    ///
+    /// Other formats are unaffected.
+    #[must_use]
+    pub fn with_pretty_json(mut self) -> Self {
+        self.pretty_json = true;
+        self
+    }
+
+    /// Export the light of [`Space`]s as it currently is, even if it has not finished
+    /// being computed.
+    ///
+    /// By default, when exporting to a format which
+    /// [includes light](ExportFormat::includes_light), all pending light updates of
+    /// each space are performed before it is exported, which may take a while for large
+    /// or freshly modified spaces. With this option, the export does not wait, but the
+    /// light data it contains may be incomplete and inconsistent. (The native format
+    /// records which cubes' light is not yet computed, so that it will be computed when
+    /// the space is loaded; other formats cannot.)
+    #[must_use]
+    pub fn with_current_light(mut self) -> Self {
+        self.current_light = true;
+        self
+    }
+
+    /// If this set has a `space_region`, replace the space with a copy of that region.
+    fn with_space_region_applied(mut self) -> Result<Self, ExportError> {
+        let Some(region) = self.space_region.take() else {
+            return Ok(self);
+        };
+        for space_ref in &mut self.contents.spaces {
+            let name = space_ref.name();
+            let space = space_ref.read()?;
+            let region = exported_bounds(&name, &space, Some(region))?;
+            let extracted = extract_space_region(&space, region);
+            drop(space);
+            *space_ref = URef::new_pending(name, extracted);
+        }
+        Ok(self)
+    }
+
+    /// If this set is to export a character's inventory, replace the character with a
+    /// copy which has only that inventory, in a placeholder space.
+    fn with_character_inventory_applied(mut self) -> Result<Self, ExportError> {
+        if !std::mem::take(&mut self.character_inventory) {
+            return Ok(self);
+        }
+        for character_ref in &mut self.contents.characters {
+            let character = character_ref.read()?;
+            let bounds = GridAab::ORIGIN_CUBE;
+            let spawn = Spawn::default_for_new_space(bounds);
+            let space = URef::new_pending(
+                Name::from("inventory space"),
+                Space::builder(bounds).spawn(spawn.clone()).build(),
+            );
+            let mut copy = Character::spawn(&spawn, space);
+
+            // Copy the slots individually, rather than via the spawn, so that they keep
+            // their positions.
+            let mut txn = InventoryTransaction::default();
+            for (i, (old, new)) in copy
+                .inventory()
+                .slots
+                .iter()
+                .zip(character.inventory().slots.iter())
+                .enumerate()
+            {
+                if old != new {
+                    txn = txn
+                        .merge(InventoryTransaction::replace(i, old.clone(), new.clone()))
+                        .expect("distinct slots cannot conflict");
+                }
+            }
+            CharacterTransaction::inventory(txn)
+                .execute(&mut copy, &mut transaction::no_outputs)
+                .expect("inventory copy failed");
+            for (which, slot) in character.selected_slots().into_iter().enumerate() {
+                copy.set_selected_slot(which, slot);
+            }
+            drop(character);
+            *character_ref = URef::new_pending(character_ref.name(), copy);
+        }
+        Ok(self)
+    }
+
+    /// Perform all pending light updates of the spaces in this set, so that their
+    /// light is complete when exported; see [`ExportSet::with_current_light()`].
+    async fn finish_light(&self, progress: YieldProgress) -> Result<(), ExportError> {
+        let spaces = &self.contents.spaces;
+        for (mut p, space_ref) in progress.split_evenly(spaces.len()).zip(spaces) {
+            p.set_label(format!("Computing light of {}", space_ref.name()));
+            // The queue may grow as updates are performed, so estimate the total amount
+            // of work as the most that has been seen so far.
+            let mut max_work = 0;
+            let mut fraction_done: f32 = 0.0;
+            loop {
+                let info = space_ref.try_modify(|space| {
+                    space.update_lighting_from_queue::<NoTime>(Some(LIGHT_UPDATE_BUDGET))
+                })?;
+                if info.queue_count == 0 {
+                    break;
+                }
+                max_work = max_work.max(info.queue_count + info.update_count);
+                fraction_done = fraction_done.max(1.0 - info.queue_count as f32 / max_work as f32);
+                p.progress(fraction_done).await;
+            }
+            p.finish().await;
+        }
+        Ok(())
+    }
+
+    /// Find every member that the members of this set transitively refer to, and keep them
+    /// all readable until the returned value is dropped, so that removing them from their
+    /// universe during the export cannot cause it to fail.
+    ///
+    /// If `format` is one which writes references to other members by name, the members
+    /// found are also added to this set, so that the export is self-contained.
+    ///
+    /// Fails with [`ExportError::Read`] if any of the members is already gone.
+    fn pin_dependencies(
+        &mut self,
+        format: ExportFormat,
+    ) -> Result<universe::PinnedMembers, ExportError> {
+        let mut resolved = self.contents.clone();
+        let pins = resolved.include_and_pin_dependencies()?;
+        if format == ExportFormat::AicJson {
+            self.contents = resolved;
+        }
+        Ok(pins)
+    }
+
+    /// Returns the bounds of the part of `space_ref` that will actually be exported,
+    /// taking into account [`ExportSet::from_space_region()`].
+    pub(crate) fn exported_space_bounds(
+        &self,
+        space_ref: &URef<Space>,
+    ) -> Result<GridAab, ExportError> {
+        exported_bounds(&space_ref.name(), &*space_ref.read()?, self.space_region)
+    }
+
     /// Calculate the file path to use supposing that we want to export one member to one file
     /// (as opposed to all members into one file).
     ///
     /// This has a suffix added for uniqueness (after the name but preserving the existing
     /// extension), based on the item's [`URef::name()`], if the [`ExportSet`] contains more
     /// than one item. If it contains only one item, then `base_path` is returned unchanged.
+    ///
+    /// If the base path has no extension, the [usual extension](ExportFormat::file_extension)
+    /// for `format` is used. Returns [`ExportError::InvalidDestination`] if the base path
+    /// does not name a file, or the member has no name.
     pub(crate) fn member_export_path(
         &self,
+        format: ExportFormat,
         base_path: &Path,
         member: &dyn universe::URefErased,
-    ) -> PathBuf {
+    ) -> Result<PathBuf, ExportError> {
+        let invalid = |reason: &str| ExportError::InvalidDestination {
+            path: base_path.to_owned(),
+            reason: reason.to_owned(),
+        };
+        let Some(stem) = base_path.file_stem() else {
+            return Err(invalid("path does not end with a file name"));
+        };
         let mut path: PathBuf = base_path.to_owned();
         if self.contents.count() > 1 {
-            let mut new_file_name: OsString =
-                base_path.file_stem().expect("file name missing").to_owned();
+            let mut new_file_name: OsString = stem.to_owned();
             new_file_name.push("-");
             match member.name() {
                 // TODO: validate member name as filename fragment
                 universe::Name::Specific(s) => new_file_name.push(&*s),
                 universe::Name::Anonym(n) => new_file_name.push(n.to_string()),
-                universe::Name::Pending => todo!(),
+                universe::Name::Pending => {
+                    return Err(invalid("member to be exported has no name yet"))
+                }
             };
             new_file_name.push(".");
-            new_file_name.push(base_path.extension().expect("extension missing"));
+            new_file_name.push(
+                base_path
+                    .extension()
+                    .unwrap_or_else(|| OsStr::new(format.file_extension())),
+            );
 
             path.set_file_name(new_file_name);
         }
-        path
+        Ok(path)
+    }
+}
+
+/// Returns the smallest [`GridAab`] containing every block in `space` that is not
+/// [`AIR`](block::AIR), or [`None`] if there are no such blocks.
+pub(crate) fn occupied_bounds(space: &Space) -> Option<GridAab> {
+    space.statistics().occupied_bounds
+}
+
+/// Returns the bounds of the part of `space` within `space_region` (or all of it if
+/// [`None`]), or an error if there is no such part.
+fn exported_bounds(
+    name: &Name,
+    space: &Space,
+    space_region: Option<GridAab>,
+) -> Result<GridAab, ExportError> {
+    match space_region {
+        None => Ok(space.bounds()),
+        Some(region) => {
+            region
+                .intersection(space.bounds())
+                .ok_or_else(|| ExportError::NotRepresentable {
+                    name: Some(name.clone()),
+                    reason: format!("export region {region:?} does not intersect the space"),
+                })
+        }
     }
 }
 
+/// Copies the portion of `space` within `region` into a new [`Space`] whose lower corner
+/// is at the origin. `region` must be within the bounds of `space`.
+fn extract_space_region(space: &Space, region: GridAab) -> Space {
+    let offset = region.lower_bounds().to_vec();
+    let mut extracted = Space::builder(region.translate(-offset))
+        .physics(space.physics().clone())
+        .build();
+    extracted
+        .fill(extracted.bounds(), |cube| Some(&space[cube + offset]))
+        .expect("extracted space bounds should be consistent");
+    extracted
+}
+
 /// Implementation of [`WhenceUniverse`] used for this library's formats.
 #[derive(Debug)]
 struct PortWhence {
@@ -273,9 +866,74 @@ pub enum ExportFormat {
     ///
     /// [STL]: <https://en.wikipedia.org/wiki/STL_(file_format)>
     Stl,
+
+    /// The light data of each [`Space`], as a raw 3D volume suitable for use as a
+    /// texture. Spaces which do not compute light ([`LightPhysics::None`]) are skipped,
+    /// and blocks cannot be exported.
+    ///
+    /// The file consists of, in order, with all numbers little-endian:
+    ///
+    /// * The 8 bytes `AICLIGHT`.
+    /// * The lower bounds of the space, as three 32-bit signed integers (X, Y, Z).
+    /// * The size of the space, as three 32-bit unsigned integers (X, Y, Z).
+    /// * For each cube, with X varying fastest and Z slowest, the light value
+    ///   as three 32-bit floats (red, green, blue; linear, not sRGB).
+    ///
+    /// Light is finished being computed before it is exported, unless
+    /// [`ExportSet::with_current_light()`] is used.
+    ///
+    /// [`LightPhysics::None`]: all_is_cubes::space::LightPhysics::None
+    LightVolume,
+
+    /// [PLY] point cloud, in ASCII encoding.
+    ///
+    /// Each [`Space`] is exported as one point per cube containing a visible block,
+    /// located at the center of the cube in the space's coordinate system and colored
+    /// with the block's overall color. Blocks cannot be exported.
+    ///
+    /// [PLY]: <https://en.wikipedia.org/wiki/PLY_(file_format)>
+    Ply,
 }
 
 impl ExportFormat {
+    /// Returns the format conventionally stored in files with the given extension
+    /// (without the leading dot), ignoring ASCII case, or [`None`] if there is none.
+    ///
+    /// This is the inverse of [`ExportFormat::file_extension()`].
+    /// Binary glTF (`glb`) is not recognized, since it cannot yet be exported.
+    ///
+    /// ```
+    /// use all_is_cubes_port::ExportFormat;
+    ///
+    /// assert_eq!(ExportFormat::from_extension("GLTF"), Some(ExportFormat::Gltf));
+    /// assert_eq!(ExportFormat::from_extension("glb"), None);
+    /// assert_eq!(ExportFormat::from_extension("txt"), None);
+    /// ```
+    pub fn from_extension(extension: &str) -> Option<ExportFormat> {
+        match extension.to_ascii_lowercase().as_str() {
+            "alliscubesjson" => Some(ExportFormat::AicJson),
+            "vox" => Some(ExportFormat::DotVox),
+            "gltf" => Some(ExportFormat::Gltf),
+            "stl" => Some(ExportFormat::Stl),
+            "aiclight" => Some(ExportFormat::LightVolume),
+            "ply" => Some(ExportFormat::Ply),
+            _ => None,
+        }
+    }
+
+    /// The file name extension, without the leading dot, to use for files of this format
+    /// when none is otherwise specified.
+    pub fn file_extension(self) -> &'static str {
+        match self {
+            ExportFormat::AicJson => "alliscubesjson",
+            ExportFormat::DotVox => "vox",
+            ExportFormat::Gltf => "gltf",
+            ExportFormat::Stl => "stl",
+            ExportFormat::LightVolume => "aiclight",
+            ExportFormat::Ply => "ply",
+        }
+    }
+
     /// Whether exporting to this format is capable of including [`Space`] light data.
     pub fn includes_light(self) -> bool {
         match self {
@@ -283,6 +941,8 @@ impl ExportFormat {
             ExportFormat::DotVox => false,
             ExportFormat::Gltf => false, // TODO: implement light
             ExportFormat::Stl => false,
+            ExportFormat::LightVolume => true,
+            ExportFormat::Ply => false,
         }
     }
 }
@@ -335,6 +995,26 @@ pub enum ImportErrorKind {
     #[non_exhaustive]
     #[error("the data is not in a recognized format")]
     UnknownFormat {},
+
+    /// The data exceeded one of the [`ImportLimits`] given in the [`ImportOptions`].
+    #[non_exhaustive]
+    #[error("the data exceeds the import limit of {limit} {resource}")]
+    ResourceLimit {
+        /// Which limit was exceeded.
+        resource: LimitedResource,
+        /// The value of the limit.
+        limit: u64,
+    },
+
+    /// The data was valid, but could not be applied to the existing universe;
+    /// for example, [`apply_patch()`] was given a patch for a different base universe.
+    #[non_exhaustive]
+    #[error("the data does not fit the existing universe")]
+    Conflict(
+        /// Details of the conflict.
+        #[source]
+        Box<dyn std::error::Error + Send + Sync>,
+    ),
 }
 
 /// Fatal errors that may be encountered during an export operation.
@@ -344,10 +1024,20 @@ pub enum ImportErrorKind {
 #[non_exhaustive]
 pub enum ExportError {
     /// IO error while writing the data to a file or stream.
-    ///
-    /// TODO: also represent file path if available
-    #[error("could not write export data")]
-    Write(#[from] std::io::Error),
+    #[error(
+        "could not write export data{}",
+        path.as_ref().map(|path| format!(" to '{}'", path.display())).unwrap_or_default()
+    )]
+    Write {
+        /// Path of the file being written, if the destination is a file.
+        /// Exports may write multiple files, so this is not necessarily the path given
+        /// to [`export_to_path()`].
+        path: Option<PathBuf>,
+
+        /// Error that occurred.
+        #[source]
+        error: std::io::Error,
+    },
 
     /// `RefError` while reading the data to be exported.
     #[error("could not read universe to be exported")]
@@ -364,6 +1054,16 @@ pub enum ExportError {
         error: block::EvalBlockError,
     },
 
+    /// The destination path given to [`export_to_path()`] cannot be used, such as because
+    /// it does not end with a file name.
+    #[error("cannot export to '{path}': {reason}", path = path.display())]
+    InvalidDestination {
+        /// The path that was given.
+        path: PathBuf,
+        /// The reason why it cannot be used.
+        reason: String,
+    },
+
     /// The requested [`ExportSet`] contained data that cannot be represented in the
     /// requested [`ExportFormat`].
     #[error("could not convert data to requested format: {reason}")]
@@ -374,3 +1074,23 @@ pub enum ExportError {
         reason: String,
     },
 }
+
+impl ExportError {
+    /// If this is an [`ExportError::Write`] without a path, add `path` to it.
+    pub(crate) fn with_path(self, path: &Path) -> Self {
+        match self {
+            ExportError::Write { path: None, error } => ExportError::Write {
+                path: Some(path.to_owned()),
+                error,
+            },
+            other => other,
+        }
+    }
+}
+
+/// Converts to [`ExportError::Write`] with no path.
+impl From<std::io::Error> for ExportError {
+    fn from(error: std::io::Error) -> Self {
+        ExportError::Write { path: None, error }
+    }
+}
@@ -0,0 +1,168 @@
+//! Export of [`Space`] light data as a raw 3D volume; see
+//! [`ExportFormat::LightVolume`](crate::ExportFormat::LightVolume).
+
+use std::fs;
+use std::io::{self, Write as _};
+use std::path::PathBuf;
+
+use all_is_cubes::space::{LightPhysics, Space};
+use all_is_cubes::universe::PartialUniverse;
+use all_is_cubes::util::YieldProgress;
+
+use crate::{ExportError, ExportFormat, ExportSet};
+
+/// Bytes at the beginning of every light volume file.
+pub(crate) const MAGIC: &[u8; 8] = b"AICLIGHT";
+
+/// Size of the header preceding the volume data.
+#[cfg(test)]
+pub(crate) const HEADER_LENGTH: usize = MAGIC.len() + 3 * 4 + 3 * 4;
+
+pub(crate) async fn export_light_volume(
+    progress: YieldProgress,
+    source: ExportSet,
+    destination: PathBuf,
+) -> Result<(), ExportError> {
+    preflight_light_volume(&source)?;
+    let ExportSet {
+        contents:
+            PartialUniverse {
+                blocks: _, // rejected by preflight_light_volume()
+                spaces,
+                characters: _,
+            },
+        crop_spaces: _,         // rejected by preflight_light_volume()
+        space_region: _,        // applied by export_to_path()
+        character_inventory: _, // applied by export_to_path()
+        pretty_json: _,         // not a JSON format
+        current_light: _,       // applied by export_to_path()
+    } = &source;
+
+    for (mut p, space_ref) in progress.split_evenly(spaces.len()).zip(spaces) {
+        p.set_label(format!("Exporting light of {}", space_ref.name()));
+        let space = space_ref.read()?;
+        // Spaces without light have nothing to write.
+        if space.physics().light != LightPhysics::None {
+            let path =
+                source.member_export_path(ExportFormat::LightVolume, &destination, space_ref)?;
+            let write_error = |error| ExportError::Write {
+                path: Some(path.clone()),
+                error,
+            };
+            let mut writer = io::BufWriter::new(fs::File::create(&path).map_err(write_error)?);
+            write_light_volume(&space, &mut writer).map_err(write_error)?;
+            writer.flush().map_err(write_error)?;
+        }
+        p.finish().await;
+    }
+
+    Ok(())
+}
+
+/// Check for members of `source` which cannot be exported as light volumes.
+pub(crate) fn preflight_light_volume(source: &ExportSet) -> Result<(), ExportError> {
+    source.check_cropping(ExportFormat::LightVolume)?;
+    if let Some(first) = source.contents.blocks.first() {
+        return Err(ExportError::NotRepresentable {
+            name: Some(first.name()),
+            reason: "BlockDefs have no light to export".into(),
+        });
+    }
+    Ok(())
+}
+
+/// Write the light of every cube in `space`, in the format described by
+/// [`ExportFormat::LightVolume`](crate::ExportFormat::LightVolume).
+pub(crate) fn write_light_volume(space: &Space, w: &mut dyn io::Write) -> io::Result<()> {
+    let bounds = space.bounds();
+    w.write_all(MAGIC)?;
+    let lower_bounds: [i32; 3] = bounds.lower_bounds().into();
+    let size: [i32; 3] = bounds.size().into();
+    for coordinate in lower_bounds {
+        w.write_all(&coordinate.to_le_bytes())?;
+    }
+    for size in size {
+        // Sizes are never negative.
+        w.write_all(&(size as u32).to_le_bytes())?;
+    }
+
+    // Not using interior_iter() because we want X to vary fastest, as is conventional
+    // for 3D textures.
+    let (x_range, y_range, z_range) = (bounds.x_range(), bounds.y_range(), bounds.z_range());
+    for z in z_range {
+        for y in y_range.clone() {
+            for x in x_range.clone() {
+                let light = space.get_lighting([x, y, z]).value();
+                for component in [light.red(), light.green(), light.blue()] {
+                    w.write_all(&component.into_inner().to_le_bytes())?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{export_to_path, ExportFormat};
+    use all_is_cubes::block::Block;
+    use all_is_cubes::math::{GridAab, Rgba};
+    use all_is_cubes::universe::{URef, Universe};
+    use all_is_cubes::util::yield_progress_for_testing;
+
+    #[tokio::test]
+    async fn export_lit_space() {
+        let mut universe = Universe::new();
+        let bounds = GridAab::from_lower_size([-1, 0, 2], [3, 4, 5]);
+        let mut space = Space::empty(bounds);
+        space.set([0, 0, 3], Block::from(Rgba::WHITE)).unwrap();
+        space.evaluate_light::<std::time::Instant>(0, |_| {});
+        let space: URef<Space> = universe.insert("lit".into(), space).unwrap();
+
+        let destination_dir = tempfile::tempdir().unwrap();
+        let destination: PathBuf = destination_dir.path().join("light.bin");
+        export_to_path(
+            yield_progress_for_testing(),
+            ExportFormat::LightVolume,
+            ExportSet::from_spaces(vec![space]),
+            destination.clone(),
+        )
+        .await
+        .unwrap();
+
+        let data = fs::read(destination).unwrap();
+        assert_eq!(&data[..8], MAGIC);
+        let header_numbers: Vec<i32> = data[8..HEADER_LENGTH]
+            .chunks_exact(4)
+            .map(|bytes| i32::from_le_bytes(bytes.try_into().unwrap()))
+            .collect();
+        assert_eq!(header_numbers, vec![-1, 0, 2, 3, 4, 5]);
+        assert_eq!(data.len(), HEADER_LENGTH + bounds.volume() * 3 * 4);
+    }
+
+    #[tokio::test]
+    async fn unlit_space_is_skipped() {
+        let mut universe = Universe::new();
+        let space: URef<Space> = universe
+            .insert(
+                "unlit".into(),
+                Space::builder(GridAab::ORIGIN_CUBE)
+                    .light_physics(LightPhysics::None)
+                    .build(),
+            )
+            .unwrap();
+
+        let destination_dir = tempfile::tempdir().unwrap();
+        export_to_path(
+            yield_progress_for_testing(),
+            ExportFormat::LightVolume,
+            ExportSet::from_spaces(vec![space]),
+            destination_dir.path().join("light.bin"),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(fs::read_dir(destination_dir.path()).unwrap().count(), 0);
+    }
+}
@@ -1,41 +1,97 @@
 //! Import and export of MagicaVoxel `.vox` files.
 
+use std::io::{self, Seek, SeekFrom, Write};
+
 use all_is_cubes::block::{self, Block};
 use all_is_cubes::cgmath::{EuclideanSpace as _, Point3, Vector3};
 use all_is_cubes::character::{Character, Spawn};
 use all_is_cubes::content::free_editing_starter_inventory;
 use all_is_cubes::linking::InGenError;
-use all_is_cubes::math::{Cube, GridAab, GridRotation, GridVector, Gridgid, Rgb, Rgba};
+use all_is_cubes::math::{
+    Cube, GridAab, GridOverflowError, GridRotation, GridVector, Gridgid, Rgb, Rgba,
+};
 use all_is_cubes::space::{LightPhysics, SetCubeError, Space};
 use all_is_cubes::universe::{self, Name, PartialUniverse, Universe};
 use all_is_cubes::util::{ConciseDebug, CustomFormat, YieldProgress};
 
-use crate::{ExportError, ExportSet};
+use crate::{
+    saturating_u64, ExportError, ExportFormat, ExportSet, ImportOptions, LimitExceeded,
+    LimitedResource,
+};
 
 pub(crate) async fn load_dot_vox(
     p: YieldProgress,
     bytes: &[u8],
+    options: &ImportOptions,
 ) -> Result<Universe, DotVoxConversionError> {
     dot_vox_data_to_universe(
         p,
         &dot_vox::load_bytes(bytes).map_err(DotVoxConversionError::Parse)?,
+        options,
     )
     .await
 }
 
+/// Write the spaces in `source` to `destination` in the MagicaVoxel `.vox` format.
+///
+/// Each space's voxels are written as they are read, rather than collected first, so that
+/// large spaces do not need to be held in memory twice. Since the `.vox` format stores the
+/// size of each chunk before its contents, `destination` must implement [`Seek`] so that
+/// the sizes can be filled in afterward.
+///
+/// TODO: also support exporting [`BlockDef`]s.
+///
+/// TODO: report export flaws (too many blocks)
+///
+/// [`BlockDef`]: all_is_cubes::block::BlockDef
 pub(crate) async fn export_dot_vox(
     p: YieldProgress,
     source: ExportSet,
-    mut destination: impl std::io::Write,
-) -> Result<(), crate::ExportError> {
-    let data: dot_vox::DotVoxData = export_to_dot_vox_data(p, source).await?;
-    data.write_vox(&mut destination)?;
+    destination: impl Write + Seek,
+) -> Result<(), ExportError> {
+    preflight_dot_vox(&source)?;
+    let ExportSet {
+        contents:
+            PartialUniverse {
+                blocks: _, // rejected by preflight_dot_vox()
+                spaces: to_export,
+                characters: _,
+            },
+        crop_spaces: _,         // rejected by preflight_dot_vox()
+        space_region: _,        // applied by export_to_path()
+        character_inventory: _, // applied by export_to_path()
+        pretty_json: _,         // not a JSON format
+        current_light: _,       // applied by export_to_path()
+    } = source;
+
+    let mut writer = io::BufWriter::new(destination);
+    writer.write_all(b"VOX ")?;
+    writer.write_all(&DOT_VOX_VERSION.to_le_bytes())?;
+    let main_chunk = ChunkHeader::begin(&mut writer, b"MAIN")?;
+
+    // The palette is shared by all models and written after them, so accumulate it.
+    let mut palette: Vec<dot_vox::Color> = Vec::new();
+    for (mut p, space_ref) in p.split_evenly(to_export.len()).zip(to_export) {
+        p.set_label(format!("Exporting space {}", space_ref.name()));
+        write_space_model(&mut writer, &space_ref, &mut palette)?;
+        p.finish().await
+    }
+
+    let palette_chunk = ChunkHeader::begin(&mut writer, b"RGBA")?;
+    for &color in &palette {
+        writer.write_all(&<[u8; 4]>::from(color))?;
+    }
+    palette_chunk.end_leaf(&mut writer)?;
+
+    main_chunk.end_parent(&mut writer)?;
+    writer.flush()?;
     Ok(())
 }
 
 pub(crate) async fn dot_vox_data_to_universe(
     p: YieldProgress,
     data: &dot_vox::DotVoxData,
+    options: &ImportOptions,
 ) -> Result<Universe, DotVoxConversionError> {
     let dot_vox::DotVoxData {
         version,
@@ -56,6 +112,24 @@ pub(crate) async fn dot_vox_data_to_universe(
     );
     p.progress(0.15).await;
 
+    // Check limits before creating any spaces, since the model sizes may be much larger
+    // than the file.
+    let member_count = models.len() + usize::from(!models.is_empty()); // models + character
+    options
+        .limits
+        .check(LimitedResource::Members, saturating_u64(member_count))?;
+    let total_volume = models.iter().fold(0u64, |total, model| {
+        let dot_vox::Size { x, y, z } = model.size;
+        total.saturating_add(
+            u64::from(x)
+                .saturating_mul(u64::from(y))
+                .saturating_mul(u64::from(z)),
+        )
+    });
+    options
+        .limits
+        .check(LimitedResource::SpaceVolume, total_volume)?;
+
     let palette = dot_vox_palette_to_blocks(palette);
     let p = p.finish_and_cut(0.3).await;
 
@@ -63,7 +137,7 @@ pub(crate) async fn dot_vox_data_to_universe(
 
     let models_progress = p.split_evenly(models.len());
     for ((i, model), model_progress) in models.iter().enumerate().zip(models_progress) {
-        let mut space = dot_vox_model_to_space(&palette, model)?;
+        let mut space = dot_vox_model_to_space(&palette, model, options)?;
         space.fast_evaluate_light();
 
         let name = Name::from(format!("model_{i}"));
@@ -83,52 +157,6 @@ pub(crate) async fn dot_vox_data_to_universe(
     Ok(universe)
 }
 
-/// Create [`DotVoxData`] from a collection of [`Space`]s.
-///
-/// TODO: also support exporting [`BlockDef`]s.
-///
-/// TODO: report export flaws (space too big, too many blocks)
-///
-pub(crate) async fn export_to_dot_vox_data(
-    p: YieldProgress,
-    source: crate::ExportSet,
-) -> Result<dot_vox::DotVoxData, ExportError> {
-    let crate::ExportSet {
-        contents:
-            PartialUniverse {
-                blocks: block_defs,
-                spaces: to_export,
-                characters: _,
-            },
-    } = source;
-
-    // If block def list is nonempty, fail.
-    if let Some(first) = block_defs.get(0) {
-        return Err(ExportError::NotRepresentable {
-            name: Some(first.name()),
-            reason: "Exporting BlockDefs to .vox is not yet supported".into(),
-        });
-    }
-
-    let mut palette: Vec<dot_vox::Color> = Vec::new();
-    let mut models: Vec<dot_vox::Model> = Vec::with_capacity(to_export.len());
-
-    for (mut p, space_ref) in p.split_evenly(to_export.len()).zip(to_export) {
-        p.set_label(format!("Exporting space {}", space_ref.name()));
-        models.push(space_to_dot_vox_model(&space_ref, &mut palette)?);
-        p.finish().await
-    }
-
-    Ok(dot_vox::DotVoxData {
-        version: 150, // TODO: magic number taken from examples; may not be right
-        models,
-        palette,
-        scenes: Vec::new(),
-        layers: Vec::new(),
-        materials: Vec::new(),
-    })
-}
-
 fn dot_vox_palette_to_blocks(palette: &[dot_vox::Color]) -> Vec<Block> {
     palette
         .iter()
@@ -151,23 +179,28 @@ fn block_to_dot_vox_palette_entry(evaluated: &block::EvaluatedBlock) -> Option<d
     }
 }
 
-/// TODO: Document and allow control over the metadata choices like spawn and physics,
+/// TODO: Document and allow control over the metadata choices like spawn,
 /// and the choice of coordinate transform.
 fn dot_vox_model_to_space(
     palette_blocks: &[Block],
     model: &dot_vox::Model,
+    options: &ImportOptions,
 ) -> Result<Space, DotVoxConversionError> {
-    let transform = mv_to_aic_coordinate_transform(model.size);
-    let bounds = GridAab::from_lower_size(
-        [0, 0, 0],
-        [
-            model.size.x as i32,
-            model.size.y as i32,
-            model.size.z as i32,
-        ],
-    )
-    .transform(transform)
-    .expect("TODO: return error");
+    // The size comes from the file, so it must be checked before being trusted.
+    let size = model.size;
+    let too_large = || DotVoxConversionError::ModelTooLarge {
+        size: [size.x, size.y, size.z],
+    };
+    let sizes = GridVector::new(
+        i32::try_from(size.x).map_err(|_| too_large())?,
+        i32::try_from(size.y).map_err(|_| too_large())?,
+        i32::try_from(size.z).map_err(|_| too_large())?,
+    );
+    let transform = mv_to_aic_coordinate_transform(size);
+    let bounds = GridAab::checked_from_lower_size([0, 0, 0], sizes)
+        .map_err(DotVoxConversionError::Bounds)?
+        .transform(transform)
+        .ok_or_else(too_large)?;
 
     let mut space = Space::builder(bounds)
         .spawn({
@@ -175,10 +208,10 @@ fn dot_vox_model_to_space(
             spawn.set_inventory(free_editing_starter_inventory(true));
             spawn
         })
-        .light_physics(LightPhysics::Rays {
+        .light_physics(options.light_physics.clone().unwrap_or(LightPhysics::Rays {
             maximum_distance: bounds.y_range().len() as u16,
-        })
-        .sky_color(Rgb::ONE)
+        }))
+        .sky_color(options.sky_color.unwrap_or(Rgb::ONE))
         .build();
 
     for v in model.voxels.iter() {
@@ -207,21 +240,53 @@ fn dot_vox_model_to_space(
 
     Ok(space)
 }
-fn space_to_dot_vox_model(
-    space_ref: &universe::URef<Space>,
-    palette: &mut Vec<dot_vox::Color>,
-) -> Result<dot_vox::Model, ExportError> {
-    let space = space_ref.read()?;
-    let bounds = space.bounds();
+/// Check for members of `source` which cannot be exported to .vox.
+pub(crate) fn preflight_dot_vox(source: &ExportSet) -> Result<(), ExportError> {
+    let PartialUniverse {
+        blocks,
+        spaces,
+        characters: _,
+    } = &source.contents;
+
+    source.check_cropping(ExportFormat::DotVox)?;
+
+    // If block def list is nonempty, fail.
+    if let Some(first) = blocks.first() {
+        return Err(ExportError::NotRepresentable {
+            name: Some(first.name()),
+            reason: "Exporting BlockDefs to .vox is not yet supported".into(),
+        });
+    }
+
+    for space_ref in spaces {
+        check_space_size(space_ref.name(), source.exported_space_bounds(space_ref)?)?;
+    }
+
+    Ok(())
+}
+
+fn check_space_size(name: Name, bounds: GridAab) -> Result<(), ExportError> {
     if bounds.size().x > 256 || bounds.size().y > 256 || bounds.size().z > 256 {
         return Err(ExportError::NotRepresentable {
-            name: Some(space_ref.name()),
+            name: Some(name),
             reason: format!(
                 "space of size {} is too large to export to .vox; must be 256 or less in each axis",
                 bounds.size().custom_format(ConciseDebug)
             ),
         });
     }
+    Ok(())
+}
+
+/// Write the `SIZE` and `XYZI` chunks for one space, adding its blocks to `palette`.
+fn write_space_model<W: Write + Seek>(
+    writer: &mut W,
+    space_ref: &universe::URef<Space>,
+    palette: &mut Vec<dot_vox::Color>,
+) -> Result<(), ExportError> {
+    let space = space_ref.read()?;
+    let bounds = space.bounds();
+    check_space_size(space_ref.name(), bounds)?;
 
     let transform = aic_to_mv_coordinate_transform(bounds);
     let block_index_to_palette_index: Vec<Option<u8>> = space
@@ -229,45 +294,117 @@ fn space_to_dot_vox_model(
         .iter()
         .map(|data| {
             if let Some(entry) = block_to_dot_vox_palette_entry(data.evaluated()) {
+                // Indices are stored 1-based in the file, so the last usable one is 254.
                 if let Ok(index) = u8::try_from(palette.len()) {
-                    palette.push(entry);
-                    return Some(index);
+                    if index < u8::MAX {
+                        palette.push(entry);
+                        return Some(index);
+                    }
                 }
             }
             None
         })
         .collect();
 
-    let mut voxels: Vec<dot_vox::Voxel> = Vec::new();
+    let size_chunk = ChunkHeader::begin(writer, b"SIZE")?;
+    let size = transform
+        .rotation
+        .transform_vector(bounds.size())
+        .map(i32::abs) // vector rotation might make it negative
+        .cast::<u32>()
+        .unwrap(); // conversion from positive i32 to u32 cannot fail
+    for component in [size.x, size.y, size.z] {
+        writer.write_all(&component.to_le_bytes())?;
+    }
+    size_chunk.end_leaf(writer)?;
+
+    let voxels_chunk = ChunkHeader::begin(writer, b"XYZI")?;
+    writer.write_all(&0u32.to_le_bytes())?; // voxel count, filled in below
+    let mut count: u32 = 0;
     for cube in bounds.interior_iter() {
         if let Some(i) =
             block_index_to_palette_index[usize::from(space.get_block_index(cube).unwrap())]
         {
             let transformed_cube = transform.transform_cube(cube);
-            voxels.push(dot_vox::Voxel {
-                // We previously checked that the size is not too big.
-                x: transformed_cube.x as u8,
-                y: transformed_cube.y as u8,
-                z: transformed_cube.z as u8,
-                i,
-            });
+            // We previously checked that the size is not too big, so the coordinates
+            // fit in u8 and the count fits in u32.
+            writer.write_all(&[
+                transformed_cube.x as u8,
+                transformed_cube.y as u8,
+                transformed_cube.z as u8,
+                i + 1,
+            ])?;
+            count += 1;
         } else {
             // Else the cube is empty space and should not be exported explicitly.
         }
     }
+    voxels_chunk.end_leaf(writer)?;
+    voxels_chunk.patch_content_start(writer, &count.to_le_bytes())?;
 
-    Ok(dot_vox::Model {
-        size: {
-            let Vector3 { x, y, z } = transform
-                .rotation
-                .transform_vector(space.bounds().size())
-                .map(i32::abs) // vector rotation might make it negative
-                .cast::<u32>()
-                .unwrap(); // conversion from positive i32 to u32 cannot fail
-            dot_vox::Size { x, y, z }
-        },
-        voxels,
-    })
+    Ok(())
+}
+
+/// Version number written in the `.vox` file header.
+const DOT_VOX_VERSION: u32 = 150; // TODO: magic number taken from examples; may not be right
+
+/// A `.vox` chunk whose header has been written with placeholder sizes, to be filled in
+/// once the chunk's contents have been written.
+struct ChunkHeader {
+    /// Position of the start of the chunk ID.
+    start: u64,
+}
+
+impl ChunkHeader {
+    /// Length of the chunk ID plus the two size fields.
+    const LEN: u64 = 12;
+
+    fn begin<W: Write + Seek>(writer: &mut W, id: &[u8; 4]) -> io::Result<Self> {
+        let start = writer.stream_position()?;
+        writer.write_all(id)?;
+        writer.write_all(&[0; 8])?;
+        Ok(Self { start })
+    }
+
+    /// Fill in the sizes of a chunk whose content has all been written since
+    /// [`Self::begin()`], and which has no children.
+    fn end_leaf<W: Write + Seek>(&self, writer: &mut W) -> io::Result<()> {
+        let len = self.len_so_far(writer)?;
+        self.patch(writer, 4, &[len.to_le_bytes(), 0u32.to_le_bytes()].concat())
+    }
+
+    /// Fill in the sizes of a chunk which has no content of its own, and whose children
+    /// have all been written since [`Self::begin()`].
+    fn end_parent<W: Write + Seek>(&self, writer: &mut W) -> io::Result<()> {
+        let len = self.len_so_far(writer)?;
+        self.patch(writer, 4, &[0u32.to_le_bytes(), len.to_le_bytes()].concat())
+    }
+
+    /// Overwrite the first bytes of the chunk's content.
+    fn patch_content_start<W: Write + Seek>(&self, writer: &mut W, bytes: &[u8]) -> io::Result<()> {
+        self.patch(writer, Self::LEN, bytes)
+    }
+
+    /// Returns the number of bytes written after the header.
+    fn len_so_far<W: Write + Seek>(&self, writer: &mut W) -> io::Result<u32> {
+        let len = writer.stream_position()? - self.start - Self::LEN;
+        u32::try_from(len).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("chunk of {len} bytes is too large for the .vox format"),
+            )
+        })
+    }
+
+    /// Overwrite `bytes` at `offset` from the start of the chunk, then return to the
+    /// current position.
+    fn patch<W: Write + Seek>(&self, writer: &mut W, offset: u64, bytes: &[u8]) -> io::Result<()> {
+        let end = writer.stream_position()?;
+        writer.seek(SeekFrom::Start(self.start + offset))?;
+        writer.write_all(bytes)?;
+        writer.seek(SeekFrom::Start(end))?;
+        Ok(())
+    }
 }
 
 /// Note: This is not a well-designed error enum (yet)
@@ -278,6 +415,12 @@ pub(crate) enum DotVoxConversionError {
     Parse(&'static str),
     #[error("palette of {len} colors too short to contain index {index}")]
     PaletteTooShort { len: usize, index: u8 },
+    #[error("model size {size:?} is too large")]
+    ModelTooLarge { size: [u32; 3] },
+    #[error("invalid model bounds")]
+    Bounds(#[source] GridOverflowError),
+    #[error(transparent)]
+    ResourceLimit(#[from] LimitExceeded),
     #[error("failed to place block")]
     SetCube(#[source] SetCubeError),
     #[error("unexpected error")]
@@ -325,11 +468,15 @@ fn aic_to_mv_coordinate_transform(aic_bounds: GridAab) -> Gridgid {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ImportLimits;
     use all_is_cubes::block::BlockDef;
+    use all_is_cubes::content::make_some_blocks;
+    use all_is_cubes::math::GridCoordinate;
     use all_is_cubes::raytracer::print_space;
     use all_is_cubes::universe::URef;
     use all_is_cubes::util::yield_progress_for_testing;
     use either::Either;
+    use std::collections::HashSet;
 
     #[test]
     #[ignore]
@@ -363,9 +510,16 @@ mod tests {
         );
     }
 
+    /// Export to an in-memory `.vox` file.
+    async fn export_to_bytes(source: ExportSet) -> Result<Vec<u8>, ExportError> {
+        let mut bytes = io::Cursor::new(Vec::new());
+        export_dot_vox(yield_progress_for_testing(), source, &mut bytes).await?;
+        Ok(bytes.into_inner())
+    }
+
     #[tokio::test]
     async fn invalid_file_error() {
-        let error = load_dot_vox(yield_progress_for_testing(), &[])
+        let error = load_dot_vox(yield_progress_for_testing(), &[], &ImportOptions::default())
             .await
             .unwrap_err();
         assert!(
@@ -377,19 +531,152 @@ mod tests {
         );
     }
 
+    #[test]
+    fn import_model_too_large() {
+        for size in [
+            dot_vox::Size {
+                x: u32::MAX,
+                y: 1,
+                z: 1,
+            },
+            dot_vox::Size {
+                x: 1,
+                y: 1,
+                z: GridCoordinate::MAX as u32 + 1,
+            },
+        ] {
+            let model = dot_vox::Model {
+                size,
+                voxels: vec![],
+            };
+            let error = dot_vox_model_to_space(&[], &model, &ImportOptions::default()).unwrap_err();
+            assert!(
+                matches!(error, DotVoxConversionError::ModelTooLarge { .. }),
+                "{error:?}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn import_options_set_physics() {
+        let mut export_universe = Universe::new();
+        export_universe.insert_anonymous(
+            Space::builder(GridAab::from_lower_size([0, 0, 0], [2, 3, 4])).build(),
+        );
+        let bytes = export_to_bytes(ExportSet::all_of_universe(&export_universe))
+            .await
+            .unwrap();
+
+        let import_physics = |options: ImportOptions| {
+            let bytes = &bytes;
+            async move {
+                let universe = load_dot_vox(yield_progress_for_testing(), bytes, &options)
+                    .await
+                    .unwrap();
+                let space: URef<Space> = universe.iter_by_type().next().unwrap().1;
+                let physics = space.read().unwrap().physics().clone();
+                physics
+            }
+        };
+
+        // Defaults
+        let physics = import_physics(ImportOptions::default()).await;
+        assert_eq!(physics.sky_color, Rgb::ONE);
+        assert_eq!(
+            physics.light,
+            LightPhysics::Rays {
+                maximum_distance: 3
+            }
+        );
+
+        // Overridden
+        let sky_color = Rgb::new(0.1, 0.2, 0.3);
+        let physics = import_physics(ImportOptions {
+            sky_color: Some(sky_color),
+            light_physics: Some(LightPhysics::None),
+            ..ImportOptions::default()
+        })
+        .await;
+        assert_eq!(physics.sky_color, sky_color);
+        assert_eq!(physics.light, LightPhysics::None);
+    }
+
+    #[tokio::test]
+    async fn import_resource_limits() {
+        let mut export_universe = Universe::new();
+        for _ in 0..2 {
+            export_universe.insert_anonymous(Space::empty_positive(2, 3, 4));
+        }
+        let bytes = export_to_bytes(ExportSet::all_of_universe(&export_universe))
+            .await
+            .unwrap();
+        let import_with_limits = |limits: ImportLimits| {
+            let bytes = &bytes;
+            async move {
+                let options = ImportOptions {
+                    limits,
+                    ..ImportOptions::default()
+                };
+                load_dot_vox(yield_progress_for_testing(), bytes, &options).await
+            }
+        };
+
+        // Two models and a character; 48 cubes.
+        let exact_limits = ImportLimits {
+            max_members: 3,
+            max_space_volume: 48,
+            ..ImportLimits::default()
+        };
+        import_with_limits(exact_limits.clone()).await.unwrap();
+
+        let error = import_with_limits(ImportLimits {
+            max_members: 2,
+            ..exact_limits.clone()
+        })
+        .await
+        .unwrap_err();
+        assert!(
+            matches!(
+                error,
+                DotVoxConversionError::ResourceLimit(LimitExceeded {
+                    resource: LimitedResource::Members,
+                    limit: 2
+                })
+            ),
+            "{error:?}"
+        );
+
+        let error = import_with_limits(ImportLimits {
+            max_space_volume: 47,
+            ..exact_limits.clone()
+        })
+        .await
+        .unwrap_err();
+        assert!(
+            matches!(
+                error,
+                DotVoxConversionError::ResourceLimit(LimitExceeded {
+                    resource: LimitedResource::SpaceVolume,
+                    limit: 47
+                })
+            ),
+            "{error:?}"
+        );
+    }
+
     async fn roundtrip(
         export_universe: &Universe,
     ) -> Result<Universe, Either<ExportError, DotVoxConversionError>> {
-        // TODO: also roundtrip through bytes, for maximum rigor
-        let data = export_to_dot_vox_data(
+        let bytes = export_to_bytes(ExportSet::all_of_universe(export_universe))
+            .await
+            .map_err(Either::Left)?;
+        load_dot_vox(
             yield_progress_for_testing(),
-            ExportSet::all_of_universe(export_universe),
+            &bytes,
+            &ImportOptions::default(),
         )
         .await
-        .map_err(Either::Left)?;
-        dot_vox_data_to_universe(yield_progress_for_testing(), &data)
-            .await
-            .map_err(Either::Right)
+        .map_err(Either::Right)
     }
 
     #[tokio::test]
@@ -446,6 +733,68 @@ mod tests {
         // TODO: make more assertions about the data?
     }
 
+    /// Export a space too large to be comfortably buffered, with multiple models and
+    /// blocks, and check that every voxel comes back.
+    #[tokio::test]
+    async fn export_large_space_streaming() {
+        let [block1, block2] = make_some_blocks();
+        let bounds = GridAab::from_lower_size([0, 0, 0], [256, 128, 96]);
+        let mut space = Space::builder(bounds).build();
+        space
+            .fill(bounds, |cube| {
+                match (cube.x + cube.y * 3 + cube.z * 7).rem_euclid(5) {
+                    0 => Some(&block1),
+                    1 | 2 => Some(&block2),
+                    _ => Some(&block::AIR),
+                }
+            })
+            .unwrap();
+        let mut universe = Universe::new();
+        universe.insert_anonymous(space);
+        universe.insert_anonymous(
+            Space::builder(GridAab::from_lower_size([0, 0, 0], [2, 3, 4])).build(),
+        );
+
+        let bytes = export_to_bytes(ExportSet::all_of_universe(&universe))
+            .await
+            .unwrap();
+        let data = dot_vox::load_bytes(&bytes).unwrap();
+
+        assert_eq!(data.models.len(), 2);
+        assert_eq!(data.palette.len(), 2);
+        let model = &data.models[0];
+        assert_eq!(
+            model.size,
+            dot_vox::Size {
+                x: 256,
+                y: 96,
+                z: 128
+            }
+        );
+        let transform = mv_to_aic_coordinate_transform(model.size);
+        let imported: HashSet<(Cube, u8)> = model
+            .voxels
+            .iter()
+            .map(|v| {
+                let cube = Cube::new(v.x.into(), v.y.into(), v.z.into());
+                (transform.transform_cube(cube), v.i)
+            })
+            .collect();
+        let expected: HashSet<(Cube, u8)> = bounds
+            .interior_iter()
+            .filter_map(
+                |cube| match (cube.x + cube.y * 3 + cube.z * 7).rem_euclid(5) {
+                    0 => Some((cube, 0)),
+                    1 | 2 => Some((cube, 1)),
+                    _ => None,
+                },
+            )
+            .collect();
+        assert_eq!(model.voxels.len(), expected.len());
+        assert!(imported == expected, "voxel sets differ");
+        assert_eq!(data.models[1].voxels, vec![]);
+    }
+
     /// [`dot_vox`] only supports coordinates from 0-255
     #[tokio::test]
     async fn export_too_large_space() {
@@ -454,12 +803,9 @@ mod tests {
             Space::builder(GridAab::from_lower_size([0, 0, 0], [257, 1, 1])).build(),
         );
 
-        let error = export_to_dot_vox_data(
-            yield_progress_for_testing(),
-            ExportSet::from_spaces(vec![space]),
-        )
-        .await
-        .unwrap_err();
+        let error = export_to_bytes(ExportSet::from_spaces(vec![space]))
+            .await
+            .unwrap_err();
         assert!(matches!(error, ExportError::NotRepresentable { .. }));
     }
 
@@ -470,12 +816,9 @@ mod tests {
             .insert("x".into(), BlockDef::new(block::AIR))
             .unwrap();
 
-        let error = export_to_dot_vox_data(
-            yield_progress_for_testing(),
-            ExportSet::all_of_universe(&universe),
-        )
-        .await
-        .unwrap_err();
+        let error = export_to_bytes(ExportSet::all_of_universe(&universe))
+            .await
+            .unwrap_err();
         assert!(matches!(
             error,
             ExportError::NotRepresentable {
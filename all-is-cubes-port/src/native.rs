@@ -1,22 +1,261 @@
+use std::fs;
+use std::io::{self, Write as _};
 use std::path::PathBuf;
-use std::{fs, io};
 
-use all_is_cubes::universe::Universe;
+use all_is_cubes::math::GridAab;
+use all_is_cubes::universe::{Name, Universe};
 use all_is_cubes::util::YieldProgress;
 
 use crate::file::Fileish;
-use crate::{ExportError, ExportSet, ImportError, ImportErrorKind};
+use crate::{
+    saturating_u64, ExportError, ExportFormat, ExportSet, ImportError, ImportErrorKind,
+    ImportLimits, LimitedResource,
+};
+
+mod patch;
+pub use patch::{apply_patch, export_patch};
 
 #[cfg(test)]
 mod tests;
 
+/// Value of the `"format"` field which identifies the native format.
+const FORMAT_MAGIC: &str = "all-is-cubes-universe";
+
+/// Version of the native format envelope written by this version of the library.
+///
+/// This should be incremented whenever a change is made that older versions
+/// cannot read.
+const FORMAT_VERSION: u64 = 1;
+
+/// Top-level structure of a native-format file, as written.
+#[derive(serde::Serialize)]
+struct EnvelopeSer<'a, T> {
+    format: &'a str,
+    version: u64,
+    universe: T,
+}
+
+/// Top-level structure of a native-format file, as read, if it has an envelope
+/// (see [`Layout`]).
+#[derive(serde::Deserialize)]
+struct EnvelopeDe<T> {
+    universe: T,
+}
+
+/// Just the identifying fields of an envelope, so that they can be checked before
+/// attempting to deserialize the contents.
+#[derive(serde::Deserialize)]
+struct EnvelopeHeader {
+    format: Option<String>,
+    version: Option<u64>,
+}
+
+/// Error returned when the native-format envelope does not identify data we can read.
+#[derive(Clone, Debug, Eq, PartialEq, thiserror::Error)]
+pub(crate) enum HeaderError {
+    #[error("not an All is Cubes universe file (format is {found:?}, expected {FORMAT_MAGIC:?})")]
+    WrongFormat { found: String },
+
+    #[error(
+        "unsupported All is Cubes universe format version {found} \
+        (this version can read up to version {FORMAT_VERSION})"
+    )]
+    UnsupportedVersion { found: u64 },
+}
+
+/// Where the universe is found in a native-format file.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Layout {
+    /// The universe is the `"universe"` field of an envelope.
+    Envelope,
+    /// The file is the universe itself, as written before the envelope was introduced.
+    Legacy,
+}
+
 pub(crate) fn import_native_json(
     progress: YieldProgress,
     bytes: &[u8],
+    limits: &ImportLimits,
     file: &dyn Fileish,
 ) -> Result<Universe, ImportError> {
+    let scanned = scan(bytes, file)?;
+    scanned.check_limits(limits, file)?;
+    deserialize_universe(progress, bytes, scanned.layout, file)
+}
+
+/// As [`import_native_json()`], but members which fail to deserialize are reported
+/// instead of failing the entire import.
+pub(crate) fn import_native_json_recovering(
+    progress: YieldProgress,
+    bytes: &[u8],
+    limits: &ImportLimits,
+    file: &dyn Fileish,
+) -> Result<(Universe, Vec<(Name, ImportErrorKind)>), ImportError> {
+    let scanned = scan(bytes, file)?;
+    scanned.check_limits(limits, file)?;
+    let universe_value: serde_json::Value =
+        deserialize_universe(progress, bytes, scanned.layout, file)?;
+    let (universe, member_errors) = Universe::deserialize_recovering(universe_value)
+        .map_err(|error| json_import_error(error, file))?;
+    Ok((
+        universe,
+        member_errors
+            .into_iter()
+            .map(|(name, error)| (name, ImportErrorKind::Parse(Box::new(error))))
+            .collect(),
+    ))
+}
+
+fn deserialize_universe<T: serde::de::DeserializeOwned>(
+    progress: YieldProgress,
+    bytes: &[u8],
+    layout: Layout,
+    file: &dyn Fileish,
+) -> Result<T, ImportError> {
     let reader = ReadProgressAdapter::new(progress, bytes);
-    serde_json::from_reader(reader).map_err(|error| ImportError {
+    match layout {
+        Layout::Envelope => serde_json::from_reader(reader).map(|EnvelopeDe { universe }| universe),
+        Layout::Legacy => serde_json::from_reader(reader),
+    }
+    .map_err(|error| json_import_error(error, file))
+}
+
+/// Everything which is read from a native-format file before deserializing the
+/// universe: the envelope header, and the totals needed to check [`ImportLimits`].
+///
+/// Every field is optional so that this accepts anything the full deserialization
+/// would, with or without an envelope.
+#[derive(serde::Deserialize)]
+struct EnvelopeScan {
+    format: Option<String>,
+    version: Option<u64>,
+    #[serde(default)]
+    universe: UniverseLimitsScan,
+    /// Members of a file with [`Layout::Legacy`].
+    #[serde(default)]
+    members: MembersLimitsScan,
+}
+
+#[derive(Default, serde::Deserialize)]
+struct UniverseLimitsScan {
+    #[serde(default)]
+    members: MembersLimitsScan,
+}
+
+/// Totals of the limited resources used by the members of a universe,
+/// computed without keeping the members.
+#[derive(Default)]
+struct MembersLimitsScan {
+    count: u64,
+    space_volume: u64,
+}
+#[derive(serde::Deserialize)]
+struct MemberLimitsScan {
+    member_type: Option<String>,
+    value: Option<MemberValueLimitsScan>,
+}
+
+#[derive(serde::Deserialize)]
+struct MemberValueLimitsScan {
+    /// Kept as a [`serde_json::Value`] so that invalid bounds are left to be reported
+    /// (or recovered from) by the full deserialization.
+    bounds: Option<serde_json::Value>,
+}
+
+impl<'de> serde::Deserialize<'de> for MembersLimitsScan {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct MembersVisitor;
+        impl<'de> serde::de::Visitor<'de> for MembersVisitor {
+            type Value = MembersLimitsScan;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                formatter.write_str("a sequence of universe members")
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> Result<Self::Value, A::Error> {
+                let mut totals = MembersLimitsScan::default();
+                while let Some(member) = seq.next_element::<MemberLimitsScan>()? {
+                    totals.count += 1;
+                    if member.member_type.as_deref() == Some("Space") {
+                        let volume = member
+                            .value
+                            .and_then(|value| value.bounds)
+                            .and_then(|bounds| serde_json::from_value::<GridAab>(bounds).ok())
+                            .map_or(0, |bounds| saturating_u64(bounds.volume()));
+                        totals.space_volume = totals.space_volume.saturating_add(volume);
+                    }
+                }
+                Ok(totals)
+            }
+        }
+        deserializer.deserialize_seq(MembersVisitor)
+    }
+}
+
+/// Result of [`scan()`].
+struct Scanned {
+    layout: Layout,
+    members: MembersLimitsScan,
+}
+
+/// Read and check the header, and total up the resources used by the members, before
+/// deserializing the universe. This way, version mismatches are reported as such rather
+/// than as whatever deserialization error results, and [`ImportLimits`] are checked
+/// before anything large is allocated.
+///
+/// This parses the entire file but keeps only the header and running totals, so it
+/// uses a bounded amount of memory however large the members are.
+fn scan(bytes: &[u8], file: &dyn Fileish) -> Result<Scanned, ImportError> {
+    let EnvelopeScan {
+        format,
+        version,
+        universe,
+        members: legacy_members,
+    } = serde_json::from_slice(bytes).map_err(|error| json_import_error(error, file))?;
+    let layout = check_header(format, version).map_err(|error| ImportError {
+        source_path: file.display_full_path(),
+        detail: ImportErrorKind::Parse(Box::new(error)),
+    })?;
+    Ok(Scanned {
+        layout,
+        members: match layout {
+            Layout::Envelope => universe.members,
+            Layout::Legacy => legacy_members,
+        },
+    })
+}
+
+impl Scanned {
+    fn check_limits(&self, limits: &ImportLimits, file: &dyn Fileish) -> Result<(), ImportError> {
+        limits
+            .check(LimitedResource::Members, self.members.count)
+            .and_then(|()| limits.check(LimitedResource::SpaceVolume, self.members.space_volume))
+            .map_err(|error| ImportError {
+                source_path: file.display_full_path(),
+                detail: error.into(),
+            })
+    }
+}
+
+fn check_header(format: Option<String>, version: Option<u64>) -> Result<Layout, HeaderError> {
+    let layout = match format {
+        // Files written before the envelope was introduced have no header at all.
+        None => Layout::Legacy,
+        Some(format) if format == FORMAT_MAGIC => Layout::Envelope,
+        Some(found) => return Err(HeaderError::WrongFormat { found }),
+    };
+    // A missing version is version 1, the only one which existed before the envelope.
+    match version {
+        Some(found) if found > FORMAT_VERSION => Err(HeaderError::UnsupportedVersion { found }),
+        _ => Ok(layout),
+    }
+}
+
+fn json_import_error(error: serde_json::Error, file: &dyn Fileish) -> ImportError {
+    ImportError {
         source_path: file.display_full_path(),
         detail: if error.is_eof() || error.is_io() {
             ImportErrorKind::Read {
@@ -26,24 +265,58 @@ pub(crate) fn import_native_json(
         } else {
             ImportErrorKind::Parse(Box::new(error))
         },
-    })
+    }
+}
+
+/// Check for options of `source` which cannot be honored by the native format.
+pub(crate) fn preflight_native(source: &ExportSet) -> Result<(), ExportError> {
+    source.check_cropping(ExportFormat::AicJson)
 }
 
 pub(crate) async fn export_native_json(
-    progress: YieldProgress,
+    mut progress: YieldProgress,
     source: ExportSet,
     destination: PathBuf,
 ) -> Result<(), ExportError> {
+    preflight_native(&source)?;
     // TODO: Spin off a blocking thread to perform this export
-    let ExportSet { contents } = source;
-    serde_json::to_writer(
-        io::BufWriter::new(fs::File::create(destination)?),
-        &contents,
-    )
+    let ExportSet {
+        contents,
+        crop_spaces: _,         // rejected by preflight_native()
+        space_region: _,        // applied by export_to_path()
+        character_inventory: _, // applied by export_to_path()
+        pretty_json,
+        current_light: _, // applied by export_to_path()
+    } = source;
+    let write_error = |error| ExportError::Write {
+        path: Some(destination.clone()),
+        error,
+    };
+    let mut writer = io::BufWriter::new(fs::File::create(&destination).map_err(write_error)?);
+    let envelope = EnvelopeSer {
+        format: FORMAT_MAGIC,
+        version: FORMAT_VERSION,
+        universe: &contents,
+    };
+
+    // Serialization writes to the file as it goes and is a single operation, so we
+    // cannot report individual members, or the write separately from serialization.
+    progress.set_label(format!(
+        "Writing {} members",
+        contents.blocks.len() + contents.spaces.len() + contents.characters.len()
+    ));
+    progress.progress(0.0).await;
+    if pretty_json {
+        serde_json::to_writer_pretty(&mut writer, &envelope)
+    } else {
+        serde_json::to_writer(&mut writer, &envelope)
+    }
     .map_err(|error| {
         // TODO: report non-IO errors distinctly
-        ExportError::Write(io::Error::new(io::ErrorKind::Other, error))
+        write_error(io::Error::new(io::ErrorKind::Other, error))
     })?;
+
+    writer.flush().map_err(write_error)?;
     progress.finish().await;
     Ok(())
 }
@@ -0,0 +1,241 @@
+//! Patches: native-format files containing only the differences between two universes.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Write as _};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use sha2::{Digest as _, Sha256};
+
+use all_is_cubes::block::BlockDef;
+use all_is_cubes::character::Character;
+use all_is_cubes::space::Space;
+use all_is_cubes::universe::{DiffError, Name, URef, Universe, UniverseDiff};
+use all_is_cubes::util::YieldProgress;
+
+use crate::file::Fileish;
+use crate::native::{json_import_error, EnvelopeHeader, ReadProgressAdapter};
+use crate::{ExportError, ImportError, ImportErrorKind};
+
+/// Value of the `"format"` field which identifies a patch.
+const PATCH_FORMAT_MAGIC: &str = "all-is-cubes-patch";
+
+/// Version of the patch envelope written by this version of the library.
+///
+/// This should be incremented whenever a change is made that older versions
+/// cannot read.
+const PATCH_FORMAT_VERSION: u64 = 1;
+
+/// Top-level structure of a patch file, as written.
+#[derive(serde::Serialize)]
+struct PatchSer<'a> {
+    format: &'a str,
+    version: u64,
+    /// [`base_hash()`] of the universe the patch applies to.
+    base: &'a str,
+    diff: &'a UniverseDiff,
+}
+
+/// Top-level structure of a patch file, as read.
+///
+/// All fields are optional so that a file which is not a patch, or is a patch of an
+/// unsupported version, can be reported as such rather than as a missing field.
+#[derive(serde::Deserialize)]
+struct PatchDe {
+    format: Option<String>,
+    version: Option<u64>,
+    base: Option<String>,
+    diff: Option<UniverseDiff>,
+}
+
+/// Error returned when a patch cannot be read or applied.
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum PatchError {
+    #[error(
+        "not an All is Cubes patch file (format is {found:?}, expected {PATCH_FORMAT_MAGIC:?})"
+    )]
+    WrongFormat { found: Option<String> },
+
+    #[error(
+        "unsupported All is Cubes patch format version {found:?} \
+        (this version can read version {PATCH_FORMAT_VERSION})"
+    )]
+    UnsupportedVersion { found: Option<u64> },
+
+    #[error("the patch is for a different base universe (expected {expected}, found {found})")]
+    BaseMismatch { expected: String, found: String },
+
+    #[error(transparent)]
+    Diff(#[from] DiffError),
+}
+
+/// Export a patch which, when given to [`apply_patch()`] along with `base`, makes `base`'s
+/// members identical to those of `current`.
+///
+/// The patch is written in the native JSON format (conventionally with the extension
+/// `.alliscubesjson`) and contains the added and changed members of `current`, and the
+/// names of the removed members. It also contains a hash of `base`'s members, so that
+/// it will not be applied to any other universe.
+pub async fn export_patch(
+    mut progress: YieldProgress,
+    base: &Universe,
+    current: &Universe,
+    destination: PathBuf,
+) -> Result<(), ExportError> {
+    let write_error = |error| ExportError::Write {
+        path: Some(destination.clone()),
+        error,
+    };
+    // TODO: report non-IO errors distinctly
+    let diff_error = |error: DiffError| write_error(io::Error::new(io::ErrorKind::Other, error));
+
+    progress.set_label("Comparing universes");
+    progress.progress(0.0).await;
+    let base_hash = base_hash(base).map_err(diff_error)?;
+    let diff = base.diff(current).map_err(diff_error)?;
+
+    // Serialization writes to the file as it goes, so it is reported as one phase.
+    progress.set_label(format!(
+        "Writing {} members",
+        diff.added().count() + diff.changed().count()
+    ));
+    progress.progress(0.5).await;
+    let mut writer = io::BufWriter::new(fs::File::create(&destination).map_err(write_error)?);
+    serde_json::to_writer(
+        &mut writer,
+        &PatchSer {
+            format: PATCH_FORMAT_MAGIC,
+            version: PATCH_FORMAT_VERSION,
+            base: &base_hash,
+            diff: &diff,
+        },
+    )
+    .map_err(|error| write_error(io::Error::new(io::ErrorKind::Other, error)))?;
+
+    writer.flush().map_err(write_error)?;
+    progress.finish().await;
+    Ok(())
+}
+
+/// Apply a patch produced by [`export_patch()`] to `base`, which must have the same
+/// members as the base universe given to [`export_patch()`].
+///
+/// If the patch was made from a different base universe, or cannot be read, this returns
+/// an error and `base` is not modified.
+pub async fn apply_patch(
+    progress: YieldProgress,
+    base: &mut Universe,
+    file: Arc<dyn Fileish>,
+) -> Result<(), ImportError> {
+    let bytes = file.read().map_err(|error| ImportError {
+        source_path: file.display_full_path(),
+        detail: ImportErrorKind::Read { path: None, error },
+    })?;
+    let error = |detail| ImportError {
+        source_path: file.display_full_path(),
+        detail,
+    };
+    let parse_error = |e: PatchError| error(ImportErrorKind::Parse(Box::new(e)));
+    let conflict_error = |e: PatchError| error(ImportErrorKind::Conflict(Box::new(e)));
+
+    let check_header = |format: Option<String>, version: Option<u64>| {
+        if format.as_deref() != Some(PATCH_FORMAT_MAGIC) {
+            Err(parse_error(PatchError::WrongFormat { found: format }))
+        } else if version != Some(PATCH_FORMAT_VERSION) {
+            Err(parse_error(PatchError::UnsupportedVersion {
+                found: version,
+            }))
+        } else {
+            Ok(())
+        }
+    };
+
+    let reader = ReadProgressAdapter::new(progress, &bytes);
+    let (found, diff) = match serde_json::from_reader(reader) {
+        Ok(PatchDe {
+            format,
+            version,
+            base,
+            diff,
+        }) => {
+            check_header(format, version)?;
+            let missing = |field| json_import_error(serde::de::Error::missing_field(field), &*file);
+            (
+                base.ok_or_else(|| missing("base"))?,
+                diff.ok_or_else(|| missing("diff"))?,
+            )
+        }
+        Err(e) => {
+            // The file could not be read as a patch. If that is because it is some other
+            // format or version, which might have a different structure, say so instead.
+            if let Ok(EnvelopeHeader { format, version }) = serde_json::from_slice(&bytes) {
+                check_header(format, version)?;
+            }
+            return Err(json_import_error(e, &*file));
+        }
+    };
+
+    let expected = base_hash(base).map_err(|e| conflict_error(e.into()))?;
+    if found != expected {
+        return Err(conflict_error(PatchError::BaseMismatch { expected, found }));
+    }
+    base.apply_diff(&diff).map_err(|e| conflict_error(e.into()))
+}
+
+/// Computes a hash of the persistent data of `universe`'s members, identifying it as the
+/// base of a patch.
+///
+/// Each member is hashed in its serialized form, one at a time in order of name, except
+/// that [`Space`] light data is left out. Light is recomputed as the universe is stepped,
+/// and should not stop a patch from applying to a universe that has merely been run.
+fn base_hash(universe: &Universe) -> Result<String, DiffError> {
+    enum Member {
+        Block(URef<BlockDef>),
+        Character(URef<Character>),
+        Space(URef<Space>),
+    }
+
+    let members: BTreeMap<Name, Member> = universe
+        .iter_by_type::<BlockDef>()
+        .map(|(name, r)| (name, Member::Block(r)))
+        .chain(
+            universe
+                .iter_by_type::<Character>()
+                .map(|(name, r)| (name, Member::Character(r))),
+        )
+        .chain(
+            universe
+                .iter_by_type::<Space>()
+                .map(|(name, r)| (name, Member::Space(r))),
+        )
+        .collect();
+
+    let mut hasher = Sha256::new();
+    for (name, member) in members {
+        let serialization_error = |error: serde_json::Error| DiffError::Serialization {
+            name: name.clone(),
+            error,
+        };
+        let read_error = |error| {
+            serialization_error(serde::ser::Error::custom(format!(
+                "failed to read universe member {name}: {error}"
+            )))
+        };
+        let value = match member {
+            Member::Block(r) => serde_json::to_value(&**r.read().map_err(read_error)?),
+            Member::Character(r) => serde_json::to_value(&*r.read().map_err(read_error)?),
+            Member::Space(r) => {
+                serde_json::to_value(&*r.read().map_err(read_error)?).map(|mut value| {
+                    if let Some(fields) = value.as_object_mut() {
+                        fields.remove("light");
+                    }
+                    value
+                })
+            }
+        }
+        .map_err(serialization_error)?;
+        serde_json::to_writer(&mut hasher, &(&name, value)).map_err(serialization_error)?;
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
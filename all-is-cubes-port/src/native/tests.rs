@@ -3,10 +3,19 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 use all_is_cubes::block;
-use all_is_cubes::universe::{Name, URef};
+use all_is_cubes::content::make_some_blocks;
+use all_is_cubes::space::Space;
+use all_is_cubes::time::NoTime;
+use all_is_cubes::universe::{Name, URef, Universe};
 use all_is_cubes::util::yield_progress_for_testing;
 
-use crate::{export_to_path, load_universe_from_file, ExportSet};
+use crate::file::NonDiskFile;
+use crate::native::patch::PatchError;
+use crate::native::HeaderError;
+use crate::{
+    apply_patch, export_patch, export_to_path, load_universe_from_file,
+    load_universe_from_file_recovering, ExportSet, ImportErrorKind,
+};
 
 #[tokio::test]
 async fn import_export_native_format() {
@@ -48,3 +57,336 @@ async fn import_export_native_format() {
         serde_json::from_reader(fs::File::open(&destination).unwrap()).unwrap();
     pretty_assertions::assert_eq!(expected_value, actual_value);
 }
+
+#[tokio::test]
+async fn pretty_and_compact_export_round_trip() {
+    let universe = load_universe_from_file(
+        yield_progress_for_testing(),
+        Arc::new(PathBuf::from(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/src/native/tests/native-test.alliscubesjson"
+        ))),
+    )
+    .await
+    .unwrap();
+
+    let destination_dir = tempfile::tempdir().unwrap();
+    let mut exported = Vec::new();
+    for (file_name, export_set) in [
+        (
+            "compact.alliscubesjson",
+            ExportSet::all_of_universe(&universe),
+        ),
+        (
+            "pretty.alliscubesjson",
+            ExportSet::all_of_universe(&universe).with_pretty_json(),
+        ),
+    ] {
+        let destination = destination_dir.path().join(file_name);
+        export_to_path(
+            yield_progress_for_testing(),
+            crate::ExportFormat::AicJson,
+            export_set,
+            destination.clone(),
+        )
+        .await
+        .unwrap();
+
+        let reimported =
+            load_universe_from_file(yield_progress_for_testing(), Arc::new(destination.clone()))
+                .await
+                .unwrap();
+        let uref: URef<block::BlockDef> = reimported.get(&Name::from("foo")).unwrap();
+        assert_eq!(**uref.read().unwrap(), block::AIR);
+
+        exported.push(fs::read_to_string(destination).unwrap());
+    }
+    let [compact, pretty] = &exported[..] else {
+        unreachable!()
+    };
+
+    assert!(!compact.contains('\n'), "{compact}");
+    assert!(pretty.contains("\n  "), "{pretty}");
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(compact).unwrap(),
+        serde_json::from_str::<serde_json::Value>(pretty).unwrap()
+    );
+}
+
+#[tokio::test]
+async fn import_unsupported_version() {
+    let result = load_universe_from_file(
+        yield_progress_for_testing(),
+        Arc::new(NonDiskFile::from_name_and_data_source(
+            "future".into(),
+            || {
+                Ok(br#"{
+                    "format": "all-is-cubes-universe",
+                    "version": 2,
+                    "universe": {"type": "UniverseV2", "something": "new"}
+                }"#
+                .to_vec())
+            },
+        )),
+    )
+    .await;
+
+    let error = result.unwrap_err();
+    match &error.detail {
+        ImportErrorKind::Parse(detail) => assert_eq!(
+            detail.downcast_ref::<HeaderError>(),
+            Some(&HeaderError::UnsupportedVersion { found: 2 })
+        ),
+        other => panic!("unexpected error {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn import_wrong_format() {
+    let result = load_universe_from_file(
+        yield_progress_for_testing(),
+        Arc::new(NonDiskFile::from_name_and_data_source(
+            "unrelated".into(),
+            || Ok(br#"{"format": "something-else", "version": 1, "universe": {}}"#.to_vec()),
+        )),
+    )
+    .await;
+
+    let error = result.unwrap_err();
+    match &error.detail {
+        ImportErrorKind::Parse(detail) => assert_eq!(
+            detail.downcast_ref::<HeaderError>(),
+            Some(&HeaderError::WrongFormat {
+                found: "something-else".into()
+            })
+        ),
+        other => panic!("unexpected error {other:?}"),
+    }
+}
+
+/// Files written before the envelope was introduced are still readable.
+#[tokio::test]
+async fn import_legacy_format() {
+    let universe = load_universe_from_file(
+        yield_progress_for_testing(),
+        Arc::new(PathBuf::from(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/src/native/tests/native-test-legacy.alliscubesjson"
+        ))),
+    )
+    .await
+    .unwrap();
+
+    let uref: URef<block::BlockDef> = universe.get(&Name::from("foo")).unwrap();
+    assert_eq!(**uref.read().unwrap(), block::AIR);
+}
+
+#[tokio::test]
+async fn import_recovering_from_corrupt_member() {
+    let file = Arc::new(NonDiskFile::from_name_and_data_source(
+        "damaged".into(),
+        || {
+            Ok(br#"{
+                "format": "all-is-cubes-universe",
+                "version": 1,
+                "universe": {
+                    "type": "UniverseV1",
+                    "members": [
+                        {
+                            "name": {"Specific": "good"},
+                            "member_type": "Block",
+                            "value": {"type": "BlockV1", "primitive": {"type": "AirV1"}}
+                        },
+                        {
+                            "name": {"Specific": "bad"},
+                            "member_type": "Block",
+                            "value": {"type": "BlockV1", "primitive": {"type": "NonsenseV1"}}
+                        },
+                        {
+                            "name": {"Specific": "referrer"},
+                            "member_type": "Block",
+                            "value": {"type": "BlockV1", "primitive": {
+                                "type": "IndirectV1",
+                                "definition": {"type": "URefV1", "Specific": "bad"}
+                            }}
+                        }
+                    ]
+                }
+            }"#
+            .to_vec())
+        },
+    ));
+
+    // Strict mode fails entirely.
+    load_universe_from_file(yield_progress_for_testing(), file.clone())
+        .await
+        .unwrap_err();
+
+    let (universe, errors) = load_universe_from_file_recovering(yield_progress_for_testing(), file)
+        .await
+        .unwrap();
+
+    // The corrupt member is reported.
+    assert_eq!(
+        errors.iter().map(|(name, _)| name).collect::<Vec<_>>(),
+        vec![&Name::from("bad")]
+    );
+    assert!(matches!(errors[0].1, ImportErrorKind::Parse(_)));
+
+    // The other members loaded, and the reference to the corrupt member is usable.
+    let good: URef<block::BlockDef> = universe.get(&Name::from("good")).unwrap();
+    assert_eq!(**good.read().unwrap(), block::AIR);
+    let referrer: URef<block::BlockDef> = universe.get(&Name::from("referrer")).unwrap();
+    let evaluated = referrer.read().unwrap().evaluate().unwrap();
+    assert_eq!(&*evaluated.attributes.display_name, "Failed to load 'bad'");
+}
+
+/// Creates a base universe and a current universe which differs from it in one block.
+fn patch_test_universes() -> (Universe, Universe) {
+    let [block_1, block_2] = make_some_blocks();
+    let mut base = Universe::new();
+    base.insert("kept".into(), block::BlockDef::new(block_1.clone()))
+        .unwrap();
+    base.insert("edited".into(), block::BlockDef::new(block_1.clone()))
+        .unwrap();
+    let mut current = Universe::new();
+    current
+        .insert("kept".into(), block::BlockDef::new(block_1))
+        .unwrap();
+    current
+        .insert("edited".into(), block::BlockDef::new(block_2))
+        .unwrap();
+    (base, current)
+}
+
+#[tokio::test]
+async fn patch_round_trip() {
+    let (mut base, current) = patch_test_universes();
+
+    let destination_dir = tempfile::tempdir().unwrap();
+    let destination = destination_dir.path().join("patch.alliscubesjson");
+    export_patch(
+        yield_progress_for_testing(),
+        &base,
+        &current,
+        destination.clone(),
+    )
+    .await
+    .unwrap();
+
+    // Only the changed member is in the patch.
+    let patch_text = fs::read_to_string(&destination).unwrap();
+    assert!(patch_text.contains("edited"), "{patch_text}");
+    assert!(!patch_text.contains("kept"), "{patch_text}");
+
+    apply_patch(
+        yield_progress_for_testing(),
+        &mut base,
+        Arc::new(destination),
+    )
+    .await
+    .unwrap();
+    assert!(base.diff(&current).unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn patch_rejects_wrong_base() {
+    let (base, current) = patch_test_universes();
+    let destination_dir = tempfile::tempdir().unwrap();
+    let destination = destination_dir.path().join("patch.alliscubesjson");
+    export_patch(
+        yield_progress_for_testing(),
+        &base,
+        &current,
+        destination.clone(),
+    )
+    .await
+    .unwrap();
+
+    // Applying the patch to the current universe, instead of the base, fails.
+    let mut wrong_base = patch_test_universes().1;
+    let error = apply_patch(
+        yield_progress_for_testing(),
+        &mut wrong_base,
+        Arc::new(destination),
+    )
+    .await
+    .unwrap_err();
+    match &error.detail {
+        ImportErrorKind::Conflict(detail) => assert!(
+            matches!(
+                detail.downcast_ref::<PatchError>(),
+                Some(PatchError::BaseMismatch { .. })
+            ),
+            "{detail:?}"
+        ),
+        other => panic!("unexpected error {other:?}"),
+    }
+    assert!(wrong_base.diff(&current).unwrap().is_empty());
+}
+
+/// A patch still applies after the base universe's light has been updated, since light
+/// is not part of the base hash.
+#[tokio::test]
+async fn patch_ignores_base_light() {
+    let (mut base, current) = patch_test_universes();
+    let [block] = make_some_blocks();
+    let space = base
+        .insert("space".into(), {
+            let mut space = Space::empty_positive(2, 2, 2);
+            space.set([0, 0, 0], block).unwrap();
+            space
+        })
+        .unwrap();
+    let destination_dir = tempfile::tempdir().unwrap();
+    let destination = destination_dir.path().join("patch.alliscubesjson");
+    export_patch(
+        yield_progress_for_testing(),
+        &base,
+        &current,
+        destination.clone(),
+    )
+    .await
+    .unwrap();
+
+    let light_before = serde_json::to_value(&*space.read().unwrap()).unwrap()["light"].clone();
+    space
+        .try_modify(|space| space.evaluate_light::<NoTime>(0, |_| {}))
+        .unwrap();
+    let light_after = serde_json::to_value(&*space.read().unwrap()).unwrap()["light"].clone();
+    assert_ne!(light_before, light_after);
+
+    apply_patch(
+        yield_progress_for_testing(),
+        &mut base,
+        Arc::new(destination),
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn patch_wrong_format() {
+    let mut universe = Universe::new();
+    let error = apply_patch(
+        yield_progress_for_testing(),
+        &mut universe,
+        Arc::new(NonDiskFile::from_name_and_data_source(
+            "universe".into(),
+            || Ok(br#"{"format": "all-is-cubes-universe", "version": 1, "universe": {}}"#.to_vec()),
+        )),
+    )
+    .await
+    .unwrap_err();
+    match &error.detail {
+        ImportErrorKind::Parse(detail) => assert!(
+            matches!(
+                detail.downcast_ref::<PatchError>(),
+                Some(PatchError::WrongFormat { found: Some(found) })
+                    if found == "all-is-cubes-universe"
+            ),
+            "{detail:?}"
+        ),
+        other => panic!("unexpected error {other:?}"),
+    }
+}
@@ -0,0 +1,153 @@
+//! Export of [`Space`]s as point clouds in the PLY format; see
+//! [`ExportFormat::Ply`](crate::ExportFormat::Ply).
+
+use std::fs;
+use std::io::{self, Write as _};
+use std::path::PathBuf;
+
+use all_is_cubes::cgmath::Point3;
+use all_is_cubes::math::FreeCoordinate;
+use all_is_cubes::space::Space;
+use all_is_cubes::universe::PartialUniverse;
+use all_is_cubes::util::YieldProgress;
+
+use crate::{ExportError, ExportFormat, ExportSet};
+
+pub(crate) async fn export_ply(
+    progress: YieldProgress,
+    source: ExportSet,
+    destination: PathBuf,
+) -> Result<(), ExportError> {
+    preflight_ply(&source)?;
+    let ExportSet {
+        contents:
+            PartialUniverse {
+                blocks: _, // rejected by preflight_ply()
+                spaces,
+                characters: _,
+            },
+        crop_spaces: _,         // rejected by preflight_ply()
+        space_region: _,        // applied by export_to_path()
+        character_inventory: _, // applied by export_to_path()
+        pretty_json: _,         // not a JSON format
+        current_light: _,       // applied by export_to_path()
+    } = &source;
+
+    for (mut p, space_ref) in progress.split_evenly(spaces.len()).zip(spaces) {
+        p.set_label(format!("Exporting points of {}", space_ref.name()));
+        let path = source.member_export_path(ExportFormat::Ply, &destination, space_ref)?;
+        let write_error = |error| ExportError::Write {
+            path: Some(path.clone()),
+            error,
+        };
+        let points = space_to_points(&*space_ref.read()?);
+        let mut writer = io::BufWriter::new(fs::File::create(&path).map_err(write_error)?);
+        write_ply(&points, &mut writer).map_err(write_error)?;
+        writer.flush().map_err(write_error)?;
+        p.finish().await;
+    }
+
+    Ok(())
+}
+
+/// Check for members of `source` which cannot be exported as point clouds.
+pub(crate) fn preflight_ply(source: &ExportSet) -> Result<(), ExportError> {
+    source.check_cropping(ExportFormat::Ply)?;
+    if let Some(first) = source.contents.blocks.first() {
+        return Err(ExportError::NotRepresentable {
+            name: Some(first.name()),
+            reason: "BlockDefs cannot be exported as point clouds".into(),
+        });
+    }
+    Ok(())
+}
+
+/// A point of a point cloud: a position and an sRGB color.
+type Point = (Point3<FreeCoordinate>, [u8; 3]);
+
+/// Returns one point at the center of each cube of `space` which contains a visible block,
+/// colored with that block's [`EvaluatedBlock::color`](all_is_cubes::block::EvaluatedBlock::color).
+fn space_to_points(space: &Space) -> Vec<Point> {
+    space
+        .bounds()
+        .interior_iter()
+        .filter_map(|cube| {
+            let evaluated = space.get_evaluated(cube);
+            if !evaluated.visible {
+                return None;
+            }
+            let [r, g, b, _] = evaluated.color.to_srgb8();
+            Some((cube.midpoint(), [r, g, b]))
+        })
+        .collect()
+}
+
+/// Write `points` as an ASCII PLY file.
+fn write_ply(points: &[Point], w: &mut dyn io::Write) -> io::Result<()> {
+    writeln!(w, "ply")?;
+    writeln!(w, "format ascii 1.0")?;
+    writeln!(w, "comment Exported by All is Cubes")?;
+    writeln!(w, "element vertex {}", points.len())?;
+    for property in ["float x", "float y", "float z"] {
+        writeln!(w, "property {property}")?;
+    }
+    for property in ["uchar red", "uchar green", "uchar blue"] {
+        writeln!(w, "property {property}")?;
+    }
+    writeln!(w, "end_header")?;
+    for (position, [r, g, b]) in points {
+        writeln!(
+            w,
+            "{} {} {} {r} {g} {b}",
+            position.x, position.y, position.z
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{export_to_path, ExportFormat};
+    use all_is_cubes::block::Block;
+    use all_is_cubes::math::{GridAab, Rgba};
+    use all_is_cubes::universe::{URef, Universe};
+    use all_is_cubes::util::yield_progress_for_testing;
+
+    #[tokio::test]
+    async fn export_two_voxels() {
+        let mut universe = Universe::new();
+        let mut space = Space::empty(GridAab::from_lower_size([-1, 0, 0], [3, 2, 2]));
+        space.set([-1, 0, 0], Block::from(Rgba::BLACK)).unwrap();
+        space.set([1, 1, 1], Block::from(Rgba::WHITE)).unwrap();
+        let space: URef<Space> = universe.insert("points".into(), space).unwrap();
+
+        let destination_dir = tempfile::tempdir().unwrap();
+        let destination: PathBuf = destination_dir.path().join("points.ply");
+        export_to_path(
+            yield_progress_for_testing(),
+            ExportFormat::Ply,
+            ExportSet::from_spaces(vec![space]),
+            destination.clone(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(destination).unwrap(),
+            "ply\n\
+            format ascii 1.0\n\
+            comment Exported by All is Cubes\n\
+            element vertex 2\n\
+            property float x\n\
+            property float y\n\
+            property float z\n\
+            property uchar red\n\
+            property uchar green\n\
+            property uchar blue\n\
+            end_header\n\
+            -0.5 0.5 0.5 0 0 0\n\
+            1.5 1.5 1.5 255 255 255\n"
+        );
+    }
+}
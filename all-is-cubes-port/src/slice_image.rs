@@ -0,0 +1,220 @@
+//! Rendering of a single layer of a [`Space`] as a flat image; see [`space_slice_image()`]
+//! and [`draw_space_slice()`].
+
+use all_is_cubes::cgmath::Vector3;
+use all_is_cubes::drawing::embedded_graphics::{
+    pixelcolor::Rgb888,
+    prelude::{DrawTarget, Point, Size},
+    primitives::Rectangle,
+};
+use all_is_cubes::math::{Cube, Face6, GridCoordinate, GridPoint, Rgba};
+use all_is_cubes::space::Space;
+
+/// Draws one layer of `space` as an image, with one square of `scale` × `scale` pixels
+/// per cube, colored with the [`EvaluatedBlock::color`] of the block in that cube.
+/// This is much cheaper than raytracing, but does not show anything above or below the
+/// layer, nor any details of blocks smaller than a cube.
+///
+/// * `up` is the direction that is toward the viewer, so that the image is the view
+///   looking in the opposite direction. For example, [`Face6::PY`] produces a top-down
+///   map, with +X to the right and +Z toward the bottom of the image. When `up` is a
+///   horizontal direction, +Y is always toward the top of the image.
+/// * `coordinate` selects the layer along the axis of `up`. If it is outside the bounds
+///   of the space, the image will be entirely transparent.
+///
+/// The image's size is that of the space's bounds, perpendicular to `up`, times `scale`.
+///
+/// [`EvaluatedBlock::color`]: all_is_cubes::block::EvaluatedBlock::color
+pub fn space_slice_image(
+    space: &Space,
+    up: Face6,
+    coordinate: GridCoordinate,
+    scale: u32,
+) -> image::RgbaImage {
+    let ([width, height], cubes) = slice_colors(space, up, coordinate);
+    let mut image = image::RgbaImage::new(width * scale, height * scale);
+    for ([x, y], color) in cubes {
+        let color = image::Rgba(color.to_srgb8());
+        for py in y * scale..(y + 1) * scale {
+            for px in x * scale..(x + 1) * scale {
+                image.put_pixel(px, py, color);
+            }
+        }
+    }
+    image
+}
+
+/// Draws one layer of `space` onto an [`embedded_graphics`] [`DrawTarget`], in the same
+/// arrangement as [`space_slice_image()`] produces, with the top left cube at the
+/// target's origin. (Use [`DrawTargetExt::translated()`] to place it elsewhere.)
+///
+/// Since [`embedded_graphics`] colors are opaque, cubes whose color is fully
+/// transparent are not drawn, leaving the target's existing pixels visible, and the
+/// alpha of all other colors is ignored.
+///
+/// [`embedded_graphics`]: all_is_cubes::drawing::embedded_graphics
+/// [`DrawTargetExt::translated()`]: all_is_cubes::drawing::embedded_graphics::draw_target::DrawTargetExt::translated
+pub fn draw_space_slice<D>(
+    space: &Space,
+    up: Face6,
+    coordinate: GridCoordinate,
+    scale: u32,
+    target: &mut D,
+) -> Result<(), D::Error>
+where
+    D: DrawTarget,
+    D::Color: From<Rgb888>,
+{
+    let to_point = |c: u32| i32::try_from(c.saturating_mul(scale)).unwrap_or(i32::MAX);
+    let (_, cubes) = slice_colors(space, up, coordinate);
+    for ([x, y], color) in cubes {
+        if color.fully_transparent() {
+            continue;
+        }
+        let [r, g, b, _] = color.to_srgb8();
+        target.fill_solid(
+            &Rectangle::new(
+                Point::new(to_point(x), to_point(y)),
+                Size::new(scale, scale),
+            ),
+            Rgb888::new(r, g, b).into(),
+        )?;
+    }
+    Ok(())
+}
+
+/// Returns the size, in cubes, of the layer of `space` selected by `up` and `coordinate`,
+/// and the position within that layer and color of each cube in it, in the arrangement
+/// documented by [`space_slice_image()`].
+fn slice_colors(
+    space: &Space,
+    up: Face6,
+    coordinate: GridCoordinate,
+) -> ([u32; 2], impl Iterator<Item = ([u32; 2], Rgba)> + '_) {
+    use Face6::*;
+    // Directions in the space corresponding to image +X and +Y.
+    let (right, down) = match up {
+        PX => (NZ, NY),
+        NX => (PZ, NY),
+        PY => (PX, PZ),
+        NY => (PX, NZ),
+        PZ => (PX, NY),
+        NZ => (NX, NY),
+    };
+
+    let bounds = space.bounds();
+    let size = bounds.unsigned_size();
+    let width = size[right.axis_number()];
+    let height = size[down.axis_number()];
+
+    // Cube to draw at the top left corner of the image; the image's X and Y axes then
+    // proceed in the `right` and `down` directions.
+    let mut origin: GridPoint = bounds.lower_bounds();
+    origin[up.axis_number()] = coordinate;
+    for face in [right, down] {
+        if face.is_negative() {
+            origin[face.axis_number()] = bounds.upper_bounds()[face.axis_number()] - 1;
+        }
+    }
+    let right_vector: Vector3<GridCoordinate> = right.normal_vector();
+    let down_vector: Vector3<GridCoordinate> = down.normal_vector();
+
+    let cubes = (0..height).flat_map(move |y| {
+        (0..width).map(move |x| {
+            // cannot overflow because the results are within the bounds of the space
+            let cube = Cube::from(
+                origin
+                    + right_vector * GridCoordinate::try_from(x).unwrap()
+                    + down_vector * GridCoordinate::try_from(y).unwrap(),
+            );
+            ([x, y], space.get_evaluated(cube).color)
+        })
+    });
+    ([width, height], cubes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use all_is_cubes::block::Block;
+    use all_is_cubes::math::{GridAab, Rgba};
+
+    fn test_space() -> (Space, [Rgba; 2]) {
+        let colors = [Rgba::new(1.0, 0.0, 0.0, 1.0), Rgba::new(0.0, 0.0, 1.0, 1.0)];
+        let mut space = Space::empty(GridAab::from_lower_size([10, 20, 30], [3, 4, 5]));
+        space.set([10, 21, 30], Block::from(colors[0])).unwrap();
+        space.set([12, 21, 34], Block::from(colors[1])).unwrap();
+        (space, colors)
+    }
+
+    fn pixel(color: Rgba) -> image::Rgba<u8> {
+        image::Rgba(color.to_srgb8())
+    }
+
+    #[test]
+    fn top_down() {
+        let (space, [c0, c1]) = test_space();
+        let image = space_slice_image(&space, Face6::PY, 21, 2);
+
+        assert_eq!(image.dimensions(), (3 * 2, 5 * 2));
+        assert_eq!(*image.get_pixel(0, 0), pixel(c0));
+        assert_eq!(*image.get_pixel(1, 1), pixel(c0));
+        assert_eq!(*image.get_pixel(2, 0), image::Rgba([0, 0, 0, 0]));
+        assert_eq!(*image.get_pixel(5, 9), pixel(c1));
+    }
+
+    #[test]
+    fn side_view() {
+        let (space, [c0, c1]) = test_space();
+
+        let image = space_slice_image(&space, Face6::PZ, 30, 1);
+        assert_eq!(image.dimensions(), (3, 4));
+        // +Y is up, so Y = 21 is the third row from the top.
+        assert_eq!(*image.get_pixel(0, 2), pixel(c0));
+
+        // Looking toward +X from the -X side, +Z is to the right.
+        let image = space_slice_image(&space, Face6::NX, 12, 1);
+        assert_eq!(image.dimensions(), (5, 4));
+        assert_eq!(*image.get_pixel(4, 2), pixel(c1));
+    }
+
+    #[test]
+    fn draw_onto_target() {
+        use all_is_cubes::drawing::embedded_graphics::mock_display::MockDisplay;
+        use all_is_cubes::drawing::embedded_graphics::primitives::PointsIter as _;
+
+        let (space, [c0, c1]) = test_space();
+        let mut display = MockDisplay::<Rgb888>::new();
+        draw_space_slice(&space, Face6::PY, 21, 2, &mut display).unwrap();
+
+        let rgb888 = |color: Rgba| {
+            let [r, g, b, _] = color.to_srgb8();
+            Rgb888::new(r, g, b)
+        };
+        assert_eq!(
+            display.affected_area(),
+            Rectangle::new(Point::zero(), Size::new(6, 10))
+        );
+        assert_eq!(display.get_pixel(Point::new(0, 0)), Some(rgb888(c0)));
+        assert_eq!(display.get_pixel(Point::new(1, 1)), Some(rgb888(c0)));
+        // Transparent cubes are not drawn.
+        assert_eq!(display.get_pixel(Point::new(2, 0)), None);
+        assert_eq!(display.get_pixel(Point::new(5, 9)), Some(rgb888(c1)));
+        // Only the two non-transparent cubes are drawn, at 2 × 2 pixels each.
+        assert_eq!(
+            display
+                .affected_area()
+                .points()
+                .filter(|&p| display.get_pixel(p).is_some())
+                .count(),
+            8
+        );
+    }
+
+    #[test]
+    fn outside_bounds() {
+        let (space, _) = test_space();
+        let image = space_slice_image(&space, Face6::PY, 100, 1);
+        assert!(image.pixels().all(|&p| p == image::Rgba([0, 0, 0, 0])));
+    }
+}
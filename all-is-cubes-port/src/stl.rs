@@ -1,6 +1,8 @@
 //! Export to the STL 3D model file format.
 
 use std::fs;
+use std::io::{self, Seek, SeekFrom, Write};
+use std::path::Path;
 
 use itertools::Itertools as _;
 use stl_io::Triangle;
@@ -10,67 +12,204 @@ use all_is_cubes::universe::PartialUniverse;
 
 use all_is_cubes::camera::GraphicsOptions;
 use all_is_cubes::cgmath::{EuclideanSpace as _, Vector3};
-use all_is_cubes::math::FreeCoordinate;
+use all_is_cubes::math::{FreeCoordinate, GridCoordinate, GridVector};
 use all_is_cubes::notnan;
 use all_is_cubes::space::Space;
-use all_is_cubes::util::YieldProgress;
+use all_is_cubes::util::{ConciseDebug, CustomFormat as _, YieldProgress};
 use all_is_cubes_mesh::{
     self as mesh,
     texture::{NoTexture, NoTextures},
     BlockVertex,
 };
 
+use crate::{ExportError, ExportFormat, ExportSet};
+
 pub(crate) async fn export_stl(
     progress: YieldProgress,
-    source: crate::ExportSet,
+    source: ExportSet,
     destination: std::path::PathBuf,
-) -> Result<(), crate::ExportError> {
-    let crate::ExportSet {
+) -> Result<(), ExportError> {
+    let ExportSet {
         contents:
             PartialUniverse {
                 blocks: block_defs,
                 spaces,
                 characters: _,
             },
+        crop_spaces,
+        space_region: _,        // applied by export_to_path()
+        character_inventory: _, // applied by export_to_path()
+        pretty_json: _,         // not a JSON format
+        current_light: _,       // applied by export_to_path()
     } = &source;
 
-    // TODO: give each exported item a distinct filename suffix
+    let mut member_progress = progress.split_evenly(spaces.len() + block_defs.len());
 
-    for space in spaces {
-        stl_io::write_stl(
-            &mut fs::File::create(source.member_export_path(&destination, space))?,
-            space_to_stl_triangles(&*space.read()?).into_iter(),
+    // Note that the member iterators must be first in the zip, so that no extra
+    // progress item is consumed when they end.
+    for (space_ref, mut p) in spaces.iter().zip(member_progress.by_ref()) {
+        p.set_label(format!("Exporting space {}", space_ref.name()));
+        p.progress(0.01).await;
+        let space = space_ref.read()?;
+        let mut translation = None;
+        write_stl_file(
+            &source.member_export_path(ExportFormat::Stl, &destination, space_ref)?,
+            |writer| {
+                translation = write_space_stl(&space, *crop_spaces, writer)?;
+                Ok(())
+            },
         )?;
+        // STL has no place to record the translation, so report it here instead.
+        if let (true, Some(translation)) = (*crop_spaces, translation) {
+            log::info!(
+                "Cropped space {} for STL export; translated by {}",
+                space_ref.name(),
+                translation.custom_format(ConciseDebug),
+            );
+        }
+        p.finish().await;
     }
 
-    for block_def in block_defs {
-        stl_io::write_stl(
-            &mut fs::File::create(source.member_export_path(&destination, block_def))?,
-            block_to_stl_triangles(&**block_def.read()?)
-                .map_err(|error| crate::ExportError::Eval {
-                    name: block_def.name(),
-                    error,
-                })?
-                .into_iter(),
+    for (block_def, mut p) in block_defs.iter().zip(member_progress) {
+        p.set_label(format!("Exporting block {}", block_def.name()));
+        p.progress(0.01).await;
+        let triangles =
+            block_to_stl_triangles(&**block_def.read()?).map_err(|error| ExportError::Eval {
+                name: block_def.name(),
+                error,
+            })?;
+        write_stl_file(
+            &source.member_export_path(ExportFormat::Stl, &destination, block_def)?,
+            |writer| {
+                triangles
+                    .iter()
+                    .try_for_each(|triangle| writer.write_triangle(triangle))
+            },
         )?;
+        p.finish().await;
     }
 
-    // TODO: progress at individual parts
-    progress.finish().await;
+    Ok(())
+}
 
+/// Create a new STL file at `path` and write the triangles `contents` provides to it.
+fn write_stl_file(
+    path: &Path,
+    contents: impl FnOnce(&mut StlWriter<fs::File>) -> io::Result<()>,
+) -> Result<(), ExportError> {
+    let write_error = |error| ExportError::Write {
+        path: Some(path.to_owned()),
+        error,
+    };
+    let file = fs::File::create(path).map_err(write_error)?;
+    let mut writer = StlWriter::new(file).map_err(write_error)?;
+    contents(&mut writer).map_err(write_error)?;
+    writer.finish().map_err(write_error)?;
     Ok(())
 }
 
-pub(crate) fn space_to_stl_triangles(space: &Space) -> Vec<Triangle> {
+/// Writes a binary STL file one triangle at a time, so that the triangles need not all
+/// be held in memory at once.
+///
+/// Binary STL stores the number of triangles before the triangles, so a placeholder
+/// count is written first and filled in by [`StlWriter::finish()`]. This is why the
+/// destination must implement [`Seek`].
+pub(crate) struct StlWriter<W: Write + Seek> {
+    writer: io::BufWriter<W>,
+    /// Position of the start of the file, in case `W` was not at its start.
+    start: u64,
+    /// Number of triangles written so far.
+    count: u32,
+}
+
+/// Length of the binary STL header, which precedes the triangle count.
+const HEADER_LEN: u64 = 80;
+
+impl<W: Write + Seek> StlWriter<W> {
+    pub fn new(mut destination: W) -> io::Result<Self> {
+        let start = destination.stream_position()?;
+        let mut writer = io::BufWriter::new(destination);
+        writer.write_all(&[0; HEADER_LEN as usize])?;
+        writer.write_all(&0u32.to_le_bytes())?;
+        Ok(Self {
+            writer,
+            start,
+            count: 0,
+        })
+    }
+
+    pub fn write_triangle(&mut self, triangle: &Triangle) -> io::Result<()> {
+        self.count = self.count.checked_add(1).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "too many triangles for the STL format",
+            )
+        })?;
+        let Triangle { normal, vertices } = triangle;
+        for vector in std::iter::once(normal).chain(vertices) {
+            for component in <[f32; 3]>::from(*vector) {
+                self.writer.write_all(&component.to_le_bytes())?;
+            }
+        }
+        // Attribute byte count
+        self.writer.write_all(&0u16.to_le_bytes())?;
+        Ok(())
+    }
+
+    /// Fill in the triangle count, and return the destination positioned after the
+    /// end of the STL data.
+    pub fn finish(self) -> io::Result<W> {
+        let Self {
+            mut writer,
+            start,
+            count,
+        } = self;
+        let end = writer.stream_position()?;
+        writer.seek(SeekFrom::Start(start + HEADER_LEN))?;
+        writer.write_all(&count.to_le_bytes())?;
+        writer.seek(SeekFrom::Start(end))?;
+        writer.into_inner().map_err(io::IntoInnerError::into_error)
+    }
+}
+
+/// Size of the chunks a space is meshed in when exported, which bounds the memory used
+/// for meshing regardless of the size of the space.
+const CHUNK_SIZE: GridCoordinate = 32;
+
+/// Convert the contents of `space` to triangles and write them to `writer`, one chunk at
+/// a time.
+///
+/// If `crop` is true, then only the region occupied by non-[`AIR`](block::AIR) blocks is
+/// included. In either case, the included region is translated so that its lower corner
+/// is at the origin, and the translation is returned; if `crop` is true and the space is
+/// empty, nothing is written and [`None`] is returned.
+pub(crate) fn write_space_stl<W: Write + Seek>(
+    space: &Space,
+    crop: bool,
+    writer: &mut StlWriter<W>,
+) -> io::Result<Option<GridVector>> {
+    let bounds = if crop {
+        match crate::occupied_bounds(space) {
+            Some(bounds) => bounds,
+            None => return Ok(None),
+        }
+    } else {
+        space.bounds()
+    };
+
     let mesh_options = mesh_options_for_stl();
     let block_meshes: Box<[mesh::BlockMesh<BlockVertex<_>, _>]> =
         mesh::block_meshes_for_space(space, &NoTextures, &mesh_options);
-    space_mesh_to_triangles(&mesh::SpaceMesh::new(
-        space,
-        space.bounds(),
-        &mesh_options,
-        &*block_meshes,
-    ))
+    for chunk in bounds.subdivide(CHUNK_SIZE) {
+        // Each chunk's mesh is translated so that the chunk's lower corner is at the
+        // origin, so translate it back to where it is relative to `bounds`.
+        let offset = (chunk.lower_bounds() - bounds.lower_bounds()).map(FreeCoordinate::from);
+        let chunk_mesh = mesh::SpaceMesh::new(space, chunk, &mesh_options, &*block_meshes);
+        for triangle in space_mesh_to_triangles(&chunk_mesh, offset) {
+            writer.write_triangle(&triangle)?;
+        }
+    }
+    Ok(Some(-bounds.lower_bounds().to_vec()))
 }
 
 pub(crate) fn block_to_stl_triangles(
@@ -78,7 +217,10 @@ pub(crate) fn block_to_stl_triangles(
 ) -> Result<Vec<Triangle>, block::EvalBlockError> {
     let block_mesh: mesh::BlockMesh<BlockVertex<_>, _> =
         mesh::BlockMesh::new(&block.evaluate()?, &NoTextures, &mesh_options_for_stl());
-    Ok(space_mesh_to_triangles(&mesh::SpaceMesh::from(&block_mesh)))
+    Ok(space_mesh_to_triangles(
+        &mesh::SpaceMesh::from(&block_mesh),
+        Vector3::new(0., 0., 0.),
+    ))
 }
 
 fn mesh_options_for_stl() -> mesh::MeshOptions {
@@ -87,14 +229,16 @@ fn mesh_options_for_stl() -> mesh::MeshOptions {
     mesh::MeshOptions::new(&g)
 }
 
+/// Convert the triangles of `mesh`, translated by `offset`.
 fn space_mesh_to_triangles(
     mesh: &mesh::SpaceMesh<BlockVertex<NoTexture>, NoTexture>,
+    offset: Vector3<FreeCoordinate>,
 ) -> Vec<Triangle> {
     let vertices = mesh.vertices();
     mesh.indices()
         .iter_u32()
         .tuples()
-        .map(|(i1, i2, i3)| {
+        .map(move |(i1, i2, i3)| {
             let tri = [
                 vertices[i1 as usize],
                 vertices[i2 as usize],
@@ -102,7 +246,7 @@ fn space_mesh_to_triangles(
             ];
             Triangle {
                 normal: convert_vector(tri[0].face.normal_vector()),
-                vertices: tri.map(|v| convert_vector(v.position.to_vec())),
+                vertices: tri.map(|v| convert_vector(v.position.to_vec() + offset)),
             }
         })
         .collect()
@@ -116,23 +260,68 @@ fn convert_vector(input: Vector3<FreeCoordinate>) -> stl_io::Vector<f32> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{ExportFormat, ExportSet};
     use all_is_cubes::block::BlockDef;
-    use all_is_cubes::content::make_some_voxel_blocks;
     use all_is_cubes::content::testing::lighting_bench_space;
+    use all_is_cubes::content::{make_some_blocks, make_some_voxel_blocks};
+    use all_is_cubes::math::GridAab;
     use all_is_cubes::universe::{Name, URef, Universe};
     use all_is_cubes::util::yield_progress_for_testing;
     use std::collections::BTreeSet;
     use std::path::PathBuf;
 
+    /// Write `space` to an in-memory STL file, check that its triangle count is
+    /// consistent, and read back the triangles.
+    fn space_to_stl_triangles(space: &Space, crop: bool) -> Vec<Triangle> {
+        let mut writer = StlWriter::new(io::Cursor::new(Vec::new())).unwrap();
+        write_space_stl(space, crop, &mut writer).unwrap();
+        let bytes = writer.finish().unwrap().into_inner();
+
+        let count = u32::from_le_bytes(bytes[80..84].try_into().unwrap());
+        assert_eq!(bytes.len(), 84 + count as usize * 50);
+        let triangles = stl_io::create_stl_reader(&mut io::Cursor::new(&bytes))
+            .unwrap()
+            .collect::<Result<Vec<Triangle>, _>>()
+            .unwrap();
+        assert_eq!(triangles.len(), count as usize);
+        triangles
+    }
+
     #[test]
     fn space_to_stl_smoke_test() {
         let mut u = Universe::new();
         let space = lighting_bench_space(&mut u, Vector3::new(54, 16, 54)).unwrap();
-        let mesh = space_to_stl_triangles(&space);
+        let mesh = space_to_stl_triangles(&space, false);
         assert!(mesh.len() > 30_000, "{}", mesh.len());
     }
 
+    /// A space spanning many chunks produces the same triangles as meshing it all at once.
+    #[test]
+    fn space_to_stl_chunked() {
+        let mut u = Universe::new();
+        let space = lighting_bench_space(&mut u, Vector3::new(54, 16, 54)).unwrap();
+        assert!(space.bounds().size().x > CHUNK_SIZE);
+
+        let options = mesh_options_for_stl();
+        let block_meshes: Box<[mesh::BlockMesh<BlockVertex<_>, _>]> =
+            mesh::block_meshes_for_space(&space, &NoTextures, &options);
+        let whole_mesh = mesh::SpaceMesh::new(&space, space.bounds(), &options, &*block_meshes);
+        let sort_key = |t: &Triangle| {
+            std::iter::once(t.normal)
+                .chain(t.vertices)
+                .flat_map(<[f32; 3]>::from)
+                .map(f32::to_bits)
+                .collect::<Vec<u32>>()
+        };
+        let mut expected = space_mesh_to_triangles(&whole_mesh, Vector3::new(0., 0., 0.));
+        expected.sort_by_key(sort_key);
+
+        let mut chunked = space_to_stl_triangles(&space, false);
+        chunked.sort_by_key(sort_key);
+
+        assert_eq!(chunked.len(), expected.len());
+        assert!(chunked == expected);
+    }
+
     #[tokio::test]
     async fn export_multiple() {
         // TODO: dedup this logic with gltf export
@@ -174,4 +363,37 @@ mod tests {
             ])
         );
     }
+
+    #[test]
+    fn space_to_stl_cropped() {
+        let [block] = make_some_blocks();
+        let mut space = Space::empty(GridAab::from_lower_size([-100, 0, 0], [200, 50, 200]));
+        space.set([70, 40, 150], &block).unwrap();
+
+        let mesh = space_to_stl_triangles(&space, true);
+        assert_eq!(mesh.len(), 12);
+        let mut writer = StlWriter::new(io::Cursor::new(Vec::new())).unwrap();
+        assert_eq!(
+            write_space_stl(&space, true, &mut writer).unwrap(),
+            Some(GridVector::new(-70, -40, -150))
+        );
+        for triangle in mesh {
+            for vertex in triangle.vertices {
+                for coordinate in <[f32; 3]>::from(vertex) {
+                    assert!(
+                        (0.0..=1.0).contains(&coordinate),
+                        "{vertex:?} not in unit cube"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn space_to_stl_cropped_empty() {
+        let space = Space::empty_positive(10, 10, 10);
+        assert_eq!(space_to_stl_triangles(&space, true), vec![]);
+        let mut writer = StlWriter::new(io::Cursor::new(Vec::new())).unwrap();
+        assert_eq!(write_space_stl(&space, true, &mut writer).unwrap(), None);
+    }
 }
@@ -1,12 +1,24 @@
 use std::error::Error as _;
 use std::sync::Arc;
 
-use all_is_cubes::block;
-use all_is_cubes::util::{assert_send_sync, yield_progress_for_testing};
+use all_is_cubes::block::{self, Block, Primitive};
+use all_is_cubes::character::{Character, Spawn};
+use all_is_cubes::content::make_some_blocks;
+use all_is_cubes::inv::{Slot, Tool};
+use all_is_cubes::math::GridAab;
+use all_is_cubes::space::Space;
+use all_is_cubes::time::NoTime;
+use all_is_cubes::transaction::Transaction as _;
+use all_is_cubes::universe::{RefError, URef, UniverseTransaction};
+use all_is_cubes::util::{
+    assert_send_sync, yield_progress_for_recording, yield_progress_for_testing,
+};
 
-use crate::file::NonDiskFile;
+use crate::file::{Fileish, NonDiskFile};
 use crate::{
-    load_universe_from_file, BlockDef, ExportError, ExportSet, ImportError, Path, PathBuf, Universe,
+    export_to_path, load_universe_from_file, load_universe_from_file_with_options, BlockDef,
+    ExportError, ExportFormat, ExportSet, ImportError, ImportErrorKind, ImportLimits,
+    ImportOptions, LimitedResource, Path, PathBuf, Universe,
 };
 
 #[test]
@@ -33,6 +45,266 @@ async fn import_unknown_format() {
     );
 }
 
+/// Exports, in the native format, a universe containing a 4×4×4 space and two
+/// block definitions.
+async fn export_for_limits_test(directory: &Path) -> PathBuf {
+    let mut universe = Universe::new();
+    universe
+        .insert("space".into(), Space::empty_positive(4, 4, 4))
+        .unwrap();
+    for block in make_some_blocks::<2>() {
+        universe.insert_anonymous(BlockDef::new(block));
+    }
+    let destination = directory.join("limits.alliscubesjson");
+    export_to_path(
+        yield_progress_for_testing(),
+        ExportFormat::AicJson,
+        ExportSet::all_of_universe(&universe),
+        destination.clone(),
+    )
+    .await
+    .unwrap();
+    destination
+}
+
+async fn import_with_limits(
+    file: Arc<dyn Fileish>,
+    limits: ImportLimits,
+) -> Result<Universe, ImportError> {
+    let options = ImportOptions {
+        limits,
+        ..ImportOptions::default()
+    };
+    load_universe_from_file_with_options(yield_progress_for_testing(), file, &options).await
+}
+
+#[track_caller]
+fn assert_resource_limit(error: ImportError, expected: LimitedResource, expected_limit: u64) {
+    match error.detail {
+        ImportErrorKind::ResourceLimit { resource, limit } => {
+            assert_eq!((resource, limit), (expected, expected_limit));
+        }
+        ref detail => panic!("expected ResourceLimit error, got {detail:?}"),
+    }
+}
+
+#[tokio::test]
+async fn import_limit_bytes() {
+    let directory = tempfile::tempdir().unwrap();
+    let path = export_for_limits_test(directory.path()).await;
+    let size = std::fs::metadata(&path).unwrap().len();
+    let contents = std::fs::read(&path).unwrap();
+
+    // Checked using the size of the file on disk, without reading it.
+    let error = import_with_limits(
+        Arc::new(path.clone()),
+        ImportLimits {
+            max_bytes: size - 1,
+            ..ImportLimits::default()
+        },
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error.source().unwrap().to_string(),
+        format!("the data exceeds the import limit of {} bytes", size - 1)
+    );
+    assert_resource_limit(error, LimitedResource::Bytes, size - 1);
+
+    // Checked after reading, when the size is not known beforehand.
+    let error = import_with_limits(
+        Arc::new(NonDiskFile::from_name_and_data_source(
+            "limits".into(),
+            move || Ok(contents.clone()),
+        )),
+        ImportLimits {
+            max_bytes: size - 1,
+            ..ImportLimits::default()
+        },
+    )
+    .await
+    .unwrap_err();
+    assert_resource_limit(error, LimitedResource::Bytes, size - 1);
+
+    import_with_limits(
+        Arc::new(path),
+        ImportLimits {
+            max_bytes: size,
+            ..ImportLimits::default()
+        },
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn import_limit_members() {
+    let directory = tempfile::tempdir().unwrap();
+    let path = export_for_limits_test(directory.path()).await;
+
+    let error = import_with_limits(
+        Arc::new(path.clone()),
+        ImportLimits {
+            max_members: 2,
+            ..ImportLimits::default()
+        },
+    )
+    .await
+    .unwrap_err();
+    assert_resource_limit(error, LimitedResource::Members, 2);
+
+    import_with_limits(
+        Arc::new(path),
+        ImportLimits {
+            max_members: 3,
+            ..ImportLimits::default()
+        },
+    )
+    .await
+    .unwrap();
+}
+
+/// Limits also apply to native files written before the format had a header.
+#[tokio::test]
+async fn import_limit_members_legacy_native() {
+    let legacy_file = || -> Arc<dyn Fileish> {
+        Arc::new(PathBuf::from(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/src/native/tests/native-test-legacy.alliscubesjson"
+        )))
+    };
+
+    let error = import_with_limits(
+        legacy_file(),
+        ImportLimits {
+            max_members: 0,
+            ..ImportLimits::default()
+        },
+    )
+    .await
+    .unwrap_err();
+    assert_resource_limit(error, LimitedResource::Members, 0);
+
+    import_with_limits(
+        legacy_file(),
+        ImportLimits {
+            max_members: 1,
+            ..ImportLimits::default()
+        },
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn import_limit_space_volume() {
+    let directory = tempfile::tempdir().unwrap();
+    let path = export_for_limits_test(directory.path()).await;
+
+    let error = import_with_limits(
+        Arc::new(path.clone()),
+        ImportLimits {
+            max_space_volume: 63,
+            ..ImportLimits::default()
+        },
+    )
+    .await
+    .unwrap_err();
+    assert_resource_limit(error, LimitedResource::SpaceVolume, 63);
+
+    import_with_limits(
+        Arc::new(path),
+        ImportLimits {
+            max_space_volume: 64,
+            ..ImportLimits::default()
+        },
+    )
+    .await
+    .unwrap();
+}
+
+/// Exporting a space whose light is not finished waits for it to be finished, unless
+/// [`ExportSet::with_current_light()`] is used.
+#[tokio::test]
+async fn export_finishes_light() {
+    for current_light in [false, true] {
+        let mut universe = Universe::new();
+        let [block] = make_some_blocks();
+        let mut space = Space::empty_positive(5, 5, 5);
+        space.evaluate_light::<NoTime>(0, |_| {});
+        // Modify the space so that light updates are pending.
+        space.set([2, 2, 2], &block).unwrap();
+        let space = universe.insert("space".into(), space).unwrap();
+
+        let mut export_set = ExportSet::from_spaces(vec![space.clone()]);
+        if current_light {
+            export_set = export_set.with_current_light();
+        }
+        let destination_dir = tempfile::tempdir().unwrap();
+        let destination = destination_dir.path().join("space.alliscubesjson");
+        export_to_path(
+            yield_progress_for_testing(),
+            ExportFormat::AicJson,
+            export_set,
+            destination.clone(),
+        )
+        .await
+        .unwrap();
+
+        let imported = load_universe_from_file(yield_progress_for_testing(), Arc::new(destination))
+            .await
+            .unwrap();
+        let imported_space = imported.get::<Space>(&"space".into()).unwrap();
+        let imported_space = imported_space.read().unwrap();
+        // `LightStatus::Uninitialized` is represented as zero.
+        let uninitialized_count = imported_space
+            .bounds()
+            .interior_iter()
+            .filter(|&cube| imported_space.get_lighting(cube).as_texel()[3] == 0)
+            .count();
+        if current_light {
+            assert!(uninitialized_count > 0, "test is ineffective");
+        } else {
+            assert_eq!(uninitialized_count, 0);
+        }
+    }
+}
+
+#[test]
+fn format_extension_round_trip() {
+    for format in [
+        ExportFormat::AicJson,
+        ExportFormat::DotVox,
+        ExportFormat::Gltf,
+        ExportFormat::Stl,
+        ExportFormat::LightVolume,
+        ExportFormat::Ply,
+    ] {
+        let extension = format.file_extension();
+        assert_eq!(ExportFormat::from_extension(extension), Some(format));
+        assert_eq!(
+            ExportFormat::from_extension(&extension.to_ascii_uppercase()),
+            Some(format)
+        );
+    }
+}
+
+#[test]
+fn format_from_extension() {
+    assert_eq!(
+        ExportFormat::from_extension("GLTF"),
+        Some(ExportFormat::Gltf)
+    );
+    assert_eq!(ExportFormat::from_extension("glb"), None);
+    assert_eq!(
+        ExportFormat::from_extension("Vox"),
+        Some(ExportFormat::DotVox)
+    );
+    assert_eq!(ExportFormat::from_extension(""), None);
+    assert_eq!(ExportFormat::from_extension(".stl"), None);
+    assert_eq!(ExportFormat::from_extension("png"), None);
+}
+
 #[test]
 fn member_export_path() {
     let mut universe = Universe::new();
@@ -45,12 +317,489 @@ fn member_export_path() {
 
     assert_eq!(
         ExportSet::all_of_universe(&universe)
-            .member_export_path(Path::new("/export/data.ext"), &foo),
+            .member_export_path(ExportFormat::Stl, Path::new("/export/data.ext"), &foo)
+            .unwrap(),
         PathBuf::from("/export/data-foo.ext"),
     );
     assert_eq!(
         ExportSet::from_block_defs(vec![foo.clone()])
-            .member_export_path(Path::new("/export/data.ext"), &foo),
+            .member_export_path(ExportFormat::Stl, Path::new("/export/data.ext"), &foo)
+            .unwrap(),
         PathBuf::from("/export/data.ext"),
     );
 }
+
+/// A base path without an extension gets the format's extension.
+#[test]
+fn member_export_path_without_extension() {
+    let mut universe = Universe::new();
+    let foo = universe
+        .insert("foo".into(), BlockDef::new(block::AIR))
+        .unwrap();
+    let _bar = universe
+        .insert("bar".into(), BlockDef::new(block::AIR))
+        .unwrap();
+    let set = ExportSet::all_of_universe(&universe);
+
+    assert_eq!(
+        set.member_export_path(ExportFormat::Stl, Path::new("/export/data"), &foo)
+            .unwrap(),
+        PathBuf::from("/export/data-foo.stl"),
+    );
+    assert_eq!(
+        set.member_export_path(ExportFormat::LightVolume, Path::new("/export/data"), &foo)
+            .unwrap(),
+        PathBuf::from("/export/data-foo.aiclight"),
+    );
+}
+
+/// A base path which does not name a file is an error, not a panic.
+#[test]
+fn member_export_path_not_a_file() {
+    let mut universe = Universe::new();
+    let foo = universe
+        .insert("foo".into(), BlockDef::new(block::AIR))
+        .unwrap();
+    let _bar = universe
+        .insert("bar".into(), BlockDef::new(block::AIR))
+        .unwrap();
+
+    for path in ["/", "/export/.."] {
+        let error = ExportSet::all_of_universe(&universe)
+            .member_export_path(ExportFormat::Stl, Path::new(path), &foo)
+            .unwrap_err();
+        assert!(
+            matches!(&error, ExportError::InvalidDestination { path: p, .. } if p == Path::new(path)),
+            "{error:?}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn export_order_is_deterministic() {
+    let [block] = make_some_blocks();
+    let mut universe = Universe::new();
+    let mut spaces = Vec::new();
+    for name in ["zebra", "aardvark", "mongoose"] {
+        let mut space = Space::empty_positive(1, 1, 1);
+        space.set([0, 0, 0], &block).unwrap();
+        spaces.push(universe.insert(name.into(), space).unwrap());
+    }
+
+    // Given order is preserved; whole-universe order is by name.
+    let set = ExportSet::from_spaces(spaces.clone());
+    assert_eq!(
+        set.members()
+            .iter()
+            .map(|member| member.name())
+            .collect::<Vec<_>>(),
+        vec!["zebra".into(), "aardvark".into(), "mongoose".into()]
+    );
+    assert_eq!(
+        ExportSet::all_of_universe(&universe)
+            .members()
+            .iter()
+            .map(|member| member.name())
+            .collect::<Vec<_>>(),
+        vec!["aardvark".into(), "mongoose".into(), "zebra".into()]
+    );
+
+    // Exporting the same set twice produces the same files.
+    let mut outputs = Vec::new();
+    for _ in 0..2 {
+        let destination_dir = tempfile::tempdir().unwrap();
+        export_to_path(
+            yield_progress_for_testing(),
+            ExportFormat::Stl,
+            set.clone(),
+            destination_dir.path().join("space.stl"),
+        )
+        .await
+        .unwrap();
+        let mut files: Vec<(std::ffi::OsString, Vec<u8>)> =
+            std::fs::read_dir(destination_dir.path())
+                .unwrap()
+                .map(|entry| {
+                    let entry = entry.unwrap();
+                    (entry.file_name(), std::fs::read(entry.path()).unwrap())
+                })
+                .collect();
+        files.sort();
+        outputs.push(files);
+    }
+    assert_eq!(
+        outputs[0].iter().map(|(name, _)| name).collect::<Vec<_>>(),
+        vec![
+            "space-aardvark.stl",
+            "space-mongoose.stl",
+            "space-zebra.stl"
+        ]
+    );
+    assert_eq!(outputs[0], outputs[1]);
+}
+
+#[tokio::test]
+async fn native_export_reports_labeled_progress() {
+    let [block] = make_some_blocks();
+    let mut universe = Universe::new();
+    let mut spaces = Vec::new();
+    for name in ["a", "b"] {
+        let mut space = Space::empty_positive(1, 1, 1);
+        space.set([0, 0, 0], &block).unwrap();
+        spaces.push(universe.insert(name.into(), space).unwrap());
+    }
+
+    let (progress, reports) = yield_progress_for_recording();
+
+    let destination_dir = tempfile::tempdir().unwrap();
+    export_to_path(
+        progress,
+        ExportFormat::AicJson,
+        ExportSet::from_spaces(spaces),
+        destination_dir.path().join("export.alliscubesjson"),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        reports.events(),
+        vec![
+            // The spaces were just modified, so their light must be finished first.
+            (0.25, "Computing light of 'a'".to_owned()),
+            (0.5, "Computing light of 'b'".to_owned()),
+            (0.5, "Writing 2 members".to_owned()),
+            (1.0, "Writing 2 members".to_owned()),
+        ]
+    );
+}
+
+/// When one of several files cannot be written, the error says which one.
+#[tokio::test]
+async fn export_write_error_has_member_path() {
+    let [block] = make_some_blocks();
+    let mut universe = Universe::new();
+    for name in ["aardvark", "mongoose"] {
+        let mut space = Space::empty_positive(1, 1, 1);
+        space.set([0, 0, 0], &block).unwrap();
+        universe.insert(name.into(), space).unwrap();
+    }
+
+    // Obstruct one of the files by creating a directory in its place.
+    let destination_dir = tempfile::tempdir().unwrap();
+    let blocked_path = destination_dir.path().join("space-mongoose.stl");
+    std::fs::create_dir(&blocked_path).unwrap();
+
+    let error = export_to_path(
+        yield_progress_for_testing(),
+        ExportFormat::Stl,
+        ExportSet::all_of_universe(&universe),
+        destination_dir.path().join("space.stl"),
+    )
+    .await
+    .unwrap_err();
+
+    match error {
+        ExportError::Write {
+            path: Some(ref path),
+            ..
+        } => assert_eq!(*path, blocked_path),
+        ref other => panic!("unexpected error {other:?}"),
+    }
+    assert_eq!(
+        error.to_string(),
+        format!(
+            "could not write export data to '{}'",
+            blocked_path.display()
+        )
+    );
+}
+
+#[tokio::test]
+async fn export_space_region() {
+    let [block] = make_some_blocks();
+    let mut universe = Universe::new();
+    let mut space = Space::empty(GridAab::from_lower_size([-10, -10, -10], [20, 20, 20]));
+    space.set([3, 4, 5], &block).unwrap();
+    space.set([4, 5, 6], &block).unwrap();
+    space.set([5, 5, 5], &block).unwrap(); // outside the region
+    let space: URef<Space> = universe.insert("space".into(), space).unwrap();
+
+    let destination_dir = tempfile::tempdir().unwrap();
+    let destination: PathBuf = destination_dir.path().join("region.alliscubesjson");
+    export_to_path(
+        yield_progress_for_testing(),
+        ExportFormat::AicJson,
+        ExportSet::from_space_region(space, GridAab::from_lower_size([3, 4, 5], [2, 2, 2])),
+        destination.clone(),
+    )
+    .await
+    .unwrap();
+
+    let imported = load_universe_from_file(yield_progress_for_testing(), Arc::new(destination))
+        .await
+        .unwrap();
+    let imported_space: URef<Space> = imported.get(&"space".into()).unwrap();
+    let imported_space = imported_space.read().unwrap();
+    assert_eq!(
+        imported_space.bounds(),
+        GridAab::from_lower_size([0, 0, 0], [2, 2, 2])
+    );
+    for cube in imported_space.bounds().interior_iter() {
+        let expected = if cube == [0, 0, 0].into() || cube == [1, 1, 1].into() {
+            &block
+        } else {
+            &block::AIR
+        };
+        assert_eq!(&imported_space[cube], expected, "{cube:?}");
+    }
+}
+
+#[tokio::test]
+async fn export_space_region_outside_space() {
+    let mut universe = Universe::new();
+    let space: URef<Space> = universe
+        .insert("space".into(), Space::empty_positive(4, 4, 4))
+        .unwrap();
+    let destination_dir = tempfile::tempdir().unwrap();
+
+    let error = export_to_path(
+        yield_progress_for_testing(),
+        ExportFormat::AicJson,
+        ExportSet::from_space_region(space, GridAab::from_lower_size([10, 0, 0], [2, 2, 2])),
+        destination_dir.path().join("region.alliscubesjson"),
+    )
+    .await
+    .unwrap_err();
+    assert!(
+        matches!(error, ExportError::NotRepresentable { name: Some(ref name), .. } if *name == "space".into()),
+        "{error:?}"
+    );
+}
+
+#[tokio::test]
+async fn export_character_inventory() {
+    let [def_block] = make_some_blocks();
+    let mut universe = Universe::new();
+    let block_def: URef<BlockDef> = universe
+        .insert("block".into(), BlockDef::new(def_block))
+        .unwrap();
+    let block = Block::from_primitive(Primitive::Indirect(block_def));
+    let space: URef<Space> = universe
+        .insert("world".into(), Space::empty_positive(4, 4, 4))
+        .unwrap();
+    let mut spawn = Spawn::default_for_new_space(space.read().unwrap().bounds());
+    spawn.set_inventory(vec![Tool::Block(block.clone()).into()]);
+    let character: URef<Character> = universe
+        .insert("character".into(), Character::spawn(&spawn, space))
+        .unwrap();
+
+    let destination_dir = tempfile::tempdir().unwrap();
+    let destination: PathBuf = destination_dir.path().join("loadout.alliscubesjson");
+    export_to_path(
+        yield_progress_for_testing(),
+        ExportFormat::AicJson,
+        ExportSet::from_character_inventory(character.clone()),
+        destination.clone(),
+    )
+    .await
+    .unwrap();
+
+    let imported = load_universe_from_file(yield_progress_for_testing(), Arc::new(destination))
+        .await
+        .unwrap();
+    let imported_character: URef<Character> = imported.get(&"character".into()).unwrap();
+    let imported_character = imported_character.read().unwrap();
+    // URefs compare by identity, so compare the tool by name and evaluation instead.
+    let Slot::Stack(_, Tool::Block(imported_block)) = &imported_character.inventory().slots[0]
+    else {
+        panic!("{:?}", imported_character.inventory().slots[0]);
+    };
+    match imported_block.primitive() {
+        Primitive::Indirect(def) => assert_eq!(def.name(), "block".into()),
+        p => panic!("{p:?}"),
+    }
+    assert_eq!(
+        imported_block.evaluate().unwrap(),
+        block.evaluate().unwrap()
+    );
+    assert_eq!(
+        imported_character.selected_slots(),
+        character.read().unwrap().selected_slots()
+    );
+    // The referenced block definition is included, but not the character's space.
+    assert!(imported.get::<BlockDef>(&"block".into()).is_some());
+    assert!(imported.get::<Space>(&"world".into()).is_none());
+}
+
+#[test]
+fn preflight_character_inventory_requires_native_format() {
+    let mut universe = Universe::new();
+    let space: URef<Space> = universe
+        .insert("space".into(), Space::empty_positive(1, 1, 1))
+        .unwrap();
+    let character: URef<Character> = universe
+        .insert("character".into(), Character::spawn_default(space))
+        .unwrap();
+    let set = ExportSet::from_character_inventory(character);
+
+    assert!(set.preflight(ExportFormat::AicJson).is_ok());
+    assert!(matches!(
+        set.preflight(ExportFormat::Gltf),
+        Err(ExportError::NotRepresentable { name: Some(ref name), .. }) if *name == "character".into()
+    ));
+}
+
+#[test]
+fn preflight_gltf_rejects_space() {
+    let mut universe = Universe::new();
+    let space: URef<Space> = universe
+        .insert("space".into(), Space::empty_positive(1, 1, 1))
+        .unwrap();
+    let set = ExportSet::from_spaces(vec![space]);
+    assert_eq!(set.len(), 1);
+
+    assert!(set.preflight(ExportFormat::AicJson).is_ok());
+    let error = set.preflight(ExportFormat::Gltf).unwrap_err();
+    assert!(
+        matches!(error, ExportError::NotRepresentable { name: Some(ref name), .. } if *name == "space".into()),
+        "{error:?}"
+    );
+}
+
+/// Formats which cannot crop spaces refuse to, rather than exporting them uncropped.
+#[tokio::test]
+async fn cropping_unsupported_format() {
+    let mut universe = Universe::new();
+    let space: URef<Space> = universe
+        .insert("space".into(), Space::empty_positive(1, 1, 1))
+        .unwrap();
+    let set = ExportSet::from_spaces(vec![space]).with_spaces_cropped();
+
+    set.preflight(ExportFormat::Stl).unwrap();
+    let destination_dir = tempfile::tempdir().unwrap();
+    for format in [
+        ExportFormat::AicJson,
+        ExportFormat::DotVox,
+        ExportFormat::Gltf,
+        ExportFormat::LightVolume,
+        ExportFormat::Ply,
+    ] {
+        let error = set.preflight(format).unwrap_err();
+        assert!(
+            matches!(error, ExportError::NotRepresentable { name: Some(ref name), .. } if *name == "space".into()),
+            "{format:?} {error:?}"
+        );
+        let error = export_to_path(
+            yield_progress_for_testing(),
+            format,
+            set.clone(),
+            destination_dir.path().join("cropped"),
+        )
+        .await
+        .unwrap_err();
+        assert!(
+            matches!(error, ExportError::NotRepresentable { .. }),
+            "{format:?} {error:?}"
+        );
+    }
+}
+
+#[test]
+fn preflight_gltf_accepts_blocks() {
+    let mut universe = Universe::new();
+    let block_def = universe
+        .insert("foo".into(), BlockDef::new(block::AIR))
+        .unwrap();
+    let set = ExportSet::from_block_defs(vec![block_def]);
+    assert!(!set.is_empty());
+
+    set.preflight(ExportFormat::Gltf).unwrap();
+    set.preflight(ExportFormat::Stl).unwrap();
+    assert!(matches!(
+        set.preflight(ExportFormat::DotVox),
+        Err(ExportError::NotRepresentable { .. })
+    ));
+}
+
+#[test]
+fn preflight_gltf_accepts_characters() {
+    let mut universe = Universe::new();
+    let space = universe
+        .insert("space".into(), Space::empty_positive(1, 1, 1))
+        .unwrap();
+    let character = universe
+        .insert("character".into(), Character::spawn_default(space))
+        .unwrap();
+    let set = ExportSet::from_characters(vec![character]);
+    assert_eq!(set.len(), 1);
+
+    set.preflight(ExportFormat::Gltf).unwrap();
+}
+
+#[test]
+fn preflight_dot_vox_uses_space_region() {
+    let mut universe = Universe::new();
+    let space: URef<Space> = universe
+        .insert("space".into(), Space::empty_positive(1000, 1, 1))
+        .unwrap();
+
+    assert!(matches!(
+        ExportSet::from_spaces(vec![space.clone()]).preflight(ExportFormat::DotVox),
+        Err(ExportError::NotRepresentable { .. })
+    ));
+    ExportSet::from_space_region(space, GridAab::from_lower_size([500, 0, 0], [100, 1, 1]))
+        .preflight(ExportFormat::DotVox)
+        .unwrap();
+}
+
+#[test]
+fn dependencies_are_included_and_pinned() {
+    let mut universe = Universe::new();
+    let space: URef<Space> = universe
+        .insert("space".into(), Space::empty_positive(1, 1, 1))
+        .unwrap();
+    let block_def = universe
+        .insert(
+            "block".into(),
+            BlockDef::new(
+                block::Block::builder()
+                    .voxels_ref(block::Resolution::R1, space.clone())
+                    .build(),
+            ),
+        )
+        .unwrap();
+    let mut set = ExportSet::from_block_defs(vec![block_def]);
+
+    let pins = set.pin_dependencies(ExportFormat::AicJson).unwrap();
+    assert_eq!(pins.count(), 2);
+    assert_eq!(set.contents.spaces, vec![space.clone()]);
+
+    // Deleting the space does not make it unavailable to the export.
+    UniverseTransaction::delete(space.clone())
+        .execute(&mut universe, &mut drop)
+        .unwrap();
+    space.read().unwrap();
+}
+
+#[test]
+fn dependency_already_gone() {
+    let mut universe = Universe::new();
+    let block_def = universe
+        .insert(
+            "block".into(),
+            BlockDef::new(
+                block::Block::builder()
+                    .voxels_ref(block::Resolution::R1, URef::new_gone("space".into()))
+                    .build(),
+            ),
+        )
+        .unwrap();
+
+    let error = ExportSet::from_block_defs(vec![block_def])
+        .pin_dependencies(ExportFormat::Stl)
+        .unwrap_err();
+    assert!(
+        matches!(error, ExportError::Read(RefError::Gone(ref name)) if *name == "space".into()),
+        "{error:?}"
+    );
+}
@@ -0,0 +1,94 @@
+//! Running blocking operations (file IO) without blocking the async executor.
+
+#[cfg(not(target_family = "wasm"))]
+use std::sync::{mpsc, Mutex};
+
+/// An operation for [`IO_THREAD`] to run.
+#[cfg(not(target_family = "wasm"))]
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Sender of operations to the single thread which runs all of them, in order.
+/// The thread is started when first needed and never exits.
+#[cfg(not(target_family = "wasm"))]
+static IO_THREAD: once_cell::sync::Lazy<Mutex<mpsc::Sender<Job>>> =
+    once_cell::sync::Lazy::new(|| {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        std::thread::Builder::new()
+            .name("all-is-cubes-port IO".into())
+            .spawn(move || {
+                for job in receiver {
+                    job();
+                }
+            })
+            .expect("failed to spawn IO thread");
+        Mutex::new(sender)
+    });
+
+/// Run `f`, which may block (e.g. by performing file IO), on a separate thread,
+/// and return its result when it completes.
+///
+/// This allows the async functions of this crate to write files without blocking
+/// whichever executor is polling them. All such operations share one thread, and
+/// run in the order they were requested. On platforms without threads, `f` is run
+/// immediately instead.
+///
+/// If `f` panics, the panic is propagated to the caller.
+#[cfg(not(target_family = "wasm"))]
+pub(crate) async fn unblock<R, F>(f: F) -> R
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    let (sender, receiver) = futures_channel::oneshot::channel();
+    let job: Job = Box::new(move || {
+        // If the receiver was dropped, the operation was cancelled and nobody
+        // wants the result.
+        let _ = sender.send(std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)));
+    });
+    IO_THREAD
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .send(job)
+        .expect("IO thread exited");
+    match receiver.await {
+        Ok(Ok(result)) => result,
+        Ok(Err(panic)) => std::panic::resume_unwind(panic),
+        Err(futures_channel::oneshot::Canceled) => {
+            unreachable!("IO thread exited without sending a result")
+        }
+    }
+}
+
+#[cfg(target_family = "wasm")]
+pub(crate) async fn unblock<R, F>(f: F) -> R
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    f()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn returns_result() {
+        assert_eq!(unblock(|| 1 + 1).await, 2);
+    }
+
+    #[tokio::test]
+    #[should_panic = "expected panic"]
+    async fn propagates_panic() {
+        unblock(|| panic!("expected panic")).await
+    }
+
+    /// Operations run on the same thread, so no more threads are created.
+    #[tokio::test]
+    async fn uses_one_thread() {
+        let a = unblock(|| std::thread::current().id()).await;
+        let b = unblock(|| std::thread::current().id()).await;
+        assert_eq!(a, b);
+        assert_ne!(a, std::thread::current().id());
+    }
+}
@@ -0,0 +1,249 @@
+//! Conversion of triangle meshes to voxels; see [`voxelize_triangles()`].
+
+use all_is_cubes::block::Resolution;
+use all_is_cubes::cgmath::{InnerSpace as _, Point3, Vector3};
+use all_is_cubes::math::{Cube, FreeCoordinate, GridAab, GridArray, GridCoordinate};
+
+/// How [`voxelize_triangles()`] decides which cubes are solid.
+///
+/// Both modes fill the interior of closed meshes; they differ in how they treat cubes
+/// which the surface passes through.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum VoxelizationMode {
+    /// A cube is solid if any part of it is inside the mesh or crossed by its surface.
+    /// Surfaces lying exactly on the boundary between cubes do not count, so that
+    /// geometry aligned with the voxel grid is reproduced exactly.
+    ///
+    /// This preserves features thinner than a voxel, such as walls, at the cost of
+    /// making other surfaces up to one voxel thicker than they were.
+    #[default]
+    Conservative,
+
+    /// A cube is solid only if its center is inside the mesh.
+    ///
+    /// This is faster, and keeps the proportions of large shapes, but features
+    /// thinner than a voxel may vanish entirely.
+    CenterSample,
+}
+
+/// Converts a triangle mesh to voxels at the given `resolution`.
+///
+/// The triangles' coordinates are in units of whole blocks, so that the region from 0 to 1
+/// on each axis becomes the voxels of one block, [`GridAab::for_block(resolution)`];
+/// geometry outside that region is ignored. The interior of the mesh is determined by
+/// counting surface crossings, so it should be closed; open meshes are still
+/// voxelized but their interior may be incorrect.
+///
+/// Returns whether each voxel is solid.
+pub fn voxelize_triangles(
+    triangles: &[[Point3<FreeCoordinate>; 3]],
+    resolution: Resolution,
+    mode: VoxelizationMode,
+) -> GridArray<bool> {
+    let bounds = GridAab::for_block(resolution);
+    let scale = FreeCoordinate::from(resolution);
+    let triangles: Vec<[Point3<FreeCoordinate>; 3]> = triangles
+        .iter()
+        .map(|triangle| triangle.map(|p| p * scale))
+        .collect();
+
+    let mut solid = GridArray::repeat(bounds, false);
+
+    if mode == VoxelizationMode::Conservative {
+        for triangle in &triangles {
+            // Only cubes overlapping the triangle's bounding box need to be tested.
+            let lower: [FreeCoordinate; 3] = std::array::from_fn(|i| {
+                triangle
+                    .iter()
+                    .map(|p| p[i])
+                    .fold(FreeCoordinate::INFINITY, f64::min)
+            });
+            let upper: [FreeCoordinate; 3] = std::array::from_fn(|i| {
+                triangle
+                    .iter()
+                    .map(|p| p[i])
+                    .fold(FreeCoordinate::NEG_INFINITY, f64::max)
+            });
+            let Some(candidates) = GridAab::from_lower_upper(
+                lower.map(|c| c.floor().max(-1.0) as GridCoordinate - 1),
+                upper.map(|c| c.ceil().min(scale + 1.0) as GridCoordinate + 1),
+            )
+            .intersection(bounds) else {
+                continue;
+            };
+            for cube in candidates.interior_iter() {
+                if triangle_crosses_cube(triangle, cube) {
+                    solid[cube] = true;
+                }
+            }
+        }
+    }
+
+    for cube in bounds.interior_iter() {
+        if !solid[cube] && point_is_inside(&triangles, cube.midpoint()) {
+            solid[cube] = true;
+        }
+    }
+
+    solid
+}
+
+/// Returns whether the triangle intersects the interior of the cube.
+///
+/// This is the separating axis test: the two shapes do not intersect exactly when
+/// there is an axis along which their projections do not overlap, and the only axes
+/// which need to be checked are the cube's face normals, the triangle's normal, and the
+/// cross products of the cube's and triangle's edges.
+fn triangle_crosses_cube(triangle: &[Point3<FreeCoordinate>; 3], cube: Cube) -> bool {
+    let center = cube.midpoint();
+    let vertices = triangle.map(|p| p - center);
+    let edges = [
+        vertices[1] - vertices[0],
+        vertices[2] - vertices[1],
+        vertices[0] - vertices[2],
+    ];
+    let cube_axes = [Vector3::unit_x(), Vector3::unit_y(), Vector3::unit_z()];
+
+    let edge_axes = cube_axes
+        .into_iter()
+        .flat_map(|axis| edges.map(|edge| axis.cross(edge)));
+    cube_axes
+        .into_iter()
+        .chain([edges[0].cross(edges[1])])
+        .chain(edge_axes)
+        // Parallel edges produce zero vectors, which separate nothing.
+        .filter(|axis| axis.magnitude2() > 0.0)
+        .all(|axis| {
+            let projections = vertices.map(|v| v.dot(axis));
+            let min = projections
+                .into_iter()
+                .fold(FreeCoordinate::INFINITY, f64::min);
+            let max = projections
+                .into_iter()
+                .fold(FreeCoordinate::NEG_INFINITY, f64::max);
+            let radius = 0.5 * (axis.x.abs() + axis.y.abs() + axis.z.abs());
+            min < radius && max > -radius
+        })
+}
+
+/// Returns whether `point` is inside the closed surface formed by `triangles`, by
+/// counting how many of them a ray from the point crosses.
+fn point_is_inside(
+    triangles: &[[Point3<FreeCoordinate>; 3]],
+    point: Point3<FreeCoordinate>,
+) -> bool {
+    // A direction unlikely to be exactly parallel to, or pass exactly through the edges
+    // of, the axis-aligned geometry which is most common.
+    let direction = Vector3::new(1.0, 0.000_123_4, 0.000_056_7);
+    let crossings = triangles
+        .iter()
+        .filter(|triangle| ray_hits_triangle(point, direction, triangle))
+        .count();
+    crossings % 2 == 1
+}
+
+/// Möller–Trumbore ray-triangle intersection, counting only hits in front of `origin`.
+fn ray_hits_triangle(
+    origin: Point3<FreeCoordinate>,
+    direction: Vector3<FreeCoordinate>,
+    [v0, v1, v2]: &[Point3<FreeCoordinate>; 3],
+) -> bool {
+    let edge1 = v1 - v0;
+    let edge2 = v2 - v0;
+    let p = direction.cross(edge2);
+    let determinant = edge1.dot(p);
+    if determinant.abs() < 1e-12 {
+        // Ray is parallel to the triangle.
+        return false;
+    }
+    let to_origin = origin - v0;
+    let u = to_origin.dot(p) / determinant;
+    if !(0.0..=1.0).contains(&u) {
+        return false;
+    }
+    let q = to_origin.cross(edge1);
+    let v = direction.dot(q) / determinant;
+    if v < 0.0 || u + v > 1.0 {
+        return false;
+    }
+    edge2.dot(q) / determinant > 0.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Triangles forming the surface of the box from `lower` to `upper`.
+    fn box_triangles(
+        lower: [FreeCoordinate; 3],
+        upper: [FreeCoordinate; 3],
+    ) -> Vec<[Point3<FreeCoordinate>; 3]> {
+        let corner = |x: usize, y: usize, z: usize| {
+            Point3::new(
+                [lower[0], upper[0]][x],
+                [lower[1], upper[1]][y],
+                [lower[2], upper[2]][z],
+            )
+        };
+        let mut triangles = Vec::new();
+        for axis in 0..3 {
+            for side in 0..2 {
+                // Corners of the face perpendicular to `axis` on `side`.
+                let [a, b, c, d] = [(0, 0), (1, 0), (1, 1), (0, 1)].map(|(s, t)| {
+                    let mut index = [0; 3];
+                    index[axis] = side;
+                    index[(axis + 1) % 3] = s;
+                    index[(axis + 2) % 3] = t;
+                    corner(index[0], index[1], index[2])
+                });
+                triangles.push([a, b, c]);
+                triangles.push([a, c, d]);
+            }
+        }
+        triangles
+    }
+
+    fn solid_cubes(voxels: &GridArray<bool>) -> Vec<Cube> {
+        voxels
+            .bounds()
+            .interior_iter()
+            .filter(|&cube| voxels[cube])
+            .collect()
+    }
+
+    #[test]
+    fn large_box_interior_is_filled() {
+        let triangles = box_triangles([0.25, 0.25, 0.25], [0.75, 0.75, 0.75]);
+        for mode in [
+            VoxelizationMode::Conservative,
+            VoxelizationMode::CenterSample,
+        ] {
+            let voxels = voxelize_triangles(&triangles, Resolution::R8, mode);
+            let expected: Vec<Cube> = GridAab::from_lower_upper([2, 2, 2], [6, 6, 6])
+                .interior_iter()
+                .collect();
+            assert_eq!(solid_cubes(&voxels), expected, "{mode:?}");
+        }
+    }
+
+    /// A plane thinner than one voxel, lying between two layers of voxel centers.
+    #[test]
+    fn thin_plane() {
+        let triangles = box_triangles([0.0, 0.4, 0.0], [1.0, 0.6, 1.0]);
+
+        let conservative =
+            voxelize_triangles(&triangles, Resolution::R4, VoxelizationMode::Conservative);
+        for cube in conservative.bounds().interior_iter() {
+            assert_eq!(
+                conservative[cube],
+                cube.y == 1 || cube.y == 2,
+                "conservative {cube:?}"
+            );
+        }
+
+        let center_sample =
+            voxelize_triangles(&triangles, Resolution::R4, VoxelizationMode::CenterSample);
+        assert_eq!(solid_cubes(&center_sample), vec![]);
+    }
+}
@@ -67,7 +67,7 @@ pub struct Session<I> {
     /// Last cursor raycast result.
     /// TODO: This needs to handle clicking on the HUD and thus explicitly point into
     /// one of two different spaces.
-    cursor_result: Option<Cursor>,
+    cursor_result: ListenableCellWithLocal<Option<Cursor>>,
 
     last_step_info: UniverseStepInfo,
 
@@ -182,7 +182,8 @@ impl<I: time::Instant> Session<I> {
         &self.graphics_options
     }
 
-    /// Create [`StandardCameras`] which may be used in rendering a view of this session.
+    /// Create [`StandardCameras`] which may be used in rendering a view of this session,
+    /// including its [cursor](Self::cursor).
     pub fn create_cameras(&self, viewport_source: ListenableSource<Viewport>) -> StandardCameras {
         StandardCameras::new(
             self.graphics_options(),
@@ -190,6 +191,7 @@ impl<I: time::Instant> Session<I> {
             self.character(),
             self.ui_view(),
         )
+        .with_cursor(self.cursor())
     }
 
     /// Listen for [`Fluff`] events from this session. Fluff constitutes short-duration
@@ -311,15 +313,25 @@ impl<I: time::Instant> Session<I> {
     /// We'd like to not have too much dependencies on the rendering, but also
     /// not obligate each platform/renderer layer to have too much boilerplate.
     pub fn update_cursor(&mut self, cameras: &StandardCameras) {
-        self.cursor_result = self
+        let cursor = self
             .input_processor
             .cursor_ndc_position()
             .and_then(|ndc_pos| cameras.project_cursor(ndc_pos));
+        // Avoid notifying listeners every frame when nothing changed.
+        if cursor != *self.cursor_result.borrow() {
+            self.cursor_result.set(cursor);
+        }
     }
 
     /// Returns the [`Cursor`] computed by the last call to [`Session::update_cursor()`].
     pub fn cursor_result(&self) -> Option<&Cursor> {
-        self.cursor_result.as_ref()
+        self.cursor_result.borrow().as_ref()
+    }
+
+    /// Returns a source for the [`Cursor`] computed by [`Session::update_cursor()`],
+    /// suitable for [`StandardCameras::with_cursor()`].
+    pub fn cursor(&self) -> ListenableSource<Option<Cursor>> {
+        self.cursor_result.as_source()
     }
 
     /// Returns the suggested mouse-pointer/cursor appearance for the current [`Cursor`]
@@ -328,7 +340,7 @@ impl<I: time::Instant> Session<I> {
     /// Note that this does not report any information about whether the pointer should be
     /// *hidden*. (TODO: Should we change that?)
     pub fn cursor_icon(&self) -> &CursorIcon {
-        match self.cursor_result {
+        match self.cursor_result() {
             // TODO: add more distinctions.
             // * Non-clickable UI should get normal arrow cursor.
             // * Maybe a lack-of-world should be indicated with a disabled cursor.
@@ -376,27 +388,27 @@ impl<I: time::Instant> Session<I> {
     /// Implementation of click interpretation logic, called by [`Self::click`].
     /// TODO: This function needs tests.
     fn click_impl(&mut self, button: usize) -> Result<(), ToolError> {
-        let cursor_space = self.cursor_result.as_ref().map(|c| c.space());
+        let cursor_space = self.cursor_result().map(|c| c.space());
         // TODO: A better condition for this would be "is one of the spaces in the UI universe"
         if cursor_space == Option::as_ref(&self.ui_view().get().space) {
             // TODO: refactor away unwrap
             self.ui
                 .as_mut()
                 .unwrap()
-                .click(button, self.cursor_result.clone())
+                .click(button, self.cursor_result.borrow().clone())
         } else {
             // Otherwise, it's a click inside the game world (even if the cursor hit nothing at all).
             // Character::click will validate against being a click in the wrong space.
             if let Some(character_ref) = self.game_character.borrow() {
                 let transaction =
-                    Character::click(character_ref.clone(), self.cursor_result.as_ref(), button)?;
+                    Character::click(character_ref.clone(), self.cursor_result(), button)?;
                 transaction
                     .execute(self.universe_mut(), &mut transaction::no_outputs)
                     .map_err(|e| ToolError::Internal(e.to_string()))?;
 
                 // Spend a little time doing light updates, to ensure that changes right in front of
                 // the player are clean (and not flashes of blackness).
-                if let Some(space_ref) = self.cursor_result.as_ref().map(Cursor::space) {
+                if let Some(space_ref) = self.cursor_result().map(Cursor::space) {
                     // TODO: make this a kind of SpaceTransaction, eliminating this try_modify.
                     let _ = space_ref.try_modify(|space| {
                         space.update_lighting_from_queue::<I>(Some(Duration::from_millis(1)));
@@ -504,7 +516,7 @@ impl<I: time::Instant> SessionBuilder<I> {
             paused,
             control_channel: control_recv,
             control_channel_sender: control_send,
-            cursor_result: None,
+            cursor_result: ListenableCellWithLocal::new(None),
             last_step_info: UniverseStepInfo::default(),
             tick_counter_for_logging: 0,
         }
@@ -621,6 +633,9 @@ pub enum CursorIcon {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use all_is_cubes::block::Block;
+    use all_is_cubes::cgmath::Point3;
+    use all_is_cubes::math::Rgba;
     use all_is_cubes::space::Space;
     use all_is_cubes::universe::{Name, Universe};
     use futures_channel::oneshot;
@@ -658,4 +673,27 @@ mod tests {
         // Verify cleanup (that the next step can succeed).
         session.maybe_step_universe();
     }
+
+    #[tokio::test]
+    async fn cameras_follow_cursor() {
+        let mut session = Session::<std::time::Instant>::builder().build().await;
+        let mut universe = Universe::new();
+        let mut space = Space::empty_positive(1, 1, 1);
+        space.set([0, 0, 0], Block::from(Rgba::WHITE)).unwrap();
+        let space = universe.insert_anonymous(space);
+        let mut character = Character::spawn_default(space);
+        character.body.position = Point3::new(0.5, 0.5, 3.0);
+        universe.insert("character".into(), character).unwrap();
+        session.set_universe(universe);
+
+        let mut cameras = session.create_cameras(ListenableSource::constant(Viewport::ARBITRARY));
+        assert_eq!(cameras.cursor(), None);
+
+        session.update_cursor(&cameras);
+        let cursor = session.cursor_result().cloned();
+        assert!(cursor.is_some());
+        assert_eq!(cameras.cursor(), None, "should not update until update()");
+        cameras.update();
+        assert_eq!(cameras.cursor(), cursor.as_ref());
+    }
 }
@@ -1,7 +1,9 @@
 use std::sync::mpsc::TryRecvError;
 use std::sync::{mpsc, Arc, Mutex};
 
-use all_is_cubes::camera::{FogOption, GraphicsOptions, UiViewState, ViewTransform, Viewport};
+use all_is_cubes::camera::{
+    FogOption, GraphicsOptions, ProjectionOption, UiViewState, ViewTransform, Viewport,
+};
 use all_is_cubes::cgmath::{Angle as _, Decomposed, Deg, One, Transform, Vector3};
 use all_is_cubes::character::{Character, Cursor};
 use all_is_cubes::inv::{EphemeralOpaque, Tool, ToolError, ToolInput};
@@ -247,6 +249,8 @@ impl Vui {
     fn graphics_options(mut options: GraphicsOptions) -> GraphicsOptions {
         // Set FOV to give a predictable, not-too-wide-angle perspective.
         options.fov_y = NotNan::from(30);
+        // The layout depends on the perspective projection.
+        options.projection = ProjectionOption::Perspective;
 
         // Disable fog for maximum clarity and because we shouldn't have any far clipping to hide.
         options.fog = FogOption::None;
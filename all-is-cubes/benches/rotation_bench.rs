@@ -0,0 +1,25 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+
+use all_is_cubes::math::GridRotation;
+
+pub fn rotation_compose(c: &mut Criterion) {
+    let mut group = c.benchmark_group("rotation");
+
+    // A long chain cycling through every rotation, as might be produced by
+    // repeatedly composing rotation modifiers.
+    let chain: Vec<GridRotation> = GridRotation::ALL.into_iter().cycle().take(1000).collect();
+    group.throughput(Throughput::Elements(chain.len() as u64));
+
+    group.bench_function("compose chain", |b| {
+        b.iter(|| {
+            black_box(&chain)
+                .iter()
+                .fold(GridRotation::IDENTITY, |acc, &r| acc * r)
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, rotation_compose);
+criterion_main!(benches);
@@ -294,6 +294,10 @@ impl Block {
     /// let double = rotated.clone().rotate(clockwise);
     /// assert_eq!(double.modifiers(), &[Modifier::Rotate(clockwise * clockwise)]);
     ///
+    /// // Rotations which cancel out are removed
+    /// let undone = rotated.clone().rotate(clockwise.inverse());
+    /// assert_eq!(undone, block);
+    ///
     /// // Atoms and AIR are never rotated
     /// let atom = Block::from(Rgba::WHITE);
     /// assert_eq!(atom.clone().rotate(clockwise), atom);
@@ -310,17 +314,67 @@ impl Block {
                 self
             }
             _ => {
-                let parts = self.make_parts_mut();
-                match parts.modifiers.last_mut() {
-                    // TODO: If the combined rotation is the identity, discard the modifier
-                    Some(Modifier::Rotate(existing_rotation)) => {
-                        *existing_rotation = rotation * *existing_rotation;
+                self.modifiers_mut().push(Modifier::Rotate(rotation));
+                self.simplify_modifiers();
+                self
+            }
+        }
+    }
+
+    /// Replaces this block's modifiers with a shorter list that has the same effect,
+    /// if possible. Specifically, this:
+    ///
+    /// * combines adjacent [`Modifier::Rotate`]s into one,
+    /// * removes [`Modifier::Rotate`]s of [`GridRotation::IDENTITY`], and
+    /// * combines adjacent [`Modifier::Quote`]s into one.
+    ///
+    /// The result of evaluating the block is not changed.
+    ///
+    /// ```
+    /// use all_is_cubes::block::{Block, Modifier};
+    /// use all_is_cubes::content::make_some_voxel_blocks;
+    /// use all_is_cubes::math::GridRotation;
+    /// use all_is_cubes::universe::Universe;
+    ///
+    /// let mut universe = Universe::new();
+    /// let [mut block] = make_some_voxel_blocks(&mut universe);
+    /// block.modifiers_mut().extend([
+    ///     Modifier::Rotate(GridRotation::CLOCKWISE),
+    ///     Modifier::Rotate(GridRotation::CLOCKWISE),
+    /// ]);
+    ///
+    /// block.simplify_modifiers();
+    /// assert_eq!(
+    ///     block.modifiers(),
+    ///     &[Modifier::Rotate(GridRotation::CLOCKWISE * GridRotation::CLOCKWISE)],
+    /// );
+    /// ```
+    pub fn simplify_modifiers(&mut self) {
+        let modifiers = self.modifiers();
+        let mut simplified: Vec<Modifier> = Vec::with_capacity(modifiers.len());
+        for modifier in modifiers {
+            match (simplified.last_mut(), modifier) {
+                (_, &Modifier::Rotate(GridRotation::IDENTITY)) => {}
+                (Some(Modifier::Rotate(previous)), &Modifier::Rotate(rotation)) => {
+                    // `previous` is applied first, so it is on the right.
+                    *previous = rotation * *previous;
+                    if *previous == GridRotation::IDENTITY {
+                        simplified.pop();
                     }
-                    None | Some(_) => parts.modifiers.push(Modifier::Rotate(rotation)),
                 }
-                self
+                (Some(Modifier::Quote(previous)), Modifier::Quote(quote)) => {
+                    // Quoting is idempotent except for the choice of suppressing emission.
+                    previous.suppress_ambient |= quote.suppress_ambient;
+                }
+                _ => simplified.push(modifier.clone()),
             }
         }
+
+        // Every simplification removes a modifier, so if the length is unchanged,
+        // nothing changed and we can avoid unsharing the block data.
+        if simplified.len() != modifiers.len() {
+            *self.modifiers_mut() = simplified;
+        }
     }
 
     /// Standardizes any characteristics of this block which may be presumed to be
@@ -407,7 +461,8 @@ impl Block {
     /// sense that none of the methods on [`Block`] will cause this listener to fire.
     /// Rather, it listens for changes in by-reference-to-interior-mutable-data sources
     /// such as the [`Space`] referred to by a [`Primitive::Recur`] or the [`BlockDef`]
-    /// referred to by a [`Primitive::Indirect`].
+    /// referred to by a [`Primitive::Indirect`], including those within other blocks
+    /// that modifiers such as [`Modifier::Composite`] refer to.
     ///
     /// # Errors
     ///
@@ -4,7 +4,7 @@ use std::borrow::Cow;
 use std::fmt;
 
 use crate::drawing::VoxelBrush;
-use crate::math::Face6;
+use crate::math::{Face6, GridRotation};
 
 #[cfg(doc)]
 use crate::{
@@ -202,6 +202,39 @@ pub enum RotationPlacementRule {
     },
 }
 
+impl RotationPlacementRule {
+    /// Returns the rotation which should be applied to a block with this rule when it is
+    /// placed so as to be attached to a surface in the direction `attach_to` from the
+    /// block's cube (that is, `attach_to` is the face of the placed block's cube which
+    /// touches the surface).
+    ///
+    /// ```
+    /// use all_is_cubes::block::RotationPlacementRule;
+    /// use all_is_cubes::math::{Face6, GridRotation};
+    ///
+    /// let rule = RotationPlacementRule::Attach { by: Face6::NY };
+    /// // Placing on a floor requires no rotation.
+    /// assert_eq!(rule.rotation_for_placement(Face6::NY), GridRotation::IDENTITY);
+    /// // Placing on a ceiling turns the block upside down.
+    /// assert_eq!(
+    ///     rule.rotation_for_placement(Face6::PY).transform(Face6::NY),
+    ///     Face6::PY,
+    /// );
+    /// ```
+    pub fn rotation_for_placement(self, attach_to: Face6) -> GridRotation {
+        match self {
+            RotationPlacementRule::Never => GridRotation::IDENTITY,
+            RotationPlacementRule::Attach { by: attached_face } => {
+                // TODO: RotationPlacementRule should control the "up" axis choices
+                GridRotation::from_to(attached_face, attach_to, Face6::PY)
+                    .or_else(|| GridRotation::from_to(attached_face, attach_to, Face6::PX))
+                    .or_else(|| GridRotation::from_to(attached_face, attach_to, Face6::PZ))
+                    .unwrap_or(GridRotation::IDENTITY)
+            }
+        }
+    }
+}
+
 /// Specifies how a [`Block`] might change in the very near future, for the benefit
 /// of rendering algorithms. Does not currently describe non-visual aspects of a block.
 ///
@@ -317,6 +350,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn rotation_for_placement_never() {
+        for face in Face6::ALL {
+            assert_eq!(
+                RotationPlacementRule::Never.rotation_for_placement(face),
+                GridRotation::IDENTITY
+            );
+        }
+    }
+
+    #[test]
+    fn rotation_for_placement_attach() {
+        for by in [Face6::NY, Face6::NZ, Face6::PX] {
+            let rule = RotationPlacementRule::Attach { by };
+            for attach_to in Face6::ALL {
+                let rotation = rule.rotation_for_placement(attach_to);
+                assert_eq!(
+                    rotation.transform(by),
+                    attach_to,
+                    "by {by:?} attach_to {attach_to:?}"
+                );
+                assert!(!rotation.is_reflection(), "{rotation:?}");
+                if attach_to == by {
+                    assert_eq!(rotation, GridRotation::IDENTITY);
+                }
+                if by.axis_number() != 1 && attach_to.axis_number() != 1 {
+                    // Horizontal attachment of a horizontally attaching block keeps it upright.
+                    assert_eq!(rotation.transform(Face6::PY), Face6::PY, "{rotation:?}");
+                }
+            }
+        }
+    }
+
     #[test]
     fn debug() {
         let default = BlockAttributes::default;
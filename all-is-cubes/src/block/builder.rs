@@ -110,6 +110,12 @@ impl<C> BlockBuilder<C> {
         self
     }
 
+    /// Adds a [`Modifier::Quote`] to the end of the list of modifiers for the block,
+    /// as constructed by [`Modifier::quote()`].
+    pub fn quoted(self, suppress_ambient: bool) -> Self {
+        self.modifier(Modifier::quote(suppress_ambient))
+    }
+
     /// Sets the color value for building a [`Primitive::Atom`].
     ///
     /// This will replace any previous color **or voxels.**
@@ -1,21 +1,24 @@
 //! [`EvaluatedBlock`] and [`Evoxel`].
 
+use std::collections::HashMap;
 use std::fmt;
 
-use cgmath::{Vector3, Vector4, Zero as _};
+use cgmath::{InnerSpace as _, Point3, Vector3, Vector4, Zero as _};
 
 use crate::block::{
-    self, BlockAttributes, BlockCollision,
+    self, Atom, Block, BlockAttributes, BlockCollision,
     Resolution::{self, R1},
 };
 use crate::content::palette;
-use crate::math::{Cube, Face6, FaceMap, GridAab, GridArray, OpacityCategory, Rgb, Rgba};
+use crate::math::{
+    Aab, Cube, Face6, FaceMap, FreeCoordinate, GridAab, GridArray, OpacityCategory, Rgb, Rgba,
+};
 use crate::raytracer;
 use crate::universe::RefError;
 
 // Things mentioned in doc comments only
 #[cfg(doc)]
-use super::{Block, Primitive, URef, AIR};
+use super::{Primitive, URef, AIR};
 
 /// A snapshotted form of [`Block`] which contains all information needed for rendering
 /// and physics, and does not require dereferencing [`URef`]s or unbounded computation.
@@ -304,6 +307,214 @@ impl EvaluatedBlock {
         self.voxels.bounds()
     }
 
+    /// Counts how many voxels of this block have each distinct color.
+    ///
+    /// The result is sorted from most to least frequent; colors which are equally frequent
+    /// are in the order they are first found, scanning the block's voxels in
+    /// [`GridAab::interior_iter()`] order. Voxels which are within the block but not
+    /// present in [`Self::voxels`] are counted as [`Rgba::TRANSPARENT`].
+    ///
+    /// This counts all voxels, including interior ones which are not visible; it is
+    /// intended for purposes such as palette building rather than rendering.
+    /// For the single color that best represents the block's appearance, use
+    /// [`Self::color`] instead.
+    ///
+    /// ```
+    /// use all_is_cubes::block::Block;
+    /// use all_is_cubes::math::Rgba;
+    ///
+    /// let ev = Block::from(Rgba::WHITE).evaluate().unwrap();
+    /// assert_eq!(ev.color_histogram(), vec![(Rgba::WHITE, 1)]);
+    /// ```
+    pub fn color_histogram(&self) -> Vec<(Rgba, usize)> {
+        if let Evoxels::One(voxel) = self.voxels {
+            return vec![(voxel.color, 1)];
+        }
+
+        // Vec preserves first-found order for ties; the map indexes into it.
+        let mut counts: Vec<(Rgba, usize)> = Vec::new();
+        let mut indices: HashMap<Rgba, usize> = HashMap::new();
+        for cube in GridAab::for_block(self.resolution()).interior_iter() {
+            let color = self.voxels.get(cube).unwrap_or(Evoxel::AIR).color;
+            let index = *indices.entry(color).or_insert_with(|| {
+                counts.push((color, 0));
+                counts.len() - 1
+            });
+            counts[index].1 += 1;
+        }
+        // Stable sort keeps the first-found order among equal counts.
+        counts.sort_by(|(_, a), (_, b)| b.cmp(a));
+        counts
+    }
+
+    /// Returns the most common voxel color of this block, as determined by
+    /// [`Self::color_histogram()`].
+    pub fn dominant_color(&self) -> Rgba {
+        self.color_histogram()
+            .first()
+            .map_or(Rgba::TRANSPARENT, |&(color, _)| color)
+    }
+
+    /// Returns an atom [`Block`] which approximates this block's appearance as a single
+    /// uniform cube, for uses such as distant level of detail and tiny thumbnails.
+    ///
+    /// * Its color is the average of the colors of all voxels, weighted by their alpha,
+    ///   and its alpha is the average alpha of all voxels (counting voxels outside of
+    ///   [`Self::voxels`] as transparent), so that it reflects how much of the block is
+    ///   covered.
+    /// * Its light emission is [`Self::light_emission`].
+    /// * Its collision is [`Self::uniform_collision`] if there is one, and otherwise
+    ///   [`BlockCollision::Hard`].
+    /// * Its attributes are copied from this block.
+    ///
+    /// If this block consists of a single voxel (such as if it was evaluated from an atom
+    /// block), then the result is equal to that atom.
+    ///
+    /// ```
+    /// use all_is_cubes::block::Block;
+    /// use all_is_cubes::math::Rgba;
+    ///
+    /// let block = Block::from(Rgba::new(0.0, 0.5, 1.0, 1.0));
+    /// assert_eq!(block.evaluate().unwrap().to_atom_approximation(), block);
+    /// ```
+    pub fn to_atom_approximation(&self) -> Block {
+        let (color, emission, collision) = match self.voxels.single_voxel() {
+            Some(voxel) => (voxel.color, voxel.emission, voxel.collision),
+            None => {
+                let mut weighted_rgb_sum: Vector3<f32> = Vector3::zero();
+                let mut alpha_sum: f32 = 0.0;
+                let mut count: usize = 0;
+                for (color, n) in self.color_histogram() {
+                    let alpha = color.alpha().into_inner() * n as f32;
+                    weighted_rgb_sum += Vector3::from(color.to_rgb()) * alpha;
+                    alpha_sum += alpha;
+                    count += n;
+                }
+                let rgb = if alpha_sum > 0.0 {
+                    weighted_rgb_sum / alpha_sum
+                } else {
+                    Vector3::zero()
+                };
+                (
+                    Rgba::try_from(rgb.extend(alpha_sum / count.max(1) as f32))
+                        .expect("block color approximation produced NaN"),
+                    self.light_emission,
+                    self.uniform_collision.unwrap_or(BlockCollision::Hard),
+                )
+            }
+        };
+        Block::from(Atom {
+            attributes: self.attributes.clone(),
+            color,
+            emission,
+            collision,
+        })
+    }
+
+    /// Returns whether `point_in_block` is inside one of this block's solid voxels.
+    ///
+    /// The point is expressed in the block's voxel coordinates: the block occupies the
+    /// region from `(0, 0, 0)` to `(r, r, r)`, where `r` is [`Self::resolution()`], so one
+    /// unit is one voxel. (For atom blocks, the resolution is 1 and the single voxel is the
+    /// whole block.) Points outside that region are never occupied.
+    ///
+    /// A voxel is considered solid if [`Self::voxel_opacity_mask`] says it is not
+    /// [`OpacityCategory::Invisible`]; [`BlockCollision`] is not consulted.
+    ///
+    /// ```
+    /// use all_is_cubes::block::{AIR, Block};
+    /// use all_is_cubes::cgmath::Point3;
+    /// use all_is_cubes::math::Rgba;
+    ///
+    /// let point = Point3::new(0.5, 0.5, 0.5);
+    /// assert!(Block::from(Rgba::WHITE).evaluate().unwrap().occupancy_at(point));
+    /// assert!(!AIR.evaluate().unwrap().occupancy_at(point));
+    /// ```
+    pub fn occupancy_at(&self, point_in_block: Point3<FreeCoordinate>) -> bool {
+        match Cube::containing(point_in_block) {
+            Some(cube) => self.voxel_is_solid(cube),
+            None => false,
+        }
+    }
+
+    /// Returns an approximate signed distance from `point_in_block` to the surface of this
+    /// block's solid voxels: positive outside them and negative inside them.
+    ///
+    /// Coordinates and distances are in voxels, as described for [`Self::occupancy_at()`];
+    /// the space outside the block counts as empty. Distances are measured to the boxes of
+    /// individual voxels, so the result is exact for points outside the solid voxels but may
+    /// overestimate the depth of points inside them. If the block has no solid voxels, the
+    /// result is [`f32::INFINITY`].
+    ///
+    /// This examines every voxel of the block, so it is not cheap for high resolutions.
+    pub fn signed_distance_to_solid(&self, point_in_block: Point3<FreeCoordinate>) -> f32 {
+        let Some(mask) = &self.voxel_opacity_mask else {
+            return f32::INFINITY;
+        };
+        let nearest = |cubes: &mut dyn Iterator<Item = Cube>| -> FreeCoordinate {
+            cubes
+                .map(|cube| distance_to_aab(point_in_block, cube.aab()))
+                .fold(FreeCoordinate::INFINITY, FreeCoordinate::min)
+        };
+
+        if self.occupancy_at(point_in_block) {
+            let block_bounds = GridAab::for_block(self.resolution());
+            let to_block_surface = distance_to_aab_surface(point_in_block, block_bounds.into());
+            let to_empty_voxel = nearest(
+                &mut block_bounds
+                    .interior_iter()
+                    .filter(|&cube| !self.voxel_is_solid(cube)),
+            );
+            -(to_block_surface.min(to_empty_voxel) as f32)
+        } else {
+            nearest(
+                &mut mask
+                    .bounds()
+                    .interior_iter()
+                    .filter(|&cube| mask[cube] != OpacityCategory::Invisible),
+            ) as f32
+        }
+    }
+
+    /// Returns the smallest box containing all of this block's voxels which are not
+    /// fully transparent, or [`None`] if there are none.
+    ///
+    /// The box is in the block's voxel coordinates, as described for
+    /// [`Self::occupancy_at()`]. This is computed from [`Self::voxel_opacity_mask`], so it
+    /// examines every voxel of the block.
+    ///
+    /// ```
+    /// use all_is_cubes::block::{AIR, Block};
+    /// use all_is_cubes::math::{GridAab, Rgba};
+    ///
+    /// assert_eq!(
+    ///     Block::from(Rgba::WHITE).evaluate().unwrap().occupied_bounds(),
+    ///     Some(GridAab::ORIGIN_CUBE),
+    /// );
+    /// assert_eq!(AIR.evaluate().unwrap().occupied_bounds(), None);
+    /// ```
+    pub fn occupied_bounds(&self) -> Option<GridAab> {
+        let mask = self.voxel_opacity_mask.as_ref()?;
+        mask.bounds()
+            .interior_iter()
+            .filter(|&cube| mask[cube] != OpacityCategory::Invisible)
+            .map(Cube::grid_aab)
+            .reduce(|a, b| {
+                a.union(b)
+                    .expect("union of boxes within the mask cannot overflow")
+            })
+    }
+
+    /// Returns whether the voxel at `cube` is solid, as defined by [`Self::occupancy_at()`].
+    fn voxel_is_solid(&self, cube: Cube) -> bool {
+        matches!(
+            self.voxel_opacity_mask
+                .as_ref()
+                .and_then(|mask| mask.get(cube)),
+            Some(OpacityCategory::Partial | OpacityCategory::Opaque)
+        )
+    }
+
     // --- Other ---
 
     #[doc(hidden)]
@@ -616,10 +827,35 @@ impl MinEval {
     }
 }
 
+/// Euclidean distance from `point` to the nearest point of `aab`; zero if it is inside.
+fn distance_to_aab(point: Point3<FreeCoordinate>, aab: Aab) -> FreeCoordinate {
+    let lower = aab.lower_bounds_p();
+    let upper = aab.upper_bounds_p();
+    Vector3::new(
+        (lower.x - point.x).max(point.x - upper.x).max(0.0),
+        (lower.y - point.y).max(point.y - upper.y).max(0.0),
+        (lower.z - point.z).max(point.z - upper.z).max(0.0),
+    )
+    .magnitude()
+}
+
+/// Distance from `point`, which is inside `aab`, to the nearest face of `aab`.
+fn distance_to_aab_surface(point: Point3<FreeCoordinate>, aab: Aab) -> FreeCoordinate {
+    let from_lower = point - aab.lower_bounds_p();
+    let from_upper = aab.upper_bounds_p() - point;
+    from_lower
+        .x
+        .min(from_lower.y)
+        .min(from_lower.z)
+        .min(from_upper.x)
+        .min(from_upper.y)
+        .min(from_upper.z)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::block::{AnimationHint, Block, Resolution, Resolution::R2, AIR};
+    use crate::block::{AnimationHint, Resolution, Resolution::*, AIR};
     use pretty_assertions::assert_eq;
 
     #[test]
@@ -718,4 +954,171 @@ mod tests {
 
         assert_eq!(ev.color, outer_color);
     }
+
+    #[test]
+    fn color_histogram_checker() {
+        let resolution = Resolution::R4;
+        let color_1 = Rgba::new(1.0, 0.0, 0.0, 1.0);
+        let color_2 = Rgba::new(0.0, 0.0, 1.0, 1.0);
+        let voxels = Evoxels::Many(
+            resolution,
+            GridArray::from_fn(GridAab::for_block(resolution), |cube| {
+                Evoxel::from_color(if (cube.x + cube.y + cube.z).rem_euclid(2) == 0 {
+                    color_1
+                } else {
+                    color_2
+                })
+            }),
+        );
+        let ev = EvaluatedBlock::from_voxels(BlockAttributes::default(), voxels);
+
+        // Equal counts, so the order is the order of first appearance.
+        assert_eq!(ev.color_histogram(), vec![(color_1, 32), (color_2, 32)]);
+        assert_eq!(ev.dominant_color(), color_1);
+    }
+
+    #[test]
+    fn color_histogram_counts_missing_voxels_as_air() {
+        let color = Rgba::new(0.0, 1.0, 0.0, 1.0);
+        let voxels = Evoxels::Many(
+            R2,
+            GridArray::from_fn(GridAab::from_lower_size([0, 0, 0], [2, 2, 1]), |_| {
+                Evoxel::from_color(color)
+            }),
+        );
+        let ev = EvaluatedBlock::from_voxels(BlockAttributes::default(), voxels);
+
+        assert_eq!(
+            ev.color_histogram(),
+            vec![(color, 4), (Rgba::TRANSPARENT, 4)]
+        );
+    }
+
+    #[test]
+    fn atom_approximation_of_black_and_white() {
+        let voxels = Evoxels::Many(
+            R2,
+            GridArray::from_fn(GridAab::for_block(R2), |cube| {
+                Evoxel::from_color(if cube.x == 0 {
+                    Rgba::BLACK
+                } else {
+                    Rgba::WHITE
+                })
+            }),
+        );
+        let ev = EvaluatedBlock::from_voxels(BlockAttributes::default(), voxels);
+
+        assert_eq!(
+            ev.to_atom_approximation(),
+            Block::from(Rgba::new(0.5, 0.5, 0.5, 1.0))
+        );
+    }
+
+    #[test]
+    fn atom_approximation_of_partial_coverage() {
+        let approx = half_filled_block().to_atom_approximation();
+
+        // Transparent voxels contribute to the alpha but not the color.
+        assert_eq!(approx.color(), Rgba::new(1.0, 1.0, 1.0, 0.5));
+        // Collision is not uniform, so it defaults to hard.
+        let ev = approx.evaluate().unwrap();
+        assert_eq!(ev.uniform_collision, Some(BlockCollision::Hard));
+    }
+
+    /// A 2×2×2 block whose lower half (y < 1) is solid.
+    fn half_filled_block() -> EvaluatedBlock {
+        let voxels = Evoxels::Many(
+            R2,
+            GridArray::from_fn(GridAab::for_block(R2), |cube| {
+                if cube.y == 0 {
+                    Evoxel::from_color(Rgba::WHITE)
+                } else {
+                    Evoxel::AIR
+                }
+            }),
+        );
+        EvaluatedBlock::from_voxels(BlockAttributes::default(), voxels)
+    }
+
+    #[test]
+    fn occupancy_at_half_filled() {
+        let ev = half_filled_block();
+        assert!(ev.occupancy_at(Point3::new(0.5, 0.5, 1.5)));
+        assert!(ev.occupancy_at(Point3::new(1.5, 0.0, 0.5)));
+        assert!(!ev.occupancy_at(Point3::new(0.5, 1.5, 0.5)));
+        assert!(
+            !ev.occupancy_at(Point3::new(0.5, -0.5, 0.5)),
+            "outside block"
+        );
+        assert!(
+            !ev.occupancy_at(Point3::new(2.5, 0.5, 0.5)),
+            "outside block"
+        );
+    }
+
+    #[test]
+    fn signed_distance_to_solid_half_filled() {
+        let ev = half_filled_block();
+        // Inside: nearest surface is the block's bottom face or the empty upper half.
+        assert_eq!(
+            ev.signed_distance_to_solid(Point3::new(1.0, 0.25, 1.0)),
+            -0.25
+        );
+        assert_eq!(
+            ev.signed_distance_to_solid(Point3::new(1.0, 0.75, 1.0)),
+            -0.25
+        );
+        // Outside, above the solid half and beside the block.
+        assert_eq!(ev.signed_distance_to_solid(Point3::new(1.0, 1.5, 1.0)), 0.5);
+        assert_eq!(ev.signed_distance_to_solid(Point3::new(3.0, 0.5, 1.0)), 1.0);
+    }
+
+    #[test]
+    fn occupied_bounds_excludes_empty_margin() {
+        let ev = EvaluatedBlock::from_voxels(
+            BlockAttributes::default(),
+            Evoxels::Many(
+                R8,
+                GridArray::from_fn(GridAab::for_block(R8), |cube| {
+                    if GridAab::from_lower_upper([3, 2, 3], [5, 6, 5]).contains_cube(cube) {
+                        Evoxel::from_color(Rgba::WHITE)
+                    } else {
+                        Evoxel::AIR
+                    }
+                }),
+            ),
+        );
+        assert_eq!(
+            ev.occupied_bounds(),
+            Some(GridAab::from_lower_upper([3, 2, 3], [5, 6, 5]))
+        );
+    }
+
+    #[test]
+    fn occupied_bounds_of_full_block() {
+        let ev = EvaluatedBlock::from_voxels(
+            BlockAttributes::default(),
+            Evoxels::Many(
+                R8,
+                GridArray::repeat(GridAab::for_block(R8), Evoxel::from_color(Rgba::WHITE)),
+            ),
+        );
+        assert_eq!(ev.occupied_bounds(), Some(GridAab::for_block(R8)));
+    }
+
+    #[test]
+    fn signed_distance_to_solid_atoms() {
+        let point = Point3::new(0.5, 0.25, 0.5);
+        assert_eq!(
+            Block::from(Rgba::WHITE)
+                .evaluate()
+                .unwrap()
+                .signed_distance_to_solid(point),
+            -0.25
+        );
+        assert_eq!(
+            AIR.evaluate().unwrap().signed_distance_to_solid(point),
+            f32::INFINITY
+        );
+    }
 }
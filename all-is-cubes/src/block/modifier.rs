@@ -1,5 +1,7 @@
-use crate::block::{self, Block, Evoxels, MinEval};
-use crate::math::{GridArray, GridRotation, Rgb};
+use cgmath::{ElementWise as _, Vector4};
+
+use crate::block::{self, Block, Evoxel, Evoxels, MinEval, Resolution::R16};
+use crate::math::{Face6, GridAab, GridArray, GridCoordinate, GridRotation, Rgb, Rgba};
 use crate::universe::{RefVisitor, VisitRefs};
 
 mod composite;
@@ -69,9 +71,60 @@ pub enum Modifier {
 
     /// Displace the block out of the grid, cropping it.
     Move(Move),
+
+    /// Make all voxels outside the given box transparent, such as to depict a
+    /// partially built or eroded block.
+    ///
+    /// The box is in units of the block's voxels, i.e. the whole block is
+    /// [`GridAab::for_block()`] of its resolution. Blocks without voxels are
+    /// treated as having resolution 16.
+    Clip(GridAab),
+
+    /// Tint the block with a linear color gradient, by multiplying each voxel's color
+    /// by the gradient's color at that voxel.
+    ///
+    /// The layer of voxels at the `direction.opposite()` face of the block is multiplied
+    /// by `from`, the layer at the `direction` face by `to`, and the layers between by
+    /// colors interpolated between them. Blocks without voxels are treated as having
+    /// resolution 16.
+    Gradient {
+        /// Direction in which the gradient goes from `from` to `to`.
+        direction: Face6,
+        /// Color at the start of the gradient.
+        from: Rgba,
+        /// Color at the end of the gradient.
+        to: Rgba,
+    },
 }
 
 impl Modifier {
+    /// Constructs a [`Modifier::Quote`], which suppresses the behaviors of the block it
+    /// is attached to. If `suppress_ambient` is true, its light emission is also
+    /// suppressed.
+    ///
+    /// ```
+    /// use all_is_cubes::block::{Block, Modifier};
+    /// use all_is_cubes::math::{Rgb, Rgba};
+    ///
+    /// let lamp = Block::builder()
+    ///     .color(Rgba::WHITE)
+    ///     .light_emission(Rgb::ONE)
+    ///     .build();
+    /// let quoted = Modifier::quote(true).attach(lamp);
+    /// assert_eq!(quoted.evaluate().unwrap().light_emission, Rgb::ZERO);
+    /// ```
+    pub fn quote(suppress_ambient: bool) -> Self {
+        Modifier::Quote(Quote { suppress_ambient })
+    }
+
+    /// Returns `block` with this modifier added after its existing modifiers.
+    ///
+    /// This is equivalent to [`Block::with_modifier()`].
+    #[must_use]
+    pub fn attach(self, block: Block) -> Block {
+        block.with_modifier(self)
+    }
+
     /// Compute the effect of this modifier.
     ///
     /// * `block` is the original block value (modifiers do not alter it).
@@ -136,6 +189,91 @@ impl Modifier {
             Modifier::Move(ref m) => {
                 m.evaluate(block, this_modifier_index, value, depth, filter)?
             }
+
+            Modifier::Clip(clip_bounds) => {
+                if filter.skip_eval {
+                    value
+                } else {
+                    let (original_bounds, resolution) = match value.voxels {
+                        Evoxels::Many(resolution, ref array) => (array.bounds(), resolution),
+                        // Treat color blocks as having a resolution of 16, like Move does.
+                        Evoxels::One(_) => (GridAab::for_block(R16), R16),
+                    };
+                    let voxels = match original_bounds.intersection(clip_bounds) {
+                        // Clipped to nothing.
+                        None => Evoxels::One(Evoxel::AIR),
+                        Some(clipped_bounds) => Evoxels::Many(
+                            resolution,
+                            match value.voxels {
+                                Evoxels::Many(_, ref array) => {
+                                    GridArray::from_fn(clipped_bounds, |cube| array[cube])
+                                }
+                                // Input block is a solid color; synthesize voxels.
+                                Evoxels::One(voxel) => {
+                                    GridArray::from_fn(clipped_bounds, |_| voxel)
+                                }
+                            },
+                        ),
+                    };
+                    MinEval {
+                        attributes: value.attributes,
+                        voxels,
+                    }
+                }
+            }
+
+            Modifier::Gradient {
+                direction,
+                from,
+                to,
+            } => {
+                if filter.skip_eval {
+                    value
+                } else {
+                    let (bounds, resolution) = match value.voxels {
+                        Evoxels::Many(resolution, ref array) => (array.bounds(), resolution),
+                        // Treat color blocks as having a resolution of 16, like Clip does.
+                        Evoxels::One(_) => (GridAab::for_block(R16), R16),
+                    };
+                    let axis = direction.axis_number();
+                    let last_layer = GridCoordinate::from(resolution) - 1;
+                    let from = Vector4::from(from);
+                    let to = Vector4::from(to);
+
+                    let voxels = GridArray::from_fn(bounds, |cube| {
+                        let voxel = match value.voxels {
+                            Evoxels::Many(_, ref array) => array[cube],
+                            // Input block is a solid color; synthesize voxels.
+                            Evoxels::One(voxel) => voxel,
+                        };
+                        let layer = if direction.is_positive() {
+                            cube.lower_bounds()[axis]
+                        } else {
+                            last_layer - cube.lower_bounds()[axis]
+                        };
+                        let t = if last_layer == 0 {
+                            0.5
+                        } else {
+                            layer as f32 / last_layer as f32
+                        };
+                        let tint = from + (to - from) * t;
+                        Evoxel {
+                            // The product can only be NaN if an input is infinite;
+                            // leave such voxels unchanged.
+                            color: Rgba::try_from(
+                                Vector4::from(voxel.color).mul_element_wise(tint),
+                            )
+                            .unwrap_or(voxel.color),
+                            ..voxel
+                        }
+                    });
+
+                    MinEval {
+                        attributes: value.attributes,
+                        voxels: Evoxels::Many(resolution, voxels),
+                    }
+                }
+            }
         })
     }
 
@@ -158,6 +296,10 @@ impl Modifier {
             // TODO: Implement deletion of moving blocks.
             // This is essentially a 2-block multiblock situation.
             Modifier::Move(_) => ModifierUnspecialize::Keep,
+
+            Modifier::Clip(_) => ModifierUnspecialize::Keep,
+
+            Modifier::Gradient { .. } => ModifierUnspecialize::Keep,
         }
     }
 }
@@ -170,6 +312,8 @@ impl VisitRefs for Modifier {
             Modifier::Composite(m) => m.visit_refs(visitor),
             Modifier::Zoom(m) => m.visit_refs(visitor),
             Modifier::Move(m) => m.visit_refs(visitor),
+            Modifier::Clip(_) => {}
+            Modifier::Gradient { .. } => {}
         }
     }
 }
@@ -189,10 +333,14 @@ pub(crate) enum ModifierUnspecialize {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::block::{BlockCollision, EvaluatedBlock, Evoxel, Primitive, Resolution::R2};
+    use crate::block::{
+        BlockCollision, EvaluatedBlock, Evoxel, Primitive,
+        Resolution::{R2, R4},
+    };
     use crate::content::make_some_voxel_blocks;
     use crate::math::{Cube, Face6, FaceMap, GridAab, OpacityCategory, Rgba};
     use crate::universe::Universe;
+    use cgmath::InnerSpace as _;
     use pretty_assertions::assert_eq;
 
     #[test]
@@ -286,4 +434,109 @@ mod tests {
             two_rotations.evaluate().unwrap()
         );
     }
+
+    #[test]
+    fn clip_atom_to_lower_octant() {
+        let octant = GridAab::from_lower_size([0, 0, 0], [8, 8, 8]);
+        let block = Modifier::Clip(octant).attach(Block::from(Rgba::WHITE));
+        let ev = block.evaluate().unwrap();
+
+        assert_eq!(ev.resolution(), R16);
+        assert!(ev.visible);
+        assert_eq!(ev.opaque, FaceMap::repeat(false));
+        let mask = ev.voxel_opacity_mask.as_ref().unwrap();
+        for cube in GridAab::for_block(R16).interior_iter() {
+            let expected = if octant.contains_cube(cube) {
+                OpacityCategory::Opaque
+            } else {
+                OpacityCategory::Invisible
+            };
+            assert_eq!(
+                mask.get(cube)
+                    .copied()
+                    .unwrap_or(OpacityCategory::Invisible),
+                expected,
+                "{cube:?}"
+            );
+            assert_eq!(
+                ev.voxels
+                    .get(cube)
+                    .unwrap_or(Evoxel::AIR)
+                    .color
+                    .fully_transparent(),
+                !octant.contains_cube(cube),
+                "{cube:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn clip_to_nothing() {
+        let mut universe = Universe::new();
+        let [block] = make_some_voxel_blocks(&mut universe);
+        let outside = GridAab::from_lower_size([100, 0, 0], [1, 1, 1]);
+        let ev = Modifier::Clip(outside).attach(block).evaluate().unwrap();
+        assert!(!ev.visible);
+    }
+
+    #[test]
+    fn gradient_voxels() {
+        let mut universe = Universe::new();
+        let from = Rgba::new(1.0, 0.0, 0.0, 1.0);
+        let to = Rgba::new(0.0, 0.0, 1.0, 0.5);
+        let block = Block::builder()
+            .voxels_fn(&mut universe, R4, |_| Block::from(Rgba::WHITE))
+            .unwrap()
+            .build();
+        let ev = Modifier::Gradient {
+            direction: Face6::PY,
+            from,
+            to,
+        }
+        .attach(block)
+        .evaluate()
+        .unwrap();
+
+        assert_eq!(ev.resolution(), R4);
+        for cube in GridAab::for_block(R4).interior_iter() {
+            let expected = match cube.y {
+                0 => from,
+                1 => Rgba::new(2. / 3., 0.0, 1. / 3., 5. / 6.),
+                2 => Rgba::new(1. / 3., 0.0, 2. / 3., 4. / 6.),
+                3 => to,
+                _ => unreachable!(),
+            };
+            let actual = ev.voxels.get(cube).unwrap().color;
+            assert!(
+                (Vector4::from(actual) - Vector4::from(expected)).magnitude() < 1e-6,
+                "{cube:?}: {actual:?} != {expected:?}"
+            );
+        }
+        // Opacity reflects the varying alpha: only the bottom layer is opaque.
+        assert!(ev.visible);
+        assert_eq!(ev.opaque, FaceMap::repeat(false).with(Face6::NY, true));
+    }
+
+    #[test]
+    fn gradient_of_atom_synthesizes_voxels() {
+        let ev = Modifier::Gradient {
+            direction: Face6::NX,
+            from: Rgba::WHITE,
+            to: Rgba::BLACK,
+        }
+        .attach(Block::from(Rgba::WHITE))
+        .evaluate()
+        .unwrap();
+
+        assert_eq!(ev.resolution(), R16);
+        assert_eq!(
+            ev.voxels.get(Cube::new(15, 0, 0)).unwrap().color,
+            Rgba::WHITE
+        );
+        assert_eq!(
+            ev.voxels.get(Cube::new(0, 0, 0)).unwrap().color,
+            Rgba::BLACK
+        );
+        assert_eq!(ev.opaque, FaceMap::repeat(true));
+    }
 }
@@ -18,6 +18,13 @@ use crate::universe;
 ///   never strt being in view, the block will be replaced with [`AIR`].
 ///
 /// (TODO: Define the conditions for “if possible”.)
+///
+/// # Collision
+///
+/// The displaced block's voxels keep their own [`BlockCollision`](block::BlockCollision);
+/// if the block is an atom, then the synthesized voxels all have the atom's collision.
+/// Thus, a moving solid block is solid wherever it has moved to, and passable where
+/// it has moved from.
 #[non_exhaustive] // TODO: needs a constructor instead
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
@@ -98,8 +105,7 @@ impl Move {
 
         // For now, our strategy is to work in units of the block's resolution.
         // TODO: Generalize to being able to increase resolution to a chosen minimum.
-        let distance_in_res =
-            GridCoordinate::from(distance) * GridCoordinate::from(effective_resolution) / 256;
+        let distance_in_res = GridCoordinate::from(distance) * effective_resolution.to_grid() / 256;
         let translation_in_res = direction.normal_vector() * distance_in_res;
 
         // This will be None if the displacement puts the block entirely out of view.
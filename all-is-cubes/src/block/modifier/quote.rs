@@ -39,7 +39,8 @@ impl universe::VisitRefs for Quote {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::block::{Block, Modifier};
+    use crate::block::{Block, Modifier, AIR};
+    use crate::drawing::VoxelBrush;
     use crate::math::{Rgb, Rgba};
     use pretty_assertions::assert_eq;
 
@@ -74,4 +75,26 @@ mod tests {
             Rgb::ZERO
         );
     }
+
+    #[test]
+    fn quote_constructor() {
+        let block = Block::builder()
+            .color(Rgba::WHITE)
+            .light_emission(Rgb::ONE)
+            .tick_action(Some(VoxelBrush::single(AIR)))
+            .build();
+        let ev = block.evaluate().unwrap();
+        assert_eq!(ev.light_emission, Rgb::ONE);
+        assert!(ev.attributes.tick_action.is_some());
+
+        let quoted = Modifier::quote(true).attach(block.clone());
+        let ev = quoted.evaluate().unwrap();
+        assert_eq!(ev.light_emission, Rgb::ZERO);
+        assert_eq!(ev.attributes.tick_action, None);
+
+        // Not suppressing ambient effects keeps the light.
+        let ev = Modifier::quote(false).attach(block).evaluate().unwrap();
+        assert_eq!(ev.light_emission, Rgb::ONE);
+        assert_eq!(ev.attributes.tick_action, None);
+    }
 }
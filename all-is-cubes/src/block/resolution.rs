@@ -1,4 +1,4 @@
-use std::ops;
+use std::ops::{self, Range};
 
 #[cfg(doc)]
 use crate::block::{EvaluatedBlock, Modifier, Primitive};
@@ -74,11 +74,32 @@ impl Resolution {
         }
     }
 
+    /// Returns this resolution as a [`GridCoordinate`].
+    ///
+    /// This is equivalent to [`GridCoordinate::from()`], but usable in `const` contexts.
+    ///
+    /// ```
+    /// use all_is_cubes::block::Resolution;
+    ///
+    /// assert_eq!(Resolution::R16.to_grid(), 16);
+    /// ```
     #[inline]
-    #[doc(hidden)] // interim while waiting for better const-eval support in Rust
     pub const fn to_grid(self) -> GridCoordinate {
         1 << self as GridCoordinate
     }
+
+    /// Returns the range of voxel coordinates along one axis of a block at this
+    /// resolution, `0..self.to_grid()`.
+    ///
+    /// ```
+    /// use all_is_cubes::block::Resolution;
+    ///
+    /// assert_eq!(Resolution::R4.range(), 0..4);
+    /// ```
+    #[inline]
+    pub const fn range(self) -> Range<GridCoordinate> {
+        0..self.to_grid()
+    }
 }
 
 impl fmt::Debug for Resolution {
@@ -230,6 +251,25 @@ mod tests {
         }
     }
 
+    #[test]
+    fn resolution_step_limits() {
+        assert_eq!(R1.halve(), None);
+        assert_eq!(R128.double(), None);
+    }
+
+    #[test]
+    fn resolution_grid_and_range() {
+        for r in RS {
+            assert_eq!(r.to_grid(), i32::from(r));
+            assert_eq!(r.range(), 0..i32::from(r));
+            assert_eq!(Resolution::try_from(r.to_grid()), Ok(r));
+        }
+        assert_eq!(Resolution::try_from(0), Err(IntoResolutionError(0)));
+        assert_eq!(Resolution::try_from(3u32), Err(IntoResolutionError(3u32)));
+        assert_eq!(Resolution::try_from(256), Err(IntoResolutionError(256)));
+        assert_eq!(Resolution::try_from(-1), Err(IntoResolutionError(-1)));
+    }
+
     #[test]
     fn resolution_values() {
         assert_eq!(RS.map(i32::from), [1, 2, 4, 8, 16, 32, 64, 128]);
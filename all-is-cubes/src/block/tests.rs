@@ -14,10 +14,10 @@ use pretty_assertions::assert_eq;
 
 use crate::block::{
     self, Atom, Block, BlockAttributes, BlockChange, BlockCollision, BlockDef, BlockDefTransaction,
-    EvalBlockError, Evoxel, Evoxels, Modifier, Primitive, Resolution, Resolution::*, AIR,
-    AIR_EVALUATED,
+    Composite, CompositeOperator, EvalBlockError, Evoxel, Evoxels, Modifier, Primitive, Resolution,
+    Resolution::*, AIR, AIR_EVALUATED,
 };
-use crate::content::make_some_blocks;
+use crate::content::{make_some_blocks, make_some_voxel_blocks};
 use crate::listen::{self, NullListener, Sink};
 use crate::math::{
     Cube, Face6, FaceMap, GridAab, GridArray, GridCoordinate, GridPoint, GridRotation, GridVector,
@@ -410,6 +410,74 @@ fn indirect_equivalence() {
     assert_eq!(eval_bare, eval_def);
 }
 
+#[test]
+fn simplify_modifiers_combines_rotations() {
+    let mut universe = Universe::new();
+    let [base] = make_some_voxel_blocks(&mut universe);
+    let mut block = base.clone();
+    block.modifiers_mut().extend([
+        Modifier::Rotate(GridRotation::CLOCKWISE),
+        Modifier::Rotate(GridRotation::RXzY),
+    ]);
+    let before = block.evaluate().unwrap();
+
+    block.simplify_modifiers();
+    assert_eq!(
+        block.modifiers(),
+        &[Modifier::Rotate(
+            GridRotation::RXzY * GridRotation::CLOCKWISE
+        )]
+    );
+    assert_eq!(block.evaluate().unwrap(), before);
+}
+
+#[test]
+fn simplify_modifiers_removes_identity() {
+    let mut universe = Universe::new();
+    let [base] = make_some_voxel_blocks(&mut universe);
+    let mut block = base.clone();
+    block.modifiers_mut().extend([
+        Modifier::Quote(block::Quote::default()),
+        Modifier::Rotate(GridRotation::IDENTITY),
+        Modifier::Rotate(GridRotation::CLOCKWISE),
+        Modifier::Rotate(GridRotation::COUNTERCLOCKWISE),
+    ]);
+    let before = block.evaluate().unwrap();
+
+    block.simplify_modifiers();
+    assert_eq!(
+        block.modifiers(),
+        &[Modifier::Quote(block::Quote::default())]
+    );
+    assert_eq!(block.evaluate().unwrap(), before);
+}
+
+#[test]
+fn simplify_modifiers_combines_quotes() {
+    let mut block = Block::builder()
+        .color(Rgba::WHITE)
+        .light_emission(Rgb::ONE)
+        .build();
+    block.modifiers_mut().extend([
+        Modifier::Quote(block::Quote {
+            suppress_ambient: false,
+        }),
+        Modifier::Quote(block::Quote {
+            suppress_ambient: true,
+        }),
+    ]);
+    let before = block.evaluate().unwrap();
+
+    block.simplify_modifiers();
+    assert_eq!(
+        block.modifiers(),
+        &[Modifier::Quote(block::Quote {
+            suppress_ambient: true
+        })]
+    );
+    assert_eq!(block.evaluate().unwrap(), before);
+}
+
 #[test]
 fn listen_atom() {
     let block = Block::from(Rgba::WHITE);
@@ -511,6 +579,31 @@ fn listen_recur() {
     assert_eq!(sink.drain(), vec![]);
 }
 
+/// Test that changes to the source block of a [`Composite`] modifier propagate to
+/// listeners on the composite block.
+#[test]
+fn listen_composite_source() {
+    let mut universe = Universe::new();
+    let [destination, source_1, source_2] = make_some_blocks();
+    let source_def_ref = universe.insert_anonymous(BlockDef::new(source_1));
+    let block = destination.with_modifier(Composite::new(
+        Block::from_primitive(Primitive::Indirect(source_def_ref.clone())),
+        CompositeOperator::Over,
+    ));
+    let sink = Sink::new();
+    block.evaluate_and_listen(sink.listener()).unwrap();
+    assert_eq!(sink.drain(), vec![]);
+
+    // Now mutate the source and we should see a notification.
+    source_def_ref
+        .execute(
+            &BlockDefTransaction::overwrite(source_2),
+            &mut transaction::no_outputs,
+        )
+        .unwrap();
+    assert_eq!(sink.drain().len(), 1);
+}
+
 #[test]
 fn overflow_evaluate() {
     let mut universe = Universe::new();
@@ -529,6 +622,27 @@ fn overflow_listen() {
     assert_eq!(listen(block, NullListener), Ok(()));
 }
 
+/// A block which is its own composite source must fail to evaluate rather than recursing
+/// forever, and listening to it must terminate.
+#[test]
+fn overflow_composite() {
+    let mut universe = Universe::new();
+    let block_def = universe.insert_anonymous(BlockDef::new(AIR));
+    let block = Block::from(Rgba::WHITE).with_modifier(Composite::new(
+        Block::from_primitive(Primitive::Indirect(block_def.clone())),
+        CompositeOperator::Over,
+    ));
+    block_def
+        .execute(
+            &BlockDefTransaction::overwrite(block.clone()),
+            &mut transaction::no_outputs,
+        )
+        .unwrap();
+
+    assert_eq!(block.evaluate(), Err(EvalBlockError::StackOverflow));
+    assert_eq!(listen(block, NullListener), Ok(()));
+}
+
 /// Helper for overflow_ tests
 fn self_referential_block(universe: &mut Universe) -> Block {
     let block_def = universe.insert_anonymous(BlockDef::new(AIR));
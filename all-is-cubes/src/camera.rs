@@ -123,7 +123,7 @@ impl Camera {
         let options = options.repair();
         self.exposure_value = options.exposure.initial();
         self.options = options;
-        // TODO: we only *need* to recompute if fov_y changed (currently)
+        // TODO: we only *need* to recompute if fov_y or projection changed (currently)
         self.compute_matrices();
     }
 
@@ -149,6 +149,13 @@ impl Camera {
         self.options.view_distance.into_inner()
     }
 
+    /// Returns the distance to the near plane of the projection matrix; objects closer
+    /// than this to the camera are not drawn.
+    /// This differs from the value in [`GraphicsOptions`] by being clamped to valid values.
+    pub fn near_plane(&self) -> FreeCoordinate {
+        self.options.near_plane.into_inner()
+    }
+
     /// Sets the view transform.
     ///
     /// Besides controlling rendering, this is used to determine world coordinates for purposes
@@ -336,12 +343,26 @@ impl Camera {
     }
 
     fn compute_matrices(&mut self) {
-        self.projection = cgmath::perspective(
-            self.fov_y(),
-            self.viewport.nominal_aspect_ratio(),
-            /* near: */ 1. / 32., // half a voxel at resolution=16
-            /* far: */ self.view_distance(),
-        );
+        let near = self.near_plane();
+        let far = self.view_distance();
+        let aspect_ratio = self.viewport.nominal_aspect_ratio();
+        self.projection = match self.options.projection {
+            ProjectionOption::Perspective => {
+                cgmath::perspective(self.fov_y(), aspect_ratio, near, far)
+            }
+            ProjectionOption::Orthographic { height } => {
+                let half_height = height.into_inner() / 2.;
+                let half_width = half_height * aspect_ratio;
+                cgmath::ortho(
+                    -half_width,
+                    half_width,
+                    -half_height,
+                    half_height,
+                    near,
+                    far,
+                )
+            }
+        };
 
         self.world_to_eye_matrix = self.eye_to_world_transform
             .inverse_transform()
@@ -470,6 +491,41 @@ impl Viewport {
         w.checked_mul(h)
     }
 
+    /// Returns the largest region of the framebuffer, centered within it, whose aspect
+    /// ratio matches the [nominal aspect ratio](Self::nominal_aspect_ratio) when the
+    /// framebuffer's pixels are taken to be square.
+    ///
+    /// The region is returned as the position of its upper-left corner in framebuffer
+    /// pixels, and a [`Viewport`] whose `framebuffer_size` is the size of the region
+    /// and whose `nominal_size` is unchanged. Drawing into that region, and filling the
+    /// rest of the framebuffer with bars, produces an image which is not stretched.
+    pub fn letterboxed(&self) -> (Vector2<u32>, Viewport) {
+        let fb_size = self.framebuffer_size;
+        let aspect_ratio = self.nominal_aspect_ratio();
+        let fb_width = FreeCoordinate::from(fb_size.x);
+        let fb_height = FreeCoordinate::from(fb_size.y);
+        let size = if fb_width > fb_height * aspect_ratio {
+            // Framebuffer is wider than the nominal aspect ratio; bars on the sides.
+            Vector2::new(
+                ((fb_height * aspect_ratio).round() as u32).min(fb_size.x),
+                fb_size.y,
+            )
+        } else {
+            // Framebuffer is taller than the nominal aspect ratio; bars above and below.
+            Vector2::new(
+                fb_size.x,
+                ((fb_width / aspect_ratio).round() as u32).min(fb_size.y),
+            )
+        };
+        (
+            (fb_size - size) / 2,
+            Viewport {
+                nominal_size: self.nominal_size,
+                framebuffer_size: size,
+            },
+        )
+    }
+
     // TODO: Maybe have a validate() that checks if the data is not fit for producing an
     // invertible transform.
 }
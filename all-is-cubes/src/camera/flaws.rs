@@ -44,6 +44,13 @@ bitflags::bitflags! {
         /// Surfaces that should have textures rather than a solid color don't.
         // TODO: Should this just be one of the things TOO_MUCH means?
         const MISSING_TEXTURES = 1 << 6;
+
+        /// Additional [`SpaceLayer`](crate::camera::SpaceLayer)s have not been drawn,
+        /// despite being requested.
+        const NO_SPACE_LAYERS = 1 << 7;
+
+        /// Surfaces have not been shaded by the light data of the space being shown.
+        const NO_LIGHTING = 1 << 8;
     }
 }
 
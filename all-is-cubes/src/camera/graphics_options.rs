@@ -21,8 +21,13 @@ pub struct GraphicsOptions {
     pub fog: FogOption,
 
     /// Field of view, in degrees from top to bottom edge of the viewport.
+    ///
+    /// Ignored unless [`projection`](Self::projection) is [`ProjectionOption::Perspective`].
     pub fov_y: NotNan<FreeCoordinate>,
 
+    /// Type of projection from the 3D scene to the 2D image.
+    pub projection: ProjectionOption,
+
     /// Method to use to remap colors to fit within the displayable range.
     pub tone_mapping: ToneMappingOperator,
 
@@ -40,6 +45,18 @@ pub struct GraphicsOptions {
     /// TODO: Implement view distance limit (and fog) in raytracer.
     pub view_distance: NotNan<FreeCoordinate>,
 
+    /// Distance, in unit cubes, from the camera to the nearest visible point; the near
+    /// clipping plane of the projection.
+    ///
+    /// Smaller values allow approaching objects more closely without them being cut off,
+    /// but reduce depth buffer precision.
+    pub near_plane: NotNan<FreeCoordinate>,
+
+    /// Distance, in unit cubes, from the camera beyond which chunks of the world may be
+    /// drawn with reduced detail, with each block drawn as a single cube of its overall
+    /// color instead of its individual voxels. [`None`] disables this.
+    pub lod_distance: Option<NotNan<FreeCoordinate>>,
+
     /// Style in which to draw the lighting of [`Space`](crate::space::Space)s.
     /// This does not affect the *computation* of lighting.
     pub lighting_display: LightingOption,
@@ -63,6 +80,16 @@ pub struct GraphicsOptions {
     /// Whether to apply antialiasing techniques.
     pub antialiasing: AntialiasingOption,
 
+    /// Whether to generate and use lower-resolution versions (mipmaps) of block
+    /// textures, reducing the aliasing (“shimmering”) of distant textured blocks at
+    /// some cost in texture memory and update time.
+    pub mipmap_block_textures: bool,
+
+    /// How to sample block textures between the centers of texels.
+    ///
+    /// Renderers may only read this option when they are created.
+    pub block_texture_filter: TextureFilterOption,
+
     /// Whether to use frustum culling for drawing only in-view chunks and objects.
     ///
     /// This option is for debugging and performance testing and should not have any
@@ -87,6 +114,13 @@ pub struct GraphicsOptions {
 
     /// Draw the light rays that contribute to the selected block.
     pub debug_light_rays_at_cursor: bool,
+
+    /// Draw all faces of blocks, even those hidden by an adjacent opaque block, so that
+    /// the interior geometry of meshes may be inspected.
+    ///
+    /// This option is for debugging and has no effect on renderers which do not use
+    /// meshes.
+    pub debug_disable_face_culling: bool,
 }
 
 impl GraphicsOptions {
@@ -106,31 +140,46 @@ impl GraphicsOptions {
     pub const UNALTERED_COLORS: Self = Self {
         fog: FogOption::None,
         fov_y: notnan!(90.),
+        projection: ProjectionOption::Perspective,
         // TODO: Change tone mapping default once we have a good implementation.
         tone_mapping: ToneMappingOperator::Clamp,
         exposure: ExposureOption::Fixed(notnan!(1.)),
         bloom_intensity: notnan!(0.),
         view_distance: notnan!(200.),
+        near_plane: notnan!(0.03125),
+        lod_distance: None,
         lighting_display: LightingOption::None,
         transparency: TransparencyOption::Volumetric,
         show_ui: true,
         antialiasing: AntialiasingOption::None,
+        mipmap_block_textures: false,
+        block_texture_filter: TextureFilterOption::Nearest,
         use_frustum_culling: true,
         debug_info_text: true,
         debug_behaviors: false,
         debug_chunk_boxes: false,
         debug_collision_boxes: false,
         debug_light_rays_at_cursor: false,
+        debug_disable_face_culling: false,
     };
 
     /// Constrain fields to valid/practical values.
     #[must_use]
     pub fn repair(mut self) -> Self {
         self.fov_y = self.fov_y.clamp(NotNan::from(1), NotNan::from(189));
+        if let ProjectionOption::Orthographic { height } = &mut self.projection {
+            *height = (*height).clamp(notnan!(0.0625), NotNan::from(10000));
+        }
         self.bloom_intensity = self.bloom_intensity.clamp(notnan!(0.0), notnan!(1.0));
         self.view_distance = self
             .view_distance
             .clamp(NotNan::from(1), NotNan::from(10000));
+        self.near_plane = self
+            .near_plane
+            .clamp(notnan!(0.001), self.view_distance / notnan!(2.0));
+        if let Some(lod_distance) = &mut self.lod_distance {
+            *lod_distance = (*lod_distance).clamp(NotNan::from(0), NotNan::from(10000));
+        }
         self
     }
 }
@@ -144,21 +193,28 @@ impl Default for GraphicsOptions {
         Self {
             fog: FogOption::Abrupt,
             fov_y: NotNan::from(90),
+            projection: ProjectionOption::Perspective,
             // TODO: Change tone mapping default once we have a good implementation.
             tone_mapping: ToneMappingOperator::Clamp,
             exposure: ExposureOption::default(),
             bloom_intensity: notnan!(0.125),
             view_distance: NotNan::from(200),
+            // half a voxel at resolution=16
+            near_plane: notnan!(0.03125),
+            lod_distance: None,
             lighting_display: LightingOption::Smooth,
             transparency: TransparencyOption::Volumetric,
             show_ui: true,
             antialiasing: AntialiasingOption::default(),
+            mipmap_block_textures: false,
+            block_texture_filter: TextureFilterOption::Nearest,
             use_frustum_culling: true,
             debug_info_text: true,
             debug_behaviors: false,
             debug_chunk_boxes: false,
             debug_collision_boxes: false,
             debug_light_rays_at_cursor: false,
+            debug_disable_face_culling: false,
         }
     }
 }
@@ -180,6 +236,26 @@ pub enum FogOption {
     Physical,
 }
 
+/// Choices for [`GraphicsOptions::projection`].
+///
+#[doc = include_str!("../save/serde-warning.md")]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "save", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum ProjectionOption {
+    /// Perspective projection: farther objects appear smaller, and the extent of the view
+    /// is determined by [`GraphicsOptions::fov_y`].
+    #[default]
+    Perspective,
+    /// Orthographic projection: all rays are parallel to the view direction, so objects
+    /// appear the same size regardless of their distance.
+    Orthographic {
+        /// Height of the visible volume, in world units (cubes), from the bottom edge
+        /// of the viewport to the top edge. The width is determined by the aspect ratio.
+        height: NotNan<FreeCoordinate>,
+    },
+}
+
 /// Choices for [`GraphicsOptions::tone_mapping`].
 ///
 #[doc = include_str!("../save/serde-warning.md")]
@@ -288,6 +364,9 @@ pub enum TransparencyOption {
     /// Alpha above or below the given threshold value will be rounded to fully opaque
     /// or fully transparent, respectively.
     Threshold(NotNan<f32>),
+    /// Only fully opaque surfaces are drawn; any alpha less than 1 is treated as fully
+    /// transparent.
+    OpaqueOnly,
 }
 
 impl TransparencyOption {
@@ -303,6 +382,13 @@ impl TransparencyOption {
                     Rgba::TRANSPARENT
                 }
             }
+            Self::OpaqueOnly => {
+                if color.fully_opaque() {
+                    color
+                } else {
+                    Rgba::TRANSPARENT
+                }
+            }
             _ => color,
         }
     }
@@ -310,7 +396,7 @@ impl TransparencyOption {
     #[inline]
     #[doc(hidden)] // TODO: make public/documented?
     pub fn will_output_alpha(&self) -> bool {
-        !matches!(self, Self::Threshold(_))
+        !matches!(self, Self::Threshold(_) | Self::OpaqueOnly)
     }
 }
 
@@ -346,6 +432,22 @@ impl AntialiasingOption {
     }
 }
 
+/// Choices for [`GraphicsOptions::block_texture_filter`].
+///
+#[doc = include_str!("../save/serde-warning.md")]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "save", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum TextureFilterOption {
+    /// Use the color of the nearest texel, giving a crisp, pixelated appearance.
+    #[default]
+    Nearest,
+    /// Blend the colors of adjacent texels, giving a smooth appearance to
+    /// high-resolution blocks.
+    Linear,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -20,9 +20,14 @@ pub trait HeadlessRenderer {
     /// is not specified, but a good implementation should attempt recovery on a future
     /// call.
     ///
+    /// If `cursor` is [`None`], the renderer should draw the cursor provided by its
+    /// [`StandardCameras::cursor()`], if any.
+    ///
     /// TODO: provide for returning performance info?
     ///
     /// TODO: we may want more dynamic information than the cursor
+    ///
+    /// [`StandardCameras::cursor()`]: crate::camera::StandardCameras::cursor
     fn update<'a>(
         &'a mut self,
         cursor: Option<&'a Cursor>,
@@ -59,6 +59,9 @@ impl<T> Layers<T> {
 /// * A [`URef`] to the [`Character`] whose eyes we look through to render the “world”
 ///   [`Space`].
 /// * A [`URef`] to the UI/HUD [`Space`] overlaid on the world, if any.
+/// * Optionally, additional [`SpaceLayer`]s drawn between the world and the UI;
+///   see [`StandardCameras::with_space_layers()`].
+/// * Optionally, the [`Cursor`] to draw; see [`StandardCameras::with_cursor()`].
 ///
 /// When [`StandardCameras::update()`] is called, all of these data sources are read
 /// and used to update the [`Camera`] data. Those cameras, and copies of the input
@@ -92,6 +95,15 @@ pub struct StandardCameras {
     viewport_source: ListenableSource<Viewport>,
     viewport_dirty: DirtyFlag,
 
+    space_layers_source: ListenableSource<Vec<SpaceLayer>>,
+    space_layers_dirty: DirtyFlag,
+    /// Spaces and cameras derived from `space_layers_source`.
+    space_layers: Vec<(URef<Space>, Camera)>,
+
+    cursor_source: ListenableSource<Option<Cursor>>,
+    cursor_dirty: DirtyFlag,
+    cursor: Option<Cursor>,
+
     cameras: Layers<Camera>,
 }
 
@@ -136,12 +148,52 @@ impl StandardCameras {
 
             viewport_dirty,
             viewport_source,
+
+            space_layers_source: ListenableSource::constant(Vec::new()),
+            space_layers_dirty: DirtyFlag::new(false),
+            space_layers: Vec::new(),
+
+            cursor_source: ListenableSource::constant(None),
+            cursor_dirty: DirtyFlag::new(false),
+            cursor: None,
         };
 
         new_self.update();
         new_self
     }
 
+    /// Adds a source of additional [`SpaceLayer`]s to be drawn, replacing any previously
+    /// set source.
+    ///
+    /// The layers are drawn in front of the world space, in back-to-front order (the last
+    /// layer in the list is frontmost), and behind the UI. Each layer is drawn using the
+    /// same [`GraphicsOptions`] and [`Viewport`] as the world space, and its own
+    /// view transform.
+    ///
+    /// Currently, only [`RtRenderer`](crate::raytracer::RtRenderer) draws space layers;
+    /// other renderers report [`Flaws::NO_SPACE_LAYERS`](crate::camera::Flaws::NO_SPACE_LAYERS).
+    #[must_use]
+    pub fn with_space_layers(mut self, source: ListenableSource<Vec<SpaceLayer>>) -> Self {
+        self.space_layers_dirty = DirtyFlag::listening(true, &source);
+        self.space_layers_source = source;
+        self.update();
+        self
+    }
+
+    /// Adds a source of the [`Cursor`] to be drawn, replacing any previously set source.
+    ///
+    /// Renderers draw this cursor when they are not given one explicitly, such as in
+    /// [`HeadlessRenderer::update()`](crate::camera::HeadlessRenderer::update). The
+    /// source would typically be updated from user input, using
+    /// [`Self::project_cursor()`].
+    #[must_use]
+    pub fn with_cursor(mut self, source: ListenableSource<Option<Cursor>>) -> Self {
+        self.cursor_dirty = DirtyFlag::listening(true, &source);
+        self.cursor_source = source;
+        self.update();
+        self
+    }
+
     #[doc(hidden)]
     pub fn from_constant_for_test(
         graphics_options: GraphicsOptions,
@@ -195,6 +247,26 @@ impl StandardCameras {
             self.cameras.ui.set_viewport(viewport);
         }
 
+        let space_layers_dirty = self.space_layers_dirty.get_and_clear();
+        if space_layers_dirty || options_dirty || viewport_dirty {
+            let world_camera = &self.cameras.world;
+            self.space_layers = self
+                .space_layers_source
+                .get()
+                .iter()
+                .map(|layer| {
+                    let mut camera =
+                        Camera::new(world_camera.options().clone(), world_camera.viewport());
+                    camera.set_view_transform(layer.view_transform);
+                    (layer.space.clone(), camera)
+                })
+                .collect();
+        }
+
+        if self.cursor_dirty.get_and_clear() {
+            self.cursor = self.cursor_source.snapshot();
+        }
+
         if self.character_dirty.get_and_clear() {
             self.character = self.character_source.snapshot();
             if self.character.is_none() {
@@ -219,6 +291,9 @@ impl StandardCameras {
                     self.cameras
                         .world
                         .set_measured_exposure(character.exposure());
+                    for (_, camera) in &mut self.space_layers {
+                        camera.set_measured_exposure(character.exposure());
+                    }
                 }
                 Err(_) => {
                     // TODO: set an error flag indicating failure to update
@@ -272,6 +347,23 @@ impl StandardCameras {
         self.ui_space.as_ref()
     }
 
+    /// Returns the additional spaces to be drawn in front of the world space, and the
+    /// cameras to draw them with, in back-to-front order.
+    ///
+    /// This is empty unless a source was provided with [`Self::with_space_layers()`],
+    /// and only updates when [`StandardCameras::update()`] is called.
+    pub fn space_layers(&self) -> &[(URef<Space>, Camera)] {
+        &self.space_layers
+    }
+
+    /// Returns the cursor that should be drawn, if any.
+    ///
+    /// This is [`None`] unless a source was provided with [`Self::with_cursor()`],
+    /// and only updates when [`StandardCameras::update()`] is called.
+    pub fn cursor(&self) -> Option<&Cursor> {
+        self.cursor.as_ref()
+    }
+
     /// Returns the current viewport.
     ///
     /// This is always equal to the viewports of all managed [`Camera`]s,
@@ -320,9 +412,24 @@ impl Clone for StandardCameras {
             self.character_source.clone(),
             self.ui_source.clone(),
         )
+        .with_space_layers(self.space_layers_source.clone())
+        .with_cursor(self.cursor_source.clone())
     }
 }
 
+/// An additional [`Space`] to draw in front of the world, and the viewpoint to draw it
+/// from. Used with [`StandardCameras::with_space_layers()`].
+///
+/// This may be used for effects such as picture-in-picture or portals.
+#[derive(Clone, Debug, PartialEq)]
+#[allow(clippy::exhaustive_structs)]
+pub struct SpaceLayer {
+    /// The [`Space`] to draw.
+    pub space: URef<Space>,
+    /// The viewpoint to draw the `space` from.
+    pub view_transform: ViewTransform,
+}
+
 /// Specifies what to render for the UI layer in front of the world.
 ///
 /// This struct contains all the information needed to know how to render the UI
@@ -357,6 +464,9 @@ impl Default for UiViewState {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::block::Block;
+    use crate::math::Rgba;
+    use crate::raycast::Ray;
     use crate::space::Space;
     use crate::universe::Universe;
 
@@ -428,4 +538,69 @@ mod tests {
         assert_eq!(cameras.cameras().world.options(), &different_o);
         assert_eq!(cameras2.cameras().world.options(), &different_o);
     }
+
+    #[test]
+    fn cameras_follow_space_layers() {
+        let mut universe = Universe::new();
+        let space_ref = universe.insert_anonymous(Space::empty_positive(1, 1, 1));
+        let layers_cell = ListenableCell::new(Vec::new());
+        let mut cameras = StandardCameras::new(
+            ListenableSource::constant(GraphicsOptions::default()),
+            ListenableSource::constant(Viewport::ARBITRARY),
+            ListenableSource::constant(None),
+            ListenableSource::constant(UiViewState::default()),
+        )
+        .with_space_layers(layers_cell.as_source());
+        assert!(cameras.space_layers().is_empty());
+
+        let view_transform = ViewTransform {
+            disp: cgmath::Vector3::new(1.0, 2.0, 3.0),
+            ..ViewTransform::one()
+        };
+        layers_cell.set(vec![SpaceLayer {
+            space: space_ref.clone(),
+            view_transform,
+        }]);
+        cameras.update();
+        let [(layer_space, layer_camera)] = cameras.space_layers() else {
+            panic!("expected one layer");
+        };
+        assert_eq!(layer_space, &space_ref);
+        assert_eq!(layer_camera.get_view_transform(), view_transform);
+
+        // Clones follow the same layers.
+        assert_eq!(cameras.clone().space_layers().len(), 1);
+    }
+
+    #[test]
+    fn cameras_follow_cursor() {
+        let mut universe = Universe::new();
+        let mut space = Space::empty_positive(1, 1, 1);
+        space.set([0, 0, 0], Block::from(Rgba::WHITE)).unwrap();
+        let space_ref = universe.insert_anonymous(space);
+        let cursor = cursor_raycast(
+            Ray::new([0.5, 0.5, -1.0], [0.0, 0.0, 1.0]),
+            &space_ref,
+            FreeCoordinate::INFINITY,
+        )
+        .unwrap();
+
+        let cursor_cell = ListenableCell::new(None);
+        let mut cameras = StandardCameras::new(
+            ListenableSource::constant(GraphicsOptions::default()),
+            ListenableSource::constant(Viewport::ARBITRARY),
+            ListenableSource::constant(None),
+            ListenableSource::constant(UiViewState::default()),
+        )
+        .with_cursor(cursor_cell.as_source());
+        assert_eq!(cameras.cursor(), None);
+
+        cursor_cell.set(Some(cursor.clone()));
+        assert_eq!(cameras.cursor(), None, "should not update until update()");
+        cameras.update();
+        assert_eq!(cameras.cursor(), Some(&cursor));
+
+        // Clones follow the same cursor.
+        assert_eq!(cameras.clone().cursor(), Some(&cursor));
+    }
 }
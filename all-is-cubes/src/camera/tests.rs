@@ -12,6 +12,32 @@ fn camera_bad_viewport_doesnt_panic() {
     );
 }
 
+#[test]
+fn viewport_letterboxed() {
+    let viewport = |nominal: [f64; 2], fb: [u32; 2]| Viewport {
+        nominal_size: Vector2::from(nominal),
+        framebuffer_size: Vector2::from(fb),
+    };
+    // Wide framebuffer: bars on the sides.
+    assert_eq!(
+        viewport([100.0, 100.0], [30, 10]).letterboxed(),
+        (Vector2::new(10, 0), viewport([100.0, 100.0], [10, 10]))
+    );
+    // Tall framebuffer: bars above and below.
+    assert_eq!(
+        viewport([200.0, 100.0], [20, 20]).letterboxed(),
+        (Vector2::new(0, 5), viewport([200.0, 100.0], [20, 10]))
+    );
+    // Matching aspect ratios: no bars.
+    assert_eq!(
+        viewport([200.0, 100.0], [40, 20]).letterboxed(),
+        (Vector2::new(0, 0), viewport([200.0, 100.0], [40, 20]))
+    );
+    // Degenerate viewports don't panic.
+    viewport([0.0, 0.0], [0, 0]).letterboxed();
+    viewport([0.0, 10.0], [10, 10]).letterboxed();
+}
+
 #[test]
 fn set_options_updates_matrices() {
     let mut camera = Camera::new(GraphicsOptions::default(), Viewport::ARBITRARY);
@@ -24,6 +50,28 @@ fn set_options_updates_matrices() {
     assert_ne!(matrix, camera.projection());
 }
 
+#[test]
+fn orthographic_rays_are_parallel() {
+    let camera = Camera::new(
+        GraphicsOptions {
+            projection: ProjectionOption::Orthographic {
+                height: NotNan::from(4),
+            },
+            ..GraphicsOptions::default()
+        },
+        Viewport::with_scale(1.0, Vector2::new(10, 5)),
+    );
+    let center = camera.project_ndc_into_world(Point2::new(0., 0.));
+    let corner = camera.project_ndc_into_world(Point2::new(1., 1.));
+    assert_eq!(
+        center.direction.normalize(),
+        corner.direction.normalize(),
+        "directions"
+    );
+    // View volume is 4 high and therefore 8 wide.
+    assert_eq!(corner.origin - center.origin, Vector3::new(4., 2., 0.));
+}
+
 #[test]
 fn camera_view_position() {
     // This test used to be less trivial when the transform was taken as a matrix
@@ -266,6 +266,44 @@ impl Character {
         }
     }
 
+    /// Replaces the entire state of this character with that of `other`, while keeping
+    /// this character's listeners, which are notified that every inventory slot and the
+    /// selections may have changed.
+    ///
+    /// Used by [`Universe::apply_diff()`](crate::universe::Universe::apply_diff) and
+    /// [`Universe::restore()`](crate::universe::Universe::restore).
+    pub(crate) fn replace_with(&mut self, other: Character) {
+        let old = std::mem::replace(self, other);
+        self.notifier = old.notifier;
+        self.notifier
+            .notify(CharacterChange::Inventory(inv::InventoryChange {
+                slots: (0..self.inventory.slots.len().max(old.inventory.slots.len())).collect(),
+            }));
+        self.notifier.notify(CharacterChange::Selections);
+    }
+
+    /// Returns a copy of this character's state, without its listeners.
+    ///
+    /// Used by [`Universe::snapshot()`](crate::universe::Universe::snapshot).
+    pub(crate) fn clone_for_snapshot(&self) -> Character {
+        Character {
+            body: self.body.clone(),
+            space: self.space.clone(),
+            velocity_input: self.velocity_input,
+            eye_displacement_pos: self.eye_displacement_pos,
+            eye_displacement_vel: self.eye_displacement_vel,
+            colliding_cubes: self.colliding_cubes.clone(),
+            last_step_info: self.last_step_info,
+            light_samples: self.light_samples,
+            light_sample_index: self.light_sample_index,
+            exposure_log: self.exposure_log,
+            inventory: self.inventory.clone(),
+            selected_slots: self.selected_slots,
+            notifier: Notifier::new(),
+            behaviors: self.behaviors.clone(),
+        }
+    }
+
     /// Advances time.
     ///
     /// Normally, this is called from [`Universe::step`](crate::universe::Universe::step).
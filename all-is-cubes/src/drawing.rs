@@ -392,6 +392,11 @@ impl<'a> VoxelBrush<'a> {
         self
     }
 
+    /// Returns the blocks in this brush and their offsets.
+    pub(crate) fn entries(&self) -> &[(GridVector, Cow<'a, Block>)] {
+        &self.0
+    }
+
     /// Computes the region affected by this brush, as if it were painted at the origin.
     ///
     /// Returns [`None`] if the brush is empty.
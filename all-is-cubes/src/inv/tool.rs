@@ -4,12 +4,12 @@ use std::borrow::Cow;
 use std::sync::Arc;
 use std::{fmt, hash};
 
-use crate::block::{self, Block, Primitive, RotationPlacementRule, AIR};
+use crate::block::{self, Block, Primitive, AIR};
 use crate::character::{Character, CharacterTransaction, Cursor};
 use crate::fluff::Fluff;
 use crate::inv::{self, Icons, InventoryTransaction, StackLimit};
 use crate::linking::BlockProvider;
-use crate::math::{Cube, Face6, GridRotation};
+use crate::math::{Cube, Face6};
 use crate::space::{Space, SpaceTransaction};
 use crate::transaction::{Merge, Transaction};
 use crate::universe::{RefError, RefVisitor, URef, UniverseTransaction, VisitRefs};
@@ -354,26 +354,17 @@ impl ToolInput {
         old_block: Block,
         new_block: Block,
     ) -> Result<UniverseTransaction, ToolError> {
-        let rotation = match new_block
+        let world_cube_face: Face6 = cursor
+            .face_selected()
+            .opposite()
+            .try_into()
+            .unwrap_or(Face6::NZ);
+        let rotation = new_block
             .evaluate()
             .map_err(|e| ToolError::Internal(e.to_string()))? // TODO: better error typing here
             .attributes
             .rotation_rule
-        {
-            RotationPlacementRule::Never => GridRotation::IDENTITY,
-            RotationPlacementRule::Attach { by: attached_face } => {
-                let world_cube_face: Face6 = cursor
-                    .face_selected()
-                    .opposite()
-                    .try_into()
-                    .unwrap_or(Face6::NZ);
-                // TODO: RotationPlacementRule should control the "up" axis choices
-                GridRotation::from_to(attached_face, world_cube_face, Face6::PY)
-                    .or_else(|| GridRotation::from_to(attached_face, world_cube_face, Face6::PX))
-                    .or_else(|| GridRotation::from_to(attached_face, world_cube_face, Face6::PZ))
-                    .unwrap_or(GridRotation::IDENTITY)
-            }
-        };
+            .rotation_for_placement(world_cube_face);
         self.set_cube(
             cursor.cube() + cursor.face_selected().normal_vector(),
             old_block,
@@ -517,7 +508,7 @@ impl<'a, T: arbitrary::Arbitrary<'a>> arbitrary::Arbitrary<'a> for EphemeralOpaq
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::block::Primitive;
+    use crate::block::{Primitive, RotationPlacementRule};
     use crate::character::cursor_raycast;
     use crate::content::{make_some_blocks, make_some_voxel_blocks};
     use crate::inv::Slot;
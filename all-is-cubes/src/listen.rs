@@ -110,6 +110,13 @@ impl<M: Clone + Send> Notifier<M> {
         }
     }
 
+    /// Returns whether there are any listeners, without checking whether they are
+    /// [`alive()`](Listener::alive). This may be used to skip constructing messages
+    /// nobody will receive.
+    pub(crate) fn has_listeners(&self) -> bool {
+        !self.listeners.read().unwrap().is_empty()
+    }
+
     /// Computes the exact count of listeners, including asking all current listeners
     /// if they are [`alive()`](Listener::alive).
     ///
@@ -114,7 +114,7 @@ impl Face6 {
     /// assert_eq!(Face6::NX.is_positive(), false);
     /// ```
     #[inline]
-    pub fn is_positive(self) -> bool {
+    pub const fn is_positive(self) -> bool {
         matches!(self, Self::PX | Self::PY | Self::PZ)
     }
 
@@ -128,7 +128,7 @@ impl Face6 {
     /// assert_eq!(Face6::NX.is_negative(), true);
     /// ```
     #[inline]
-    pub fn is_negative(self) -> bool {
+    pub const fn is_negative(self) -> bool {
         matches!(self, Self::NX | Self::NY | Self::NZ)
     }
 
@@ -550,6 +550,11 @@ impl TryFrom<GridVector> for Face7 {
 pub struct Faceless;
 
 /// Container for values keyed by [`Face6`]s. Always holds exactly six elements.
+///
+/// Since the keys are [`Face6`]s, there is no entry for [`Face7::Within`].
+///
+/// Iteration (via [`FaceMap::iter()`], [`FaceMap::iter_mut()`], or [`IntoIterator`])
+/// produces key-value pairs in the same order as [`Face6::ALL`].
 #[allow(clippy::exhaustive_structs)]
 #[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
@@ -625,15 +630,7 @@ impl<V> FaceMap<V> {
 
     /// Iterate over the map's key-value pairs by mutable reference, in the same order as [`Face6::ALL`].
     pub fn iter_mut(&mut self) -> impl Iterator<Item = (Face6, &mut V)> {
-        [
-            (Face6::NX, &mut self.nx),
-            (Face6::NY, &mut self.ny),
-            (Face6::NZ, &mut self.nz),
-            (Face6::PX, &mut self.px),
-            (Face6::PY, &mut self.py),
-            (Face6::PZ, &mut self.pz),
-        ]
-        .into_iter()
+        self.into_iter()
     }
 
     /// Iterate over the map values by reference, in the same order as [`Face6::ALL`].
@@ -652,7 +649,7 @@ impl<V> FaceMap<V> {
         self.into_values().into_iter()
     }
 
-    /// Transform values.
+    /// Transform values, using a function which is also given the key of each value.
     pub fn map<U>(self, mut f: impl FnMut(Face6, V) -> U) -> FaceMap<U> {
         FaceMap {
             nx: f(Face6::NX, self.nx),
@@ -770,6 +767,55 @@ impl<V> IndexMut<Face6> for FaceMap<V> {
     }
 }
 
+impl<V> IntoIterator for FaceMap<V> {
+    type Item = (Face6, V);
+    type IntoIter = std::iter::Zip<std::array::IntoIter<Face6, 6>, std::array::IntoIter<V, 6>>;
+
+    /// Iterate over the map's key-value pairs, in the same order as [`Face6::ALL`].
+    fn into_iter(self) -> Self::IntoIter {
+        Face6::ALL.into_iter().zip(self.into_values())
+    }
+}
+
+impl<'a, V> IntoIterator for &'a FaceMap<V> {
+    type Item = (Face6, &'a V);
+    type IntoIter = std::iter::Zip<std::array::IntoIter<Face6, 6>, std::array::IntoIter<&'a V, 6>>;
+
+    /// Iterate over the map's key-value pairs by reference, in the same order as
+    /// [`Face6::ALL`].
+    fn into_iter(self) -> Self::IntoIter {
+        let FaceMap {
+            nx,
+            ny,
+            nz,
+            px,
+            py,
+            pz,
+        } = self;
+        Face6::ALL.into_iter().zip([nx, ny, nz, px, py, pz])
+    }
+}
+
+impl<'a, V> IntoIterator for &'a mut FaceMap<V> {
+    type Item = (Face6, &'a mut V);
+    type IntoIter =
+        std::iter::Zip<std::array::IntoIter<Face6, 6>, std::array::IntoIter<&'a mut V, 6>>;
+
+    /// Iterate over the map's key-value pairs by mutable reference, in the same order as
+    /// [`Face6::ALL`].
+    fn into_iter(self) -> Self::IntoIter {
+        let FaceMap {
+            nx,
+            ny,
+            nz,
+            px,
+            py,
+            pz,
+        } = self;
+        Face6::ALL.into_iter().zip([nx, ny, nz, px, py, pz])
+    }
+}
+
 /// The combination of a [`Cube`] and [`Face7`] identifying one face of it or the interior.
 /// This pattern appears in cursor selection and collision detection.
 #[derive(Clone, Copy, Hash, Eq, PartialEq)]
@@ -855,6 +901,29 @@ impl Geometry for CubeFace {
 mod tests {
     use super::*;
 
+    #[test]
+    fn face7_to_face6_rejects_within() {
+        assert_eq!(Face6::try_from(Face7::Within), Err(Faceless));
+    }
+
+    #[test]
+    fn face6_face7_round_trip() {
+        for face in Face6::ALL {
+            let face7 = Face7::from(face);
+            assert_eq!(Face6::try_from(face7), Ok(face));
+            assert_eq!(face7.axis_number(), Some(face.axis_number()));
+            assert_eq!(face7.is_positive(), face.is_positive());
+            assert_eq!(face7.normal_vector::<i32>(), face.normal_vector::<i32>());
+        }
+        assert_eq!(
+            Face7::ALL
+                .into_iter()
+                .filter_map(|face| Face6::try_from(face).ok())
+                .collect::<Vec<_>>(),
+            Face6::ALL.to_vec()
+        );
+    }
+
     #[test]
     fn face_transform_does_not_reflect() {
         for face in Face6::ALL {
@@ -888,10 +957,52 @@ mod tests {
             map.values().copied().collect::<Vec<_>>(),
         );
 
+        // <&FaceMap as IntoIterator>
+        assert_eq!(
+            expected_both,
+            (&map).into_iter().map(|(k, &v)| (k, v)).collect::<Vec<_>>(),
+        );
+
+        // <&mut FaceMap as IntoIterator>
+        assert_eq!(
+            expected_both,
+            (&mut map)
+                .into_iter()
+                .map(|(k, &mut v)| (k, v))
+                .collect::<Vec<_>>(),
+        );
+
+        // <FaceMap as IntoIterator>
+        assert_eq!(expected_both, map.into_iter().collect::<Vec<_>>());
+
         // FaceMap::into_values()
         assert_eq!(Face6::ALL, map.into_values());
     }
 
+    #[test]
+    fn face_map_iter_mut_modifies() {
+        let mut map = FaceMap::from_fn(|f| f as u8);
+        for (face, value) in map.iter_mut() {
+            if face.is_positive() {
+                *value += 100;
+            }
+        }
+        for (_, value) in &mut map {
+            *value += 1;
+        }
+        assert_eq!(
+            map,
+            FaceMap {
+                nx: Face6::NX as u8 + 1,
+                ny: Face6::NY as u8 + 1,
+                nz: Face6::NZ as u8 + 1,
+                px: Face6::PX as u8 + 101,
+                py: Face6::PY as u8 + 101,
+                pz: Face6::PZ as u8 + 101,
+            }
+        );
+    }
+
     #[test]
     fn face_map_rotate() {
         assert_eq!(
@@ -78,8 +78,16 @@ impl GridAab {
     /// (inclusive) and the occupied volume (from a perspective of continuous
     /// rather than discrete coordinates) spans 5 to 15.
     ///
-    /// Returns [`Err`] if the sizes are non-negative or the resulting range would cause
-    /// numeric overflow.
+    /// Returns [`Err`] if the sizes are negative or the resulting range would cause
+    /// numeric overflow. This is suitable for bounds read from untrusted data.
+    ///
+    /// ```
+    /// use all_is_cubes::math::{GridAab, GridCoordinate};
+    ///
+    /// assert!(GridAab::checked_from_lower_size([0, 0, 0], [10, 10, 10]).is_ok());
+    /// assert!(GridAab::checked_from_lower_size([0, 0, 0], [-1, 10, 10]).is_err());
+    /// assert!(GridAab::checked_from_lower_size([GridCoordinate::MAX, 0, 0], [1, 1, 1]).is_err());
+    /// ```
     pub fn checked_from_lower_size(
         lower_bounds: impl Into<GridPoint>,
         sizes: impl Into<GridVector>,
@@ -87,7 +95,6 @@ impl GridAab {
         let lower_bounds = lower_bounds.into();
         let sizes = sizes.into();
 
-        // TODO: Test these error cases.
         // TODO: Replace string error construction with an error enum.
         for i in 0..3 {
             if sizes[i] < 0 {
@@ -614,6 +621,53 @@ impl GridAab {
         )
     }
 
+    /// Splits the box into sub-boxes of size `chunk_size` on each axis, starting at
+    /// the lower bounds. Where the size of the box is not a multiple of `chunk_size`,
+    /// the sub-boxes at the upper edges are clipped to fit.
+    ///
+    /// The sub-boxes do not overlap and their union is exactly `self`.
+    /// They are produced in the same order as [`GridAab::interior_iter()`] produces
+    /// cubes: the Z coordinate varies fastest and the X coordinate slowest.
+    /// If the box is empty, no sub-boxes are produced.
+    ///
+    /// Panics if `chunk_size` is not positive.
+    ///
+    /// ```
+    /// # use all_is_cubes::math::GridAab;
+    /// assert_eq!(
+    ///     GridAab::from_lower_size([0, 0, 10], [1, 1, 25])
+    ///         .subdivide(10)
+    ///         .collect::<Vec<_>>(),
+    ///     vec![
+    ///         GridAab::from_lower_size([0, 0, 10], [1, 1, 10]),
+    ///         GridAab::from_lower_size([0, 0, 20], [1, 1, 10]),
+    ///         GridAab::from_lower_size([0, 0, 30], [1, 1, 5]),
+    ///     ],
+    /// );
+    /// ```
+    #[track_caller]
+    pub fn subdivide(self, chunk_size: GridCoordinate) -> impl Iterator<Item = GridAab> {
+        assert!(
+            chunk_size > 0,
+            "GridAab::subdivide: chunk_size must be > 0, not {chunk_size}"
+        );
+        // Round up without computing `size + chunk_size - 1`, which could overflow.
+        let chunk_counts = self.sizes.map(|size| {
+            size.div_euclid(chunk_size) + GridCoordinate::from(size.rem_euclid(chunk_size) != 0)
+        });
+        GridAab::from_lower_size([0, 0, 0], chunk_counts)
+            .interior_iter()
+            .map(move |chunk_index| {
+                // Each chunk's lower bounds are within `self`, so this cannot overflow,
+                // but its unclipped upper bounds may exceed `GridCoordinate::MAX`.
+                let lower = self.lower_bounds + chunk_index.lower_bounds().to_vec() * chunk_size;
+                let upper = lower
+                    .map(|c| c.saturating_add(chunk_size))
+                    .zip(self.upper_bounds(), GridCoordinate::min);
+                GridAab::from_lower_upper(lower, upper)
+            })
+    }
+
     /// Scales the box up by the given factor.
     ///
     /// Panics on numeric overflow.
@@ -632,6 +686,19 @@ impl GridAab {
         Self::from_lower_size(self.lower_bounds * scale, self.sizes * scale)
     }
 
+    /// Scales the box up by the given factor, or returns [`Err`] on numeric overflow.
+    pub(crate) fn checked_multiply(self, scale: GridCoordinate) -> Result<Self, GridOverflowError> {
+        let mut lower_bounds = self.lower_bounds;
+        let mut sizes = self.sizes;
+        for i in 0..3 {
+            let overflow =
+                || GridOverflowError(format!("{self:?} multiplied by {scale} overflows"));
+            lower_bounds[i] = lower_bounds[i].checked_mul(scale).ok_or_else(overflow)?;
+            sizes[i] = sizes[i].checked_mul(scale).ok_or_else(overflow)?;
+        }
+        Self::checked_from_lower_size(lower_bounds, sizes)
+    }
+
     /// Moves all bounds outward or inward by the specified distances.
     ///
     /// TODO: Currently this will panic if the result is empty. Make it return Option
@@ -733,6 +800,59 @@ impl GridAab {
 
         GridAab::checked_from_lower_size(lower_bounds, size)
     }
+
+    /// Returns the eight corner points of this box.
+    ///
+    /// The corners are ordered so that bits 0, 1, and 2 of the index select the upper
+    /// rather than the lower bound on the X, Y, and Z axes respectively; thus, the first
+    /// element is [`Self::lower_bounds()`] and the last is [`Self::upper_bounds()`].
+    ///
+    /// ```
+    /// use all_is_cubes::cgmath::Point3;
+    /// use all_is_cubes::math::GridAab;
+    ///
+    /// let corners = GridAab::from_lower_upper([1, 2, 3], [4, 5, 6]).corners();
+    /// assert_eq!(corners[0], Point3::new(1, 2, 3));
+    /// assert_eq!(corners[0b001], Point3::new(4, 2, 3));
+    /// assert_eq!(corners[0b110], Point3::new(1, 5, 6));
+    /// assert_eq!(corners[7], Point3::new(4, 5, 6));
+    /// ```
+    pub fn corners(&self) -> [GridPoint; 8] {
+        let l = self.lower_bounds();
+        let u = self.upper_bounds();
+        std::array::from_fn(|i| {
+            GridPoint::new(
+                if i & 1 == 0 { l.x } else { u.x },
+                if i & 2 == 0 { l.y } else { u.y },
+                if i & 4 == 0 { l.z } else { u.z },
+            )
+        })
+    }
+
+    /// Returns the one-cube-thick slab of this box which lies against the given face;
+    /// that is, the cubes within `self` which are touching that face of it.
+    ///
+    /// If `self` has zero size on that face's axis, then the result does too.
+    /// To get a slab outside the box instead, use [`Self::abut()`].
+    ///
+    /// ```
+    /// use all_is_cubes::math::{Face6, GridAab};
+    ///
+    /// let b = GridAab::from_lower_upper([10, 10, 10], [20, 20, 20]);
+    /// assert_eq!(b.face_aab(Face6::NY), GridAab::from_lower_upper([10, 10, 10], [20, 11, 20]));
+    /// assert_eq!(b.face_aab(Face6::PX), GridAab::from_lower_upper([19, 10, 10], [20, 20, 20]));
+    /// ```
+    #[must_use]
+    pub fn face_aab(&self, face: Face6) -> GridAab {
+        let axis = face.axis_number();
+        let mut lower_bounds = self.lower_bounds();
+        let mut size = self.size();
+        size[axis] = size[axis].min(1);
+        if face.is_positive() {
+            lower_bounds[axis] = self.upper_bounds()[axis] - size[axis];
+        }
+        GridAab::from_lower_size(lower_bounds, size)
+    }
 }
 
 impl fmt::Debug for GridAab {
@@ -1043,6 +1163,54 @@ impl<V> GridArray<V> {
         }
     }
 
+    /// Iterates over all the cubes in this array, in the ordering used by
+    /// [`GridAab::interior_iter()`], together with a function which returns the value at
+    /// a given offset from that cube, or `default` if that position is out of bounds.
+    ///
+    /// This is useful for computations where each cube depends on its neighbors, such as
+    /// blurring, ambient occlusion, or cellular automata.
+    /// See also [`GridArray::map_with_neighbors()`].
+    ///
+    /// ```
+    /// use all_is_cubes::math::{GridAab, GridArray, GridVector};
+    ///
+    /// let array = GridArray::from_fn(GridAab::from_lower_size([0, 0, 0], [3, 1, 1]), |cube| cube.x);
+    /// let right_neighbors: Vec<i32> = array
+    ///     .neighborhood_iter(&-1)
+    ///     .map(|(_cube, neighbor)| *neighbor(GridVector::new(1, 0, 0)))
+    ///     .collect();
+    /// assert_eq!(right_neighbors, vec![1, 2, -1]);
+    /// ```
+    pub fn neighborhood_iter<'a>(
+        &'a self,
+        default: &'a V,
+    ) -> impl Iterator<Item = (Cube, impl Fn(GridVector) -> &'a V + 'a)> + 'a {
+        self.bounds.interior_iter().map(move |cube| {
+            (cube, move |offset: GridVector| {
+                // A neighbor outside the numeric range is necessarily out of bounds.
+                cube.checked_add(offset)
+                    .and_then(|neighbor| self.get(neighbor))
+                    .unwrap_or(default)
+            })
+        })
+    }
+
+    /// Produces a new array of the same bounds by applying `f` to each cube together with
+    /// a function which returns the value at a given offset from that cube, or `default` if
+    /// that position is out of bounds, as in [`GridArray::neighborhood_iter()`].
+    pub fn map_with_neighbors<'a, T, F>(&'a self, default: &'a V, mut f: F) -> GridArray<T>
+    where
+        F: FnMut(Cube, &dyn Fn(GridVector) -> &'a V) -> T,
+    {
+        GridArray {
+            bounds: self.bounds,
+            contents: self
+                .neighborhood_iter(default)
+                .map(|(cube, neighbor)| f(cube, &neighbor))
+                .collect(),
+        }
+    }
+
     /// Returns the contents without copying. They are ordered in the same order that
     /// [`GridArray::from_elements()`] expects.
     pub(crate) fn into_elements(self) -> Box<[V]> {
@@ -1176,6 +1344,54 @@ mod tests {
         );
     }
 
+    #[test]
+    fn checked_from_lower_size_valid() {
+        assert_eq!(
+            GridAab::checked_from_lower_size([1, 2, 3], [4, 5, 6]),
+            Ok(GridAab::from_lower_upper([1, 2, 3], [5, 7, 9]))
+        );
+        // The exclusive upper bound may be exactly MAX, including with zero size.
+        assert_eq!(
+            GridAab::checked_from_lower_size([GridCoordinate::MAX - 1, 0, 0], [1, 1, 1])
+                .map(|aab| aab.upper_bounds()),
+            Ok(GridPoint::new(GridCoordinate::MAX, 1, 1))
+        );
+        assert!(GridAab::checked_from_lower_size([GridCoordinate::MAX, 0, 0], [0, 1, 1]).is_ok());
+        assert!(GridAab::checked_from_lower_size(
+            [GridCoordinate::MIN, 0, 0],
+            [GridCoordinate::MAX, 1, 1]
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn checked_from_lower_size_negative() {
+        for axis in 0..3 {
+            let mut sizes = GridVector::new(1, 1, 1);
+            sizes[axis] = -1;
+            assert!(
+                GridAab::checked_from_lower_size([0, 0, 0], sizes).is_err(),
+                "{sizes:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn checked_from_lower_size_overflow() {
+        assert!(GridAab::checked_from_lower_size([GridCoordinate::MAX, 0, 0], [1, 1, 1]).is_err());
+        assert!(GridAab::checked_from_lower_size([0, 0, 1], [1, 1, GridCoordinate::MAX]).is_err());
+        // Volume overflows usize.
+        assert!(GridAab::checked_from_lower_size(
+            [0, 0, 0],
+            [
+                GridCoordinate::MAX,
+                GridCoordinate::MAX,
+                GridCoordinate::MAX
+            ]
+        )
+        .is_err());
+    }
+
     #[test]
     fn index_overflow_low() {
         // Indexing calculates (point - lower_bounds), so this would overflow in the negative direction if the overflow weren't checked.
@@ -1229,6 +1445,66 @@ mod tests {
         let _ = GridAab::from_lower_size([-10, -10, -10], [20, 20, 20]).divide(-10);
     }
 
+    #[test]
+    fn subdivide_not_multiple() {
+        let aab = GridAab::from_lower_size([-5, 0, 3], [7, 4, 10]);
+        let chunks: Vec<GridAab> = aab.subdivide(3).collect();
+
+        assert_eq!(chunks.len(), 3 * 2 * 4);
+        assert_eq!(chunks[0], GridAab::from_lower_size([-5, 0, 3], [3, 3, 3]));
+        assert_eq!(
+            chunks.last().copied(),
+            Some(GridAab::from_lower_size([1, 3, 12], [1, 1, 1]))
+        );
+
+        // Every cube is covered exactly once, and nothing outside is.
+        let mut counts = GridArray::repeat(aab, 0);
+        for chunk in &chunks {
+            assert!(aab.contains_box(*chunk), "{chunk:?}");
+            for cube in chunk.interior_iter() {
+                counts[cube] += 1;
+            }
+        }
+        assert!(counts.iter().all(|(_, &count)| count == 1), "{counts:?}");
+    }
+
+    #[test]
+    fn subdivide_at_numeric_limits() {
+        let aab = GridAab::from_lower_upper(
+            [GridCoordinate::MAX - 10, GridCoordinate::MIN, 0],
+            [GridCoordinate::MAX, GridCoordinate::MIN + 4, 1],
+        );
+        assert_eq!(
+            aab.subdivide(8).collect::<Vec<_>>(),
+            vec![
+                GridAab::from_lower_upper(
+                    [GridCoordinate::MAX - 10, GridCoordinate::MIN, 0],
+                    [GridCoordinate::MAX - 2, GridCoordinate::MIN + 4, 1],
+                ),
+                GridAab::from_lower_upper(
+                    [GridCoordinate::MAX - 2, GridCoordinate::MIN, 0],
+                    [GridCoordinate::MAX, GridCoordinate::MIN + 4, 1],
+                ),
+            ]
+        );
+
+        // A chunk size larger than any box.
+        assert_eq!(
+            aab.subdivide(GridCoordinate::MAX).collect::<Vec<_>>(),
+            vec![aab]
+        );
+    }
+
+    #[test]
+    fn subdivide_empty() {
+        assert_eq!(
+            GridAab::from_lower_size([0, 0, 0], [10, 0, 10])
+                .subdivide(3)
+                .count(),
+            0
+        );
+    }
+
     #[test]
     fn transform_general() {
         assert_eq!(
@@ -1286,6 +1562,53 @@ mod tests {
         );
     }
 
+    #[test]
+    fn corners_of_box() {
+        let b = GridAab::from_lower_upper([-1, 2, 3], [4, 5, 7]);
+        assert_eq!(
+            b.corners(),
+            [
+                GridPoint::new(-1, 2, 3),
+                GridPoint::new(4, 2, 3),
+                GridPoint::new(-1, 5, 3),
+                GridPoint::new(4, 5, 3),
+                GridPoint::new(-1, 2, 7),
+                GridPoint::new(4, 2, 7),
+                GridPoint::new(-1, 5, 7),
+                GridPoint::new(4, 5, 7),
+            ]
+        );
+    }
+
+    #[test]
+    fn face_aab_each_face() {
+        let b = GridAab::from_lower_upper([-1, 2, 3], [4, 5, 7]);
+        for face in Face6::ALL {
+            let slab = b.face_aab(face);
+            let axis = face.axis_number();
+            assert_eq!(slab.size()[axis], 1, "{face:?}");
+            assert!(b.contains_box(slab), "{face:?}");
+            // The slab is flush with the face, and extends across the whole face.
+            assert_eq!(slab.abut(face, 0).unwrap(), b.abut(face, 0).unwrap());
+        }
+        assert_eq!(
+            b.face_aab(Face6::NZ),
+            GridAab::from_lower_upper([-1, 2, 3], [4, 5, 4])
+        );
+        assert_eq!(
+            b.face_aab(Face6::PZ),
+            GridAab::from_lower_upper([-1, 2, 6], [4, 5, 7])
+        );
+    }
+
+    #[test]
+    fn face_aab_of_flat_box() {
+        let b = GridAab::from_lower_upper([0, 0, 0], [3, 0, 3]);
+        assert_eq!(b.face_aab(Face6::PY), b);
+        assert_eq!(b.face_aab(Face6::NY), b);
+        assert_eq!(b.face_aab(Face6::PX).size(), GridVector::new(1, 0, 3));
+    }
+
     /// Test `Debug` formatting. Note this should be similar to the [`Aab`] formatting.
     #[test]
     fn debug() {
@@ -1412,6 +1735,48 @@ mod tests {
         );
     }
 
+    /// Blur with a 3×3×3 box filter, which reaches outside the array at its edges.
+    #[test]
+    fn array_map_with_neighbors_blur() {
+        let bounds = GridAab::from_lower_size([0, 0, 0], [3, 3, 3]);
+        let array = GridArray::repeat(bounds, 1.0f32);
+        let blur = |default: f32| {
+            array.map_with_neighbors(&default, |_cube, neighbor| {
+                GridAab::from_lower_size([-1, -1, -1], [3, 3, 3])
+                    .interior_iter()
+                    .map(|offset| *neighbor(offset.lower_bounds().to_vec()))
+                    .sum::<f32>()
+                    / 27.0
+            })
+        };
+
+        let blurred_with_zero = blur(0.0);
+        // The center cube's neighborhood is entirely in bounds...
+        assert_eq!(blurred_with_zero[[1, 1, 1]], 1.0);
+        // ...but a corner's has only 8 of 27 cubes in bounds, and an edge's 12.
+        assert_eq!(blurred_with_zero[[0, 0, 0]], 8.0 / 27.0);
+        assert_eq!(blurred_with_zero[[2, 2, 2]], 8.0 / 27.0);
+        assert_eq!(blurred_with_zero[[1, 0, 0]], 12.0 / 27.0);
+
+        // If the default matches the contents, the edges are not darkened.
+        assert_eq!(blur(1.0), GridArray::repeat(bounds, 1.0));
+    }
+
+    /// Neighbors whose coordinates would overflow are out of bounds, not a panic.
+    #[test]
+    fn array_neighborhood_at_numeric_limits() {
+        let array = GridArray::from_fn(
+            GridAab::from_lower_upper([GridCoordinate::MIN, 0, 0], [GridCoordinate::MIN + 1, 1, 1]),
+            |_| 1,
+        );
+        let (cube, neighbor) = array.neighborhood_iter(&0).next().unwrap();
+        assert_eq!(cube, Cube::new(GridCoordinate::MIN, 0, 0));
+        assert_eq!(*neighbor(GridVector::new(0, 0, 0)), 1);
+        assert_eq!(*neighbor(GridVector::new(-1, 0, 0)), 0);
+        assert_eq!(*neighbor(GridVector::new(GridCoordinate::MIN, 0, 0)), 0);
+        assert_eq!(*neighbor(GridVector::new(0, GridCoordinate::MAX, 0)), 0);
+    }
+
     #[cfg(feature = "arbitrary")]
     #[test]
     fn arbitrary_grid_aab_size_hint() {
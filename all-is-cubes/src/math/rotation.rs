@@ -340,13 +340,72 @@ impl GridRotation {
         }
     }
 
+    /// Expresses this rotation as a unit quaternion, or returns [`None`] if it is a
+    /// reflection (which quaternions cannot represent).
+    ///
+    /// ```
+    /// use all_is_cubes::cgmath::{InnerSpace as _, Rotation as _, Vector3};
+    /// use all_is_cubes::math::{Face6, GridRotation};
+    ///
+    /// let quaternion = GridRotation::CLOCKWISE.to_quaternion().unwrap();
+    /// let rotated = quaternion.rotate_vector(Vector3::new(1.0, 0.0, 0.0));
+    /// assert!((rotated - Vector3::new(0.0, 0.0, 1.0)).magnitude2() < 1e-12);
+    ///
+    /// assert_eq!(GridRotation::from_basis([Face6::NX, Face6::PY, Face6::PZ]).to_quaternion(), None);
+    /// ```
+    pub fn to_quaternion(self) -> Option<cgmath::Quaternion<f64>> {
+        if self.is_reflection() {
+            return None;
+        }
+        let basis = self.to_basis();
+        Some(cgmath::Quaternion::from(cgmath::Matrix3::from_cols(
+            basis.x.normal_vector(),
+            basis.y.normal_vector(),
+            basis.z.normal_vector(),
+        )))
+    }
+
+    /// Returns the rotation (never a reflection) which is closest to the rotation
+    /// described by `quaternion`.
+    ///
+    /// `quaternion` need not be normalized, but if it is zero or not finite, then the
+    /// result is arbitrary.
+    ///
+    /// ```
+    /// use all_is_cubes::cgmath::{Deg, Quaternion, Rotation3 as _, Vector3};
+    /// use all_is_cubes::math::GridRotation;
+    ///
+    /// assert_eq!(
+    ///     GridRotation::from_quaternion(Quaternion::from_axis_angle(Vector3::unit_y(), Deg(-85.0))),
+    ///     GridRotation::CLOCKWISE,
+    /// );
+    /// ```
+    pub fn from_quaternion(quaternion: cgmath::Quaternion<f64>) -> Self {
+        use cgmath::InnerSpace as _;
+        let quaternion = quaternion.normalize();
+        Self::ALL_BUT_REFLECTIONS
+            .into_iter()
+            .map(|rotation| {
+                // q and -q are the same rotation, so compare by absolute value.
+                let similarity = rotation.to_quaternion().unwrap().dot(quaternion).abs();
+                (rotation, similarity)
+            })
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .unwrap()
+            .0
+    }
+
     /// Rotate the face by this rotation.
     // TODO: test equivalence with matrix
     #[inline]
-    pub fn transform(self, face: Face6) -> Face6 {
+    pub const fn transform(self, face: Face6) -> Face6 {
         // TODO: there ought to be a much cleaner way to express this
-        // ... and it should be a const fn, too
-        let p = self.to_basis()[face.axis_number()];
+        let basis = self.to_basis();
+        let p = match face.axis_number() {
+            0 => basis.x,
+            1 => basis.y,
+            _ => basis.z,
+        };
         if face.is_negative() {
             p.opposite()
         } else {
@@ -484,16 +543,56 @@ impl Mul<Self> for GridRotation {
     /// ```
     #[inline]
     fn mul(self, rhs: Self) -> Self::Output {
-        Self::from_basis(rhs.to_basis().map(|v| self.transform(v)))
+        MULTIPLICATION_TABLE[self as usize][rhs as usize]
     }
 }
 
+/// Precomputed results of [`GridRotation`] multiplication, indexed by
+/// `[lhs as usize][rhs as usize]`.
+///
+/// This relies on [`GridRotation::ALL`] being in discriminant order.
+const MULTIPLICATION_TABLE: [[GridRotation; 48]; 48] = {
+    const fn same_basis(a: Vector3<Face6>, b: Vector3<Face6>) -> bool {
+        // u8 casts are a kludge to make == work as a const fn.
+        a.x as u8 == b.x as u8 && a.y as u8 == b.y as u8 && a.z as u8 == b.z as u8
+    }
+
+    const fn compute(lhs: GridRotation, rhs: GridRotation) -> GridRotation {
+        let rhs_basis = rhs.to_basis();
+        let basis = Vector3 {
+            x: lhs.transform(rhs_basis.x),
+            y: lhs.transform(rhs_basis.y),
+            z: lhs.transform(rhs_basis.z),
+        };
+        let mut i = 0;
+        while i < GridRotation::ALL.len() {
+            if same_basis(GridRotation::ALL[i].to_basis(), basis) {
+                return GridRotation::ALL[i];
+            }
+            i += 1;
+        }
+        panic!("rotation composition produced an invalid basis")
+    }
+
+    let mut table = [[GridRotation::IDENTITY; 48]; 48];
+    let mut i = 0;
+    while i < 48 {
+        let mut j = 0;
+        while j < 48 {
+            table[i][j] = compute(GridRotation::ALL[i], GridRotation::ALL[j]);
+            j += 1;
+        }
+        i += 1;
+    }
+    table
+};
+
 // TODO: consider implementing cgmath::Transform for GridRotation.
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use cgmath::{EuclideanSpace as _, Transform as _};
+    use cgmath::{EuclideanSpace as _, InnerSpace as _, Rotation as _, Transform as _};
     use std::collections::HashSet;
     use Face6::*;
 
@@ -540,6 +639,41 @@ mod tests {
         assert_eq!(48, GridRotation::ALL.len());
     }
 
+    #[test]
+    fn quaternion_round_trip() {
+        for rotation in GridRotation::ALL {
+            let quaternion = rotation.to_quaternion();
+            assert_eq!(
+                quaternion.is_none(),
+                rotation.is_reflection(),
+                "{rotation:?}"
+            );
+            if let Some(quaternion) = quaternion {
+                assert_eq!(GridRotation::from_quaternion(quaternion), rotation);
+                assert_eq!(GridRotation::from_quaternion(-quaternion), rotation);
+                // Check agreement with the matrix form.
+                for face in Face6::ALL {
+                    let expected = rotation.transform(face).normal_vector::<f64>();
+                    let actual = quaternion.rotate_vector(face.normal_vector::<f64>());
+                    assert!(
+                        (actual - expected).magnitude2() < 1e-12,
+                        "{rotation:?} {face:?}: {actual:?} != {expected:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn clockwise_quaternion() {
+        let quaternion = GridRotation::CLOCKWISE.to_quaternion().unwrap();
+        let rotated = quaternion.rotate_vector(PX.normal_vector::<f64>());
+        assert!(
+            (rotated - PZ.normal_vector()).magnitude2() < 1e-12,
+            "{rotated:?}"
+        );
+    }
+
     /// Test that `GridRotation::ALL_BUT_REFLECTIONS` is complete.
     #[test]
     fn all_but_reflections() {
@@ -567,6 +701,22 @@ mod tests {
         }
     }
 
+    #[test]
+    fn multiplication_table_matches_basis_composition() {
+        for (i, rot) in GridRotation::ALL.into_iter().enumerate() {
+            assert_eq!(rot as usize, i, "ALL is not in discriminant order");
+        }
+        for lhs in GridRotation::ALL {
+            for rhs in GridRotation::ALL {
+                assert_eq!(
+                    lhs * rhs,
+                    GridRotation::from_basis(rhs.to_basis().map(|v| lhs.transform(v))),
+                    "{lhs:?} * {rhs:?}"
+                );
+            }
+        }
+    }
+
     /// The set of possible inputs is small enough to test its properties exhaustively
     #[test]
     fn from_to_exhaustive() {
@@ -563,7 +563,7 @@ pub(crate) fn nudge_on_ray(
 #[cfg(test)]
 mod tests {
     use crate::block::Resolution::*;
-    use crate::block::{Block, AIR};
+    use crate::block::{Block, Move, AIR};
     use crate::content::{make_slab, make_some_blocks};
     use crate::raytracer::print_space;
     use crate::universe::Universe;
@@ -650,6 +650,37 @@ mod tests {
         );
     }
 
+    /// A solid atom block displaced by [`Move`] should collide as a solid at its displaced
+    /// position, with no gaps from being converted to voxels.
+    #[test]
+    fn collide_along_ray_with_moved_block() {
+        let [block] = make_some_blocks();
+        // Displaced downward by half a block, so it occupies the lower half of its cube.
+        let mut space = Space::empty_positive(1, 2, 1);
+        space
+            .set([0, 0, 0], block.with_modifier(Move::new(Face6::NY, 128, 0)))
+            .unwrap();
+
+        // Drop a small box at several positions; it should always land on the displaced
+        // top surface.
+        let aab = Aab::from_lower_upper([-0.0625, 0., -0.0625], [0.0625, 0.25, 0.0625]);
+        for x in [0.0625, 0.5, 0.9375] {
+            let ray = Ray::new([x, 1.5, 0.5], [0., -2., 0.]);
+            let result = collide_along_ray(&space, ray, aab, |_| {}, StopAt::NotAlreadyColliding)
+                .unwrap_or_else(|| panic!("no collision at x = {x}"));
+            assert_eq!(result.t_distance, 0.5, "x = {x}");
+            assert_eq!(
+                (
+                    result.contact.cube(),
+                    result.contact.normal(),
+                    result.contact.resolution()
+                ),
+                (Cube::ORIGIN, Face7::PY, R16),
+                "x = {x}"
+            );
+        }
+    }
+
     fn collide_along_ray_tester(
         initial_y: FreeCoordinate,
         block_gen: fn(&mut Universe) -> [Block; 2],
@@ -15,6 +15,7 @@ use cgmath::{
 };
 use cgmath::{Point3, Vector4};
 use ordered_float::NotNan;
+use rand::Rng as _;
 #[cfg(feature = "threads")]
 use rayon::iter::{IntoParallelIterator as _, ParallelIterator as _};
 
@@ -29,6 +30,8 @@ use crate::util::{CustomFormat, StatusText};
 
 mod accum;
 pub use accum::*;
+mod contrast;
+pub use contrast::*;
 mod renderer;
 pub use renderer::*;
 mod surface;
@@ -109,15 +112,86 @@ impl<D: RtBlockData> SpaceRaytracer<D> {
         ray: Ray,
         include_sky: bool,
     ) -> (P, RaytraceInfo) {
+        let sky_color = if include_sky {
+            self.sky_color.with_alpha_one()
+        } else {
+            Rgba::TRANSPARENT
+        };
+        self.trace_ray_onto(ray, P::default(), sky_color, None)
+    }
+
+    /// As [`Self::trace_ray()`], but rays which are not entirely blocked by the contents
+    /// of the space end at the given [`Background`] instead of the space's sky color.
+    ///
+    /// The background does not affect lighting.
+    pub fn trace_ray_with_background<P: Accumulate<BlockData = D>>(
+        &self,
+        ray: Ray,
+        background: &Background,
+    ) -> (P, RaytraceInfo) {
+        let sky_color = background.color_with_alpha(ray.direction);
+        self.trace_ray_onto(ray, P::default(), sky_color, None)
+    }
+
+    /// Returns the sky color of the space, which is used as the background if no
+    /// [`Background`] is specified.
+    pub(crate) fn sky_color(&self) -> Rgb {
+        self.sky_color
+    }
+
+    /// Traces a ray, continuing to accumulate into `accumulator`, which may already
+    /// contain the results of tracing something in front of this space, and ending
+    /// with `sky_color` if the ray is not blocked.
+    ///
+    /// If `stochastic_rng` is given, then transparency is stochastic; see
+    /// [`TracingState::stochastic_rng`].
+    pub(crate) fn trace_ray_onto<P: Accumulate<BlockData = D>>(
+        &self,
+        ray: Ray,
+        accumulator: P,
+        sky_color: Rgba,
+        stochastic_rng: Option<&mut StochasticRng>,
+    ) -> (P, RaytraceInfo) {
+        self.trace_ray_state(ray, accumulator, stochastic_rng)
+            .finish(sky_color, &self.sky_data)
+    }
+
+    /// Traces a ray, continuing to accumulate into `accumulator`, without adding any
+    /// sky color, so that something behind this space may be traced next.
+    ///
+    /// Unlike [`Self::trace_ray_onto()`], if the ray misses the space entirely,
+    /// `accumulator` is not informed of that.
+    pub(crate) fn trace_ray_partial<P: Accumulate<BlockData = D>>(
+        &self,
+        ray: Ray,
+        accumulator: P,
+        stochastic_rng: Option<&mut StochasticRng>,
+    ) -> (P, RaytraceInfo) {
+        let state = self.trace_ray_state(ray, accumulator, stochastic_rng);
+        (
+            state.accumulator,
+            RaytraceInfo {
+                cubes_traced: state.cubes_traced,
+            },
+        )
+    }
+
+    fn trace_ray_state<'r, P: Accumulate<BlockData = D>>(
+        &self,
+        ray: Ray,
+        accumulator: P,
+        stochastic_rng: Option<&'r mut StochasticRng>,
+    ) -> TracingState<'r, P> {
         let options = RtOptionsRef {
             graphics_options: &self.graphics_options,
             custom_options: &self.custom_options,
         };
 
-        let mut state: TracingState<P> = TracingState {
+        let mut state: TracingState<'_, P> = TracingState {
             t_to_absolute_distance: ray.direction.magnitude(),
             cubes_traced: 0,
-            accumulator: P::default(),
+            accumulator,
+            stochastic_rng,
         };
         let surface_iter = SurfaceIter::new(self, ray);
 
@@ -159,14 +233,7 @@ impl<D: RtBlockData> SpaceRaytracer<D> {
                 }
             }
         }
-        state.finish(
-            if include_sky {
-                self.sky_color.with_alpha_one()
-            } else {
-                Rgba::TRANSPARENT
-            },
-            &self.sky_data,
-        )
+        state
     }
 
     #[inline]
@@ -387,6 +454,74 @@ where
     }
 }
 
+/// Specifies what a raytracer should draw where nothing in the [`Space`] blocks the view,
+/// instead of the [`Space`]'s sky color.
+///
+/// This does not affect the lighting of the space, only the color of rays which pass
+/// through it.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum Background {
+    /// A single color in every direction.
+    Solid(Rgb),
+    /// A gradient which varies with the vertical component of the ray direction,
+    /// from `bottom` when looking straight down to `top` when looking straight up.
+    VerticalGradient {
+        /// Color when looking straight up.
+        top: Rgb,
+        /// Color when looking straight down.
+        bottom: Rgb,
+    },
+    /// No color; pixels whose rays are not blocked are fully transparent, so that the
+    /// image may be composited over something else.
+    Transparent,
+}
+
+impl Background {
+    /// Returns the color of the background in the given direction.
+    ///
+    /// [`Background::Transparent`] has no color, and this returns black for it.
+    ///
+    /// ```
+    /// use all_is_cubes::cgmath::Vector3;
+    /// use all_is_cubes::math::Rgb;
+    /// use all_is_cubes::raytracer::Background;
+    ///
+    /// let background = Background::VerticalGradient {
+    ///     top: Rgb::ONE,
+    ///     bottom: Rgb::ZERO,
+    /// };
+    /// assert_eq!(background.color(Vector3::new(0.0, 1.0, 0.0)), Rgb::ONE);
+    /// assert_eq!(background.color(Vector3::new(0.0, -2.0, 0.0)), Rgb::ZERO);
+    /// assert_eq!(background.color(Vector3::new(1.0, 0.0, 0.0)), Rgb::ONE * 0.5);
+    /// ```
+    pub fn color(&self, direction: Vector3<FreeCoordinate>) -> Rgb {
+        match *self {
+            Background::Solid(color) => color,
+            Background::VerticalGradient { top, bottom } => {
+                let magnitude = direction.magnitude();
+                let height = if magnitude > 0.0 {
+                    direction.y / magnitude
+                } else {
+                    0.0
+                };
+                // Map [-1, 1] to [0, 1].
+                let t = ((height * 0.5 + 0.5) as f32).clamp(0.0, 1.0);
+                bottom * (1.0 - t) + top * t
+            }
+            Background::Transparent => Rgb::ZERO,
+        }
+    }
+
+    /// Returns the color, including alpha, that rays in the given direction end with.
+    pub(crate) fn color_with_alpha(&self, direction: Vector3<FreeCoordinate>) -> Rgba {
+        match self {
+            Background::Transparent => Rgba::TRANSPARENT,
+            _ => self.color(direction).with_alpha_one(),
+        }
+    }
+}
+
 /// Performance info from a [`SpaceRaytracer`] operation.
 ///
 /// The contents of this structure are subject to change; use [`Debug`] to view it.
@@ -464,10 +599,13 @@ impl<D: RtBlockData> TracingBlock<D> {
     }
 }
 
+/// Random number generator used for stochastic transparency.
+pub(crate) type StochasticRng = rand_xoshiro::Xoshiro256Plus;
+
 /// Holds an [`Accumulate`] and other per-ray state, and updates it
 /// according to the things it encounters.
-#[derive(Clone, Debug, Default)]
-struct TracingState<P: Accumulate> {
+#[derive(Debug, Default)]
+struct TracingState<'r, P: Accumulate> {
     /// Conversion factor from raycaster `t` values to “true” [`Space`] distance values
     /// where 1 unit = 1 block thickness.
     t_to_absolute_distance: f64,
@@ -477,8 +615,14 @@ struct TracingState<P: Accumulate> {
     cubes_traced: usize,
 
     accumulator: P,
+
+    /// If present, transparency is stochastic: each surface is either drawn as opaque,
+    /// with probability equal to its alpha, or skipped entirely. The mean of many such
+    /// rays is equal to the result of ordinary alpha blending, regardless of the order
+    /// in which the surfaces are blended.
+    stochastic_rng: Option<&'r mut StochasticRng>,
 }
-impl<P: Accumulate> TracingState<P> {
+impl<P: Accumulate> TracingState<'_, P> {
     #[inline]
     fn count_step_should_stop(
         &mut self,
@@ -532,7 +676,13 @@ impl<P: Accumulate> TracingState<P> {
         surface: Surface<'_, P::BlockData>,
         rt: &SpaceRaytracer<P::BlockData>,
     ) {
-        if let Some(color) = surface.to_lit_color(rt) {
+        if let Some(mut color) = surface.to_lit_color(rt) {
+            if let Some(rng) = self.stochastic_rng.as_deref_mut() {
+                if rng.gen::<f32>() >= color.alpha().into_inner() {
+                    return;
+                }
+                color = color.to_rgb().with_alpha_one();
+            }
             self.accumulator.add(color, surface.block_data);
         }
     }
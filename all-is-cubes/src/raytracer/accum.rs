@@ -4,6 +4,7 @@ use std::convert::TryFrom as _;
 
 use cgmath::{Vector3, Zero as _};
 
+use crate::block::Block;
 use crate::camera::GraphicsOptions;
 use crate::math::Rgba;
 use crate::space::SpaceBlockData;
@@ -186,11 +187,13 @@ impl From<ColorBuf> for Rgba {
     ///
     /// [`Camera::post_process_color()`]: crate::camera::Camera::post_process_color()
     fn from(buf: ColorBuf) -> Rgba {
-        if buf.ray_alpha >= 1.0 {
-            // Special case to avoid dividing by zero
+        let color_alpha = 1.0 - buf.ray_alpha;
+        if color_alpha.is_nan() || color_alpha <= f32::EPSILON {
+            // Special case to avoid dividing by zero or by a value so small that the
+            // quotient is meaningless (or infinite). Pixels this close to transparent
+            // are indistinguishable from fully transparent, so treat them as such.
             Rgba::TRANSPARENT
         } else {
-            let color_alpha = 1.0 - buf.ray_alpha;
             let non_premultiplied_color = buf.color_accumulator / color_alpha;
             Rgba::try_from(non_premultiplied_color.extend(color_alpha))
                 .unwrap_or_else(|_| Rgba::new(1.0, 0.0, 0.0, 1.0))
@@ -198,9 +201,158 @@ impl From<ColorBuf> for Rgba {
     }
 }
 
+/// [`RtBlockData`] for [`CoverageBuf`], which distinguishes the sky from everything else.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct CoverageRtData {
+    is_sky: bool,
+}
+
+impl RtBlockData for CoverageRtData {
+    type Options = ();
+
+    fn from_block(_: RtOptionsRef<'_, Self::Options>, _: &SpaceBlockData) -> Self {
+        Self { is_sky: false }
+    }
+
+    fn error(_: RtOptionsRef<'_, Self::Options>) -> Self {
+        Self { is_sky: false }
+    }
+
+    fn sky(_: RtOptionsRef<'_, Self::Options>) -> Self {
+        Self { is_sky: true }
+    }
+}
+
+/// Implements [`Accumulate`] for coverage masks: records only whether the ray hit
+/// any visible surface, not what color it was, and converts to [`bool`].
+///
+/// This is cheaper than [`ColorBuf`] since tracing stops at the first visible surface,
+/// and is suitable for producing silhouettes and alpha masks. The sky does not count as
+/// coverage.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct CoverageBuf {
+    covered: bool,
+}
+
+impl Accumulate for CoverageBuf {
+    type BlockData = CoverageRtData;
+
+    #[inline]
+    fn opaque(&self) -> bool {
+        self.covered
+    }
+
+    #[inline]
+    fn add(&mut self, surface_color: Rgba, block_data: &Self::BlockData) {
+        if !block_data.is_sky && !surface_color.fully_transparent() {
+            self.covered = true;
+        }
+    }
+
+    fn paint(color: Rgba, _: RtOptionsRef<'_, ()>) -> Self {
+        Self {
+            covered: !color.fully_transparent(),
+        }
+    }
+
+    /// Returns a buffer which is covered if at least half of the `items` are.
+    fn mean<const N: usize>(items: [Self; N]) -> Self {
+        let count = items.iter().filter(|buf| buf.covered).count();
+        Self {
+            covered: count * 2 >= N,
+        }
+    }
+}
+
+impl From<CoverageBuf> for bool {
+    /// Returns whether the ray hit any visible surface.
+    #[inline]
+    fn from(buf: CoverageBuf) -> bool {
+        buf.covered
+    }
+}
+
+/// [`RtBlockData`] for [`PickBuf`], which is the block being traced, if it is a block.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct PickRtData {
+    block: Option<Block>,
+}
+
+impl RtBlockData for PickRtData {
+    type Options = ();
+
+    fn from_block(_: RtOptionsRef<'_, Self::Options>, block: &SpaceBlockData) -> Self {
+        Self {
+            block: Some(block.block().clone()),
+        }
+    }
+
+    fn error(_: RtOptionsRef<'_, Self::Options>) -> Self {
+        Self { block: None }
+    }
+
+    fn sky(_: RtOptionsRef<'_, Self::Options>) -> Self {
+        Self { block: None }
+    }
+}
+
+/// Implements [`Accumulate`] for picking: records which block is the first visible
+/// surface the ray hit, and converts to [`Option<Block>`], which is [`None`] if the ray
+/// hit the sky (or anything else that is not a block).
+///
+/// An image of these values can be used to tell which parts of an image rendered with
+/// [`ColorBuf`] show which blocks, such as to find their edges with
+/// [`low_contrast_boundaries()`](super::low_contrast_boundaries).
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct PickBuf {
+    covered: bool,
+    block: Option<Block>,
+}
+
+impl Accumulate for PickBuf {
+    type BlockData = PickRtData;
+
+    #[inline]
+    fn opaque(&self) -> bool {
+        self.covered
+    }
+
+    #[inline]
+    fn add(&mut self, surface_color: Rgba, block_data: &Self::BlockData) {
+        if !self.covered && !surface_color.fully_transparent() {
+            self.covered = true;
+            self.block = block_data.block.clone();
+        }
+    }
+
+    /// Returns the most common of the `items`, preferring earlier ones in case of a tie.
+    fn mean<const N: usize>(items: [Self; N]) -> Self {
+        let count_of = |item: &Self| items.iter().filter(|other| *other == item).count();
+        let mut best: Option<(usize, &Self)> = None;
+        for item in &items {
+            let count = count_of(item);
+            if best.map_or(true, |(best_count, _)| count > best_count) {
+                best = Some((count, item));
+            }
+        }
+        best.map(|(_, item)| item.clone()).unwrap_or_default()
+    }
+}
+
+impl From<PickBuf> for Option<Block> {
+    /// Returns the block the ray hit, if any.
+    #[inline]
+    fn from(buf: PickBuf) -> Option<Block> {
+        buf.block
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::raycast::Ray;
+    use crate::raytracer::SpaceRaytracer;
+    use crate::space::Space;
 
     #[test]
     fn color_buf() {
@@ -234,4 +386,77 @@ mod tests {
         //);
         assert!(buf.opaque());
     }
+
+    /// A partially transparent pixel must convert to non-premultiplied alpha which,
+    /// when composited with the usual “over” operator onto an opaque background,
+    /// gives the same result as continuing to trace into that background.
+    #[test]
+    fn color_buf_composite_over_opaque() {
+        let ui_color = Rgba::new(1.0, 0.0, 0.0, 0.5);
+        let world_color = Rgba::new(0.0, 0.0, 1.0, 1.0);
+        let expected = Rgba::new(0.5, 0.0, 0.5, 1.0);
+
+        let mut ui_buf = ColorBuf::default();
+        ui_buf.add(ui_color, &());
+        let ui_pixel = Rgba::from(ui_buf);
+        assert_eq!(ui_pixel, ui_color);
+
+        // Straight-alpha “over” compositing of the separately rendered UI pixel.
+        let ui_alpha = ui_pixel.alpha().into_inner();
+        let composited = Rgba::try_from(
+            (Vector3::from(ui_pixel.to_rgb()) * ui_alpha
+                + Vector3::from(world_color.to_rgb()) * (1.0 - ui_alpha))
+                .extend(1.0),
+        )
+        .unwrap();
+        assert_eq!(composited, expected);
+
+        // Compositing within the accumulator.
+        ui_buf.add(world_color, &());
+        assert_eq!(Rgba::from(ui_buf), expected);
+    }
+
+    #[test]
+    fn color_buf_nearly_transparent_is_not_error() {
+        let mut buf = ColorBuf::default();
+        buf.add(Rgba::new(1.0, 1.0, 1.0, 1e-7), &());
+        assert_eq!(Rgba::from(buf), Rgba::TRANSPARENT);
+
+        let nan_buf = ColorBuf {
+            color_accumulator: Vector3::zero(),
+            ray_alpha: f32::NAN,
+        };
+        assert_eq!(Rgba::from(nan_buf), Rgba::TRANSPARENT);
+    }
+
+    #[test]
+    fn coverage_buf() {
+        let mut space = Space::empty_positive(1, 1, 1);
+        space.set([0, 0, 0], Block::from(Rgba::BLACK)).unwrap();
+        let rt = SpaceRaytracer::<CoverageRtData>::new(&space, GraphicsOptions::default(), ());
+
+        let (hit, _) =
+            rt.trace_ray::<CoverageBuf>(Ray::new([0.5, 0.5, -1.0], [0.0, 0.0, 1.0]), true);
+        assert!(bool::from(hit));
+        let (sky, _) =
+            rt.trace_ray::<CoverageBuf>(Ray::new([0.5, 5.0, -1.0], [0.0, 0.0, 1.0]), true);
+        assert!(!bool::from(sky));
+    }
+
+    #[test]
+    fn pick_buf() {
+        let block = Block::from(Rgba::BLACK);
+        let mut space = Space::empty_positive(1, 1, 2);
+        space
+            .set([0, 0, 0], Block::from(rgba_const!(1.0, 0.0, 0.0, 0.0)))
+            .unwrap();
+        space.set([0, 0, 1], &block).unwrap();
+        let rt = SpaceRaytracer::<PickRtData>::new(&space, GraphicsOptions::default(), ());
+
+        // The invisible block in front is skipped.
+        let (hit, _) = rt.trace_ray::<PickBuf>(Ray::new([0.5, 0.5, -1.0], [0.0, 0.0, 1.0]), true);
+        assert_eq!(Option::<Block>::from(hit), Some(block));
+        let (sky, _) = rt.trace_ray::<PickBuf>(Ray::new([0.5, 5.0, -1.0], [0.0, 0.0, 1.0]), true);
+        assert_eq!(Option::<Block>::from(sky), None);
+    }
 }
@@ -0,0 +1,161 @@
+//! Finding low-contrast edges in rendered images, for accessibility checking.
+
+use cgmath::Vector2;
+
+use crate::math::{Rgb, Rgba};
+
+/// Finds the boundaries between different objects in a rendered image whose colors
+/// have too little contrast to be easily told apart, and returns an overlay image which
+/// is `highlight` on both sides of each such boundary and transparent elsewhere.
+///
+/// * `size` is the width and height of all three images.
+/// * `colors` is the image as displayed, such as from
+///   [`RtRenderer::draw_rgba_into()`](super::RtRenderer::draw_rgba_into). Alpha is
+///   ignored.
+/// * `ids` identifies which object is visible at each pixel; two adjacent pixels are on
+///   a boundary if their IDs are unequal. For example, this may be an image of
+///   [`Option<Block>`](crate::block::Block) produced by rendering with
+///   [`PickBuf`](super::PickBuf), so that the boundaries are between different blocks
+///   or between blocks and the sky.
+/// * `minimum_contrast_ratio` is the smallest acceptable contrast ratio, as defined
+///   by [WCAG 2], between the colors on either side of a boundary. It ranges from 1
+///   (identical) to 21 (black and white); WCAG recommends at least 3 for graphics.
+///
+/// Pixels are compared with their horizontal and vertical neighbors only.
+///
+/// Panics if `colors` or `ids` does not have the length implied by `size`.
+///
+/// [WCAG 2]: https://www.w3.org/TR/WCAG21/#dfn-contrast-ratio
+pub fn low_contrast_boundaries<I: PartialEq>(
+    size: Vector2<u32>,
+    colors: &[Rgba],
+    ids: &[I],
+    minimum_contrast_ratio: f32,
+    highlight: Rgba,
+) -> Vec<Rgba> {
+    let width = size.x as usize;
+    let length = width * size.y as usize;
+    assert_eq!(colors.len(), length, "colors has wrong length for {size:?}");
+    assert_eq!(ids.len(), length, "ids has wrong length for {size:?}");
+
+    let mut overlay = vec![Rgba::TRANSPARENT; length];
+    for index in 0..length {
+        // Check the neighbor to the right (if not at the right edge) and the one below.
+        let right = Some(index + 1).filter(|_| (index + 1) % width != 0);
+        let below = Some(index + width).filter(|&i| i < length);
+        for neighbor in [right, below].into_iter().flatten() {
+            if ids[index] != ids[neighbor]
+                && contrast_ratio(colors[index].to_rgb(), colors[neighbor].to_rgb())
+                    < minimum_contrast_ratio
+            {
+                overlay[index] = highlight;
+                overlay[neighbor] = highlight;
+            }
+        }
+    }
+    overlay
+}
+
+/// Contrast ratio between two linear colors, as defined by WCAG 2.
+fn contrast_ratio(a: Rgb, b: Rgb) -> f32 {
+    let a = a.luminance() + 0.05;
+    let b = b.luminance() + 0.05;
+    a.max(b) / a.min(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::Block;
+    use crate::camera::{GraphicsOptions, LightingOption, StandardCameras, Viewport};
+    use crate::character::{Character, Spawn};
+    use crate::listen::ListenableSource;
+    use crate::math::GridAab;
+    use crate::raytracer::{PickBuf, PickRtData, RtRenderer};
+    use crate::space::Space;
+    use crate::universe::Universe;
+
+    #[test]
+    fn contrast_ratio_extremes() {
+        let black_white = contrast_ratio(Rgb::ZERO, Rgb::ONE);
+        assert!((black_white - 21.0).abs() < 1e-5, "{black_white}");
+        assert_eq!(contrast_ratio(Rgb::ONE, Rgb::ZERO), black_white);
+        assert_eq!(contrast_ratio(Rgb::ONE, Rgb::ONE), 1.0);
+    }
+
+    /// Renders a row of four blocks, two near-identical grays followed by black and
+    /// white, and checks which of the boundaries between them are flagged.
+    #[test]
+    fn gray_boundary_is_flagged_and_black_white_is_not() {
+        let gray_1 = Block::from(rgb_const!(0.5, 0.5, 0.5));
+        let gray_2 = Block::from(rgb_const!(0.52, 0.52, 0.52));
+        let black = Block::from(Rgb::ZERO);
+        let white = Block::from(Rgb::ONE);
+
+        let mut universe = Universe::new();
+        let bounds = GridAab::from_lower_size([0, 0, 0], [4, 1, 1]);
+        let mut space = Space::builder(bounds)
+            .sky_color(rgb_const!(0.2, 0.2, 0.2))
+            .spawn(Spawn::looking_at_space(bounds, [0., 0., 1.]))
+            .build();
+        for (x, block) in [&gray_1, &gray_2, &black, &white].into_iter().enumerate() {
+            space.set([x as i32, 0, 0], block).unwrap();
+        }
+        let space = universe.insert_anonymous(space);
+        universe
+            .insert("character".into(), Character::spawn_default(space))
+            .unwrap();
+
+        // Display the blocks' colors unaltered by lighting.
+        let options = GraphicsOptions {
+            lighting_display: LightingOption::None,
+            ..GraphicsOptions::default()
+        };
+        let cameras = || {
+            StandardCameras::from_constant_for_test(
+                options.clone(),
+                Viewport::with_scale(1.0, Vector2::new(64, 16)),
+                &universe,
+            )
+        };
+        let mut color_renderer =
+            RtRenderer::new(cameras(), Box::new(|v| v), ListenableSource::constant(()));
+        let mut pick_renderer = RtRenderer::<PickRtData>::new(
+            cameras(),
+            Box::new(|v| v),
+            ListenableSource::constant(()),
+        );
+        color_renderer.update(None).unwrap();
+        pick_renderer.update(None).unwrap();
+        let size = color_renderer.modified_viewport().framebuffer_size;
+        let length = (size.x * size.y) as usize;
+        let mut colors = vec![Rgba::TRANSPARENT; length];
+        color_renderer
+            .draw_rgba_into(|_| String::new(), &mut colors)
+            .unwrap();
+        let mut ids: Vec<Option<Block>> = vec![None; length];
+        pick_renderer.draw::<PickBuf, _, _, _>(|_| String::new(), Option::from, &mut ids);
+
+        let overlay = low_contrast_boundaries(size, &colors, &ids, 3.0, Rgba::WHITE);
+
+        let mut gray_boundary_seen = false;
+        let mut black_white_boundary_seen = false;
+        for (index, pair) in ids.windows(2).enumerate() {
+            let flagged = overlay[index] == Rgba::WHITE && overlay[index + 1] == Rgba::WHITE;
+            if pair == [Some(gray_1.clone()), Some(gray_2.clone())] {
+                gray_boundary_seen = true;
+                assert!(flagged, "gray boundary at {index} not flagged");
+            }
+            if pair == [Some(black.clone()), Some(white.clone())] {
+                black_white_boundary_seen = true;
+            }
+        }
+        assert!(gray_boundary_seen && black_white_boundary_seen);
+        // The sky contrasts with both black and white, so neither should be flagged at all.
+        for (index, id) in ids.iter().enumerate() {
+            if *id == Some(black.clone()) || *id == Some(white.clone()) {
+                assert_eq!(overlay[index], Rgba::TRANSPARENT, "{index} {id:?}");
+            }
+        }
+    }
+}
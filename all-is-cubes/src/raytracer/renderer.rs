@@ -1,8 +1,10 @@
 use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
 
-use cgmath::{ElementWise, Point2, Vector2};
+use cgmath::{ElementWise, Matrix4, Point2, Vector2};
 use futures_core::future::BoxFuture;
 use ordered_float::NotNan;
+use rand::SeedableRng as _;
 
 use crate::camera::{
     AntialiasingOption, Camera, Flaws, FogOption, GraphicsOptions, HeadlessRenderer, Layers,
@@ -11,10 +13,10 @@ use crate::camera::{
 use crate::character::Cursor;
 use crate::content::palette;
 use crate::listen::ListenableSource;
-use crate::math::Rgba;
+use crate::math::{FaceMap, FreeCoordinate, GridAab, Rgba};
 use crate::raytracer::{
-    Accumulate, ColorBuf, RaytraceInfo, RtBlockData, RtOptionsRef, SpaceRaytracer,
-    UpdatingSpaceRaytracer,
+    Accumulate, Background, ColorBuf, RaytraceInfo, RtBlockData, RtOptionsRef, SpaceRaytracer,
+    StochasticRng, UpdatingSpaceRaytracer,
 };
 use crate::space::Space;
 use crate::universe::URef;
@@ -24,6 +26,10 @@ use crate::universe::URef;
 pub struct RtRenderer<D: RtBlockData = ()> {
     rts: Layers<Option<UpdatingSpaceRaytracer<D>>>,
 
+    /// Raytracers for [`StandardCameras::space_layers()`], in the same order.
+    /// (The [`Option`] is always [`Some`] after [`Self::update()`].)
+    space_layer_rts: Vec<Option<UpdatingSpaceRaytracer<D>>>,
+
     cameras: StandardCameras,
 
     /// Adjusts the `cameras` viewport to control how many pixels are actually traced.
@@ -32,16 +38,45 @@ pub struct RtRenderer<D: RtBlockData = ()> {
 
     custom_options: ListenableSource<D::Options>,
 
+    /// If set, replaces the world space's sky color as the background.
+    background: Option<Background>,
+
+    /// If set, the image is letterboxed with bars of this color;
+    /// see [`Self::set_letterbox()`].
+    letterbox: Option<Rgba>,
+
     /// Whether there was a [`Cursor`] to be drawn.
     /// Raytracing doesn't yet support cursors but we need to report that.
     had_cursor: bool,
+
+    /// Whether [`Self::draw()`] may skip tracing pixels which cannot have changed;
+    /// see [`Self::set_incremental()`].
+    incremental: bool,
+
+    /// Whether to use stochastic transparency when antialiasing;
+    /// see [`Self::set_stochastic_transparency()`].
+    stochastic_transparency: bool,
+
+    /// What has changed since the last [`Self::draw()`].
+    redraw: Redraw,
+
+    /// Set by [`Self::draw()`] so that the next [`Self::update()`] knows to discard the
+    /// already-drawn changes in `redraw`.
+    drawn: AtomicBool,
+
+    /// Whether the last incremental [`Self::draw()`] drew info text over the image,
+    /// which must then be erased by a full redraw.
+    drew_info_text: AtomicBool,
 }
 
 impl<D: RtBlockData> RtRenderer<D>
 where
     D::Options: Clone + Sync + 'static,
 {
-    /// * `cameras`: Scene to draw.
+    /// * `cameras`: Scene to draw. If it has a UI space, that space is drawn over the
+    ///   world space, which shows through any transparent parts of it.
+    ///   Its [space layers](StandardCameras::space_layers), if any, are drawn between
+    ///   the world and the UI in the same fashion.
     /// * `size_policy`: Modifier to the `cameras`' provided viewport to control how many
     ///    pixels are actually traced.
     /// * `custom_options`: The custom options for the `D` block data type; see
@@ -53,13 +88,85 @@ where
     ) -> Self {
         RtRenderer {
             rts: Layers::<Option<_>>::default(),
+            space_layer_rts: Vec::new(),
             cameras,
             size_policy,
             custom_options,
+            background: None,
+            letterbox: None,
             had_cursor: false,
+            incremental: false,
+            stochastic_transparency: false,
+            redraw: Redraw::default(),
+            drawn: AtomicBool::new(false),
+            drew_info_text: AtomicBool::new(false),
         }
     }
 
+    /// Sets the background to draw behind the world space, wherever nothing in it blocks
+    /// the view. If [`None`], which is the default, the space's sky color is used.
+    ///
+    /// This does not affect the lighting of the world.
+    pub fn set_background(&mut self, background: Option<Background>) {
+        self.background = background;
+        self.redraw_everything();
+    }
+
+    /// Sets whether to preserve the aspect ratio of the scene when the viewport's
+    /// `framebuffer_size` does not have the same aspect ratio as its `nominal_size`.
+    ///
+    /// If [`None`], which is the default, the scene fills the whole image, and is
+    /// stretched if the aspect ratios differ (that is, the framebuffer is taken to have
+    /// non-square pixels). If [`Some`], the framebuffer is taken to have square pixels,
+    /// the scene is drawn in the region given by [`Viewport::letterboxed()`], and the
+    /// rest of the image is filled with bars of the given color (which may be
+    /// transparent). Like the info text, the bars are encoded in the same way as the
+    /// rest of the image, so for example the `draw_rgba` functions apply
+    /// [`Camera::post_process_color()`] to them.
+    pub fn set_letterbox(&mut self, bar_color: Option<Rgba>) {
+        self.letterbox = bar_color;
+        self.redraw_everything();
+    }
+
+    /// Sets whether [`Self::draw()`] and the other drawing functions which write into a
+    /// provided buffer should trace only those pixels whose rays pass through or next to
+    /// cubes which have changed since the previous drawing, leaving the rest of the
+    /// buffer unchanged. Any change to the cameras or options causes a full redraw.
+    ///
+    /// This is only correct if each call is given the same buffer, containing the
+    /// previously drawn image, and the same encoding function. If debug info text is
+    /// drawn, the next drawing is a full redraw.
+    ///
+    /// The default is `false`, in which case every pixel is traced every time.
+    pub fn set_incremental(&mut self, incremental: bool) {
+        self.incremental = incremental;
+        self.redraw_everything();
+    }
+
+    /// Sets whether transparent surfaces are drawn using _stochastic transparency_:
+    /// instead of blending each surface's color with what is behind it, each sample
+    /// either stops at the surface, with probability equal to its opacity, or passes
+    /// through it. Averaged over many samples, the result is the same as blending, but
+    /// does not depend on the order in which surfaces are blended.
+    ///
+    /// This has an effect only when antialiasing is enabled (by
+    /// [`AntialiasingOption::Always`]), since that is when multiple samples are taken
+    /// per pixel; with few samples, the result is noisy. The random choices are
+    /// determined by the pixel position, so that drawing the same scene twice gives the
+    /// same image.
+    ///
+    /// The default is `false`.
+    pub fn set_stochastic_transparency(&mut self, stochastic_transparency: bool) {
+        self.stochastic_transparency = stochastic_transparency;
+        self.redraw_everything();
+    }
+
+    fn redraw_everything(&mut self) {
+        // Keep, rather than discard, what is accumulated after this.
+        *self.drawn.get_mut() = false;
+        self.redraw.everything = true;
+    }
+
     /// Update the renderer's internal copy of the scene from the data sources
     /// (`URef<Character>` etc.) it is tracking.
     ///
@@ -69,16 +176,25 @@ where
     /// This method is equivalent to [`HeadlessRenderer::update()`] except for
     /// fitting the raytracer's needs and capabilities (works with all types;
     /// not `async`).
+    ///
+    /// If `cursor` is [`None`], the cursor from [`StandardCameras::cursor()`] is used
+    /// instead.
     pub fn update(&mut self, cursor: Option<&Cursor>) -> Result<(), RenderError> {
-        // TODO: raytracer needs to implement drawing the cursor
-        self.had_cursor = cursor.is_some();
         self.cameras.update();
+        // TODO: raytracer needs to implement drawing the cursor
+        self.had_cursor = cursor.or(self.cameras.cursor()).is_some();
+
+        if self.drawn.swap(false, Ordering::Relaxed) {
+            self.redraw.everything = false;
+            self.redraw.regions.clear();
+        }
 
         fn sync_space<D: RtBlockData>(
             cached_rt: &mut Option<UpdatingSpaceRaytracer<D>>,
             optional_space: Option<&URef<Space>>,
             graphics_options_source: &ListenableSource<GraphicsOptions>,
             custom_options_source: &ListenableSource<D::Options>,
+            redraw_everything: &mut bool,
         ) -> Result<(), RenderError>
         where
             D::Options: Clone + Sync + 'static,
@@ -95,10 +211,15 @@ where
                         space.clone(),
                         graphics_options_source.clone(),
                         custom_options_source.clone(),
-                    ))
+                    ));
+                    *redraw_everything = true;
                 }
                 // Space is None, so drop raytracer if any
-                (None, c) => *c = None,
+                (None, c) => {
+                    if c.take().is_some() {
+                        *redraw_everything = true;
+                    }
+                }
             }
             // Now that we have one if we should have one, update it.
             if let Some(rt) = cached_rt {
@@ -107,19 +228,63 @@ where
             Ok(())
         }
         let gs = self.cameras.graphics_options_source();
+        let redraw_everything = &mut self.redraw.everything;
         sync_space(
             &mut self.rts.world,
             Option::as_ref(&self.cameras.world_space().get()),
             &gs,
             &self.custom_options,
+            redraw_everything,
         )?;
         sync_space(
             &mut self.rts.ui,
             self.cameras.ui_space(),
             &gs,
             &self.custom_options,
+            redraw_everything,
         )?;
 
+        let space_layers = self.cameras.space_layers();
+        if self.space_layer_rts.len() != space_layers.len() {
+            self.space_layer_rts
+                .resize_with(space_layers.len(), || None);
+            *redraw_everything = true;
+        }
+        for (cached_rt, (space, _)) in self.space_layer_rts.iter_mut().zip(space_layers) {
+            sync_space(
+                cached_rt,
+                Some(space),
+                &gs,
+                &self.custom_options,
+                redraw_everything,
+            )?;
+        }
+
+        // Collect the changed regions of all spaces.
+        let rts = [&mut self.rts.world, &mut self.rts.ui]
+            .into_iter()
+            .chain(self.space_layer_rts.iter_mut());
+        for (i, rt) in rts.enumerate() {
+            if self.redraw.regions.len() <= i {
+                self.redraw.regions.push(None);
+            }
+            let region = &mut self.redraw.regions[i];
+            if let Some(new) = rt.as_mut().and_then(|rt| rt.take_dirty_region()) {
+                *region = Some(match *region {
+                    // Overflow is only possible if the space was replaced, in which
+                    // case everything is redrawn anyway.
+                    Some(old) => old.union(new).unwrap_or(new),
+                    None => new,
+                });
+            }
+        }
+
+        let frame = Some(FrameKey::new(&self.cameras, self.modified_viewport()));
+        if frame != self.redraw.frame {
+            self.redraw.everything = true;
+            self.redraw.frame = frame;
+        }
+
         Ok(())
     }
 
@@ -134,6 +299,9 @@ where
     /// This operation does not attempt to access the scene objects and therefore may be
     /// called while the [`Universe`] is being stepped, etc.
     ///
+    /// If [incremental drawing](Self::set_incremental) is enabled, `output` must contain
+    /// the image from the previous call.
+    ///
     /// This method is equivalent to [`HeadlessRenderer::draw()`] except that it works
     /// with any [`Accumulate`] instead of requiring [`ColorBuf`] and [`Rgba`] output,
     /// is not async, and does not require `&mut self`.
@@ -145,35 +313,113 @@ where
         E: Fn(P) -> O + Send + Sync,
         O: Clone + Send + Sync, // Clone is used in the no-data case
         IF: FnOnce(&RaytraceInfo) -> String,
+    {
+        self.draw_impl(info_text_fn, encoder, output, self.incremental)
+    }
+
+    fn draw_impl<P, E, O, IF>(
+        &self,
+        info_text_fn: IF,
+        encoder: E,
+        output: &mut [O],
+        incremental: bool,
+    ) -> RaytraceInfo
+    where
+        P: Accumulate<BlockData = D>,
+        E: Fn(P) -> O + Send + Sync,
+        O: Clone + Send + Sync,
+        IF: FnOnce(&RaytraceInfo) -> String,
     {
         let mut cameras = self.cameras.cameras().clone();
         let viewport = (self.size_policy)(cameras.world.viewport());
-        cameras.world.set_viewport(viewport);
-        cameras.ui.set_viewport(viewport);
         assert_eq!(
             viewport.pixel_count(),
             Some(output.len()),
             "Viewport size does not match output buffer length",
         );
+        let (letterbox, traced_viewport) = match self.letterbox {
+            Some(bar_color) => {
+                let (offset, traced_viewport) = viewport.letterboxed();
+                let letterbox = Letterbox {
+                    output_size: viewport.framebuffer_size,
+                    offset,
+                    bar_color,
+                };
+                (Some(letterbox), traced_viewport)
+            }
+            None => (None, viewport),
+        };
+        cameras.world.set_viewport(traced_viewport);
+        cameras.ui.set_viewport(traced_viewport);
 
         let options = RtOptionsRef {
             graphics_options: self.cameras.graphics_options(),
             custom_options: &*self.custom_options.get(),
         };
 
+        let space_layer_cameras: Vec<Camera> = self
+            .cameras
+            .space_layers()
+            .iter()
+            .map(|(_, camera)| {
+                let mut camera = camera.clone();
+                camera.set_viewport(traced_viewport);
+                camera
+            })
+            .collect();
+        let space_layers: Vec<(&SpaceRaytracer<D>, &Camera)> = self
+            .space_layer_rts
+            .iter()
+            .zip(&space_layer_cameras)
+            .filter_map(|(opt_urt, camera)| Some((opt_urt.as_ref()?.get(), camera)))
+            .collect();
+
+        let redraw_regions: Option<Vec<(&Camera, GridAab)>> = if incremental
+            && !self.redraw.everything
+            && !self.drew_info_text.load(Ordering::Relaxed)
+        {
+            let all_cameras = [&cameras.world, &cameras.ui]
+                .into_iter()
+                .chain(&space_layer_cameras);
+            Some(
+                all_cameras
+                    .zip(&self.redraw.regions)
+                    .filter_map(|(camera, &region)| {
+                        // Changes may affect the lighting of neighboring cubes' faces.
+                        Some((camera, region?.expand(FaceMap::repeat(1))))
+                    })
+                    .collect(),
+            )
+        } else {
+            None
+        };
+        if incremental {
+            self.drawn.store(true, Ordering::Relaxed);
+        }
+
         let scene = RtScene {
             rts: self
                 .rts
                 .as_refs()
                 .map(|opt_urt| opt_urt.as_ref().map(|urt| urt.get())),
+            space_layers: &space_layers,
             cameras: &cameras,
+            background: self.background.as_ref(),
+            letterbox,
+            redraw_regions: redraw_regions.as_deref(),
+            stochastic_transparency: self.stochastic_transparency,
             options,
         };
 
         let info = trace_image::trace_scene_to_image_impl(scene, &encoder, output);
 
         let info_text: String = info_text_fn(&info);
-        if !info_text.is_empty() && self.cameras.cameras().world.options().debug_info_text {
+        let draw_info_text =
+            !info_text.is_empty() && self.cameras.cameras().world.options().debug_info_text;
+        if incremental {
+            self.drew_info_text.store(draw_info_text, Ordering::Relaxed);
+        }
+        if draw_info_text {
             eg::draw_info_text(
                 output,
                 viewport,
@@ -208,21 +454,92 @@ impl RtRenderer<()> {
     /// As [`Self::draw()`], but the output is an [`Rendering`], and
     /// [`Camera::post_process_color()`] is applied to the pixels.
     ///
+    /// To reuse an image buffer instead of allocating a new one for each frame, use
+    /// [`Self::draw_srgb8_into()`] instead.
+    ///
     ///  [`Camera::post_process_color()`]: crate::camera::Camera::post_process_color
     pub fn draw_rgba(
         &self,
         info_text_fn: impl FnOnce(&RaytraceInfo) -> String,
     ) -> (Rendering, RaytraceInfo) {
-        let camera = self.cameras.cameras().world.clone();
         let size = self.modified_viewport().framebuffer_size;
 
         let mut data = vec![[0; 4]; usize::try_from(size.x * size.y).unwrap()];
-        let info = self.draw::<ColorBuf, _, [u8; 4], _>(
+        let (flaws, info) = self
+            .draw_srgb8_into_impl(info_text_fn, &mut data, false)
+            .expect("buffer was allocated with the wrong length");
+
+        (Rendering { size, data, flaws }, info)
+    }
+
+    /// As [`Self::draw_rgba()`], but writes the image into `output` instead of allocating
+    /// a new buffer, so that the same buffer can be reused for many frames.
+    /// The pixel values are linear colors, after [`Camera::post_process_color()`].
+    ///
+    /// `output` must have exactly as many elements as the [`Self::modified_viewport()`]
+    /// has pixels; if it does not, returns an error without drawing anything.
+    ///
+    /// Returns the flaws of the image and information about the tracing.
+    ///
+    ///  [`Camera::post_process_color()`]: crate::camera::Camera::post_process_color
+    pub fn draw_rgba_into(
+        &self,
+        info_text_fn: impl FnOnce(&RaytraceInfo) -> String,
+        output: &mut [Rgba],
+    ) -> Result<(Flaws, RaytraceInfo), BufferLengthError> {
+        let camera = self.cameras.cameras().world.clone();
+        self.check_buffer_length(output.len())?;
+        let info = self.draw_impl::<ColorBuf, _, Rgba, _>(
+            info_text_fn,
+            |pixel_buf| camera.post_process_color(Rgba::from(pixel_buf)),
+            output,
+            self.incremental,
+        );
+        Ok((self.flaws(), info))
+    }
+
+    /// As [`Self::draw_rgba_into()`], but the pixels are written as 8-bit sRGB values,
+    /// as in [`Rendering::data`].
+    pub fn draw_srgb8_into(
+        &self,
+        info_text_fn: impl FnOnce(&RaytraceInfo) -> String,
+        output: &mut [[u8; 4]],
+    ) -> Result<(Flaws, RaytraceInfo), BufferLengthError> {
+        self.draw_srgb8_into_impl(info_text_fn, output, self.incremental)
+    }
+
+    fn draw_srgb8_into_impl(
+        &self,
+        info_text_fn: impl FnOnce(&RaytraceInfo) -> String,
+        output: &mut [[u8; 4]],
+        incremental: bool,
+    ) -> Result<(Flaws, RaytraceInfo), BufferLengthError> {
+        let camera = self.cameras.cameras().world.clone();
+        self.check_buffer_length(output.len())?;
+        let info = self.draw_impl::<ColorBuf, _, [u8; 4], _>(
             info_text_fn,
             |pixel_buf| camera.post_process_color(Rgba::from(pixel_buf)).to_srgb8(),
-            &mut data,
+            output,
+            incremental,
         );
+        Ok((self.flaws(), info))
+    }
 
+    fn check_buffer_length(&self, buffer_length: usize) -> Result<(), BufferLengthError> {
+        let viewport = self.modified_viewport();
+        if viewport.pixel_count() == Some(buffer_length) {
+            Ok(())
+        } else {
+            Err(BufferLengthError {
+                buffer_length,
+                size: viewport.framebuffer_size,
+            })
+        }
+    }
+
+    /// Flaws of images drawn with the current options, which the raytracer does not
+    /// implement.
+    fn flaws(&self) -> Flaws {
         let options = self.cameras.graphics_options();
         let mut flaws = Flaws::empty();
         if options.bloom_intensity != NotNan::from(0u8) {
@@ -234,11 +551,23 @@ impl RtRenderer<()> {
         if !matches!(options.fog, FogOption::None) {
             flaws |= Flaws::NO_FOG;
         }
-
-        (Rendering { size, data, flaws }, info)
+        flaws
     }
 }
 
+/// Error from [`RtRenderer::draw_rgba_into()`] or [`RtRenderer::draw_srgb8_into()`]
+/// being given a buffer of the wrong length.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, thiserror::Error)]
+#[error(
+    "buffer of length {buffer_length} cannot hold image of size {w}×{h}",
+    w = self.size.x,
+    h = self.size.y
+)]
+pub struct BufferLengthError {
+    buffer_length: usize,
+    size: Vector2<u32>,
+}
+
 // manual impl avoids `D: Debug` bound
 impl<D: RtBlockData> fmt::Debug for RtRenderer<D>
 where
@@ -249,6 +578,11 @@ where
         f.debug_struct("RtRenderer")
             .field("cameras", &self.cameras)
             .field("rts", &self.rts)
+            .field("space_layer_rts", &self.space_layer_rts)
+            .field("background", &self.background)
+            .field("letterbox", &self.letterbox)
+            .field("incremental", &self.incremental)
+            .field("redraw", &self.redraw)
             .finish()
     }
 }
@@ -270,6 +604,45 @@ impl HeadlessRenderer for RtRenderer<()> {
     }
 }
 
+/// Changes which [`RtRenderer::draw()`] must retrace when drawing incrementally.
+#[derive(Debug, Default)]
+struct Redraw {
+    /// Every pixel must be retraced.
+    everything: bool,
+    /// Changed region of each space, in the order world, UI, then space layers.
+    regions: Vec<Option<GridAab>>,
+    /// Frame parameters as of the last update, to detect changes.
+    frame: Option<FrameKey>,
+}
+
+/// The parameters other than space contents which affect every pixel of an image
+/// drawn by [`RtRenderer`].
+#[derive(Debug, PartialEq)]
+struct FrameKey {
+    viewport: Viewport,
+    graphics_options: GraphicsOptions,
+    exposure: NotNan<f32>,
+    /// View and projection matrices of each camera, in the same order as
+    /// [`Redraw::regions`].
+    matrices: Vec<[Matrix4<FreeCoordinate>; 2]>,
+}
+
+impl FrameKey {
+    fn new(cameras: &StandardCameras, viewport: Viewport) -> Self {
+        let Layers { world, ui } = cameras.cameras();
+        Self {
+            viewport,
+            graphics_options: cameras.graphics_options().clone(),
+            exposure: world.exposure(),
+            matrices: [world, ui]
+                .into_iter()
+                .chain(cameras.space_layers().iter().map(|(_, camera)| camera))
+                .map(|camera| [camera.view_matrix(), camera.projection()])
+                .collect(),
+        }
+    }
+}
+
 /// Bundle of references to the current scene data in a [`RtRenderer`],
 /// used to implement tracing individual rays independent of how they
 /// are assembled into an image. Differs from [`SpaceRaytracer::trace_ray`]
@@ -277,8 +650,18 @@ impl HeadlessRenderer for RtRenderer<()> {
 /// rather than a lay) and [`Layers`] rather than one space.
 struct RtScene<'a, P: Accumulate> {
     rts: Layers<Option<&'a SpaceRaytracer<P::BlockData>>>,
-    /// Cameras *with* size_policy applied.
+    /// Space layers and their cameras, back to front, with `size_policy` applied.
+    space_layers: &'a [(&'a SpaceRaytracer<P::BlockData>, &'a Camera)],
+    /// Cameras *with* `size_policy` and letterboxing applied.
     cameras: &'a Layers<Camera>,
+    background: Option<&'a Background>,
+    /// If not [`None`], the cameras' viewport is only part of the output image.
+    letterbox: Option<Letterbox>,
+    /// If not [`None`], only pixels whose rays pass through these regions, as seen by
+    /// these cameras, need to be traced.
+    redraw_regions: Option<&'a [(&'a Camera, GridAab)]>,
+    /// Whether to use stochastic transparency when antialiasing.
+    stochastic_transparency: bool,
     options: RtOptionsRef<'a, <P::BlockData as RtBlockData>::Options>,
 }
 
@@ -290,61 +673,204 @@ impl<'a, P: Accumulate> Clone for RtScene<'a, P> {
 impl<P: Accumulate> Copy for RtScene<'_, P> {}
 
 impl<P: Accumulate> RtScene<'_, P> {
+    /// Returns the size of the image to be produced, which is the cameras'
+    /// `framebuffer_size` unless letterboxing.
+    fn output_size(&self) -> Vector2<usize> {
+        match self.letterbox {
+            Some(letterbox) => letterbox.output_size,
+            None => self.cameras.world.viewport().framebuffer_size,
+        }
+        .map(|s| s as usize)
+    }
+
     /// Called from threaded or non-threaded `trace_scene_to_image()` implementations
-    /// to produce a single image pixel.
+    /// to produce the output pixel at `(x, y)`.
+    ///
+    /// Returns [`None`] if [`Self::patch_needs_trace()`] excludes the pixel.
     #[inline]
-    fn trace_patch(&self, patch: NdcRect) -> (P, RaytraceInfo) {
-        if let Some(ui) = self.rts.ui {
-            let (pixel, info): (P, RaytraceInfo) =
-                trace_patch_in_one_space(ui, &self.cameras.ui, patch, false);
-            if pixel.opaque() {
-                // TODO: We should be doing alpha blending, but doing that requires
-                // having control over the `Accumulate` that trace_ray starts with.
-                return (pixel, info);
-            }
-        }
-        if let Some(world) = self.rts.world {
-            return trace_patch_in_one_space(world, &self.cameras.world, patch, true);
+    fn draw_pixel(&self, x: usize, y: usize) -> Option<(P, RaytraceInfo)> {
+        match self.pixel_patch(x, y) {
+            Ok(patch) => self
+                .patch_needs_trace(patch)
+                .then(|| self.trace_patch(patch)),
+            Err(bar_color) => Some((P::paint(bar_color, self.options), RaytraceInfo::default())),
         }
-        (
-            P::paint(palette::NO_WORLD_TO_SHOW, self.options),
-            RaytraceInfo::default(),
-        )
     }
-}
 
-fn trace_patch_in_one_space<P: Accumulate>(
-    space: &SpaceRaytracer<<P as Accumulate>::BlockData>,
-    camera: &Camera,
-    patch: NdcRect,
-    include_sky: bool,
-) -> (P, RaytraceInfo) {
-    match camera.options().antialiasing {
-        AntialiasingOption::None | AntialiasingOption::IfCheap => {
-            space.trace_ray(camera.project_ndc_into_world(patch.center()), include_sky)
-        }
-        AntialiasingOption::Always => {
-            const N: usize = 4;
-            const SAMPLE_POINTS: [Vector2<f64>; N] = [
-                Vector2::new(1. / 8., 5. / 8.),
-                Vector2::new(3. / 8., 1. / 8.),
-                Vector2::new(5. / 8., 7. / 8.),
-                Vector2::new(7. / 8., 3. / 8.),
-            ];
+    /// Returns the region which the output pixel at `(x, y)` covers, or the color of
+    /// the letterbox bar it is part of.
+    #[inline]
+    fn pixel_patch(&self, x: usize, y: usize) -> Result<NdcRect, Rgba> {
+        let viewport = self.cameras.world.viewport();
+        let (x, y) = match self.letterbox {
+            Some(Letterbox {
+                offset, bar_color, ..
+            }) => {
+                let within = |coord: usize, offset: u32, size: u32| {
+                    coord
+                        .checked_sub(offset as usize)
+                        .filter(|&c| c < size as usize)
+                };
+                match (
+                    within(x, offset.x, viewport.framebuffer_size.x),
+                    within(y, offset.y, viewport.framebuffer_size.y),
+                ) {
+                    (Some(x), Some(y)) => (x, y),
+                    _ => return Err(bar_color),
+                }
+            }
+            None => (x, y),
+        };
+        Ok(NdcRect {
+            low: Point2::new(
+                viewport.normalize_fb_x_edge(x),
+                viewport.normalize_fb_y_edge(y),
+            ),
+            high: Point2::new(
+                viewport.normalize_fb_x_edge(x + 1),
+                viewport.normalize_fb_y_edge(y + 1),
+            ),
+        })
+    }
+
+    #[inline]
+    fn trace_patch(&self, patch: NdcRect) -> (P, RaytraceInfo) {
+        if self.antialias() {
             let mut info = RaytraceInfo::default();
-            let samples: [P; N] = std::array::from_fn(|i| {
-                let (p, i) = space.trace_ray(
-                    camera.project_ndc_into_world(patch.point_within(SAMPLE_POINTS[i])),
-                    include_sky,
-                );
+            let samples: [P; SAMPLE_POINTS.len()] = std::array::from_fn(|i| {
+                let mut rng = self
+                    .stochastic_transparency
+                    .then(|| patch.sample_rng(i as u64));
+                let (p, i) = self.trace_point(patch.point_within(SAMPLE_POINTS[i]), rng.as_mut());
                 info += i;
                 p
             });
             (P::mean(samples), info)
+        } else {
+            self.trace_point(patch.center(), None)
+        }
+    }
+
+    /// Returns whether the pixel `patch` must be traced, rather than keeping its
+    /// previous value, because some of the rays [`Self::trace_patch()`] would trace
+    /// pass through a changed region.
+    #[inline]
+    fn patch_needs_trace(&self, patch: NdcRect) -> bool {
+        let Some(regions) = self.redraw_regions else {
+            return true;
+        };
+        let point_needs_trace = |ndc: Point2<f64>| {
+            regions.iter().any(|&(camera, region)| {
+                camera
+                    .project_ndc_into_world(ndc)
+                    .cast()
+                    .within(region)
+                    .next()
+                    .is_some()
+            })
+        };
+        if self.antialias() {
+            SAMPLE_POINTS
+                .iter()
+                .any(|&uv| point_needs_trace(patch.point_within(uv)))
+        } else {
+            point_needs_trace(patch.center())
+        }
+    }
+
+    fn antialias(&self) -> bool {
+        // The UI and world must be sampled at the same points so that the UI can be
+        // composited over the world, so antialias both if either wants it.
+        [&self.cameras.ui, &self.cameras.world]
+            .into_iter()
+            .any(|camera| camera.options().antialiasing == AntialiasingOption::Always)
+    }
+
+    /// Trace the UI and then, if it is not opaque at this point, the space layers and
+    /// world behind it.
+    #[inline]
+    fn trace_point(
+        &self,
+        ndc: Point2<f64>,
+        mut stochastic_rng: Option<&mut StochasticRng>,
+    ) -> (P, RaytraceInfo) {
+        let mut info = RaytraceInfo::default();
+        let mut accumulator = P::default();
+
+        if let Some(ui) = self.rts.ui {
+            let (ui_pixel, ui_info): (P, RaytraceInfo) = ui.trace_ray_onto(
+                self.cameras.ui.project_ndc_into_world(ndc),
+                P::default(),
+                Rgba::TRANSPARENT,
+                stochastic_rng.as_deref_mut(),
+            );
+            if ui_pixel.opaque() {
+                return (ui_pixel, ui_info);
+            }
+            // If the ray missed the UI space entirely, then `ui_pixel` has been told so via
+            // `hit_nothing()`, which would suppress the world; so don't use it.
+            if ui_info != RaytraceInfo::default() {
+                accumulator = ui_pixel;
+            }
+            info += ui_info;
+        }
+
+        // Space layers are listed back to front, so trace them in reverse.
+        for &(layer, camera) in self.space_layers.iter().rev() {
+            if accumulator.opaque() {
+                return (accumulator, info);
+            }
+            let (pixel, layer_info) = layer.trace_ray_partial(
+                camera.project_ndc_into_world(ndc),
+                accumulator,
+                stochastic_rng.as_deref_mut(),
+            );
+            accumulator = pixel;
+            info += layer_info;
+        }
+
+        if let Some(world) = self.rts.world {
+            let ray = self.cameras.world.project_ndc_into_world(ndc);
+            let sky_color = match self.background {
+                Some(background) => background.color_with_alpha(ray.direction),
+                None => world.sky_color().with_alpha_one(),
+            };
+            let (pixel, world_info) =
+                world.trace_ray_onto(ray, accumulator, sky_color, stochastic_rng);
+            info += world_info;
+            (pixel, info)
+        } else if info == RaytraceInfo::default() {
+            (P::paint(palette::NO_WORLD_TO_SHOW, self.options), info)
+        } else {
+            accumulator.add(
+                palette::NO_WORLD_TO_SHOW,
+                &<P::BlockData as RtBlockData>::sky(self.options),
+            );
+            (accumulator, info)
         }
     }
 }
 
+/// Points within each pixel sampled when antialiasing, as fractions of the pixel.
+const SAMPLE_POINTS: [Vector2<f64>; 4] = [
+    Vector2::new(1. / 8., 5. / 8.),
+    Vector2::new(3. / 8., 1. / 8.),
+    Vector2::new(5. / 8., 7. / 8.),
+    Vector2::new(7. / 8., 3. / 8.),
+];
+
+/// Placement of the traced image within a larger output image;
+/// see [`RtRenderer::set_letterbox()`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Letterbox {
+    /// Size of the entire output image.
+    output_size: Vector2<u32>,
+    /// Position of the upper-left corner of the traced image within the output image.
+    offset: Vector2<u32>,
+    /// Color of the pixels outside the traced image.
+    bar_color: Rgba,
+}
+
 /// A rectangle in normalized device coordinates (-1 to 1 is the viewport).
 #[derive(Clone, Copy, Debug, PartialEq)]
 struct NdcRect {
@@ -359,31 +885,37 @@ impl NdcRect {
     fn point_within(self, uv: Vector2<f64>) -> Point2<f64> {
         self.low + (self.high - self.low).mul_element_wise(uv.x)
     }
+
+    /// Returns a random number generator for stochastic transparency which depends
+    /// only on the position of this rectangle and on `sample`.
+    fn sample_rng(self, sample: u64) -> StochasticRng {
+        StochasticRng::seed_from_u64(
+            self.low.x.to_bits() ^ self.low.y.to_bits().rotate_left(32) ^ sample,
+        )
+    }
 }
 
 /// Threaded and non-threaded implementations of generating a full image.
 /// TODO: The design of this code (and its documentation) are slightly residual from
 /// when `trace_scene_to_image()` was a public interface. Revisit them.
 mod trace_image {
-    use super::*;
+    use super::RtScene;
     use crate::raytracer::{Accumulate, RaytraceInfo};
-    use cgmath::Point2;
 
     /// Compute a full image, writing it into `output`.
     ///
     /// The produced data is in the usual left-right then top-bottom raster order;
-    /// its dimensions are `camera.framebuffer_size`.
+    /// its dimensions are [`RtScene::output_size()`].
     ///
     /// `encoder` may be used to transform the output of the [`Accumulate`] into the stored
     /// representation.
     ///
-    /// Panics if `output`'s length does not match the area of `camera.framebuffer_size`.
+    /// Panics if `output`'s length does not match the area of [`RtScene::output_size()`].
     ///
-    /// TODO: Add a mechanism for incrementally rendering (not 100% of pixels) for
-    /// interactive use.
+    /// Pixels which [`RtScene::patch_needs_trace()`] excludes are left unchanged.
     #[cfg(feature = "threads")]
     pub(super) fn trace_scene_to_image_impl<P, E, O>(
-        scene: super::RtScene<'_, P>,
+        scene: RtScene<'_, P>,
         encoder: E,
         output: &mut [O],
     ) -> RaytraceInfo
@@ -397,28 +929,22 @@ mod trace_image {
         };
         use rayon::slice::ParallelSliceMut as _;
 
-        let viewport = scene.cameras.world.viewport();
-        let viewport_size = viewport.framebuffer_size.map(|s| s as usize);
+        let output_size = scene.output_size();
         let encoder = &encoder; // make shareable
 
         // x.max(1) is zero-sized-viewport protection; the chunk size will be wrong, but there
         // will be zero chunks anyway.
         let total_info = output
-            .par_chunks_mut(viewport_size.x.max(1))
+            .par_chunks_mut(output_size.x.max(1))
             .enumerate()
             .map(move |(ych, raster_row)| {
-                let y0 = viewport.normalize_fb_y_edge(ych);
-                let y1 = viewport.normalize_fb_y_edge(ych + 1);
                 raster_row
                     .into_par_iter()
                     .enumerate()
                     .map(move |(xch, pixel_out)| {
-                        let x0 = viewport.normalize_fb_x_edge(xch);
-                        let x1 = viewport.normalize_fb_x_edge(xch + 1);
-                        let (pixel, info) = scene.trace_patch(NdcRect {
-                            low: Point2::new(x0, y0),
-                            high: Point2::new(x1, y1),
-                        });
+                        let Some((pixel, info)) = scene.draw_pixel(xch, ych) else {
+                            return RaytraceInfo::default();
+                        };
                         *pixel_out = encoder(pixel);
                         info
                     })
@@ -432,18 +958,17 @@ mod trace_image {
     /// Compute a full image, writing it into `output`.
     ///
     /// The produced data is in the usual left-right then top-bottom raster order;
-    /// its dimensions are `camera.framebuffer_size`.
+    /// its dimensions are [`RtScene::output_size()`].
     ///
     /// `encoder` may be used to transform the output of the [`Accumulate`] into the stored
     /// representation.
     ///
-    /// Panics if `output`'s length does not match the area of `camera.framebuffer_size`.
+    /// Panics if `output`'s length does not match the area of [`RtScene::output_size()`].
     ///
-    /// TODO: Add a mechanism for incrementally rendering (not 100% of pixels) for
-    /// interactive use.
+    /// Pixels which [`RtScene::patch_needs_trace()`] excludes are left unchanged.
     #[cfg(not(feature = "threads"))]
     pub(super) fn trace_scene_to_image_impl<P, E, O>(
-        scene: super::RtScene<'_, P>,
+        scene: RtScene<'_, P>,
         encoder: E,
         output: &mut [O],
     ) -> RaytraceInfo
@@ -452,27 +977,18 @@ mod trace_image {
         E: Fn(P) -> O + Send + Sync,
         O: Send + Sync,
     {
-        let viewport = scene.cameras.world.viewport();
-        let viewport_size = viewport.framebuffer_size.map(|s| s as usize);
+        let output_size = scene.output_size();
 
         let mut total_info = RaytraceInfo::default();
         let mut index = 0;
-        let mut y0 = viewport.normalize_fb_y_edge(0);
-        for y_edge in 1..=viewport_size.y {
-            let y1 = viewport.normalize_fb_y_edge(y_edge);
-            let mut x0 = viewport.normalize_fb_x_edge(0);
-            for x_edge in 1..=viewport_size.x {
-                let x1 = viewport.normalize_fb_x_edge(x_edge);
-                let (pixel, info) = scene.trace_patch(NdcRect {
-                    low: Point2::new(x0, y0),
-                    high: Point2::new(x1, y1),
-                });
-                output[index] = encoder(pixel);
-                total_info += info;
+        for y in 0..output_size.y {
+            for x in 0..output_size.x {
+                if let Some((pixel, info)) = scene.draw_pixel(x, y) {
+                    output[index] = encoder(pixel);
+                    total_info += info;
+                }
                 index += 1;
-                x0 = x1;
             }
-            y0 = y1;
         }
 
         total_info
@@ -565,12 +1081,478 @@ mod eg {
 
 #[cfg(test)]
 mod tests {
-    use crate::util::assert_send_sync;
+    use cgmath::{Basis3, Decomposed, InnerSpace as _, One as _, Vector3, Vector4, Zero as _};
 
     use super::*;
+    use crate::block::Block;
+    use crate::camera::{SpaceLayer, UiViewState, ViewTransform};
+    use crate::character::{cursor_raycast, Character, Spawn};
+    use crate::listen::ListenableCell;
+    use crate::math::GridAab;
+    use crate::raycast::Ray;
+    use crate::space::LightPhysics;
+    use crate::universe::Universe;
+    use crate::util::assert_send_sync;
 
     #[test]
     fn renderer_is_send_sync() {
         assert_send_sync::<RtRenderer>()
     }
+
+    /// Create a universe whose default character is in `space`.
+    fn universe_with_character(space: Space) -> (Universe, URef<Space>) {
+        let mut universe = Universe::new();
+        let space = universe.insert_anonymous(space);
+        universe
+            .insert("character".into(), Character::spawn_default(space.clone()))
+            .unwrap();
+        (universe, space)
+    }
+
+    /// Cameras viewing `universe`'s default character, with default graphics options and
+    /// a viewport of `size` pixels.
+    fn test_cameras(universe: &Universe, size: [u32; 2]) -> StandardCameras {
+        StandardCameras::from_constant_for_test(
+            GraphicsOptions::default(),
+            Viewport::with_scale(1.0, size.into()),
+            universe,
+        )
+    }
+
+    fn test_renderer(cameras: StandardCameras) -> RtRenderer {
+        RtRenderer::new(cameras, Box::new(|v| v), ListenableSource::constant(()))
+    }
+
+    /// Update `renderer` and draw an image.
+    fn update_and_draw(renderer: &mut RtRenderer) -> Rendering {
+        renderer.update(None).unwrap();
+        renderer.draw_rgba(|_| String::new()).0
+    }
+
+    /// A world containing only blue sky, and a space containing a translucent red block
+    /// which covers the center of the view when used as an overlay with [`overlay_view()`].
+    fn world_and_translucent_overlay() -> (Universe, URef<Space>) {
+        let (mut universe, _) = universe_with_character(
+            Space::builder(GridAab::ORIGIN_CUBE)
+                .sky_color(rgb_const!(0.0, 0.0, 1.0))
+                .build(),
+        );
+        let overlay_space = universe.insert_anonymous({
+            let mut space = Space::builder(GridAab::ORIGIN_CUBE)
+                .light_physics(LightPhysics::None)
+                .build();
+            space
+                .set([0, 0, 0], Block::from(rgba_const!(1.0, 0.0, 0.0, 0.5)))
+                .unwrap();
+            space
+        });
+        (universe, overlay_space)
+    }
+
+    fn overlay_view() -> ViewTransform {
+        Decomposed {
+            scale: 1.0,
+            rot: Basis3::one(),
+            disp: Vector3::new(0.5, 0.5, 5.0),
+        }
+    }
+
+    /// Draw the center pixel of a 9×9 image.
+    fn center_pixel(cameras: StandardCameras) -> [u8; 4] {
+        update_and_draw(&mut test_renderer(cameras)).data[4 * 9 + 4]
+    }
+
+    /// Check that the overlay from [`world_and_translucent_overlay()`] added red, but
+    /// the world's blue sky is still visible through it.
+    #[track_caller]
+    fn assert_overlay_composited(without_overlay: [u8; 4], with_overlay: [u8; 4]) {
+        assert_eq!(without_overlay[0], 0, "{without_overlay:?}");
+        assert!(
+            with_overlay[0] > without_overlay[0],
+            "{with_overlay:?} vs. {without_overlay:?}"
+        );
+        assert!(with_overlay[2] > 0, "{with_overlay:?}");
+        assert!(
+            with_overlay[2] < without_overlay[2],
+            "{with_overlay:?} vs. {without_overlay:?}"
+        );
+    }
+
+    #[test]
+    fn ui_is_composited_over_world() {
+        let (universe, overlay_space) = world_and_translucent_overlay();
+        let cameras = |ui_state: UiViewState| {
+            StandardCameras::new(
+                ListenableSource::constant(GraphicsOptions::default()),
+                ListenableSource::constant(Viewport::with_scale(1.0, Vector2::new(9, 9))),
+                ListenableSource::constant(universe.get_default_character()),
+                ListenableSource::constant(ui_state),
+            )
+        };
+
+        assert_overlay_composited(
+            center_pixel(cameras(UiViewState::default())),
+            center_pixel(cameras(UiViewState {
+                space: Some(overlay_space),
+                view_transform: overlay_view(),
+                graphics_options: GraphicsOptions::default(),
+            })),
+        );
+    }
+
+    #[test]
+    fn space_layer_is_composited_over_world() {
+        let (universe, overlay_space) = world_and_translucent_overlay();
+        let cameras = |space_layers: Vec<SpaceLayer>| {
+            test_cameras(&universe, [9, 9])
+                .with_space_layers(ListenableSource::constant(space_layers))
+        };
+
+        assert_overlay_composited(
+            center_pixel(cameras(vec![])),
+            center_pixel(cameras(vec![SpaceLayer {
+                space: overlay_space,
+                view_transform: overlay_view(),
+            }])),
+        );
+    }
+
+    /// With letterboxing, the scene is drawn at its nominal aspect ratio in the center of
+    /// a framebuffer of a different aspect ratio, with bars filling the rest.
+    #[test]
+    fn letterbox() {
+        let (mut universe, _) = universe_with_character(
+            Space::builder(GridAab::ORIGIN_CUBE)
+                .sky_color(rgb_const!(0.0, 0.0, 1.0))
+                .build(),
+        );
+        // An opaque block in the center of the view, so that the scene is not uniform and
+        // any stretching of it would be visible.
+        let block_space = universe.insert_anonymous({
+            let mut space = Space::builder(GridAab::ORIGIN_CUBE)
+                .light_physics(LightPhysics::None)
+                .build();
+            space
+                .set([0, 0, 0], Block::from(rgba_const!(1.0, 0.0, 0.0, 1.0)))
+                .unwrap();
+            space
+        });
+
+        let render = |framebuffer_size: Vector2<u32>, bar_color: Option<Rgba>| -> Rendering {
+            let mut renderer = test_renderer(
+                StandardCameras::from_constant_for_test(
+                    GraphicsOptions::default(),
+                    Viewport {
+                        nominal_size: Vector2::new(10.0, 10.0),
+                        framebuffer_size,
+                    },
+                    &universe,
+                )
+                .with_space_layers(ListenableSource::constant(vec![SpaceLayer {
+                    space: block_space.clone(),
+                    view_transform: overlay_view(),
+                }])),
+            );
+            renderer.set_letterbox(bar_color);
+            update_and_draw(&mut renderer)
+        };
+
+        // The scene as drawn without any bars.
+        let square = render(Vector2::new(10, 10), None);
+        let sky = [0, 0, 255, 255];
+        assert!(square.data.contains(&sky), "{:?}", square.data);
+        assert!(
+            square.data.iter().any(|&pixel| pixel != sky),
+            "{:?}",
+            square.data
+        );
+
+        // Wide framebuffer: bars on the left and right thirds.
+        let wide = render(Vector2::new(30, 10), Some(Rgba::BLACK));
+        for (index, &pixel) in wide.data.iter().enumerate() {
+            let (x, y) = (index % 30, index / 30);
+            let expected = if (10..20).contains(&x) {
+                square.data[y * 10 + (x - 10)]
+            } else {
+                [0, 0, 0, 255]
+            };
+            assert_eq!(pixel, expected, "pixel {x}, {y}");
+        }
+
+        // Tall framebuffer with transparent bars: bars on the top and bottom.
+        let tall = render(Vector2::new(10, 20), Some(Rgba::TRANSPARENT));
+        for (index, &pixel) in tall.data.iter().enumerate() {
+            let (x, y) = (index % 10, index / 10);
+            let expected = if (5..15).contains(&y) {
+                square.data[(y - 5) * 10 + x]
+            } else {
+                [0, 0, 0, 0]
+            };
+            assert_eq!(pixel, expected, "pixel {x}, {y}");
+        }
+    }
+
+    #[test]
+    fn background_gradient() {
+        let (universe, _) = universe_with_character(
+            Space::builder(GridAab::ORIGIN_CUBE)
+                .sky_color(rgb_const!(0.5, 0.5, 0.5))
+                .build(),
+        );
+
+        let render_top_and_bottom = |background: Option<Background>| -> [[u8; 4]; 2] {
+            let mut renderer = test_renderer(test_cameras(&universe, [9, 9]));
+            renderer.set_background(background);
+            let rendering = update_and_draw(&mut renderer);
+            [rendering.data[4], rendering.data[8 * 9 + 4]]
+        };
+
+        let [top, bottom] = render_top_and_bottom(None);
+        assert_eq!(top, bottom);
+
+        let [top, bottom] = render_top_and_bottom(Some(Background::VerticalGradient {
+            top: rgb_const!(0.0, 0.0, 1.0),
+            bottom: rgb_const!(1.0, 0.0, 0.0),
+        }));
+        assert!(top[2] > bottom[2], "{top:?} vs. {bottom:?}");
+        assert!(top[0] < bottom[0], "{top:?} vs. {bottom:?}");
+    }
+
+    /// A single block against [`Background::Transparent`] has opaque pixels where the
+    /// block is and transparent ones elsewhere.
+    #[test]
+    fn background_transparent() {
+        let bounds = GridAab::from_lower_size([0, 0, 0], [5, 5, 1]);
+        let (universe, _) = universe_with_character({
+            let mut space = Space::builder(bounds)
+                .sky_color(rgb_const!(0.5, 0.5, 0.5))
+                .spawn(Spawn::looking_at_space(bounds, [0., 0., 1.]))
+                .build();
+            space
+                .set([2, 2, 0], Block::from(rgba_const!(1.0, 0.0, 0.0, 1.0)))
+                .unwrap();
+            space
+        });
+
+        let mut renderer = test_renderer(test_cameras(&universe, [9, 9]));
+        renderer.set_background(Some(Background::Transparent));
+        let rendering = update_and_draw(&mut renderer);
+
+        assert_eq!(rendering.data[4 * 9 + 4][3], 255, "center should be opaque");
+        for corner in [0, 8, 8 * 9, 8 * 9 + 8] {
+            assert_eq!(rendering.data[corner], [0, 0, 0, 0], "corner {corner}");
+        }
+    }
+
+    /// A ray which misses the space entirely, and so calls [`Accumulate::hit_nothing()`],
+    /// is also transparent.
+    #[test]
+    fn background_transparent_hit_nothing() {
+        let space = Space::empty_positive(1, 1, 1);
+        let rt = SpaceRaytracer::<()>::new(&space, GraphicsOptions::default(), ());
+        let (buf, info): (ColorBuf, _) = rt.trace_ray_with_background(
+            crate::raycast::Ray::new([10., 10., 10.], [0., 1., 0.]),
+            &Background::Transparent,
+        );
+        assert_eq!(info, RaytraceInfo::default());
+        assert_eq!(Rgba::from(buf), Rgba::TRANSPARENT);
+    }
+
+    /// Over many samples, stochastic transparency converges to the same color as
+    /// ordinary blending of a stack of translucent surfaces.
+    #[test]
+    fn stochastic_transparency_converges() {
+        let mut space = Space::builder(GridAab::from_lower_size([0, 0, 0], [1, 1, 3]))
+            .light_physics(LightPhysics::None)
+            .build();
+        space
+            .set([0, 0, 2], Block::from(rgba_const!(1.0, 0.0, 0.0, 0.5)))
+            .unwrap();
+        space
+            .set([0, 0, 1], Block::from(rgba_const!(0.0, 1.0, 0.0, 0.5)))
+            .unwrap();
+        space
+            .set([0, 0, 0], Block::from(rgba_const!(0.0, 0.0, 1.0, 0.5)))
+            .unwrap();
+        let rt = SpaceRaytracer::<()>::new(&space, GraphicsOptions::default(), ());
+        let ray = crate::raycast::Ray::new([0.5, 0.5, 10.], [0., 0., -1.]);
+        let sky = Rgba::WHITE;
+
+        let (blended, _) = rt.trace_ray_onto(ray, ColorBuf::default(), sky, None);
+        let blended = Rgba::from(blended);
+
+        const SAMPLES: u32 = 10_000;
+        let sum = (0..SAMPLES)
+            .map(|i| {
+                let mut rng = StochasticRng::seed_from_u64(i.into());
+                let (buf, _) = rt.trace_ray_onto(ray, ColorBuf::default(), sky, Some(&mut rng));
+                Vector4::from(Rgba::from(buf))
+            })
+            .fold(Vector4::zero(), |a, b| a + b);
+        let mean = sum / SAMPLES as f32;
+
+        assert!(
+            (mean - Vector4::from(blended)).magnitude() < 0.02,
+            "stochastic {mean:?} vs. blended {blended:?}"
+        );
+    }
+
+    /// Stochastic transparency depends only on the pixel position, so the same scene
+    /// is always drawn the same way.
+    #[test]
+    fn stochastic_transparency_is_deterministic() {
+        let bounds = GridAab::from_lower_size([0, 0, 0], [5, 5, 2]);
+        let (universe, _) = universe_with_character({
+            let mut space = Space::builder(bounds)
+                .light_physics(LightPhysics::None)
+                .spawn(Spawn::looking_at_space(bounds, [0., 0., 1.]))
+                .build();
+            space
+                .fill_uniform(bounds, Block::from(rgba_const!(1.0, 0.0, 0.0, 0.5)))
+                .unwrap();
+            space
+        });
+        let options = GraphicsOptions {
+            antialiasing: AntialiasingOption::Always,
+            ..GraphicsOptions::default()
+        };
+
+        let draw = || {
+            let mut renderer = test_renderer(StandardCameras::from_constant_for_test(
+                options.clone(),
+                Viewport::with_scale(1.0, Vector2::new(10, 10)),
+                &universe,
+            ));
+            renderer.set_stochastic_transparency(true);
+            update_and_draw(&mut renderer).data
+        };
+
+        assert_eq!(draw(), draw());
+    }
+
+    /// Incremental drawing produces the same image as full drawing, while tracing fewer
+    /// pixels when only one block changed.
+    #[test]
+    fn incremental_draw() {
+        let bounds = GridAab::from_lower_size([0, 0, 0], [5, 5, 1]);
+        let (universe, space) = universe_with_character({
+            let mut space = Space::builder(bounds)
+                .light_physics(LightPhysics::None)
+                .spawn(Spawn::looking_at_space(bounds, [0., 0., 1.]))
+                .build();
+            space
+                .set([2, 2, 0], Block::from(rgba_const!(1.0, 0.0, 0.0, 1.0)))
+                .unwrap();
+            space
+        });
+        let mut renderer = test_renderer(test_cameras(&universe, [20, 20]));
+        renderer.set_incremental(true);
+        let mut buffer = vec![[0; 4]; 20 * 20];
+
+        renderer.update(None).unwrap();
+        let (_, full_info) = renderer
+            .draw_srgb8_into(|_| String::new(), &mut buffer)
+            .unwrap();
+        let (expected, _) = renderer.draw_rgba(|_| String::new());
+        assert_eq!(buffer, expected.data);
+
+        space
+            .try_modify(|space| {
+                space
+                    .set([0, 0, 0], Block::from(rgba_const!(0.0, 1.0, 0.0, 1.0)))
+                    .unwrap()
+            })
+            .unwrap();
+        renderer.update(None).unwrap();
+        let (_, incremental_info) = renderer
+            .draw_srgb8_into(|_| String::new(), &mut buffer)
+            .unwrap();
+        let (expected, _) = renderer.draw_rgba(|_| String::new());
+        assert_eq!(buffer, expected.data);
+        assert!(
+            incremental_info.cubes_traced < full_info.cubes_traced,
+            "{incremental_info:?} vs. {full_info:?}"
+        );
+
+        // With no changes, nothing is traced.
+        renderer.update(None).unwrap();
+        let (_, unchanged_info) = renderer
+            .draw_srgb8_into(|_| String::new(), &mut buffer)
+            .unwrap();
+        assert_eq!(buffer, expected.data);
+        assert_eq!(unchanged_info, RaytraceInfo::default());
+    }
+
+    /// The renderer uses the cursor from [`StandardCameras::cursor()`] when not given one.
+    /// Since the raytracer cannot draw cursors, this shows up as a flaw.
+    #[test]
+    fn cursor_from_cameras() {
+        let (universe, space) = universe_with_character({
+            let mut space = Space::empty_positive(1, 1, 1);
+            space.set([0, 0, 0], Block::from(Rgba::WHITE)).unwrap();
+            space
+        });
+        let cursor = cursor_raycast(
+            Ray::new([0.5, 0.5, -1.0], [0.0, 0.0, 1.0]),
+            &space,
+            FreeCoordinate::INFINITY,
+        )
+        .unwrap();
+        let cursor_cell = ListenableCell::new(None);
+        let mut renderer = test_renderer(
+            StandardCameras::from_constant_for_test(
+                GraphicsOptions::UNALTERED_COLORS,
+                Viewport::with_scale(1.0, Vector2::new(4, 4)),
+                &universe,
+            )
+            .with_cursor(cursor_cell.as_source()),
+        );
+
+        assert_eq!(update_and_draw(&mut renderer).flaws, Flaws::empty());
+        renderer.update(None).unwrap();
+        let (rendering, _) = renderer.draw_rgba(|_| String::new());
+        assert_eq!(rendering.flaws, Flaws::empty());
+
+        cursor_cell.set(Some(cursor));
+        assert_eq!(update_and_draw(&mut renderer).flaws, Flaws::NO_CURSOR);
+    }
+
+    /// Drawing into a reused buffer gives the same images as allocating a new one,
+    /// even when the image changes.
+    #[test]
+    fn draw_into_reused_buffer() {
+        let (universe, _) = universe_with_character(Space::empty_positive(1, 1, 1));
+        let mut renderer = test_renderer(test_cameras(&universe, [8, 6]));
+
+        let mut srgb_buffer = vec![[0; 4]; 8 * 6];
+        let mut rgba_buffer = vec![Rgba::TRANSPARENT; 8 * 6];
+        let mut frames = Vec::new();
+        renderer.update(None).unwrap();
+        for sky_color in [rgb_const!(0.0, 0.0, 1.0), rgb_const!(1.0, 0.0, 0.0)] {
+            renderer.set_background(Some(Background::Solid(sky_color)));
+
+            let (expected, _) = renderer.draw_rgba(|_| String::new());
+            let (flaws, _) = renderer
+                .draw_srgb8_into(|_| String::new(), &mut srgb_buffer)
+                .unwrap();
+            assert_eq!((&srgb_buffer, flaws), (&expected.data, expected.flaws));
+            renderer
+                .draw_rgba_into(|_| String::new(), &mut rgba_buffer)
+                .unwrap();
+            assert_eq!(
+                rgba_buffer.iter().map(|c| c.to_srgb8()).collect::<Vec<_>>(),
+                expected.data
+            );
+            frames.push(expected.data);
+        }
+        assert_ne!(frames[0], frames[1], "background change not visible");
+
+        assert_eq!(
+            renderer
+                .draw_srgb8_into(|_| String::new(), &mut [[0; 4]; 3])
+                .unwrap_err()
+                .to_string(),
+            "buffer of length 3 cannot hold image of size 8×6"
+        );
+    }
 }
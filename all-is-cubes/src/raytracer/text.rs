@@ -134,8 +134,11 @@ fn print_space_impl<F: FnMut(&str)>(
 mod tests {
     use super::*;
     use crate::block::{Block, Resolution::R4};
+    use crate::camera::ProjectionOption;
     use crate::content::make_some_blocks;
+    use crate::math::{GridAab, NotNan};
     use crate::universe::Universe;
+    use cgmath::{Basis3, One as _};
 
     #[test]
     fn print_space_test() {
@@ -264,4 +267,49 @@ mod tests {
             "
         );
     }
+
+    #[test]
+    fn orthographic_size_independent_of_depth() {
+        // Two blocks, side by side but at different distances from the camera.
+        let mut space = Space::empty(GridAab::from_lower_size([0, 0, 0], [3, 1, 5]));
+        let [b0, b1] = make_some_blocks();
+        space.set([0, 0, 0], &b0).unwrap();
+        space.set([2, 0, 4], &b1).unwrap();
+
+        let count_chars = |projection: ProjectionOption| -> [usize; 2] {
+            let options = GraphicsOptions {
+                projection,
+                ..GraphicsOptions::default()
+            };
+            let mut camera = Camera::new(
+                options.clone(),
+                Viewport {
+                    nominal_size: Vector2::new(40., 40.),
+                    framebuffer_size: Vector2::new(40, 40),
+                },
+            );
+            camera.set_view_transform(Decomposed {
+                scale: 1.0,
+                rot: Basis3::one(),
+                disp: Vector3::new(1.5, 0.5, 10.0),
+            });
+            let output = SpaceRaytracer::<CharacterRtData>::new(&space, options, ())
+                .trace_scene_to_string::<CharacterBuf>(&camera, "\n");
+            print!("{output}");
+            [
+                output.chars().filter(|&c| c == '0').count(),
+                output.chars().filter(|&c| c == '1').count(),
+            ]
+        };
+
+        let [far, near] = count_chars(ProjectionOption::Orthographic {
+            height: NotNan::from(4),
+        });
+        // The view is 4 cubes wide and high, so each block should cover 1/16 of it.
+        assert_eq!([far, near], [100, 100]);
+
+        // For contrast, check that perspective does not do the same.
+        let [far, near] = count_chars(ProjectionOption::Perspective);
+        assert!(far < near, "{far} < {near}");
+    }
 }
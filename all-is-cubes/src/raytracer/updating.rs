@@ -7,7 +7,7 @@ use crate::block::AIR;
 use crate::camera::GraphicsOptions;
 use crate::content::palette;
 use crate::listen::{Listen as _, ListenableSource, Listener};
-use crate::math::Cube;
+use crate::math::{Cube, GridAab};
 use crate::raytracer::{RtBlockData, RtOptionsRef, SpaceRaytracer, TracingBlock, TracingCubeData};
 use crate::space::{BlockIndex, Space, SpaceChange};
 use crate::universe::{RefError, URef};
@@ -20,6 +20,10 @@ pub struct UpdatingSpaceRaytracer<D: RtBlockData> {
     custom_options: ListenableSource<D::Options>,
     state: SpaceRaytracer<D>,
     todo: Arc<Mutex<SrtTodo>>,
+
+    /// Region of the space whose appearance may have changed in calls to
+    /// [`Self::update()`] since the last [`Self::take_dirty_region()`].
+    dirty: Option<GridAab>,
 }
 
 // manual impl avoids `D: Debug` bound
@@ -34,6 +38,7 @@ where
             .field("custom_options", &self.custom_options)
             .field("state", &self.state)
             .field("todo", &self.todo)
+            .field("dirty", &self.dirty)
             .finish()
     }
 }
@@ -55,6 +60,7 @@ where
         let todo = Arc::new(Mutex::new(SrtTodo {
             listener: true,
             everything: true,
+            block_values: false,
             blocks: HashSet::new(),
             cubes: HashSet::new(),
         }));
@@ -73,6 +79,7 @@ where
             graphics_options,
             custom_options,
             todo,
+            dirty: None,
         }
     }
 
@@ -87,6 +94,19 @@ where
         &self.state
     }
 
+    /// Returns the region of the space which has changed as a result of calls to
+    /// [`Self::update()`] since the last call to this method, or [`None`] if nothing has
+    /// changed, and resets it to [`None`].
+    ///
+    /// This may be used to avoid retracing rays which cannot have been affected by the
+    /// changes. The region is conservative: it is the bounding box of all changed cubes,
+    /// and is the entire bounds of the space if a block definition or the options have
+    /// changed. It does not account for the effects of a change on its neighbors
+    /// (such as the lighting of adjacent faces).
+    pub fn take_dirty_region(&mut self) -> Option<GridAab> {
+        self.dirty.take()
+    }
+
     /// Reads the previously provided [`Space`] and updates the local copy of its contents.
     ///
     /// Returns an error if reading fails.
@@ -116,8 +136,10 @@ where
                 self.graphics_options.snapshot(),
                 self.custom_options.snapshot(),
             );
+            todo.block_values = false;
             todo.blocks.clear();
             todo.cubes.clear();
+            include_in_dirty(&mut self.dirty, space.bounds());
         } else {
             let graphics_options = &*self.graphics_options.get();
             let custom_options = &*self.custom_options.get();
@@ -134,6 +156,10 @@ where
                         .push(TracingBlock::from_block(options, block_data));
                 }
             }
+            if mem::take(&mut todo.block_values) {
+                // Blocks whose definitions changed could be anywhere in the space.
+                include_in_dirty(&mut self.dirty, space.bounds());
+            }
             for block_index in todo.blocks.drain() {
                 // TODO: handle extending the vector
                 let block_index = usize::from(block_index);
@@ -149,6 +175,7 @@ where
                     lighting: space.get_lighting(cube),
                     always_invisible: block_data_slice[block_index as usize].block() == &AIR,
                 };
+                include_in_dirty(&mut self.dirty, cube.grid_aab());
             }
         }
 
@@ -156,6 +183,15 @@ where
     }
 }
 
+/// Expand `dirty` to include `region`.
+fn include_in_dirty(dirty: &mut Option<GridAab>, region: GridAab) {
+    *dirty = Some(match *dirty {
+        // Cannot overflow since both boxes are within the bounds of the space.
+        Some(old) => old.union(region).unwrap(),
+        None => region,
+    });
+}
+
 #[derive(Debug)]
 struct SrtTodo {
     /// Listener upon the space is not yet installed.
@@ -164,6 +200,10 @@ struct SrtTodo {
     /// All blocks and cubes must be updated.
     everything: bool,
 
+    /// Some block definition has changed (as opposed to a block index being reassigned,
+    /// in which case the affected cubes are also individually reported).
+    block_values: bool,
+
     // TODO: Benchmark using a BitVec instead.
     blocks: HashSet<BlockIndex>,
     cubes: HashSet<Cube>,
@@ -180,13 +220,18 @@ impl Listener<SpaceChange> for TodoListener {
                 match message {
                     SpaceChange::EveryBlock => {
                         todo.everything = true;
+                        todo.block_values = false;
                         todo.blocks.clear();
                         todo.cubes.clear()
                     }
                     SpaceChange::Lighting(p) | SpaceChange::Block(p) => {
                         todo.cubes.insert(p);
                     }
-                    SpaceChange::Number(index) | SpaceChange::BlockValue(index) => {
+                    SpaceChange::Number(index) => {
+                        todo.blocks.insert(index);
+                    }
+                    SpaceChange::BlockValue(index) => {
+                        todo.block_values = true;
                         todo.blocks.insert(index);
                     }
                 }
@@ -206,6 +251,7 @@ mod tests {
     use crate::camera::{eye_for_look_at, Camera, Viewport};
     use crate::content::make_some_voxel_blocks;
     use crate::raytracer::{CharacterBuf, CharacterRtData};
+    use crate::space::LightPhysics;
     use crate::universe::Universe;
     use crate::util::{CustomFormat, Unquote};
     use cgmath::{Decomposed, Transform as _, Vector2, Vector3};
@@ -316,6 +362,40 @@ mod tests {
         // TODO: Also test changing existing block's data
     }
 
+    #[test]
+    fn dirty_region_of_single_block_change() {
+        let mut universe = Universe::new();
+        let [block] = make_some_voxel_blocks(&mut universe);
+        let space = universe.insert_anonymous(
+            Space::builder(GridAab::from_lower_size([0, 0, 0], [10, 10, 10]))
+                .light_physics(LightPhysics::None)
+                .build(),
+        );
+        let mut tester = EquivalenceTester::new(space.clone());
+
+        // The first update covers everything.
+        tester.update_and_assert().unwrap();
+        assert_eq!(
+            tester.updating.take_dirty_region(),
+            Some(space.read().unwrap().bounds())
+        );
+        assert_eq!(tester.updating.take_dirty_region(), None);
+
+        // A single block change is reported as only that cube.
+        space
+            .try_modify(|space| space.set([3, 4, 5], &block).unwrap())
+            .unwrap();
+        tester.update_and_assert().unwrap();
+        assert_eq!(
+            tester.updating.take_dirty_region(),
+            Some(GridAab::single_cube([3, 4, 5].into()))
+        );
+
+        // Nothing further changed.
+        tester.update_and_assert().unwrap();
+        assert_eq!(tester.updating.take_dirty_region(), None);
+    }
+
     #[test]
     fn updating_after_space_is_unavailable() {
         let mut universe = Universe::new();
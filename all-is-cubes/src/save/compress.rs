@@ -123,6 +123,22 @@ impl From<Leu16> for u16 {
     }
 }
 
+/// u32, but in guaranteed little-endian, unaligned representation.
+#[derive(Copy, Clone, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(transparent)]
+pub(crate) struct Leu32([u8; 4]);
+
+impl From<u32> for Leu32 {
+    fn from(value: u32) -> Self {
+        Self(value.to_le_bytes())
+    }
+}
+impl From<Leu32> for u32 {
+    fn from(value: Leu32) -> Self {
+        u32::from_le_bytes(value.0)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
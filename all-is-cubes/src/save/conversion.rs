@@ -78,6 +78,7 @@ mod block {
         AnimationChange, AnimationHint, Atom, Block, BlockAttributes, BlockCollision, Composite,
         Modifier, Move, Primitive, Quote, RotationPlacementRule, Zoom,
     };
+    use crate::drawing::VoxelBrush;
     use crate::math::{Rgb, Rgba};
     use schema::{BlockSer, ModifierSer};
 
@@ -182,13 +183,14 @@ mod block {
                 ref display_name,
                 selectable,
                 rotation_rule,
-                tick_action: _, // TODO: serialize tick_action once it is cleaner
+                ref tick_action,
                 animation_hint,
             } = value;
             schema::BlockAttributesV1Ser {
                 display_name: display_name.to_string(),
                 selectable,
                 rotation_rule: rotation_rule.into(),
+                tick_action: tick_action.as_ref().map(schema::VoxelBrushSer::from),
                 animation_hint: animation_hint.into(),
             }
         }
@@ -196,18 +198,18 @@ mod block {
 
     impl From<schema::BlockAttributesV1Ser> for BlockAttributes {
         fn from(value: schema::BlockAttributesV1Ser) -> Self {
-            // TODO: implement deserializing all attributes
             let schema::BlockAttributesV1Ser {
                 display_name,
                 selectable,
                 rotation_rule,
+                tick_action,
                 animation_hint,
             } = value;
             Self {
                 display_name: display_name.into(),
                 selectable,
                 rotation_rule: rotation_rule.into(),
-                tick_action: None,
+                tick_action: tick_action.map(VoxelBrush::from),
                 animation_hint: animation_hint.into(),
             }
         }
@@ -331,6 +333,16 @@ mod block {
                     distance,
                     velocity,
                 },
+                Modifier::Clip(bounds) => ModifierSer::ClipV1 { bounds },
+                Modifier::Gradient {
+                    direction,
+                    from,
+                    to,
+                } => ModifierSer::GradientV1 {
+                    direction,
+                    from: from.into(),
+                    to: to.into(),
+                },
             }
         }
     }
@@ -361,6 +373,16 @@ mod block {
                     distance,
                     velocity,
                 } => Modifier::Move(Move::new(direction, distance, velocity)),
+                ModifierSer::ClipV1 { bounds } => Modifier::Clip(bounds),
+                ModifierSer::GradientV1 {
+                    direction,
+                    from,
+                    to,
+                } => Modifier::Gradient {
+                    direction,
+                    from: Rgba::from(from),
+                    to: Rgba::from(to),
+                },
             }
         }
     }
@@ -369,6 +391,40 @@ mod block {
 // `character::Character` and `character::Spawn` serialization are inside their module
 // for the sake of private fields.
 
+mod drawing {
+    use super::*;
+    use crate::block::Block;
+    use crate::drawing::VoxelBrush;
+    use schema::{VoxelBrushEntrySer, VoxelBrushSer};
+
+    impl From<&VoxelBrush<'_>> for VoxelBrushSer {
+        fn from(value: &VoxelBrush<'_>) -> Self {
+            VoxelBrushSer::VoxelBrushV1 {
+                contents: value
+                    .entries()
+                    .iter()
+                    .map(|(offset, block)| VoxelBrushEntrySer {
+                        offset: (*offset).into(),
+                        block: Block::clone(block),
+                    })
+                    .collect(),
+            }
+        }
+    }
+
+    impl From<VoxelBrushSer> for VoxelBrush<'static> {
+        fn from(value: VoxelBrushSer) -> Self {
+            match value {
+                VoxelBrushSer::VoxelBrushV1 { contents } => VoxelBrush::new(
+                    contents
+                        .into_iter()
+                        .map(|VoxelBrushEntrySer { offset, block }| (offset, block)),
+                ),
+            }
+        }
+    }
+}
+
 mod math {
     use super::*;
     use crate::math::{Aab, GridAab};
@@ -536,7 +592,15 @@ mod space {
     use super::*;
     use crate::math::GridArray;
     use crate::save::compress::{GzSerde, Leu16};
-    use crate::space::{self, LightPhysics, Space, SpacePhysics};
+    use crate::space::{self, BlockIndex, LightPhysics, Space, SpacePhysics};
+
+    /// Spaces with fewer cubes than this are always serialized densely, since there is
+    /// little to gain.
+    const RUNS_MIN_VOLUME: usize = 16 * 16 * 16;
+
+    /// Spaces are serialized as runs only if the average run is at least this many cubes
+    /// long; otherwise the runs would not be much smaller than the dense array.
+    const RUNS_MIN_AVERAGE_LENGTH: usize = 16;
 
     impl Serialize for Space {
         fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -551,11 +615,18 @@ mod space {
                     .iter()
                     .map(|bd| bd.block().clone())
                     .collect(),
-                contents: GzSerde(Cow::Owned(
-                    self.extract(self.bounds(), |e| Leu16::from(e.block_index()))
-                        .into_elements()
-                        .into(),
-                )),
+                contents: match contents_runs(self) {
+                    Some(runs) => {
+                        schema::SpaceContentsSer::Runs(schema::SpaceContentsRunsSer::RunsV1 {
+                            runs: GzSerde(Cow::Owned(runs)),
+                        })
+                    }
+                    None => schema::SpaceContentsSer::Dense(GzSerde(Cow::Owned(
+                        self.extract(self.bounds(), |e| Leu16::from(e.block_index()))
+                            .into_elements()
+                            .into(),
+                    ))),
+                },
                 light: if matches!(self.physics().light, space::LightPhysics::None) {
                     None
                 } else {
@@ -588,20 +659,24 @@ mod space {
                     bounds,
                     physics,
                     blocks,
-                    contents: GzSerde(contents),
+                    contents,
                     light,
                     behaviors,
                     spawn,
                 } => {
                     // Convert data representations
-                    let contents = GridArray::from_elements(
-                        bounds,
-                        Vec::from(contents)
+                    let contents: Box<[BlockIndex]> = match contents {
+                        schema::SpaceContentsSer::Dense(GzSerde(contents)) => Vec::from(contents)
                             .into_iter()
-                            .map(space::BlockIndex::from)
-                            .collect::<Box<[_]>>(),
-                    )
-                    .map_err(serde::de::Error::custom)?;
+                            .map(BlockIndex::from)
+                            .collect(),
+                        schema::SpaceContentsSer::Runs(schema::SpaceContentsRunsSer::RunsV1 {
+                            runs: GzSerde(runs),
+                        }) => expand_contents_runs(&runs, bounds.volume())
+                            .map_err(serde::de::Error::custom)?,
+                    };
+                    let contents = GridArray::from_elements(bounds, contents)
+                        .map_err(serde::de::Error::custom)?;
                     let light = light
                         .map(|GzSerde(data)| {
                             GridArray::from_elements(
@@ -629,6 +704,72 @@ mod space {
         }
     }
 
+    /// Computes the run-length encoding of the space's contents, or returns [`None`] if
+    /// the space is not mostly made of long runs and so should be serialized densely.
+    ///
+    /// This gives up as soon as there are too many runs, so that dense spaces do not pay
+    /// much for the attempt.
+    fn contents_runs(space: &Space) -> Option<Vec<schema::ContentsRunSerV1>> {
+        let bounds = space.bounds();
+        let volume = bounds.volume();
+        if volume < RUNS_MIN_VOLUME {
+            return None;
+        }
+        let max_runs = volume / RUNS_MIN_AVERAGE_LENGTH;
+
+        let mut runs = Vec::new();
+        let mut current: Option<(BlockIndex, u32)> = None;
+        for cube in bounds.interior_iter() {
+            let block_index = space.get_block_index(cube).unwrap();
+            match &mut current {
+                Some((run_index, length)) if *run_index == block_index && *length < u32::MAX => {
+                    *length += 1;
+                }
+                _ => {
+                    if let Some(run) = current.replace((block_index, 1)) {
+                        if runs.len() >= max_runs {
+                            return None;
+                        }
+                        runs.push(run);
+                    }
+                }
+            }
+        }
+        runs.extend(current);
+
+        Some(
+            runs.into_iter()
+                .map(|(block_index, length)| schema::ContentsRunSerV1 {
+                    length: length.into(),
+                    block_index: block_index.into(),
+                })
+                .collect(),
+        )
+    }
+
+    /// Converts the run-length encoding produced by [`contents_runs`] into one block index
+    /// per cube, checking that there are exactly `volume` cubes.
+    fn expand_contents_runs(
+        runs: &[schema::ContentsRunSerV1],
+        volume: usize,
+    ) -> Result<Box<[BlockIndex]>, String> {
+        let mut contents: Vec<BlockIndex> = Vec::with_capacity(volume);
+        for run in runs {
+            let length = usize::try_from(u32::from(run.length)).unwrap_or(usize::MAX);
+            if length > volume - contents.len() {
+                return Err(format!("space contents runs exceed volume {volume}"));
+            }
+            contents.extend(std::iter::repeat(BlockIndex::from(run.block_index)).take(length));
+        }
+        if contents.len() != volume {
+            return Err(format!(
+                "space contents runs have total length {}, expected {volume}",
+                contents.len()
+            ));
+        }
+        Ok(contents.into_boxed_slice())
+    }
+
     impl From<&SpacePhysics> for schema::SpacePhysicsSerV1 {
         fn from(value: &SpacePhysics) -> Self {
             let &SpacePhysics {
@@ -680,16 +821,18 @@ mod space {
     }
 }
 
-mod universe {
+pub(crate) mod universe {
     use super::*;
     use crate::block::{Block, BlockDef};
     use crate::character::Character;
+    use crate::content::palette;
     use crate::save::schema::MemberEntrySer;
     use crate::space::Space;
     use crate::time;
-    use crate::universe::{self, Name, PartialUniverse, UBorrow, URef, Universe};
+    use crate::universe::{self, AnyURef, Name, PartialUniverse, UBorrow, URef, Universe};
     use schema::{MemberDe, NameSer, URefSer};
     use std::cell::RefCell;
+    use std::collections::BTreeMap;
 
     impl From<&BlockDef> for schema::MemberSer {
         fn from(block_def: &BlockDef) -> Self {
@@ -766,18 +909,7 @@ mod universe {
             match data {
                 schema::UniverseDe::UniverseV1 { members } => {
                     for schema::MemberEntrySer { name, value } in members {
-                        match value {
-                            MemberDe::Block { value: block } => universe
-                                .insert_deserialized(name, BlockDef::new(block))
-                                .map(|_| ()),
-                            MemberDe::Character { value: character } => {
-                                universe.insert_deserialized(name, character).map(|_| ())
-                            }
-                            MemberDe::Space { value: space } => {
-                                universe.insert_deserialized(name, space).map(|_| ())
-                            }
-                        }
-                        .expect("insertion from deserialization failed");
+                        insert_member(&mut universe, name, value);
                     }
                 }
             }
@@ -795,6 +927,210 @@ mod universe {
         }
     }
 
+    impl Universe {
+        /// Deserialize a [`Universe`] from JSON data (in the same format accepted by its
+        /// [`Deserialize`] implementation), without failing entirely if individual members
+        /// cannot be deserialized. This is intended for recovering data from damaged files.
+        ///
+        /// Each member which fails to deserialize is omitted, and its name and error are
+        /// included in the returned list. If another member refers to it, or to any other
+        /// name which has no definition, a placeholder is substituted and that name is also
+        /// reported:
+        ///
+        /// * blocks are replaced with a block which displays the error,
+        /// * spaces are replaced with an empty space, and
+        /// * characters are replaced with a default character in an empty space.
+        ///
+        /// Errors in the structure of the data outside of any single member still cause
+        /// the entire operation to fail.
+        pub fn deserialize_recovering(
+            value: serde_json::Value,
+        ) -> Result<(Universe, Vec<(Name, serde_json::Error)>), serde_json::Error> {
+            use serde::de::Error as _;
+
+            /// Like [`schema::UniverseDe`] but with members not yet deserialized.
+            #[derive(Deserialize)]
+            #[serde(tag = "type")]
+            enum UniverseRecoveringDe {
+                UniverseV1 {
+                    members: Vec<MemberEntrySer<serde_json::Value>>,
+                },
+            }
+
+            let UniverseRecoveringDe::UniverseV1 { members } =
+                UniverseRecoveringDe::deserialize(value)?;
+
+            let mut loaded = Vec::with_capacity(members.len());
+            let mut errors: Vec<(Name, serde_json::Error)> = Vec::new();
+            let mut universe = {
+                let scope = ContextScope::install(DeContext {
+                    universe: Universe::new(),
+                });
+                for MemberEntrySer { name, value } in members {
+                    match MemberDe::deserialize(value) {
+                        Ok(member) => loaded.push((name, member)),
+                        Err(error) => errors.push((name, error)),
+                    }
+                }
+                scope.take().universe
+            };
+
+            for (name, member) in loaded {
+                insert_member(&mut universe, name, member);
+            }
+
+            // Give every dangling reference a placeholder value.
+            let mut report_missing = |name: &Name| {
+                if !errors.iter().any(|(failed_name, _)| failed_name == name) {
+                    errors.push((
+                        name.clone(),
+                        serde_json::Error::custom("member was referenced but not defined"),
+                    ));
+                }
+            };
+            for name in universe.unfilled_deserialized_names::<BlockDef>() {
+                report_missing(&name);
+                let block = Block::builder()
+                    .display_name(format!("Failed to load {name}"))
+                    .color(palette::MISSING_VOXEL_FALLBACK)
+                    .build();
+                universe
+                    .insert_deserialized(name, BlockDef::new(block))
+                    .expect("insertion of placeholder failed");
+            }
+            for name in universe.unfilled_deserialized_names::<Space>() {
+                report_missing(&name);
+                universe
+                    .insert_deserialized(name, Space::empty_positive(1, 1, 1))
+                    .expect("insertion of placeholder failed");
+            }
+            for name in universe.unfilled_deserialized_names::<Character>() {
+                report_missing(&name);
+                let space = universe.insert_anonymous(Space::empty_positive(1, 1, 1));
+                universe
+                    .insert_deserialized(name, Character::spawn_default(space))
+                    .expect("insertion of placeholder failed");
+            }
+
+            universe
+                .fix_deserialized_refs()
+                .map_err(serde_json::Error::custom)?;
+            universe.step(true, time::DeadlineNt::Asap);
+
+            Ok((universe, errors))
+        }
+    }
+
+    /// Insert a deserialized member into a universe under construction.
+    fn insert_member(universe: &mut Universe, name: Name, member: MemberDe) {
+        match member {
+            MemberDe::Block { value: block } => {
+                universe.insert_deserialized(name, BlockDef::new(block))
+            }
+            MemberDe::Character { value: character } => {
+                universe.insert_deserialized(name, character)
+            }
+            MemberDe::Space { value: space } => universe.insert_deserialized(name, space),
+        }
+        .expect("insertion from deserialization failed");
+    }
+
+    /// Serialize a single member, in the same form it would take within a serialized
+    /// [`Universe`], but without its name.
+    pub(crate) fn member_to_json(member: &AnyURef) -> Result<serde_json::Value, serde_json::Error> {
+        let member_repr = match member {
+            AnyURef::BlockDef(member_ref) => {
+                let read_guard: UBorrow<BlockDef> = member_ref.read().map_err(|e| {
+                    serde::ser::Error::custom(format!(
+                        "Failed to read universe member {name}: {e}",
+                        name = member_ref.name()
+                    ))
+                })?;
+                schema::MemberSer::from(&*read_guard)
+            }
+            AnyURef::Character(member_ref) => schema::MemberSer::Character {
+                value: schema::SerializeRef(member_ref.clone()),
+            },
+            AnyURef::Space(member_ref) => schema::MemberSer::Space {
+                value: schema::SerializeRef(member_ref.clone()),
+            },
+        };
+        serde_json::to_value(member_repr)
+    }
+
+    /// Deserialize members produced by [`member_to_json()`], such that the [`URef`]s
+    /// they contain refer to members of `universe`.
+    ///
+    /// As in ordinary [`Universe`] deserialization, a reference to a name which `universe`
+    /// does not contain causes a placeholder entry (with no value) to be added to it.
+    /// The caller is responsible for either giving those entries values or removing them.
+    pub(crate) fn members_from_json<'a>(
+        universe: &mut Universe,
+        members: impl IntoIterator<Item = (&'a Name, &'a serde_json::Value)>,
+    ) -> Result<Vec<(Name, MemberDe)>, (Name, serde_json::Error)> {
+        let scope = ContextScope::install(DeContext {
+            universe: std::mem::take(universe),
+        });
+        let result = members
+            .into_iter()
+            .map(|(name, value)| match MemberDe::deserialize(value) {
+                Ok(member) => Ok((name.clone(), member)),
+                Err(error) => Err((name.clone(), error)),
+            })
+            .collect();
+        *universe = scope.take().universe;
+        result
+    }
+
+    impl Serialize for universe::UniverseDiff {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let Self {
+                added,
+                removed,
+                changed,
+            } = self;
+            fn entries(
+                members: &BTreeMap<Name, serde_json::Value>,
+            ) -> Vec<MemberEntrySer<Cow<'_, serde_json::Value>>> {
+                members
+                    .iter()
+                    .map(|(name, value)| MemberEntrySer {
+                        name: name.clone(),
+                        value: Cow::Borrowed(value),
+                    })
+                    .collect()
+            }
+            schema::UniverseDiffSer::UniverseDiffV1 {
+                added: entries(added),
+                removed: removed.iter().cloned().collect(),
+                changed: entries(changed),
+            }
+            .serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for universe::UniverseDiff {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let entries = |members: Vec<MemberEntrySer<Cow<'_, serde_json::Value>>>| {
+                members
+                    .into_iter()
+                    .map(|MemberEntrySer { name, value }| (name, value.into_owned()))
+                    .collect()
+            };
+            match schema::UniverseDiffSer::deserialize(deserializer)? {
+                schema::UniverseDiffSer::UniverseDiffV1 {
+                    added,
+                    removed,
+                    changed,
+                } => Ok(universe::UniverseDiff {
+                    added: entries(added),
+                    removed: removed.into_iter().collect(),
+                    changed: entries(changed),
+                }),
+            }
+        }
+    }
+
     impl<T: 'static> Serialize for URef<T> {
         fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
             URefSer::URefV1 { name: self.name() }.serialize(serializer)
@@ -16,14 +16,16 @@
 //! * [`Cow`] is sometimes used to avoid unnecessary clones during serialization.
 
 use std::borrow::Cow;
+use std::fmt;
+use std::marker::PhantomData;
 use std::sync::Arc;
 
 use ordered_float::NotNan;
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Serialize};
 
 use crate::block::Block;
 use crate::math::{Aab, Face6, GridAab, GridCoordinate, GridRotation};
-use crate::save::compress::{GzSerde, Leu16};
+use crate::save::compress::{GzSerde, Leu16, Leu32};
 use crate::universe::URef;
 use crate::{behavior, block, character, inv, space, universe};
 
@@ -102,8 +104,9 @@ pub(crate) struct BlockAttributesV1Ser {
     pub selectable: bool,
     #[serde(default, skip_serializing_if = "is_default")]
     pub rotation_rule: RotationPlacementRuleSer,
-    // TODO: tick_action is a kludge but we should serialize it or its replacement
-    // pub(crate) tick_action: Option<VoxelBrush<'static>>,
+    // TODO: tick_action is a kludge; when it is replaced, so should this be
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tick_action: Option<VoxelBrushSer>,
     #[serde(default, skip_serializing_if = "is_default")]
     pub animation_hint: AnimationHintSer,
 }
@@ -182,6 +185,14 @@ pub(crate) enum ModifierSer {
         distance: u16,
         velocity: i16,
     },
+    ClipV1 {
+        bounds: GridAab,
+    },
+    GradientV1 {
+        direction: Face6,
+        from: RgbaSer,
+        to: RgbaSer,
+    },
 }
 
 //------------------------------------------------------------------------------------------------//
@@ -216,6 +227,22 @@ pub(crate) enum SpawnSer {
         inventory: Vec<Option<InvStackSer>>,
     },
 }
+
+//------------------------------------------------------------------------------------------------//
+// Schema corresponding to the `drawing` module
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(tag = "type")]
+pub(crate) enum VoxelBrushSer {
+    VoxelBrushV1 { contents: Vec<VoxelBrushEntrySer> },
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct VoxelBrushEntrySer {
+    pub offset: [GridCoordinate; 3],
+    pub block: Block,
+}
+
 //------------------------------------------------------------------------------------------------//
 // Schema corresponding to the `inv` module
 
@@ -279,7 +306,7 @@ pub(crate) enum SpaceSer<'a> {
         bounds: GridAab,
         physics: SpacePhysicsSerV1,
         blocks: Vec<block::Block>,
-        contents: GzSerde<'a, Leu16>,
+        contents: SpaceContentsSer<'a>,
         light: Option<GzSerde<'a, LightSerV1>>,
         #[serde(default, skip_serializing_if = "behavior::BehaviorSet::is_empty")]
         behaviors: Cow<'a, behavior::BehaviorSet<space::Space>>,
@@ -287,6 +314,106 @@ pub(crate) enum SpaceSer<'a> {
     },
 }
 
+/// Block indices of every cube of a space, in `GridArray` order.
+///
+/// This is untagged because the dense form predates the alternatives.
+/// Deserialization is not derived, because `#[serde(untagged)]` would discard the
+/// error from each variant; instead, the variant is chosen by the first key of the map,
+/// since the keys of the dense form (`Base64Gzip` or `Gzip`) differ from all others.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub(crate) enum SpaceContentsSer<'a> {
+    Dense(GzSerde<'a, Leu16>),
+    Runs(SpaceContentsRunsSer<'a>),
+}
+
+impl<'de, 'a> Deserialize<'de> for SpaceContentsSer<'a> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct ContentsVisitor<'a>(PhantomData<SpaceContentsSer<'a>>);
+
+        impl<'de, 'a> de::Visitor<'de> for ContentsVisitor<'a> {
+            type Value = SpaceContentsSer<'a>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("space contents")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::MapAccess<'de>,
+            {
+                let Some(first_key) = map.next_key::<String>()? else {
+                    return Err(de::Error::invalid_length(0, &self));
+                };
+                let is_dense = matches!(first_key.as_str(), "Base64Gzip" | "Gzip");
+                let map = de::value::MapAccessDeserializer::new(ReplayKey {
+                    key: Some(first_key),
+                    map,
+                });
+                if is_dense {
+                    GzSerde::deserialize(map).map(SpaceContentsSer::Dense)
+                } else {
+                    SpaceContentsRunsSer::deserialize(map).map(SpaceContentsSer::Runs)
+                }
+            }
+        }
+
+        /// [`MapAccess`](de::MapAccess) which produces `key` and then the rest of `map`.
+        struct ReplayKey<A> {
+            key: Option<String>,
+            map: A,
+        }
+
+        impl<'de, A: de::MapAccess<'de>> de::MapAccess<'de> for ReplayKey<A> {
+            type Error = A::Error;
+
+            fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, A::Error>
+            where
+                K: de::DeserializeSeed<'de>,
+            {
+                match self.key.take() {
+                    Some(key) => seed
+                        .deserialize(de::IntoDeserializer::<A::Error>::into_deserializer(key))
+                        .map(Some),
+                    None => self.map.next_key_seed(seed),
+                }
+            }
+
+            fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, A::Error>
+            where
+                V: de::DeserializeSeed<'de>,
+            {
+                self.map.next_value_seed(seed)
+            }
+
+            fn size_hint(&self) -> Option<usize> {
+                Some(self.map.size_hint()? + usize::from(self.key.is_some()))
+            }
+        }
+
+        deserializer.deserialize_map(ContentsVisitor(PhantomData))
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(tag = "type")]
+pub(crate) enum SpaceContentsRunsSer<'a> {
+    RunsV1 { runs: GzSerde<'a, ContentsRunSerV1> },
+}
+
+/// A run of consecutive cubes, in `GridArray` order, which have the same block index.
+///
+/// Note: This is used inside `GzSerde`, so it must be endiannness-independent.
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+pub(crate) struct ContentsRunSerV1 {
+    pub length: Leu32,
+    pub block_index: Leu16,
+}
+
 /// Schema for serializing `PackedLight`.
 ///
 /// Note: This is used inside `GzSerde`, so it must be endiannness-independent.
@@ -359,6 +486,20 @@ pub(crate) type MemberSer =
     MemberSchema<SerializeRef<character::Character>, SerializeRef<space::Space>>;
 pub(crate) type MemberDe = MemberSchema<character::Character, space::Space>;
 
+/// Schema for `UniverseDiff` serialization and deserialization.
+///
+/// The members are kept in their already-serialized form (see `member_to_json()`),
+/// so that they need not be deserialized until the diff is applied.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(tag = "type")]
+pub(crate) enum UniverseDiffSer<'a> {
+    UniverseDiffV1 {
+        added: Vec<MemberEntrySer<Cow<'a, serde_json::Value>>>,
+        removed: Vec<universe::Name>,
+        changed: Vec<MemberEntrySer<Cow<'a, serde_json::Value>>>,
+    },
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(tag = "type")]
 pub(crate) enum URefSer {
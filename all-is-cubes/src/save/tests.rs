@@ -13,9 +13,11 @@ use crate::block::{
 };
 use crate::character::{Character, Spawn};
 use crate::content::make_some_blocks;
+use crate::drawing::VoxelBrush;
 use crate::inv::Tool;
 use crate::math::{Face6, GridAab, GridRotation, Rgb, Rgba};
 use crate::save::compress::{GzSerde, Leu16};
+use crate::save::schema;
 use crate::space::{self, BlockIndex, LightPhysics, Space, SpacePhysics};
 use crate::time::{self, Tick};
 use crate::transaction::Transaction as _;
@@ -159,7 +161,6 @@ fn block_atom_default() {
 
 #[test]
 fn block_atom_with_all_attributes() {
-    // TODO: tick_action is not serialized yet,
     assert_round_trip_value(
         &Block::builder()
             .color(Rgba::new(1.0, 0.5, 0.0, 0.5))
@@ -168,6 +169,7 @@ fn block_atom_with_all_attributes() {
             .selectable(false)
             .rotation_rule(block::RotationPlacementRule::Attach { by: Face6::PX })
             .light_emission(Rgb::new(1.0, 0.0, 10.0))
+            .tick_action(Some(VoxelBrush::new([([0, 1, 0], AIR)])))
             .animation_hint(AnimationHint {
                 redefinition: AnimationChange::ColorSameCategory,
                 replacement: AnimationChange::Shape,
@@ -186,6 +188,18 @@ fn block_atom_with_all_attributes() {
                     "by": "PX",
                 },
                 "light_emission": [1.0, 0.0, 10.0],
+                "tick_action": {
+                    "type": "VoxelBrushV1",
+                    "contents": [
+                        {
+                            "offset": [0, 1, 0],
+                            "block": {
+                                "type": "BlockV1",
+                                "primitive": { "type": "AirV1" },
+                            },
+                        },
+                    ],
+                },
                 "animation_hint": {
                     "type": "AnimationHintV1",
                     "redefinition": "ColorSameCategory",
@@ -218,6 +232,82 @@ fn block_with_modifiers() {
     );
 }
 
+#[test]
+fn block_with_clip_modifier() {
+    assert_round_trip_value(
+        &Block::builder()
+            .color(Rgba::WHITE)
+            .modifier(Modifier::Clip(GridAab::from_lower_size(
+                [0, 0, 0],
+                [8, 8, 8],
+            )))
+            .build(),
+        json!({
+            "type": "BlockV1",
+            "primitive": {
+                "type": "AtomV1",
+                "color": [1.0, 1.0, 1.0, 1.0],
+            },
+            "modifiers": [
+                {
+                    "type": "ClipV1",
+                    "bounds": { "lower": [0, 0, 0], "upper": [8, 8, 8] },
+                },
+            ]
+        }),
+    );
+}
+
+#[test]
+fn block_with_gradient_modifier() {
+    assert_round_trip_value(
+        &Block::builder()
+            .color(Rgba::WHITE)
+            .modifier(Modifier::Gradient {
+                direction: Face6::PY,
+                from: Rgba::new(1.0, 0.0, 0.0, 1.0),
+                to: Rgba::new(0.0, 0.0, 1.0, 0.5),
+            })
+            .build(),
+        json!({
+            "type": "BlockV1",
+            "primitive": {
+                "type": "AtomV1",
+                "color": [1.0, 1.0, 1.0, 1.0],
+            },
+            "modifiers": [
+                {
+                    "type": "GradientV1",
+                    "direction": "PY",
+                    "from": [1.0, 0.0, 0.0, 1.0],
+                    "to": [0.0, 0.0, 1.0, 0.5],
+                },
+            ]
+        }),
+    );
+}
+
+/// A block's `tick_action` must survive a round trip for it to still animate.
+#[test]
+fn block_tick_action_still_animates() {
+    let [block2] = make_some_blocks();
+    let block1 = Block::builder()
+        .color(Rgba::WHITE)
+        .tick_action(Some(VoxelBrush::single(block2.clone())))
+        .build();
+
+    let block1: Block = from_value(to_value(&block1).unwrap()).unwrap();
+    assert_eq!(
+        block1.evaluate().unwrap().attributes.tick_action,
+        Some(VoxelBrush::single(block2.clone()))
+    );
+
+    let mut space = Space::empty_positive(1, 1, 1);
+    space.set([0, 0, 0], block1).unwrap();
+    let _ = space.step(None, Tick::arbitrary(), time::DeadlineStd::Whenever);
+    assert_eq!(space[[0, 0, 0]], block2);
+}
+
 // TODO: test serialization of each modifier
 
 #[test]
@@ -508,6 +598,119 @@ fn space_with_sparse_indices() {
     assert_eq!(space2[[2, 0, 0]], block2);
 }
 
+#[test]
+fn space_mostly_empty_uses_runs() {
+    let [block0, block1] = make_some_blocks();
+    let bounds = GridAab::from_lower_size([0, 0, 0], [64, 64, 64]);
+    let mut space = Space::builder(bounds).build();
+    space.set([0, 0, 0], &block0).unwrap();
+    space.set([10, 20, 30], &block1).unwrap();
+    space.set([10, 20, 31], &block1).unwrap();
+    space.set([63, 63, 63], &block0).unwrap();
+
+    let json_value = to_value(&space).unwrap();
+    let contents_json = &json_value["contents"];
+    assert_eq!(contents_json["type"], "RunsV1");
+
+    let dense_json = space_contents_json(
+        space
+            .extract(bounds, |e| e.block_index())
+            .into_elements()
+            .into_vec(),
+    );
+    let runs_length = contents_json.to_string().len();
+    let dense_length = dense_json.to_string().len();
+    assert!(
+        runs_length * 4 < dense_length,
+        "runs {runs_length} bytes should be far smaller than dense {dense_length} bytes"
+    );
+
+    let space2: Space = from_value(json_value).unwrap();
+    for cube in bounds.interior_iter() {
+        assert_eq!(space2[cube], space[cube], "{cube:?}");
+    }
+}
+
+#[test]
+fn space_dense_not_runs() {
+    let [block] = make_some_blocks();
+    let bounds = GridAab::from_lower_size([0, 0, 0], [16, 16, 16]);
+    let mut space = Space::builder(bounds).build();
+    space
+        .fill(bounds, |cube| {
+            if (cube.x + cube.y + cube.z) % 2 == 0 {
+                Some(&block)
+            } else {
+                Some(&AIR)
+            }
+        })
+        .unwrap();
+
+    let json_value = to_value(&space).unwrap();
+    assert_eq!(
+        json_value["contents"],
+        space_contents_json(
+            space
+                .extract(bounds, |e| e.block_index())
+                .into_elements()
+                .into_vec()
+        )
+    );
+}
+
+#[test]
+fn space_de_runs_wrong_length() {
+    assert_de_error::<Space>(
+        json!({
+            "type": "SpaceV1",
+            "bounds": {
+                "lower": [0, 0, 0],
+                "upper": [3, 1, 1],
+            },
+            "physics": dont_care_physics_json(),
+            "spawn": {
+                "type": "SpawnV1",
+                "bounds": {
+                    "lower": [0, 0, 2],
+                    "upper": [2, 2, 42],
+                },
+                "eye_position": null,
+                "inventory": [],
+                "look_direction": [0.0, 0.0, -1.0],
+            },
+            "blocks": [
+                {
+                    "type": "BlockV1",
+                    "primitive": {"type": "AirV1"},
+                },
+            ],
+            "contents": {
+                "type": "RunsV1",
+                "runs": serde_json::to_value(GzSerde(vec![[2, 0, 0, 0, 0, 0u8]].into())).unwrap(),
+            },
+        }),
+        "space contents runs have total length 2, expected 3",
+    )
+}
+
+/// Errors in either form of space contents are reported as such, not as a failure to
+/// match any form.
+#[test]
+fn space_de_contents_errors() {
+    type Contents = schema::SpaceContentsSer<'static>;
+    // The rest of this message comes from the base64 library.
+    let error = from_value::<Contents>(json!({"Base64Gzip": "!"})).unwrap_err();
+    assert!(
+        error.to_string().starts_with("invalid base64+gzip data: "),
+        "{error}"
+    );
+    assert_de_error::<Contents>(
+        json!({"type": "RunsV2", "runs": {"Gzip": []}}),
+        "unknown variant `RunsV2`, expected `RunsV1`",
+    );
+    assert_de_error::<Contents>(json!({"runs": {"Gzip": []}}), "missing field `type`");
+}
+
 #[test]
 fn space_light_queue_remembered() {
     use space::LightStatus::{NoRays, Opaque, Uninitialized, Visible};
@@ -767,6 +970,132 @@ fn universe_de_error_in_member() {
     );
 }
 
+/// [`Universe::deserialize_recovering()`] substitutes placeholders for missing members,
+/// of each member type, and reports them.
+#[test]
+fn universe_de_recovering_missing_members() {
+    let (universe, errors) = Universe::deserialize_recovering(json!({
+        "type": "UniverseV1",
+        "members": [
+            {
+                "name": {"Specific": "broken_block"},
+                "member_type": "Block",
+                "value": {"type": "BlockV1"}
+            },
+            {
+                "name": {"Specific": "referrer"},
+                "member_type": "Block",
+                "value": {
+                    "type": "BlockV1",
+                    "primitive": {
+                        "type": "IndirectV1",
+                        "definition": {"type": "URefV1", "Specific": "missing_block"},
+                    }
+                }
+            },
+            {
+                "name": {"Specific": "a_character"},
+                "member_type": "Character",
+                "value": {
+                    "type": "CharacterV1",
+                    "space": {"type": "URefV1", "Specific": "missing_space"},
+                    "position": [0.5, 0.5, 0.5],
+                    "velocity": [0.0, 0.0, 0.0],
+                    "collision_box": {
+                        "lower": [-0.35, -1.75, -0.35],
+                        "upper": [0.35, 0.15, 0.35],
+                    },
+                    "flying": false,
+                    "noclip": false,
+                    "yaw": 0.0,
+                    "pitch": 0.0,
+                    "selected_slots": [0, 0, 0],
+                    "inventory": {"type": "InventoryV1", "slots": []},
+                }
+            },
+        ],
+    }))
+    .unwrap();
+
+    assert_eq!(
+        errors
+            .iter()
+            .map(|(name, error)| (name.clone(), error.to_string()))
+            .collect::<Vec<_>>(),
+        vec![
+            (
+                Name::from("broken_block"),
+                "missing field `primitive`".to_owned()
+            ),
+            (
+                Name::from("missing_block"),
+                "member was referenced but not defined".to_owned()
+            ),
+            (
+                Name::from("missing_space"),
+                "member was referenced but not defined".to_owned()
+            ),
+        ]
+    );
+
+    let referrer = universe.get::<BlockDef>(&"referrer".into()).unwrap();
+    assert_eq!(
+        referrer
+            .read()
+            .unwrap()
+            .evaluate()
+            .unwrap()
+            .attributes
+            .display_name,
+        "Failed to load 'missing_block'"
+    );
+    let character = universe.get::<Character>(&"a_character".into()).unwrap();
+    assert_eq!(
+        character.read().unwrap().space,
+        universe.get::<Space>(&"missing_space".into()).unwrap()
+    );
+}
+
+#[test]
+fn universe_diff() {
+    let mut old = Universe::new();
+    old.insert("removed".into(), BlockDef::new(AIR)).unwrap();
+    old.insert("changed".into(), BlockDef::new(AIR)).unwrap();
+    let mut new = Universe::new();
+    new.insert("changed".into(), BlockDef::new(Block::from(Rgba::WHITE)))
+        .unwrap();
+    new.insert("added".into(), BlockDef::new(AIR)).unwrap();
+    let diff = old.diff(&new).unwrap();
+
+    assert_round_trip_value(
+        &diff,
+        json!({
+            "type": "UniverseDiffV1",
+            "added": [
+                {
+                    "name": {"Specific": "added"},
+                    "member_type": "Block",
+                    "value": {
+                        "type": "BlockV1",
+                        "primitive": {"type": "AirV1"},
+                    },
+                },
+            ],
+            "removed": [{"Specific": "removed"}],
+            "changed": [
+                {
+                    "name": {"Specific": "changed"},
+                    "member_type": "Block",
+                    "value": {
+                        "type": "BlockV1",
+                        "primitive": {"type": "AtomV1", "color": [1.0, 1.0, 1.0, 1.0]},
+                    },
+                },
+            ],
+        }),
+    );
+}
+
 #[test]
 fn uref_de_named() {
     let r: URef<BlockDef> = from_value(json!({
@@ -3,12 +3,13 @@
 use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::hash::{Hash as _, Hasher as _};
 use std::time::Duration;
 
-use cgmath::Vector3;
+use cgmath::{EuclideanSpace as _, Vector3};
 
 use crate::behavior::{self, BehaviorSet};
-use crate::block::{Block, EvaluatedBlock, Resolution, AIR, AIR_EVALUATED};
+use crate::block::{Block, BlockAttributes, EvaluatedBlock, Resolution, AIR, AIR_EVALUATED};
 #[cfg(doc)]
 use crate::character::Character;
 use crate::character::Spawn;
@@ -17,13 +18,13 @@ use crate::drawing::DrawingPlane;
 use crate::inv::EphemeralOpaque;
 use crate::listen::{Listen, Listener, Notifier};
 use crate::math::{
-    Cube, Face6, FreeCoordinate, GridAab, GridArray, GridCoordinate, GridRotation, Gridgid, NotNan,
-    Rgb,
+    Cube, Face6, FreeCoordinate, GridAab, GridArray, GridCoordinate, GridPoint, GridRotation,
+    Gridgid, NotNan, Rgb,
 };
 use crate::time;
 use crate::transaction::{Merge, Transaction as _};
 use crate::universe::{RefVisitor, URef, UniverseTransaction, VisitRefs};
-use crate::util::{ConciseDebug, CustomFormat, StatusText, TimeStats};
+use crate::util::{ConciseDebug, CustomFormat, StableHasher, StatusText, TimeStats, YieldProgress};
 
 mod builder;
 pub use builder::{SpaceBuilder, SpaceBuilderBounds};
@@ -31,14 +32,20 @@ pub use builder::{SpaceBuilder, SpaceBuilderBounds};
 mod light;
 #[doc(hidden)] // pub only for visualization by all-is-cubes-gpu
 pub use light::LightUpdateCubeInfo;
+pub(crate) use light::LightUpdateRequest;
 use light::{opaque_for_light_computation, LightUpdateQueue, PackedLightScalar};
-pub(crate) use light::{LightStatus, LightUpdateRequest};
-pub use light::{LightUpdatesInfo, PackedLight};
+pub use light::{LightStatus, LightUpdatesInfo, PackedLight};
 
 mod palette;
 use palette::Palette;
 pub use palette::{PaletteError, SpaceBlockData};
 
+mod raycast;
+pub use raycast::{RaycastHit, SpaceRaycaster};
+
+mod resample;
+pub use resample::ResampleMode;
+
 mod space_txn;
 pub use space_txn::*;
 
@@ -90,6 +97,9 @@ pub struct Space {
     cubes_wanting_ticks: HashSet<Cube>,
 
     notifier: Notifier<SpaceChange>,
+
+    /// Notifier for [`Space::edit_events()`].
+    edit_notifier: Notifier<SpaceEdit>,
 }
 
 impl fmt::Debug for Space {
@@ -203,6 +213,7 @@ impl Space {
             spawn: spawn.unwrap_or_else(|| Spawn::default_for_new_space(bounds)),
             cubes_wanting_ticks: HashSet::new(),
             notifier: Notifier::new(),
+            edit_notifier: Notifier::new(),
         }
     }
 
@@ -266,6 +277,85 @@ impl Space {
         }
     }
 
+    /// Computes a hash of the contents of the cubes of this space within `bounds`, for
+    /// detecting whether that region has changed.
+    ///
+    /// The hash depends on `bounds` and on the evaluated voxels and [attributes] of the
+    /// block in each cube (cubes outside the space count as [`AIR`]), but not on lighting
+    /// or on the internal numbering of blocks. Blocks in a [`tick_action`] are hashed by
+    /// what they evaluate to, since a [`URef`]'s identity is not stable. The hash is
+    /// computed with a fixed algorithm, so equal contents produce equal hashes in every run
+    /// of the program and on every platform.
+    ///
+    /// [attributes]: crate::block::BlockAttributes
+    /// [`tick_action`]: crate::block::BlockAttributes::tick_action
+    pub fn region_hash(&self, bounds: GridAab) -> u64 {
+        fn block_hash(evaluated: &EvaluatedBlock) -> u64 {
+            let mut hasher = StableHasher::default();
+            hash_evaluated(evaluated, &mut hasher, true);
+            hasher.finish()
+        }
+
+        fn hash_evaluated(
+            evaluated: &EvaluatedBlock,
+            hasher: &mut StableHasher,
+            include_tick_action: bool,
+        ) {
+            // Destructure so that new attributes cannot be forgotten here.
+            let BlockAttributes {
+                display_name,
+                selectable,
+                rotation_rule,
+                tick_action,
+                animation_hint,
+            } = &evaluated.attributes;
+
+            evaluated.voxels.hash(hasher);
+            display_name.hash(hasher);
+            selectable.hash(hasher);
+            rotation_rule.hash(hasher);
+            animation_hint.hash(hasher);
+
+            // Don't follow tick actions of tick actions, which may form cycles.
+            match tick_action {
+                Some(brush) if include_tick_action => {
+                    hasher.write_u8(1);
+                    hasher.write_u64(brush.entries().len() as u64);
+                    for (offset, block) in brush.entries() {
+                        offset.hash(hasher);
+                        match block.evaluate() {
+                            Ok(evaluated) => {
+                                hasher.write_u8(1);
+                                hash_evaluated(&evaluated, hasher, false);
+                            }
+                            Err(_) => hasher.write_u8(0),
+                        }
+                    }
+                }
+                _ => hasher.write_u8(0),
+            }
+        }
+
+        let palette_hashes: Vec<u64> = self
+            .palette
+            .entries()
+            .iter()
+            .map(|data| block_hash(data.evaluated()))
+            .collect();
+        let air_hash = block_hash(&AIR_EVALUATED);
+
+        let mut hasher = StableHasher::default();
+        bounds.hash(&mut hasher);
+        for cube in bounds.interior_iter() {
+            let cube_hash = match self.get_block_index(cube) {
+                Some(index) => palette_hashes[usize::from(index)],
+                None => air_hash,
+            };
+            hasher.write_u64(cube_hash);
+        }
+        hasher.finish()
+    }
+
     /// Returns the light occupying the given cube.
     ///
     /// This value may be considered as representing the average of the light reflecting
@@ -314,10 +404,15 @@ impl Space {
     ) -> Result<bool, SetCubeError> {
         // Delegate to a monomorphic function.
         // This may reduce compile time and code size.
-        self.set_impl(position.into(), &block.into())
+        self.set_impl(position.into(), &block.into(), SpaceEditSource::Set)
     }
 
-    fn set_impl(&mut self, position: Cube, block: &Block) -> Result<bool, SetCubeError> {
+    fn set_impl(
+        &mut self,
+        position: Cube,
+        block: &Block,
+        source: SpaceEditSource,
+    ) -> Result<bool, SetCubeError> {
         if let Some(contents_index) = self.bounds.index(position) {
             let old_block_index = self.contents[contents_index];
             let old_block = self.palette.entry(old_block_index).block();
@@ -326,6 +421,16 @@ impl Space {
                 return Ok(false);
             }
 
+            // Only pay for constructing the edit event if someone is listening.
+            let old_block_name = self.edit_notifier.has_listeners().then(|| {
+                self.palette
+                    .entry(old_block_index)
+                    .evaluated
+                    .attributes
+                    .display_name
+                    .clone()
+            });
+
             // Replacing one unique block with a new one.
             //
             // This special case is worth having because it means that if a block is
@@ -339,6 +444,7 @@ impl Space {
                 .try_replace_unique(old_block_index, block, &self.notifier)
             {
                 self.side_effects_of_set(old_block_index, position, contents_index);
+                self.notify_edit(position, old_block_name, old_block_index, source);
                 return Ok(true);
             }
 
@@ -353,6 +459,7 @@ impl Space {
             self.contents[contents_index] = new_block_index;
 
             self.side_effects_of_set(new_block_index, position, contents_index);
+            self.notify_edit(position, old_block_name, new_block_index, source);
             Ok(true)
         } else {
             Err(SetCubeError::OutOfBounds {
@@ -411,6 +518,31 @@ impl Space {
         self.notifier.notify(SpaceChange::Block(position));
     }
 
+    /// Deliver a [`SpaceEdit`] for a completed [`Space::set_impl()`], if there are
+    /// listeners (in which case `old_block_name` was computed).
+    fn notify_edit(
+        &self,
+        cube: Cube,
+        old_block_name: Option<Cow<'static, str>>,
+        new_block_index: BlockIndex,
+        source: SpaceEditSource,
+    ) {
+        if let Some(old_block_name) = old_block_name {
+            self.edit_notifier.notify(SpaceEdit {
+                cube,
+                old_block_name,
+                new_block_name: self
+                    .palette
+                    .entry(new_block_index)
+                    .evaluated
+                    .attributes
+                    .display_name
+                    .clone(),
+                source,
+            });
+        }
+    }
+
     /// Replace blocks in `region` with a block computed by the function.
     ///
     /// The function may return a reference to a block or a block. If it returns [`None`],
@@ -454,12 +586,61 @@ impl Space {
             if let Some(block) = function(cube) {
                 // TODO: Optimize side effect processing by batching lighting updates for
                 // when we know what's now opaque or not.
-                self.set(cube, block.borrow())?;
+                self.set_impl(cube, block.borrow(), SpaceEditSource::Fill)?;
             }
         }
         Ok(())
     }
 
+    /// As [`Space::fill`], but divides the region into slices which are filled one at a
+    /// time, reporting progress and yielding via `progress` after each one, so that
+    /// filling a large region does not block other tasks for long.
+    ///
+    /// The cubes are visited in the same order as by [`Space::fill`], so the result is the
+    /// same as if `function` had been given to [`Space::fill`] instead, even if `function`
+    /// has side effects.
+    pub async fn fill_region_async<F, B>(
+        &mut self,
+        region: GridAab,
+        mut function: F,
+        progress: YieldProgress,
+    ) -> Result<(), SetCubeError>
+    where
+        F: FnMut(Cube) -> Option<B>,
+        B: std::borrow::Borrow<Block>,
+    {
+        /// Approximate number of cubes to fill between yields.
+        const CUBES_PER_SLICE: usize = 4096;
+
+        if !self.bounds.contains_box(region) {
+            return Err(SetCubeError::OutOfBounds {
+                modification: region,
+                space_bounds: self.bounds,
+            });
+        }
+
+        // Slice perpendicular to the X axis, since X is the outermost loop of
+        // `interior_iter()`; thus the slices are visited in the same order as `fill()`.
+        let cubes_per_x = region.size().y as usize * region.size().z as usize;
+        let slice_thickness = (CUBES_PER_SLICE / cubes_per_x.max(1)).max(1) as GridCoordinate;
+        let x_range = region.x_range();
+        let mut x = x_range.start;
+        while x < x_range.end {
+            let slice_end = x.saturating_add(slice_thickness).min(x_range.end);
+            let slice = GridAab::from_lower_upper(
+                [x, region.lower_bounds().y, region.lower_bounds().z],
+                [slice_end, region.upper_bounds().y, region.upper_bounds().z],
+            );
+            self.fill(slice, &mut function)?;
+            x = slice_end;
+            progress
+                .progress((x - x_range.start) as f32 / region.size().x as f32)
+                .await;
+        }
+        progress.finish().await;
+        Ok(())
+    }
+
     /// Replace blocks in `region` with the given block.
     ///
     /// TODO: Document error behavior
@@ -490,8 +671,9 @@ impl Space {
                 modification: region,
                 space_bounds: self.bounds,
             })
-        } else if self.bounds() == region {
+        } else if self.bounds() == region && !self.edit_notifier.has_listeners() {
             // We're overwriting the entire space, so we might as well re-initialize it.
+            // (But not if there are edit listeners, since they expect an event per cube.)
             let block = block.into();
             let volume = self.bounds().volume();
             self.palette = Palette::new(block.clone().into_owned(), volume);
@@ -507,6 +689,69 @@ impl Space {
         }
     }
 
+    /// Copy the blocks in `src_bounds` of `src` into this space, rotated by `rotation`
+    /// and translated so that the lower corner of the rotated region is at `dst_origin`.
+    ///
+    /// Each copied block is also rotated (using [`Block::rotate()`]) so that its
+    /// orientation matches the rotated positions. Cubes of `src_bounds` which are outside
+    /// of `src`'s bounds are treated as [`AIR`], as [`Space`]'s indexing does.
+    ///
+    /// As with [`Space::fill()`], if the destination region extends outside of
+    /// [`self.bounds()`](Self::bounds), that will be rejected before any changes are made.
+    ///
+    /// ```
+    /// use all_is_cubes::block::Block;
+    /// use all_is_cubes::math::{GridAab, GridRotation, Rgba};
+    /// use all_is_cubes::space::Space;
+    ///
+    /// let a_block: Block = Rgba::new(1.0, 0.0, 0.0, 1.0).into();
+    /// let mut source = Space::empty_positive(2, 1, 1);
+    /// source.set([1, 0, 0], &a_block).unwrap();
+    ///
+    /// let mut destination = Space::empty_positive(10, 10, 10);
+    /// destination
+    ///     .copy_region(&source, source.bounds(), [5, 0, 5].into(), GridRotation::CLOCKWISE)
+    ///     .unwrap();
+    /// assert_eq!(destination[[5, 0, 6]], a_block);
+    /// ```
+    pub fn copy_region(
+        &mut self,
+        src: &Space,
+        src_bounds: GridAab,
+        dst_origin: GridPoint,
+        rotation: GridRotation,
+    ) -> Result<(), SetCubeError> {
+        let dst_size = rotation
+            .transform_vector(src_bounds.size())
+            .map(GridCoordinate::abs);
+        let out_of_bounds = |modification| SetCubeError::OutOfBounds {
+            modification,
+            space_bounds: self.bounds,
+        };
+        // If the destination region overflows, report the part of it that doesn't.
+        let dst_bounds = GridAab::checked_from_lower_size(dst_origin, dst_size).map_err(|_| {
+            out_of_bounds(
+                GridAab::from_lower_size([0, 0, 0], dst_size).translate(dst_origin.to_vec()),
+            )
+        })?;
+        let rotated_bounds = src_bounds
+            .transform(rotation.into())
+            .ok_or_else(|| out_of_bounds(dst_bounds))?;
+        let transform = Gridgid {
+            rotation,
+            translation: dst_origin - rotated_bounds.lower_bounds(),
+        };
+        let inverse = transform.inverse();
+
+        self.fill(dst_bounds, |dst_cube| {
+            Some(
+                src[inverse.transform_cube(dst_cube)]
+                    .clone()
+                    .rotate(rotation),
+            )
+        })
+    }
+
     /// Provides an [`DrawTarget`](embedded_graphics::prelude::DrawTarget)
     /// adapter for 2.5D drawing.
     ///
@@ -539,6 +784,108 @@ impl Space {
         self.palette.entries()
     }
 
+    /// Computes statistics about the blocks in this space: how many of each there are,
+    /// and where the non-[`AIR`] ones are.
+    ///
+    /// This examines every cube of the space, so it is not cheap for large spaces.
+    pub fn statistics(&self) -> SpaceStats {
+        let is_air: Vec<bool> = self
+            .palette
+            .entries()
+            .iter()
+            .map(|data| data.block == AIR)
+            .collect();
+        let mut block_counts = vec![0; is_air.len()];
+        let mut non_air_count = 0;
+        let mut occupied: Option<([GridCoordinate; 3], [GridCoordinate; 3])> = None;
+
+        // `contents` is in the same order as `interior_iter()`.
+        for (cube, &index) in self.bounds.interior_iter().zip(self.contents.iter()) {
+            let index = usize::from(index);
+            block_counts[index] += 1;
+            if !is_air[index] {
+                non_air_count += 1;
+                let cube: [GridCoordinate; 3] = cube.into();
+                occupied = Some(match occupied {
+                    None => (cube, cube),
+                    Some((lower, upper)) => (
+                        [0, 1, 2].map(|axis| lower[axis].min(cube[axis])),
+                        [0, 1, 2].map(|axis| upper[axis].max(cube[axis])),
+                    ),
+                });
+            }
+        }
+
+        SpaceStats {
+            cube_count: self.contents.len(),
+            non_air_count,
+            distinct_blocks: block_counts.iter().filter(|&&count| count > 0).count(),
+            block_counts,
+            occupied_bounds: occupied.map(|(lower, upper)| {
+                GridAab::from_lower_upper(lower, upper.map(|coord| coord + 1))
+            }),
+        }
+    }
+
+    /// Finds the cubes which can be reached from `start` by moving between face-adjacent
+    /// cubes whose blocks satisfy `predicate` (a 6-connected flood fill), and returns an
+    /// array, with the same bounds as the space, which is `true` for those cubes.
+    ///
+    /// If `start` is out of bounds or its block does not satisfy `predicate`, the result
+    /// is entirely `false`. The region never extends outside the bounds of the space.
+    ///
+    /// `predicate` is called once for each distinct block in the space, rather than
+    /// once per cube.
+    ///
+    /// ```
+    /// use all_is_cubes::block::{Block, AIR};
+    /// use all_is_cubes::math::Rgba;
+    /// use all_is_cubes::space::Space;
+    ///
+    /// let mut space = Space::empty_positive(3, 1, 1);
+    /// space.set([1, 0, 0], Block::from(Rgba::WHITE))?;
+    ///
+    /// // The wall in the middle separates the two ends.
+    /// let region = space.connected_region([0, 0, 0], |block| *block == AIR);
+    /// assert_eq!(region.get([0, 0, 0]), Some(&true));
+    /// assert_eq!(region.get([2, 0, 0]), Some(&false));
+    /// # Ok::<(), all_is_cubes::space::SetCubeError>(())
+    /// ```
+    pub fn connected_region(
+        &self,
+        start: impl Into<Cube>,
+        predicate: impl Fn(&Block) -> bool,
+    ) -> GridArray<bool> {
+        let matches: Vec<bool> = self
+            .palette
+            .entries()
+            .iter()
+            .map(|data| predicate(&data.block))
+            .collect();
+        // `region`'s elements are in the same order as `self.contents`.
+        let mut region = GridArray::repeat(self.bounds, false);
+        let reached = region.elements_mut();
+
+        // Explicit stack rather than recursion, so that large regions cannot overflow.
+        let mut stack: Vec<Cube> = vec![start.into()];
+        while let Some(cube) = stack.pop() {
+            let Some(index) = self.bounds.index(cube) else {
+                continue;
+            };
+            if reached[index] || !matches[usize::from(self.contents[index])] {
+                continue;
+            }
+            reached[index] = true;
+            for face in Face6::ALL {
+                if let Some(neighbor) = cube.checked_add(face.normal_vector()) {
+                    stack.push(neighbor);
+                }
+            }
+        }
+
+        region
+    }
+
     /// Advance time in the space.
     ///
     /// * `tick` is how much time is to pass in the simulation.
@@ -685,11 +1032,82 @@ impl Space {
         self.spawn = spawn;
     }
 
+    /// Returns a source of [`SpaceEdit`] records describing each block replaced by
+    /// [`Space::set()`], [`Space::fill()`], and operations built on them, such as for
+    /// keeping an audit log of edits.
+    ///
+    /// Unlike [`SpaceChange`] (from [`Space::listen()`](Listen::listen)), which is intended
+    /// for invalidating caches and may describe changes coarsely, these records always
+    /// describe individual cubes and the blocks involved. They are only constructed while
+    /// there are listeners, so there is no cost when this is not used.
+    ///
+    /// ```
+    /// use all_is_cubes::block::{Block, AIR};
+    /// use all_is_cubes::listen::{Listen as _, Sink};
+    /// use all_is_cubes::math::Rgba;
+    /// use all_is_cubes::space::{Space, SpaceEditSource};
+    ///
+    /// let mut space = Space::empty_positive(1, 1, 1);
+    /// let sink = Sink::new();
+    /// space.edit_events().listen(sink.listener());
+    ///
+    /// let block = Block::builder().display_name("Red").color(Rgba::new(1.0, 0.0, 0.0, 1.0)).build();
+    /// space.set([0, 0, 0], &block).unwrap();
+    ///
+    /// let edit = sink.drain().pop().unwrap();
+    /// assert_eq!(edit.cube, [0, 0, 0].into());
+    /// assert_eq!(edit.old_block_name, AIR.evaluate().unwrap().attributes.display_name);
+    /// assert_eq!(edit.new_block_name, "Red");
+    /// assert_eq!(edit.source, SpaceEditSource::Set);
+    /// ```
+    pub fn edit_events(&self) -> impl Listen<Msg = SpaceEdit> + '_ {
+        &self.edit_notifier
+    }
+
     /// Returns the [`BehaviorSet`] of behaviors attached to this space.
     pub fn behaviors(&self) -> &BehaviorSet<Space> {
         &self.behaviors
     }
 
+    /// Replaces the entire contents of this space with those of `other`, while keeping
+    /// this space's listeners, which are notified with [`SpaceChange::EveryBlock`].
+    ///
+    /// Used by [`Universe::apply_diff()`](crate::universe::Universe::apply_diff) and
+    /// [`Universe::restore()`](crate::universe::Universe::restore).
+    pub(crate) fn replace_with(&mut self, other: Space) {
+        let old = std::mem::replace(self, other);
+        self.notifier = old.notifier;
+        self.edit_notifier = old.edit_notifier;
+        self.notifier.notify(SpaceChange::EveryBlock);
+    }
+
+    /// Returns a copy of this space's contents, lighting, and behaviors, without its
+    /// listeners.
+    ///
+    /// Used by [`Universe::snapshot()`](crate::universe::Universe::snapshot).
+    pub(crate) fn clone_for_snapshot(&self) -> Space {
+        let mut copy = Space::builder(self.bounds)
+            .physics(self.physics.clone())
+            .palette_and_contents(
+                self.palette
+                    .entries()
+                    .iter()
+                    .map(|data| data.block().clone()),
+                GridArray::from_elements(self.bounds, self.contents.clone())
+                    .expect("space contents should match its bounds"),
+                None,
+            )
+            .expect("space palette should be valid")
+            .behaviors(self.behaviors.clone())
+            .spawn(self.spawn.clone())
+            .build();
+        copy.lighting = self.lighting.clone();
+        copy.light_update_queue = self.light_update_queue.clone();
+        copy.light_cost_scale = self.light_cost_scale;
+        copy.cubes_wanting_ticks = self.cubes_wanting_ticks.clone();
+        copy
+    }
+
     #[cfg(test)]
     #[track_caller]
     pub(crate) fn consistency_check(&self) {
@@ -745,6 +1163,7 @@ impl VisitRefs for Space {
             spawn,
             cubes_wanting_ticks: _,
             notifier: _,
+            edit_notifier: _,
         } = self;
         palette.visit_refs(visitor);
         behaviors.visit_refs(visitor);
@@ -959,6 +1378,55 @@ pub enum SpaceChange {
     EveryBlock,
 }
 
+/// Description of a block being replaced in a [`Space`], delivered to listeners of
+/// [`Space::edit_events()`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct SpaceEdit {
+    /// The cube whose block was replaced.
+    pub cube: Cube,
+    /// The [`display_name`](crate::block::BlockAttributes::display_name) of the block
+    /// that was previously there.
+    pub old_block_name: Cow<'static, str>,
+    /// The [`display_name`](crate::block::BlockAttributes::display_name) of the block
+    /// that is now there.
+    pub new_block_name: Cow<'static, str>,
+    /// Which kind of operation made the edit.
+    pub source: SpaceEditSource,
+}
+
+/// Which kind of operation produced a [`SpaceEdit`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum SpaceEditSource {
+    /// [`Space::set()`], including when used by transactions.
+    Set,
+    /// [`Space::fill()`] or other operations on regions, such as [`Space::fill_uniform()`].
+    Fill,
+}
+
+/// Statistics about the contents of a [`Space`], returned by [`Space::statistics`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct SpaceStats {
+    /// Number of cubes in the space's bounds.
+    pub cube_count: usize,
+
+    /// Number of cubes containing a block other than [`AIR`].
+    pub non_air_count: usize,
+
+    /// Number of cubes containing each block, indexed by [`BlockIndex`] in the same way as
+    /// [`Space::block_data()`].
+    pub block_counts: Vec<usize>,
+
+    /// Number of different blocks (including [`AIR`]) present in at least one cube.
+    pub distinct_blocks: usize,
+
+    /// The smallest box containing every cube whose block is not [`AIR`], or [`None`] if
+    /// there are no such cubes.
+    pub occupied_bounds: Option<GridAab>,
+}
+
 /// Performance data returned by [`Space::step`]. The exact contents of this structure
 /// are unstable; use only `Debug` formatting to examine its contents unless you have
 /// a specific need for one of the values.
@@ -1,8 +1,6 @@
 mod data;
-pub use data::PackedLight;
-pub(crate) use data::{
-    LightStatus, LightUpdateQueue, LightUpdateRequest, PackedLightScalar, Priority,
-};
+pub use data::{LightStatus, PackedLight};
+pub(crate) use data::{LightUpdateQueue, LightUpdateRequest, PackedLightScalar, Priority};
 
 mod debug;
 #[doc(hidden)] // pub only for visualization by all-is-cubes-gpu
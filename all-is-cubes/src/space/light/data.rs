@@ -19,7 +19,8 @@ pub(crate) type PackedLightScalar = u8;
 /// it into an "RGBA" color value. They should not be considered a stable API element.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u8)]
-pub(crate) enum LightStatus {
+#[non_exhaustive]
+pub enum LightStatus {
     /// The cube's light value has never been computed.
     #[allow(unused)] // currently only used on feature=save
     Uninitialized = 0,
@@ -64,7 +65,10 @@ impl PackedLight {
         },
     };
 
-    pub(crate) fn some(value: Rgb) -> Self {
+    /// Constructs a visible light value from `value`, with reduced precision and range.
+    ///
+    /// [`PackedLight::to_rgb()`] will return approximately but not exactly `value`.
+    pub fn from_rgb(value: Rgb) -> Self {
         PackedLight {
             value: Vector3::new(
                 Self::scalar_in(value.red()),
@@ -92,11 +96,35 @@ impl PackedLight {
         )
     }
 
-    // TODO: Expose LightStatus once we are more confident in its API stability
-    pub(crate) fn status(&self) -> LightStatus {
+    /// Returns the light level. This is the same as [`PackedLight::value()`], and is the
+    /// inverse (within quantization error) of [`PackedLight::from_rgb()`].
+    #[inline]
+    pub fn to_rgb(self) -> Rgb {
+        self.value()
+    }
+
+    /// Returns the reason, if any, why this light value is zero or should not be used.
+    pub fn status(&self) -> LightStatus {
         self.status
     }
 
+    /// Linearly interpolates between `self` and `other`, with `t = 0.0` returning `self`
+    /// and `t = 1.0` returning `other`.
+    ///
+    /// The light levels are interpolated as linear [`Rgb`] values, not in the packed
+    /// representation. If both inputs have the same non-[`Visible`](LightStatus::Visible)
+    /// status, that status is kept; otherwise the result is visible.
+    #[must_use]
+    pub fn lerp(self, other: PackedLight, t: f32) -> PackedLight {
+        if t <= 0.0 || (self.status == other.status && self.status != LightStatus::Visible) {
+            self
+        } else if t >= 1.0 {
+            other
+        } else {
+            PackedLight::from_rgb(self.value() * (1.0 - t) + other.value() * t)
+        }
+    }
+
     /// Returns true if the light value is meaningful, or false if it is
     /// inside an opaque block or in empty unlit air (in which case [`Self::value`]
     /// always returns zero).
@@ -191,7 +219,7 @@ impl fmt::Debug for PackedLight {
 impl From<Rgb> for PackedLight {
     #[inline]
     fn from(value: Rgb) -> Self {
-        PackedLight::some(value)
+        PackedLight::from_rgb(value)
     }
 }
 
@@ -304,6 +332,7 @@ impl fmt::Debug for Priority {
 
 /// A priority queue for [`LightUpdateRequest`]s which contains cubes
 /// at most once, even when added with different priorities.
+#[derive(Clone)]
 pub(crate) struct LightUpdateQueue {
     /// Sorted storage of queue elements.
     /// This is a BTreeSet rather than a BinaryHeap so that items can be removed.
@@ -453,6 +482,52 @@ mod tests {
         );
     }
 
+    #[test]
+    fn packed_light_rgb_roundtrip() {
+        for rgb in [
+            Rgb::ONE,
+            Rgb::new(0.5, 0.25, 2.0),
+            Rgb::new(0.01, 10.0, 100.0),
+            Rgb::new(0.0, 1.0, 0.0),
+        ] {
+            let out = PackedLight::from_rgb(rgb).to_rgb();
+            for (component_in, component_out) in [
+                (rgb.red(), out.red()),
+                (rgb.green(), out.green()),
+                (rgb.blue(), out.blue()),
+            ] {
+                // Each step of the packed representation is a factor of 2^(1/16).
+                let (component_in, component_out) =
+                    (component_in.into_inner(), component_out.into_inner());
+                assert!(
+                    (component_in - component_out).abs() <= component_in * 0.05,
+                    "{rgb:?} became {out:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn packed_light_lerp() {
+        // Powers of 2 are exactly representable.
+        let a = PackedLight::from_rgb(Rgb::new(2.0, 0.0, 1.0));
+        let b = PackedLight::from_rgb(Rgb::new(2.0, 4.0, 1.0));
+        assert_eq!(a.lerp(b, 0.0), a);
+        assert_eq!(a.lerp(b, 1.0), b);
+        assert_eq!(
+            a.lerp(b, 0.5),
+            PackedLight::from_rgb(Rgb::new(2.0, 2.0, 1.0))
+        );
+        assert_eq!(
+            PackedLight::OPAQUE.lerp(PackedLight::OPAQUE, 0.5),
+            PackedLight::OPAQUE
+        );
+        assert_eq!(
+            PackedLight::OPAQUE.lerp(b, 0.5).status(),
+            LightStatus::Visible
+        );
+    }
+
     #[test]
     fn packed_light_is_packed() {
         // Technically this is not guaranteed by the compiler, but if it's false something probably went wrong.
@@ -92,7 +92,9 @@ impl Space {
 
             let t1 = I::now();
             let cost_scale = t1.saturating_duration_since(t0).as_secs_f32() / cost as f32;
-            if cost_scale.is_finite() {
+            // A zero duration means that there is no clock (`NoTime`) or it is too coarse
+            // to measure this; either way, it tells us nothing about the cost.
+            if cost_scale.is_finite() && cost_scale > 0.0 {
                 // TODO(time-budget): don't let this grow or shrink too fast due to outliers
                 self.light_cost_scale = 0.125 * cost_scale + 0.875 * self.light_cost_scale;
             }
@@ -498,7 +500,7 @@ impl LightBuffer {
         // We just need to avoid dividing by zero.
         let scale = NotNan::new(1.0 / self.total_ray_weight.max(1.0)).unwrap();
         let new_light_value: PackedLight = if self.total_rays > 0 {
-            PackedLight::some(self.incoming_light * scale)
+            PackedLight::from_rgb(self.incoming_light * scale)
         } else if origin_is_opaque {
             PackedLight::OPAQUE
         } else {
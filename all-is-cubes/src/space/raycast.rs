@@ -0,0 +1,190 @@
+//! [`Space::raycast()`] and [`RaycastHit`].
+
+use cgmath::{Point3, Vector3};
+
+use crate::block::Block;
+use crate::math::{Cube, Face7, FreeCoordinate};
+use crate::raycast::{RaycastStep, Raycaster};
+use crate::space::Space;
+
+impl Space {
+    /// Returns an iterator over the cubes of this space which the ray starting at
+    /// `origin` and extending in `direction` passes through, in order along the ray,
+    /// together with the blocks in them.
+    ///
+    /// This uses the same traversal as [`Raycaster`], restricted to
+    /// [`self.bounds()`](Self::bounds), so it is finite. The ray may start inside or
+    /// outside the space; if it starts inside, the first hit is the cube containing
+    /// `origin`, with a face of [`Face7::Within`].
+    ///
+    /// Distances are measured in multiples of `direction`, so if `direction` is a unit
+    /// vector, they are distances in cubes.
+    ///
+    /// ```
+    /// use all_is_cubes::block::{Block, AIR};
+    /// use all_is_cubes::math::{Cube, Face7, Rgba};
+    /// use all_is_cubes::space::Space;
+    ///
+    /// let block = Block::from(Rgba::WHITE);
+    /// let mut space = Space::empty_positive(10, 1, 1);
+    /// space.set([7, 0, 0], &block).unwrap();
+    ///
+    /// // Find the first non-air block along a ray.
+    /// let hit = space
+    ///     .raycast([0.5, 0.5, 0.5], [1.0, 0.0, 0.0])
+    ///     .find(|hit| *hit.block() != AIR)
+    ///     .unwrap();
+    /// assert_eq!(hit.cube(), Cube::new(7, 0, 0));
+    /// assert_eq!(hit.face(), Face7::NX);
+    /// assert_eq!(hit.t_distance(), 6.5);
+    /// ```
+    pub fn raycast(
+        &self,
+        origin: impl Into<Point3<FreeCoordinate>>,
+        direction: impl Into<Vector3<FreeCoordinate>>,
+    ) -> SpaceRaycaster<'_> {
+        SpaceRaycaster {
+            space: self,
+            raycaster: Raycaster::new(origin, direction).within(self.bounds()),
+        }
+    }
+}
+
+/// Iterator returned by [`Space::raycast()`].
+#[derive(Clone, Debug)]
+pub struct SpaceRaycaster<'a> {
+    space: &'a Space,
+    raycaster: Raycaster,
+}
+
+impl<'a> Iterator for SpaceRaycaster<'a> {
+    type Item = RaycastHit<'a>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let step = self.raycaster.next()?;
+        Some(RaycastHit {
+            step,
+            block: &self.space[step.cube_ahead()],
+        })
+    }
+}
+
+impl std::iter::FusedIterator for SpaceRaycaster<'_> {}
+
+/// A cube of a [`Space`] which a ray passed through, as produced by [`Space::raycast()`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RaycastHit<'a> {
+    step: RaycastStep,
+    block: &'a Block,
+}
+
+impl<'a> RaycastHit<'a> {
+    /// Returns the cube the ray entered.
+    #[inline]
+    pub fn cube(&self) -> Cube {
+        self.step.cube_ahead()
+    }
+
+    /// Returns the face of [`Self::cube()`] through which the ray entered it, or
+    /// [`Face7::Within`] if the ray started inside that cube.
+    #[inline]
+    pub fn face(&self) -> Face7 {
+        self.step.face()
+    }
+
+    /// Returns the distance along the ray at which it entered the cube, as measured in
+    /// multiples of the ray's direction vector.
+    #[inline]
+    pub fn t_distance(&self) -> FreeCoordinate {
+        self.step.t_distance()
+    }
+
+    /// Returns the block in [`Self::cube()`].
+    #[inline]
+    pub fn block(&self) -> &'a Block {
+        self.block
+    }
+
+    /// Returns the underlying [`RaycastStep`], which offers further information such as
+    /// the exact intersection point.
+    #[inline]
+    pub fn step(&self) -> RaycastStep {
+        self.step
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content::make_some_blocks;
+    use crate::math::GridAab;
+
+    #[test]
+    fn diagonal_ray_cube_sequence() {
+        let [block] = make_some_blocks();
+        let mut space = Space::empty_positive(3, 3, 3);
+        space.set([1, 1, 1], &block).unwrap();
+
+        let hits: Vec<(Cube, Face7)> = space
+            .raycast([0.5, 0.25, 0.5], [1.0, 1.0, 0.0])
+            .map(|hit| (hit.cube(), hit.face()))
+            .collect();
+        assert_eq!(
+            hits,
+            vec![
+                (Cube::new(0, 0, 0), Face7::Within),
+                (Cube::new(1, 0, 0), Face7::NX),
+                (Cube::new(1, 1, 0), Face7::NY),
+                (Cube::new(2, 1, 0), Face7::NX),
+                (Cube::new(2, 2, 0), Face7::NY),
+            ]
+        );
+
+        // The block is reported along with the cube.
+        let hits: Vec<(Cube, &Block)> = space
+            .raycast([-0.5, -0.25, 1.5], [1.0, 1.0, 0.0])
+            .map(|hit| (hit.cube(), hit.block()))
+            .filter(|&(_, b)| *b == block)
+            .collect();
+        assert_eq!(hits, vec![(Cube::new(1, 1, 1), &block)]);
+    }
+
+    #[test]
+    fn axis_aligned_ray_entry_faces() {
+        let space = Space::empty(GridAab::from_lower_size([-2, 0, 0], [4, 1, 1]));
+
+        // Starting outside the space, the first hit is on its boundary.
+        let hits: Vec<(Cube, Face7, FreeCoordinate)> = space
+            .raycast([10.5, 0.5, 0.5], [-1.0, 0.0, 0.0])
+            .map(|hit| (hit.cube(), hit.face(), hit.t_distance()))
+            .collect();
+        assert_eq!(
+            hits,
+            vec![
+                (Cube::new(1, 0, 0), Face7::PX, 8.5),
+                (Cube::new(0, 0, 0), Face7::PX, 9.5),
+                (Cube::new(-1, 0, 0), Face7::PX, 10.5),
+                (Cube::new(-2, 0, 0), Face7::PX, 11.5),
+            ]
+        );
+
+        // Starting inside the space.
+        let hits: Vec<(Cube, Face7)> = space
+            .raycast([-1.5, 0.5, 0.5], [2.0, 0.0, 0.0])
+            .map(|hit| (hit.cube(), hit.face()))
+            .collect();
+        assert_eq!(
+            hits,
+            vec![
+                (Cube::new(-2, 0, 0), Face7::Within),
+                (Cube::new(-1, 0, 0), Face7::NX),
+                (Cube::new(0, 0, 0), Face7::NX),
+                (Cube::new(1, 0, 0), Face7::NX),
+            ]
+        );
+
+        // A ray parallel to the space but outside it hits nothing.
+        assert_eq!(space.raycast([0.5, 5.5, 0.5], [1.0, 0.0, 0.0]).count(), 0);
+    }
+}
@@ -0,0 +1,305 @@
+//! [`Space::resampled()`] and [`ResampleMode`].
+
+use crate::block::{Block, Resolution, AIR};
+use crate::math::{Cube, GridAab, GridCoordinate, GridOverflowError, GridPoint, Rgb};
+use crate::space::{BlockIndex, Space};
+
+/// How [`Space::resampled()`] should scale a space.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum ResampleMode {
+    /// Shrink the space, replacing each `factor`³ group of cubes with a single cube
+    /// containing whichever block occurs most often in the group.
+    /// Ties are broken in favor of the block which occurs first in
+    /// [`GridAab::interior_iter()`] order.
+    DownscaleMajority,
+
+    /// Shrink the space, replacing each `factor`³ group of cubes with a single cube
+    /// containing an atom block whose color is the average of the groups' blocks' colors,
+    /// weighted by their opacity. Groups containing only invisible blocks become [`AIR`].
+    DownscaleAverage,
+
+    /// Enlarge the space, copying each block into a `factor`³ group of cubes.
+    Upscale,
+}
+
+impl Space {
+    /// Returns a new space which is this space scaled up or down by `factor`, as
+    /// specified by `mode`. The new space has the same [`physics`](Self::physics) as this
+    /// space; its other properties, such as the spawn, are the defaults.
+    ///
+    /// When downscaling, any part of this space's bounds which is not a complete group of
+    /// `factor`³ cubes aligned to multiples of `factor` is dropped, and a warning is
+    /// logged.
+    ///
+    /// Returns [`Err`] if upscaling would produce bounds which overflow
+    /// [`GridCoordinate`].
+    ///
+    /// ```
+    /// use all_is_cubes::block::{Block, Resolution};
+    /// use all_is_cubes::math::{GridAab, Rgba};
+    /// use all_is_cubes::space::{ResampleMode, Space};
+    ///
+    /// let block = Block::from(Rgba::WHITE);
+    /// let mut space = Space::empty_positive(1, 1, 1);
+    /// space.set([0, 0, 0], &block).unwrap();
+    ///
+    /// let big = space.resampled(Resolution::R2, ResampleMode::Upscale)?;
+    /// assert_eq!(big.bounds(), GridAab::from_lower_size([0, 0, 0], [2, 2, 2]));
+    /// assert_eq!(big[[1, 1, 1]], block);
+    /// # Ok::<(), all_is_cubes::math::GridOverflowError>(())
+    /// ```
+    pub fn resampled(
+        &self,
+        factor: Resolution,
+        mode: ResampleMode,
+    ) -> Result<Space, GridOverflowError> {
+        let factor = GridCoordinate::from(factor);
+        match mode {
+            ResampleMode::Upscale => {
+                let bounds = self.bounds.checked_multiply(factor)?;
+                let mut space = self.resampled_empty(bounds);
+                space
+                    .fill(bounds, |cube| Some(&self[scale_down(cube, factor)]))
+                    .expect("resampled space should contain its own bounds");
+                Ok(space)
+            }
+            ResampleMode::DownscaleMajority | ResampleMode::DownscaleAverage => {
+                let bounds = divide_inward(self.bounds, factor);
+                if bounds.multiply(factor) != self.bounds {
+                    log::warn!(
+                        "Space::resampled(): bounds {:?} are not divisible by {factor}; \
+                        clipping to {:?}",
+                        self.bounds,
+                        bounds.multiply(factor),
+                    );
+                }
+                let mut space = self.resampled_empty(bounds);
+                space
+                    .fill(bounds, |cube| {
+                        let group = GridAab::from_lower_size(
+                            cube.lower_bounds() * factor,
+                            [factor, factor, factor],
+                        );
+                        Some(if mode == ResampleMode::DownscaleMajority {
+                            self.majority_block(group)
+                        } else {
+                            self.average_block(group)
+                        })
+                    })
+                    .expect("resampled space should contain its own bounds");
+                Ok(space)
+            }
+        }
+    }
+
+    fn resampled_empty(&self, bounds: GridAab) -> Space {
+        Space::builder(bounds)
+            .physics(self.physics().clone())
+            .build()
+    }
+
+    /// Returns the most common block in `group`, which must be within this space's bounds.
+    fn majority_block(&self, group: GridAab) -> Block {
+        // Groups are small, so a linear search is fine.
+        let mut counts: Vec<(BlockIndex, usize)> = Vec::new();
+        for cube in group.interior_iter() {
+            let index = self
+                .get_block_index(cube)
+                .expect("group should be in bounds");
+            match counts.iter_mut().find(|(i, _)| *i == index) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((index, 1)),
+            }
+        }
+        // max_by_key() picks the last of equal elements, so search in reverse.
+        let (index, _) = counts
+            .into_iter()
+            .rev()
+            .max_by_key(|&(_, count)| count)
+            .expect("group should not be empty");
+        self.block_data()[usize::from(index)].block().clone()
+    }
+
+    /// Returns an atom block with the opacity-weighted average color of the blocks in
+    /// `group`.
+    fn average_block(&self, group: GridAab) -> Block {
+        let mut color_sum = Rgb::ZERO;
+        let mut alpha_sum = 0.0;
+        for cube in group.interior_iter() {
+            let color = self.get_evaluated(cube).color;
+            let alpha = color.alpha().into_inner();
+            color_sum += color.to_rgb() * alpha;
+            alpha_sum += alpha;
+        }
+        if alpha_sum <= 0.0 {
+            return AIR;
+        }
+        let volume = group.volume() as f32;
+        let alpha = (alpha_sum / volume)
+            .try_into()
+            .expect("alpha should not be NaN");
+        Block::from((color_sum * alpha_sum.recip()).with_alpha(alpha))
+    }
+}
+
+/// Returns the cube containing `cube` when the grid is scaled down by `factor`.
+fn scale_down(cube: Cube, factor: GridCoordinate) -> Cube {
+    Cube::from(cube.lower_bounds().map(|c| c.div_euclid(factor)))
+}
+
+/// Like [`GridAab::divide()`], but rounds inward, so that the result multiplied by
+/// `divisor` is the largest box within `bounds` whose corners are multiples of `divisor`.
+///
+/// The result is always within `bounds`, so multiplying it by `divisor` cannot overflow.
+fn divide_inward(bounds: GridAab, divisor: GridCoordinate) -> GridAab {
+    // Round up without computing `c + divisor - 1`, which could overflow.
+    let lower: GridPoint = bounds
+        .lower_bounds()
+        .map(|c| c.div_euclid(divisor) + GridCoordinate::from(c.rem_euclid(divisor) != 0));
+    let upper: GridPoint = bounds.upper_bounds().map(|c| c.div_euclid(divisor));
+    // If the bounds are smaller than one group, the result is empty.
+    GridAab::from_lower_upper(lower, lower.zip(upper, GridCoordinate::max))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content::make_some_blocks;
+    use crate::math::Rgba;
+    use pretty_assertions::assert_eq;
+
+    /// Makes a space whose blocks alternate between black and white.
+    fn checkerboard(bounds: GridAab) -> Space {
+        let black = Block::from(Rgba::BLACK);
+        let white = Block::from(Rgba::WHITE);
+        let mut space = Space::empty(bounds);
+        space
+            .fill(bounds, |cube| {
+                let p = cube.lower_bounds();
+                Some(if (p.x + p.y + p.z).rem_euclid(2) == 0 {
+                    &black
+                } else {
+                    &white
+                })
+            })
+            .unwrap();
+        space
+    }
+
+    #[test]
+    fn downscale_average_checkerboard() {
+        let space = checkerboard(GridAab::from_lower_size([0, 0, 0], [4, 4, 4]));
+        let small = space
+            .resampled(Resolution::R2, ResampleMode::DownscaleAverage)
+            .unwrap();
+        assert_eq!(
+            small.bounds(),
+            GridAab::from_lower_size([0, 0, 0], [2, 2, 2])
+        );
+        let gray = Block::from(Rgba::new(0.5, 0.5, 0.5, 1.0));
+        for cube in small.bounds().interior_iter() {
+            assert_eq!(small[cube], gray, "{cube:?}");
+        }
+    }
+
+    #[test]
+    fn downscale_average_weights_by_alpha() {
+        // One opaque red block and seven air blocks.
+        let mut space = Space::empty_positive(2, 2, 2);
+        space
+            .set([0, 0, 0], Block::from(Rgba::new(1.0, 0.0, 0.0, 1.0)))
+            .unwrap();
+        let small = space
+            .resampled(Resolution::R2, ResampleMode::DownscaleAverage)
+            .unwrap();
+        assert_eq!(
+            small[[0, 0, 0]],
+            Block::from(Rgba::new(1.0, 0.0, 0.0, 1.0 / 8.0))
+        );
+
+        // All air remains air.
+        let empty = Space::empty_positive(2, 2, 2);
+        let small = empty
+            .resampled(Resolution::R2, ResampleMode::DownscaleAverage)
+            .unwrap();
+        assert_eq!(small[[0, 0, 0]], AIR);
+    }
+
+    #[test]
+    fn downscale_majority() {
+        let [block_1, block_2] = make_some_blocks();
+        let mut space = Space::empty_positive(2, 2, 2);
+        space.fill_uniform(space.bounds(), &block_1).unwrap();
+        space.set([1, 1, 1], &block_2).unwrap();
+        let small = space
+            .resampled(Resolution::R2, ResampleMode::DownscaleMajority)
+            .unwrap();
+        assert_eq!(small[[0, 0, 0]], block_1);
+    }
+
+    #[test]
+    fn downscale_clips_non_divisible_bounds() {
+        let space = checkerboard(GridAab::from_lower_size([-1, 0, 0], [6, 3, 2]));
+        let small = space
+            .resampled(Resolution::R2, ResampleMode::DownscaleMajority)
+            .unwrap();
+        assert_eq!(
+            small.bounds(),
+            GridAab::from_lower_upper([0, 0, 0], [2, 1, 1])
+        );
+
+        // Smaller than one group in some axis.
+        let space = Space::empty_positive(1, 4, 4);
+        let small = space
+            .resampled(Resolution::R2, ResampleMode::DownscaleAverage)
+            .unwrap();
+        assert_eq!(small.bounds().volume(), 0);
+    }
+
+    #[test]
+    fn upscale_replicates() {
+        let [block] = make_some_blocks();
+        let mut space = Space::empty(GridAab::from_lower_size([-1, 0, 0], [1, 1, 1]));
+        space.set([-1, 0, 0], &block).unwrap();
+        let big = space
+            .resampled(Resolution::R2, ResampleMode::Upscale)
+            .unwrap();
+        assert_eq!(
+            big.bounds(),
+            GridAab::from_lower_size([-2, 0, 0], [2, 2, 2])
+        );
+        for cube in big.bounds().interior_iter() {
+            assert_eq!(big[cube], block, "{cube:?}");
+        }
+    }
+
+    #[test]
+    fn upscale_overflow_is_error() {
+        let space = Space::empty(GridAab::from_lower_size(
+            [GridCoordinate::MAX / 2, 0, 0],
+            [1, 1, 1],
+        ));
+        assert!(space
+            .resampled(Resolution::R4, ResampleMode::Upscale)
+            .is_err());
+    }
+
+    #[test]
+    fn downscale_at_extremes_does_not_overflow() {
+        let space = Space::empty(GridAab::from_lower_upper(
+            [GridCoordinate::MAX - 5, GridCoordinate::MIN, 0],
+            [GridCoordinate::MAX - 1, GridCoordinate::MIN + 5, 2],
+        ));
+        let small = space
+            .resampled(Resolution::R2, ResampleMode::DownscaleMajority)
+            .unwrap();
+        assert_eq!(
+            small.bounds(),
+            GridAab::from_lower_upper(
+                [GridCoordinate::MAX / 2 - 2, GridCoordinate::MIN / 2, 0],
+                [GridCoordinate::MAX / 2, GridCoordinate::MIN / 2 + 2, 1],
+            )
+        );
+    }
+}
@@ -11,13 +11,15 @@ use crate::block::{
 use crate::content::make_some_blocks;
 use crate::drawing::VoxelBrush;
 use crate::listen::{Listen as _, Sink};
-use crate::math::{Cube, GridCoordinate, GridPoint, Rgba};
+use crate::math::{Cube, GridCoordinate, GridPoint, GridRotation, Rgba};
 use crate::space::{
-    GridAab, LightPhysics, PackedLight, SetCubeError, Space, SpaceChange, SpacePhysics,
+    GridAab, LightPhysics, PackedLight, SetCubeError, Space, SpaceChange, SpaceEdit,
+    SpaceEditSource, SpacePhysics,
 };
 use crate::time::{self, Tick};
 use crate::transaction;
 use crate::universe::{Name, RefError, URef, Universe, UniverseTransaction};
+use crate::util::yield_progress_for_recording;
 
 // TODO: test consistency between the index and get_* methods
 // TODO: test fill() equivalence and error handling
@@ -54,6 +56,56 @@ fn set_success() {
     space.consistency_check(); // bonus testing
 }
 
+#[test]
+fn set_emits_edit_event() {
+    let [first, second] = make_some_blocks();
+    let name = |block: &Block| block.evaluate().unwrap().attributes.display_name;
+    let mut space = Space::empty_positive(2, 1, 1);
+    let sink = Sink::new();
+    space.edit_events().listen(sink.listener());
+
+    space.set([0, 0, 0], &first).unwrap();
+    space.set([0, 0, 0], &first).unwrap(); // no change, so no event
+    space.set([0, 0, 0], &second).unwrap();
+    assert_eq!(
+        sink.drain(),
+        vec![
+            SpaceEdit {
+                cube: Cube::new(0, 0, 0),
+                old_block_name: name(&AIR),
+                new_block_name: name(&first),
+                source: SpaceEditSource::Set,
+            },
+            SpaceEdit {
+                cube: Cube::new(0, 0, 0),
+                old_block_name: name(&first),
+                new_block_name: name(&second),
+                source: SpaceEditSource::Set,
+            },
+        ]
+    );
+
+    // Filling the whole space still reports each cube.
+    space.fill_uniform(space.bounds(), &first).unwrap();
+    assert_eq!(
+        sink.drain(),
+        vec![
+            SpaceEdit {
+                cube: Cube::new(0, 0, 0),
+                old_block_name: name(&second),
+                new_block_name: name(&first),
+                source: SpaceEditSource::Fill,
+            },
+            SpaceEdit {
+                cube: Cube::new(1, 0, 0),
+                old_block_name: name(&AIR),
+                new_block_name: name(&first),
+                source: SpaceEditSource::Fill,
+            },
+        ]
+    );
+}
+
 /// Test set() with a block that fails evaluation.
 /// This should succeed but leave a placeholder.
 ///
@@ -268,6 +320,46 @@ fn extract_out_of_bounds() {
     space.extract(extract_bounds, |_| ());
 }
 
+#[test]
+fn connected_region_separate_blobs() {
+    let [block1, block2] = make_some_blocks();
+    let mut space = Space::empty_positive(10, 3, 3);
+    // Two blobs of different blocks that touch each other, and a third blob separated
+    // from them by air.
+    space
+        .fill_uniform(GridAab::from_lower_size([0, 0, 0], [3, 2, 2]), &block1)
+        .unwrap();
+    space
+        .fill_uniform(GridAab::from_lower_size([3, 0, 0], [1, 1, 1]), &block2)
+        .unwrap();
+    space
+        .fill_uniform(GridAab::from_lower_size([6, 0, 0], [4, 3, 1]), &block1)
+        .unwrap();
+    let is_solid = |block: &Block| *block != AIR;
+
+    let region = space.connected_region([0, 0, 0], is_solid);
+    assert_eq!(region.bounds(), space.bounds());
+    let reached: Vec<Cube> = region
+        .iter()
+        .filter(|&(_, &r)| r)
+        .map(|(cube, _)| cube)
+        .collect();
+    assert_eq!(reached.len(), 3 * 2 * 2 + 1);
+    assert!(reached.contains(&Cube::new(3, 0, 0)));
+    assert_eq!(region.get([6, 0, 0]), Some(&false));
+
+    // Starting from the other blob reaches only it.
+    let region = space.connected_region([9, 2, 0], is_solid);
+    assert_eq!(region.iter().filter(|&(_, &r)| r).count(), 4 * 3);
+    assert_eq!(region.get([0, 0, 0]), Some(&false));
+
+    // Starting from a cube not matching the predicate, or outside the space, finds nothing.
+    for start in [[5, 0, 0], [100, 0, 0]] {
+        let region = space.connected_region(start, is_solid);
+        assert!(region.iter().all(|(_, &r)| !r), "{start:?}");
+    }
+}
+
 #[test]
 fn fill_out_of_bounds() {
     let mut space = Space::empty_positive(2, 1, 1);
@@ -295,6 +387,89 @@ fn fill_entire_space() {
     }
 }
 
+/// [`Space::fill_region_async`] should report progress along the way, and produce the same
+/// result as [`Space::fill`], including calling the function in the same order.
+#[tokio::test]
+async fn fill_region_async_equivalent() {
+    let blocks = make_some_blocks::<3>();
+    let space_bounds = GridAab::from_lower_size([-5, 0, 0], [40, 20, 20]);
+    let region = GridAab::from_lower_size([-4, 1, 1], [38, 18, 18]);
+
+    let mut sync_order = Vec::new();
+    let mut sync_space = Space::empty(space_bounds);
+    sync_space
+        .fill(region, |cube| {
+            sync_order.push(cube);
+            let i = (cube.x + cube.y * 2 + cube.z * 3).rem_euclid(4) as usize;
+            blocks.get(i)
+        })
+        .unwrap();
+
+    let (progress, reports) = yield_progress_for_recording();
+    let mut async_order = Vec::new();
+    let mut async_space = Space::empty(space_bounds);
+    async_space
+        .fill_region_async(
+            region,
+            |cube| {
+                async_order.push(cube);
+                let i = (cube.x + cube.y * 2 + cube.z * 3).rem_euclid(4) as usize;
+                blocks.get(i)
+            },
+            progress,
+        )
+        .await
+        .unwrap();
+
+    async_space.consistency_check();
+    assert_eq!(async_order, sync_order);
+    for cube in space_bounds.interior_iter() {
+        assert_eq!(async_space[cube], sync_space[cube], "{cube:?}");
+    }
+    let reports = reports.events();
+    assert!(reports.len() > 2, "{reports:?}");
+    assert_eq!(reports.last().map(|&(fraction, _)| fraction), Some(1.0));
+}
+
+#[test]
+fn statistics() {
+    let [block0, block1, block2] = make_some_blocks();
+    let mut space = Space::empty(GridAab::from_lower_size([-2, 0, 10], [6, 5, 4]));
+    space.set([-1, 2, 11], &block0).unwrap();
+    space.set([2, 1, 11], &block0).unwrap();
+    space.set([0, 3, 12], &block1).unwrap();
+    // Set and remove a block so that its palette entry is left unused.
+    space.set([0, 0, 10], &block2).unwrap();
+    space.set([0, 0, 10], AIR).unwrap();
+
+    let stats = space.statistics();
+    assert_eq!(stats.cube_count, 6 * 5 * 4);
+    assert_eq!(stats.non_air_count, 3);
+    assert_eq!(stats.distinct_blocks, 3);
+    assert_eq!(
+        stats.occupied_bounds,
+        Some(GridAab::from_lower_upper([-1, 1, 11], [3, 4, 13]))
+    );
+    assert_eq!(stats.block_counts.len(), space.block_data().len());
+    let count_at = |cube: [GridCoordinate; 3]| {
+        stats.block_counts[usize::from(space.get_block_index(cube).unwrap())]
+    };
+    assert_eq!(count_at([0, 0, 10]), 6 * 5 * 4 - 3);
+    assert_eq!(count_at([-1, 2, 11]), 2);
+    assert_eq!(count_at([0, 3, 12]), 1);
+    assert_eq!(stats.block_counts.iter().sum::<usize>(), stats.cube_count);
+}
+
+#[test]
+fn statistics_all_air() {
+    let stats = Space::empty_positive(3, 2, 1).statistics();
+    assert_eq!(stats.cube_count, 6);
+    assert_eq!(stats.non_air_count, 0);
+    assert_eq!(stats.block_counts, vec![6]);
+    assert_eq!(stats.distinct_blocks, 1);
+    assert_eq!(stats.occupied_bounds, None);
+}
+
 /// Test filling an entire space with one block using [`Space::fill_uniform`].
 #[test]
 fn fill_uniform_entire_space() {
@@ -314,6 +489,103 @@ fn fill_uniform_entire_space() {
     }
 }
 
+/// Test [`Space::copy_region`] with an L-shaped region and a rotation.
+#[test]
+fn copy_region_rotated() {
+    let mut universe = Universe::new();
+    // Use indirect blocks because atoms are unaffected by rotation.
+    let [a, b, c, d] = make_some_blocks().map(|block| {
+        Block::from_primitive(Primitive::Indirect(
+            universe.insert_anonymous(BlockDef::new(block)),
+        ))
+    });
+    let mut source = Space::empty(GridAab::from_lower_size([10, 0, 10], [3, 1, 2]));
+    source.set([10, 0, 10], &a).unwrap();
+    source.set([11, 0, 10], &b).unwrap();
+    source.set([12, 0, 10], &c).unwrap();
+    source.set([10, 0, 11], &d).unwrap();
+
+    let mut destination = Space::empty_positive(10, 10, 10);
+    destination
+        .copy_region(
+            &source,
+            source.bounds(),
+            GridPoint::new(5, 0, 5),
+            GridRotation::CLOCKWISE,
+        )
+        .unwrap();
+    destination.consistency_check();
+
+    // Clockwise rotation maps +X to +Z and +Z to −X.
+    let rot = |block: &Block| block.clone().rotate(GridRotation::CLOCKWISE);
+    let expected = [
+        ([6, 0, 5], rot(&a)),
+        ([6, 0, 6], rot(&b)),
+        ([6, 0, 7], rot(&c)),
+        ([5, 0, 5], rot(&d)),
+    ];
+    for cube in destination.bounds().interior_iter() {
+        let expected_block = expected
+            .iter()
+            .find(|(position, _)| Cube::from(*position) == cube)
+            .map_or(&AIR, |(_, block)| block);
+        assert_eq!(&destination[cube], expected_block, "{cube:?}");
+    }
+}
+
+#[test]
+fn copy_region_out_of_bounds() {
+    let [block] = make_some_blocks();
+    let mut source = Space::empty_positive(3, 1, 1);
+    source.set([0, 0, 0], &block).unwrap();
+    let mut destination = Space::empty_positive(2, 2, 2);
+
+    let result = destination.copy_region(
+        &source,
+        source.bounds(),
+        GridPoint::new(0, 0, 0),
+        GridRotation::IDENTITY,
+    );
+
+    assert_eq!(
+        result,
+        Err(SetCubeError::OutOfBounds {
+            modification: GridAab::from_lower_size([0, 0, 0], [3, 1, 1]),
+            space_bounds: destination.bounds(),
+        })
+    );
+    assert_eq!(destination[[0, 0, 0]], AIR);
+}
+
+/// If the destination region overflows the numeric range, the error reports the part of
+/// the destination region that does not.
+#[test]
+fn copy_region_overflow_reports_destination() {
+    let source = Space::empty_positive(3, 1, 1);
+    let mut destination = Space::empty(GridAab::from_lower_size(
+        [GridCoordinate::MAX - 2, 0, 0],
+        [2, 1, 1],
+    ));
+
+    let result = destination.copy_region(
+        &source,
+        source.bounds(),
+        GridPoint::new(GridCoordinate::MAX - 1, 0, 0),
+        GridRotation::IDENTITY,
+    );
+
+    assert_eq!(
+        result,
+        Err(SetCubeError::OutOfBounds {
+            modification: GridAab::from_lower_upper(
+                [GridCoordinate::MAX - 1, 0, 0],
+                [GridCoordinate::MAX, 1, 1]
+            ),
+            space_bounds: destination.bounds(),
+        })
+    );
+}
+
 /// There was a bug triggered when the last instance of a block was replaced with
 /// a block already in the space. This specifically runs a consistency check in that
 /// case.
@@ -498,3 +770,59 @@ fn block_tick_action() {
 
     assert_eq!(&space[[0, 0, 0]], &block2);
 }
+
+#[test]
+fn region_hash() {
+    let [block_1, block_2] = make_some_blocks();
+    let region = GridAab::from_lower_size([0, 0, 0], [2, 2, 2]);
+    let mut space = Space::empty_positive(4, 4, 4);
+    space.set([1, 1, 1], &block_1).unwrap();
+    let original = space.region_hash(region);
+
+    // Editing outside the region does not change the hash.
+    space.set([3, 3, 3], &block_2).unwrap();
+    assert_eq!(space.region_hash(region), original);
+
+    // Editing inside the region does.
+    space.set([0, 1, 0], &block_2).unwrap();
+    let edited = space.region_hash(region);
+    assert_ne!(edited, original);
+
+    // The hash depends on content, not palette numbering.
+    let mut other_space = Space::empty_positive(2, 2, 2);
+    other_space.set([0, 1, 0], &block_2).unwrap();
+    other_space.set([1, 1, 1], &block_1).unwrap();
+    assert_eq!(other_space.region_hash(region), edited);
+}
+
+/// [`Space::region_hash()`] covers all block attributes, and hashes tick actions by their
+/// evaluation rather than by the identity of the blocks' [`URef`]s.
+#[test]
+fn region_hash_attributes() {
+    let hash_of = |block: &Block| {
+        let mut space = Space::empty_positive(1, 1, 1);
+        space.set([0, 0, 0], block).unwrap();
+        space.region_hash(space.bounds())
+    };
+    let plain = Block::builder().color(Rgba::WHITE).build();
+    let unselectable = Block::builder()
+        .color(Rgba::WHITE)
+        .selectable(false)
+        .build();
+    assert_ne!(hash_of(&plain), hash_of(&unselectable));
+
+    let ticking_in = |universe: &mut Universe| {
+        let target = universe.insert_anonymous(BlockDef::new(Block::from(Rgba::BLACK)));
+        Block::builder()
+            .color(Rgba::WHITE)
+            .tick_action(Some(VoxelBrush::single(Block::from_primitive(
+                Primitive::Indirect(target),
+            ))))
+            .build()
+    };
+    let ticking_1 = ticking_in(&mut Universe::new());
+    let ticking_2 = ticking_in(&mut Universe::new());
+    assert_ne!(ticking_1, ticking_2);
+    assert_ne!(hash_of(&plain), hash_of(&ticking_1));
+    assert_eq!(hash_of(&ticking_1), hash_of(&ticking_2));
+}
@@ -13,6 +13,7 @@
 //! bring your own synchronization mechanisms to ensure that readers and writers do not
 //! run at the same time.
 
+use std::collections::{HashSet, VecDeque};
 use std::fmt;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
@@ -30,6 +31,11 @@ mod members;
 pub use members::AnyURef;
 pub(crate) use members::*;
 
+#[cfg(feature = "save")]
+mod diff;
+#[cfg(feature = "save")]
+pub use diff::*;
+
 mod universe_txn;
 pub use universe_txn::*;
 
@@ -38,6 +44,9 @@ pub use uref::*;
 
 mod owning_guard;
 
+mod snapshot;
+pub use snapshot::*;
+
 mod visit;
 pub use visit::*;
 
@@ -312,6 +321,47 @@ impl Universe {
         UniverseOps::insert(self, name, value)
     }
 
+    /// Changes the name of an existing member of this universe.
+    ///
+    /// All existing [`URef`]s to the member remain valid and report the new name
+    /// from [`URef::name()`].
+    ///
+    /// Returns an error if there is no member named `old`, if `new` is not a
+    /// [`Name::Specific`], or if `new` is already in use.
+    ///
+    /// ```
+    /// use all_is_cubes::space::Space;
+    /// use all_is_cubes::universe::{Name, Universe, URef};
+    ///
+    /// let mut universe = Universe::new();
+    /// let space: URef<Space> = universe.insert("old".into(), Space::empty_positive(1, 1, 1))?;
+    ///
+    /// universe.rename(&"old".into(), "new".into())?;
+    ///
+    /// assert_eq!(space.name(), Name::from("new"));
+    /// assert_eq!(universe.get::<Space>(&"new".into()), Some(space));
+    /// assert_eq!(universe.get::<Space>(&"old".into()), None);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn rename(&mut self, old: &Name, new: Name) -> Result<(), RenameError> {
+        if !matches!(new, Name::Specific(_)) {
+            return Err(RenameError::InvalidName(new));
+        }
+        if self.get_any(old).is_none() {
+            return Err(RenameError::NotFound(old.clone()));
+        }
+        if *old == new {
+            return Ok(());
+        }
+        if self.get_any(&new).is_some() {
+            return Err(RenameError::AlreadyExists(new));
+        }
+
+        let renamed = self.tables.rename(old, &new);
+        debug_assert!(renamed);
+        Ok(())
+    }
+
     /// Returns a `URef` to a member whose referent may or may not be deserialized yet.
     #[cfg(feature = "save")]
     pub(crate) fn get_or_insert_deserializing<T>(
@@ -329,7 +379,11 @@ impl Universe {
                     kind: InsertErrorKind::InvalidName,
                 })
             }
-            Name::Specific(_) | Name::Anonym(_) => {}
+            Name::Specific(_) => {}
+            Name::Anonym(number) => {
+                // Ensure future anonymous insertions will not collide with this name.
+                self.next_anonym = self.next_anonym.max(number + 1);
+            }
         }
         match <Universe as UniverseTable<T>>::table_mut(self).entry(name.clone()) {
             std::collections::btree_map::Entry::Occupied(oe) => Ok(oe.get().downgrade()),
@@ -446,11 +500,31 @@ impl Universe {
             || spaces.remove(name).is_some()
     }
 
-    /// Delete all anonymous members which have no references to them.
+    /// Returns the number of members of each type in this universe, for monitoring
+    /// purposes.
+    pub fn member_count_by_type(&self) -> MemberCounts {
+        let UniverseTables {
+            blocks,
+            characters,
+            spaces,
+        } = &self.tables;
+        MemberCounts {
+            blocks: blocks.len(),
+            characters: characters.len(),
+            spaces: spaces.len(),
+        }
+    }
+
+    /// Delete all anonymous members which have no references to them, and report how
+    /// many were deleted.
+    ///
+    /// Members with [`Name::Specific`] names are never deleted by garbage collection,
+    /// even if no [`URef`]s to them exist; use [`UniverseTransaction::delete()`] to
+    /// remove them.
     ///
     /// This may happen at any time during operations of the universe; calling this method
     /// merely ensures that it happens now and not earlier.
-    pub fn gc(&mut self) {
+    pub fn gc(&mut self) -> GcReport {
         let UniverseTables {
             blocks,
             characters,
@@ -465,9 +539,13 @@ impl Universe {
         // members around if there are `URef`s to them outside of the Universe, whereas the
         // preferred behavior, for consistency of the game logic, would be that they
         // go away at a time that is deterministic with respect to the simulation.
-        gc_members(blocks);
-        gc_members(characters);
-        gc_members(spaces);
+        GcReport {
+            collected: MemberCounts {
+                blocks: gc_members(blocks),
+                characters: gc_members(characters),
+                spaces: gc_members(spaces),
+            },
+        }
     }
 
     /// Traverse all members and find [`URef`]s that were deserialized in disconnected form.
@@ -511,6 +589,21 @@ impl Universe {
 
         Ok(())
     }
+
+    /// Returns the names of members of type `T` which were referenced during deserialization
+    /// (and therefore given entries by [`Self::get_or_insert_deserializing()`])
+    /// but have not been given values.
+    #[cfg(feature = "save")]
+    pub(crate) fn unfilled_deserialized_names<T: 'static>(&self) -> Vec<Name>
+    where
+        Self: UniverseTable<T, Table = Storage<T>>,
+    {
+        <Universe as UniverseTable<T>>::table(self)
+            .iter()
+            .filter(|(_, root)| matches!(root.downgrade().read(), Err(RefError::NotReady(_))))
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
 }
 
 impl fmt::Debug for Universe {
@@ -598,6 +691,22 @@ impl fmt::Display for InsertError {
     }
 }
 
+/// Errors resulting from attempting to rename a member of a [`Universe`] using
+/// [`Universe::rename()`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq, thiserror::Error)]
+#[non_exhaustive]
+pub enum RenameError {
+    /// No object exists with the old name.
+    #[error("no object exists with name {0}")]
+    NotFound(Name),
+    /// The new name may not be used.
+    #[error("the name {0} may not be used as a new name")]
+    InvalidName(Name),
+    /// An object already exists with the new name.
+    #[error("an object already exists with name {0}")]
+    AlreadyExists(Name),
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, thiserror::Error)]
 #[error("data contains a reference to {to} that was not defined")]
 pub(crate) struct DeserializeRefsError {
@@ -645,23 +754,63 @@ impl CustomFormat<StatusText> for UniverseStepInfo {
     }
 }
 
-/// Helper for [`Universe::gc()`].
-fn gc_members<T>(table: &mut Storage<T>) {
+/// Number of members of each type in a [`Universe`].
+///
+/// Returned by [`Universe::member_count_by_type()`] and as part of [`GcReport`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct MemberCounts {
+    /// Number of [`BlockDef`]s.
+    pub blocks: usize,
+    /// Number of [`Character`]s.
+    pub characters: usize,
+    /// Number of [`Space`]s.
+    pub spaces: usize,
+}
+
+impl MemberCounts {
+    /// Returns the total number of members of all types.
+    pub fn total(&self) -> usize {
+        let Self {
+            blocks,
+            characters,
+            spaces,
+        } = *self;
+        blocks + characters + spaces
+    }
+}
+
+/// Information about what a garbage collection did, returned by [`Universe::gc()`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct GcReport {
+    /// Number of members of each type which were deleted.
+    pub collected: MemberCounts,
+}
+
+/// Helper for [`Universe::gc()`]. Returns the number of members deleted.
+fn gc_members<T>(table: &mut Storage<T>) -> usize {
     let mut dead: Vec<Name> = Vec::new();
     for (name, root) in table.iter() {
-        if root.weak_ref_count() == 0 {
+        if matches!(name, Name::Anonym(_)) && root.weak_ref_count() == 0 {
             dead.push(name.clone());
         }
     }
-    for name in dead {
-        table.remove(&name);
+    for name in &dead {
+        table.remove(name);
     }
+    dead.len()
 }
 
 /// A subset of the [`URef`]s in one universe.
 ///
 /// May be serialized as if it was a [`Universe`].
 ///
+/// The members of each type are kept in a deterministic order, so that exports which
+/// iterate over them produce the same output every time:
+/// [`PartialUniverse::all_of()`] orders members by [`Name`], and
+/// [`PartialUniverse::from_set()`] preserves the order in which members were given.
+///
 /// This structure is not currently exposed because it is a helper for
 /// `all_is_cubes_port::ExportSet` and doesn't play a role in the API itself.
 #[doc(hidden)]
@@ -676,6 +825,7 @@ pub struct PartialUniverse {
 }
 
 impl PartialUniverse {
+    /// Select all members of `universe`, ordered by [`Name`].
     pub fn all_of(universe: &Universe) -> Self {
         Self {
             blocks: universe.iter_by_type().map(|(_, r)| r).collect(),
@@ -684,7 +834,7 @@ impl PartialUniverse {
         }
     }
 
-    /// Select only the given members.
+    /// Select only the given members, in the order they are given.
     pub fn from_set<T>(members: impl IntoIterator<Item = URef<T>>) -> Self
     where
         T: UniverseMember,
@@ -693,6 +843,23 @@ impl PartialUniverse {
         <Self as PartialUniverseOps<T>>::from_set(members)
     }
 
+    /// Returns all of the selected members, in their stored order: blocks first, then
+    /// characters, then spaces.
+    pub fn members(&self) -> Vec<AnyURef> {
+        let Self {
+            blocks,
+            characters,
+            spaces,
+        } = self;
+        blocks
+            .iter()
+            .cloned()
+            .map(AnyURef::BlockDef)
+            .chain(characters.iter().cloned().map(AnyURef::Character))
+            .chain(spaces.iter().cloned().map(AnyURef::Space))
+            .collect()
+    }
+
     #[doc(hidden)]
     pub fn count(&self) -> usize {
         let Self {
@@ -702,4 +869,72 @@ impl PartialUniverse {
         } = self;
         blocks.len() + characters.len() + spaces.len()
     }
+
+    /// Adds every member which is transitively referenced by the selected members, but
+    /// not itself selected, after the already selected members of its type.
+    /// Then, returns strong references which keep all of the selected members readable,
+    /// even if they are removed from their universe, until the [`PinnedMembers`] is dropped.
+    ///
+    /// Returns an error if any member cannot be read; in particular, [`RefError::Gone`]
+    /// naming the first member found to no longer exist.
+    #[doc(hidden)]
+    pub fn include_and_pin_dependencies(&mut self) -> Result<PinnedMembers, RefError> {
+        let mut pins = PinnedMembers::default();
+        let mut to_visit: VecDeque<AnyURef> = self.members().into();
+        #[allow(clippy::mutable_key_type)] // URefs are hashed by pointer, not by state
+        let mut seen: HashSet<AnyURef> = to_visit.iter().cloned().collect();
+        while let Some(member) = to_visit.pop_front() {
+            let mut found = |r: &dyn URefErased| {
+                let r = r.to_any_uref();
+                if seen.insert(r.clone()) {
+                    match &r {
+                        AnyURef::BlockDef(r) => self.blocks.push(r.clone()),
+                        AnyURef::Character(r) => self.characters.push(r.clone()),
+                        AnyURef::Space(r) => self.spaces.push(r.clone()),
+                    }
+                    to_visit.push_back(r);
+                }
+            };
+            match member {
+                AnyURef::BlockDef(r) => {
+                    let strong = r.to_strong()?;
+                    r.read()?.visit_refs(&mut found);
+                    pins.blocks.push(strong);
+                }
+                AnyURef::Character(r) => {
+                    let strong = r.to_strong()?;
+                    r.read()?.visit_refs(&mut found);
+                    pins.characters.push(strong);
+                }
+                AnyURef::Space(r) => {
+                    let strong = r.to_strong()?;
+                    r.read()?.visit_refs(&mut found);
+                    pins.spaces.push(strong);
+                }
+            }
+        }
+        Ok(pins)
+    }
+}
+
+/// Strong references to the members of a [`PartialUniverse`], keeping them readable;
+/// returned by [`PartialUniverse::include_and_pin_dependencies()`].
+#[doc(hidden)]
+#[derive(Clone, Debug, Default)]
+pub struct PinnedMembers {
+    blocks: Vec<StrongURef<BlockDef>>,
+    characters: Vec<StrongURef<Character>>,
+    spaces: Vec<StrongURef<Space>>,
+}
+
+impl PinnedMembers {
+    /// Returns the number of members pinned.
+    pub fn count(&self) -> usize {
+        let Self {
+            blocks,
+            characters,
+            spaces,
+        } = self;
+        blocks.len() + characters.len() + spaces.len()
+    }
 }
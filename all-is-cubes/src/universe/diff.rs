@@ -0,0 +1,505 @@
+//! [`UniverseDiff`] and the [`Universe`] methods which produce and apply it.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::block::BlockDefTransaction;
+use crate::save::conversion::universe::{member_to_json, members_from_json};
+use crate::save::schema::MemberDe;
+use crate::transaction::{self, ExecuteError, Merge as _, Transaction as _};
+use crate::universe::{
+    AnyURef, Name, URef, URefErased, Universe, UniverseTables, UniverseTransaction, VisitRefs,
+};
+
+/// The differences between two [`Universe`]s, member by member, as computed by
+/// [`Universe::diff()`] and applied by [`Universe::apply_diff()`].
+///
+/// Members are matched by [`Name`] and compared by their serialized form; a member is
+/// considered changed if and only if saving it would produce different data.
+/// The diff contains serialized copies of all added and changed members, not references
+/// to the universe it was computed from, so it stays valid if that universe is later
+/// modified or dropped. It can itself be serialized, to be stored or transmitted and
+/// applied elsewhere.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct UniverseDiff {
+    pub(crate) added: BTreeMap<Name, serde_json::Value>,
+    pub(crate) removed: BTreeSet<Name>,
+    pub(crate) changed: BTreeMap<Name, serde_json::Value>,
+}
+
+impl UniverseDiff {
+    /// Returns whether applying this diff would make no changes.
+    pub fn is_empty(&self) -> bool {
+        let Self {
+            added,
+            removed,
+            changed,
+        } = self;
+        added.is_empty() && removed.is_empty() && changed.is_empty()
+    }
+
+    /// Names of members which are present in the new universe but not the old one.
+    pub fn added(&self) -> impl Iterator<Item = &Name> + '_ {
+        self.added.keys()
+    }
+
+    /// Names of members which are present in the old universe but not the new one.
+    pub fn removed(&self) -> impl Iterator<Item = &Name> + '_ {
+        self.removed.iter()
+    }
+
+    /// Names of members which are present in both universes with different values.
+    pub fn changed(&self) -> impl Iterator<Item = &Name> + '_ {
+        self.changed.keys()
+    }
+}
+
+/// Errors resulting from [`Universe::diff()`] or [`Universe::apply_diff()`].
+///
+/// If [`Universe::apply_diff()`] returns one of these errors, then the universe has not
+/// been modified.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum DiffError {
+    /// A member could not be serialized for comparison (for example, because it was
+    /// currently borrowed), or a member in the diff could not be deserialized.
+    #[error("could not convert member {name}")]
+    Serialization {
+        /// Name of the member.
+        name: Name,
+        /// Error that occurred.
+        #[source]
+        error: serde_json::Error,
+    },
+
+    /// The diff removes or changes a member that does not exist.
+    #[error("member {0} does not exist")]
+    Missing(Name),
+
+    /// The diff adds a member whose name is already in use.
+    #[error("member {0} already exists")]
+    AlreadyExists(Name),
+
+    /// The diff changes a member to a different type (e.g. from a block to a space),
+    /// which is not supported.
+    #[error("member {0} cannot be changed to a different type")]
+    TypeChanged(Name),
+
+    /// An added or changed member refers to a member that would not exist once the
+    /// diff was applied.
+    #[error("member {from} refers to {to}, which would not exist")]
+    MissingReference {
+        /// The member containing the reference.
+        from: Name,
+        /// The name referred to.
+        to: Name,
+    },
+
+    /// A member to be changed is currently in use and cannot be modified.
+    #[error("member {0} is in use")]
+    InUse(Name),
+
+    /// The transaction performing the removals and block changes failed.
+    #[error("transaction failed")]
+    Transaction(#[source] ExecuteError),
+}
+
+impl Universe {
+    /// Compares this universe to `other` and returns the changes which would make
+    /// this universe's members match `other`'s when given to [`Universe::apply_diff()`].
+    ///
+    /// Members are matched by [`Name`], regardless of type, and compared in serialized
+    /// form. Only members are compared; other universe state, such as the clock,
+    /// is ignored.
+    ///
+    /// Returns an error if any member cannot be read.
+    pub fn diff(&self, other: &Universe) -> Result<UniverseDiff, DiffError> {
+        let old = members_by_name(self);
+        let new = members_by_name(other);
+
+        let mut diff = UniverseDiff::default();
+        for (name, old_ref) in &old {
+            match new.get(name) {
+                None => {
+                    diff.removed.insert(name.clone());
+                }
+                Some(new_ref) => {
+                    let new_value = serialize_member(new_ref)?;
+                    if serialize_member(old_ref)? != new_value {
+                        diff.changed.insert(name.clone(), new_value);
+                    }
+                }
+            }
+        }
+        for (name, new_ref) in &new {
+            if !old.contains_key(name) {
+                diff.added.insert(name.clone(), serialize_member(new_ref)?);
+            }
+        }
+        Ok(diff)
+    }
+
+    /// Modifies this universe's members as described by `diff`, such that it matches
+    /// the universe the diff was computed from (in the members the diff covers).
+    ///
+    /// Either the whole diff is applied or, if an error is returned, none of it is.
+    /// The removals and changes to [`BlockDef`](crate::block::BlockDef)s are performed
+    /// by a single [`UniverseTransaction`]; changed [`Space`](crate::space::Space)s and
+    /// [`Character`](crate::character::Character)s are replaced in place, so existing
+    /// [`URef`]s to them and listeners on them remain valid.
+    ///
+    /// # Conflicts
+    ///
+    /// Since `self` may have been modified independently since the diff was computed,
+    /// the diff might not fit. In that case this returns an error and does nothing:
+    ///
+    /// * If a removed or changed member does not exist, [`DiffError::Missing`].
+    /// * If an added member's name is already in use, [`DiffError::AlreadyExists`].
+    /// * If an added or changed member refers to a name which is neither an existing
+    ///   member nor added by the diff, or which the diff removes,
+    ///   [`DiffError::MissingReference`].
+    ///
+    /// Members which are not themselves part of the diff are not checked: if they refer to
+    /// a removed member, then, just as with [`UniverseTransaction::delete()`], those
+    /// references will fail with [`RefError::Gone`](crate::universe::RefError::Gone).
+    pub fn apply_diff(&mut self, diff: &UniverseDiff) -> Result<(), DiffError> {
+        let UniverseDiff {
+            added,
+            removed,
+            changed,
+        } = diff;
+
+        let existing = members_by_name(self);
+        for name in removed.iter().chain(changed.keys()) {
+            if !existing.contains_key(name) {
+                return Err(DiffError::Missing(name.clone()));
+            }
+        }
+        if let Some(name) = added.keys().find(|name| existing.contains_key(name)) {
+            return Err(DiffError::AlreadyExists(name.clone()));
+        }
+
+        // Deserializing may insert placeholders for referenced names, so from here on,
+        // any failure must remove them.
+        let new_values = match members_from_json(self, added.iter().chain(changed.iter())) {
+            Ok(values) => values,
+            Err((name, error)) => {
+                self.remove_members_not_in(&existing);
+                return Err(DiffError::Serialization { name, error });
+            }
+        };
+        if let Err(error) = check_new_members(&existing, diff, &new_values) {
+            self.remove_members_not_in(&existing);
+            return Err(error);
+        }
+
+        // Assemble the transaction and the replacements which have no transaction.
+        let mut transaction = UniverseTransaction::default();
+        for name in removed {
+            transaction = transaction
+                .merge(UniverseTransaction::delete(existing[name].clone()))
+                .expect("diff transactions cannot conflict");
+        }
+        let mut additions = Vec::new();
+        let mut replacements = Vec::new();
+        for (name, value) in new_values {
+            match (existing.get(&name), value) {
+                (None, value) => additions.push((name, value)),
+                (Some(AnyURef::BlockDef(r)), MemberDe::Block { value: block }) => {
+                    transaction = transaction
+                        .merge(BlockDefTransaction::overwrite(block).bind(r.clone()))
+                        .expect("diff transactions cannot conflict");
+                }
+                (Some(AnyURef::Character(r)), MemberDe::Character { value }) => {
+                    replacements.push(Replacement::Character(r.clone(), value));
+                }
+                (Some(AnyURef::Space(r)), MemberDe::Space { value }) => {
+                    replacements.push(Replacement::Space(r.clone(), value));
+                }
+                (Some(_), _) => unreachable!("types already checked"),
+            }
+        }
+
+        // Check everything that can fail before committing anything.
+        let check = match transaction.check(self) {
+            Ok(check) => check,
+            Err(e) => {
+                self.remove_members_not_in(&existing);
+                return Err(DiffError::Transaction(ExecuteError::Check(e)));
+            }
+        };
+        if let Some(name) = replacements.iter().find_map(Replacement::in_use) {
+            drop(check);
+            self.remove_members_not_in(&existing);
+            return Err(DiffError::InUse(name));
+        }
+
+        transaction
+            .commit(self, check, &mut transaction::no_outputs)
+            .map_err(|e| DiffError::Transaction(ExecuteError::Commit(e)))?;
+        for replacement in replacements {
+            replacement.apply();
+        }
+        for (name, value) in additions {
+            match value {
+                MemberDe::Block { value } => {
+                    self.insert_deserialized(name, crate::block::BlockDef::new(value))
+                }
+                MemberDe::Character { value } => self.insert_deserialized(name, value),
+                MemberDe::Space { value } => self.insert_deserialized(name, value),
+            }
+            .expect("insertion from diff failed");
+        }
+        self.wants_gc = true;
+
+        Ok(())
+    }
+
+    /// Removes every member whose name is not a key of `keep`.
+    /// Used to undo placeholder insertions from deserialization.
+    fn remove_members_not_in(&mut self, keep: &BTreeMap<Name, AnyURef>) {
+        let UniverseTables {
+            blocks,
+            characters,
+            spaces,
+        } = &mut self.tables;
+        blocks.retain(|name, _| keep.contains_key(name));
+        characters.retain(|name, _| keep.contains_key(name));
+        spaces.retain(|name, _| keep.contains_key(name));
+    }
+}
+
+/// A changed member which is not modified through a transaction.
+#[allow(clippy::large_enum_variant)] // short-lived
+enum Replacement {
+    Character(
+        URef<crate::character::Character>,
+        crate::character::Character,
+    ),
+    Space(URef<crate::space::Space>, crate::space::Space),
+}
+
+impl Replacement {
+    /// Returns the name of the member if it cannot currently be modified.
+    fn in_use(&self) -> Option<Name> {
+        let (name, result) = match self {
+            Replacement::Character(r, _) => (r.name(), r.try_modify(|_| ())),
+            Replacement::Space(r, _) => (r.name(), r.try_modify(|_| ())),
+        };
+        result.is_err().then_some(name)
+    }
+
+    fn apply(self) {
+        match self {
+            Replacement::Character(r, value) => r.try_modify(|c| c.replace_with(value)),
+            Replacement::Space(r, value) => r.try_modify(|s| s.replace_with(value)),
+        }
+        .expect("member was checked to be not in use")
+    }
+}
+
+fn members_by_name(universe: &Universe) -> BTreeMap<Name, AnyURef> {
+    let UniverseTables {
+        blocks,
+        characters,
+        spaces,
+    } = &universe.tables;
+    blocks
+        .iter()
+        .map(|(name, root)| (name.clone(), AnyURef::BlockDef(root.downgrade())))
+        .chain(
+            characters
+                .iter()
+                .map(|(name, root)| (name.clone(), AnyURef::Character(root.downgrade()))),
+        )
+        .chain(
+            spaces
+                .iter()
+                .map(|(name, root)| (name.clone(), AnyURef::Space(root.downgrade()))),
+        )
+        .collect()
+}
+
+fn serialize_member(member: &AnyURef) -> Result<serde_json::Value, DiffError> {
+    member_to_json(member).map_err(|error| DiffError::Serialization {
+        name: member.name(),
+        error,
+    })
+}
+
+/// Checks that the deserialized members of `diff` have the same types as the members they
+/// replace, and refer only to members which will exist.
+fn check_new_members(
+    existing: &BTreeMap<Name, AnyURef>,
+    diff: &UniverseDiff,
+    new_values: &[(Name, MemberDe)],
+) -> Result<(), DiffError> {
+    for (name, value) in new_values {
+        let same_type = match (existing.get(name), value) {
+            (None, _) => true,
+            (Some(AnyURef::BlockDef(_)), MemberDe::Block { .. }) => true,
+            (Some(AnyURef::Character(_)), MemberDe::Character { .. }) => true,
+            (Some(AnyURef::Space(_)), MemberDe::Space { .. }) => true,
+            (Some(_), _) => false,
+        };
+        if !same_type {
+            return Err(DiffError::TypeChanged(name.clone()));
+        }
+
+        let mut missing: Option<Name> = None;
+        let visitor = &mut |r: &dyn URefErased| {
+            let to = r.name();
+            let will_exist = (existing.contains_key(&to) && !diff.removed.contains(&to))
+                || diff.added.contains_key(&to);
+            if !will_exist && missing.is_none() {
+                missing = Some(to);
+            }
+        };
+        match value {
+            MemberDe::Block { value } => value.visit_refs(visitor),
+            MemberDe::Character { value } => value.visit_refs(visitor),
+            MemberDe::Space { value } => value.visit_refs(visitor),
+        }
+        if let Some(to) = missing {
+            return Err(DiffError::MissingReference {
+                from: name.clone(),
+                to,
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::{Block, BlockDef, Primitive, AIR};
+    use crate::content::make_some_blocks;
+    use crate::space::Space;
+    use pretty_assertions::assert_eq;
+
+    fn names<'a>(iter: impl Iterator<Item = &'a Name>) -> Vec<Name> {
+        iter.cloned().collect()
+    }
+
+    #[test]
+    fn diff_of_identical_is_empty() {
+        let mut u = Universe::new();
+        let [block] = make_some_blocks();
+        u.insert("a".into(), BlockDef::new(block)).unwrap();
+        assert!(u.diff(&u).unwrap().is_empty());
+    }
+
+    #[test]
+    fn block_def_add_remove_modify() {
+        let [block_1, block_2, block_3] = make_some_blocks();
+        let mut old = Universe::new();
+        old.insert("removed".into(), BlockDef::new(block_1.clone()))
+            .unwrap();
+        let changed_ref = old
+            .insert("changed".into(), BlockDef::new(block_1.clone()))
+            .unwrap();
+        old.insert("same".into(), BlockDef::new(AIR)).unwrap();
+
+        let mut new = Universe::new();
+        new.insert("changed".into(), BlockDef::new(block_2.clone()))
+            .unwrap();
+        new.insert("same".into(), BlockDef::new(AIR)).unwrap();
+        new.insert("added".into(), BlockDef::new(block_3.clone()))
+            .unwrap();
+
+        let diff = old.diff(&new).unwrap();
+        assert_eq!(names(diff.added()), vec![Name::from("added")]);
+        assert_eq!(names(diff.removed()), vec![Name::from("removed")]);
+        assert_eq!(names(diff.changed()), vec![Name::from("changed")]);
+
+        old.apply_diff(&diff).unwrap();
+
+        assert!(old.diff(&new).unwrap().is_empty());
+        assert!(old.get::<BlockDef>(&"removed".into()).is_none());
+        assert_eq!(
+            **old
+                .get::<BlockDef>(&"added".into())
+                .unwrap()
+                .read()
+                .unwrap(),
+            block_3
+        );
+        // Existing references see the new value.
+        assert_eq!(**changed_ref.read().unwrap(), block_2);
+    }
+
+    #[test]
+    fn added_members_may_refer_to_each_other() {
+        let [block] = make_some_blocks();
+        let mut old = Universe::new();
+        let mut new = Universe::new();
+        let inner = new.insert("inner".into(), BlockDef::new(block)).unwrap();
+        new.insert(
+            "outer".into(),
+            BlockDef::new(Block::from_primitive(Primitive::Indirect(inner))),
+        )
+        .unwrap();
+
+        old.apply_diff(&old.diff(&new).unwrap()).unwrap();
+
+        assert!(old.diff(&new).unwrap().is_empty());
+        let outer = old.get::<BlockDef>(&"outer".into()).unwrap();
+        outer.read().unwrap().evaluate().unwrap();
+    }
+
+    #[test]
+    fn changed_space_keeps_uref() {
+        let [block] = make_some_blocks();
+        let mut old = Universe::new();
+        let space_ref = old
+            .insert("space".into(), Space::empty_positive(1, 1, 1))
+            .unwrap();
+        let mut new = Universe::new();
+        let mut new_space = Space::empty_positive(1, 1, 1);
+        new_space.set([0, 0, 0], &block).unwrap();
+        new.insert("space".into(), new_space).unwrap();
+
+        old.apply_diff(&old.diff(&new).unwrap()).unwrap();
+
+        assert_eq!(space_ref.read().unwrap()[[0, 0, 0]], block);
+    }
+
+    #[test]
+    fn conflict_missing_reference_changes_nothing() {
+        let mut base = Universe::new();
+        base.insert("target".into(), BlockDef::new(AIR)).unwrap();
+        let mut new = Universe::new();
+        let target = new.insert("target".into(), BlockDef::new(AIR)).unwrap();
+        new.insert(
+            "referrer".into(),
+            BlockDef::new(Block::from_primitive(Primitive::Indirect(target))),
+        )
+        .unwrap();
+        let diff = base.diff(&new).unwrap();
+
+        // Apply the diff to a universe which lacks the referenced member.
+        let mut other = Universe::new();
+        let error = other.apply_diff(&diff).unwrap_err();
+
+        assert!(
+            matches!(
+                &error,
+                DiffError::MissingReference { from, to }
+                    if *from == "referrer".into() && *to == "target".into()
+            ),
+            "{error:?}"
+        );
+        assert!(other.get_any(&"referrer".into()).is_none());
+        assert!(other.get_any(&"target".into()).is_none());
+    }
+
+    #[test]
+    fn conflict_missing_removed() {
+        let mut old = Universe::new();
+        old.insert("x".into(), BlockDef::new(AIR)).unwrap();
+        let diff = old.diff(&Universe::new()).unwrap();
+
+        let error = Universe::new().apply_diff(&diff).unwrap_err();
+        assert!(matches!(&error, DiffError::Missing(name) if *name == "x".into()));
+    }
+}
@@ -213,6 +213,22 @@ macro_rules! member_enums_and_impls {
                 )*
                 None
             }
+
+            /// Moves the member named `old_name`, of whatever type, to `new_name`,
+            /// updating the name known to its [`URef`]s.
+            ///
+            /// Returns whether the member existed. Does not check `new_name` for validity
+            /// or collisions; [`Universe::rename()`] is responsible for that.
+            pub(crate) fn rename(&mut self, old_name: &Name, new_name: &Name) -> bool {
+                $(
+                    if let Some(root_ref) = self.$table_name.remove(old_name) {
+                        root_ref.set_name(new_name.clone());
+                        self.$table_name.insert(new_name.clone(), root_ref);
+                        return true;
+                    }
+                )*
+                false
+            }
         }
 
         /// Holds any one of the concrete [`URef<T>`](URef) types that can be in a [`Universe`].
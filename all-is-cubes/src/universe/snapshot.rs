@@ -0,0 +1,179 @@
+//! [`UniverseSnapshot`] and the [`Universe`] methods which produce and restore it.
+
+use std::fmt;
+
+use crate::block::{Block, BlockDef, BlockDefTransaction};
+use crate::character::Character;
+use crate::space::Space;
+use crate::transaction::{self, Transaction as _};
+use crate::universe::{Name, RefError, Storage, URootRef, Universe, UniverseId, UniverseTables};
+
+/// An in-memory copy of the state of all members of a [`Universe`], taken by
+/// [`Universe::snapshot()`] and put back by [`Universe::restore()`]; for example, to
+/// implement undo in an editor.
+///
+/// # Cost
+///
+/// A snapshot is a full copy of every member, including the complete block and light
+/// data of every [`Space`]. This is much faster than saving and loading (there is no
+/// serialization), but it still takes time and memory proportional to the size of the
+/// entire universe, so it is not suitable for taking after every edit of a large world.
+/// In that case, consider recording only what changed, such as by keeping the inverse of
+/// each applied transaction, or copying only the affected region of a [`Space`] with
+/// [`Space::extract()`].
+///
+/// A snapshot also keeps alive every member which existed when it was taken, even if
+/// they are deleted from the universe, so that they can be restored.
+pub struct UniverseSnapshot {
+    universe_id: UniverseId,
+    next_anonym: usize,
+    blocks: Vec<(URootRef<BlockDef>, Block)>,
+    characters: Vec<(URootRef<Character>, Character)>,
+    spaces: Vec<(URootRef<Space>, Space)>,
+}
+
+impl fmt::Debug for UniverseSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Members are too big to print; list their names only.
+        let Self {
+            universe_id,
+            next_anonym: _,
+            blocks,
+            characters,
+            spaces,
+        } = self;
+        f.debug_struct("UniverseSnapshot")
+            .field("universe_id", universe_id)
+            .field("blocks", &names(blocks))
+            .field("characters", &names(characters))
+            .field("spaces", &names(spaces))
+            .finish()
+    }
+}
+
+/// Errors resulting from [`Universe::restore()`].
+///
+/// If one of these errors is returned, then the universe has not been modified.
+#[derive(Clone, Debug, Eq, PartialEq, thiserror::Error)]
+#[non_exhaustive]
+pub enum RestoreError {
+    /// The snapshot was taken from a different universe.
+    #[error("snapshot is not of this universe")]
+    WrongUniverse,
+
+    /// A member to be restored is currently in use and cannot be modified.
+    #[error("member {0} is in use")]
+    InUse(Name),
+}
+
+impl Universe {
+    /// Copies the state of all members of this universe, so that it can be put back
+    /// later by [`Universe::restore()`].
+    ///
+    /// See [`UniverseSnapshot`] for the costs of doing this.
+    ///
+    /// Returns an error if any member is currently being modified.
+    pub fn snapshot(&self) -> Result<UniverseSnapshot, RefError> {
+        let UniverseTables {
+            blocks,
+            characters,
+            spaces,
+        } = &self.tables;
+        Ok(UniverseSnapshot {
+            universe_id: self.id,
+            next_anonym: self.next_anonym,
+            blocks: copy_table(blocks, |def| Block::clone(def))?,
+            characters: copy_table(characters, Character::clone_for_snapshot)?,
+            spaces: copy_table(spaces, Space::clone_for_snapshot)?,
+        })
+    }
+
+    /// Returns all members of this universe to the state recorded in `snapshot`.
+    ///
+    /// * Members which existed when the snapshot was taken have their state replaced.
+    ///   This is done in place, so existing [`URef`](crate::universe::URef)s to them and
+    ///   listeners on them remain valid, and the listeners are notified of the change.
+    /// * Members which were deleted since then are put back, with the same [`Name`]s,
+    ///   and existing `URef`s to them become valid again.
+    /// * Members which were inserted since then are deleted.
+    ///
+    /// Anonymous members keep their [`Name::Anonym`] numbers, and names assigned to future
+    /// anonymous members will be the same as if the snapshot had just been taken.
+    /// Other universe state, such as the clock, is not affected.
+    ///
+    /// Either the whole snapshot is restored or, if an error is returned, none of it is.
+    pub fn restore(&mut self, snapshot: UniverseSnapshot) -> Result<(), RestoreError> {
+        let UniverseSnapshot {
+            universe_id,
+            next_anonym,
+            blocks,
+            characters,
+            spaces,
+        } = snapshot;
+
+        if universe_id != self.id {
+            return Err(RestoreError::WrongUniverse);
+        }
+        check_not_in_use(&blocks)?;
+        check_not_in_use(&characters)?;
+        check_not_in_use(&spaces)?;
+
+        self.tables = UniverseTables {
+            blocks: restore_table(blocks, |def, block| {
+                if **def != block {
+                    BlockDefTransaction::overwrite(block)
+                        .execute(def, &mut transaction::no_outputs)
+                        .expect("overwriting a BlockDef cannot fail");
+                }
+            }),
+            characters: restore_table(characters, Character::replace_with),
+            spaces: restore_table(spaces, Space::replace_with),
+        };
+        self.next_anonym = next_anonym;
+        self.wants_gc = true;
+
+        Ok(())
+    }
+}
+
+fn copy_table<T: 'static, S>(
+    table: &Storage<T>,
+    copy: impl Fn(&T) -> S,
+) -> Result<Vec<(URootRef<T>, S)>, RefError> {
+    table
+        .values()
+        .map(|root| Ok((root.clone_root(), copy(&*root.downgrade().read()?))))
+        .collect()
+}
+
+fn check_not_in_use<T: 'static, S>(members: &[(URootRef<T>, S)]) -> Result<(), RestoreError> {
+    for (root, _) in members {
+        let r = root.downgrade();
+        if r.try_modify(|_| ()).is_err() {
+            return Err(RestoreError::InUse(r.name()));
+        }
+    }
+    Ok(())
+}
+
+fn restore_table<T: 'static, S>(
+    members: Vec<(URootRef<T>, S)>,
+    restore: impl Fn(&mut T, S),
+) -> Storage<T> {
+    members
+        .into_iter()
+        .map(|(root, state)| {
+            let r = root.downgrade();
+            r.try_modify(|value| restore(value, state))
+                .expect("member was checked to be not in use");
+            (r.name(), root)
+        })
+        .collect()
+}
+
+fn names<T: 'static, S>(members: &[(URootRef<T>, S)]) -> Vec<Name> {
+    members
+        .iter()
+        .map(|(root, _)| root.downgrade().name())
+        .collect()
+}
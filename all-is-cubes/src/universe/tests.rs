@@ -11,7 +11,8 @@ use crate::space::Space;
 use crate::time;
 use crate::transaction::{self, Transaction};
 use crate::universe::{
-    list_refs, InsertError, InsertErrorKind, Name, RefError, URef, Universe, UniverseTransaction,
+    list_refs, GcReport, InsertError, InsertErrorKind, MemberCounts, Name, RefError, RenameError,
+    RestoreError, URef, Universe, UniverseTransaction,
 };
 use crate::util::assert_send_sync;
 
@@ -247,6 +248,24 @@ fn delete_success() {
     let _ = ref_2.read().unwrap();
 }
 
+#[test]
+fn strong_ref_keeps_deleted_member_readable() {
+    let mut u = Universe::new();
+    let [block] = make_some_blocks();
+    let uref = u
+        .insert("foo".into(), BlockDef::new(block.clone()))
+        .unwrap();
+    let strong = uref.to_strong().unwrap();
+
+    UniverseTransaction::delete(uref.clone())
+        .execute(&mut u, &mut drop)
+        .unwrap();
+    assert_eq!(**strong.uref().read().unwrap(), block);
+
+    drop(strong);
+    assert_eq!(uref.read().unwrap_err(), RefError::Gone("foo".into()));
+}
+
 /// Anonymous members are strictly garbage collected, and cannot be deleted.
 #[test]
 fn delete_anonymous_fails() {
@@ -285,6 +304,86 @@ fn delete_wrong_universe_fails() {
     txn.execute(&mut u2, &mut drop).unwrap_err();
 }
 
+#[test]
+fn rename_success() {
+    let mut u = Universe::new();
+    let space = u
+        .insert("old".into(), Space::empty_positive(1, 1, 1))
+        .unwrap();
+    let block_def = u
+        .insert(
+            "block".into(),
+            BlockDef::new(
+                Block::builder()
+                    .voxels_ref(Resolution::R1, space.clone())
+                    .build(),
+            ),
+        )
+        .unwrap();
+
+    u.rename(&"old".into(), "new".into()).unwrap();
+
+    assert_eq!(space.name(), Name::from("new"));
+    space.read().unwrap();
+    assert_eq!(u.get::<Space>(&"new".into()), Some(space.clone()));
+    assert_eq!(u.get::<Space>(&"old".into()), None);
+    assert!(u.get_any(&"old".into()).is_none());
+
+    // A ref held by another member sees the new name too.
+    let Primitive::Recur { space: inner, .. } = block_def.read().unwrap().primitive().clone()
+    else {
+        panic!("expected Recur");
+    };
+    assert_eq!(inner, space);
+    assert_eq!(inner.name(), Name::from("new"));
+}
+
+#[test]
+fn rename_anonymous_to_specific() {
+    let mut u = Universe::new();
+    let block_def = u.insert_anonymous(BlockDef::new(AIR));
+    let old_name = block_def.name();
+
+    u.rename(&old_name, "named".into()).unwrap();
+
+    assert_eq!(block_def.name(), Name::from("named"));
+    assert_eq!(u.get::<BlockDef>(&"named".into()), Some(block_def));
+    assert_eq!(u.get::<BlockDef>(&old_name), None);
+}
+
+#[test]
+fn rename_errors() {
+    let mut u = Universe::new();
+    u.insert("a".into(), BlockDef::new(AIR)).unwrap();
+    u.insert("b".into(), Space::empty_positive(1, 1, 1))
+        .unwrap();
+
+    assert_eq!(
+        u.rename(&"nonexistent".into(), "c".into()),
+        Err(RenameError::NotFound("nonexistent".into()))
+    );
+    assert_eq!(
+        u.rename(&"a".into(), "b".into()),
+        Err(RenameError::AlreadyExists("b".into()))
+    );
+    assert_eq!(
+        u.rename(&"a".into(), Name::Anonym(0)),
+        Err(RenameError::InvalidName(Name::Anonym(0)))
+    );
+    assert_eq!(
+        u.rename(&"a".into(), Name::Pending),
+        Err(RenameError::InvalidName(Name::Pending))
+    );
+
+    // Nothing changed.
+    assert!(u.get::<BlockDef>(&"a".into()).is_some());
+    assert!(u.get::<Space>(&"b".into()).is_some());
+
+    // Renaming to the same name is a no-op success.
+    u.rename(&"a".into(), "a".into()).unwrap();
+    assert!(u.get::<BlockDef>(&"a".into()).is_some());
+}
+
 #[test]
 fn step_time() {
     let mut u = Universe::new();
@@ -304,6 +403,38 @@ fn gc_explicit() {
     assert_eq!(0, u.iter_by_type::<BlockDef>().count());
 }
 
+#[test]
+fn gc_report() {
+    let mut u = Universe::new();
+    let space = u.insert_anonymous(Space::empty_positive(1, 1, 1));
+    let _kept = u.insert_anonymous(Space::empty_positive(1, 1, 1));
+    assert_eq!(u.member_count_by_type().spaces, 2);
+
+    // Nothing to collect while there is still a reference.
+    assert_eq!(u.gc(), GcReport::default());
+
+    drop(space);
+    assert_eq!(
+        u.gc(),
+        GcReport {
+            collected: MemberCounts {
+                spaces: 1,
+                ..MemberCounts::default()
+            }
+        }
+    );
+    assert_eq!(u.member_count_by_type().spaces, 1);
+    assert_eq!(u.member_count_by_type().total(), 1);
+}
+
+#[test]
+fn gc_keeps_named_members() {
+    let mut u = Universe::new();
+    drop(u.insert("named".into(), BlockDef::new(AIR)).unwrap());
+    assert_eq!(u.gc().collected.total(), 0);
+    assert_eq!(1, u.iter_by_type::<BlockDef>().count());
+}
+
 #[test]
 fn gc_implicit() {
     let mut u = Universe::new();
@@ -313,6 +444,70 @@ fn gc_implicit() {
     assert_eq!(0, u.iter_by_type::<BlockDef>().count());
 }
 
+#[test]
+fn snapshot_and_restore() {
+    let mut u = Universe::new();
+    let [block_1, block_2] = make_some_blocks();
+    let def_ref = u
+        .insert("def".into(), BlockDef::new(block_1.clone()))
+        .unwrap();
+    let space_ref = u
+        .insert("space".into(), Space::empty_positive(1, 1, 1))
+        .unwrap();
+    let anon_ref = u.insert_anonymous(Space::empty_positive(1, 1, 1));
+    let deleted_ref = u.insert("deleted".into(), BlockDef::new(AIR)).unwrap();
+
+    let snapshot = u.snapshot().unwrap();
+
+    // Make every kind of change.
+    space_ref
+        .try_modify(|space| space.set([0, 0, 0], &block_2))
+        .unwrap()
+        .unwrap();
+    anon_ref
+        .try_modify(|space| space.set([0, 0, 0], &block_2))
+        .unwrap()
+        .unwrap();
+    BlockDefTransaction::overwrite(block_2.clone())
+        .bind(def_ref.clone())
+        .execute(&mut u, &mut drop)
+        .unwrap();
+    UniverseTransaction::delete(deleted_ref.clone())
+        .execute(&mut u, &mut drop)
+        .unwrap();
+    let added_ref = u.insert("added".into(), BlockDef::new(AIR)).unwrap();
+    let anon_name_after_snapshot = u.insert_anonymous(BlockDef::new(AIR)).name();
+
+    u.restore(snapshot).unwrap();
+
+    assert_eq!(space_ref.read().unwrap()[[0, 0, 0]], AIR);
+    assert_eq!(anon_ref.read().unwrap()[[0, 0, 0]], AIR);
+    assert_eq!(**def_ref.read().unwrap(), block_1);
+    assert_eq!(**deleted_ref.read().unwrap(), AIR);
+    assert_eq!(
+        added_ref.read().unwrap_err(),
+        RefError::Gone("added".into())
+    );
+    assert_eq!(
+        u.get::<Space>(&anon_ref.name()).as_ref(),
+        Some(&anon_ref),
+        "anonymous member should keep its name"
+    );
+    assert_eq!(
+        u.insert_anonymous(BlockDef::new(AIR)).name(),
+        anon_name_after_snapshot,
+        "anonymous names should be assigned as if the snapshot was just taken"
+    );
+}
+
+#[test]
+fn restore_wrong_universe() {
+    let u1 = Universe::new();
+    let mut u2 = Universe::new();
+    let snapshot = u1.snapshot().unwrap();
+    assert_eq!(u2.restore(snapshot), Err(RestoreError::WrongUniverse));
+}
+
 #[test]
 fn visit_refs_block_def_no_ref() {
     assert_eq!(list_refs(&BlockDef::new(AIR)), vec![]);
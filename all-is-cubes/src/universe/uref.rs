@@ -239,6 +239,17 @@ impl<T: 'static> URef<T> {
         })?
     }
 
+    /// Obtain a [`StrongURef`] which keeps the referent from being deallocated, so that
+    /// this `URef` remains readable even if the referent is removed from its [`Universe`].
+    ///
+    /// Returns [`RefError::Gone`] if the referent is already gone.
+    pub fn to_strong(&self) -> Result<StrongURef<T>, RefError> {
+        Ok(StrongURef {
+            strong_ref: self.upgrade()?,
+            uref: self.clone(),
+        })
+    }
+
     fn upgrade(&self) -> Result<StrongEntryRef<T>, RefError> {
         self.weak_ref
             .upgrade()
@@ -442,6 +453,41 @@ impl<T> Clone for URef<T> {
     }
 }
 
+/// A [`URef`] which also keeps its referent from being deallocated; obtained from
+/// [`URef::to_strong()`].
+///
+/// Ordinary [`URef`]s are weak, so that members may be removed from a [`Universe`] while
+/// still referenced. A `StrongURef` is for code which needs a consistent view of data for
+/// the duration of some operation, such as an export. It should not be stored
+/// long-term, since it prevents the referent's memory from being freed.
+pub struct StrongURef<T> {
+    strong_ref: StrongEntryRef<T>,
+    uref: URef<T>,
+}
+
+impl<T> StrongURef<T> {
+    /// Returns the [`URef`] this was obtained from, which will remain readable for as long
+    /// as this `StrongURef` exists.
+    pub fn uref(&self) -> &URef<T> {
+        &self.uref
+    }
+}
+
+impl<T: fmt::Debug + 'static> fmt::Debug for StrongURef<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("StrongURef").field(&self.uref).finish()
+    }
+}
+
+impl<T> Clone for StrongURef<T> {
+    fn clone(&self) -> Self {
+        StrongURef {
+            strong_ref: self.strong_ref.clone(),
+            uref: self.uref.clone(),
+        }
+    }
+}
+
 impl<T: UniverseMember> AsRef<dyn URefErased> for URef<T> {
     fn as_ref(&self) -> &dyn URefErased {
         self
@@ -606,6 +652,33 @@ impl<T> URootRef<T> {
         }
     }
 
+    /// Changes the name which this entry and all [`URef`]s to it report.
+    ///
+    /// The caller is responsible for also updating the key under which the entry is
+    /// stored in the [`Universe`].
+    pub(super) fn set_name(&self, new_name: Name) {
+        let mut state_guard: std::sync::MutexGuard<'_, State<T>> =
+            self.state.lock().expect("URef::state lock error");
+        match &mut *state_guard {
+            State::Member { name, .. } => *name = new_name,
+            #[cfg(feature = "save")]
+            State::Deserializing { name, .. } => *name = new_name,
+            State::Pending { .. } | State::Gone { .. } => {
+                unreachable!("URootRef should always be in a universe")
+            }
+        }
+    }
+
+    /// Returns another root reference to the same entry, which keeps it alive.
+    ///
+    /// This is not a [`Clone`] impl, so that extra roots are not created casually.
+    pub(crate) fn clone_root(&self) -> Self {
+        URootRef {
+            strong_ref: self.strong_ref.clone(),
+            state: self.state.clone(),
+        }
+    }
+
     /// Returns the number of weak references to this entry, which is greater than
     /// or equal to the number of [`URef`]s to it.
     pub(crate) fn weak_ref_count(&self) -> usize {
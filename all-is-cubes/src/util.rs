@@ -4,6 +4,7 @@ use std::error::Error;
 use std::fmt::{self, Debug, Display};
 use std::marker::PhantomData;
 use std::ops::AddAssign;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use cgmath::{Matrix4, Point3, Vector2, Vector3, Vector4};
@@ -18,6 +19,46 @@ pub fn yield_progress_for_testing() -> YieldProgress {
     yield_progress::Builder::new().build()
 }
 
+/// Creates a [`YieldProgress`] which never actually yields, and which records every
+/// progress report made through it or its descendants in the returned [`ProgressRecord`].
+///
+/// This is intended only for tests and benchmarks which want to check what progress is
+/// reported, or need deterministic behavior without an async executor's yielding.
+#[doc(hidden)]
+pub fn yield_progress_for_recording() -> (YieldProgress, ProgressRecord) {
+    let record = ProgressRecord::default();
+    let progress = yield_progress::Builder::new()
+        .yield_using(|_| std::future::ready(()))
+        .progress_using({
+            let record = record.clone();
+            move |info| {
+                record
+                    .events
+                    .lock()
+                    .unwrap()
+                    .push((info.fraction(), info.label_str().to_owned()));
+            }
+        })
+        .build();
+    (progress, record)
+}
+
+/// Progress reports captured by [`yield_progress_for_recording()`].
+///
+/// This is intended only for tests and benchmarks.
+#[doc(hidden)]
+#[derive(Clone, Debug, Default)]
+pub struct ProgressRecord {
+    events: Arc<Mutex<Vec<(f32, String)>>>,
+}
+
+impl ProgressRecord {
+    /// Returns all progress reports so far, in order, as `(fraction, label)` pairs.
+    pub fn events(&self) -> Vec<(f32, String)> {
+        self.events.lock().unwrap().clone()
+    }
+}
+
 /// Generic extension to [`std::fmt`'s set of formatting traits](std::fmt#formatting-traits).
 ///
 /// This can be thought of as a mechanism to easily create a new special-purpose
@@ -314,6 +355,64 @@ impl Display for TimeStats {
     }
 }
 
+/// [`Hasher`](std::hash::Hasher) implementing the 64-bit FNV-1a algorithm.
+///
+/// Unlike [`std::collections::hash_map::DefaultHasher`], this algorithm is fixed, so hashes
+/// of the same data are the same in every run and every version of the program (as long
+/// as the [`Hash`](std::hash::Hash) implementations involved do not change).
+/// It is not resistant to collision attacks, so it should not be used for hash tables
+/// containing untrusted keys.
+///
+/// Hashes produced by this hasher may be stored, so its output is a stable format which
+/// must not change:
+///
+/// * The state starts as the FNV offset basis `0xcbf29ce484222325`. For each byte written,
+///   the byte is exclusive-ored into the state, and then the state is multiplied by the
+///   FNV prime `0x100000001b3`, wrapping on overflow.
+///   [`finish()`](std::hash::Hasher::finish) returns the state.
+/// * Integers are written as their little-endian bytes, and [`usize`] and [`isize`]
+///   (which includes the lengths of slices and collections) are first converted to
+///   64 bits, so the hash is the same on every platform.
+#[derive(Clone, Debug)]
+pub(crate) struct StableHasher(u64);
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl std::hash::Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    // The default implementations of these use native endianness and size.
+    // The signed integer methods delegate to these.
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes());
+    }
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+    fn write_u128(&mut self, i: u128) {
+        self.write(&i.to_le_bytes());
+    }
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+}
+
 #[doc(hidden)]
 pub fn assert_send_sync<T: Send + Sync>() {
     // We don't need to do anything in this function; the call to it having been successfully
@@ -324,6 +423,18 @@ pub fn assert_send_sync<T: Send + Sync>() {
 mod tests {
     use super::*;
 
+    #[tokio::test]
+    async fn recording_progress() {
+        let (mut progress, record) = yield_progress_for_recording();
+        progress.set_label("working");
+        progress.progress(0.5).await;
+        progress.finish().await;
+        assert_eq!(
+            record.events(),
+            vec![(0.5, "working".to_owned()), (1.0, "working".to_owned())]
+        );
+    }
+
     #[test]
     fn basic_concise_debug() {
         #[derive(Debug)]
@@ -351,4 +462,30 @@ mod tests {
             "TestError2\n\nCaused by:\n    TestError1"
         );
     }
+
+    /// [`StableHasher`]'s output must not change, so check it against FNV-1a test vectors
+    /// and check that integers are written in a fixed format.
+    #[test]
+    fn stable_hasher_format() {
+        use std::hash::Hasher as _;
+
+        fn hash(f: impl FnOnce(&mut StableHasher)) -> u64 {
+            let mut hasher = StableHasher::default();
+            f(&mut hasher);
+            hasher.finish()
+        }
+
+        assert_eq!(hash(|_| {}), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(hash(|h| h.write(b"a")), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(hash(|h| h.write(b"foobar")), 0x8594_4171_f739_67e8);
+
+        let bytes = hash(|h| h.write(&[1, 2, 0, 0, 0, 0, 0, 0]));
+        assert_eq!(hash(|h| h.write_u64(0x0201)), bytes);
+        assert_eq!(hash(|h| h.write_usize(0x0201)), bytes);
+        assert_eq!(hash(|h| h.write_isize(0x0201)), bytes);
+        assert_eq!(
+            hash(|h| h.write_u32(0x0201)),
+            hash(|h| h.write(&[1, 2, 0, 0]))
+        );
+    }
 }